@@ -40,4 +40,7 @@ pub enum AuthError {
 
     #[error("Random number generation failed: {0}")]
     RngFailed(String),
+
+    #[error("Invalid PKCE code verifier length: expected 43 to 128 characters, got {0}")]
+    InvalidVerifierLength(usize),
 }
@@ -1,6 +1,7 @@
 //! JWE (JSON Web Encryption) with ECDH-ES+A256KW / A256GCM.
 //!
-//! Implements compact JWE format per RFC 7516 with:
+//! Implements the compact and JSON (general and flattened) serializations of
+//! RFC 7516 with:
 //! - Key agreement: ECDH-ES+A256KW (RFC 7518 §4.6)
 //! - Content encryption: A256GCM (RFC 7518 §5.3)
 //!
@@ -15,6 +16,7 @@ use betterbase_crypto::{base64url_decode, base64url_encode};
 use p256::ecdh::EphemeralSecret;
 use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use p256::{EncodedPoint, PublicKey};
+use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
 use zeroize::Zeroize;
 
@@ -24,6 +26,10 @@ const ALG_ID: &str = "ECDH-ES+A256KW";
 const CEK_LENGTH: usize = 32;
 /// AES-KW output for 32-byte key: 32 + 8 = 40 bytes.
 const AES_KW_OUTPUT_LENGTH: usize = 40;
+/// A256GCM IV length.
+const IV_LENGTH: usize = 12;
+/// A256GCM authentication tag length.
+const TAG_LENGTH: usize = 16;
 
 /// Decrypt a compact JWE string using ECDH-ES+A256KW / A256GCM.
 ///
@@ -59,6 +65,247 @@ pub fn decrypt_jwe(
         serde_json::from_slice(&header_bytes).map_err(|e| AuthError::JweFormat(e.to_string()))?;
 
     // 3. Validate algorithms
+    check_algorithms(&header)?;
+
+    // 4. Import recipient private key
+    let recipient_secret = import_p256_private_jwk(recipient_private_jwk)?;
+
+    // 5. ECDH + Concat KDF + AES-KW unwrap of the CEK
+    let encrypted_key =
+        base64url_decode(encrypted_key_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+    let mut cek = unwrap_cek(&header, &encrypted_key, &recipient_secret)?;
+
+    // 6. AES-256-GCM decrypt; AAD is the protected header base64url string
+    let plaintext = decrypt_content(&cek, iv_b64, ciphertext_b64, tag_b64, header_b64.as_bytes());
+    cek.zeroize();
+    plaintext
+}
+
+/// Encrypt plaintext as a compact JWE using ECDH-ES+A256KW / A256GCM.
+///
+/// # Arguments
+/// * `plaintext` - Bytes to encrypt
+/// * `recipient_public_jwk` - Recipient's P-256 public key as JWK JSON
+///
+/// # Returns
+/// Compact JWE string (5 base64url parts separated by dots).
+pub fn encrypt_jwe(
+    plaintext: &[u8],
+    recipient_public_jwk: &serde_json::Value,
+) -> Result<String, AuthError> {
+    let recipient_public_key = import_p256_public_jwk(recipient_public_jwk)?;
+
+    let mut cek = random_cek()?;
+    let (wrapped_cek, epk_jwk) = wrap_cek(&cek, &recipient_public_key)?;
+
+    // Build protected header with ephemeral public key
+    let header = serde_json::json!({
+        "alg": "ECDH-ES+A256KW",
+        "enc": "A256GCM",
+        "epk": epk_jwk
+    });
+    // AAD for AES-GCM is the base64url-encoded header (RFC 7516 §5.1 step 14).
+    // Use canonical_json for deterministic key ordering — header contains the
+    // nested `epk` object, so serde_json insertion order is not sufficient.
+    let header_json = betterbase_crypto::canonical_json(&header)
+        .map_err(|e| AuthError::JweEncryptionFailed(format!("header serialization: {}", e)))?;
+    let header_b64 = base64url_encode(header_json.as_bytes());
+
+    let content = encrypt_content(&cek, plaintext, header_b64.as_bytes());
+    cek.zeroize();
+    let SealedContent {
+        iv,
+        ciphertext,
+        tag,
+    } = content?;
+
+    // Build compact JWE: header.encrypted_key.iv.ciphertext.tag
+    Ok(format!(
+        "{}.{}.{}.{}.{}",
+        header_b64,
+        base64url_encode(&wrapped_cek),
+        base64url_encode(&iv),
+        base64url_encode(&ciphertext),
+        base64url_encode(&tag)
+    ))
+}
+
+/// Decrypt a JWE in the JSON serialization (RFC 7516 §7.2), general or
+/// flattened, using ECDH-ES+A256KW / A256GCM.
+///
+/// Each recipient's parameters are the union of the `protected`,
+/// `unprotected` and per-recipient `header` members. The AAD is the
+/// `protected` member, followed by `.` and the `aad` member when present
+/// (RFC 7516 §5.1 step 14).
+///
+/// # Arguments
+/// * `jwe` - JWE JSON object, with a `recipients` array (general) or a
+///   top-level `header` / `encrypted_key` (flattened)
+/// * `recipient_private_jwk` - Recipient's P-256 private key as JWK JSON
+/// * `recipient_kid` - Only try recipients with this `kid`; with `None`,
+///   each recipient is tried until one unwraps the CEK
+///
+/// # Returns
+/// Decrypted plaintext bytes.
+pub fn decrypt_jwe_json(
+    jwe: &Value,
+    recipient_private_jwk: &Value,
+    recipient_kid: Option<&str>,
+) -> Result<Vec<u8>, AuthError> {
+    let obj = jwe
+        .as_object()
+        .ok_or_else(|| AuthError::JweFormat("expected a JSON object".to_string()))?;
+
+    // 1. Shared headers
+    let protected_b64 = optional_str(obj, "protected")?;
+    let protected = match protected_b64 {
+        Some(b64) => {
+            let bytes = base64url_decode(b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+            let header: Value =
+                serde_json::from_slice(&bytes).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+            header_object(Some(&header), "protected")?
+        }
+        None => Map::new(),
+    };
+    let unprotected = header_object(obj.get("unprotected"), "unprotected")?;
+
+    // 2. Recipients: the `recipients` array, or the object itself when flattened
+    let recipients: Vec<&Map<String, Value>> = match obj.get("recipients") {
+        Some(list) => {
+            if obj.contains_key("header") || obj.contains_key("encrypted_key") {
+                return Err(AuthError::JweFormat(
+                    "recipients cannot be combined with header or encrypted_key".to_string(),
+                ));
+            }
+            let list = list
+                .as_array()
+                .filter(|list| !list.is_empty())
+                .ok_or_else(|| {
+                    AuthError::JweFormat("recipients must be a non-empty array".to_string())
+                })?;
+            list.iter()
+                .map(|r| {
+                    r.as_object().ok_or_else(|| {
+                        AuthError::JweFormat("recipient must be a JSON object".to_string())
+                    })
+                })
+                .collect::<Result<_, _>>()?
+        }
+        None => vec![obj],
+    };
+
+    // 3. AAD: ASCII(protected) or ASCII(protected || '.' || aad)
+    let mut aad = protected_b64.unwrap_or_default().to_string();
+    if let Some(extra) = optional_str(obj, "aad")? {
+        aad.push('.');
+        aad.push_str(extra);
+    }
+
+    let iv_b64 = required_str(obj, "iv")?;
+    let ciphertext_b64 = required_str(obj, "ciphertext")?;
+    let tag_b64 = required_str(obj, "tag")?;
+
+    let recipient_secret = import_p256_private_jwk(recipient_private_jwk)?;
+
+    // 4. Unwrap the CEK for the first matching recipient
+    let mut last_error = None;
+    for recipient in recipients {
+        let own = header_object(recipient.get("header"), "header")?;
+        let header = merge_headers(&[&protected, &unprotected, &own])?;
+        if let Some(kid) = recipient_kid {
+            if header["kid"].as_str() != Some(kid) {
+                continue;
+            }
+        }
+
+        let unwrapped = check_algorithms(&header).and_then(|()| {
+            let encrypted_key = base64url_decode(required_str(recipient, "encrypted_key")?)
+                .map_err(|e| AuthError::JweFormat(e.to_string()))?;
+            unwrap_cek(&header, &encrypted_key, &recipient_secret)
+        });
+        match unwrapped {
+            Ok(mut cek) => {
+                let plaintext =
+                    decrypt_content(&cek, iv_b64, ciphertext_b64, tag_b64, aad.as_bytes());
+                cek.zeroize();
+                return plaintext;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        AuthError::JweDecryptionFailed(format!(
+            "no recipient with kid {}",
+            recipient_kid.unwrap_or_default()
+        ))
+    }))
+}
+
+/// Encrypt plaintext once for several recipients as a general JSON JWE
+/// (RFC 7516 §7.2.1) using ECDH-ES+A256KW / A256GCM.
+///
+/// One random CEK encrypts the content and is wrapped for each recipient
+/// under its own ephemeral key. `enc` goes in the protected header; `alg`,
+/// `kid` and `epk` go in each recipient's unprotected `header`.
+///
+/// # Arguments
+/// * `plaintext` - Bytes to encrypt
+/// * `recipients` - `(kid, public JWK)` pairs; the kid lets each recipient
+///   find its entry
+///
+/// # Returns
+/// General JWE JSON object.
+pub fn encrypt_jwe_json_multi(
+    plaintext: &[u8],
+    recipients: &[(&str, &Value)],
+) -> Result<Value, AuthError> {
+    if recipients.is_empty() {
+        return Err(AuthError::JweEncryptionFailed(
+            "at least one recipient is required".to_string(),
+        ));
+    }
+    let public_keys = recipients
+        .iter()
+        .map(|(_, jwk)| import_p256_public_jwk(jwk))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut cek = random_cek()?;
+    let mut entries = Vec::with_capacity(recipients.len());
+    for ((kid, _), public_key) in recipients.iter().zip(&public_keys) {
+        let (wrapped_cek, epk_jwk) = match wrap_cek(&cek, public_key) {
+            Ok(wrapped) => wrapped,
+            Err(e) => {
+                cek.zeroize();
+                return Err(e);
+            }
+        };
+        entries.push(serde_json::json!({
+            "header": { "alg": ALG_ID, "kid": kid, "epk": epk_jwk },
+            "encrypted_key": base64url_encode(&wrapped_cek),
+        }));
+    }
+
+    let protected_b64 = base64url_encode(br#"{"enc":"A256GCM"}"#);
+    let content = encrypt_content(&cek, plaintext, protected_b64.as_bytes());
+    cek.zeroize();
+    let SealedContent {
+        iv,
+        ciphertext,
+        tag,
+    } = content?;
+
+    Ok(serde_json::json!({
+        "protected": protected_b64,
+        "recipients": entries,
+        "iv": base64url_encode(&iv),
+        "ciphertext": base64url_encode(&ciphertext),
+        "tag": base64url_encode(&tag),
+    }))
+}
+
+/// Fail unless `header` names ECDH-ES+A256KW and A256GCM.
+fn check_algorithms(header: &Value) -> Result<(), AuthError> {
     let alg = header["alg"]
         .as_str()
         .ok_or_else(|| AuthError::JweFormat("missing alg in header".to_string()))?;
@@ -78,29 +325,35 @@ pub fn decrypt_jwe(
             enc
         )));
     }
+    Ok(())
+}
 
-    // 4. Extract sender's ephemeral public key from header
+/// Unwrap the CEK from `encrypted_key` using the KEK agreed between the
+/// recipient's key and the sender's `epk` (plus any `apu`/`apv`) in `header`.
+fn unwrap_cek(
+    header: &Value,
+    encrypted_key: &[u8],
+    recipient_secret: &p256::SecretKey,
+) -> Result<[u8; CEK_LENGTH], AuthError> {
     let epk = header
         .get("epk")
         .ok_or_else(|| AuthError::JweFormat("missing epk in header".to_string()))?;
-
     let sender_public_key = import_p256_public_jwk(epk)?;
 
-    // 5. Import recipient private key
-    let recipient_secret = import_p256_private_jwk(recipient_private_jwk)?;
-
-    // 6. ECDH key agreement
     let shared_secret = p256::ecdh::diffie_hellman(
         recipient_secret.to_nonzero_scalar(),
         sender_public_key.as_affine(),
     );
 
-    // 7. Concat KDF to derive KEK
-    let mut kek_bytes = concat_kdf(shared_secret.raw_secret_bytes().as_slice(), ALG_ID, 256);
-
-    // 8. AES-KW unwrap CEK
-    let encrypted_key =
-        base64url_decode(encrypted_key_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+    let apu = party_info(header, "apu")?;
+    let apv = party_info(header, "apv")?;
+    let mut kek_bytes = concat_kdf(
+        shared_secret.raw_secret_bytes().as_slice(),
+        ALG_ID,
+        &apu,
+        &apv,
+        256,
+    );
     let kek = Kek::from(
         <[u8; 32]>::try_from(kek_bytes.as_slice())
             .map_err(|_| AuthError::JweDecryptionFailed("KEK is not 32 bytes".to_string()))?,
@@ -108,67 +361,33 @@ pub fn decrypt_jwe(
     kek_bytes.zeroize();
 
     let mut cek = [0u8; CEK_LENGTH];
-    kek.unwrap(&encrypted_key, &mut cek)
+    kek.unwrap(encrypted_key, &mut cek)
         .map_err(|e| AuthError::JweDecryptionFailed(format!("AES-KW unwrap failed: {:?}", e)))?;
-
-    // 9. AES-256-GCM decrypt
-    let iv = base64url_decode(iv_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
-    let ciphertext =
-        base64url_decode(ciphertext_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
-    let tag = base64url_decode(tag_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
-
-    // Concatenate ciphertext + tag for aes-gcm (it expects them together)
-    let mut ct_with_tag = ciphertext;
-    ct_with_tag.extend_from_slice(&tag);
-
-    let cipher = Aes256Gcm::new_from_slice(&cek)
-        .map_err(|e| AuthError::JweDecryptionFailed(format!("AES-GCM init: {:?}", e)))?;
-    cek.zeroize();
-
-    let nonce = Nonce::from_slice(&iv);
-
-    // AAD is the protected header base64url string (ASCII bytes)
-    let aad = aes_gcm::aead::Payload {
-        msg: &ct_with_tag,
-        aad: header_b64.as_bytes(),
-    };
-
-    let plaintext = cipher
-        .decrypt(nonce, aad)
-        .map_err(|e| AuthError::JweDecryptionFailed(format!("AES-GCM decrypt: {:?}", e)))?;
-
-    Ok(plaintext)
+    Ok(cek)
 }
 
-/// Encrypt plaintext as a compact JWE using ECDH-ES+A256KW / A256GCM.
-///
-/// # Arguments
-/// * `plaintext` - Bytes to encrypt
-/// * `recipient_public_jwk` - Recipient's P-256 public key as JWK JSON
-///
-/// # Returns
-/// Compact JWE string (5 base64url parts separated by dots).
-pub fn encrypt_jwe(
-    plaintext: &[u8],
-    recipient_public_jwk: &serde_json::Value,
-) -> Result<String, AuthError> {
-    let recipient_public_key = import_p256_public_jwk(recipient_public_jwk)?;
-
+/// Wrap `cek` for `recipient` under a fresh ephemeral key. Returns the
+/// wrapped CEK and the ephemeral public key as a JWK (`epk`).
+fn wrap_cek(
+    cek: &[u8; CEK_LENGTH],
+    recipient: &PublicKey,
+) -> Result<([u8; AES_KW_OUTPUT_LENGTH], Value), AuthError> {
     // Generate ephemeral keypair for ECDH
     let ephemeral_secret = EphemeralSecret::random(&mut p256::elliptic_curve::rand_core::OsRng);
     let ephemeral_public = p256::PublicKey::from(&ephemeral_secret);
     let ephemeral_point = ephemeral_public.to_encoded_point(false);
 
     // ECDH key agreement
-    let shared_secret = ephemeral_secret.diffie_hellman(&recipient_public_key);
+    let shared_secret = ephemeral_secret.diffie_hellman(recipient);
 
     // Concat KDF to derive KEK
-    let mut kek_bytes = concat_kdf(shared_secret.raw_secret_bytes().as_slice(), ALG_ID, 256);
-
-    // Generate random CEK
-    let mut cek = [0u8; CEK_LENGTH];
-    getrandom::getrandom(&mut cek)
-        .map_err(|e| AuthError::JweEncryptionFailed(format!("RNG failed: {}", e)))?;
+    let mut kek_bytes = concat_kdf(
+        shared_secret.raw_secret_bytes().as_slice(),
+        ALG_ID,
+        &[],
+        &[],
+        256,
+    );
 
     // AES-KW wrap CEK
     let kek = Kek::from(
@@ -178,56 +397,159 @@ pub fn encrypt_jwe(
     kek_bytes.zeroize();
 
     let mut wrapped_cek = [0u8; AES_KW_OUTPUT_LENGTH];
-    kek.wrap(&cek, &mut wrapped_cek)
+    kek.wrap(cek, &mut wrapped_cek)
         .map_err(|e| AuthError::JweEncryptionFailed(format!("AES-KW wrap failed: {:?}", e)))?;
 
-    // Build protected header with ephemeral public key
-    let epk_jwk = encode_point_as_jwk(&ephemeral_point);
-    let header = serde_json::json!({
-        "alg": "ECDH-ES+A256KW",
-        "enc": "A256GCM",
-        "epk": epk_jwk
-    });
-    // AAD for AES-GCM is the base64url-encoded header (RFC 7516 §5.1 step 14).
-    // Use canonical_json for deterministic key ordering — header contains the
-    // nested `epk` object, so serde_json insertion order is not sufficient.
-    let header_json = betterbase_crypto::canonical_json(&header)
-        .map_err(|e| AuthError::JweEncryptionFailed(format!("header serialization: {}", e)))?;
-    let header_b64 = base64url_encode(header_json.as_bytes());
+    Ok((wrapped_cek, encode_point_as_jwk(&ephemeral_point)))
+}
+
+/// Generate a random content encryption key.
+fn random_cek() -> Result<[u8; CEK_LENGTH], AuthError> {
+    let mut cek = [0u8; CEK_LENGTH];
+    getrandom::getrandom(&mut cek)
+        .map_err(|e| AuthError::JweEncryptionFailed(format!("RNG failed: {}", e)))?;
+    Ok(cek)
+}
+
+/// AES-256-GCM decrypt the base64url `ciphertext` and `tag` under `cek`,
+/// authenticating `aad`.
+fn decrypt_content(
+    cek: &[u8; CEK_LENGTH],
+    iv_b64: &str,
+    ciphertext_b64: &str,
+    tag_b64: &str,
+    aad: &[u8],
+) -> Result<Vec<u8>, AuthError> {
+    let iv = base64url_decode(iv_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+    let ciphertext =
+        base64url_decode(ciphertext_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+    let tag = base64url_decode(tag_b64).map_err(|e| AuthError::JweFormat(e.to_string()))?;
+
+    // `Nonce::from_slice` panics on any other length.
+    if iv.len() != IV_LENGTH {
+        return Err(AuthError::JweFormat(format!(
+            "expected {}-byte iv, got {}",
+            IV_LENGTH,
+            iv.len()
+        )));
+    }
+    if tag.len() != TAG_LENGTH {
+        return Err(AuthError::JweFormat(format!(
+            "expected {}-byte tag, got {}",
+            TAG_LENGTH,
+            tag.len()
+        )));
+    }
 
-    // AES-256-GCM encrypt
-    let mut iv = [0u8; 12];
+    // Concatenate ciphertext + tag for aes-gcm (it expects them together)
+    let mut ct_with_tag = ciphertext;
+    ct_with_tag.extend_from_slice(&tag);
+
+    let cipher = Aes256Gcm::new_from_slice(cek)
+        .map_err(|e| AuthError::JweDecryptionFailed(format!("AES-GCM init: {:?}", e)))?;
+    let nonce = Nonce::from_slice(&iv);
+    let payload = aes_gcm::aead::Payload {
+        msg: &ct_with_tag,
+        aad,
+    };
+
+    cipher
+        .decrypt(nonce, payload)
+        .map_err(|e| AuthError::JweDecryptionFailed(format!("AES-GCM decrypt: {:?}", e)))
+}
+
+/// Content encrypted by [`encrypt_content`], split as the JWE carries it.
+struct SealedContent {
+    iv: [u8; IV_LENGTH],
+    ciphertext: Vec<u8>,
+    tag: Vec<u8>,
+}
+
+/// AES-256-GCM encrypt `plaintext` under `cek` with a fresh IV,
+/// authenticating `aad`.
+fn encrypt_content(
+    cek: &[u8; CEK_LENGTH],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<SealedContent, AuthError> {
+    let mut iv = [0u8; IV_LENGTH];
     getrandom::getrandom(&mut iv)
         .map_err(|e| AuthError::JweEncryptionFailed(format!("RNG failed: {}", e)))?;
 
-    let cipher = Aes256Gcm::new_from_slice(&cek)
+    let cipher = Aes256Gcm::new_from_slice(cek)
         .map_err(|e| AuthError::JweEncryptionFailed(format!("AES-GCM init: {:?}", e)))?;
-    cek.zeroize();
-
     let nonce = Nonce::from_slice(&iv);
-    let aad = aes_gcm::aead::Payload {
+    let payload = aes_gcm::aead::Payload {
         msg: plaintext,
-        aad: header_b64.as_bytes(),
+        aad,
     };
 
-    let ciphertext_with_tag = cipher
-        .encrypt(nonce, aad)
+    let mut ciphertext = cipher
+        .encrypt(nonce, payload)
         .map_err(|e| AuthError::JweEncryptionFailed(format!("AES-GCM encrypt: {:?}", e)))?;
 
     // Split ciphertext and tag (last 16 bytes is the tag)
-    let tag_offset = ciphertext_with_tag.len() - 16;
-    let ciphertext_part = &ciphertext_with_tag[..tag_offset];
-    let tag_part = &ciphertext_with_tag[tag_offset..];
+    let tag = ciphertext.split_off(ciphertext.len() - TAG_LENGTH);
+    Ok(SealedContent {
+        iv,
+        ciphertext,
+        tag,
+    })
+}
 
-    // Build compact JWE: header.encrypted_key.iv.ciphertext.tag
-    Ok(format!(
-        "{}.{}.{}.{}.{}",
-        header_b64,
-        base64url_encode(&wrapped_cek),
-        base64url_encode(&iv),
-        base64url_encode(ciphertext_part),
-        base64url_encode(tag_part)
-    ))
+/// Union of JSON-serialization headers, whose parameter names must be
+/// disjoint (RFC 7516 §7.2.1).
+fn merge_headers(parts: &[&Map<String, Value>]) -> Result<Value, AuthError> {
+    let mut merged = Map::new();
+    for part in parts {
+        for (name, value) in part.iter() {
+            if merged.insert(name.clone(), value.clone()).is_some() {
+                return Err(AuthError::JweFormat(format!(
+                    "duplicate header parameter {}",
+                    name
+                )));
+            }
+        }
+    }
+    Ok(Value::Object(merged))
+}
+
+/// A header member as a JSON object; absent members are empty.
+fn header_object(value: Option<&Value>, member: &str) -> Result<Map<String, Value>, AuthError> {
+    match value {
+        None => Ok(Map::new()),
+        Some(Value::Object(map)) => Ok(map.clone()),
+        Some(_) => Err(AuthError::JweFormat(format!(
+            "{} must be a JSON object",
+            member
+        ))),
+    }
+}
+
+fn optional_str<'a>(
+    obj: &'a Map<String, Value>,
+    member: &str,
+) -> Result<Option<&'a str>, AuthError> {
+    match obj.get(member) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(_) => Err(AuthError::JweFormat(format!("{} must be a string", member))),
+    }
+}
+
+fn required_str<'a>(obj: &'a Map<String, Value>, member: &str) -> Result<&'a str, AuthError> {
+    optional_str(obj, member)?.ok_or_else(|| AuthError::JweFormat(format!("missing {}", member)))
+}
+
+/// Decoded `apu` / `apv` party info from `header`; empty when absent.
+fn party_info(header: &Value, name: &str) -> Result<Vec<u8>, AuthError> {
+    match header.get(name) {
+        None => Ok(Vec::new()),
+        Some(Value::String(s)) => {
+            base64url_decode(s).map_err(|e| AuthError::JweFormat(e.to_string()))
+        }
+        Some(_) => Err(AuthError::JweFormat(format!("{} must be a string", name))),
+    }
 }
 
 /// Concat KDF (NIST SP 800-56A, single-pass for <=256 bits).
@@ -237,10 +559,10 @@ pub fn encrypt_jwe(
 ///
 /// Where:
 ///   algID = [len(alg):4 BE][alg bytes]
-///   partyUInfo = [0:4 BE] (empty)
-///   partyVInfo = [0:4 BE] (empty)
+///   partyUInfo = [len(apu):4 BE][apu bytes] (empty unless the header has `apu`)
+///   partyVInfo = [len(apv):4 BE][apv bytes] (empty unless the header has `apv`)
 ///   suppPubInfo = [keydatalen:4 BE]
-fn concat_kdf(z: &[u8], alg: &str, key_data_len_bits: u32) -> Vec<u8> {
+fn concat_kdf(z: &[u8], alg: &str, apu: &[u8], apv: &[u8], key_data_len_bits: u32) -> Vec<u8> {
     let mut hasher = Sha256::new();
 
     // Round counter (always 1 for <= 256 bits)
@@ -253,11 +575,11 @@ fn concat_kdf(z: &[u8], alg: &str, key_data_len_bits: u32) -> Vec<u8> {
     hasher.update((alg.len() as u32).to_be_bytes());
     hasher.update(alg.as_bytes());
 
-    // PartyUInfo: empty (length 0)
-    hasher.update(0u32.to_be_bytes());
-
-    // PartyVInfo: empty (length 0)
-    hasher.update(0u32.to_be_bytes());
+    // PartyUInfo / PartyVInfo: length-prefixed, usually empty
+    hasher.update((apu.len() as u32).to_be_bytes());
+    hasher.update(apu);
+    hasher.update((apv.len() as u32).to_be_bytes());
+    hasher.update(apv);
 
     // SuppPubInfo: key data length in bits
     hasher.update(key_data_len_bits.to_be_bytes());
@@ -458,21 +780,91 @@ mod tests {
         assert!(decrypt_jwe(&tampered_jwe, &private_jwk).is_err());
     }
 
+    #[test]
+    fn rejects_wrong_iv_and_tag_lengths() {
+        let (public_jwk, private_jwk) = generate_test_keypair();
+        let jwe = encrypt_jwe(b"secret", &public_jwk).unwrap();
+        let parts: Vec<&str> = jwe.split('.').collect();
+
+        for iv in [&[][..], &[0u8; 1], &[0u8; 16]] {
+            let tampered = format!(
+                "{}.{}.{}.{}.{}",
+                parts[0],
+                parts[1],
+                base64url_encode(iv),
+                parts[3],
+                parts[4]
+            );
+            assert!(matches!(
+                decrypt_jwe(&tampered, &private_jwk),
+                Err(AuthError::JweFormat(_))
+            ));
+        }
+
+        // Moving tag bytes into the ciphertext must not be accepted either.
+        let mut ct = base64url_decode(parts[3]).unwrap();
+        let tag = base64url_decode(parts[4]).unwrap();
+        ct.extend_from_slice(&tag[..8]);
+        let shifted = format!(
+            "{}.{}.{}.{}.{}",
+            parts[0],
+            parts[1],
+            parts[2],
+            base64url_encode(&ct),
+            base64url_encode(&tag[8..])
+        );
+        assert!(matches!(
+            decrypt_jwe(&shifted, &private_jwk),
+            Err(AuthError::JweFormat(_))
+        ));
+    }
+
     #[test]
     fn concat_kdf_produces_32_bytes() {
         let z = [0u8; 32];
-        let result = concat_kdf(&z, "A256KW", 256);
+        let result = concat_kdf(&z, "A256KW", &[], &[], 256);
         assert_eq!(result.len(), 32);
     }
 
     #[test]
     fn concat_kdf_is_deterministic() {
         let z = [42u8; 32];
-        let r1 = concat_kdf(&z, "A256KW", 256);
-        let r2 = concat_kdf(&z, "A256KW", 256);
+        let r1 = concat_kdf(&z, "A256KW", &[], &[], 256);
+        let r2 = concat_kdf(&z, "A256KW", &[], &[], 256);
         assert_eq!(r1, r2);
     }
 
+    #[test]
+    fn concat_kdf_matches_rfc7518_appendix_c() {
+        // ECDH-ES key agreement example from RFC 7518 Appendix C: Alice's
+        // ephemeral key, Bob's static key, apu "Alice", apv "Bob", A128GCM
+        let alice = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+            "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps",
+            "d": "0_NxaRPUMQoAJt50Gz8YiTr8gRTwyEaCumd-MToTmIo"
+        });
+        let bob = serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+            "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+            "d": "VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"
+        });
+
+        let alice_secret = import_p256_private_jwk(&alice).unwrap();
+        let bob_public = import_p256_public_jwk(&bob).unwrap();
+        let z =
+            p256::ecdh::diffie_hellman(alice_secret.to_nonzero_scalar(), bob_public.as_affine());
+
+        let derived = concat_kdf(z.raw_secret_bytes(), "A128GCM", b"Alice", b"Bob", 128);
+        assert_eq!(
+            derived[..16],
+            base64url_decode("VqqN6vgjbSBcIijNcacQGg").unwrap()[..]
+        );
+    }
+
     #[test]
     fn empty_plaintext_round_trips() {
         let (public_jwk, private_jwk) = generate_test_keypair();
@@ -570,4 +962,144 @@ mod tests {
         // Different ephemeral keys and IVs mean different output
         assert_ne!(jwe1, jwe2);
     }
+
+    // FLATTENED_FIXTURE and GENERAL_FIXTURE are meant to be jose output from
+    // testdata/jose-jwe.mjs (FlattenedEncrypt with `apu`/`apv` and an `aad`
+    // member; GeneralEncrypt with `alg`/`kid`/`epk` per recipient). The ones
+    // below still come from a hand-written WebCrypto script with the same
+    // layout, so jose interop stays unverified until they are regenerated.
+    const FIXTURE_ALICE_JWK: &str = r#"{"kty":"EC","x":"bee-lBR3C8mIgslefMioS3sKDySFOKmRrAPosJjFz_k","y":"vl8Wf0V0uTacALw1PfDgMkgvWdlj2JMJ7q_sQVtPRk0","crv":"P-256","d":"3l4VlwME72sAt7xX1wwvpWHi0V8a7Isinw-spdp6O2s"}"#;
+    const FIXTURE_BOB_JWK: &str = r#"{"kty":"EC","x":"XNrBFnYuYKC3lms3AgPQpvZTnU_OTzGb8FaKblmoxsg","y":"He4RZfumfFotSH8A4yfAyIwVsMSNrfOWkdBBb1WOis8","crv":"P-256","d":"bvk_Atew4r_hwQDSL5vdlybFPO8rpw_R2bqZW6CD4Uk"}"#;
+    const FLATTENED_FIXTURE: &str = r#"{"protected":"eyJhbGciOiJFQ0RILUVTK0EyNTZLVyIsImVuYyI6IkEyNTZHQ00iLCJlcGsiOnsiY3J2IjoiUC0yNTYiLCJrdHkiOiJFQyIsIngiOiJxR3pnd1lGeXRJdVkzdGdBOWVuMW44SDdFSC1GNGkxTU5QMlQ2ZU9fMldJIiwieSI6IjRSUFBZZUh4Wk1lS05oeWo0bHloeFVSRWdfeTFUdXkwUDZ6S2tsbDlLSjAifSwiYXB1IjoiUVd4cFkyVSIsImFwdiI6IlFtOWkifQ","unprotected":{"kid":"alice"},"encrypted_key":"Km-C91yqbvdVKjkPwwZV-ZhoRkFDcPhgXPJofQYraq_dKGZLPYg5NA","aad":"bWFpbGJveDp2MQ","iv":"yzVMDcqAq6Fg9sqv","ciphertext":"GA91VhuU0jI-G442NCds","tag":"tDVrshmMIFdOQf07YO4h2w"}"#;
+    const GENERAL_FIXTURE: &str = r#"{"protected":"eyJlbmMiOiJBMjU2R0NNIn0","unprotected":{"cty":"text/plain"},"recipients":[{"header":{"alg":"ECDH-ES+A256KW","kid":"alice","epk":{"crv":"P-256","kty":"EC","x":"cCA8PO-m1az_oELaYUiD-4IM19PJKc5-gRVVVQ4zEGM","y":"x66uEd01bfeRymmLkyROkfBic_JL_Ko_8y6eB6rgODc"}},"encrypted_key":"Xm6v5qTfUF8D0_DY5iEizZBXqTA1bzJKDiEXUWmWGYtP41eIHnKBdA"},{"header":{"alg":"ECDH-ES+A256KW","kid":"bob","epk":{"crv":"P-256","kty":"EC","x":"FZTWwjMSVQdCXjuCyQqYAdUFj53oNLlKLXJAOQ04qhw","y":"sOLLq8M3m7UOILE7cND5Zjvvb_sC4pTxG8Rayq-nmpI"}},"encrypted_key":"tNF9JNAi0mPUc8ObpI85XB3qfcf18BSHiGfwlUuvvg1PRQ4QMslCLA"}],"iv":"iGu8oqUZ_FhCN4lu","ciphertext":"4j8UX0wxQ3N4DqHrpg","tag":"zR4RAeNRcFNtsJeygMGH5A"}"#;
+
+    fn fixture(json: &str) -> serde_json::Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn decrypts_flattened_fixture_with_aad_and_party_info() {
+        let jwe = fixture(FLATTENED_FIXTURE);
+        let alice = fixture(FIXTURE_ALICE_JWK);
+
+        let by_kid = decrypt_jwe_json(&jwe, &alice, Some("alice")).unwrap();
+        assert_eq!(by_kid, b"flattened hello");
+        let by_trial = decrypt_jwe_json(&jwe, &alice, None).unwrap();
+        assert_eq!(by_trial, b"flattened hello");
+
+        // The `aad` member is authenticated
+        let mut tampered = jwe.clone();
+        tampered["aad"] = serde_json::json!(base64url_encode(b"mailbox:v2"));
+        assert!(matches!(
+            decrypt_jwe_json(&tampered, &alice, None),
+            Err(AuthError::JweDecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn decrypts_general_fixture_for_each_recipient() {
+        let jwe = fixture(GENERAL_FIXTURE);
+        let alice = fixture(FIXTURE_ALICE_JWK);
+        let bob = fixture(FIXTURE_BOB_JWK);
+
+        assert_eq!(
+            decrypt_jwe_json(&jwe, &alice, Some("alice")).unwrap(),
+            b"general hello"
+        );
+        assert_eq!(
+            decrypt_jwe_json(&jwe, &bob, Some("bob")).unwrap(),
+            b"general hello"
+        );
+        // Bob's entry is second; trial unwrap gets past Alice's
+        assert_eq!(
+            decrypt_jwe_json(&jwe, &bob, None).unwrap(),
+            b"general hello"
+        );
+        // Selecting someone else's entry fails rather than falling back
+        assert!(decrypt_jwe_json(&jwe, &bob, Some("alice")).is_err());
+    }
+
+    #[test]
+    fn json_multi_round_trips_for_each_recipient() {
+        let (alice_public, alice_private) = generate_test_keypair();
+        let (bob_public, bob_private) = generate_test_keypair();
+        let (_, carol_private) = generate_test_keypair();
+
+        let jwe = encrypt_jwe_json_multi(
+            b"to both",
+            &[("alice", &alice_public), ("bob", &bob_public)],
+        )
+        .unwrap();
+        assert_eq!(jwe["recipients"].as_array().unwrap().len(), 2);
+
+        for (kid, private) in [("alice", &alice_private), ("bob", &bob_private)] {
+            assert_eq!(
+                decrypt_jwe_json(&jwe, private, Some(kid)).unwrap(),
+                b"to both"
+            );
+            assert_eq!(decrypt_jwe_json(&jwe, private, None).unwrap(), b"to both");
+        }
+
+        assert!(matches!(
+            decrypt_jwe_json(&jwe, &carol_private, None),
+            Err(AuthError::JweDecryptionFailed(_))
+        ));
+        assert!(matches!(
+            decrypt_jwe_json(&jwe, &carol_private, Some("carol")),
+            Err(AuthError::JweDecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn json_multi_requires_a_recipient() {
+        assert!(matches!(
+            encrypt_jwe_json_multi(b"nobody", &[]),
+            Err(AuthError::JweEncryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn compact_jwe_decrypts_as_flattened_json() {
+        let (public_jwk, private_jwk) = generate_test_keypair();
+        let compact = encrypt_jwe(b"same bytes", &public_jwk).unwrap();
+        let parts: Vec<&str> = compact.split('.').collect();
+
+        let flattened = serde_json::json!({
+            "protected": parts[0],
+            "encrypted_key": parts[1],
+            "iv": parts[2],
+            "ciphertext": parts[3],
+            "tag": parts[4],
+        });
+        assert_eq!(
+            decrypt_jwe_json(&flattened, &private_jwk, None).unwrap(),
+            b"same bytes"
+        );
+    }
+
+    #[test]
+    fn json_rejects_overlapping_headers_and_mixed_forms() {
+        let alice = fixture(FIXTURE_ALICE_JWK);
+
+        let mut overlapping = fixture(GENERAL_FIXTURE);
+        overlapping["unprotected"]["enc"] = serde_json::json!("A256GCM");
+        assert!(matches!(
+            decrypt_jwe_json(&overlapping, &alice, None),
+            Err(AuthError::JweFormat(_))
+        ));
+
+        let mut mixed = fixture(GENERAL_FIXTURE);
+        mixed["encrypted_key"] = mixed["recipients"][0]["encrypted_key"].clone();
+        assert!(matches!(
+            decrypt_jwe_json(&mixed, &alice, None),
+            Err(AuthError::JweFormat(_))
+        ));
+
+        let mut empty = fixture(GENERAL_FIXTURE);
+        empty["recipients"] = serde_json::json!([]);
+        assert!(matches!(
+            decrypt_jwe_json(&empty, &alice, None),
+            Err(AuthError::JweFormat(_))
+        ));
+    }
 }
@@ -3,7 +3,7 @@
 //! This crate provides pure-Rust implementations of:
 //! - PKCE (RFC 7636) with extended key binding
 //! - JWK thumbprint (RFC 7638)
-//! - JWE ECDH-ES+A256KW decryption
+//! - JWE ECDH-ES+A256KW encryption and decryption (compact and JSON serializations)
 //! - Scoped key extraction
 //! - Mailbox ID derivation
 //! - Ephemeral P-256 keypair generation
@@ -20,9 +20,11 @@ mod thumbprint;
 mod types;
 
 pub use error::AuthError;
-pub use jwe::{decrypt_jwe, encrypt_jwe};
+pub use jwe::{decrypt_jwe, decrypt_jwe_json, encrypt_jwe, encrypt_jwe_json_multi};
 pub use key_extraction::{extract_app_keypair, extract_encryption_key, EncryptionKeyResult};
 pub use mailbox::derive_mailbox_id;
-pub use pkce::{compute_code_challenge, generate_code_verifier, generate_state};
+pub use pkce::{
+    compute_code_challenge, generate_code_verifier, generate_code_verifier_with_len, generate_state,
+};
 pub use thumbprint::compute_jwk_thumbprint;
 pub use types::{AppKeypairJwk, EcPublicJwk, ScopedKeyEntry, ScopedKeys};
@@ -4,6 +4,10 @@ use crate::error::AuthError;
 use betterbase_crypto::base64url_encode;
 use sha2::{Digest, Sha256};
 
+/// Code verifier length bounds from RFC 7636 section 4.1.
+const MIN_VERIFIER_LEN: usize = 43;
+const MAX_VERIFIER_LEN: usize = 128;
+
 /// Generate a cryptographically random code verifier (43 characters).
 ///
 /// Produces 32 random bytes encoded as base64url (43 chars).
@@ -13,6 +17,26 @@ pub fn generate_code_verifier() -> Result<String, AuthError> {
     Ok(base64url_encode(&bytes))
 }
 
+/// Generate a cryptographically random code verifier of `len` characters.
+///
+/// Every character is a random base64url digit (6 bits of entropy), so a
+/// 128-character verifier carries 768 bits.
+///
+/// # Errors
+/// Returns `AuthError::InvalidVerifierLength` unless `len` is in `43..=128`.
+pub fn generate_code_verifier_with_len(len: usize) -> Result<String, AuthError> {
+    if !(MIN_VERIFIER_LEN..=MAX_VERIFIER_LEN).contains(&len) {
+        return Err(AuthError::InvalidVerifierLength(len));
+    }
+    // One byte more than `len` digits need, so the digits kept are never the
+    // short trailing one that base64url pads with zero bits.
+    let mut bytes = vec![0u8; len * 3 / 4 + 1];
+    getrandom::getrandom(&mut bytes).map_err(|e| AuthError::RngFailed(e.to_string()))?;
+    let mut verifier = base64url_encode(&bytes);
+    verifier.truncate(len);
+    Ok(verifier)
+}
+
 /// Generate a code challenge from a verifier using SHA-256.
 ///
 /// Standard PKCE: `challenge = base64url(SHA-256(verifier))`
@@ -64,6 +88,47 @@ mod tests {
             .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
     }
 
+    #[test]
+    fn code_verifier_with_len_accepts_rfc_bounds() {
+        for len in [43, 128] {
+            let verifier = generate_code_verifier_with_len(len).unwrap();
+            assert_eq!(verifier.len(), len);
+            assert!(verifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        }
+    }
+
+    #[test]
+    fn code_verifier_with_len_rejects_out_of_range() {
+        for len in [42, 129] {
+            let err = generate_code_verifier_with_len(len).unwrap_err();
+            assert!(
+                matches!(err, AuthError::InvalidVerifierLength(n) if n == len),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn code_challenge_matches_rfc_7636_vector() {
+        // RFC 7636 Appendix B
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert_eq!(
+            compute_code_challenge(verifier, None),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn code_challenge_is_sha256_for_any_verifier_length() {
+        for len in [43, 128] {
+            let verifier = generate_code_verifier_with_len(len).unwrap();
+            let expected = base64url_encode(&Sha256::digest(verifier.as_bytes()));
+            assert_eq!(compute_code_challenge(&verifier, None), expected);
+        }
+    }
+
     #[test]
     fn code_challenge_is_43_chars() {
         let verifier = generate_code_verifier().unwrap();
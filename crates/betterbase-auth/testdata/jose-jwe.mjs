@@ -0,0 +1,48 @@
+// Generates the JWE JSON fixtures for the interop tests in src/jwe.rs with
+// the `jose` library, so decrypt_jwe_json is checked against real jose
+// output rather than our own encoder.
+//
+//   cd "$(mktemp -d)" && npm install jose && node /path/to/jose-jwe.mjs
+//
+// Paste the printed constants over FLATTENED_FIXTURE and GENERAL_FIXTURE.
+// The recipient keys are the fixed FIXTURE_ALICE_JWK / FIXTURE_BOB_JWK
+// from the tests; everything else (CEK, IV, ephemeral keys) is fresh on
+// each run, so the output differs every time but always decrypts.
+
+import { FlattenedEncrypt, GeneralEncrypt, importJWK } from "jose";
+
+const ALICE = {
+  kty: "EC",
+  crv: "P-256",
+  x: "bee-lBR3C8mIgslefMioS3sKDySFOKmRrAPosJjFz_k",
+  y: "vl8Wf0V0uTacALw1PfDgMkgvWdlj2JMJ7q_sQVtPRk0",
+};
+const BOB = {
+  kty: "EC",
+  crv: "P-256",
+  x: "XNrBFnYuYKC3lms3AgPQpvZTnU_OTzGb8FaKblmoxsg",
+  y: "He4RZfumfFotSH8A4yfAyIwVsMSNrfOWkdBBb1WOis8",
+};
+
+const ALG = "ECDH-ES+A256KW";
+const text = new TextEncoder();
+const alice = await importJWK(ALICE, ALG);
+const bob = await importJWK(BOB, ALG);
+
+// Everything protected, with party info and an `aad` member
+const flattened = await new FlattenedEncrypt(text.encode("flattened hello"))
+  .setProtectedHeader({ alg: ALG, enc: "A256GCM" })
+  .setUnprotectedHeader({ kid: "alice" })
+  .setKeyManagementParameters({ apu: text.encode("Alice"), apv: text.encode("Bob") })
+  .setAdditionalAuthenticatedData(text.encode("mailbox:v1"))
+  .encrypt(alice);
+
+// `enc` protected; `alg`, `kid` and the ephemeral key per recipient
+const general = new GeneralEncrypt(text.encode("general hello"))
+  .setProtectedHeader({ enc: "A256GCM" })
+  .setSharedUnprotectedHeader({ cty: "text/plain" });
+general.addRecipient(alice).setUnprotectedHeader({ alg: ALG, kid: "alice" });
+general.addRecipient(bob).setUnprotectedHeader({ alg: ALG, kid: "bob" });
+
+console.log(`const FLATTENED_FIXTURE: &str = r#"${JSON.stringify(flattened)}"#;`);
+console.log(`const GENERAL_FIXTURE: &str = r#"${JSON.stringify(await general.encrypt())}"#;`);
@@ -3,14 +3,25 @@
 //! Wire format v4 (per-record DEK):
 //! [1 byte: version=4][12 bytes: IV][N bytes: ciphertext + tag]
 //! DEK is wrapped separately. No epoch field in blob.
+//!
+//! Wire format v5 is identical except the body is AES-256-GCM-SIV, which
+//! stays safe if an IV is ever repeated under the same DEK. Select it with
+//! [`CipherSuite`]; `decrypt_v4` reads either from the version byte.
+//!
+//! Chunked segments (`SyncCrypto::encrypt_chunked`):
+//! [4 bytes: counter (u32 BE)][1 byte: flags][12 bytes: IV][N bytes: ciphertext + tag]
+//! The counter and final-chunk flag are also bound into each segment's AAD.
+
+use std::io::Read;
 
-use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::aead::{self, Aead, KeyInit, Payload};
 use aes_gcm::{Aes256Gcm, Nonce};
+use aes_gcm_siv::Aes256GcmSiv;
 
 use crate::error::CryptoError;
 use crate::types::{
     EncryptionContext, AES_GCM_IV_LENGTH, AES_GCM_TAG_LENGTH, AES_KEY_LENGTH, CURRENT_VERSION,
-    SUPPORTED_VERSIONS,
+    GCM_SIV_VERSION, SUPPORTED_VERSIONS,
 };
 
 /// Build AAD (Additional Authenticated Data) from encryption context.
@@ -25,6 +36,118 @@ fn build_aad(context: &EncryptionContext) -> Vec<u8> {
     aad
 }
 
+/// AEAD used for the body of a v4-layout blob. The choice is recorded in
+/// the version byte, so decryption needs no hint.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CipherSuite {
+    /// AES-256-GCM, written as version 4.
+    #[default]
+    Aes256Gcm,
+    /// AES-256-GCM-SIV, written as version 5. Nonce-misuse resistant: a
+    /// repeated IV under one DEK only reveals whether two plaintexts match.
+    Aes256GcmSiv,
+}
+
+impl CipherSuite {
+    /// The version byte this suite writes.
+    pub const fn version(self) -> u8 {
+        match self {
+            CipherSuite::Aes256Gcm => CURRENT_VERSION,
+            CipherSuite::Aes256GcmSiv => GCM_SIV_VERSION,
+        }
+    }
+
+    /// The suite a version byte selects.
+    pub fn from_version(version: u8) -> Result<Self, CryptoError> {
+        match version {
+            CURRENT_VERSION => Ok(CipherSuite::Aes256Gcm),
+            GCM_SIV_VERSION => Ok(CipherSuite::Aes256GcmSiv),
+            _ => Err(CryptoError::UnsupportedVersion(version)),
+        }
+    }
+}
+
+/// Split a v4-layout blob into its cipher suite, IV and ciphertext+tag.
+///
+/// Callers choosing between wire versions dispatch on the version byte
+/// before reaching here (see `betterbase_sync_core::wire`); this only
+/// guards against handing an unknown blob to the v4 codec.
+fn split_v4_blob(blob: &[u8]) -> Result<(CipherSuite, &[u8], &[u8]), CryptoError> {
+    let min_length = 1 + AES_GCM_IV_LENGTH + AES_GCM_TAG_LENGTH;
+    if blob.len() < min_length {
+        return Err(CryptoError::DataTooShort);
+    }
+    let version = blob[0];
+    if !SUPPORTED_VERSIONS.contains(&version) {
+        return Err(CryptoError::UnsupportedVersion(version));
+    }
+    let suite = CipherSuite::from_version(version)?;
+    let (iv, ciphertext) = blob[1..].split_at(AES_GCM_IV_LENGTH);
+    Ok((suite, iv, ciphertext))
+}
+
+/// Encrypt under `C`, binding `context` as AAD when present.
+fn seal<C: Aead + KeyInit>(
+    key: &[u8],
+    iv: &[u8],
+    data: &[u8],
+    context: Option<&EncryptionContext>,
+) -> Result<Vec<u8>, CryptoError> {
+    let cipher =
+        C::new_from_slice(key).map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+    let nonce = aead::Nonce::<C>::from_slice(iv);
+    match context {
+        Some(ctx) => {
+            let aad = build_aad(ctx);
+            cipher.encrypt(
+                nonce,
+                Payload {
+                    msg: data,
+                    aad: &aad,
+                },
+            )
+        }
+        None => cipher.encrypt(nonce, data),
+    }
+    .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))
+}
+
+/// Decrypt under `C`, checking `context` as AAD when present.
+fn open<C: Aead + KeyInit>(
+    key: &[u8],
+    iv: &[u8],
+    ciphertext: &[u8],
+    context: Option<&EncryptionContext>,
+) -> Result<Vec<u8>, CryptoError> {
+    let cipher =
+        C::new_from_slice(key).map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+    open_with(&cipher, iv, ciphertext, context)
+}
+
+/// [`open`] with an already keyed cipher.
+fn open_with<C: Aead>(
+    cipher: &C,
+    iv: &[u8],
+    ciphertext: &[u8],
+    context: Option<&EncryptionContext>,
+) -> Result<Vec<u8>, CryptoError> {
+    let nonce = aead::Nonce::<C>::from_slice(iv);
+    match context {
+        Some(ctx) => {
+            let aad = build_aad(ctx);
+            cipher.decrypt(
+                nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+        }
+        None => cipher.decrypt(nonce, ciphertext),
+    }
+    .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))
+}
+
 /// Generate a random 12-byte IV for AES-GCM.
 pub fn generate_iv() -> Result<[u8; AES_GCM_IV_LENGTH], CryptoError> {
     let mut iv = [0u8; AES_GCM_IV_LENGTH];
@@ -34,10 +157,12 @@ pub fn generate_iv() -> Result<[u8; AES_GCM_IV_LENGTH], CryptoError> {
 
 /// AES-256-GCM encryption using scoped keys.
 ///
-/// Writes v4 wire format: [version=4][IV:12][ciphertext+tag]
+/// Writes v4 wire format: [version=4][IV:12][ciphertext+tag], and reads
+/// both v4 and v5 (GCM-SIV) blobs.
 /// No epoch in blob — DEKs are wrapped separately.
 pub struct SyncCrypto {
     cipher: Aes256Gcm,
+    siv: Aes256GcmSiv,
     pub epoch: u32,
 }
 
@@ -56,7 +181,9 @@ impl SyncCrypto {
         }
         let cipher = Aes256Gcm::new_from_slice(key)
             .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
-        Ok(Self { cipher, epoch })
+        let siv = Aes256GcmSiv::new_from_slice(key)
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+        Ok(Self { cipher, siv, epoch })
     }
 
     /// Encrypt data using AES-256-GCM with v4 wire format.
@@ -90,52 +217,242 @@ impl SyncCrypto {
         Ok(result)
     }
 
-    /// Decrypt data using AES-256-GCM v4 wire format.
+    /// Decrypt a v4 (AES-256-GCM) or v5 (AES-256-GCM-SIV) blob, using the
+    /// suite named by its version byte.
     pub fn decrypt(
         &self,
         encrypted: &[u8],
         context: Option<&EncryptionContext>,
     ) -> Result<Vec<u8>, CryptoError> {
-        let min_length = 1 + AES_GCM_IV_LENGTH + AES_GCM_TAG_LENGTH;
-        if encrypted.len() < min_length {
-            return Err(CryptoError::DataTooShort);
+        let (suite, iv, ciphertext) = split_v4_blob(encrypted)?;
+        match suite {
+            CipherSuite::Aes256Gcm => open_with(&self.cipher, iv, ciphertext, context),
+            CipherSuite::Aes256GcmSiv => open_with(&self.siv, iv, ciphertext, context),
         }
+    }
+}
 
-        let version = encrypted[0];
-        if !SUPPORTED_VERSIONS.contains(&version) {
-            return Err(CryptoError::UnsupportedVersion(version));
+// ---------------------------------------------------------------------------
+// Chunked encryption
+// ---------------------------------------------------------------------------
+
+/// Segment header: counter (4) + flags (1).
+const SEGMENT_HEADER_LENGTH: usize = 5;
+
+/// Flag bit marking the last segment of a stream.
+const FINAL_SEGMENT: u8 = 0x01;
+
+/// AAD for one segment: the context AAD (if any), then counter and flags,
+/// so segments cannot be reordered or an early one passed off as final.
+fn segment_aad(context: Option<&EncryptionContext>, counter: u32, flags: u8) -> Vec<u8> {
+    let mut aad = context.map(build_aad).unwrap_or_default();
+    aad.extend_from_slice(&counter.to_be_bytes());
+    aad.push(flags);
+    aad
+}
+
+impl SyncCrypto {
+    /// Encrypt `reader` as a stream of framed segments of at most
+    /// `chunk_size` plaintext bytes each.
+    ///
+    /// Only two chunks are held in memory at a time. Empty input yields a
+    /// single empty final segment. Decrypt with [`SyncCrypto::decrypt_chunked`].
+    pub fn encrypt_chunked<'a, R: Read>(
+        &'a self,
+        reader: R,
+        chunk_size: usize,
+        context: Option<&'a EncryptionContext>,
+    ) -> Result<ChunkedEncryptor<'a, R>, CryptoError> {
+        if chunk_size == 0 {
+            return Err(CryptoError::EncryptionFailed(
+                "chunk size must be positive".to_string(),
+            ));
         }
+        Ok(ChunkedEncryptor {
+            crypto: self,
+            reader,
+            chunk_size,
+            context,
+            counter: 0,
+            lookahead: None,
+            done: false,
+        })
+    }
 
-        let iv = &encrypted[1..1 + AES_GCM_IV_LENGTH];
-        let ciphertext = &encrypted[1 + AES_GCM_IV_LENGTH..];
-        let nonce = Nonce::from_slice(iv);
+    /// Start decrypting a segment stream produced by
+    /// [`SyncCrypto::encrypt_chunked`].
+    pub fn decrypt_chunked<'a>(
+        &'a self,
+        context: Option<&'a EncryptionContext>,
+    ) -> ChunkedDecryptor<'a> {
+        ChunkedDecryptor {
+            crypto: self,
+            context,
+            next_counter: 0,
+            finished: false,
+        }
+    }
+}
 
-        let plaintext = match context {
-            Some(ctx) => {
-                let aad = build_aad(ctx);
-                self.cipher.decrypt(
-                    nonce,
-                    Payload {
-                        msg: ciphertext,
-                        aad: &aad,
-                    },
-                )
+/// Iterator of encrypted segments returned by [`SyncCrypto::encrypt_chunked`].
+///
+/// Stops after the first error.
+pub struct ChunkedEncryptor<'a, R> {
+    crypto: &'a SyncCrypto,
+    reader: R,
+    chunk_size: usize,
+    context: Option<&'a EncryptionContext>,
+    counter: u32,
+    /// Chunk read ahead to learn whether the current one is the last.
+    lookahead: Option<Vec<u8>>,
+    done: bool,
+}
+
+impl<R: Read> ChunkedEncryptor<'_, R> {
+    fn read_chunk(&mut self) -> Result<Vec<u8>, CryptoError> {
+        let mut chunk = vec![0u8; self.chunk_size];
+        let mut filled = 0;
+        while filled < chunk.len() {
+            match self.reader.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(CryptoError::EncryptionFailed(format!("read: {}", e))),
             }
-            None => self.cipher.decrypt(nonce, ciphertext),
         }
-        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+        chunk.truncate(filled);
+        Ok(chunk)
+    }
+
+    fn next_segment(&mut self) -> Result<Vec<u8>, CryptoError> {
+        let chunk = match self.lookahead.take() {
+            Some(chunk) => chunk,
+            None => self.read_chunk()?,
+        };
+        let next = self.read_chunk()?;
+        let flags = if next.is_empty() {
+            self.done = true;
+            FINAL_SEGMENT
+        } else {
+            self.lookahead = Some(next);
+            0
+        };
+        if flags != FINAL_SEGMENT && self.counter == u32::MAX {
+            return Err(CryptoError::EncryptionFailed("too many chunks".to_string()));
+        }
+
+        let iv = generate_iv()?;
+        let aad = segment_aad(self.context, self.counter, flags);
+        let ciphertext = self
+            .crypto
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&iv),
+                Payload {
+                    msg: &chunk,
+                    aad: &aad,
+                },
+            )
+            .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+        let mut segment = Vec::with_capacity(SEGMENT_HEADER_LENGTH + iv.len() + ciphertext.len());
+        segment.extend_from_slice(&self.counter.to_be_bytes());
+        segment.push(flags);
+        segment.extend_from_slice(&iv);
+        segment.extend_from_slice(&ciphertext);
+        self.counter = self.counter.wrapping_add(1);
+        Ok(segment)
+    }
+}
+
+impl<R: Read> Iterator for ChunkedEncryptor<'_, R> {
+    type Item = Result<Vec<u8>, CryptoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let segment = self.next_segment();
+        if segment.is_err() {
+            self.done = true;
+        }
+        Some(segment)
+    }
+}
+
+/// Incremental decryptor returned by [`SyncCrypto::decrypt_chunked`].
+///
+/// Segments must be fed in order. Call [`ChunkedDecryptor::finish`] once the
+/// input is exhausted to detect a stream cut short before its final segment.
+pub struct ChunkedDecryptor<'a> {
+    crypto: &'a SyncCrypto,
+    context: Option<&'a EncryptionContext>,
+    next_counter: u32,
+    finished: bool,
+}
+
+impl ChunkedDecryptor<'_> {
+    /// Decrypt the next segment, rejecting gaps, duplicates and reordering.
+    pub fn decrypt_segment(&mut self, segment: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if self.finished {
+            return Err(CryptoError::TrailingChunk);
+        }
+        if segment.len() < SEGMENT_HEADER_LENGTH + AES_GCM_IV_LENGTH + AES_GCM_TAG_LENGTH {
+            return Err(CryptoError::DataTooShort);
+        }
+        let counter = u32::from_be_bytes(segment[..4].try_into().expect("4 bytes"));
+        if counter != self.next_counter {
+            return Err(CryptoError::ChunkOutOfOrder {
+                expected: self.next_counter,
+                got: counter,
+            });
+        }
+        let flags = segment[4];
+        if flags & !FINAL_SEGMENT != 0 {
+            return Err(CryptoError::DecryptionFailed(format!(
+                "unknown segment flags: {:#04x}",
+                flags
+            )));
+        }
+        let (iv, ciphertext) = segment[SEGMENT_HEADER_LENGTH..].split_at(AES_GCM_IV_LENGTH);
+
+        let aad = segment_aad(self.context, counter, flags);
+        let plaintext = self
+            .crypto
+            .cipher
+            .decrypt(
+                Nonce::from_slice(iv),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
 
+        self.finished = flags == FINAL_SEGMENT;
+        self.next_counter = self.next_counter.wrapping_add(1);
         Ok(plaintext)
     }
+
+    /// Confirm the final segment was seen.
+    pub fn finish(self) -> Result<(), CryptoError> {
+        if self.finished {
+            Ok(())
+        } else {
+            Err(CryptoError::StreamTruncated)
+        }
+    }
 }
 
-/// Encrypt data using AES-256-GCM with v4 wire format (per-record DEK).
+/// Encrypt data with v4 wire format (per-record DEK) under `suite`.
 ///
-/// Returns: [version=4:1B][IV:12B][ciphertext+tag]
+/// Returns: [version:1B][IV:12B][ciphertext+tag], where the version byte is
+/// 4 for AES-256-GCM and 5 for AES-256-GCM-SIV.
 pub fn encrypt_v4(
     data: &[u8],
     dek: &[u8],
     context: Option<&EncryptionContext>,
+    suite: CipherSuite,
 ) -> Result<Vec<u8>, CryptoError> {
     if dek.len() != AES_KEY_LENGTH {
         return Err(CryptoError::InvalidKeyLength {
@@ -143,34 +460,21 @@ pub fn encrypt_v4(
             got: dek.len(),
         });
     }
-    let cipher =
-        Aes256Gcm::new_from_slice(dek).map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
     let iv = generate_iv()?;
-    let nonce = Nonce::from_slice(&iv);
-
-    let ciphertext = match context {
-        Some(ctx) => {
-            let aad = build_aad(ctx);
-            cipher.encrypt(
-                nonce,
-                Payload {
-                    msg: data,
-                    aad: &aad,
-                },
-            )
-        }
-        None => cipher.encrypt(nonce, data),
-    }
-    .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+    let ciphertext = match suite {
+        CipherSuite::Aes256Gcm => seal::<Aes256Gcm>(dek, &iv, data, context)?,
+        CipherSuite::Aes256GcmSiv => seal::<Aes256GcmSiv>(dek, &iv, data, context)?,
+    };
 
     let mut result = Vec::with_capacity(1 + iv.len() + ciphertext.len());
-    result.push(CURRENT_VERSION);
+    result.push(suite.version());
     result.extend_from_slice(&iv);
     result.extend_from_slice(&ciphertext);
     Ok(result)
 }
 
-/// Decrypt data using AES-256-GCM with v4 wire format (per-record DEK).
+/// Decrypt data with v4 wire format (per-record DEK), using the cipher
+/// suite named by the blob's version byte.
 pub fn decrypt_v4(
     blob: &[u8],
     dek: &[u8],
@@ -182,39 +486,11 @@ pub fn decrypt_v4(
             got: dek.len(),
         });
     }
-    let min_length = 1 + AES_GCM_IV_LENGTH + AES_GCM_TAG_LENGTH;
-    if blob.len() < min_length {
-        return Err(CryptoError::DataTooShort);
-    }
-
-    let version = blob[0];
-    if !SUPPORTED_VERSIONS.contains(&version) {
-        return Err(CryptoError::ExpectedV4(version));
-    }
-
-    let iv = &blob[1..1 + AES_GCM_IV_LENGTH];
-    let ciphertext = &blob[1 + AES_GCM_IV_LENGTH..];
-
-    let cipher =
-        Aes256Gcm::new_from_slice(dek).map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
-    let nonce = Nonce::from_slice(iv);
-
-    let plaintext = match context {
-        Some(ctx) => {
-            let aad = build_aad(ctx);
-            cipher.decrypt(
-                nonce,
-                Payload {
-                    msg: ciphertext,
-                    aad: &aad,
-                },
-            )
-        }
-        None => cipher.decrypt(nonce, ciphertext),
+    let (suite, iv, ciphertext) = split_v4_blob(blob)?;
+    match suite {
+        CipherSuite::Aes256Gcm => open::<Aes256Gcm>(dek, iv, ciphertext, context),
+        CipherSuite::Aes256GcmSiv => open::<Aes256GcmSiv>(dek, iv, ciphertext, context),
     }
-    .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
-
-    Ok(plaintext)
 }
 
 /// Encrypt raw bytes with AES-256-GCM without the v4 wire format prefix.
@@ -275,7 +551,8 @@ pub fn aes_gcm_decrypt(key: &[u8], data: &[u8], aad: &[u8]) -> Result<Vec<u8>, C
         .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))
 }
 
-// Aes256Gcm zeroizes its key schedule on drop via the `zeroize` feature.
+// Aes256Gcm and Aes256GcmSiv zeroize their key schedules on drop via the
+// `zeroize` feature.
 
 #[cfg(test)]
 mod tests {
@@ -452,7 +729,7 @@ mod tests {
     fn v4_round_trip() {
         let dek = random_key();
         let plaintext = b"Hello, World!";
-        let encrypted = encrypt_v4(plaintext, &dek, None).unwrap();
+        let encrypted = encrypt_v4(plaintext, &dek, None, CipherSuite::Aes256Gcm).unwrap();
         let decrypted = decrypt_v4(&encrypted, &dek, None).unwrap();
         assert_eq!(decrypted, plaintext);
     }
@@ -460,7 +737,7 @@ mod tests {
     #[test]
     fn v4_version_byte() {
         let dek = random_key();
-        let encrypted = encrypt_v4(&[1, 2, 3], &dek, None).unwrap();
+        let encrypted = encrypt_v4(&[1, 2, 3], &dek, None, CipherSuite::Aes256Gcm).unwrap();
         assert_eq!(encrypted[0], CURRENT_VERSION);
     }
 
@@ -468,8 +745,8 @@ mod tests {
     fn v4_different_ciphertext() {
         let dek = random_key();
         let plaintext = b"test";
-        let enc1 = encrypt_v4(plaintext, &dek, None).unwrap();
-        let enc2 = encrypt_v4(plaintext, &dek, None).unwrap();
+        let enc1 = encrypt_v4(plaintext, &dek, None, CipherSuite::Aes256Gcm).unwrap();
+        let enc2 = encrypt_v4(plaintext, &dek, None, CipherSuite::Aes256Gcm).unwrap();
         assert_ne!(enc1, enc2);
     }
 
@@ -477,14 +754,14 @@ mod tests {
     fn v4_wrong_dek_fails() {
         let dek1 = random_key();
         let dek2 = random_key();
-        let encrypted = encrypt_v4(b"secret", &dek1, None).unwrap();
+        let encrypted = encrypt_v4(b"secret", &dek1, None, CipherSuite::Aes256Gcm).unwrap();
         assert!(decrypt_v4(&encrypted, &dek2, None).is_err());
     }
 
     #[test]
     fn v4_tampered_fails() {
         let dek = random_key();
-        let mut encrypted = encrypt_v4(b"secret", &dek, None).unwrap();
+        let mut encrypted = encrypt_v4(b"secret", &dek, None, CipherSuite::Aes256Gcm).unwrap();
         let last = encrypted.len() - 1;
         encrypted[last] ^= 0xff;
         assert!(decrypt_v4(&encrypted, &dek, None).is_err());
@@ -496,7 +773,7 @@ mod tests {
         let mut blob = vec![0u8; 30];
         blob[0] = 3;
         let err = decrypt_v4(&blob, &dek, None).unwrap_err();
-        assert!(err.to_string().contains("Expected v4"));
+        assert!(matches!(err, CryptoError::UnsupportedVersion(3)));
     }
 
     #[test]
@@ -510,7 +787,7 @@ mod tests {
     #[test]
     fn v4_empty_plaintext() {
         let dek = random_key();
-        let encrypted = encrypt_v4(b"", &dek, None).unwrap();
+        let encrypted = encrypt_v4(b"", &dek, None, CipherSuite::Aes256Gcm).unwrap();
         let decrypted = decrypt_v4(&encrypted, &dek, None).unwrap();
         assert!(decrypted.is_empty());
     }
@@ -520,7 +797,7 @@ mod tests {
         let dek = random_key();
         let mut plaintext = vec![0u8; 100 * 1024];
         getrandom::getrandom(&mut plaintext).unwrap();
-        let encrypted = encrypt_v4(&plaintext, &dek, None).unwrap();
+        let encrypted = encrypt_v4(&plaintext, &dek, None, CipherSuite::Aes256Gcm).unwrap();
         let decrypted = decrypt_v4(&encrypted, &dek, None).unwrap();
         assert_eq!(decrypted, plaintext);
     }
@@ -532,7 +809,8 @@ mod tests {
             space_id: "space-1".into(),
             record_id: "record-1".into(),
         };
-        let encrypted = encrypt_v4(b"bound data", &dek, Some(&ctx)).unwrap();
+        let encrypted =
+            encrypt_v4(b"bound data", &dek, Some(&ctx), CipherSuite::Aes256Gcm).unwrap();
         let decrypted = decrypt_v4(&encrypted, &dek, Some(&ctx)).unwrap();
         assert_eq!(decrypted, b"bound data");
     }
@@ -548,7 +826,7 @@ mod tests {
             space_id: "space-2".into(),
             record_id: "record-1".into(),
         };
-        let encrypted = encrypt_v4(b"data", &dek, Some(&ctx1)).unwrap();
+        let encrypted = encrypt_v4(b"data", &dek, Some(&ctx1), CipherSuite::Aes256Gcm).unwrap();
         assert!(decrypt_v4(&encrypted, &dek, Some(&ctx2)).is_err());
     }
 
@@ -559,10 +837,280 @@ mod tests {
             space_id: "space-1".into(),
             record_id: "record-1".into(),
         };
-        let enc1 = encrypt_v4(b"data", &dek, Some(&ctx)).unwrap();
+        let enc1 = encrypt_v4(b"data", &dek, Some(&ctx), CipherSuite::Aes256Gcm).unwrap();
         assert!(decrypt_v4(&enc1, &dek, None).is_err());
 
-        let enc2 = encrypt_v4(b"data", &dek, None).unwrap();
+        let enc2 = encrypt_v4(b"data", &dek, None, CipherSuite::Aes256Gcm).unwrap();
         assert!(decrypt_v4(&enc2, &dek, Some(&ctx)).is_err());
     }
+
+    // AES-256-GCM-SIV (v5)
+
+    /// AES-256-GCM-SIV, DEK 00..1f, IV a0..ab, AAD for space-1/record-1,
+    /// plaintext "betterbase wire v5". Produced independently of this crate.
+    const V5_VECTOR: &str = "05a0a1a2a3a4a5a6a7a8a9aaab66582ee9ae5ad1ed0a83cf468ca200d99b\
+                             0fba3f5d3b2a53505c44e00e8c0fe482e2";
+
+    fn vector_context() -> EncryptionContext {
+        EncryptionContext {
+            space_id: "space-1".into(),
+            record_id: "record-1".into(),
+        }
+    }
+
+    #[test]
+    fn v5_round_trip() {
+        let dek = random_key();
+        let ctx = vector_context();
+        let encrypted =
+            encrypt_v4(b"siv data", &dek, Some(&ctx), CipherSuite::Aes256GcmSiv).unwrap();
+        assert_eq!(encrypted[0], GCM_SIV_VERSION);
+        assert_eq!(
+            encrypted.len(),
+            1 + AES_GCM_IV_LENGTH + 8 + AES_GCM_TAG_LENGTH
+        );
+        assert_eq!(
+            decrypt_v4(&encrypted, &dek, Some(&ctx)).unwrap(),
+            b"siv data"
+        );
+
+        let empty = encrypt_v4(b"", &dek, None, CipherSuite::Aes256GcmSiv).unwrap();
+        assert!(decrypt_v4(&empty, &dek, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn v5_regression_vector_opens() {
+        let blob = hex::decode(V5_VECTOR).unwrap();
+        let dek: Vec<u8> = (0u8..32).collect();
+        let plaintext = decrypt_v4(&blob, &dek, Some(&vector_context())).unwrap();
+        assert_eq!(plaintext, b"betterbase wire v5");
+    }
+
+    #[test]
+    fn v5_binds_context_and_key() {
+        let dek = random_key();
+        let ctx = vector_context();
+        let other = EncryptionContext {
+            space_id: "space-2".into(),
+            record_id: "record-1".into(),
+        };
+        let encrypted = encrypt_v4(b"data", &dek, Some(&ctx), CipherSuite::Aes256GcmSiv).unwrap();
+        assert!(decrypt_v4(&encrypted, &dek, Some(&other)).is_err());
+        assert!(decrypt_v4(&encrypted, &dek, None).is_err());
+        assert!(decrypt_v4(&encrypted, &random_key(), Some(&ctx)).is_err());
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+        assert!(decrypt_v4(&tampered, &dek, Some(&ctx)).is_err());
+    }
+
+    #[test]
+    fn cross_version_blobs_decrypt_under_their_own_suite() {
+        let dek = random_key();
+        let ctx = vector_context();
+        let v4 = encrypt_v4(b"same", &dek, Some(&ctx), CipherSuite::Aes256Gcm).unwrap();
+        let v5 = encrypt_v4(b"same", &dek, Some(&ctx), CipherSuite::Aes256GcmSiv).unwrap();
+        assert_eq!(v4[0], CURRENT_VERSION);
+        assert_eq!(v5[0], GCM_SIV_VERSION);
+        assert_eq!(decrypt_v4(&v4, &dek, Some(&ctx)).unwrap(), b"same");
+        assert_eq!(decrypt_v4(&v5, &dek, Some(&ctx)).unwrap(), b"same");
+
+        // Relabelling a blob sends it to the other cipher, which rejects it
+        let mut v4_as_v5 = v4.clone();
+        v4_as_v5[0] = GCM_SIV_VERSION;
+        assert!(decrypt_v4(&v4_as_v5, &dek, Some(&ctx)).is_err());
+        let mut v5_as_v4 = v5.clone();
+        v5_as_v4[0] = CURRENT_VERSION;
+        assert!(decrypt_v4(&v5_as_v4, &dek, Some(&ctx)).is_err());
+    }
+
+    #[test]
+    fn cipher_suite_version_bytes() {
+        assert_eq!(CipherSuite::default(), CipherSuite::Aes256Gcm);
+        for suite in [CipherSuite::Aes256Gcm, CipherSuite::Aes256GcmSiv] {
+            assert!(SUPPORTED_VERSIONS.contains(&suite.version()));
+            assert_eq!(CipherSuite::from_version(suite.version()).unwrap(), suite);
+        }
+        assert!(matches!(
+            CipherSuite::from_version(3),
+            Err(CryptoError::UnsupportedVersion(3))
+        ));
+    }
+
+    #[test]
+    fn sync_crypto_reads_v5_blob() {
+        let key = random_key();
+        let ctx = vector_context();
+        let sc = SyncCrypto::new(&key, 1).unwrap();
+        let blob = encrypt_v4(b"data", &key, Some(&ctx), CipherSuite::Aes256GcmSiv).unwrap();
+        assert_eq!(sc.decrypt(&blob, Some(&ctx)).unwrap(), b"data");
+        assert!(sc.decrypt(&blob, None).is_err());
+
+        let mut relabelled = blob.clone();
+        relabelled[0] = CURRENT_VERSION;
+        assert!(sc.decrypt(&relabelled, Some(&ctx)).is_err());
+        relabelled[0] = 6;
+        assert!(matches!(
+            sc.decrypt(&relabelled, Some(&ctx)),
+            Err(CryptoError::UnsupportedVersion(6))
+        ));
+    }
+
+    // -- Chunked --
+
+    fn encrypt_segments(
+        sc: &SyncCrypto,
+        data: &[u8],
+        ctx: Option<&EncryptionContext>,
+    ) -> Vec<Vec<u8>> {
+        sc.encrypt_chunked(data, 10, ctx)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn decrypt_segments(
+        sc: &SyncCrypto,
+        segments: &[Vec<u8>],
+        ctx: Option<&EncryptionContext>,
+    ) -> Result<Vec<u8>, CryptoError> {
+        let mut decryptor = sc.decrypt_chunked(ctx);
+        let mut out = Vec::new();
+        for segment in segments {
+            out.extend(decryptor.decrypt_segment(segment)?);
+        }
+        decryptor.finish()?;
+        Ok(out)
+    }
+
+    #[test]
+    fn chunked_round_trip() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let ctx = EncryptionContext {
+            space_id: "space-1".into(),
+            record_id: "record-1".into(),
+        };
+        for len in [0, 1, 9, 10, 11, 20, 95] {
+            let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let segments = encrypt_segments(&sc, &data, Some(&ctx));
+            assert_eq!(segments.len(), (len as usize).div_ceil(10).max(1));
+            assert_eq!(decrypt_segments(&sc, &segments, Some(&ctx)).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn chunked_segments_use_distinct_ivs() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let segments = encrypt_segments(&sc, &[7u8; 30], None);
+        assert_ne!(segments[0][5..17], segments[1][5..17]);
+        assert_ne!(segments[1][5..17], segments[2][5..17]);
+    }
+
+    #[test]
+    fn chunked_rejects_missing_final_chunk() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let mut segments = encrypt_segments(&sc, &[1u8; 25], None);
+        segments.pop();
+        assert!(matches!(
+            decrypt_segments(&sc, &segments, None),
+            Err(CryptoError::StreamTruncated)
+        ));
+    }
+
+    #[test]
+    fn chunked_rejects_truncated_final_chunk() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let mut segments = encrypt_segments(&sc, &[1u8; 25], None);
+        let last = segments.last_mut().unwrap();
+        last.truncate(last.len() - 1);
+        assert!(matches!(
+            decrypt_segments(&sc, &segments, None),
+            Err(CryptoError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn chunked_rejects_early_chunk_marked_final() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let mut segments = encrypt_segments(&sc, &[1u8; 25], None);
+        segments[1][4] = FINAL_SEGMENT;
+        segments.truncate(2);
+        assert!(decrypt_segments(&sc, &segments, None).is_err());
+    }
+
+    #[test]
+    fn chunked_rejects_reordered_chunks() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let mut segments = encrypt_segments(&sc, &[1u8; 25], None);
+        segments.swap(0, 1);
+        assert!(matches!(
+            decrypt_segments(&sc, &segments, None),
+            Err(CryptoError::ChunkOutOfOrder {
+                expected: 0,
+                got: 1
+            })
+        ));
+
+        // Relabelling the counters to hide the swap breaks authentication
+        segments[0][..4].copy_from_slice(&0u32.to_be_bytes());
+        segments[1][..4].copy_from_slice(&1u32.to_be_bytes());
+        assert!(matches!(
+            decrypt_segments(&sc, &segments, None),
+            Err(CryptoError::DecryptionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn chunked_rejects_gaps_and_duplicates() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let segments = encrypt_segments(&sc, &[1u8; 35], None);
+
+        let gap = vec![segments[0].clone(), segments[2].clone()];
+        assert!(matches!(
+            decrypt_segments(&sc, &gap, None),
+            Err(CryptoError::ChunkOutOfOrder {
+                expected: 1,
+                got: 2
+            })
+        ));
+
+        let duplicate = vec![segments[0].clone(), segments[0].clone()];
+        assert!(matches!(
+            decrypt_segments(&sc, &duplicate, None),
+            Err(CryptoError::ChunkOutOfOrder {
+                expected: 1,
+                got: 0
+            })
+        ));
+
+        let mut trailing = segments.clone();
+        trailing.push(segments[3].clone());
+        assert!(matches!(
+            decrypt_segments(&sc, &trailing, None),
+            Err(CryptoError::TrailingChunk)
+        ));
+    }
+
+    #[test]
+    fn chunked_binds_context() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        let ctx = EncryptionContext {
+            space_id: "space-1".into(),
+            record_id: "record-1".into(),
+        };
+        let other = EncryptionContext {
+            space_id: "space-1".into(),
+            record_id: "record-2".into(),
+        };
+        let segments = encrypt_segments(&sc, b"attachment", Some(&ctx));
+        assert!(decrypt_segments(&sc, &segments, Some(&other)).is_err());
+        assert!(decrypt_segments(&sc, &segments, None).is_err());
+    }
+
+    #[test]
+    fn chunked_rejects_zero_chunk_size() {
+        let sc = SyncCrypto::new(&random_key(), 1).unwrap();
+        assert!(sc.encrypt_chunked(&b"data"[..], 0, None).is_err());
+    }
 }
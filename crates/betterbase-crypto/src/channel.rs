@@ -1,6 +1,10 @@
 //! Channel key derivation for encrypted presence and events.
 //!
 //! channelKey = HKDF-SHA256(epochKey, salt="betterbase:channel-salt:v1", info="betterbase:channel:v1:{spaceId}")
+//!
+//! This info string predates [`derive_labeled`](crate::hkdf::derive_labeled)
+//! and is shared with the WebCrypto path, so it stays as-is: moving it to a
+//! labeled info would change every channel key.
 
 use crate::error::CryptoError;
 use crate::hkdf::hkdf_derive;
@@ -10,6 +14,8 @@ const CHANNEL_SALT: &[u8] = b"betterbase:channel-salt:v1";
 const CHANNEL_INFO_PREFIX: &str = "betterbase:channel:v1:";
 const PRESENCE_AAD_PREFIX: &str = "betterbase:presence:v1\0";
 const EVENT_AAD_PREFIX: &str = "betterbase:event:v1\0";
+const PRESENCE_SEQ_AAD_PREFIX: &str = "betterbase:presence:v2\0";
+const EVENT_SEQ_AAD_PREFIX: &str = "betterbase:event:v2\0";
 
 /// Derive a channel key from an epoch key for a given space.
 pub fn derive_channel_key(
@@ -39,6 +45,34 @@ pub fn build_event_aad(space_id: &str) -> Vec<u8> {
     format!("{}{}", EVENT_AAD_PREFIX, space_id).into_bytes()
 }
 
+/// Build AAD for a presence frame from `sender` bound to its per-sender
+/// `sequence`.
+/// Format: "betterbase:presence:v2\0" || sequence (u64 BE) || len(sender) (u32 BE) || sender || spaceId
+pub fn build_presence_aad_seq(space_id: &str, sender: &str, sequence: u64) -> Vec<u8> {
+    sequenced_aad(PRESENCE_SEQ_AAD_PREFIX, space_id, sender, sequence)
+}
+
+/// Build AAD for an event frame from `sender` bound to its per-sender
+/// `sequence`.
+/// Format: "betterbase:event:v2\0" || sequence (u64 BE) || len(sender) (u32 BE) || sender || spaceId
+pub fn build_event_aad_seq(space_id: &str, sender: &str, sequence: u64) -> Vec<u8> {
+    sequenced_aad(EVENT_SEQ_AAD_PREFIX, space_id, sender, sequence)
+}
+
+/// The sequence is fixed-width and the sender length-prefixed, both ahead
+/// of the space id, so no `(spaceId, sender, sequence)` triple can collide
+/// with another. Binding the sender keeps a relay from replaying one
+/// sender's frame under another sender's replay window.
+fn sequenced_aad(prefix: &str, space_id: &str, sender: &str, sequence: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(prefix.len() + 12 + sender.len() + space_id.len());
+    aad.extend_from_slice(prefix.as_bytes());
+    aad.extend_from_slice(&sequence.to_be_bytes());
+    aad.extend_from_slice(&(sender.len() as u32).to_be_bytes());
+    aad.extend_from_slice(sender.as_bytes());
+    aad.extend_from_slice(space_id.as_bytes());
+    aad
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -127,4 +161,43 @@ mod tests {
         let event = build_event_aad("space-1");
         assert_ne!(presence, event);
     }
+
+    #[test]
+    fn sequenced_aad_format() {
+        let aad = build_presence_aad_seq("my-space", "alice", 0x0102);
+        let mut expected = b"betterbase:presence:v2\0".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 2]);
+        expected.extend_from_slice(&[0, 0, 0, 5]);
+        expected.extend_from_slice(b"alice");
+        expected.extend_from_slice(b"my-space");
+        assert_eq!(aad, expected);
+        assert!(
+            build_event_aad_seq("my-space", "alice", 0x0102).starts_with(b"betterbase:event:v2\0")
+        );
+    }
+
+    #[test]
+    fn sequenced_aad_binds_sender_sequence_and_version() {
+        assert_ne!(
+            build_event_aad_seq("space-1", "alice", 1),
+            build_event_aad_seq("space-1", "alice", 2)
+        );
+        assert_ne!(
+            build_event_aad_seq("space-1", "alice", 1),
+            build_event_aad_seq("space-1", "bob", 1)
+        );
+        // The length prefix keeps the sender/space boundary unambiguous
+        assert_ne!(
+            build_event_aad_seq("b-space", "alice", 1),
+            build_event_aad_seq("space", "alice-b", 1)
+        );
+        assert_ne!(
+            build_presence_aad_seq("space-1", "alice", 1),
+            build_event_aad_seq("space-1", "alice", 1)
+        );
+        assert_ne!(
+            build_event_aad_seq("space-1", "alice", 0),
+            build_event_aad("space-1")
+        );
+    }
 }
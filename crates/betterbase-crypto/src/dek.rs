@@ -4,16 +4,66 @@
 //! The DEK is wrapped (encrypted) with the epoch KEK using AES-KW.
 //!
 //! Wrapped DEK wire format: [epoch:4 BE][AES-KW(KEK, DEK):40] = 44 bytes total
+//!
+//! [`wrap_dek_kw`] / [`unwrap_dek_kw`] produce bare AES-KW output under a
+//! 128-, 192- or 256-bit KEK, for exchanging DEKs with external key stores.
+//!
+//! [`merge_wrapped_dek_sets`] reconciles the wrapped DEKs of two spaces being
+//! merged under one surviving epoch key.
+
+use std::collections::BTreeMap;
 
 use crate::error::CryptoError;
 use crate::types::AES_KEY_LENGTH;
-use aes_kw::Kek;
+use aes_kw::{Kek, KekAes128, KekAes192, KekAes256};
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::Zeroizing;
 
 /// Size of a wrapped DEK in bytes: 4 (epoch) + 40 (AES-KW output for 32-byte key).
 pub const WRAPPED_DEK_SIZE: usize = 44;
 
 /// AES-KW output size for a 32-byte key: 32 + 8 = 40 bytes.
-const AES_KW_OUTPUT_SIZE: usize = 40;
+pub const AES_KW_OUTPUT_SIZE: usize = 40;
+
+/// AES-KW key, with the AES variant picked by KEK length.
+enum AnyKek {
+    Aes128(KekAes128),
+    Aes192(KekAes192),
+    Aes256(KekAes256),
+}
+
+impl AnyKek {
+    fn new(kek: &[u8]) -> Result<Self, CryptoError> {
+        Ok(match kek.len() {
+            16 => Self::Aes128(KekAes128::from(
+                <[u8; 16]>::try_from(kek).expect("length checked"),
+            )),
+            24 => Self::Aes192(KekAes192::from(
+                <[u8; 24]>::try_from(kek).expect("length checked"),
+            )),
+            32 => Self::Aes256(KekAes256::from(
+                <[u8; 32]>::try_from(kek).expect("length checked"),
+            )),
+            n => return Err(CryptoError::InvalidKekLength(n)),
+        })
+    }
+
+    fn wrap(&self, data: &[u8], out: &mut [u8]) -> Result<(), aes_kw::Error> {
+        match self {
+            Self::Aes128(k) => k.wrap(data, out),
+            Self::Aes192(k) => k.wrap(data, out),
+            Self::Aes256(k) => k.wrap(data, out),
+        }
+    }
+
+    fn unwrap(&self, data: &[u8], out: &mut [u8]) -> Result<(), aes_kw::Error> {
+        match self {
+            Self::Aes128(k) => k.unwrap(data, out),
+            Self::Aes192(k) => k.unwrap(data, out),
+            Self::Aes256(k) => k.unwrap(data, out),
+        }
+    }
+}
 
 /// Generate a random 256-bit Data Encryption Key.
 pub fn generate_dek() -> Result<[u8; AES_KEY_LENGTH], CryptoError> {
@@ -22,6 +72,46 @@ pub fn generate_dek() -> Result<[u8; AES_KEY_LENGTH], CryptoError> {
     Ok(dek)
 }
 
+/// Generate `n` random 256-bit DEKs from a single CSPRNG draw.
+///
+/// Draws `n * 32` bytes with one `getrandom` call and splits them, which
+/// avoids a syscall per key when provisioning many records at once. The
+/// intermediate buffer is zeroized before returning. Fails with
+/// `InvalidDekCount` if `n * 32` overflows `usize`.
+pub fn generate_deks(n: usize) -> Result<Vec<[u8; AES_KEY_LENGTH]>, CryptoError> {
+    let len = n
+        .checked_mul(AES_KEY_LENGTH)
+        .ok_or(CryptoError::InvalidDekCount(n))?;
+    let mut pool = vec![0u8; len];
+    getrandom::getrandom(&mut pool).map_err(|e| CryptoError::RngFailed(e.to_string()))?;
+
+    let deks = pool
+        .chunks_exact(AES_KEY_LENGTH)
+        .map(|chunk| {
+            let mut dek = [0u8; AES_KEY_LENGTH];
+            dek.copy_from_slice(chunk);
+            dek
+        })
+        .collect();
+    zeroize::Zeroize::zeroize(&mut pool);
+    Ok(deks)
+}
+
+/// Compare two byte strings (e.g. unwrapped DEKs) in constant time.
+///
+/// Every byte position up to the longer length is compared, so the running
+/// time depends only on the lengths, never on the content. Inputs of
+/// different lengths are unequal.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    let same_len = (a.len() as u64).ct_eq(&(b.len() as u64));
+    let same_bytes = (0..a.len().max(b.len())).fold(Choice::from(1), |acc, i| {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        acc & x.ct_eq(&y)
+    });
+    (same_len & same_bytes).into()
+}
+
 /// Wrap a DEK with a KEK using AES-KW, prefixed with the epoch number.
 ///
 /// # Arguments
@@ -102,9 +192,123 @@ pub fn unwrap_dek(wrapped_dek: &[u8], kek: &[u8]) -> Result<(Vec<u8>, u32), Cryp
         .unwrap(wrapped_key_bytes, &mut dek)
         .map_err(|e| CryptoError::UnwrapFailed(format!("{:?}", e)))?;
 
+    // Self-check: AES-KW is deterministic, so re-wrapping must reproduce the
+    // input exactly. Compared in constant time like any key material.
+    let mut rewrapped = [0u8; AES_KW_OUTPUT_SIZE];
+    kek_key
+        .wrap(&dek, &mut rewrapped)
+        .map_err(|e| CryptoError::UnwrapFailed(format!("{:?}", e)))?;
+    if !ct_eq(&rewrapped, wrapped_key_bytes) {
+        zeroize::Zeroize::zeroize(&mut dek);
+        return Err(CryptoError::UnwrapFailed(
+            "re-wrap self-check failed".to_string(),
+        ));
+    }
+
     Ok((dek, epoch))
 }
 
+/// Wrap a DEK with AES-KW (RFC 3394) under a 16-, 24- or 32-byte KEK,
+/// using AES-128, AES-192 or AES-256 to match.
+///
+/// Returns the bare 40-byte AES-KW output, without the epoch prefix of
+/// [`wrap_dek`]: this is for exchanging DEKs with external key stores, not
+/// for records.
+pub fn wrap_dek_kw(dek: &[u8], kek: &[u8]) -> Result<[u8; AES_KW_OUTPUT_SIZE], CryptoError> {
+    if dek.len() != AES_KEY_LENGTH {
+        return Err(CryptoError::InvalidDekLength {
+            expected: AES_KEY_LENGTH,
+            got: dek.len(),
+        });
+    }
+    let kek_key = AnyKek::new(kek)?;
+    let mut wrapped = [0u8; AES_KW_OUTPUT_SIZE];
+    kek_key
+        .wrap(dek, &mut wrapped)
+        .map_err(|e| CryptoError::WrapFailed(format!("{:?}", e)))?;
+    Ok(wrapped)
+}
+
+/// Unwrap a DEK produced by [`wrap_dek_kw`] (or any RFC 3394 AES-KW of a
+/// 32-byte key) under a 16-, 24- or 32-byte KEK.
+pub fn unwrap_dek_kw(wrapped: &[u8], kek: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if wrapped.len() != AES_KW_OUTPUT_SIZE {
+        return Err(CryptoError::InvalidWrappedDekLength {
+            expected: AES_KW_OUTPUT_SIZE,
+            got: wrapped.len(),
+        });
+    }
+    let kek_key = AnyKek::new(kek)?;
+    let mut dek = vec![0u8; AES_KEY_LENGTH];
+    kek_key
+        .unwrap(wrapped, &mut dek)
+        .map_err(|e| CryptoError::UnwrapFailed(format!("{:?}", e)))?;
+    Ok(dek)
+}
+
+/// Wrapped DEKs of one side of a space merge, keyed by record id.
+///
+/// Every entry must unwrap under `kek`; rewrap entries from older epochs
+/// before merging.
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedDekSet<'a> {
+    pub kek: &'a [u8],
+    pub entries: &'a BTreeMap<String, Vec<u8>>,
+}
+
+/// Result of [`merge_wrapped_dek_sets`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergedDekSet {
+    /// One DEK per record id, wrapped under the surviving epoch key.
+    pub entries: BTreeMap<String, [u8; WRAPPED_DEK_SIZE]>,
+    /// Record ids present in both sets whose DEKs differ, sorted. These are
+    /// left out of `entries`: the caller must decide which content survives.
+    pub conflicts: Vec<String>,
+}
+
+/// Merge the wrapped-DEK sets of two spaces under the surviving epoch key.
+///
+/// A record id in both sets must unwrap to the same DEK on each side
+/// (compared in constant time); otherwise it is reported in `conflicts`.
+/// Every other record is rewrapped once under `surviving_kek` at
+/// `surviving_epoch`. Unwrapped DEKs are zeroized before returning.
+///
+/// # Errors
+/// Fails if any entry does not unwrap under its set's KEK, or if
+/// `surviving_kek` is not 32 bytes.
+pub fn merge_wrapped_dek_sets(
+    a: &WrappedDekSet<'_>,
+    b: &WrappedDekSet<'_>,
+    surviving_kek: &[u8],
+    surviving_epoch: u32,
+) -> Result<MergedDekSet, CryptoError> {
+    let mut merged = MergedDekSet::default();
+    let b_only = b.entries.keys().filter(|id| !a.entries.contains_key(*id));
+    for id in a.entries.keys().chain(b_only) {
+        let dek = match (a.entries.get(id), b.entries.get(id)) {
+            (Some(wrapped_a), Some(wrapped_b)) => {
+                let dek_a = unwrap_zeroizing(wrapped_a, a.kek)?;
+                let dek_b = unwrap_zeroizing(wrapped_b, b.kek)?;
+                if !ct_eq(&dek_a, &dek_b) {
+                    merged.conflicts.push(id.clone());
+                    continue;
+                }
+                dek_a
+            }
+            (Some(wrapped), None) => unwrap_zeroizing(wrapped, a.kek)?,
+            (None, Some(wrapped)) => unwrap_zeroizing(wrapped, b.kek)?,
+            (None, None) => unreachable!("id comes from one of the sets"),
+        };
+        let rewrapped = wrap_dek(&dek, surviving_kek, surviving_epoch)?;
+        merged.entries.insert(id.clone(), rewrapped);
+    }
+    Ok(merged)
+}
+
+fn unwrap_zeroizing(wrapped: &[u8], kek: &[u8]) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    Ok(Zeroizing::new(unwrap_dek(wrapped, kek)?.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +332,33 @@ mod tests {
         assert_ne!(dek1, dek2);
     }
 
+    #[test]
+    fn generate_deks_returns_n_distinct_keys() {
+        let deks = generate_deks(4096).unwrap();
+        assert_eq!(deks.len(), 4096);
+
+        let unique: std::collections::HashSet<_> = deks.iter().collect();
+        assert_eq!(unique.len(), deks.len(), "batch DEKs must all differ");
+        assert!(deks.iter().all(|d| d.iter().any(|b| *b != 0)));
+    }
+
+    #[test]
+    fn generate_deks_zero_is_empty() {
+        assert!(generate_deks(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn generate_deks_rejects_overflowing_count() {
+        assert!(matches!(
+            generate_deks(usize::MAX),
+            Err(CryptoError::InvalidDekCount(usize::MAX))
+        ));
+        assert!(matches!(
+            generate_deks(usize::MAX / AES_KEY_LENGTH + 1),
+            Err(CryptoError::InvalidDekCount(_))
+        ));
+    }
+
     #[test]
     fn wrap_unwrap_round_trip() {
         let dek = generate_dek().unwrap();
@@ -205,6 +436,99 @@ mod tests {
         assert_eq!(epoch, 0);
     }
 
+    #[test]
+    fn ct_eq_matches_equal_keys() {
+        let dek = generate_dek().unwrap();
+        assert!(ct_eq(&dek, &dek.clone()));
+        assert!(ct_eq(&[], &[]));
+    }
+
+    #[test]
+    fn ct_eq_rejects_unequal_keys() {
+        let dek = generate_dek().unwrap();
+        for i in [0, 15, 31] {
+            let mut other = dek;
+            other[i] ^= 0x01;
+            assert!(!ct_eq(&dek, &other), "differs at byte {i}");
+        }
+        assert!(!ct_eq(&dek, &generate_dek().unwrap()));
+    }
+
+    #[test]
+    fn ct_eq_rejects_length_mismatch() {
+        let dek = generate_dek().unwrap();
+        assert!(!ct_eq(&dek, &dek[..31]));
+        assert!(!ct_eq(&dek[..31], &dek));
+        // A zero-padded prefix must not compare equal to the padded key
+        assert!(!ct_eq(&[0u8; 31], &[0u8; 32]));
+        assert!(!ct_eq(&[], &dek));
+        assert!(!ct_eq(&dek, &[]));
+    }
+
+    #[test]
+    fn ct_eq_handles_empty_and_full_length_inputs() {
+        let zeros = [0u8; AES_KEY_LENGTH];
+        let ones = [0xffu8; AES_KEY_LENGTH];
+        assert!(ct_eq(&zeros, &zeros));
+        assert!(!ct_eq(&zeros, &ones));
+        assert!(!ct_eq(&[], &zeros));
+    }
+
+    #[test]
+    fn kw_round_trip_for_each_kek_size() {
+        let dek = generate_dek().unwrap();
+        for size in [16, 24, 32] {
+            let mut kek = vec![0u8; size];
+            getrandom::getrandom(&mut kek).unwrap();
+            let wrapped = wrap_dek_kw(&dek, &kek).unwrap();
+            assert_eq!(
+                unwrap_dek_kw(&wrapped, &kek).unwrap(),
+                dek,
+                "{size}-byte KEK"
+            );
+        }
+    }
+
+    #[test]
+    fn kw_kek_sizes_are_not_interchangeable() {
+        let dek = generate_dek().unwrap();
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F1011121314151617").unwrap();
+        let wrapped = wrap_dek_kw(&dek, &kek).unwrap();
+
+        // Neither AES-128 with its prefix nor AES-256 with it zero-extended
+        assert!(unwrap_dek_kw(&wrapped, &kek[..16]).is_err());
+        let mut extended = kek.clone();
+        extended.extend_from_slice(&[0u8; 8]);
+        assert!(unwrap_dek_kw(&wrapped, &extended).is_err());
+
+        let mut other = kek.clone();
+        other[0] ^= 0x01;
+        assert!(unwrap_dek_kw(&wrapped, &other).is_err());
+    }
+
+    #[test]
+    fn kw_with_256_bit_kek_matches_wrap_dek_payload() {
+        let dek = generate_dek().unwrap();
+        let kek = random_key();
+        let wrapped = wrap_dek(&dek, &kek, 7).unwrap();
+        assert_eq!(wrap_dek_kw(&dek, &kek).unwrap(), wrapped[4..]);
+    }
+
+    #[test]
+    fn kw_rejects_invalid_kek_length() {
+        let dek = generate_dek().unwrap();
+        for size in [0, 8, 20, 33] {
+            assert!(matches!(
+                wrap_dek_kw(&dek, &vec![0u8; size]),
+                Err(CryptoError::InvalidKekLength(n)) if n == size
+            ));
+            assert!(matches!(
+                unwrap_dek_kw(&[0u8; AES_KW_OUTPUT_SIZE], &vec![0u8; size]),
+                Err(CryptoError::InvalidKekLength(n)) if n == size
+            ));
+        }
+    }
+
     #[test]
     fn large_epoch() {
         let dek = generate_dek().unwrap();
@@ -215,4 +539,101 @@ mod tests {
         assert_eq!(unwrap_epoch, epoch);
         assert_eq!(unwrapped, dek);
     }
+
+    fn wrapped_set(kek: &[u8], epoch: u32, deks: &[(&str, [u8; 32])]) -> BTreeMap<String, Vec<u8>> {
+        deks.iter()
+            .map(|(id, dek)| (id.to_string(), wrap_dek(dek, kek, epoch).unwrap().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn merge_wrapped_dek_sets_rewraps_under_surviving_key() {
+        let (kek_a, kek_b, surviving) = (random_key(), random_key(), random_key());
+        let (shared, only_a, only_b) = (
+            generate_dek().unwrap(),
+            generate_dek().unwrap(),
+            generate_dek().unwrap(),
+        );
+        let a = wrapped_set(&kek_a, 3, &[("shared", shared), ("only-a", only_a)]);
+        let b = wrapped_set(&kek_b, 7, &[("shared", shared), ("only-b", only_b)]);
+
+        let merged = merge_wrapped_dek_sets(
+            &WrappedDekSet {
+                kek: &kek_a,
+                entries: &a,
+            },
+            &WrappedDekSet {
+                kek: &kek_b,
+                entries: &b,
+            },
+            &surviving,
+            8,
+        )
+        .unwrap();
+
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.entries.keys().collect::<Vec<_>>(),
+            ["only-a", "only-b", "shared"]
+        );
+        for (id, dek) in [("shared", shared), ("only-a", only_a), ("only-b", only_b)] {
+            let (unwrapped, epoch) = unwrap_dek(&merged.entries[id], &surviving).unwrap();
+            assert_eq!(unwrapped, dek, "{id}");
+            assert_eq!(epoch, 8);
+        }
+    }
+
+    #[test]
+    fn merge_wrapped_dek_sets_flags_conflicting_deks() {
+        let (kek_a, kek_b, surviving) = (random_key(), random_key(), random_key());
+        let (dek_a, dek_b, same) = (
+            generate_dek().unwrap(),
+            generate_dek().unwrap(),
+            generate_dek().unwrap(),
+        );
+        let a = wrapped_set(&kek_a, 1, &[("forked", dek_a), ("same", same)]);
+        let b = wrapped_set(&kek_b, 1, &[("forked", dek_b), ("same", same)]);
+
+        let merged = merge_wrapped_dek_sets(
+            &WrappedDekSet {
+                kek: &kek_a,
+                entries: &a,
+            },
+            &WrappedDekSet {
+                kek: &kek_b,
+                entries: &b,
+            },
+            &surviving,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(merged.conflicts, ["forked"]);
+        assert!(!merged.entries.contains_key("forked"));
+        assert!(merged.entries.contains_key("same"));
+    }
+
+    #[test]
+    fn merge_wrapped_dek_sets_rejects_entry_under_wrong_kek() {
+        let (kek_a, kek_b, surviving) = (random_key(), random_key(), random_key());
+        let dek = generate_dek().unwrap();
+        // Wrapped under B's key but presented as part of A.
+        let a = wrapped_set(&kek_b, 1, &[("r1", dek)]);
+        let b = BTreeMap::new();
+
+        let err = merge_wrapped_dek_sets(
+            &WrappedDekSet {
+                kek: &kek_a,
+                entries: &a,
+            },
+            &WrappedDekSet {
+                kek: &kek_b,
+                entries: &b,
+            },
+            &surviving,
+            2,
+        )
+        .unwrap_err();
+        assert!(matches!(err, CryptoError::UnwrapFailed(_)), "{err}");
+    }
 }
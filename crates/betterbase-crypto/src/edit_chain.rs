@@ -1,18 +1,27 @@
 //! Signed edit chain primitives.
 //!
 //! An append-only chain of signed entries that captures who edited a record
-//! and what changed. Each entry includes an ECDSA P-256 signature and a
-//! hash link to the previous entry, making the chain tamper-evident.
+//! and what changed. Each entry includes an ECDSA P-256 or Ed25519
+//! signature and a hash link to the previous entry, making the chain
+//! tamper-evident.
+//!
+//! An author who rotates identity keys appends a key rotation entry, signed
+//! by the old key, naming the new did:key. Later entries by the new key are
+//! vouched for by it (see [`verify_edit_chain_for_authors`]); later entries
+//! by the old key are rejected.
 
 use p256::ecdsa::SigningKey;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 
 use crate::base64url::{base64url_decode, base64url_encode};
 use crate::error::CryptoError;
-use crate::signing::{sign, verify};
-use crate::ucan::encode_did_key_from_jwk;
+use crate::signing::{
+    canonicalize_jwk, export_public_key_jwk, sign, verify_batch_with_jwk, verify_with_jwk,
+};
+use crate::ucan::{did_key_algorithm, encode_did_key_from_jwk};
 
 // ---------------------------------------------------------------------------
 // Types
@@ -43,10 +52,13 @@ pub struct EditEntry {
     pub d: Vec<EditDiff>,
     /// Hex SHA-256 of previous entry's `s` bytes (null for first). Signed.
     pub p: Option<String>,
-    /// ECDSA P-256 signature (64 bytes IEEE P1363).
+    /// Signature by `k`: ECDSA P-256 (64 bytes IEEE P1363) or Ed25519 (64 bytes).
     pub s: Vec<u8>,
     /// Signer's public key JWK (self-contained verification).
     pub k: Value,
+    /// For a key rotation entry, the did:key the author rotates to. Signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -142,6 +154,32 @@ pub fn build_edit_signing_message(
     message.into_bytes()
 }
 
+/// Build the signing message for a key rotation entry.
+///
+/// Format: `betterbase:editlog:rotate:v1\0{collection}\0{recordId}\0{oldDid}\0{newDid}\0{timestamp}\0{prevHash}`
+///
+/// The distinct prefix keeps a rotation signature from ever verifying as an
+/// edit signature, or the reverse.
+pub fn build_key_rotation_signing_message(
+    collection: &str,
+    record_id: &str,
+    old_did: &str,
+    new_did: &str,
+    timestamp: u64,
+    prev_hash: Option<&str>,
+) -> Vec<u8> {
+    let message = format!(
+        "betterbase:editlog:rotate:v1\0{}\0{}\0{}\0{}\0{}\0{}",
+        collection,
+        record_id,
+        old_did,
+        new_did,
+        timestamp,
+        prev_hash.unwrap_or("")
+    );
+    message.into_bytes()
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -164,6 +202,7 @@ fn sha256_hash(data: &[u8]) -> Vec<u8> {
 ///
 /// Computes prevHash from the previous entry's signature via SHA-256.
 /// Enforces timestamp monotonicity: `t = max(t, prevEntry.t + 1)`.
+/// The public key is stored as `k` in canonical form (see [`canonicalize_jwk`]).
 #[allow(clippy::too_many_arguments)]
 pub fn sign_edit_entry(
     private_key: &SigningKey,
@@ -193,16 +232,124 @@ pub fn sign_edit_entry(
     );
     let s = sign(private_key, &message)?;
 
+    let mut k = canonicalize_jwk(public_key_jwk)?;
+    // Never publish a private scalar, even if a private JWK was passed in
+    if let Some(members) = k.as_object_mut() {
+        members.remove("d");
+    }
+
     Ok(EditEntry {
         a: author.to_string(),
         t,
         d: diffs,
         p: prev_hash,
         s,
-        k: public_key_jwk.clone(),
+        k,
+        r: None,
+    })
+}
+
+/// Sign a key rotation entry and return it.
+///
+/// The entry is authored by `author_old_did` and signed by `old_key`,
+/// attesting that `author_new_did` (the did:key of `new_key`/`new_jwk`) now
+/// speaks for the same author. It carries no diffs, and is linked and
+/// timestamped like [`sign_edit_entry`].
+#[allow(clippy::too_many_arguments)]
+pub fn sign_key_rotation_entry(
+    old_key: &SigningKey,
+    new_key: &SigningKey,
+    new_jwk: &Value,
+    collection: &str,
+    record_id: &str,
+    author_old_did: &str,
+    author_new_did: &str,
+    timestamp: u64,
+    prev_entry: Option<&EditEntry>,
+) -> Result<EditEntry, CryptoError> {
+    let k = export_public_key_jwk(old_key.verifying_key());
+    if encode_did_key_from_jwk(&k)? != author_old_did {
+        return Err(CryptoError::InvalidJwk(
+            "old key does not match author_old_did".to_string(),
+        ));
+    }
+    let new_public = export_public_key_jwk(new_key.verifying_key());
+    let mut new_canonical = canonicalize_jwk(new_jwk)?;
+    if let Some(members) = new_canonical.as_object_mut() {
+        members.remove("d");
+    }
+    if new_canonical != new_public {
+        return Err(CryptoError::InvalidJwk(
+            "new_jwk does not match new key".to_string(),
+        ));
+    }
+    if encode_did_key_from_jwk(&new_public)? != author_new_did {
+        return Err(CryptoError::InvalidJwk(
+            "new key does not match author_new_did".to_string(),
+        ));
+    }
+    if author_new_did == author_old_did {
+        return Err(CryptoError::InvalidJwk(
+            "rotation must change the key".to_string(),
+        ));
+    }
+
+    let mut prev_hash: Option<String> = None;
+    let mut t = timestamp;
+
+    if let Some(prev) = prev_entry {
+        prev_hash = Some(uint8_to_hex(&sha256_hash(&prev.s)));
+        t = t.max(prev.t + 1);
+    }
+
+    let message = build_key_rotation_signing_message(
+        collection,
+        record_id,
+        author_old_did,
+        author_new_did,
+        t,
+        prev_hash.as_deref(),
+    );
+    let s = sign(old_key, &message)?;
+
+    Ok(EditEntry {
+        a: author_old_did.to_string(),
+        t,
+        d: vec![],
+        p: prev_hash,
+        s,
+        k,
+        r: Some(author_new_did.to_string()),
     })
 }
 
+/// The message `entry.s` must sign, or `None` if the entry is malformed.
+fn entry_signing_message(entry: &EditEntry, collection: &str, record_id: &str) -> Option<Vec<u8>> {
+    if let Some(new_did) = &entry.r {
+        // A rotation changes nothing but the author's key
+        if !entry.d.is_empty() || *new_did == entry.a || did_key_algorithm(new_did).is_err() {
+            return None;
+        }
+        return Some(build_key_rotation_signing_message(
+            collection,
+            record_id,
+            &entry.a,
+            new_did,
+            entry.t,
+            entry.p.as_deref(),
+        ));
+    }
+
+    Some(build_edit_signing_message(
+        collection,
+        record_id,
+        &entry.a,
+        entry.t,
+        entry.p.as_deref(),
+        &entry.d,
+    ))
+}
+
 /// Verify a single edit entry's signature and DID/key consistency.
 pub fn verify_edit_entry(entry: &EditEntry, collection: &str, record_id: &str) -> bool {
     // Check that entry.k encodes to entry.a
@@ -214,30 +361,68 @@ pub fn verify_edit_entry(entry: &EditEntry, collection: &str, record_id: &str) -
         return false;
     }
 
-    let message = build_edit_signing_message(
-        collection,
-        record_id,
-        &entry.a,
-        entry.t,
-        entry.p.as_deref(),
-        &entry.d,
-    );
-    verify(&entry.k, &message, &entry.s)
+    match entry_signing_message(entry, collection, record_id) {
+        Some(message) => verify_with_jwk(&entry.k, &message, &entry.s),
+        None => false,
+    }
 }
 
 /// Verify the entire chain: all signatures + hash linkage.
 pub fn verify_edit_chain(entries: &[EditEntry], collection: &str, record_id: &str) -> bool {
-    if entries.is_empty() {
-        return true;
+    if entries.first().is_some_and(|e| e.p.is_some()) {
+        return false;
     }
-    if entries[0].p.is_some() {
+    verify_edit_chain_tail(entries, collection, record_id)
+}
+
+/// [`verify_edit_chain`], additionally requiring every entry to be authored
+/// by one of `authors` or by a did:key that a rotation entry earlier in the
+/// chain vouched for.
+///
+/// Use this when the trusted authors are known (e.g. a space's members), so
+/// a rotated-to key is trusted only through its rotation entry.
+pub fn verify_edit_chain_for_authors(
+    entries: &[EditEntry],
+    collection: &str,
+    record_id: &str,
+    authors: &[&str],
+) -> bool {
+    if !verify_edit_chain(entries, collection, record_id) {
         return false;
     }
+    let mut trusted: HashSet<&str> = authors.iter().copied().collect();
+    for entry in entries {
+        if !trusted.contains(entry.a.as_str()) {
+            return false;
+        }
+        if let Some(new_did) = &entry.r {
+            trusted.insert(new_did.as_str());
+        }
+    }
+    true
+}
 
+/// Verify the newest entries of a chain whose head was trimmed (e.g. by
+/// local compaction): every signature and every link within `entries`, but
+/// the first entry may point at an entry that is no longer present.
+///
+/// Once a key rotation entry retires a did:key, no later entry may be
+/// authored by it or rotate back to it.
+pub fn verify_edit_chain_tail(entries: &[EditEntry], collection: &str, record_id: &str) -> bool {
+    let mut retired: HashSet<&str> = HashSet::new();
     for i in 0..entries.len() {
         if !verify_edit_entry(&entries[i], collection, record_id) {
             return false;
         }
+        if retired.contains(entries[i].a.as_str()) {
+            return false;
+        }
+        if let Some(new_did) = &entries[i].r {
+            if retired.contains(new_did.as_str()) {
+                return false;
+            }
+            retired.insert(entries[i].a.as_str());
+        }
 
         if i > 0 {
             let expected_hash = uint8_to_hex(&sha256_hash(&entries[i - 1].s));
@@ -250,12 +435,98 @@ pub fn verify_edit_chain(entries: &[EditEntry], collection: &str, record_id: &st
     true
 }
 
+/// Same result as [`verify_edit_chain`], with signatures grouped by signer.
+///
+/// Links, the no-`p` first entry and key retirement are checked in order as
+/// in [`verify_edit_chain`]. Signatures are then verified per signer JWK,
+/// so each distinct key is imported and encoded to its did:key once rather
+/// than once per entry. Neither curve is batch-verified: p256 has no batch
+/// ECDSA verification, and batched Ed25519 accepts a different set of
+/// signatures than the strict check used for single entries.
+pub fn verify_edit_chain_batch(entries: &[EditEntry], collection: &str, record_id: &str) -> bool {
+    if entries.first().is_some_and(|e| e.p.is_some()) {
+        return false;
+    }
+
+    let mut retired: HashSet<&str> = HashSet::new();
+    let mut messages = Vec::with_capacity(entries.len());
+    // Canonical JSON of the signer JWK -> indexes of the entries it signed
+    let mut by_signer: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let Some(message) = entry_signing_message(entry, collection, record_id) else {
+            return false;
+        };
+        messages.push(message);
+
+        if i > 0 {
+            let expected_hash = uint8_to_hex(&sha256_hash(&entries[i - 1].s));
+            if entry.p.as_deref() != Some(&expected_hash) {
+                return false;
+            }
+        }
+
+        if retired.contains(entry.a.as_str()) {
+            return false;
+        }
+        if let Some(new_did) = &entry.r {
+            if retired.contains(new_did.as_str()) {
+                return false;
+            }
+            retired.insert(entry.a.as_str());
+        }
+
+        let Ok(signer) = canonical_json(&entry.k) else {
+            return false;
+        };
+        by_signer.entry(signer).or_default().push(i);
+    }
+
+    by_signer.values().all(|indexes| {
+        let jwk = &entries[indexes[0]].k;
+        let Ok(derived_did) = encode_did_key_from_jwk(jwk) else {
+            return false;
+        };
+        if indexes.iter().any(|&i| entries[i].a != derived_did) {
+            return false;
+        }
+        let items: Vec<(&[u8], &[u8])> = indexes
+            .iter()
+            .map(|&i| (messages[i].as_slice(), entries[i].s.as_slice()))
+            .collect();
+        verify_batch_with_jwk(jwk, &items)
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Diff
 // ---------------------------------------------------------------------------
 
 /// Compute diffs between two plain-object views at the shallowest changed path.
 pub fn value_diff(old_view: &Value, new_view: &Value, prefix: Option<&str>) -> Vec<EditDiff> {
+    diff_views(old_view, new_view, prefix, false)
+}
+
+/// [`value_diff`] with array element granularity.
+///
+/// A changed array whose length is unchanged emits one diff per changed
+/// element at `path.<index>`, recursing into elements that are objects on
+/// both sides (`tags.0.name`). A length change still replaces the whole
+/// array at its path. [`reconstruct_state`] resolves index segments against
+/// arrays, so these diffs fold to the same state as the coarse ones.
+pub fn value_diff_granular(
+    old_view: &Value,
+    new_view: &Value,
+    prefix: Option<&str>,
+) -> Vec<EditDiff> {
+    diff_views(old_view, new_view, prefix, true)
+}
+
+fn diff_views(
+    old_view: &Value,
+    new_view: &Value,
+    prefix: Option<&str>,
+    granular: bool,
+) -> Vec<EditDiff> {
     let old_obj = match old_view.as_object() {
         Some(o) => o,
         None => return vec![],
@@ -293,10 +564,19 @@ pub fn value_diff(old_view: &Value, new_view: &Value, prefix: Option<&str>) -> V
             && !old_val.is_array()
             && !new_v.is_array()
         {
-            diffs.extend(value_diff(old_val, new_v, Some(&path)));
+            diffs.extend(diff_views(old_val, new_v, Some(&path), granular));
             continue;
         }
 
+        if granular && !is_deleted {
+            if let (Some(old_items), Some(new_items)) = (old_val.as_array(), new_v.as_array()) {
+                if old_items.len() == new_items.len() {
+                    diff_array_elements(old_items, new_items, &path, &mut diffs);
+                    continue;
+                }
+            }
+        }
+
         // Arrays or primitives: emit at this path
         if canonical_json_value(old_val) != canonical_json_value(new_v) || is_deleted {
             let from = if old_obj.contains_key(key) {
@@ -321,6 +601,37 @@ pub fn value_diff(old_view: &Value, new_view: &Value, prefix: Option<&str>) -> V
     diffs
 }
 
+/// Element-wise diffs between two equal-length arrays at `path`.
+fn diff_array_elements(
+    old_items: &[Value],
+    new_items: &[Value],
+    path: &str,
+    diffs: &mut Vec<EditDiff>,
+) {
+    for (i, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+        if canonical_json_value(old_item) == canonical_json_value(new_item) {
+            continue;
+        }
+        let item_path = format!("{}.{}", path, i);
+        match (old_item, new_item) {
+            (Value::Object(_), Value::Object(_)) => {
+                diffs.extend(diff_views(old_item, new_item, Some(&item_path), true));
+            }
+            (Value::Array(old_inner), Value::Array(new_inner))
+                if old_inner.len() == new_inner.len() =>
+            {
+                diff_array_elements(old_inner, new_inner, &item_path, diffs);
+            }
+            _ => diffs.push(EditDiff {
+                path: item_path,
+                from: old_item.clone(),
+                to: new_item.clone(),
+                del: None,
+            }),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Serialization
 // ---------------------------------------------------------------------------
@@ -333,6 +644,8 @@ struct SerializedEditEntry {
     p: Option<String>,
     s: String, // base64url
     k: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    r: Option<String>,
 }
 
 /// Serialize an edit chain to a JSON string for storage in BlobEnvelope.h.
@@ -346,6 +659,7 @@ pub fn serialize_edit_chain(entries: &[EditEntry]) -> String {
             p: e.p.clone(),
             s: base64url_encode(&e.s),
             k: e.k.clone(),
+            r: e.r.clone(),
         })
         .collect();
     serde_json::to_string(&serialized).unwrap()
@@ -368,6 +682,7 @@ pub fn parse_edit_chain(serialized: &str) -> Result<Vec<EditEntry>, CryptoError>
                 p: e.p,
                 s,
                 k: e.k,
+                r: e.r,
             })
         })
         .collect()
@@ -389,16 +704,34 @@ fn assert_safe_path(parts: &[&str]) -> Result<(), CryptoError> {
     Ok(())
 }
 
-fn navigate_to_parent<'a>(
-    obj: &'a mut serde_json::Map<String, Value>,
-    parts: &[&str],
-) -> &'a mut serde_json::Map<String, Value> {
-    let mut current = obj;
+/// The index `segment` names in `value`, if `value` is an array and the
+/// segment is a canonical in-range index. Anything else is an object key.
+fn array_index(value: &Value, segment: &str) -> Option<usize> {
+    let len = value.as_array()?.len();
+    let i: usize = segment.parse().ok()?;
+    (i < len && i.to_string() == segment).then_some(i)
+}
+
+/// `value` as an object, replacing any non-object with `{}`.
+fn object_or_reset(value: &mut Value) -> &mut serde_json::Map<String, Value> {
+    if !value.is_object() {
+        *value = serde_json::json!({});
+    }
+    value.as_object_mut().unwrap()
+}
+
+/// Walk to the container holding the last path segment, creating objects
+/// for missing or non-object intermediates. Arrays are walked through only
+/// at an index segment.
+fn navigate_to_parent<'a>(root: &'a mut Value, parts: &[&str]) -> &'a mut Value {
+    let mut current = root;
     for &key in &parts[..parts.len() - 1] {
-        if !current.contains_key(key) || !current[key].is_object() || current[key].is_null() {
-            current.insert(key.to_string(), serde_json::json!({}));
-        }
-        current = current.get_mut(key).unwrap().as_object_mut().unwrap();
+        current = match array_index(current, key) {
+            Some(i) => &mut current.as_array_mut().unwrap()[i],
+            None => object_or_reset(current)
+                .entry(key)
+                .or_insert_with(|| serde_json::json!({})),
+        };
     }
     current
 }
@@ -410,8 +743,16 @@ fn set_nested_path(
 ) -> Result<(), CryptoError> {
     let parts: Vec<&str> = path.split('.').collect();
     assert_safe_path(&parts)?;
-    let parent = navigate_to_parent(obj, &parts);
-    parent.insert(parts[parts.len() - 1].to_string(), value);
+    let mut root = Value::Object(std::mem::take(obj));
+    let parent = navigate_to_parent(&mut root, &parts);
+    let last = parts[parts.len() - 1];
+    match array_index(parent, last) {
+        Some(i) => parent.as_array_mut().unwrap()[i] = value,
+        None => {
+            object_or_reset(parent).insert(last.to_string(), value);
+        }
+    }
+    *obj = std::mem::take(object_or_reset(&mut root));
     Ok(())
 }
 
@@ -421,8 +762,10 @@ fn delete_nested_path(
 ) -> Result<(), CryptoError> {
     let parts: Vec<&str> = path.split('.').collect();
     assert_safe_path(&parts)?;
-    let parent = navigate_to_parent(obj, &parts);
-    parent.remove(parts[parts.len() - 1]);
+    let mut root = Value::Object(std::mem::take(obj));
+    let parent = navigate_to_parent(&mut root, &parts);
+    object_or_reset(parent).remove(parts[parts.len() - 1]);
+    *obj = std::mem::take(object_or_reset(&mut root));
     Ok(())
 }
 
@@ -455,10 +798,99 @@ pub fn reconstruct_state(entries: &[EditEntry], up_to_index: usize) -> Result<Va
     Ok(Value::Object(state))
 }
 
+/// Hex SHA-256 of the canonical JSON of the state reconstructed at
+/// `up_to_index`. This is the value to store next to a blob so readers can
+/// check it with [`chain_state_matches`].
+pub fn chain_state_hash(entries: &[EditEntry], up_to_index: usize) -> Result<String, CryptoError> {
+    let state = reconstruct_state(entries, up_to_index)?;
+    let canonical = canonical_json(&state)?;
+    Ok(uint8_to_hex(&sha256_hash(canonical.as_bytes())))
+}
+
+/// Check that the chain reconstructs, at `up_to_index`, to the state whose
+/// hash is `expected_state_hash` (hex, case-insensitive).
+///
+/// Signatures and hash links only prove each entry is authentic and in
+/// order; this catches a chain that still verifies but was cut short or
+/// swapped for another valid chain, and so folds to a different state.
+pub fn chain_state_matches(
+    entries: &[EditEntry],
+    up_to_index: usize,
+    expected_state_hash: &str,
+) -> Result<bool, CryptoError> {
+    let actual = chain_state_hash(entries, up_to_index)?;
+    Ok(actual.eq_ignore_ascii_case(expected_state_hash))
+}
+
+// ---------------------------------------------------------------------------
+// Merge
+// ---------------------------------------------------------------------------
+
+/// Index of the last entry two chains share, or `None` if they differ from
+/// the first entry on (or either is empty).
+///
+/// Entries are compared by signature: `p` links hash the signature, so two
+/// chains that agree on a signature agree on everything before it.
+pub fn find_common_ancestor(a: &[EditEntry], b: &[EditEntry]) -> Option<usize> {
+    a.iter()
+        .zip(b)
+        .take_while(|(x, y)| sha256_hash(&x.s) == sha256_hash(&y.s))
+        .count()
+        .checked_sub(1)
+}
+
+/// Merge `other` into `onto` by replaying the entries of `other` after
+/// their common ancestor on top of `onto`'s head.
+///
+/// Replayed entries keep their diffs and timestamps but are re-signed with
+/// `private_key` as `author`, so `t = max(t, prev.t + 1)` holds across the
+/// join. If `onto` is itself a prefix of `other`, the rest of `other`
+/// already links to it and is appended unchanged. Key rotation entries
+/// can't be re-signed by another key, so a tail containing one is rejected.
+pub fn rebase_chain(
+    onto: &[EditEntry],
+    other: &[EditEntry],
+    private_key: &SigningKey,
+    public_key_jwk: &Value,
+    collection: &str,
+    record_id: &str,
+    author: &str,
+) -> Result<Vec<EditEntry>, CryptoError> {
+    let start = find_common_ancestor(onto, other).map_or(0, |i| i + 1);
+    let mut merged = onto.to_vec();
+    if start == onto.len() {
+        merged.extend_from_slice(&other[start..]);
+        return Ok(merged);
+    }
+
+    for entry in &other[start..] {
+        if entry.r.is_some() {
+            return Err(CryptoError::InvalidEditChain(
+                "cannot rebase a key rotation entry".to_string(),
+            ));
+        }
+        let rebased = sign_edit_entry(
+            private_key,
+            public_key_jwk,
+            collection,
+            record_id,
+            author,
+            entry.t,
+            entry.d.clone(),
+            merged.last(),
+        )?;
+        merged.push(rebased);
+    }
+    Ok(merged)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::signing::{export_public_key_jwk, generate_p256_keypair};
+    use crate::signing::{
+        export_ed25519_public_key_jwk, export_public_key_jwk, generate_ed25519_keypair,
+        generate_p256_keypair, sign_ed25519,
+    };
     use crate::ucan::encode_did_key;
 
     const COLLECTION: &str = "test";
@@ -529,6 +961,258 @@ mod tests {
         assert!(verify_edit_entry(&entry, COLLECTION, RECORD_ID));
     }
 
+    #[test]
+    fn ed25519_entry_verifies() {
+        let key = generate_ed25519_keypair();
+        let jwk = export_ed25519_public_key_jwk(&key.verifying_key());
+        let did = encode_did_key_from_jwk(&jwk).unwrap();
+        assert!(did.starts_with("did:key:z6Mk"));
+
+        let diffs = vec![EditDiff {
+            path: "name".to_string(),
+            from: Value::Null,
+            to: serde_json::json!("Alice"),
+            del: None,
+        }];
+        let message = build_edit_signing_message(COLLECTION, RECORD_ID, &did, 1000, None, &diffs);
+        let mut entry = EditEntry {
+            a: did,
+            t: 1000,
+            d: diffs,
+            p: None,
+            s: sign_ed25519(&key, &message).unwrap(),
+            k: jwk,
+            r: None,
+        };
+        assert!(verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+        assert!(verify_edit_chain(
+            std::slice::from_ref(&entry),
+            COLLECTION,
+            RECORD_ID
+        ));
+
+        // An Ed25519 signature must not pass as P-256 or vice versa
+        entry.k["crv"] = serde_json::json!("P-256");
+        assert!(!verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+
+        entry.k["crv"] = serde_json::json!("Ed25519");
+        entry.t += 1;
+        assert!(!verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+    }
+
+    #[test]
+    fn mixed_author_chain_verifies() {
+        let alice = generate_p256_keypair();
+        let bob = generate_ed25519_keypair();
+        let bob_jwk = export_ed25519_public_key_jwk(&bob.verifying_key());
+        let bob_did = encode_did_key_from_jwk(&bob_jwk).unwrap();
+
+        let first = set_entry(&alice, 1000, "name", serde_json::json!("Alice"), None);
+
+        let prev_hash = uint8_to_hex(&sha256_hash(&first.s));
+        let diffs = vec![EditDiff {
+            path: "name".to_string(),
+            from: serde_json::json!("Alice"),
+            to: serde_json::json!("Bob"),
+            del: None,
+        }];
+        let message = build_edit_signing_message(
+            COLLECTION,
+            RECORD_ID,
+            &bob_did,
+            2000,
+            Some(&prev_hash),
+            &diffs,
+        );
+        let second = EditEntry {
+            a: bob_did,
+            t: 2000,
+            d: diffs,
+            p: Some(prev_hash),
+            s: sign_ed25519(&bob, &message).unwrap(),
+            k: bob_jwk,
+            r: None,
+        };
+
+        let third = set_entry(&alice, 3000, "age", serde_json::json!(30), Some(&second));
+
+        let chain = vec![first, second, third];
+        assert!(verify_edit_chain(&chain, COLLECTION, RECORD_ID));
+        assert!(verify_edit_chain_batch(&chain, COLLECTION, RECORD_ID));
+
+        let mut tampered = chain.clone();
+        tampered[1].s[0] ^= 0x01;
+        assert!(!verify_edit_chain(&tampered, COLLECTION, RECORD_ID));
+        assert!(!verify_edit_chain_batch(&tampered, COLLECTION, RECORD_ID));
+    }
+
+    #[test]
+    fn p256_entry_round_trips_through_serialization() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+        assert_eq!(encode_did_key_from_jwk(&jwk).unwrap(), did);
+
+        let entry =
+            sign_edit_entry(&key, &jwk, COLLECTION, RECORD_ID, &did, 1000, vec![], None).unwrap();
+        let parsed = parse_edit_chain(&serialize_edit_chain(std::slice::from_ref(&entry))).unwrap();
+        assert_eq!(parsed[0].s, entry.s);
+        assert_eq!(parsed[0].k, entry.k);
+        assert!(verify_edit_chain(&parsed, COLLECTION, RECORD_ID));
+    }
+
+    #[test]
+    fn key_rotation_vouches_for_new_key() {
+        let old_key = generate_p256_keypair();
+        let old_jwk = export_public_key_jwk(old_key.verifying_key());
+        let old_did = encode_did_key(&old_key).unwrap();
+        let new_key = generate_p256_keypair();
+        let new_jwk = export_public_key_jwk(new_key.verifying_key());
+        let new_did = encode_did_key(&new_key).unwrap();
+
+        let diff = |to: i64| EditDiff {
+            path: "n".to_string(),
+            from: Value::Null,
+            to: serde_json::json!(to),
+            del: None,
+        };
+
+        let e1 = sign_edit_entry(
+            &old_key,
+            &old_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &old_did,
+            1000,
+            vec![diff(1)],
+            None,
+        )
+        .unwrap();
+        let rotation = sign_key_rotation_entry(
+            &old_key,
+            &new_key,
+            &new_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &old_did,
+            &new_did,
+            1000,
+            Some(&e1),
+        )
+        .unwrap();
+        assert_eq!(rotation.a, old_did);
+        assert_eq!(rotation.r.as_deref(), Some(new_did.as_str()));
+        assert_eq!(rotation.t, 1001);
+        assert!(verify_edit_entry(&rotation, COLLECTION, RECORD_ID));
+
+        let e3 = sign_edit_entry(
+            &new_key,
+            &new_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &new_did,
+            2000,
+            vec![diff(3)],
+            Some(&rotation),
+        )
+        .unwrap();
+        let chain = vec![e1.clone(), rotation.clone(), e3.clone()];
+        assert!(verify_edit_chain(&chain, COLLECTION, RECORD_ID));
+        assert!(verify_edit_chain_for_authors(
+            &chain,
+            COLLECTION,
+            RECORD_ID,
+            &[&old_did]
+        ));
+        assert_eq!(
+            reconstruct_state(&chain, 2).unwrap(),
+            serde_json::json!({"n": 3})
+        );
+
+        // Without the rotation, nothing vouches for the new key
+        let unvouched = sign_edit_entry(
+            &new_key,
+            &new_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &new_did,
+            2000,
+            vec![diff(3)],
+            Some(&e1),
+        )
+        .unwrap();
+        assert!(!verify_edit_chain_for_authors(
+            &[e1.clone(), unvouched],
+            COLLECTION,
+            RECORD_ID,
+            &[&old_did]
+        ));
+
+        // The rotation survives serialization
+        let parsed = parse_edit_chain(&serialize_edit_chain(&chain)).unwrap();
+        assert_eq!(parsed[1].r, rotation.r);
+        assert!(verify_edit_chain(&parsed, COLLECTION, RECORD_ID));
+
+        // The old key is retired after the rotation
+        let stale = sign_edit_entry(
+            &old_key,
+            &old_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &old_did,
+            2000,
+            vec![diff(3)],
+            Some(&rotation),
+        )
+        .unwrap();
+        assert!(verify_edit_entry(&stale, COLLECTION, RECORD_ID));
+        assert!(!verify_edit_chain(
+            &[e1.clone(), rotation.clone(), stale],
+            COLLECTION,
+            RECORD_ID
+        ));
+
+        // The new DID is covered by the old key's signature
+        let mut forged = rotation;
+        forged.r = Some(encode_did_key(&generate_p256_keypair()).unwrap());
+        assert!(!verify_edit_entry(&forged, COLLECTION, RECORD_ID));
+    }
+
+    #[test]
+    fn key_rotation_rejects_mismatched_keys() {
+        let old_key = generate_p256_keypair();
+        let old_did = encode_did_key(&old_key).unwrap();
+        let new_key = generate_p256_keypair();
+        let new_jwk = export_public_key_jwk(new_key.verifying_key());
+        let new_did = encode_did_key(&new_key).unwrap();
+        let other_jwk = export_public_key_jwk(generate_p256_keypair().verifying_key());
+
+        let rotate = |old_did: &str, new_jwk: &Value, new_did: &str| {
+            sign_key_rotation_entry(
+                &old_key, &new_key, new_jwk, COLLECTION, RECORD_ID, old_did, new_did, 1000, None,
+            )
+        };
+        assert!(rotate(&old_did, &new_jwk, &new_did).is_ok());
+        assert!(rotate(&new_did, &new_jwk, &new_did).is_err());
+        assert!(rotate(&old_did, &other_jwk, &new_did).is_err());
+        assert!(rotate(&old_did, &new_jwk, &old_did).is_err());
+    }
+
+    #[test]
+    fn stores_canonical_public_jwk() {
+        let key = generate_p256_keypair();
+        let mut jwk = crate::signing::export_private_key_jwk(&key);
+        jwk["kid"] = serde_json::json!("device-1");
+        jwk["alg"] = serde_json::json!("ES256");
+        let did = encode_did_key(&key).unwrap();
+
+        let entry =
+            sign_edit_entry(&key, &jwk, COLLECTION, RECORD_ID, &did, 1000, vec![], None).unwrap();
+
+        assert_eq!(entry.k, export_public_key_jwk(key.verifying_key()));
+        assert!(verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+    }
+
     #[test]
     fn rejects_did_mismatch() {
         let key = generate_p256_keypair();
@@ -588,6 +1272,24 @@ mod tests {
         assert!(!verify_edit_entry(&entry, COLLECTION, RECORD_ID));
     }
 
+    #[test]
+    fn rejects_high_s_signature() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+
+        let mut entry =
+            sign_edit_entry(&key, &jwk, COLLECTION, RECORD_ID, &did, 1000, vec![], None).unwrap();
+        assert!(verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+
+        // (r, n - s) is valid ECDSA but would give the entry a second hash
+        let signature = p256::ecdsa::Signature::from_slice(&entry.s).unwrap();
+        let (r, s) = signature.split_scalars();
+        let flipped = p256::ecdsa::Signature::from_scalars(r, -s).unwrap();
+        entry.s = flipped.to_bytes().to_vec();
+        assert!(!verify_edit_entry(&entry, COLLECTION, RECORD_ID));
+    }
+
     #[test]
     fn timestamp_monotonicity() {
         let key = generate_p256_keypair();
@@ -696,6 +1398,73 @@ mod tests {
         assert!(verify_edit_chain(&[e1, e2, e3], COLLECTION, RECORD_ID));
     }
 
+    #[test]
+    fn batch_verification_matches_sequential() {
+        let alice = generate_p256_keypair();
+        let alice_jwk = export_public_key_jwk(alice.verifying_key());
+        let alice_did = encode_did_key(&alice).unwrap();
+        let bob = generate_p256_keypair();
+        let bob_jwk = export_public_key_jwk(bob.verifying_key());
+        let bob_did = encode_did_key(&bob).unwrap();
+
+        let mut chain: Vec<EditEntry> = Vec::with_capacity(200);
+        for i in 0..200u64 {
+            let (key, jwk, did) = if i % 3 == 0 {
+                (&bob, &bob_jwk, &bob_did)
+            } else {
+                (&alice, &alice_jwk, &alice_did)
+            };
+            let diffs = vec![EditDiff {
+                path: "n".to_string(),
+                from: serde_json::json!(i),
+                to: serde_json::json!(i + 1),
+                del: None,
+            }];
+            let entry = sign_edit_entry(
+                key,
+                jwk,
+                COLLECTION,
+                RECORD_ID,
+                did,
+                1000 + i,
+                diffs,
+                chain.last(),
+            )
+            .unwrap();
+            chain.push(entry);
+        }
+
+        let check = |entries: &[EditEntry]| {
+            let sequential = verify_edit_chain(entries, COLLECTION, RECORD_ID);
+            let batch = verify_edit_chain_batch(entries, COLLECTION, RECORD_ID);
+            assert_eq!(batch, sequential);
+            batch
+        };
+
+        assert!(check(&chain));
+        assert!(check(&[]));
+
+        let mut bad_signature = chain.clone();
+        bad_signature[150].s[10] ^= 0x01;
+        assert!(!check(&bad_signature));
+
+        let mut bad_diff = chain.clone();
+        bad_diff[99].d[0].to = serde_json::json!("tampered");
+        assert!(!check(&bad_diff));
+
+        let mut bad_author = chain.clone();
+        bad_author[43].a = bob_did.clone();
+        assert!(!check(&bad_author));
+
+        let mut swapped = chain.clone();
+        swapped.swap(10, 11);
+        assert!(!check(&swapped));
+
+        // A tail verifies only through verify_edit_chain_tail
+        assert!(!check(&chain[1..]));
+        assert!(verify_edit_chain_tail(&chain[1..], COLLECTION, RECORD_ID));
+    }
+
     #[test]
     fn empty_chain_valid() {
         assert!(verify_edit_chain(&[], COLLECTION, RECORD_ID));
@@ -744,6 +1513,43 @@ mod tests {
         assert!(!verify_edit_chain(&[e2], COLLECTION, RECORD_ID));
     }
 
+    #[test]
+    fn trimmed_tail_verifies_only_as_tail() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+
+        let mut chain: Vec<EditEntry> = Vec::new();
+        for i in 0..4 {
+            let entry = sign_edit_entry(
+                &key,
+                &jwk,
+                COLLECTION,
+                RECORD_ID,
+                &did,
+                1000 * (i + 1),
+                vec![EditDiff {
+                    path: "x".to_string(),
+                    from: serde_json::json!(i),
+                    to: serde_json::json!(i + 1),
+                    del: None,
+                }],
+                chain.last(),
+            )
+            .unwrap();
+            chain.push(entry);
+        }
+
+        let tail = &chain[2..];
+        assert!(verify_edit_chain_tail(tail, COLLECTION, RECORD_ID));
+        assert!(!verify_edit_chain(tail, COLLECTION, RECORD_ID));
+        assert!(verify_edit_chain_tail(&chain, COLLECTION, RECORD_ID));
+
+        // Links inside the tail are still checked
+        let gapped = [chain[1].clone(), chain[3].clone()];
+        assert!(!verify_edit_chain_tail(&gapped, COLLECTION, RECORD_ID));
+    }
+
     #[test]
     fn detects_swapped_order() {
         let key = generate_p256_keypair();
@@ -865,6 +1671,112 @@ mod tests {
         assert_eq!(diffs[0].path, "tags");
     }
 
+    #[test]
+    fn value_diff_granular_replaces_elements() {
+        let old = serde_json::json!({"tags": ["a", "b", "c"], "name": "x"});
+        let new = serde_json::json!({"tags": ["z", "b", "y"], "name": "x"});
+        let diffs = value_diff_granular(&old, &new, None);
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[0].path, "tags.0");
+        assert_eq!(diffs[0].from, serde_json::json!("a"));
+        assert_eq!(diffs[0].to, serde_json::json!("z"));
+        assert_eq!(diffs[1].path, "tags.2");
+        assert_eq!(diffs[1].to, serde_json::json!("y"));
+
+        // The default stays coarse
+        let coarse = value_diff(&old, &new, None);
+        assert_eq!(coarse.len(), 1);
+        assert_eq!(coarse[0].path, "tags");
+    }
+
+    #[test]
+    fn value_diff_granular_length_change_replaces_array() {
+        let diffs = value_diff_granular(
+            &serde_json::json!({"tags": ["a", "b"]}),
+            &serde_json::json!({"tags": ["a", "c", "d"]}),
+            None,
+        );
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].path, "tags");
+        assert_eq!(diffs[0].to, serde_json::json!(["a", "c", "d"]));
+    }
+
+    #[test]
+    fn value_diff_granular_recurses_into_object_elements() {
+        let old = serde_json::json!({"tags": [{"name": "a", "n": 1}, {"name": "b"}]});
+        let new = serde_json::json!({"tags": [{"name": "z", "n": 1}, {"name": "b", "x": true}]});
+        let diffs = value_diff_granular(&old, &new, None);
+        let paths: Vec<&str> = diffs.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, ["tags.0.name", "tags.1.x"]);
+    }
+
+    #[test]
+    fn value_diff_granular_folds_to_new_state() {
+        let old = serde_json::json!({
+            "tags": [{"name": "a", "drop": 1}, "b", [1, 2]],
+            "meta": {"list": [1, 2, 3]},
+        });
+        let new = serde_json::json!({
+            "tags": [{"name": "z"}, "c", [1, 3]],
+            "meta": {"list": [1, 2, 4]},
+        });
+        let key = generate_p256_keypair();
+        let first = set_entry(&key, 1000, "tags", old["tags"].clone(), None);
+        let second = set_entry(&key, 2000, "meta", old["meta"].clone(), Some(&first));
+        assert_eq!(
+            reconstruct_state(&[first.clone(), second.clone()], 1).unwrap(),
+            old
+        );
+
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+        let diffs = value_diff_granular(&old, &new, None);
+        assert!(diffs
+            .iter()
+            .any(|d| d.path == "tags.0.drop" && d.del == Some(true)));
+        let third = sign_edit_entry(
+            &key,
+            &jwk,
+            COLLECTION,
+            RECORD_ID,
+            &did,
+            3000,
+            diffs,
+            Some(&second),
+        )
+        .unwrap();
+        assert_eq!(reconstruct_state(&[first, second, third], 2).unwrap(), new);
+    }
+
+    #[test]
+    fn reconstruct_state_treats_non_index_segments_as_keys() {
+        let stub = |path: &str, to: Value| EditEntry {
+            a: String::new(),
+            t: 0,
+            d: vec![EditDiff {
+                path: path.to_string(),
+                from: Value::Null,
+                to,
+                del: None,
+            }],
+            p: None,
+            s: vec![],
+            k: Value::Null,
+            r: None,
+        };
+        let entries = [
+            stub("tags", serde_json::json!(["a", "b"])),
+            stub("tags.1", serde_json::json!("c")),
+            stub("other", serde_json::json!(["a"])),
+            // Out of range: the array is replaced by an object, as before
+            stub("other.5", serde_json::json!("x")),
+        ];
+        assert_eq!(
+            reconstruct_state(&entries, 3).unwrap(),
+            serde_json::json!({"tags": ["a", "c"], "other": {"5": "x"}})
+        );
+    }
+
     #[test]
     fn value_diff_identical() {
         let diffs = value_diff(
@@ -1175,6 +2087,7 @@ mod tests {
             p: None,
             s: vec![0u8; 64],
             k: Value::Null,
+            r: None,
         };
         assert!(reconstruct_state(&[stub], 0).is_err());
     }
@@ -1295,6 +2208,7 @@ mod tests {
             p: None,
             s: vec![0u8; 64],
             k: Value::Null,
+            r: None,
         };
         assert_eq!(
             reconstruct_state(&[stub], 0).unwrap(),
@@ -1402,4 +2316,200 @@ mod tests {
         .unwrap();
         assert_eq!(result, r#"{"a":4,"z":{"a":3,"b":{"c":2,"d":1}}}"#);
     }
+
+    fn set_entry(
+        key: &SigningKey,
+        t: u64,
+        path: &str,
+        to: Value,
+        prev: Option<&EditEntry>,
+    ) -> EditEntry {
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(key).unwrap();
+        let diffs = vec![EditDiff {
+            path: path.to_string(),
+            from: Value::Null,
+            to,
+            del: None,
+        }];
+        sign_edit_entry(key, &jwk, COLLECTION, RECORD_ID, &did, t, diffs, prev).unwrap()
+    }
+
+    /// `values.len()` entries setting `path`, one second apart from `t0`.
+    fn extend_chain(
+        key: &SigningKey,
+        chain: &[EditEntry],
+        t0: u64,
+        path: &str,
+        values: &[i64],
+    ) -> Vec<EditEntry> {
+        let mut chain = chain.to_vec();
+        for (i, v) in values.iter().enumerate() {
+            let entry = set_entry(
+                key,
+                t0 + 1000 * i as u64,
+                path,
+                serde_json::json!(v),
+                chain.last(),
+            );
+            chain.push(entry);
+        }
+        chain
+    }
+
+    #[test]
+    fn common_ancestor_of_identical_chains_is_head() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+        let chain = extend_chain(&key, &[], 1000, "x", &[1, 2, 3]);
+
+        assert_eq!(find_common_ancestor(&chain, &chain), Some(2));
+        assert_eq!(find_common_ancestor(&chain, &[]), None);
+
+        let merged = rebase_chain(&chain, &chain, &key, &jwk, COLLECTION, RECORD_ID, &did).unwrap();
+        assert_eq!(serialize_edit_chain(&merged), serialize_edit_chain(&chain));
+
+        // A chain that only runs ahead is fast-forwarded, not re-signed
+        let merged =
+            rebase_chain(&chain[..1], &chain, &key, &jwk, COLLECTION, RECORD_ID, &did).unwrap();
+        assert_eq!(serialize_edit_chain(&merged), serialize_edit_chain(&chain));
+    }
+
+    #[test]
+    fn rebase_fully_divergent_chains() {
+        let alice = generate_p256_keypair();
+        let alice_jwk = export_public_key_jwk(alice.verifying_key());
+        let alice_did = encode_did_key(&alice).unwrap();
+        let bob = generate_p256_keypair();
+
+        let ours = extend_chain(&alice, &[], 5000, "a", &[1, 2]);
+        let theirs = extend_chain(&bob, &[], 1000, "b", &[10, 20, 30]);
+        assert_eq!(find_common_ancestor(&ours, &theirs), None);
+
+        let merged = rebase_chain(
+            &ours, &theirs, &alice, &alice_jwk, COLLECTION, RECORD_ID, &alice_did,
+        )
+        .unwrap();
+        assert_eq!(merged.len(), 5);
+        assert!(verify_edit_chain(&merged, COLLECTION, RECORD_ID));
+        assert!(merged[2..].iter().all(|e| e.a == alice_did));
+        assert!(merged.windows(2).all(|w| w[1].t > w[0].t));
+        // Their timestamps predate our head, so they are bumped past it
+        assert_eq!(merged[2].t, merged[1].t + 1);
+        assert_eq!(
+            reconstruct_state(&merged, 4).unwrap(),
+            serde_json::json!({"a": 2, "b": 30})
+        );
+    }
+
+    #[test]
+    fn rebase_onto_partial_common_prefix() {
+        let alice = generate_p256_keypair();
+        let alice_jwk = export_public_key_jwk(alice.verifying_key());
+        let alice_did = encode_did_key(&alice).unwrap();
+        let bob = generate_p256_keypair();
+
+        let shared = extend_chain(&alice, &[], 1000, "x", &[1, 2]);
+        let ours = extend_chain(&alice, &shared, 3000, "x", &[3]);
+        let theirs = extend_chain(&bob, &shared, 10_000, "y", &[7, 8]);
+        assert_eq!(find_common_ancestor(&ours, &theirs), Some(1));
+        assert_eq!(find_common_ancestor(&theirs, &ours), Some(1));
+
+        let merged = rebase_chain(
+            &ours, &theirs, &alice, &alice_jwk, COLLECTION, RECORD_ID, &alice_did,
+        )
+        .unwrap();
+        assert_eq!(merged.len(), 5);
+        assert_eq!(find_common_ancestor(&merged, &ours), Some(2));
+        assert!(verify_edit_chain(&merged, COLLECTION, RECORD_ID));
+        // Timestamps already past our head are kept
+        assert_eq!(merged[3].t, theirs[2].t);
+        assert_eq!(merged[4].t, theirs[3].t);
+        assert_eq!(merged[3].d, theirs[2].d);
+        assert_eq!(
+            reconstruct_state(&merged, 4).unwrap(),
+            serde_json::json!({"x": 3, "y": 8})
+        );
+    }
+
+    #[test]
+    fn rebase_rejects_key_rotation_in_tail() {
+        let old_key = generate_p256_keypair();
+        let new_key = generate_p256_keypair();
+        let new_jwk = export_public_key_jwk(new_key.verifying_key());
+        let shared = extend_chain(&old_key, &[], 1000, "x", &[1]);
+        let ours = extend_chain(&old_key, &shared, 2000, "x", &[2]);
+        let rotation = sign_key_rotation_entry(
+            &old_key,
+            &new_key,
+            &new_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &encode_did_key(&old_key).unwrap(),
+            &encode_did_key(&new_key).unwrap(),
+            2000,
+            shared.last(),
+        )
+        .unwrap();
+        let theirs = [shared[0].clone(), rotation];
+
+        let result = rebase_chain(
+            &ours,
+            &theirs,
+            &new_key,
+            &new_jwk,
+            COLLECTION,
+            RECORD_ID,
+            &encode_did_key(&new_key).unwrap(),
+        );
+        assert!(matches!(result, Err(CryptoError::InvalidEditChain(_))));
+    }
+
+    #[test]
+    fn chain_state_hash_is_sha256_of_canonical_state() {
+        let key = generate_p256_keypair();
+        let e1 = set_entry(&key, 1000, "y", serde_json::json!(10), None);
+        let e2 = set_entry(&key, 2000, "x", serde_json::json!(1), Some(&e1));
+        let entries = [e1, e2];
+
+        let expected = "ffa45b959a53eef1065db856002875d1fac14ab8f01d31ec57fc22f25cd96cc4";
+        assert_eq!(chain_state_hash(&entries, 1).unwrap(), expected);
+        assert!(chain_state_matches(&entries, 1, expected).unwrap());
+        assert!(chain_state_matches(&entries, 1, &expected.to_uppercase()).unwrap());
+        assert!(!chain_state_matches(&entries, 0, expected).unwrap());
+    }
+
+    #[test]
+    fn chain_state_mismatch_despite_valid_signatures() {
+        let key = generate_p256_keypair();
+        let e1 = set_entry(&key, 1000, "title", serde_json::json!("draft"), None);
+        let e2 = set_entry(&key, 2000, "title", serde_json::json!("final"), Some(&e1));
+        let original = [e1.clone(), e2];
+        let expected = chain_state_hash(&original, 1).unwrap();
+
+        // Same signer forks the chain after e1: every signature and link checks out
+        let forged = set_entry(
+            &key,
+            2000,
+            "title",
+            serde_json::json!("tampered"),
+            Some(&e1),
+        );
+        let altered = [e1.clone(), forged];
+        assert!(verify_edit_chain(&altered, COLLECTION, RECORD_ID));
+        assert!(!chain_state_matches(&altered, 1, &expected).unwrap());
+
+        // Truncation also verifies but folds to an older state
+        let truncated = [e1];
+        assert!(verify_edit_chain(&truncated, COLLECTION, RECORD_ID));
+        assert!(!chain_state_matches(&truncated, 1, &expected).unwrap());
+    }
+
+    #[test]
+    fn chain_state_matches_rejects_unsafe_paths() {
+        let key = generate_p256_keypair();
+        let entry = set_entry(&key, 1000, "__proto__.x", serde_json::json!(1), None);
+        assert!(chain_state_matches(&[entry], 0, "").is_err());
+    }
 }
@@ -4,6 +4,10 @@
 //!
 //! Forward-only: knowing epoch_key_N lets you derive N+1 but NOT N-1.
 //! The root key (epoch 0) is the scoped_key from OPAQUE.
+//!
+//! The chain predates [`derive_labeled`](crate::hkdf::derive_labeled) and
+//! keeps its original info strings; relabeling would orphan every record
+//! wrapped under an existing epoch key.
 
 use crate::error::CryptoError;
 use crate::hkdf::hkdf_derive;
@@ -11,8 +11,8 @@ pub enum CryptoError {
     #[error("Unsupported encryption version: {0}")]
     UnsupportedVersion(u8),
 
-    #[error("Expected v4 blob, got version {0}")]
-    ExpectedV4(u8),
+    #[error("Invalid KEK length: expected 16, 24 or 32 bytes, got {0}")]
+    InvalidKekLength(usize),
 
     #[error("Invalid wrapped DEK length: expected {expected} bytes, got {got}")]
     InvalidWrappedDekLength { expected: usize, got: usize },
@@ -20,6 +20,9 @@ pub enum CryptoError {
     #[error("Invalid DEK length: expected {expected} bytes, got {got}")]
     InvalidDekLength { expected: usize, got: usize },
 
+    #[error("Invalid DEK count: {0} DEKs overflow the draw size")]
+    InvalidDekCount(usize),
+
     #[error("Invalid epoch: must be a positive integer, got {0}")]
     InvalidEpoch(i64),
 
@@ -32,6 +35,15 @@ pub enum CryptoError {
     #[error("Decryption failed: {0}")]
     DecryptionFailed(String),
 
+    #[error("Chunk out of order: expected {expected}, got {got}")]
+    ChunkOutOfOrder { expected: u32, got: u32 },
+
+    #[error("Chunk stream ended before its final chunk")]
+    StreamTruncated,
+
+    #[error("Chunk stream continues after its final chunk")]
+    TrailingChunk,
+
     #[error("AES-KW wrap failed: {0}")]
     WrapFailed(String),
 
@@ -41,6 +53,9 @@ pub enum CryptoError {
     #[error("Signing failed: {0}")]
     SigningFailed(String),
 
+    #[error("Invalid signature encoding: {0}")]
+    InvalidSignature(String),
+
     #[error("JWK missing {0}")]
     MissingJwkField(&'static str),
 
@@ -50,6 +65,27 @@ pub enum CryptoError {
     #[error("Invalid JWK: {0}")]
     InvalidJwk(String),
 
+    #[error("Invalid edit chain: {0}")]
+    InvalidEditChain(String),
+
+    #[error("Invalid UCAN: {0}")]
+    InvalidUcan(String),
+
+    #[error("UCAN expired at {exp} (now {now})")]
+    UcanExpired { exp: u64, now: u64 },
+
+    #[error("UCAN is for space {got}, not {expected}")]
+    UcanSpaceMismatch { expected: String, got: String },
+
+    #[error("UCAN chain is rooted at {0}, which is not a trusted root")]
+    UcanUntrustedRoot(String),
+
+    #[error("UCAN grants {granted}, but {required} is required")]
+    UcanPermissionDenied {
+        granted: &'static str,
+        required: &'static str,
+    },
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
@@ -2,10 +2,33 @@
 
 use hkdf::Hkdf;
 use sha2::Sha256;
+use zeroize::Zeroize;
 
 use crate::error::CryptoError;
 use crate::types::AES_KEY_LENGTH;
 
+/// HKDF-Extract: condense `ikm` into a pseudorandom key.
+///
+/// A `None` salt is the RFC 5869 default of 32 zero bytes. Extract once and
+/// call [`hkdf_expand`] for each key when deriving several from one secret.
+pub fn hkdf_extract(salt: Option<&[u8]>, ikm: &[u8]) -> [u8; AES_KEY_LENGTH] {
+    let (prk, _) = Hkdf::<Sha256>::extract(salt, ikm);
+    prk.into()
+}
+
+/// HKDF-Expand: derive `len` bytes (at most 8160) from a PRK produced by
+/// [`hkdf_extract`].
+pub fn hkdf_expand(prk: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, CryptoError> {
+    let hk = Hkdf::<Sha256>::from_prk(prk).map_err(|_| CryptoError::InvalidKeyLength {
+        expected: AES_KEY_LENGTH,
+        got: prk.len(),
+    })?;
+    let mut okm = vec![0u8; len];
+    hk.expand(info, &mut okm)
+        .map_err(|e| CryptoError::EncryptionFailed(format!("HKDF expand failed: {}", e)))?;
+    Ok(okm)
+}
+
 /// Derive a 256-bit key using HKDF-SHA256.
 ///
 /// # Arguments
@@ -20,11 +43,34 @@ pub fn hkdf_derive(
     salt: &[u8],
     info: &[u8],
 ) -> Result<[u8; AES_KEY_LENGTH], CryptoError> {
-    let hk = Hkdf::<Sha256>::new(Some(salt), ikm);
-    let mut okm = [0u8; AES_KEY_LENGTH];
-    hk.expand(info, &mut okm)
-        .map_err(|e| CryptoError::EncryptionFailed(format!("HKDF expand failed: {}", e)))?;
-    Ok(okm)
+    let mut prk = hkdf_extract(Some(salt), ikm);
+    let okm = hkdf_expand(&prk, info, AES_KEY_LENGTH);
+    prk.zeroize();
+    let mut okm = okm?;
+    let mut key = [0u8; AES_KEY_LENGTH];
+    key.copy_from_slice(&okm);
+    okm.zeroize();
+    Ok(key)
+}
+
+/// Versioned prefix for [`derive_labeled`] info strings.
+const LABELED_INFO_PREFIX: &str = "less:hkdf:v1\0";
+
+/// Derive `length` bytes (at most 8160) for a named purpose.
+///
+/// The HKDF info is `less:hkdf:v1\0{label}`, so call sites using distinct
+/// labels can never derive the same key from the same `ikm` and `salt`.
+pub fn derive_labeled(
+    ikm: &[u8],
+    salt: &[u8],
+    label: &str,
+    length: usize,
+) -> Result<Vec<u8>, CryptoError> {
+    let info = format!("{}{}", LABELED_INFO_PREFIX, label);
+    let mut prk = hkdf_extract(Some(salt), ikm);
+    let okm = hkdf_expand(&prk, info.as_bytes(), length);
+    prk.zeroize();
+    okm
 }
 
 #[cfg(test)]
@@ -85,4 +131,80 @@ mod tests {
         let b = hkdf_derive(&[0x02u8; 32], b"salt", b"info").unwrap();
         assert_ne!(a, b);
     }
+
+    // RFC 5869 Test Case 1
+    const TC1_IKM: &str = "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b";
+    const TC1_PRK: &str = "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5";
+    const TC1_OKM: &str = "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c\
+                           5db02d56ecc4c5bf34007208d5b887185865";
+
+    // RFC 5869 Test Case 3 (zero-length salt and info)
+    const TC3_PRK: &str = "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04";
+    const TC3_OKM: &str = "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879e\
+                           c3454e5f3c738d2d9d201395faa4b61a96c8";
+
+    #[test]
+    fn rfc5869_extract_vectors() {
+        let ikm = hex::decode(TC1_IKM).unwrap();
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        assert_eq!(hex::encode(hkdf_extract(Some(&salt), &ikm)), TC1_PRK);
+        assert_eq!(hex::encode(hkdf_extract(Some(b""), &ikm)), TC3_PRK);
+        // No salt means HashLen zero bytes, which HMAC treats like an empty key
+        assert_eq!(hex::encode(hkdf_extract(None, &ikm)), TC3_PRK);
+    }
+
+    #[test]
+    fn rfc5869_expand_vectors() {
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let okm = hkdf_expand(&hex::decode(TC1_PRK).unwrap(), &info, 42).unwrap();
+        assert_eq!(hex::encode(okm), TC1_OKM);
+        let okm = hkdf_expand(&hex::decode(TC3_PRK).unwrap(), b"", 42).unwrap();
+        assert_eq!(hex::encode(okm), TC3_OKM);
+    }
+
+    #[test]
+    fn expand_reuses_prk_across_infos() {
+        let prk = hkdf_extract(Some(b"salt"), &[0x42u8; 32]);
+        let a = hkdf_expand(&prk, b"msg-1", 32).unwrap();
+        let b = hkdf_expand(&prk, b"msg-2", 32).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, hkdf_derive(&[0x42u8; 32], b"salt", b"msg-1").unwrap());
+    }
+
+    #[test]
+    fn labels_separate_outputs() {
+        let ikm = [0x42u8; 32];
+        let a = derive_labeled(&ikm, b"salt", "channel", 32).unwrap();
+        let b = derive_labeled(&ikm, b"salt", "epoch", 32).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(a, derive_labeled(&ikm, b"salt", "channel", 32).unwrap());
+        // Never equal to the unlabeled derivation over the bare label
+        assert_ne!(a, hkdf_derive(&ikm, b"salt", b"channel").unwrap().to_vec());
+    }
+
+    #[test]
+    fn labeled_known_answer() {
+        let ikm = hex::decode(TC1_IKM).unwrap();
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let okm = derive_labeled(&ikm, &salt, "test", 42).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "99458115e9c8568b351e617a8326983467b1b45474861f80\
+             9d502681dca1e0aa645dfbc3382bedb1a247"
+        );
+    }
+
+    #[test]
+    fn expand_rejects_short_prk_and_oversized_output() {
+        assert!(matches!(
+            hkdf_expand(&[0u8; 16], b"", 32),
+            Err(CryptoError::InvalidKeyLength {
+                expected: 32,
+                got: 16
+            })
+        ));
+        let prk = [0u8; 32];
+        assert_eq!(hkdf_expand(&prk, b"", 255 * 32).unwrap().len(), 8160);
+        assert!(hkdf_expand(&prk, b"", 255 * 32 + 1).is_err());
+    }
 }
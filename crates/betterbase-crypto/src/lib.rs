@@ -10,23 +10,39 @@ pub mod signing;
 pub mod types;
 pub mod ucan;
 
-pub use aes_gcm::{aes_gcm_decrypt, aes_gcm_encrypt, decrypt_v4, encrypt_v4, SyncCrypto};
+pub use aes_gcm::{
+    aes_gcm_decrypt, aes_gcm_encrypt, decrypt_v4, encrypt_v4, ChunkedDecryptor, ChunkedEncryptor,
+    CipherSuite, SyncCrypto,
+};
 pub use base64url::{base64url_decode, base64url_encode};
-pub use channel::{build_event_aad, build_presence_aad, derive_channel_key};
-pub use dek::{generate_dek, unwrap_dek, wrap_dek, WRAPPED_DEK_SIZE};
+pub use channel::{
+    build_event_aad, build_event_aad_seq, build_presence_aad, build_presence_aad_seq,
+    derive_channel_key,
+};
+pub use dek::{
+    ct_eq, generate_dek, generate_deks, merge_wrapped_dek_sets, unwrap_dek, unwrap_dek_kw,
+    wrap_dek, wrap_dek_kw, MergedDekSet, WrappedDekSet, AES_KW_OUTPUT_SIZE, WRAPPED_DEK_SIZE,
+};
 pub use edit_chain::{
-    canonical_json, parse_edit_chain, reconstruct_state, serialize_edit_chain, sign_edit_entry,
-    value_diff, verify_edit_chain, verify_edit_entry, EditDiff, EditEntry,
+    canonical_json, chain_state_hash, chain_state_matches, find_common_ancestor, parse_edit_chain,
+    rebase_chain, reconstruct_state, serialize_edit_chain, sign_edit_entry,
+    sign_key_rotation_entry, value_diff, value_diff_granular, verify_edit_chain,
+    verify_edit_chain_batch, verify_edit_chain_for_authors, verify_edit_chain_tail,
+    verify_edit_entry, EditDiff, EditEntry,
 };
 pub use epoch::{derive_epoch_key_from_root, derive_next_epoch_key};
 pub use error::CryptoError;
-pub use hkdf::hkdf_derive;
+pub use hkdf::{derive_labeled, hkdf_derive, hkdf_expand, hkdf_extract};
 pub use signing::{
-    export_private_key_jwk, export_public_key_jwk, generate_p256_keypair, import_private_key_jwk,
-    import_public_key_jwk, sign, verify,
+    canonicalize_jwk, export_ed25519_public_key_jwk, export_private_key_jwk, export_public_key_jwk,
+    generate_ed25519_keypair, generate_p256_keypair, import_ed25519_public_key_jwk,
+    import_private_key_jwk, import_public_key_jwk, jwk_algorithm, sign, sign_der, sign_ed25519,
+    signature_from_der, signature_to_der, verify, verify_batch, verify_batch_with_jwk, verify_der,
+    verify_ed25519, verify_ed25519_batch, verify_with_jwk, JwkAlgorithm,
 };
-pub use types::{EncryptionContext, CURRENT_VERSION, SUPPORTED_VERSIONS};
+pub use types::{EncryptionContext, CURRENT_VERSION, GCM_SIV_VERSION, SUPPORTED_VERSIONS};
 pub use ucan::{
-    compress_p256_public_key, decode_did_key_to_jwk, delegate_ucan, encode_did_key,
-    encode_did_key_from_jwk, issue_root_ucan, UCANPermission,
+    authorize, compress_p256_public_key, decode_did_key_to_jwk, delegate_ucan, did_key_algorithm,
+    encode_did_key, encode_did_key_from_jwk, issue_root_ucan, verify_ucan, verify_ucan_chain,
+    verify_with_did, DidAlgorithm, UCANChainInfo, UCANClaims, UCANPermission,
 };
@@ -1,8 +1,26 @@
-//! ECDSA P-256 signing and verification primitives.
+//! ECDSA P-256 and Ed25519 signing and verification primitives.
 //!
-//! Produces IEEE P1363 format signatures (raw r||s, 64 bytes).
+//! P-256 produces IEEE P1363 format signatures (raw r||s, 64 bytes).
+//!
+//! Signatures are canonical "low-S": `s <= n/2`. ECDSA accepts both `(r, s)`
+//! and `(r, n - s)`, so without this anyone could re-encode a signature and
+//! break dedup or hash links that cover signature bytes. `sign` normalizes
+//! and `verify` rejects the high-S form.
+//!
+//! P1363 is our wire format. `sign_der`/`verify_der` and the conversion
+//! helpers exist for tools that speak ASN.1 DER instead (OpenSSL and most
+//! X.509 libraries).
+//!
+//! Ed25519 keys (JWK `kty: "OKP"`, `crv: "Ed25519"`) come from partners who
+//! issue Ed25519 did:keys. `verify_ed25519` uses strict verification, which
+//! likewise rejects non-canonical signatures. `verify_with_jwk` picks the
+//! algorithm from the JWK for callers that accept either.
 
 use ecdsa::signature::{Signer, Verifier};
+use ed25519_dalek::{
+    Signature as Ed25519Signature, SigningKey as Ed25519SigningKey,
+    VerifyingKey as Ed25519VerifyingKey, PUBLIC_KEY_LENGTH,
+};
 use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
 use serde_json::Value;
 
@@ -16,12 +34,28 @@ use crate::error::CryptoError;
 /// * `message` - Message bytes to sign
 ///
 /// # Returns
-/// 64-byte IEEE P1363 signature (r||s)
+/// 64-byte IEEE P1363 signature (r||s), always low-S
 pub fn sign(private_key: &SigningKey, message: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ok(sign_low_s(private_key, message)?.to_bytes().to_vec())
+}
+
+/// Sign a message with ECDSA P-256 + SHA-256, DER-encoded.
+///
+/// Same signature as [`sign`] (always low-S), as an ASN.1 DER
+/// `ECDSA-Sig-Value` for DER-based verifiers.
+pub fn sign_der(private_key: &SigningKey, message: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    Ok(sign_low_s(private_key, message)?
+        .to_der()
+        .as_bytes()
+        .to_vec())
+}
+
+fn sign_low_s(private_key: &SigningKey, message: &[u8]) -> Result<Signature, CryptoError> {
     let signature: Signature = private_key
         .try_sign(message)
         .map_err(|e| CryptoError::SigningFailed(e.to_string()))?;
-    Ok(signature.to_bytes().to_vec())
+    // p256 leaves S as computed; `normalize_s` returns None when already low.
+    Ok(signature.normalize_s().unwrap_or(signature))
 }
 
 /// Verify an ECDSA P-256 + SHA-256 signature.
@@ -32,15 +66,68 @@ pub fn sign(private_key: &SigningKey, message: &[u8]) -> Result<Vec<u8>, CryptoE
 /// * `signature` - 64-byte IEEE P1363 signature to verify
 ///
 /// # Returns
-/// true if valid, false otherwise (never errors on invalid signature)
+/// true if valid, false otherwise (never errors on invalid signature).
+/// High-S signatures are invalid even if they would otherwise verify.
 pub fn verify(public_key_jwk: &Value, message: &[u8], signature_bytes: &[u8]) -> bool {
-    (|| -> Result<bool, CryptoError> {
-        let verifying_key = import_public_key_jwk(public_key_jwk)?;
-        let signature = Signature::from_slice(signature_bytes)
-            .map_err(|e| CryptoError::InvalidJwk(e.to_string()))?;
-        Ok(verifying_key.verify(message, &signature).is_ok())
-    })()
-    .unwrap_or(false)
+    match import_public_key_jwk(public_key_jwk) {
+        Ok(verifying_key) => verify_with_key(&verifying_key, message, signature_bytes),
+        Err(_) => false,
+    }
+}
+
+/// Verify a DER-encoded ECDSA P-256 + SHA-256 signature.
+///
+/// Same rules as [`verify`], so high-S signatures are invalid; signatures
+/// from signers that don't normalize S (such as OpenSSL) must be normalized
+/// before they're accepted. Returns false for anything that isn't strict DER.
+pub fn verify_der(public_key_jwk: &Value, message: &[u8], der_signature: &[u8]) -> bool {
+    let Ok(signature) = Signature::from_der(der_signature) else {
+        return false;
+    };
+    verify(public_key_jwk, message, &signature.to_bytes())
+}
+
+/// Convert a 64-byte P1363 signature (r||s) to ASN.1 DER.
+pub fn signature_to_der(signature: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let signature = Signature::from_slice(signature)
+        .map_err(|e| CryptoError::InvalidSignature(format!("P1363: {}", e)))?;
+    Ok(signature.to_der().as_bytes().to_vec())
+}
+
+/// Convert an ASN.1 DER signature to 64-byte P1363 (r||s).
+///
+/// S is kept as encoded; pass the result through [`verify`] only if it came
+/// from a low-S signer.
+pub fn signature_from_der(der_signature: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let signature = Signature::from_der(der_signature)
+        .map_err(|e| CryptoError::InvalidSignature(format!("DER: {}", e)))?;
+    Ok(signature.to_bytes().to_vec())
+}
+
+/// Verify many ECDSA P-256 + SHA-256 signatures by one signer.
+///
+/// `items` are `(message, signature)` pairs. p256 has no batch ECDSA
+/// verification, so each signature is still checked on its own; what is
+/// shared is the JWK import and point decoding. Returns true only if every
+/// signature is valid under the same rules as [`verify`].
+pub fn verify_batch(public_key_jwk: &Value, items: &[(&[u8], &[u8])]) -> bool {
+    match import_public_key_jwk(public_key_jwk) {
+        Ok(verifying_key) => items
+            .iter()
+            .all(|(message, signature)| verify_with_key(&verifying_key, message, signature)),
+        Err(_) => false,
+    }
+}
+
+fn verify_with_key(verifying_key: &VerifyingKey, message: &[u8], signature_bytes: &[u8]) -> bool {
+    let signature = match Signature::from_slice(signature_bytes) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    if signature.normalize_s().is_some() {
+        return false;
+    }
+    verifying_key.verify(message, &signature).is_ok()
 }
 
 /// Import a P-256 public key from JWK format.
@@ -119,15 +206,205 @@ pub fn import_private_key_jwk(jwk: &Value) -> Result<SigningKey, CryptoError> {
         .ok_or(CryptoError::MissingJwkField("d"))?;
     let d_bytes =
         base64url_decode(d_b64).map_err(|e| CryptoError::InvalidJwk(format!("d: {}", e)))?;
+    if d_bytes.len() != 32 {
+        return Err(CryptoError::InvalidKeyLength {
+            expected: 32,
+            got: d_bytes.len(),
+        });
+    }
     SigningKey::from_bytes(d_bytes.as_slice().into())
         .map_err(|e| CryptoError::InvalidJwk(format!("P-256 scalar: {}", e)))
 }
 
+/// Re-encode a P-256 JWK into canonical minimal form.
+///
+/// Keeps only `kty`, `crv`, `x`, `y` (and `d` if present), dropping optional
+/// members such as `kid`, `use` and `alg`. Coordinates are re-encoded as
+/// 32-byte big-endian values, so two JWKs for the same key canonicalize to
+/// identical JSON regardless of member order or producer.
+pub fn canonicalize_jwk(jwk: &Value) -> Result<Value, CryptoError> {
+    let kty = jwk
+        .get("kty")
+        .and_then(|v| v.as_str())
+        .ok_or(CryptoError::MissingJwkField("kty"))?;
+    if kty != "EC" {
+        return Err(CryptoError::InvalidJwk(format!("unsupported kty: {}", kty)));
+    }
+    let crv = jwk
+        .get("crv")
+        .and_then(|v| v.as_str())
+        .ok_or(CryptoError::MissingJwkField("crv"))?;
+    if crv != "P-256" {
+        return Err(CryptoError::InvalidJwk(format!("unsupported crv: {}", crv)));
+    }
+
+    // Rejects coordinates that are too long or not on the curve
+    let point = import_public_key_jwk(jwk)?.to_encoded_point(false);
+    let mut canonical = serde_json::json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": crate::base64url::base64url_encode(point.x().unwrap().as_slice()),
+        "y": crate::base64url::base64url_encode(point.y().unwrap().as_slice()),
+    });
+
+    if jwk.get("d").is_some() {
+        let key = import_private_key_jwk(jwk)?;
+        if key.verifying_key().to_encoded_point(false) != point {
+            return Err(CryptoError::InvalidJwk("d does not match x/y".to_string()));
+        }
+        let mut scalar_bytes = key.to_bytes().to_vec();
+        canonical["d"] = Value::String(crate::base64url::base64url_encode(&scalar_bytes));
+        zeroize::Zeroize::zeroize(&mut scalar_bytes);
+    }
+
+    Ok(canonical)
+}
+
 /// Generate a new P-256 signing key pair.
 pub fn generate_p256_keypair() -> SigningKey {
     SigningKey::random(&mut p256::elliptic_curve::rand_core::OsRng)
 }
 
+/// Sign a message with Ed25519.
+///
+/// # Returns
+/// 64-byte signature (R||S)
+pub fn sign_ed25519(
+    private_key: &Ed25519SigningKey,
+    message: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let signature: Ed25519Signature = private_key
+        .try_sign(message)
+        .map_err(|e| CryptoError::SigningFailed(e.to_string()))?;
+    Ok(signature.to_bytes().to_vec())
+}
+
+/// Verify an Ed25519 signature.
+///
+/// # Arguments
+/// * `public_key_jwk` - Ed25519 public key as JWK (serde_json::Value)
+/// * `message` - Original message bytes
+/// * `signature` - 64-byte signature to verify
+///
+/// # Returns
+/// true if valid, false otherwise (never errors on invalid signature).
+/// Non-canonical signatures and small-order keys are invalid.
+pub fn verify_ed25519(public_key_jwk: &Value, message: &[u8], signature_bytes: &[u8]) -> bool {
+    match import_ed25519_public_key_jwk(public_key_jwk) {
+        Ok(verifying_key) => verify_ed25519_with_key(&verifying_key, message, signature_bytes),
+        Err(_) => false,
+    }
+}
+
+/// Verify many Ed25519 signatures by one signer.
+///
+/// Each signature is checked with strict verification, as in
+/// [`verify_ed25519`]; batched Ed25519 verification accepts a different set
+/// of signatures, so it is not used. The JWK import is shared.
+pub fn verify_ed25519_batch(public_key_jwk: &Value, items: &[(&[u8], &[u8])]) -> bool {
+    match import_ed25519_public_key_jwk(public_key_jwk) {
+        Ok(verifying_key) => items.iter().all(|(message, signature)| {
+            verify_ed25519_with_key(&verifying_key, message, signature)
+        }),
+        Err(_) => false,
+    }
+}
+
+fn verify_ed25519_with_key(
+    verifying_key: &Ed25519VerifyingKey,
+    message: &[u8],
+    signature_bytes: &[u8],
+) -> bool {
+    match Ed25519Signature::from_slice(signature_bytes) {
+        Ok(signature) => verifying_key.verify_strict(message, &signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Import an Ed25519 public key from JWK format.
+pub fn import_ed25519_public_key_jwk(jwk: &Value) -> Result<Ed25519VerifyingKey, CryptoError> {
+    let x_b64 = jwk
+        .get("x")
+        .and_then(|v| v.as_str())
+        .ok_or(CryptoError::MissingJwkField("x"))?;
+    let x_bytes =
+        base64url_decode(x_b64).map_err(|e| CryptoError::InvalidJwk(format!("x: {}", e)))?;
+    let x: [u8; PUBLIC_KEY_LENGTH] =
+        x_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| CryptoError::InvalidKeyLength {
+                expected: PUBLIC_KEY_LENGTH,
+                got: x_bytes.len(),
+            })?;
+
+    Ed25519VerifyingKey::from_bytes(&x)
+        .map_err(|e| CryptoError::InvalidJwk(format!("Ed25519 point: {}", e)))
+}
+
+/// Export an Ed25519 verifying key to JWK format.
+pub fn export_ed25519_public_key_jwk(key: &Ed25519VerifyingKey) -> Value {
+    serde_json::json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "x": crate::base64url::base64url_encode(key.as_bytes()),
+    })
+}
+
+/// Generate a new Ed25519 signing key pair.
+pub fn generate_ed25519_keypair() -> Ed25519SigningKey {
+    Ed25519SigningKey::generate(&mut p256::elliptic_curve::rand_core::OsRng)
+}
+
+/// Signature algorithm of a public key JWK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwkAlgorithm {
+    /// `kty: "EC"`, `crv: "P-256"`, verified with [`verify`].
+    P256,
+    /// `kty: "OKP"`, `crv: "Ed25519"`, verified with [`verify_ed25519`].
+    Ed25519,
+}
+
+/// Read the signature algorithm of a public key JWK.
+///
+/// An OKP key must be on Ed25519, and an Ed25519 key must be OKP. Anything
+/// else is taken as P-256, which keeps JWKs written before Ed25519 support
+/// (some without `kty`) on the path they have always taken.
+pub fn jwk_algorithm(jwk: &Value) -> Result<JwkAlgorithm, CryptoError> {
+    let kty = jwk.get("kty").and_then(|v| v.as_str());
+    let crv = jwk.get("crv").and_then(|v| v.as_str());
+    match (kty, crv) {
+        (Some("OKP"), Some("Ed25519")) => Ok(JwkAlgorithm::Ed25519),
+        (Some("OKP"), _) => Err(CryptoError::InvalidJwk(
+            "OKP key must have crv Ed25519".to_string(),
+        )),
+        (_, Some("Ed25519")) => Err(CryptoError::InvalidJwk(
+            "Ed25519 key must have kty OKP".to_string(),
+        )),
+        _ => Ok(JwkAlgorithm::P256),
+    }
+}
+
+/// Verify a signature with the algorithm named by the JWK.
+///
+/// Returns false for a JWK [`jwk_algorithm`] rejects.
+pub fn verify_with_jwk(public_key_jwk: &Value, message: &[u8], signature_bytes: &[u8]) -> bool {
+    match jwk_algorithm(public_key_jwk) {
+        Ok(JwkAlgorithm::P256) => verify(public_key_jwk, message, signature_bytes),
+        Ok(JwkAlgorithm::Ed25519) => verify_ed25519(public_key_jwk, message, signature_bytes),
+        Err(_) => false,
+    }
+}
+
+/// [`verify_with_jwk`] for several signatures by the same JWK.
+pub fn verify_batch_with_jwk(public_key_jwk: &Value, items: &[(&[u8], &[u8])]) -> bool {
+    match jwk_algorithm(public_key_jwk) {
+        Ok(JwkAlgorithm::P256) => verify_batch(public_key_jwk, items),
+        Ok(JwkAlgorithm::Ed25519) => verify_ed25519_batch(public_key_jwk, items),
+        Err(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +439,121 @@ mod tests {
         assert!(!verify(&jwk, b"tampered", &signature));
     }
 
+    #[test]
+    fn verify_with_jwk_dispatches_on_key_type() {
+        let message = b"either curve";
+
+        let p256 = generate_p256_keypair();
+        let p256_jwk = export_public_key_jwk(p256.verifying_key());
+        let p256_sig = sign(&p256, message).unwrap();
+        assert_eq!(jwk_algorithm(&p256_jwk).unwrap(), JwkAlgorithm::P256);
+        assert!(verify_with_jwk(&p256_jwk, message, &p256_sig));
+
+        let ed = generate_ed25519_keypair();
+        let ed_jwk = export_ed25519_public_key_jwk(&ed.verifying_key());
+        let ed_sig = sign_ed25519(&ed, message).unwrap();
+        assert_eq!(jwk_algorithm(&ed_jwk).unwrap(), JwkAlgorithm::Ed25519);
+        assert!(verify_with_jwk(&ed_jwk, message, &ed_sig));
+        assert!(verify_batch_with_jwk(
+            &ed_jwk,
+            &[(&message[..], &ed_sig[..]), (&message[..], &ed_sig[..])]
+        ));
+
+        // Both are 64 bytes; neither passes under the other key
+        assert!(!verify_with_jwk(&p256_jwk, message, &ed_sig));
+        assert!(!verify_with_jwk(&ed_jwk, message, &p256_sig));
+
+        // Mismatched kty/crv pairs are rejected outright
+        let mut okp_p256 = ed_jwk.clone();
+        okp_p256["crv"] = serde_json::json!("P-256");
+        assert!(jwk_algorithm(&okp_p256).is_err());
+        assert!(!verify_with_jwk(&okp_p256, message, &ed_sig));
+
+        let mut ec_ed25519 = p256_jwk.clone();
+        ec_ed25519["crv"] = serde_json::json!("Ed25519");
+        assert!(jwk_algorithm(&ec_ed25519).is_err());
+        assert!(!verify_with_jwk(&ec_ed25519, message, &p256_sig));
+    }
+
+    #[test]
+    fn der_sign_verify_round_trip() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let message = b"hello der";
+
+        let der = sign_der(&key, message).unwrap();
+        assert_eq!(der[0], 0x30, "DER SEQUENCE tag");
+        assert!(verify_der(&jwk, message, &der));
+        assert!(!verify_der(&jwk, b"tampered", &der));
+        assert!(!verify_der(&jwk, message, &der[..der.len() - 1]));
+
+        // The DER and P1363 forms carry the same signature
+        let p1363 = signature_from_der(&der).unwrap();
+        assert!(verify(&jwk, message, &p1363));
+        // P1363 is still the default wire format
+        assert!(!verify(&jwk, message, &der));
+    }
+
+    #[test]
+    fn p1363_to_der_round_trips() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let message = b"convert me";
+
+        let p1363 = sign(&key, message).unwrap();
+        let der = signature_to_der(&p1363).unwrap();
+        assert!(verify_der(&jwk, message, &der));
+        assert_eq!(signature_from_der(&der).unwrap(), p1363);
+
+        assert!(matches!(
+            signature_to_der(&p1363[..63]),
+            Err(CryptoError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            signature_from_der(&p1363),
+            Err(CryptoError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn verify_der_rejects_high_s() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let message = b"openssl style";
+
+        let signature = sign(&key, message).unwrap();
+        assert!(verify_der(
+            &jwk,
+            message,
+            &signature_to_der(&signature).unwrap()
+        ));
+
+        let flipped = flip_s(&signature);
+        let der = signature_to_der(&flipped).unwrap();
+        // Valid ECDSA, but the same malleable form `verify` refuses
+        let raw = Signature::from_der(&der).unwrap();
+        assert!(key.verifying_key().verify(message, &raw).is_ok());
+        assert!(!verify_der(&jwk, message, &der));
+    }
+
+    #[test]
+    fn verify_batch_requires_every_signature() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let messages: Vec<Vec<u8>> = (0u8..8).map(|i| vec![i; 16]).collect();
+        let signatures: Vec<Vec<u8>> = messages.iter().map(|m| sign(&key, m).unwrap()).collect();
+        let mut items: Vec<(&[u8], &[u8])> = messages
+            .iter()
+            .zip(&signatures)
+            .map(|(m, s)| (m.as_slice(), s.as_slice()))
+            .collect();
+
+        assert!(verify_batch(&jwk, &items));
+        items[5].0 = &b"tampered"[..];
+        assert!(!verify_batch(&jwk, &items));
+        assert!(!verify_batch(&serde_json::json!({"kty": "EC"}), &[]));
+    }
+
     #[test]
     fn signature_is_64_bytes() {
         let key = generate_p256_keypair();
@@ -185,4 +577,199 @@ mod tests {
         let bad_jwk = serde_json::json!({"kty": "EC"});
         assert!(!verify(&bad_jwk, b"test", &[0u8; 64]));
     }
+
+    /// P-256 group order n, big-endian.
+    const N: [u8; 32] = [
+        0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63,
+        0x25, 0x51,
+    ];
+
+    /// floor(n / 2), big-endian.
+    const HALF_N: [u8; 32] = [
+        0x7f, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xde, 0x73, 0x7d, 0x56, 0xd3, 0x8b, 0xcf, 0x42, 0x79, 0xdc, 0xe5, 0x61, 0x7e, 0x31,
+        0x92, 0xa8,
+    ];
+
+    /// Replace s with n - s, giving the other signature that ECDSA accepts.
+    fn flip_s(signature: &[u8]) -> Vec<u8> {
+        let mut out = signature.to_vec();
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let mut diff = N[i] as i16 - signature[32 + i] as i16 - borrow;
+            borrow = (diff < 0) as i16;
+            if diff < 0 {
+                diff += 256;
+            }
+            out[32 + i] = diff as u8;
+        }
+        out
+    }
+
+    #[test]
+    fn sign_never_emits_high_s() {
+        let key = generate_p256_keypair();
+        for i in 0u32..256 {
+            let signature = sign(&key, &i.to_be_bytes()).unwrap();
+            assert!(
+                signature[32..] <= HALF_N[..],
+                "high-S signature for message {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn high_s_variant_is_rejected() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let message = b"malleable";
+        let signature = sign(&key, message).unwrap();
+        assert!(verify(&jwk, message, &signature));
+
+        let flipped = flip_s(&signature);
+        assert_ne!(flipped, signature);
+        assert!(flipped[32..] > HALF_N[..]);
+        // Still a mathematically valid ECDSA signature...
+        let raw = Signature::from_slice(&flipped).unwrap();
+        assert!(key.verifying_key().verify(message, &raw).is_ok());
+        // ...but not accepted here, nor by the paths built on `verify`.
+        assert!(!verify(&jwk, message, &flipped));
+        assert!(!verify_with_jwk(&jwk, message, &flipped));
+        assert!(!verify_batch(
+            &jwk,
+            &[(message, &signature), (message, &flipped)]
+        ));
+        assert!(!verify_batch_with_jwk(&jwk, &[(message, &flipped)]));
+    }
+
+    #[test]
+    fn ed25519_sign_verify_round_trip() {
+        let key = generate_ed25519_keypair();
+        let jwk = export_ed25519_public_key_jwk(&key.verifying_key());
+        let message = b"hello world";
+
+        let signature = sign_ed25519(&key, message).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(verify_ed25519(&jwk, message, &signature));
+        assert!(!verify_ed25519(&jwk, b"tampered", &signature));
+
+        let other = generate_ed25519_keypair();
+        let other_jwk = export_ed25519_public_key_jwk(&other.verifying_key());
+        assert!(!verify_ed25519(&other_jwk, message, &signature));
+    }
+
+    #[test]
+    fn ed25519_rfc8032_test_vector() {
+        // RFC 8032 section 7.1, TEST 1 (empty message).
+        let secret: [u8; 32] =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let public =
+            hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+                .unwrap();
+        let expected = hex::decode(
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        )
+        .unwrap();
+
+        let key = Ed25519SigningKey::from_bytes(&secret);
+        let jwk = export_ed25519_public_key_jwk(&key.verifying_key());
+        assert_eq!(
+            jwk["x"],
+            Value::String(crate::base64url::base64url_encode(&public))
+        );
+        assert_eq!(sign_ed25519(&key, b"").unwrap(), expected);
+        assert!(verify_ed25519(&jwk, b"", &expected));
+    }
+
+    #[test]
+    fn ed25519_malformed_jwk_returns_false() {
+        let key = generate_ed25519_keypair();
+        let signature = sign_ed25519(&key, b"test").unwrap();
+
+        let missing_x = serde_json::json!({"kty": "OKP", "crv": "Ed25519"});
+        assert!(!verify_ed25519(&missing_x, b"test", &signature));
+
+        let short_x = serde_json::json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "x": crate::base64url::base64url_encode(&[1u8; 31]),
+        });
+        assert!(matches!(
+            import_ed25519_public_key_jwk(&short_x),
+            Err(CryptoError::InvalidKeyLength {
+                expected: 32,
+                got: 31
+            })
+        ));
+        assert!(!verify_ed25519(&short_x, b"test", &signature));
+    }
+
+    #[test]
+    fn canonicalize_jwk_ignores_member_order_and_optional_fields() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let x = jwk["x"].as_str().unwrap();
+        let y = jwk["y"].as_str().unwrap();
+
+        let a: Value = serde_json::from_str(&format!(
+            r#"{{"kty":"EC","crv":"P-256","x":"{x}","y":"{y}"}}"#
+        ))
+        .unwrap();
+        let b: Value = serde_json::from_str(&format!(
+            r#"{{"y":"{y}","kid":"key-1","x":"{x}","alg":"ES256","use":"sig","crv":"P-256","kty":"EC"}}"#
+        ))
+        .unwrap();
+
+        let ca = canonicalize_jwk(&a).unwrap();
+        let cb = canonicalize_jwk(&b).unwrap();
+        assert_eq!(ca, cb);
+        assert_eq!(ca.to_string(), cb.to_string());
+        assert_eq!(ca, jwk);
+    }
+
+    #[test]
+    fn canonicalize_jwk_keeps_matching_private_scalar() {
+        let key = generate_p256_keypair();
+        let mut jwk = export_private_key_jwk(&key);
+        jwk["kid"] = Value::String("device".to_string());
+
+        let canonical = canonicalize_jwk(&jwk).unwrap();
+        assert_eq!(canonical, export_private_key_jwk(&key));
+
+        let other = generate_p256_keypair();
+        jwk["d"] = export_private_key_jwk(&other)["d"].clone();
+        assert!(canonicalize_jwk(&jwk).is_err());
+    }
+
+    #[test]
+    fn canonicalize_jwk_rejects_bad_coordinates_and_curves() {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+
+        let mut long_x = jwk.clone();
+        long_x["x"] = Value::String(crate::base64url::base64url_encode(&[1u8; 33]));
+        assert!(canonicalize_jwk(&long_x).is_err());
+
+        let mut off_curve = jwk.clone();
+        off_curve["y"] = Value::String(crate::base64url::base64url_encode(&[1u8; 32]));
+        assert!(canonicalize_jwk(&off_curve).is_err());
+
+        let mut other_curve = jwk.clone();
+        other_curve["crv"] = Value::String("P-384".to_string());
+        assert!(canonicalize_jwk(&other_curve).is_err());
+
+        let mut short_d = export_private_key_jwk(&key);
+        short_d["d"] = Value::String(crate::base64url::base64url_encode(&[1u8; 31]));
+        assert!(matches!(
+            canonicalize_jwk(&short_d),
+            Err(CryptoError::InvalidKeyLength {
+                expected: 32,
+                got: 31
+            })
+        ));
+    }
 }
@@ -5,8 +5,13 @@
 /// DEK is wrapped separately with AES-KW: [epoch:4B][AES-KW(KEK, DEK):40B] = 44 bytes
 pub const CURRENT_VERSION: u8 = 4;
 
-/// Supported wire format versions (for decryption).
-pub const SUPPORTED_VERSIONS: &[u8] = &[4];
+/// Version 5: the version 4 layout with an AES-256-GCM-SIV body.
+/// Written only when a caller asks for `CipherSuite::Aes256GcmSiv`.
+pub const GCM_SIV_VERSION: u8 = 5;
+
+/// Wire format versions the v4 codec in `aes_gcm` accepts. Choosing a
+/// version per space and peer is `betterbase_sync_core::wire`'s job.
+pub const SUPPORTED_VERSIONS: &[u8] = &[4, 5];
 
 /// Default epoch advance interval in milliseconds (30 days).
 pub const DEFAULT_EPOCH_ADVANCE_INTERVAL_MS: u64 = 30 * 24 * 60 * 60 * 1000;
@@ -1,21 +1,32 @@
 //! UCAN (User Controlled Authorization Network) primitives.
 //!
-//! Provides DID key encoding and UCAN token issuance for P-256 keys.
+//! Provides DID key encoding, UCAN token issuance, and delegation chain
+//! verification for P-256 keys. did:key encoding and decoding also handle
+//! Ed25519 keys.
 
 use p256::ecdsa::SigningKey;
 use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::base64url::{base64url_decode, base64url_encode};
 use crate::edit_chain::canonical_json;
 use crate::error::CryptoError;
-use crate::signing::{export_public_key_jwk, sign};
+use crate::signing::{
+    export_ed25519_public_key_jwk, export_public_key_jwk, import_ed25519_public_key_jwk,
+    jwk_algorithm, sign, verify, verify_with_jwk, JwkAlgorithm,
+};
 
 /// UCAN permission levels for space authorization.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serializes as its `cmd` claim (see [`UCANPermission::as_str`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UCANPermission {
+    #[serde(rename = "/space/admin")]
     Admin,
+    #[serde(rename = "/space/write")]
     Write,
+    #[serde(rename = "/space/read")]
     Read,
 }
 
@@ -27,6 +38,83 @@ impl UCANPermission {
             UCANPermission::Read => "/space/read",
         }
     }
+
+    /// Parse a `cmd` claim; `None` for anything but the three space commands.
+    pub fn from_command(cmd: &str) -> Option<Self> {
+        match cmd {
+            "/space/admin" => Some(UCANPermission::Admin),
+            "/space/write" => Some(UCANPermission::Write),
+            "/space/read" => Some(UCANPermission::Read),
+            _ => None,
+        }
+    }
+
+    /// Whether a holder of `self` may delegate `other` (admin ⊇ write ⊇ read).
+    pub fn covers(&self, other: UCANPermission) -> bool {
+        self.rank() >= other.rank()
+    }
+
+    /// Whether a token granting `self` meets a `required` capability
+    /// (admin ⊇ write ⊇ read).
+    pub fn satisfies(&self, required: UCANPermission) -> bool {
+        self.covers(required)
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            UCANPermission::Admin => 2,
+            UCANPermission::Write => 1,
+            UCANPermission::Read => 0,
+        }
+    }
+}
+
+/// Longest proof chain `verify_ucan_chain` will follow above the leaf.
+const MAX_UCAN_CHAIN_DEPTH: usize = 16;
+
+/// Facts established by [`verify_ucan_chain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UCANChainInfo {
+    /// Issuer of the root UCAN (the one with an empty `prf`).
+    pub root_issuer: String,
+    /// Audience of the leaf UCAN.
+    pub audience: String,
+    /// Permission granted by the leaf UCAN.
+    pub permission: UCANPermission,
+    /// Earliest `exp` anywhere in the chain.
+    pub expires_at: u64,
+}
+
+/// Claims of a UCAN whose proof chain verified, from [`verify_ucan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UCANClaims {
+    /// Issuer of the token itself.
+    pub issuer: String,
+    /// Issuer of the root UCAN (the one with an empty `prf`).
+    pub root_issuer: String,
+    /// Audience of the token.
+    pub audience: String,
+    /// Permission granted by the token.
+    pub permission: UCANPermission,
+    /// Space named by the token's `with` (`space:<id>`).
+    pub space_id: String,
+    /// Earliest `exp` anywhere in the chain.
+    pub expires_at: u64,
+}
+
+/// Multicodec for a compressed P-256 public key.
+const P256_MULTICODEC: u32 = 0x1200;
+
+/// Multicodec for an Ed25519 public key.
+const ED25519_MULTICODEC: u32 = 0xed;
+
+/// Key algorithm named by a did:key's multicodec prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DidAlgorithm {
+    P256,
+    Ed25519,
+    /// Any other multicodec, carried so callers can report it.
+    Unknown(u32),
 }
 
 /// Encode an unsigned integer as a varint (unsigned LEB128).
@@ -81,17 +169,24 @@ pub fn compress_p256_public_key(jwk: &Value) -> Result<Vec<u8>, CryptoError> {
     Ok(result)
 }
 
-/// Encode a P-256 public key JWK as a did:key string.
+/// Encode a P-256 or Ed25519 public key JWK as a did:key string.
 ///
-/// Format: `did:key:z<base58btc(varint(0x1200) || compressed_point)>`
-/// where 0x1200 is the multicodec for P-256 public key.
+/// Format: `did:key:z<base58btc(varint(codec) || key)>`. An OKP Ed25519
+/// JWK uses codec 0xed and its 32-byte key; an EC JWK uses codec 0x1200 and
+/// the compressed point. See [`jwk_algorithm`] for how the two are told apart.
 pub fn encode_did_key_from_jwk(jwk: &Value) -> Result<String, CryptoError> {
-    let compressed = compress_p256_public_key(jwk)?;
-    let varint = varint_encode(0x1200); // P-256 multicodec
-
-    let mut payload = Vec::with_capacity(varint.len() + compressed.len());
+    let (codec, key) = match jwk_algorithm(jwk)? {
+        JwkAlgorithm::Ed25519 => (
+            ED25519_MULTICODEC,
+            import_ed25519_public_key_jwk(jwk)?.to_bytes().to_vec(),
+        ),
+        JwkAlgorithm::P256 => (P256_MULTICODEC, compress_p256_public_key(jwk)?),
+    };
+    let varint = varint_encode(codec);
+
+    let mut payload = Vec::with_capacity(varint.len() + key.len());
     payload.extend_from_slice(&varint);
-    payload.extend_from_slice(&compressed);
+    payload.extend_from_slice(&key);
 
     Ok(format!("did:key:z{}", bs58::encode(&payload).into_string()))
 }
@@ -102,11 +197,11 @@ pub fn encode_did_key(signing_key: &SigningKey) -> Result<String, CryptoError> {
     encode_did_key_from_jwk(&jwk)
 }
 
-/// Decode a `did:key:z...` string back to a P-256 public key JWK.
+/// Decode a `did:key:z...` string back to a public key JWK.
 ///
 /// Reverses `encode_did_key_from_jwk`: strips the `did:key:z` prefix,
-/// base58-decodes, parses the P-256 multicodec varint (0x1200), and
-/// decompresses the SEC1 compressed point to an uncompressed JWK.
+/// base58-decodes and parses the multicodec varint. P-256 (0x1200) points
+/// are decompressed to an EC JWK; Ed25519 (0xed) keys become an OKP JWK.
 pub fn decode_did_key_to_jwk(did: &str) -> Result<Value, CryptoError> {
     let encoded = did
         .strip_prefix("did:key:z")
@@ -116,19 +211,44 @@ pub fn decode_did_key_to_jwk(did: &str) -> Result<Value, CryptoError> {
         .into_vec()
         .map_err(|e| CryptoError::InvalidJwk(format!("base58 decode: {}", e)))?;
 
-    // Parse varint — P-256 multicodec is 0x1200, encoded as [0x80, 0x24]
+    // Parse varint — P-256 is 0x1200 ([0x80, 0x24]), Ed25519 0xed ([0xed, 0x01])
     if payload.len() < 2 {
         return Err(CryptoError::InvalidJwk("DID payload too short".to_string()));
     }
     let (codec, varint_len) = varint_decode(&payload)?;
-    if codec != 0x1200 {
-        return Err(CryptoError::InvalidJwk(format!(
-            "expected P-256 multicodec 0x1200, got 0x{:04x}",
-            codec
-        )));
+    match codec {
+        P256_MULTICODEC => decompress_p256_did_key(&payload[varint_len..]),
+        ED25519_MULTICODEC => ed25519_did_key_jwk(&payload[varint_len..]),
+        other => Err(CryptoError::InvalidJwk(format!(
+            "unsupported multicodec 0x{:04x}",
+            other
+        ))),
     }
+}
+
+/// Verify a signature by the key a `did:key` names.
+///
+/// Decodes the DID with [`decode_did_key_to_jwk`] and verifies with
+/// [`verify_with_jwk`], so P-256 signatures must be low-S and the algorithm
+/// follows the DID's multicodec. Fails only if the DID does not decode;
+/// a signature that doesn't verify is `Ok(false)`.
+pub fn verify_with_did(did: &str, message: &[u8], signature: &[u8]) -> Result<bool, CryptoError> {
+    let jwk = decode_did_key_to_jwk(did)?;
+    Ok(verify_with_jwk(&jwk, message, signature))
+}
+
+/// Check a did:key's 32-byte Ed25519 key and wrap it in an OKP JWK.
+fn ed25519_did_key_jwk(key: &[u8]) -> Result<Value, CryptoError> {
+    let bytes: [u8; 32] = key.try_into().map_err(|_| {
+        CryptoError::InvalidJwk(format!("expected 32-byte Ed25519 key, got {}", key.len()))
+    })?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(&bytes)
+        .map_err(|e| CryptoError::InvalidJwk(format!("invalid Ed25519 key: {}", e)))?;
+    Ok(export_ed25519_public_key_jwk(&key))
+}
 
-    let compressed = &payload[varint_len..];
+/// Decompress a did:key's 33-byte SEC1 P-256 point to an EC JWK.
+fn decompress_p256_did_key(compressed: &[u8]) -> Result<Value, CryptoError> {
     if compressed.len() != 33 {
         return Err(CryptoError::InvalidJwk(format!(
             "expected 33-byte compressed point, got {}",
@@ -155,17 +275,51 @@ pub fn decode_did_key_to_jwk(did: &str) -> Result<Value, CryptoError> {
     }))
 }
 
+/// Read the key algorithm of a `did:key:z...` string without decoding the key.
+///
+/// Base58 has no byte alignment, so the payload is still decoded in full;
+/// what this skips is the point decompression and curve check, which is
+/// the expensive part of [`decode_did_key_to_jwk`].
+pub fn did_key_algorithm(did: &str) -> Result<DidAlgorithm, CryptoError> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| CryptoError::InvalidJwk("expected did:key:z prefix".to_string()))?;
+
+    let payload = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| CryptoError::InvalidJwk(format!("base58 decode: {}", e)))?;
+
+    let (codec, varint_len) = varint_decode(&payload)?;
+    if varint_len == payload.len() {
+        return Err(CryptoError::InvalidJwk(
+            "DID payload has no key".to_string(),
+        ));
+    }
+    Ok(match codec {
+        P256_MULTICODEC => DidAlgorithm::P256,
+        ED25519_MULTICODEC => DidAlgorithm::Ed25519,
+        other => DidAlgorithm::Unknown(other),
+    })
+}
+
 /// Decode an unsigned varint (LEB128). Returns (value, bytes_consumed).
 fn varint_decode(bytes: &[u8]) -> Result<(u32, usize), CryptoError> {
     let mut value: u32 = 0;
     let mut shift: u32 = 0;
     for (i, &byte) in bytes.iter().enumerate() {
-        if shift >= 32 {
+        let bits = (byte & 0x7f) as u32;
+        // The fifth byte may only carry the top 4 bits of a u32.
+        if shift >= 32 || (shift == 28 && bits > 0x0f) {
             return Err(CryptoError::InvalidJwk("varint overflow".to_string()));
         }
-        value |= ((byte & 0x7f) as u32) << shift;
+        value |= bits << shift;
         shift += 7;
         if byte & 0x80 == 0 {
+            // A trailing zero byte is an overlong encoding; accepting it
+            // would give one key several did:key spellings.
+            if byte == 0 && i > 0 {
+                return Err(CryptoError::InvalidJwk("non-minimal varint".to_string()));
+            }
             return Ok((value, i + 1));
         }
     }
@@ -239,7 +393,7 @@ pub fn delegate_ucan(
     // Best-effort: cap expiry to not exceed the parent UCAN's exp.
     // Silently ignores malformed proofs — the delegation is still valid,
     // it just won't have its expiry capped. Proof verification happens
-    // on the consuming side, not here.
+    // on the consuming side (see `verify_ucan_chain`), not here.
     if let Some(parent_payload_b64) = proof.split('.').nth(1) {
         if let Ok(parent_bytes) = base64url_decode(parent_payload_b64) {
             if let Ok(parent_payload) = serde_json::from_slice::<Value>(&parent_bytes) {
@@ -265,6 +419,235 @@ pub fn delegate_ucan(
     sign_es256_jwt(private_key, &payload)
 }
 
+/// Verify a UCAN and its `prf` chain up to the root.
+///
+/// Every link must carry a valid ES256 signature from its `iss` did:key,
+/// target `space:{expected_space_id}`, and be unexpired at `now_seconds`.
+/// Each issuer must be an audience of its proof, and may only delegate a
+/// permission its proof covers. Links with more than one proof are rejected.
+pub fn verify_ucan_chain(
+    token: &str,
+    expected_space_id: &str,
+    now_seconds: u64,
+) -> Result<UCANChainInfo, CryptoError> {
+    let resource = format!("space:{}", expected_space_id);
+    let leaf = verify_ucan_link(token, &resource, now_seconds)?;
+    let audience = leaf.aud[0].clone();
+    let permission = leaf.permission;
+    let mut expires_at = leaf.exp;
+
+    let mut child = leaf;
+    for _ in 0..MAX_UCAN_CHAIN_DEPTH {
+        let parent = match child.prf.as_slice() {
+            [] => {
+                return Ok(UCANChainInfo {
+                    root_issuer: child.iss,
+                    audience,
+                    permission,
+                    expires_at,
+                })
+            }
+            [proof] => verify_ucan_link(proof, &resource, now_seconds)?,
+            _ => {
+                return Err(CryptoError::InvalidUcan(
+                    "multiple proofs are not supported".to_string(),
+                ))
+            }
+        };
+        if !parent.aud.contains(&child.iss) {
+            return Err(CryptoError::InvalidUcan(format!(
+                "issuer {} is not an audience of its proof",
+                child.iss
+            )));
+        }
+        if !parent.permission.covers(child.permission) {
+            return Err(CryptoError::InvalidUcan(format!(
+                "{} cannot delegate {}",
+                parent.permission.as_str(),
+                child.permission.as_str()
+            )));
+        }
+        expires_at = expires_at.min(parent.exp);
+        child = parent;
+    }
+    Err(CryptoError::InvalidUcan(format!(
+        "proof chain longer than {} links",
+        MAX_UCAN_CHAIN_DEPTH
+    )))
+}
+
+/// Verify a UCAN and its `prf` chain for the space the token names.
+///
+/// Same checks as [`verify_ucan_chain`], with the expected space read from
+/// the token's own `with`; every proof must target that space too. Prefer
+/// `verify_ucan_chain` when the caller already knows which space the token
+/// should grant, since this accepts a valid token for any space.
+pub fn verify_ucan(token: &str, now_seconds: u64) -> Result<UCANClaims, CryptoError> {
+    let payload_b64 = token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| CryptoError::InvalidUcan("expected three JWT segments".to_string()))?;
+    let payload = decode_jwt_segment(payload_b64, "payload")?;
+    let with = payload
+        .get("with")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing with".to_string()))?;
+    let space_id = with
+        .strip_prefix("space:")
+        .ok_or_else(|| CryptoError::InvalidUcan(format!("resource {} is not a space", with)))?;
+    let issuer = payload
+        .get("iss")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing iss".to_string()))?;
+
+    let info = verify_ucan_chain(token, space_id, now_seconds)?;
+    Ok(UCANClaims {
+        issuer: issuer.to_string(),
+        root_issuer: info.root_issuer,
+        audience: info.audience,
+        permission: info.permission,
+        space_id: space_id.to_string(),
+        expires_at: info.expires_at,
+    })
+}
+
+/// Check that `token` lets its holder act on `space_id` with at least
+/// `required` permission.
+///
+/// Verifies the chain as [`verify_ucan`] does, then requires the token's
+/// `with` to be exactly `space:{space_id}`, the chain's root issuer to be
+/// one of `trusted_roots` (the space owner, or a server that issues root
+/// UCANs for it), and its permission to satisfy `required`. A valid chain
+/// only proves who signed it: anyone can issue themselves an Admin root for
+/// any space, so the root is what ties the grant to the space.
+pub fn authorize(
+    token: &str,
+    space_id: &str,
+    trusted_roots: &[&str],
+    required: UCANPermission,
+    now_seconds: u64,
+) -> Result<(), CryptoError> {
+    let claims = verify_ucan(token, now_seconds)?;
+    if claims.space_id != space_id {
+        return Err(CryptoError::UcanSpaceMismatch {
+            expected: space_id.to_string(),
+            got: claims.space_id,
+        });
+    }
+    if !trusted_roots.contains(&claims.root_issuer.as_str()) {
+        return Err(CryptoError::UcanUntrustedRoot(claims.root_issuer));
+    }
+    if !claims.permission.satisfies(required) {
+        return Err(CryptoError::UcanPermissionDenied {
+            granted: claims.permission.as_str(),
+            required: required.as_str(),
+        });
+    }
+    Ok(())
+}
+
+/// Claims of one verified UCAN in a chain.
+struct UCANLink {
+    iss: String,
+    aud: Vec<String>,
+    permission: UCANPermission,
+    exp: u64,
+    prf: Vec<String>,
+}
+
+/// Parse one UCAN JWT, check its signature, resource and expiry.
+fn verify_ucan_link(
+    token: &str,
+    resource: &str,
+    now_seconds: u64,
+) -> Result<UCANLink, CryptoError> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let [header_b64, payload_b64, signature_b64] = parts[..] else {
+        return Err(CryptoError::InvalidUcan(
+            "expected three JWT segments".to_string(),
+        ));
+    };
+
+    let header = decode_jwt_segment(header_b64, "header")?;
+    if header.get("alg").and_then(Value::as_str) != Some("ES256") {
+        return Err(CryptoError::InvalidUcan("alg must be ES256".to_string()));
+    }
+    let payload = decode_jwt_segment(payload_b64, "payload")?;
+    let signature = base64url_decode(signature_b64)
+        .map_err(|e| CryptoError::InvalidUcan(format!("signature: {}", e)))?;
+
+    let iss = payload
+        .get("iss")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing iss".to_string()))?;
+    let issuer_jwk = decode_did_key_to_jwk(iss)?;
+    let signing_input = format!("{}.{}", header_b64, payload_b64);
+    if !verify(&issuer_jwk, signing_input.as_bytes(), &signature) {
+        return Err(CryptoError::InvalidUcan(format!(
+            "signature does not verify for {}",
+            iss
+        )));
+    }
+
+    let aud = string_array(&payload, "aud")?;
+    if aud.is_empty() {
+        return Err(CryptoError::InvalidUcan("empty aud".to_string()));
+    }
+    let cmd = payload
+        .get("cmd")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing cmd".to_string()))?;
+    let permission = UCANPermission::from_command(cmd)
+        .ok_or_else(|| CryptoError::InvalidUcan(format!("unknown cmd {}", cmd)))?;
+    let with = payload
+        .get("with")
+        .and_then(Value::as_str)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing with".to_string()))?;
+    if with != resource {
+        return Err(CryptoError::InvalidUcan(format!(
+            "resource {} does not match {}",
+            with, resource
+        )));
+    }
+    let exp = payload
+        .get("exp")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| CryptoError::InvalidUcan("missing exp".to_string()))?;
+    if exp <= now_seconds {
+        return Err(CryptoError::UcanExpired {
+            exp,
+            now: now_seconds,
+        });
+    }
+
+    Ok(UCANLink {
+        iss: iss.to_string(),
+        aud,
+        permission,
+        exp,
+        prf: string_array(&payload, "prf")?,
+    })
+}
+
+fn decode_jwt_segment(segment: &str, name: &str) -> Result<Value, CryptoError> {
+    let bytes = base64url_decode(segment)
+        .map_err(|e| CryptoError::InvalidUcan(format!("{}: {}", name, e)))?;
+    serde_json::from_slice(&bytes).map_err(|e| CryptoError::InvalidUcan(format!("{}: {}", name, e)))
+}
+
+fn string_array(payload: &Value, field: &str) -> Result<Vec<String>, CryptoError> {
+    payload
+        .get(field)
+        .and_then(Value::as_array)
+        .and_then(|items| {
+            items
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or_else(|| CryptoError::InvalidUcan(format!("{} must be an array of strings", field)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,15 +767,153 @@ mod tests {
         assert!(decode_did_key_to_jwk("not-a-did").is_err());
     }
 
+    #[test]
+    fn decode_did_key_rejects_non_canonical_varints() {
+        let key = generate_p256_keypair();
+        let did = encode_did_key(&key).unwrap();
+        let payload = bs58::decode(&did["did:key:z".len()..]).into_vec().unwrap();
+        let point = &payload[2..];
+
+        // 0x1200 padded with an overlong trailing zero, and with bits past u32.
+        for varint in [&[0x80, 0xa4, 0x00][..], &[0x80, 0xa4, 0x80, 0x80, 0x10]] {
+            let mut spelled = varint.to_vec();
+            spelled.extend_from_slice(point);
+            let did = format!("did:key:z{}", bs58::encode(&spelled).into_string());
+            assert!(decode_did_key_to_jwk(&did).is_err(), "{:02x?}", varint);
+        }
+    }
+
     #[test]
     fn decode_did_key_rejects_wrong_codec() {
-        // Construct a did:key with Ed25519 multicodec (0xed)
-        let mut payload = vec![0xed, 0x01]; // varint for 0xed
-        payload.extend_from_slice(&[0u8; 32]); // dummy 32-byte key
+        // secp256k1-pub (0xe7) is not supported
+        let mut payload = vec![0xe7, 0x01];
+        payload.extend_from_slice(&[0x02; 33]);
         let encoded = format!("did:key:z{}", bs58::encode(&payload).into_string());
         assert!(decode_did_key_to_jwk(&encoded).is_err());
     }
 
+    #[test]
+    fn decode_did_key_ed25519_test_vectors() {
+        // Test vectors from the did:key spec.
+        let vectors = [
+            (
+                "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+                "Lm_M42cB3HkUiODQsXRcweM6TByfzEHGO9ND274JcOY",
+            ),
+            (
+                "did:key:z6MkjchhfUsD6mmvni8mCdXHw216Xrm9bQe2mBH1P5RDjVJG",
+                "TLWr9q15-_WrvMr8wmnYXNJlHtS4hbWGnyQa7fCluik",
+            ),
+            (
+                "did:key:z6MknGc3ocHs3zdPiJbnaaqDi58NGb4pk1Sp9WxWufuXSdxf",
+                "dCK5iHWYBo4yxESKlJrbKQ0PTjW54BsO5fGh5gD-JnQ",
+            ),
+        ];
+        for (did, x) in vectors {
+            let jwk = decode_did_key_to_jwk(did).unwrap();
+            assert_eq!(
+                jwk,
+                serde_json::json!({"kty": "OKP", "crv": "Ed25519", "x": x}),
+                "{}",
+                did
+            );
+            assert_eq!(encode_did_key_from_jwk(&jwk).unwrap(), did);
+        }
+
+        // Wrong key length after the 0xed prefix
+        let mut payload = vec![0xed, 0x01];
+        payload.extend_from_slice(&[0x01; 31]);
+        let did = format!("did:key:z{}", bs58::encode(&payload).into_string());
+        assert!(decode_did_key_to_jwk(&did).is_err());
+    }
+
+    #[test]
+    fn decode_did_key_round_trips_ed25519() {
+        let key = crate::signing::generate_ed25519_keypair();
+        let jwk = export_ed25519_public_key_jwk(&key.verifying_key());
+        let did = encode_did_key_from_jwk(&jwk).unwrap();
+        assert_eq!(decode_did_key_to_jwk(&did).unwrap(), jwk);
+    }
+
+    #[test]
+    fn verify_with_did_checks_against_the_did_key() {
+        let key = generate_p256_keypair();
+        let did = encode_did_key(&key).unwrap();
+        let signature = sign(&key, b"entry").unwrap();
+        assert!(verify_with_did(&did, b"entry", &signature).unwrap());
+        assert!(!verify_with_did(&did, b"other", &signature).unwrap());
+
+        let other = encode_did_key(&generate_p256_keypair()).unwrap();
+        assert!(!verify_with_did(&other, b"entry", &signature).unwrap());
+
+        let ed_key = crate::signing::generate_ed25519_keypair();
+        let ed_did =
+            encode_did_key_from_jwk(&export_ed25519_public_key_jwk(&ed_key.verifying_key()))
+                .unwrap();
+        let ed_signature = crate::signing::sign_ed25519(&ed_key, b"entry").unwrap();
+        assert!(verify_with_did(&ed_did, b"entry", &ed_signature).unwrap());
+        assert!(!verify_with_did(&ed_did, b"entry", &signature).unwrap());
+
+        assert!(verify_with_did("did:web:example.com", b"entry", &signature).is_err());
+    }
+
+    #[test]
+    fn did_key_algorithm_detects_p256() {
+        let did = encode_did_key(&generate_p256_keypair()).unwrap();
+        assert_eq!(did_key_algorithm(&did).unwrap(), DidAlgorithm::P256);
+        assert_eq!(
+            did_key_algorithm("did:key:zDnaerx9CtbPJ1q36T5Ln5wYt3MQYeGRG5ehnPAmxcf5mDZpv").unwrap(),
+            DidAlgorithm::P256
+        );
+    }
+
+    #[test]
+    fn did_key_algorithm_detects_ed25519() {
+        // Test vector from the did:key spec.
+        let did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+        assert_eq!(did_key_algorithm(did).unwrap(), DidAlgorithm::Ed25519);
+    }
+
+    #[test]
+    fn encode_did_key_from_ed25519_jwk() {
+        let key = crate::signing::generate_ed25519_keypair();
+        let jwk = crate::signing::export_ed25519_public_key_jwk(&key.verifying_key());
+        let did = encode_did_key_from_jwk(&jwk).unwrap();
+        assert_eq!(did_key_algorithm(&did).unwrap(), DidAlgorithm::Ed25519);
+
+        let payload = bs58::decode(did.strip_prefix("did:key:z").unwrap())
+            .into_vec()
+            .unwrap();
+        assert_eq!(&payload[..2], &[0xed, 0x01]);
+        assert_eq!(&payload[2..], key.verifying_key().as_bytes());
+
+        let mut bad = jwk.clone();
+        bad["x"] = Value::String(base64url_encode(&[1u8; 31]));
+        assert!(encode_did_key_from_jwk(&bad).is_err());
+    }
+
+    #[test]
+    fn did_key_algorithm_reports_unknown_codec() {
+        // secp256k1-pub (0xe7)
+        let mut payload = vec![0xe7, 0x01];
+        payload.extend_from_slice(&[0x02; 33]);
+        let did = format!("did:key:z{}", bs58::encode(&payload).into_string());
+        assert_eq!(
+            did_key_algorithm(&did).unwrap(),
+            DidAlgorithm::Unknown(0xe7)
+        );
+    }
+
+    #[test]
+    fn did_key_algorithm_rejects_malformed() {
+        assert!(did_key_algorithm("did:web:example.com").is_err());
+        assert!(did_key_algorithm("did:key:z0OIl").is_err()); // not base58
+        assert!(did_key_algorithm("did:key:z").is_err());
+        // Codec varint with no key after it.
+        let did = format!("did:key:z{}", bs58::encode([0x80, 0x24]).into_string());
+        assert!(did_key_algorithm(&did).is_err());
+    }
+
     #[test]
     fn issue_root_ucan_structure() {
         let key = generate_p256_keypair();
@@ -591,4 +1112,391 @@ mod tests {
         let (_, payload) = parse_jwt(&result.unwrap());
         assert_eq!(payload["prf"], serde_json::json!(["not.a-valid-jwt.token"]));
     }
+
+    // -- verify_ucan_chain --
+
+    const SPACE: &str = "test-space";
+
+    struct Party {
+        key: SigningKey,
+        did: String,
+    }
+
+    fn party() -> Party {
+        let key = generate_p256_keypair();
+        let did = encode_did_key(&key).unwrap();
+        Party { key, did }
+    }
+
+    fn root(owner: &Party, aud: &str, permission: UCANPermission, ttl: u64, now: u64) -> String {
+        issue_root_ucan(&owner.key, &owner.did, aud, SPACE, permission, ttl, now).unwrap()
+    }
+
+    fn delegate(
+        from: &Party,
+        aud: &str,
+        permission: UCANPermission,
+        ttl: u64,
+        proof: &str,
+        now: u64,
+    ) -> String {
+        delegate_ucan(
+            &from.key, &from.did, aud, SPACE, permission, ttl, proof, now,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn verify_chain_accepts_valid_delegation() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Admin, 3600, now);
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Write,
+            600,
+            &root_ucan,
+            now,
+        );
+
+        let info = verify_ucan_chain(&leaf, SPACE, now).unwrap();
+        assert_eq!(
+            info,
+            UCANChainInfo {
+                root_issuer: owner.did.clone(),
+                audience: "did:key:zRecipient".to_string(),
+                permission: UCANPermission::Write,
+                expires_at: now + 600,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_chain_accepts_root_alone() {
+        let owner = party();
+        let now = now_secs();
+        let root_ucan = root(&owner, &owner.did, UCANPermission::Read, 60, now);
+        let info = verify_ucan_chain(&root_ucan, SPACE, now).unwrap();
+        assert_eq!(info.root_issuer, owner.did);
+        assert_eq!(info.audience, owner.did);
+        assert_eq!(info.permission, UCANPermission::Read);
+        assert_eq!(info.expires_at, now + 60);
+    }
+
+    #[test]
+    fn verify_chain_reports_earliest_expiry() {
+        let (owner, alice, bob) = (party(), party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Admin, 3600, now);
+        let middle = delegate(
+            &alice,
+            &bob.did,
+            UCANPermission::Write,
+            300,
+            &root_ucan,
+            now,
+        );
+        let leaf = delegate(
+            &bob,
+            "did:key:zRecipient",
+            UCANPermission::Read,
+            3000,
+            &middle,
+            now,
+        );
+
+        let info = verify_ucan_chain(&leaf, SPACE, now).unwrap();
+        assert_eq!(info.root_issuer, owner.did);
+        assert_eq!(info.expires_at, now + 300);
+    }
+
+    #[test]
+    fn verify_chain_rejects_permission_escalation() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Read, 3600, now);
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Write,
+            600,
+            &root_ucan,
+            now,
+        );
+        assert!(matches!(
+            verify_ucan_chain(&leaf, SPACE, now),
+            Err(CryptoError::InvalidUcan(_))
+        ));
+    }
+
+    #[test]
+    fn verify_chain_rejects_issuer_outside_parent_audience() {
+        let (owner, alice, mallory) = (party(), party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Admin, 3600, now);
+        let leaf = delegate(
+            &mallory,
+            "did:key:zRecipient",
+            UCANPermission::Read,
+            600,
+            &root_ucan,
+            now,
+        );
+        assert!(verify_ucan_chain(&leaf, SPACE, now).is_err());
+    }
+
+    #[test]
+    fn verify_chain_rejects_other_space() {
+        let owner = party();
+        let now = now_secs();
+        let root_ucan = root(&owner, &owner.did, UCANPermission::Admin, 3600, now);
+        assert!(verify_ucan_chain(&root_ucan, "other-space", now).is_err());
+    }
+
+    #[test]
+    fn verify_chain_rejects_expired_link() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Admin, 60, now);
+        // Delegated before the root expired, checked after
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Read,
+            30,
+            &root_ucan,
+            now,
+        );
+        assert!(verify_ucan_chain(&leaf, SPACE, now + 10).is_ok());
+        assert!(matches!(
+            verify_ucan_chain(&leaf, SPACE, now + 60),
+            Err(CryptoError::UcanExpired { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_chain_rejects_malformed_proof() {
+        let alice = party();
+        let now = now_secs();
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Read,
+            600,
+            "not.a-valid-jwt.token",
+            now,
+        );
+        assert!(matches!(
+            verify_ucan_chain(&leaf, SPACE, now),
+            Err(CryptoError::InvalidUcan(_))
+        ));
+    }
+
+    // -- verify_ucan --
+
+    #[test]
+    fn verify_ucan_accepts_root() {
+        let owner = party();
+        let now = now_secs();
+        let root_ucan = root(&owner, &owner.did, UCANPermission::Admin, 60, now);
+        assert_eq!(
+            verify_ucan(&root_ucan, now).unwrap(),
+            UCANClaims {
+                issuer: owner.did.clone(),
+                root_issuer: owner.did.clone(),
+                audience: owner.did.clone(),
+                permission: UCANPermission::Admin,
+                space_id: SPACE.to_string(),
+                expires_at: now + 60,
+            }
+        );
+    }
+
+    #[test]
+    fn verify_ucan_accepts_two_link_delegation() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Write, 300, now);
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Read,
+            3600,
+            &root_ucan,
+            now,
+        );
+
+        let claims = verify_ucan(&leaf, now).unwrap();
+        assert_eq!(claims.issuer, alice.did);
+        assert_eq!(claims.root_issuer, owner.did);
+        assert_eq!(claims.audience, "did:key:zRecipient");
+        assert_eq!(claims.permission, UCANPermission::Read);
+        assert_eq!(claims.space_id, SPACE);
+        assert_eq!(claims.expires_at, now + 300);
+    }
+
+    #[test]
+    fn verify_ucan_rejects_expired() {
+        let owner = party();
+        let now = now_secs();
+        let root_ucan = root(&owner, &owner.did, UCANPermission::Read, 60, now);
+        assert!(verify_ucan(&root_ucan, now + 59).is_ok());
+        assert!(matches!(
+            verify_ucan(&root_ucan, now + 60),
+            Err(CryptoError::UcanExpired { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_ucan_rejects_privilege_escalation() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let root_ucan = root(&owner, &alice.did, UCANPermission::Write, 3600, now);
+        let leaf = delegate(
+            &alice,
+            "did:key:zRecipient",
+            UCANPermission::Admin,
+            600,
+            &root_ucan,
+            now,
+        );
+        assert!(matches!(
+            verify_ucan(&leaf, now),
+            Err(CryptoError::InvalidUcan(_))
+        ));
+    }
+
+    #[test]
+    fn verify_ucan_rejects_non_space_resource() {
+        assert!(matches!(
+            verify_ucan("not-a-jwt", now_secs()),
+            Err(CryptoError::InvalidUcan(_))
+        ));
+        let payload = base64url_encode(br#"{"with":"file:x","iss":"did:key:z"}"#);
+        let token = format!("e30.{}.AA", payload);
+        assert!(matches!(
+            verify_ucan(&token, now_secs()),
+            Err(CryptoError::InvalidUcan(_))
+        ));
+    }
+
+    // -- authorize --
+
+    #[test]
+    fn permission_satisfies_lattice() {
+        use UCANPermission::*;
+        for (granted, required, ok) in [
+            (Admin, Admin, true),
+            (Admin, Write, true),
+            (Admin, Read, true),
+            (Write, Admin, false),
+            (Write, Write, true),
+            (Write, Read, true),
+            (Read, Admin, false),
+            (Read, Write, false),
+            (Read, Read, true),
+        ] {
+            assert_eq!(
+                granted.satisfies(required),
+                ok,
+                "{granted:?} ⊇ {required:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn authorize_write_token_allows_read_but_not_admin() {
+        let (owner, alice) = (party(), party());
+        let now = now_secs();
+        let token = root(&owner, &alice.did, UCANPermission::Write, 300, now);
+
+        let roots = [owner.did.as_str()];
+        assert!(authorize(&token, SPACE, &roots, UCANPermission::Read, now).is_ok());
+        assert!(authorize(&token, SPACE, &roots, UCANPermission::Write, now).is_ok());
+        assert!(matches!(
+            authorize(&token, SPACE, &roots, UCANPermission::Admin, now),
+            Err(CryptoError::UcanPermissionDenied {
+                granted: "/space/write",
+                required: "/space/admin",
+            })
+        ));
+    }
+
+    #[test]
+    fn authorize_rejects_mismatched_space() {
+        let owner = party();
+        let now = now_secs();
+        let token = root(&owner, &owner.did, UCANPermission::Admin, 300, now);
+
+        for other in [
+            "other-space",
+            "",
+            &SPACE[..SPACE.len() - 1],
+            format!("{SPACE} ").as_str(),
+        ] {
+            assert!(matches!(
+                authorize(&token, other, &[&owner.did], UCANPermission::Read, now),
+                Err(CryptoError::UcanSpaceMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn authorize_rejects_expired() {
+        let owner = party();
+        let now = now_secs();
+        let token = root(&owner, &owner.did, UCANPermission::Admin, 60, now);
+        assert!(matches!(
+            authorize(&token, SPACE, &[&owner.did], UCANPermission::Read, now + 60),
+            Err(CryptoError::UcanExpired { .. })
+        ));
+    }
+
+    #[test]
+    fn authorize_rejects_untrusted_root() {
+        let (owner, mallory) = (party(), party());
+        let now = now_secs();
+        // Validly signed, but Mallory rooted the chain in herself
+        let token = root(&mallory, &mallory.did, UCANPermission::Admin, 300, now);
+        assert!(verify_ucan(&token, now).is_ok());
+        assert!(matches!(
+            authorize(&token, SPACE, &[&owner.did], UCANPermission::Read, now),
+            Err(CryptoError::UcanUntrustedRoot(root)) if root == mallory.did
+        ));
+        assert!(matches!(
+            authorize(&token, SPACE, &[], UCANPermission::Read, now),
+            Err(CryptoError::UcanUntrustedRoot(_))
+        ));
+
+        // A delegation from a trusted root is fine whoever holds it
+        let granted = root(&owner, &mallory.did, UCANPermission::Write, 300, now);
+        let delegated = delegate(
+            &mallory,
+            &owner.did,
+            UCANPermission::Read,
+            300,
+            &granted,
+            now,
+        );
+        assert!(authorize(&delegated, SPACE, &[&owner.did], UCANPermission::Read, now).is_ok());
+    }
+
+    #[test]
+    fn verify_chain_rejects_forged_signature() {
+        let (owner, mallory) = (party(), party());
+        let now = now_secs();
+        // Mallory signs a root claiming to be the owner
+        let forged = issue_root_ucan(
+            &mallory.key,
+            &owner.did,
+            &mallory.did,
+            SPACE,
+            UCANPermission::Admin,
+            3600,
+            now,
+        )
+        .unwrap();
+        assert!(verify_ucan_chain(&forged, SPACE, now).is_err());
+    }
 }
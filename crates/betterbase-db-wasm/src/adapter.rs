@@ -16,18 +16,25 @@ use wasm_bindgen::prelude::*;
 
 use betterbase_db::{
     collection::builder::CollectionDef,
-    query::types::{Query, SortDirection, SortEntry, SortInput},
-    reactive::adapter::ReactiveAdapter,
+    query::{
+        aggregate::AggregateSpec,
+        types::{NullsOrder, Query, SortDirection, SortEntry, SortInput},
+    },
+    reactive::{adapter::ReactiveAdapter, ObserveOptions, StaleThreshold, SubscriptionDiagnostics},
     storage::traits::{StorageLifecycle, StorageRead, StorageSync, StorageWrite},
     types::{
-        DeleteOptions, GetOptions, ListOptions, PatchOptions, PutOptions, StoredRecordWithMeta,
+        DeleteKind, DeleteOptions, GetOptions, ListOptions, PatchOptions, PutOptions, ResetOptions,
+        StoredRecordWithMeta,
     },
 };
 
 use crate::{
     collection::WasmCollectionDef,
     conversions::{js_to_value, value_to_js},
-    error::IntoJsResult,
+    error::{
+        js_collection_error, js_error, to_js_error, with_op_index, IntoJsResult,
+        COLLECTION_NOT_REGISTERED, INVALID_ARGUMENT, SERIALIZATION, STORAGE_OPFS,
+    },
     wasm_sqlite::Connection,
     wasm_sqlite_backend::WasmSqliteBackend,
 };
@@ -63,7 +70,8 @@ impl WasmDb {
                 .chars()
                 .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
         {
-            return Err(JsValue::from_str(
+            return Err(js_error(
+                INVALID_ARGUMENT,
                 "db_name must be non-empty and contain only alphanumeric, underscore, or hyphen characters",
             ));
         }
@@ -104,29 +112,31 @@ impl WasmDb {
                         sleep_ms(delay as i32).await;
                         last_err = Some(msg);
                     } else {
-                        return Err(JsValue::from_str(&format!(
-                            "Failed to install OPFS VFS after 5 attempts: {msg}"
-                        )));
+                        return Err(js_error(
+                            STORAGE_OPFS,
+                            &format!("Failed to install OPFS VFS after 5 attempts: {msg}"),
+                        ));
                     }
                 }
             }
         }
         if let Some(msg) = last_err {
-            return Err(JsValue::from_str(&format!(
-                "Failed to install OPFS VFS after retries: {msg}"
-            )));
+            return Err(js_error(
+                STORAGE_OPFS,
+                &format!("Failed to install OPFS VFS after retries: {msg}"),
+            ));
         }
 
         // Open SQLite connection (sync after VFS is installed)
         let db_path = format!("/{db_name}.sqlite3");
         let conn = Connection::open(&db_path)
-            .map_err(|e| JsValue::from_str(&format!("Failed to open SQLite: {e}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to open SQLite: {e}")))?;
 
         // Create backend and initialize schema
         let backend = WasmSqliteBackend::new(conn);
         backend
             .init_schema()
-            .map_err(|e| JsValue::from_str(&format!("Failed to init schema: {e}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to init schema: {e}")))?;
 
         let adapter = ReactiveAdapter::new(betterbase_db::storage::adapter::Adapter::new(backend));
 
@@ -191,12 +201,20 @@ impl WasmDb {
         // Get a reference to the existing VFS pool (already registered by create()).
         let pool_util = install::<sqlite_wasm_rs::WasmOsCallback>(&cfg, false)
             .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to get OPFS pool util: {e:?}")))?;
+            .map_err(|e| {
+                js_error(
+                    STORAGE_OPFS,
+                    &format!("Failed to get OPFS pool util: {e:?}"),
+                )
+            })?;
 
         // Pause = unregister VFS + close all OPFS access handles.
-        pool_util
-            .pause_vfs()
-            .map_err(|e| JsValue::from_str(&format!("Failed to release access handles: {e:?}")))?;
+        pool_util.pause_vfs().map_err(|e| {
+            js_error(
+                STORAGE_OPFS,
+                &format!("Failed to release access handles: {e:?}"),
+            )
+        })?;
 
         Ok(())
     }
@@ -215,12 +233,17 @@ impl WasmDb {
 
         let pool_util = install::<sqlite_wasm_rs::WasmOsCallback>(&cfg, false)
             .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to get OPFS pool util: {e:?}")))?;
+            .map_err(|e| {
+                js_error(
+                    STORAGE_OPFS,
+                    &format!("Failed to get OPFS pool util: {e:?}"),
+                )
+            })?;
 
         let db_path = format!("/{}.sqlite3", self.db_name);
         pool_util
             .delete_db(&db_path)
-            .map_err(|e| JsValue::from_str(&format!("Failed to delete database: {e:?}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to delete database: {e:?}")))?;
 
         Ok(())
     }
@@ -254,6 +277,29 @@ impl WasmDb {
         }
     }
 
+    /// Get the record `id`, inserting `data` under that id if it does not
+    /// exist yet. Returns `{ record, created }`.
+    #[wasm_bindgen(js_name = "getOrCreate")]
+    pub fn get_or_create(
+        &self,
+        collection: &str,
+        id: &str,
+        data: JsValue,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let data_val = js_to_value(data)?;
+        let opts = parse_put_options(options)?;
+        let (record, created) = self
+            .adapter
+            .get_or_create(&def, id, data_val, &opts)
+            .into_js()?;
+        let out = js_sys::Object::new();
+        js_sys::Reflect::set(&out, &"record".into(), &record_to_js_data(record)?)?;
+        js_sys::Reflect::set(&out, &"created".into(), &JsValue::from_bool(created))?;
+        Ok(out.into())
+    }
+
     /// Patch (partial update) a record.
     pub fn patch(
         &self,
@@ -275,6 +321,19 @@ impl WasmDb {
         self.adapter.delete(&def, id, &opts).into_js()
     }
 
+    /// Convert an archived record into a tombstone (space admins only).
+    #[wasm_bindgen(js_name = "tombstoneArchived")]
+    pub fn tombstone_archived(
+        &self,
+        collection: &str,
+        id: &str,
+        options: JsValue,
+    ) -> Result<bool, JsValue> {
+        let def = self.get_def(collection)?;
+        let opts = parse_delete_options(id, options)?;
+        self.adapter.tombstone_archived(&def, id, &opts).into_js()
+    }
+
     // ========================================================================
     // Query
     // ========================================================================
@@ -310,6 +369,23 @@ impl WasmDb {
         Ok(result as f64)
     }
 
+    /// Compute grouped metrics over a collection.
+    ///
+    /// `spec` is `{ groupBy: string[], metrics: Metric[], filter? }`, where a
+    /// metric is `{ op: "count" }` or `{ op: "sum" | "avg" | "min" | "max", field }`
+    /// and `filter` uses the query filter language. Returns
+    /// `{ group, values, skipped }[]` with values and non-numeric skip counts
+    /// in `metrics` order.
+    pub fn aggregate(&self, collection: &str, spec: JsValue) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let spec: AggregateSpec = serde_json::from_value(js_to_value(spec)?)
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid aggregate spec: {e}")))?;
+        let rows = self.adapter.aggregate(&def, &spec).into_js()?;
+        let val = serde_json::to_value(&rows)
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
+        value_to_js(&val)
+    }
+
     /// Get all records in a collection.
     #[wasm_bindgen(js_name = "getAll")]
     pub fn get_all(&self, collection: &str, options: JsValue) -> Result<JsValue, JsValue> {
@@ -334,7 +410,7 @@ impl WasmDb {
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let records_val: Vec<Value> = serde_wasm_bindgen::from_value(records)
-            .map_err(|e| JsValue::from_str(&format!("Invalid records array: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid records array: {e}")))?;
         let opts = parse_put_options(options)?;
         let result = self.adapter.bulk_put(&def, records_val, &opts).into_js()?;
 
@@ -360,78 +436,311 @@ impl WasmDb {
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let id_strings: Vec<String> = serde_wasm_bindgen::from_value(ids)
-            .map_err(|e| JsValue::from_str(&format!("Invalid ids array: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid ids array: {e}")))?;
         let id_refs: Vec<&str> = id_strings.iter().map(|s| s.as_str()).collect();
         let opts = parse_delete_options("", options)?;
         let result = self.adapter.bulk_delete(&def, &id_refs, &opts).into_js()?;
         let val = serde_json::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
+    /// Apply `ops` atomically, in order, in one storage transaction.
+    ///
+    /// Each op is `{ type: "put" | "patch" | "delete", collection, data?, id?,
+    /// options? }`, taking the same `data` / `options` as the single-record
+    /// method; `id` names the record for `delete` and `patch`. Returns one
+    /// result per op: the record for `put` / `patch`, the boolean for `delete`.
+    ///
+    /// If any op fails, nothing is applied and the thrown error carries the
+    /// failing op's `opIndex`. On success one bulk change event fires per
+    /// touched collection.
+    pub fn batch(&self, ops: JsValue) -> Result<JsValue, JsValue> {
+        if !js_sys::Array::is_array(&ops) {
+            return Err(js_error(INVALID_ARGUMENT, "Batch ops must be an array"));
+        }
+        let ops = js_sys::Array::from(&ops)
+            .iter()
+            .enumerate()
+            .map(|(index, op)| self.parse_batch_op(op).map_err(|e| with_op_index(e, index)))
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        let failed = Cell::new(None);
+        let results = self
+            .adapter
+            .batch_transaction(|tx| {
+                let mut results = Vec::with_capacity(ops.len());
+                for (index, op) in ops.into_iter().enumerate() {
+                    failed.set(Some(index));
+                    results.push(match op {
+                        BatchOp::Put { def, data, opts } => {
+                            BatchOutcome::Record(tx.put(&def, data, &opts)?)
+                        }
+                        BatchOp::Patch { def, data, opts } => {
+                            BatchOutcome::Record(tx.patch(&def, data, &opts)?)
+                        }
+                        BatchOp::Delete { def, opts } => {
+                            BatchOutcome::Deleted(tx.delete(&def, &opts.id, &opts)?)
+                        }
+                    });
+                }
+                failed.set(None);
+                Ok(results)
+            })
+            .map_err(|e| match failed.get() {
+                Some(index) => with_op_index(to_js_error(e), index),
+                None => to_js_error(e),
+            })?;
+
+        let out = js_sys::Array::new();
+        for outcome in results {
+            out.push(&match outcome {
+                BatchOutcome::Record(record) => record_to_js_data(record)?,
+                BatchOutcome::Deleted(deleted) => JsValue::from_bool(deleted),
+            });
+        }
+        Ok(out.into())
+    }
+
     // ========================================================================
     // Observe (reactive subscriptions)
     // ========================================================================
 
     /// Observe a single record by id. Returns an unsubscribe function.
+    ///
+    /// `options` may carry `{ label }`, an owner tag shown in
+    /// `subscriptionReport()`. Throws when the subscription cap is reached.
     pub fn observe(
         &self,
         collection: &str,
         id: &str,
         callback: js_sys::Function,
+        options: JsValue,
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
+        let opts = parse_observe_options(options)?;
         let cb = Arc::new(SendSyncCallback(callback));
-        let unsub = self.adapter.observe(
-            def,
-            id,
-            Arc::new(move |record: Option<Value>| {
-                let js_val = match record {
-                    Some(ref data) => value_to_js(data).unwrap_or(JsValue::NULL),
-                    None => JsValue::NULL,
-                };
-                let _ = cb.0.call1(&JsValue::NULL, &js_val);
-            }),
-            None,
-        );
+        let unsub = self
+            .adapter
+            .observe_with_options(
+                def,
+                id,
+                Arc::new(move |record: Option<Value>| {
+                    let js_val = match record {
+                        Some(ref data) => value_to_js(data).unwrap_or(JsValue::NULL),
+                        None => JsValue::NULL,
+                    };
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                &opts,
+            )
+            .into_js()?;
 
         let unsub_fn = idempotent_unsub(unsub);
         Ok(unsub_fn)
     }
 
     /// Observe a query. Returns an unsubscribe function.
+    ///
+    /// Takes the same `options` as `observe`.
     #[wasm_bindgen(js_name = "observeQuery")]
     pub fn observe_query(
         &self,
         collection: &str,
         query: JsValue,
         callback: js_sys::Function,
+        options: JsValue,
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let q = parse_query(query)?;
+        let opts = parse_observe_options(options)?;
         let cb = Arc::new(SendSyncCallback(callback));
 
-        let unsub = self.adapter.observe_query(
-            def,
-            q,
-            Arc::new(move |result| {
-                let records = result.records.clone();
-                let mut out = serde_json::Map::new();
-                out.insert("records".to_string(), Value::Array(records));
-                out.insert(
-                    "total".to_string(),
-                    Value::Number(serde_json::Number::from(result.total)),
-                );
-                let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
-                let _ = cb.0.call1(&JsValue::NULL, &js_val);
-            }),
-            None,
-        );
+        let unsub = self
+            .adapter
+            .observe_query_with_options(
+                def,
+                q,
+                Arc::new(move |result| {
+                    let records = result.records.clone();
+                    let mut out = serde_json::Map::new();
+                    out.insert("records".to_string(), Value::Array(records));
+                    out.insert(
+                        "total".to_string(),
+                        Value::Number(serde_json::Number::from(result.total)),
+                    );
+                    let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                &opts,
+            )
+            .into_js()?;
+
+        let unsub_fn = idempotent_unsub(unsub);
+        Ok(unsub_fn)
+    }
+
+    /// Observe rows `offset..offset + limit` of a sorted query, for virtual
+    /// scrolling. The callback receives `{ records, offset, total }` and only
+    /// fires when the window or the total changes. Returns an unsubscribe
+    /// function; takes the same `options` as `observe`.
+    #[wasm_bindgen(js_name = "observeWindow")]
+    pub fn observe_window(
+        &self,
+        collection: &str,
+        query: JsValue,
+        offset: usize,
+        limit: usize,
+        callback: js_sys::Function,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let q = parse_query(query)?;
+        let opts = parse_observe_options(options)?;
+        let cb = Arc::new(SendSyncCallback(callback));
+
+        let unsub = self
+            .adapter
+            .observe_window_with_options(
+                def,
+                q,
+                offset,
+                limit,
+                Arc::new(move |window| {
+                    let mut out = serde_json::Map::new();
+                    out.insert("records".to_string(), Value::Array(window.records));
+                    out.insert("offset".to_string(), Value::from(window.offset));
+                    out.insert("total".to_string(), Value::from(window.total));
+                    let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                &opts,
+            )
+            .into_js()?;
 
         let unsub_fn = idempotent_unsub(unsub);
         Ok(unsub_fn)
     }
 
+    /// Observe a query, receiving only what changed since the last call.
+    /// The callback receives `{ added, removed, updated, moved, total }`:
+    /// `added` records, `removed` ids, `updated` as `{ old, new }` pairs and
+    /// `moved` ids. The first call has the whole result in `added`. Returns
+    /// an unsubscribe function; takes the same `options` as `observe`.
+    #[wasm_bindgen(js_name = "observeQueryDiff")]
+    pub fn observe_query_diff(
+        &self,
+        collection: &str,
+        query: JsValue,
+        callback: js_sys::Function,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let q = parse_query(query)?;
+        let opts = parse_observe_options(options)?;
+        let cb = Arc::new(SendSyncCallback(callback));
+
+        let unsub = self
+            .adapter
+            .observe_query_diff_with_options(
+                def,
+                q,
+                Arc::new(move |diff| {
+                    let updated = diff
+                        .updated
+                        .into_iter()
+                        .map(|u| serde_json::json!({ "old": u.old, "new": u.new }))
+                        .collect();
+                    let mut out = serde_json::Map::new();
+                    out.insert("added".to_string(), Value::Array(diff.added));
+                    out.insert("removed".to_string(), Value::from(diff.removed));
+                    out.insert("updated".to_string(), Value::Array(updated));
+                    out.insert("moved".to_string(), Value::from(diff.moved));
+                    out.insert("total".to_string(), Value::from(diff.total));
+                    let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                &opts,
+            )
+            .into_js()?;
+
+        let unsub_fn = idempotent_unsub(unsub);
+        Ok(unsub_fn)
+    }
+
+    /// Keep an aggregate live. `spec` is the same as for `aggregate`;
+    /// `filter`, if not null, must match as well as `spec.filter`. The
+    /// callback receives the `{ group, values, skipped }[]` rows and only
+    /// fires when a group appears, disappears or changes a metric. Returns
+    /// an unsubscribe function; takes the same `options` as `observe`.
+    #[wasm_bindgen(js_name = "observeAggregate")]
+    pub fn observe_aggregate(
+        &self,
+        collection: &str,
+        spec: JsValue,
+        filter: JsValue,
+        callback: js_sys::Function,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let spec: AggregateSpec = serde_json::from_value(js_to_value(spec)?)
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid aggregate spec: {e}")))?;
+        let filter = if filter.is_undefined() || filter.is_null() {
+            None
+        } else {
+            Some(js_to_value(filter)?)
+        };
+        let opts = parse_observe_options(options)?;
+        let cb = Arc::new(SendSyncCallback(callback));
+
+        let unsub = self
+            .adapter
+            .observe_aggregate_with_options(
+                def,
+                spec,
+                filter,
+                Arc::new(move |rows| {
+                    let js_val = serde_json::to_value(&rows)
+                        .ok()
+                        .and_then(|v| value_to_js(&v).ok())
+                        .unwrap_or(JsValue::NULL);
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                &opts,
+            )
+            .into_js()?;
+
+        let unsub_fn = idempotent_unsub(unsub);
+        Ok(unsub_fn)
+    }
+
+    /// Every live subscription with its label, age, and fire statistics,
+    /// plus `byLabel` counts (largest first).
+    #[wasm_bindgen(js_name = "subscriptionReport")]
+    pub fn subscription_report(&self) -> Result<JsValue, JsValue> {
+        let val = serde_json::to_value(self.adapter.subscription_report())
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
+        value_to_js(&val)
+    }
+
+    /// Configure subscription leak checks:
+    /// `{ maxSubscriptions?, staleAfterMs?, staleAfterChanges? }`.
+    ///
+    /// Past `maxSubscriptions`, `observe` and `observeQuery` throw. Setting
+    /// either stale field enables `subscriptionStale` lifecycle events (the
+    /// other defaults to 0). Passing `null` turns both checks off.
+    #[wasm_bindgen(js_name = "setSubscriptionDiagnostics")]
+    pub fn set_subscription_diagnostics(&self, options: JsValue) -> Result<(), JsValue> {
+        let diagnostics = parse_subscription_diagnostics(options)?;
+        self.adapter.set_subscription_diagnostics(diagnostics);
+        Ok(())
+    }
+
     /// Flush all dirty reactive subscriptions, firing their callbacks synchronously.
     ///
     /// Called by the worker after registering observe/observeQuery subscriptions
@@ -440,6 +749,19 @@ impl WasmDb {
         self.adapter.flush();
     }
 
+    /// Flush, then resolve once the callbacks it fired have settled.
+    ///
+    /// The flush itself is synchronous; awaiting one microtask afterwards
+    /// lets promise reactions queued by those callbacks run before the
+    /// returned promise resolves. Lets callers await initial snapshots
+    /// instead of guessing with `setTimeout(0)`.
+    #[wasm_bindgen(js_name = "flushAsync")]
+    pub async fn flush_async(&self) -> Result<(), JsValue> {
+        self.adapter.flush();
+        wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&JsValue::UNDEFINED)).await?;
+        Ok(())
+    }
+
     /// Register a global change listener. Returns an unsubscribe function.
     #[wasm_bindgen(js_name = "onChange")]
     pub fn on_change(&self, callback: js_sys::Function) -> JsValue {
@@ -451,6 +773,104 @@ impl WasmDb {
         idempotent_unsub(unsub)
     }
 
+    /// Register a collection lifecycle listener. Events are delivered as
+    /// objects tagged by `type` (e.g. `{ type: "migrationFinished", ... }`).
+    /// Returns an unsubscribe function.
+    #[wasm_bindgen(js_name = "onLifecycle")]
+    pub fn on_lifecycle(&self, callback: js_sys::Function) -> JsValue {
+        let cb = Arc::new(SendSyncCallback(callback));
+        let unsub = self.adapter.on_lifecycle(move |event| {
+            let val = serde_json::to_value(event).unwrap_or(Value::Null);
+            let js_val = value_to_js(&val).unwrap_or(JsValue::NULL);
+            let _ = cb.0.call1(&JsValue::NULL, &js_val);
+        });
+
+        idempotent_unsub(unsub)
+    }
+
+    // ========================================================================
+    // Maintenance
+    // ========================================================================
+
+    /// Eagerly migrate every stale record in a collection to the current
+    /// schema version. Returns `{ migrated: string[], failed: RecordError[] }`;
+    /// failed records keep their stored version.
+    #[wasm_bindgen(js_name = "migrateAll")]
+    pub fn migrate_all(&self, collection: &str) -> Result<JsValue, JsValue> {
+        let def = self.get_def(collection)?;
+        let report = self.adapter.migrate_all(&def).into_js()?;
+        let val = serde_json::to_value(&report)
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
+        value_to_js(&val)
+    }
+
+    /// Drop and recreate every index of a collection and recompute its
+    /// stored computed-index values, e.g. after adding a computed index.
+    /// Returns the number of records reindexed.
+    pub fn reindex(&self, collection: &str) -> Result<f64, JsValue> {
+        let def = self.get_def(collection)?;
+        let count = self.adapter.reindex(&def).into_js()?;
+        Ok(count as f64)
+    }
+
+    /// Hard-remove every record of a collection, with its history and index
+    /// entries, in one transaction. Nothing is pushed. With
+    /// `resetSyncState`, the pull cursor is cleared too. Returns the number
+    /// of records removed.
+    #[wasm_bindgen(js_name = "resetCollection")]
+    pub fn reset_collection(
+        &self,
+        collection: &str,
+        reset_sync_state: Option<bool>,
+    ) -> Result<f64, JsValue> {
+        let def = self.get_def(collection)?;
+        let opts = ResetOptions {
+            reset_sync_state: reset_sync_state.unwrap_or(false),
+        };
+        let count = self.adapter.reset_collection(&def, &opts).into_js()?;
+        Ok(count as f64)
+    }
+
+    /// Move every record stored under `from` to the registered collection
+    /// `to`, which must be empty, and hard-remove the originals. Dirty
+    /// records are pushed under the new name. Returns the number of records
+    /// moved.
+    #[wasm_bindgen(js_name = "renameCollection")]
+    pub fn rename_collection(&self, from: &str, to: &str) -> Result<f64, JsValue> {
+        let def = self.get_def(to)?;
+        let count = self.adapter.rename_collection(from, &def).into_js()?;
+        Ok(count as f64)
+    }
+
+    /// Hard-remove every record of a collection together with its pull
+    /// cursor and push retry state. Returns the number of records removed.
+    #[wasm_bindgen(js_name = "dropCollection")]
+    pub fn drop_collection(&self, collection: &str) -> Result<f64, JsValue> {
+        let def = self.get_def(collection)?;
+        let count = self.adapter.drop_collection(&def).into_js()?;
+        Ok(count as f64)
+    }
+
+    /// Purge acknowledged tombstones older than each collection's
+    /// `tombstoneRetention`. Collections without a retention are skipped.
+    /// Returns `{ [collection]: purgedCount }` for the collections purged.
+    #[wasm_bindgen(js_name = "purgeTombstones")]
+    pub fn purge_tombstones(&self) -> Result<JsValue, JsValue> {
+        let out = js_sys::Object::new();
+        for def in self.collections.values() {
+            if def.tombstone_retention.is_none() {
+                continue;
+            }
+            let purged = self.adapter.purge_expired_tombstones(def).into_js()?;
+            js_sys::Reflect::set(
+                &out,
+                &JsValue::from_str(&def.name),
+                &JsValue::from_f64(purged as f64),
+            )?;
+        }
+        Ok(out.into())
+    }
+
     // ========================================================================
     // Sync storage operations
     // ========================================================================
@@ -462,7 +882,7 @@ impl WasmDb {
         let def = self.get_def(collection)?;
         let result = self.adapter.get_dirty(&def).into_js()?;
         let val = serde_json::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
@@ -481,7 +901,7 @@ impl WasmDb {
         } else {
             let val = js_to_value(snapshot)?;
             let s: betterbase_db::types::PushSnapshot = serde_json::from_value(val)
-                .map_err(|e| JsValue::from_str(&format!("Invalid snapshot: {e}")))?;
+                .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid snapshot: {e}")))?;
             Some(s)
         };
         self.adapter
@@ -500,16 +920,16 @@ impl WasmDb {
         let def = self.get_def(collection)?;
         let records_val: Vec<betterbase_db::types::RemoteRecord> =
             serde_wasm_bindgen::from_value(records)
-                .map_err(|e| JsValue::from_str(&format!("Invalid remote records: {e}")))?;
+                .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid remote records: {e}")))?;
         let opts_val = js_to_value(options)?;
         let opts: betterbase_db::types::ApplyRemoteOptions = serde_json::from_value(opts_val)
-            .map_err(|e| JsValue::from_str(&format!("Invalid apply options: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid apply options: {e}")))?;
         let result = self
             .adapter
             .apply_remote_changes(&def, &records_val, &opts)
             .into_js()?;
         let val = serde_json::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
@@ -536,11 +956,80 @@ impl WasmDb {
 impl WasmDb {
     fn get_def(&self, collection: &str) -> Result<Arc<CollectionDef>, JsValue> {
         self.collections.get(collection).cloned().ok_or_else(|| {
-            JsValue::from_str(&format!(
-                "Collection \"{collection}\" not registered. Call initialize() first."
-            ))
+            js_collection_error(
+                COLLECTION_NOT_REGISTERED,
+                &format!("Collection \"{collection}\" not registered. Call initialize() first."),
+                collection,
+            )
         })
     }
+
+    /// Parse one `batch` op descriptor, resolving its collection.
+    fn parse_batch_op(&self, op: JsValue) -> Result<BatchOp, JsValue> {
+        let field = |name: &str| {
+            js_sys::Reflect::get(&op, &JsValue::from_str(name)).unwrap_or(JsValue::UNDEFINED)
+        };
+        let collection = field("collection")
+            .as_string()
+            .ok_or_else(|| js_error(INVALID_ARGUMENT, "Batch op must have a \"collection\""))?;
+        let def = self.get_def(&collection)?;
+        let id = field("id").as_string();
+        match field("type").as_string().as_deref() {
+            Some("put") => Ok(BatchOp::Put {
+                def,
+                data: js_to_value(field("data"))?,
+                opts: parse_put_options(field("options"))?,
+            }),
+            Some("patch") => {
+                let mut opts = parse_patch_options(field("options"))?;
+                if let Some(id) = id {
+                    opts.id = id;
+                }
+                Ok(BatchOp::Patch {
+                    def,
+                    data: js_to_value(field("data"))?,
+                    opts,
+                })
+            }
+            Some("delete") => {
+                let id = id.ok_or_else(|| {
+                    js_error(INVALID_ARGUMENT, "Batch delete op must have an \"id\"")
+                })?;
+                Ok(BatchOp::Delete {
+                    def,
+                    opts: parse_delete_options(&id, field("options"))?,
+                })
+            }
+            other => Err(js_error(
+                INVALID_ARGUMENT,
+                &format!("Batch op type must be \"put\", \"patch\" or \"delete\", got {other:?}"),
+            )),
+        }
+    }
+}
+
+/// A parsed `WasmDb::batch` op.
+enum BatchOp {
+    Put {
+        def: Arc<CollectionDef>,
+        data: Value,
+        opts: PutOptions,
+    },
+    Patch {
+        def: Arc<CollectionDef>,
+        data: Value,
+        opts: PatchOptions,
+    },
+    Delete {
+        def: Arc<CollectionDef>,
+        opts: DeleteOptions,
+    },
+}
+
+/// Result of one applied `WasmDb::batch` op.
+enum BatchOutcome {
+    Record(StoredRecordWithMeta),
+    Deleted(bool),
 }
 
 /// Wrap an unsubscribe closure so that calling it multiple times is safe.
@@ -610,7 +1099,7 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
     let val = js_to_value(js)?;
     let obj = val
         .as_object()
-        .ok_or_else(|| JsValue::from_str("Query must be an object"))?;
+        .ok_or_else(|| js_error(INVALID_ARGUMENT, "Query must be an object"))?;
 
     let filter = obj.get("filter").cloned();
 
@@ -621,13 +1110,15 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
             let entries: Result<Vec<SortEntry>, JsValue> = arr
                 .iter()
                 .map(|entry| {
-                    let entry_obj = entry
-                        .as_object()
-                        .ok_or_else(|| JsValue::from_str("Sort entry must be an object"))?;
+                    let entry_obj = entry.as_object().ok_or_else(|| {
+                        js_error(INVALID_ARGUMENT, "Sort entry must be an object")
+                    })?;
                     let field = entry_obj
                         .get("field")
                         .and_then(|v| v.as_str())
-                        .ok_or_else(|| JsValue::from_str("Sort entry must have a \"field\""))?
+                        .ok_or_else(|| {
+                            js_error(INVALID_ARGUMENT, "Sort entry must have a \"field\"")
+                        })?
                         .to_string();
                     let direction = match entry_obj
                         .get("direction")
@@ -637,7 +1128,16 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
                         "desc" => SortDirection::Desc,
                         _ => SortDirection::Asc,
                     };
-                    Ok(SortEntry { field, direction })
+                    let nulls = match entry_obj.get("nulls").and_then(|v| v.as_str()) {
+                        Some("first") => Some(NullsOrder::First),
+                        Some("last") => Some(NullsOrder::Last),
+                        _ => None,
+                    };
+                    Ok(SortEntry {
+                        field,
+                        direction,
+                        nulls,
+                    })
                 })
                 .collect();
             Some(SortInput::Entries(entries?))
@@ -654,6 +1154,7 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
                     SortEntry {
                         field: field.clone(),
                         direction,
+                        nulls: None,
                     }
                 })
                 .collect();
@@ -676,6 +1177,7 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
         sort,
         limit,
         offset,
+        context: Default::default(),
     })
 }
 
@@ -684,17 +1186,19 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
     use betterbase_db::reactive::event::ChangeEvent;
     let mut obj = serde_json::Map::new();
     match event {
-        ChangeEvent::Put { collection, id } => {
+        ChangeEvent::Put { collection, id, .. } => {
             obj.insert("type".to_string(), Value::String("put".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert("id".to_string(), Value::String(id.clone()));
         }
-        ChangeEvent::Delete { collection, id } => {
+        ChangeEvent::Delete { collection, id, .. } => {
             obj.insert("type".to_string(), Value::String("delete".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert("id".to_string(), Value::String(id.clone()));
         }
-        ChangeEvent::Bulk { collection, ids } => {
+        ChangeEvent::Bulk {
+            collection, ids, ..
+        } => {
             obj.insert("type".to_string(), Value::String("bulk".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert(
@@ -702,7 +1206,9 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
                 Value::Array(ids.iter().map(|s| Value::String(s.clone())).collect()),
             );
         }
-        ChangeEvent::Remote { collection, ids } => {
+        ChangeEvent::Remote {
+            collection, ids, ..
+        } => {
             obj.insert("type".to_string(), Value::String("remote".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert(
@@ -711,6 +1217,7 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
             );
         }
     }
+    obj.insert("txId".to_string(), Value::from(event.tx_id()));
     Value::Object(obj)
 }
 
@@ -731,6 +1238,7 @@ fn parse_put_options(js: JsValue) -> Result<PutOptions, JsValue> {
             .unwrap_or(false),
         meta: val.get("meta").cloned(),
         should_reset_sync_state: None,
+        context: Default::default(),
     })
 }
 
@@ -744,7 +1252,12 @@ fn parse_get_options(js: JsValue) -> Result<GetOptions, JsValue> {
             .get("includeDeleted")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
+        include_archived: val
+            .get("includeArchived")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
         migrate: val.get("migrate").and_then(|v| v.as_bool()).unwrap_or(true),
+        context: Default::default(),
     })
 }
 
@@ -770,6 +1283,7 @@ fn parse_patch_options(js: JsValue) -> Result<PatchOptions, JsValue> {
             .unwrap_or(false),
         meta: val.get("meta").cloned(),
         should_reset_sync_state: None,
+        context: Default::default(),
     })
 }
 
@@ -788,6 +1302,40 @@ fn parse_delete_options(id: &str, js: JsValue) -> Result<DeleteOptions, JsValue>
             .and_then(|v| v.as_f64())
             .map(|n| n as u64),
         meta: val.get("meta").cloned(),
+        kind: match val.get("kind").and_then(|v| v.as_str()) {
+            Some("archive") => DeleteKind::Archive,
+            _ => DeleteKind::Tombstone,
+        },
+        context: Default::default(),
+    })
+}
+
+fn parse_observe_options(js: JsValue) -> Result<ObserveOptions, JsValue> {
+    if js.is_null() || js.is_undefined() {
+        return Ok(ObserveOptions::default());
+    }
+    let val = js_to_value(js)?;
+    Ok(ObserveOptions {
+        label: val.get("label").and_then(|v| v.as_str()).map(String::from),
+    })
+}
+
+fn parse_subscription_diagnostics(js: JsValue) -> Result<SubscriptionDiagnostics, JsValue> {
+    if js.is_null() || js.is_undefined() {
+        return Ok(SubscriptionDiagnostics::default());
+    }
+    let val = js_to_value(js)?;
+    let count = |key: &str| val.get(key).and_then(|v| v.as_f64()).map(|n| n as u64);
+    let stale_after_ms = count("staleAfterMs");
+    let stale_after_changes = count("staleAfterChanges");
+    Ok(SubscriptionDiagnostics {
+        max_subscriptions: count("maxSubscriptions").map(|n| n as usize),
+        stale: (stale_after_ms.is_some() || stale_after_changes.is_some()).then(|| {
+            StaleThreshold {
+                idle_ms: stale_after_ms.unwrap_or(0),
+                min_changes: stale_after_changes.unwrap_or(0),
+            }
+        }),
     })
 }
 
@@ -818,6 +1366,10 @@ fn parse_list_options(js: JsValue) -> Result<ListOptions, JsValue> {
             .get("includeDeleted")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
+        include_archived: val
+            .get("includeArchived")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
         limit: val
             .get("limit")
             .and_then(|v| v.as_f64())
@@ -826,5 +1378,6 @@ fn parse_list_options(js: JsValue) -> Result<ListOptions, JsValue> {
             .get("offset")
             .and_then(|v| v.as_f64())
             .map(|n| n as usize),
+        context: Default::default(),
     })
 }
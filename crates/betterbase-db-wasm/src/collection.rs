@@ -5,6 +5,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use betterbase_db::collection::builder::{self, CollectionDef};
 use betterbase_db::index::types::IndexableValue;
@@ -13,6 +14,7 @@ use serde_json::Value;
 use wasm_bindgen::prelude::*;
 
 use crate::conversions::{js_to_value, parse_schema, to_js};
+use crate::error::{js_error, INVALID_ARGUMENT};
 
 // ============================================================================
 // WasmCollectionDef — opaque handle to a built CollectionDef
@@ -57,6 +59,7 @@ pub struct WasmCollectionBuilder {
     name: String,
     versions: Vec<VersionEntry>,
     indexes: Vec<IndexEntry>,
+    tombstone_retention: Option<Duration>,
 }
 
 /// Internal version entry.
@@ -91,6 +94,7 @@ impl WasmCollectionBuilder {
             name: name.to_string(),
             versions: Vec::new(),
             indexes: Vec::new(),
+            tombstone_retention: None,
         }
     }
 
@@ -128,7 +132,7 @@ impl WasmCollectionBuilder {
     /// `name`, `unique`, and `sparse` boolean fields.
     pub fn index(&mut self, fields: JsValue, options: JsValue) -> Result<(), JsValue> {
         let fields_val: Vec<String> = serde_wasm_bindgen::from_value(fields)
-            .map_err(|e| JsValue::from_str(&format!("Invalid fields array: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid fields array: {e}")))?;
 
         let opts: Value = if options.is_undefined() || options.is_null() {
             Value::Object(serde_json::Map::new())
@@ -188,16 +192,33 @@ impl WasmCollectionBuilder {
         Ok(())
     }
 
+    /// Keep tombstones for at least `ms` milliseconds before
+    /// `purgeTombstones()` may drop them.
+    #[wasm_bindgen(js_name = "tombstoneRetention")]
+    pub fn tombstone_retention(&mut self, ms: f64) -> Result<(), JsValue> {
+        if !ms.is_finite() || ms < 0.0 {
+            return Err(js_error(
+                INVALID_ARGUMENT,
+                "Tombstone retention must be a non-negative number of milliseconds",
+            ));
+        }
+        self.tombstone_retention = Some(Duration::from_millis(ms as u64));
+        Ok(())
+    }
+
     /// Finalize and build the collection definition.
     pub fn build(&mut self) -> Result<WasmCollectionDef, JsValue> {
         if self.versions.is_empty() {
-            return Err(JsValue::from_str("At least one version must be defined"));
+            return Err(js_error(
+                INVALID_ARGUMENT,
+                "At least one version must be defined",
+            ));
         }
 
         // Build using the core collection builder API.
         let first = &self.versions[0];
         if first.version != 1 {
-            return Err(JsValue::from_str("First version must be 1"));
+            return Err(js_error(INVALID_ARGUMENT, "First version must be 1"));
         }
 
         let mut bld = builder::collection(&self.name).v(1, first.schema.clone());
@@ -205,10 +226,10 @@ impl WasmCollectionBuilder {
         // Add subsequent versions with migration functions.
         for entry in self.versions.iter().skip(1) {
             let migrate_fn = entry.migrate.clone().ok_or_else(|| {
-                JsValue::from_str(&format!(
-                    "Version {} requires a migration function",
-                    entry.version
-                ))
+                js_error(
+                    INVALID_ARGUMENT,
+                    &format!("Version {} requires a migration function", entry.version),
+                )
             })?;
 
             // Wrap JS function as a Send+Sync migration closure.
@@ -243,6 +264,10 @@ impl WasmCollectionBuilder {
             }
         }
 
+        if let Some(retention) = self.tombstone_retention {
+            bld = bld.tombstone_retention(retention);
+        }
+
         let mut def = bld.build();
 
         // Patch unique/sparse flags on computed indexes that need non-default values.
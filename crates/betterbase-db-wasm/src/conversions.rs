@@ -7,6 +7,8 @@ use serde::Serialize;
 use serde_json::Value;
 use wasm_bindgen::prelude::*;
 
+use crate::error::{js_error, INVALID_ARGUMENT, SERIALIZATION};
+
 /// Create a serde-wasm-bindgen serializer that produces plain JS objects
 /// (not `Map` instances) for Rust maps/structs.
 fn js_serializer() -> serde_wasm_bindgen::Serializer {
@@ -20,14 +22,14 @@ pub fn to_js<T: Serialize + ?Sized>(v: &T) -> Result<JsValue, serde_wasm_bindgen
 
 /// Convert a `serde_json::Value` to a `JsValue` using serde-wasm-bindgen.
 pub fn value_to_js(v: &Value) -> Result<JsValue, JsValue> {
-    to_js(v).map_err(|e| JsValue::from_str(&e.to_string()))
+    to_js(v).map_err(|e| js_error(SERIALIZATION, &e.to_string()))
 }
 
 /// Convert a `JsValue` to a `serde_json::Value` using serde-wasm-bindgen.
 ///
 /// Takes ownership of the `JsValue` to avoid cloning — `from_value` consumes it.
 pub fn js_to_value(v: JsValue) -> Result<Value, JsValue> {
-    serde_wasm_bindgen::from_value(v).map_err(|e| JsValue::from_str(&e.to_string()))
+    serde_wasm_bindgen::from_value(v).map_err(|e| js_error(INVALID_ARGUMENT, &e.to_string()))
 }
 
 /// Parse a JSON schema definition into a `BTreeMap<String, SchemaNode>`.
@@ -40,12 +42,16 @@ pub fn parse_schema(js: JsValue) -> Result<BTreeMap<String, SchemaNode>, JsValue
     let val: Value = js_to_value(js)?;
     let obj = val
         .as_object()
-        .ok_or_else(|| JsValue::from_str("Schema must be an object"))?;
+        .ok_or_else(|| js_error(INVALID_ARGUMENT, "Schema must be an object"))?;
 
     let mut schema = BTreeMap::new();
     for (key, node_val) in obj {
-        let node = parse_schema_node(node_val)
-            .map_err(|e| JsValue::from_str(&format!("Invalid schema for field \"{key}\": {e}")))?;
+        let node = parse_schema_node(node_val).map_err(|e| {
+            js_error(
+                INVALID_ARGUMENT,
+                &format!("Invalid schema for field \"{key}\": {e}"),
+            )
+        })?;
         schema.insert(key.clone(), node);
     }
     Ok(schema)
@@ -1,14 +1,94 @@
 //! Error conversion: LessDbError → JsValue for wasm-bindgen boundaries.
+//!
+//! Every error thrown across the boundary is a JS `Error` named
+//! `BetterbaseDbError` with a stable `code` property (see
+//! `LessDbError::code()`), plus `collection` / `field` when the failure names
+//! them. JS callers branch on `e.code` instead of parsing messages.
+//!
+//! Unique-constraint violations are named `UniqueConstraintError` instead and
+//! carry `index`, `fields` (indexed field → colliding value) and `existingId`.
 
-use betterbase_db::error::LessDbError;
+use betterbase_db::{error::LessDbError, types::UniqueConflict};
 use wasm_bindgen::JsValue;
 
+use crate::conversions::to_js;
+
+/// `name` of every error thrown by this crate, bar unique-constraint violations.
+pub const ERROR_NAME: &str = "BetterbaseDbError";
+/// `name` of errors thrown for unique-constraint violations.
+pub const UNIQUE_ERROR_NAME: &str = "UniqueConstraintError";
+
+/// A JS argument could not be parsed into the expected Rust shape.
+pub const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+/// A Rust result could not be converted back into a JS value.
+pub const SERIALIZATION: &str = "SERIALIZATION";
+/// Setting up or tearing down the OPFS-backed SQLite file failed.
+pub const STORAGE_OPFS: &str = "STORAGE_OPFS";
+/// The database was used before `initialize()`, or initialized twice.
+pub const NOT_INITIALIZED: &str = "STORAGE_NOT_INITIALIZED";
+/// Operation referenced a collection that was not passed to `initialize()`.
+pub const COLLECTION_NOT_REGISTERED: &str = "STORAGE_COLLECTION_NOT_REGISTERED";
+
+/// Build a structured JS `Error` with `name`, `code`, and `message` set.
+pub fn js_error(code: &str, message: &str) -> JsValue {
+    let err = js_sys::Error::new(message);
+    err.set_name(ERROR_NAME);
+    let err: JsValue = err.into();
+    set_prop(&err, "code", code);
+    err
+}
+
+/// [`js_error`] for a failure tied to one collection.
+pub fn js_collection_error(code: &str, message: &str, collection: &str) -> JsValue {
+    let err = js_error(code, message);
+    set_prop(&err, "collection", collection);
+    err
+}
+
 /// Convert a `LessDbError` into a `JsValue` suitable for throwing across the WASM boundary.
 ///
-/// Creates a JS Error object with the display message of the Rust error.
+/// Creates a JS Error object with the display message of the Rust error and
+/// the `code`, `collection`, and `field` it reports.
 pub fn to_js_error(e: LessDbError) -> JsValue {
-    let msg = e.to_string();
-    js_sys::Error::new(&msg).into()
+    let err = js_error(e.code(), &e.to_string());
+    if let Some(collection) = e.collection() {
+        set_prop(&err, "collection", collection);
+    }
+    if let Some(field) = e.field() {
+        set_prop(&err, "field", field);
+    }
+    if let Some(conflict) = e.unique_conflict() {
+        set_unique_conflict(&err, &conflict);
+    }
+    err
+}
+
+fn set_unique_conflict(err: &JsValue, conflict: &UniqueConflict) {
+    set_prop(err, "name", UNIQUE_ERROR_NAME);
+    set_prop(err, "index", &conflict.index);
+    set_prop(err, "existingId", &conflict.existing_id);
+    // Strings, numbers, booleans and nulls taken from a stored record always
+    // convert
+    if let Ok(fields) = to_js(&conflict.fields) {
+        let _ = js_sys::Reflect::set(err, &JsValue::from_str("fields"), &fields);
+    }
+}
+
+/// Tag an error thrown by `WasmDb::batch` with the index of the op that
+/// caused it, as a numeric `opIndex` property.
+pub fn with_op_index(err: JsValue, index: usize) -> JsValue {
+    // Same as set_prop: a fresh Error is neither frozen nor a non-object
+    let _ = js_sys::Reflect::set(
+        &err,
+        &JsValue::from_str("opIndex"),
+        &JsValue::from_f64(index as f64),
+    );
+    err
+}
+
+fn set_prop(target: &JsValue, key: &str, value: &str) {
+    // Reflect::set only fails on frozen or non-object targets; a fresh Error is neither
+    let _ = js_sys::Reflect::set(target, &JsValue::from_str(key), &JsValue::from_str(value));
 }
 
 /// Convert any `LessDbError` result into a `Result<T, JsValue>`.
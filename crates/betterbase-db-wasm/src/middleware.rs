@@ -16,19 +16,22 @@ use betterbase_db::{
         typed_adapter::TypedAdapter,
         types::{MetaFilterFn, Middleware},
     },
-    query::types::{Query, SortDirection, SortEntry, SortInput},
+    query::types::{NullsOrder, Query, SortDirection, SortEntry, SortInput},
     reactive::adapter::ReactiveAdapter,
     storage::{
         adapter::Adapter,
         traits::{StorageLifecycle, StorageSync},
     },
-    types::{DeleteOptions, GetOptions, ListOptions, PatchOptions, PutOptions},
+    types::{DeleteKind, DeleteOptions, GetOptions, ListOptions, PatchOptions, PutOptions},
 };
 
 use crate::{
     collection::WasmCollectionDef,
     conversions::{js_to_value, value_to_js},
-    error::IntoJsResult,
+    error::{
+        js_collection_error, js_error, IntoJsResult, COLLECTION_NOT_REGISTERED, INVALID_ARGUMENT,
+        NOT_INITIALIZED, SERIALIZATION, STORAGE_OPFS,
+    },
     wasm_sqlite::Connection,
     wasm_sqlite_backend::WasmSqliteBackend,
 };
@@ -236,16 +239,16 @@ impl WasmTypedDb {
 
         install::<sqlite_wasm_rs::WasmOsCallback>(&cfg, true)
             .await
-            .map_err(|e| JsValue::from_str(&format!("Failed to install OPFS VFS: {e:?}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to install OPFS VFS: {e:?}")))?;
 
         let db_path = format!("/{db_name}.sqlite3");
         let conn = Connection::open(&db_path)
-            .map_err(|e| JsValue::from_str(&format!("Failed to open SQLite: {e}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to open SQLite: {e}")))?;
 
         let backend = WasmSqliteBackend::new(conn);
         backend
             .init_schema()
-            .map_err(|e| JsValue::from_str(&format!("Failed to init schema: {e}")))?;
+            .map_err(|e| js_error(STORAGE_OPFS, &format!("Failed to init schema: {e}")))?;
 
         let inner_adapter = Adapter::new(backend);
         let reactive = ReactiveAdapter::new(inner_adapter);
@@ -268,7 +271,7 @@ impl WasmTypedDb {
         let mut reactive = self
             .reactive
             .take()
-            .ok_or_else(|| JsValue::from_str("initialize() has already been called"))?;
+            .ok_or_else(|| js_error(NOT_INITIALIZED, "initialize() has already been called"))?;
 
         // Create collection-specific SQL indexes before initializing the adapter
         reactive.with_backend(|backend| {
@@ -357,6 +360,23 @@ impl WasmTypedDb {
             .into_js()
     }
 
+    /// Convert an archived record into a tombstone (space admins only).
+    #[wasm_bindgen(js_name = "tombstoneArchived")]
+    pub fn tombstone_archived(
+        &self,
+        collection: &str,
+        id: &str,
+        write_opts: JsValue,
+        options: JsValue,
+    ) -> Result<bool, JsValue> {
+        let def = self.get_def(collection)?;
+        let w_opts = parse_opaque_opts(write_opts)?;
+        let del_opts = parse_delete_options(id, options)?;
+        self.typed()?
+            .tombstone_archived(&def, id, w_opts.as_ref(), Some(&del_opts))
+            .into_js()
+    }
+
     // ========================================================================
     // Query
     // ========================================================================
@@ -430,7 +450,7 @@ impl WasmTypedDb {
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let records_val: Vec<Value> = serde_wasm_bindgen::from_value(records)
-            .map_err(|e| JsValue::from_str(&format!("Invalid records array: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid records array: {e}")))?;
         let w_opts = parse_opaque_opts(write_opts)?;
         let put_opts = parse_put_options(options)?;
         let result = self
@@ -455,7 +475,7 @@ impl WasmTypedDb {
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let id_strings: Vec<String> = serde_wasm_bindgen::from_value(ids)
-            .map_err(|e| JsValue::from_str(&format!("Invalid ids array: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid ids array: {e}")))?;
         let id_refs: Vec<&str> = id_strings.iter().map(|s| s.as_str()).collect();
         let w_opts = parse_opaque_opts(write_opts)?;
         let del_opts = parse_delete_options("", options)?;
@@ -464,7 +484,7 @@ impl WasmTypedDb {
             .bulk_delete(&def, &id_refs, w_opts.as_ref(), Some(&del_opts))
             .into_js()?;
         let val = serde_json::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
@@ -481,18 +501,21 @@ impl WasmTypedDb {
     ) -> Result<JsValue, JsValue> {
         let def = self.get_def(collection)?;
         let cb = Arc::new(SendSyncCallback(callback));
-        let unsub = self.typed()?.observe(
-            def,
-            id,
-            Arc::new(move |data: Option<Value>| {
-                let js_val = match data {
-                    Some(ref d) => value_to_js(d).unwrap_or(JsValue::NULL),
-                    None => JsValue::NULL,
-                };
-                let _ = cb.0.call1(&JsValue::NULL, &js_val);
-            }),
-            None,
-        );
+        let unsub = self
+            .typed()?
+            .observe(
+                def,
+                id,
+                Arc::new(move |data: Option<Value>| {
+                    let js_val = match data {
+                        Some(ref d) => value_to_js(d).unwrap_or(JsValue::NULL),
+                        None => JsValue::NULL,
+                    };
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+            )
+            .into_js()?;
 
         let unsub_fn = Closure::once_into_js(move || {
             unsub();
@@ -518,22 +541,25 @@ impl WasmTypedDb {
             js_to_value(query_opts).ok()
         };
 
-        let unsub = self.typed()?.observe_query(
-            def,
-            q,
-            Arc::new(move |result| {
-                let mut out = serde_json::Map::new();
-                out.insert("records".to_string(), Value::Array(result.records));
-                out.insert(
-                    "total".to_string(),
-                    Value::Number(serde_json::Number::from(result.total)),
-                );
-                let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
-                let _ = cb.0.call1(&JsValue::NULL, &js_val);
-            }),
-            None,
-            q_opts,
-        );
+        let unsub = self
+            .typed()?
+            .observe_query(
+                def,
+                q,
+                Arc::new(move |result| {
+                    let mut out = serde_json::Map::new();
+                    out.insert("records".to_string(), Value::Array(result.records));
+                    out.insert(
+                        "total".to_string(),
+                        Value::Number(serde_json::Number::from(result.total)),
+                    );
+                    let js_val = value_to_js(&Value::Object(out)).unwrap_or(JsValue::NULL);
+                    let _ = cb.0.call1(&JsValue::NULL, &js_val);
+                }),
+                None,
+                q_opts,
+            )
+            .into_js()?;
 
         let unsub_fn = Closure::once_into_js(move || {
             unsub();
@@ -565,7 +591,7 @@ impl WasmTypedDb {
         let def = self.get_def(collection)?;
         let result = self.typed()?.inner().get_dirty(&def).into_js()?;
         let val = serde_json::to_value(&result.records)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
@@ -583,7 +609,7 @@ impl WasmTypedDb {
         } else {
             let val = js_to_value(snapshot)?;
             let s: betterbase_db::types::PushSnapshot = serde_json::from_value(val)
-                .map_err(|e| JsValue::from_str(&format!("Invalid snapshot: {e}")))?;
+                .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid snapshot: {e}")))?;
             Some(s)
         };
         self.typed()?
@@ -602,17 +628,17 @@ impl WasmTypedDb {
         let def = self.get_def(collection)?;
         let records_val: Vec<betterbase_db::types::RemoteRecord> =
             serde_wasm_bindgen::from_value(records)
-                .map_err(|e| JsValue::from_str(&format!("Invalid remote records: {e}")))?;
+                .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid remote records: {e}")))?;
         let opts_val = js_to_value(options)?;
         let opts: betterbase_db::types::ApplyRemoteOptions = serde_json::from_value(opts_val)
-            .map_err(|e| JsValue::from_str(&format!("Invalid apply options: {e}")))?;
+            .map_err(|e| js_error(INVALID_ARGUMENT, &format!("Invalid apply options: {e}")))?;
         let result = self
             .typed()?
             .inner()
             .apply_remote_changes(&def, &records_val, &opts)
             .into_js()?;
         let val = serde_json::to_value(&result)
-            .map_err(|e| JsValue::from_str(&format!("Serialization error: {e}")))?;
+            .map_err(|e| js_error(SERIALIZATION, &format!("Serialization error: {e}")))?;
         value_to_js(&val)
     }
 
@@ -642,16 +668,21 @@ impl WasmTypedDb {
 impl WasmTypedDb {
     fn get_def(&self, collection: &str) -> Result<Arc<CollectionDef>, JsValue> {
         self.collections.get(collection).cloned().ok_or_else(|| {
-            JsValue::from_str(&format!(
-                "Collection \"{collection}\" not registered. Call initialize() first."
-            ))
+            js_collection_error(
+                COLLECTION_NOT_REGISTERED,
+                &format!("Collection \"{collection}\" not registered. Call initialize() first."),
+                collection,
+            )
         })
     }
 
     fn typed(&self) -> Result<&TypedAdapter<WasmSqliteBackend>, JsValue> {
-        self.adapter
-            .as_ref()
-            .ok_or_else(|| JsValue::from_str("Database not initialized. Call initialize() first."))
+        self.adapter.as_ref().ok_or_else(|| {
+            js_error(
+                NOT_INITIALIZED,
+                "Database not initialized. Call initialize() first.",
+            )
+        })
     }
 }
 
@@ -667,17 +698,19 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
     use betterbase_db::reactive::event::ChangeEvent;
     let mut obj = serde_json::Map::new();
     match event {
-        ChangeEvent::Put { collection, id } => {
+        ChangeEvent::Put { collection, id, .. } => {
             obj.insert("type".to_string(), Value::String("put".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert("id".to_string(), Value::String(id.clone()));
         }
-        ChangeEvent::Delete { collection, id } => {
+        ChangeEvent::Delete { collection, id, .. } => {
             obj.insert("type".to_string(), Value::String("delete".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert("id".to_string(), Value::String(id.clone()));
         }
-        ChangeEvent::Bulk { collection, ids } => {
+        ChangeEvent::Bulk {
+            collection, ids, ..
+        } => {
             obj.insert("type".to_string(), Value::String("bulk".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert(
@@ -685,7 +718,9 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
                 Value::Array(ids.iter().map(|s| Value::String(s.clone())).collect()),
             );
         }
-        ChangeEvent::Remote { collection, ids } => {
+        ChangeEvent::Remote {
+            collection, ids, ..
+        } => {
             obj.insert("type".to_string(), Value::String("remote".to_string()));
             obj.insert("collection".to_string(), Value::String(collection.clone()));
             obj.insert(
@@ -694,6 +729,7 @@ fn change_event_to_value(event: &betterbase_db::reactive::event::ChangeEvent) ->
             );
         }
     }
+    obj.insert("txId".to_string(), Value::from(event.tx_id()));
     Value::Object(obj)
 }
 
@@ -714,6 +750,7 @@ fn parse_put_options(js: JsValue) -> Result<PutOptions, JsValue> {
             .unwrap_or(false),
         meta: None,                    // TypedAdapter resolves meta via middleware
         should_reset_sync_state: None, // TypedAdapter handles this
+        context: Default::default(),
     })
 }
 
@@ -727,7 +764,12 @@ fn parse_get_options(js: JsValue) -> Result<GetOptions, JsValue> {
             .get("includeDeleted")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
+        include_archived: val
+            .get("includeArchived")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
         migrate: val.get("migrate").and_then(|v| v.as_bool()).unwrap_or(true),
+        context: Default::default(),
     })
 }
 
@@ -753,6 +795,7 @@ fn parse_patch_options(js: JsValue) -> Result<PatchOptions, JsValue> {
             .unwrap_or(false),
         meta: None,
         should_reset_sync_state: None,
+        context: Default::default(),
     })
 }
 
@@ -771,6 +814,11 @@ fn parse_delete_options(id: &str, js: JsValue) -> Result<DeleteOptions, JsValue>
             .and_then(|v| v.as_f64())
             .map(|n| n as u64),
         meta: None,
+        kind: match val.get("kind").and_then(|v| v.as_str()) {
+            Some("archive") => DeleteKind::Archive,
+            _ => DeleteKind::Tombstone,
+        },
+        context: Default::default(),
     })
 }
 
@@ -784,6 +832,10 @@ fn parse_list_options(js: JsValue) -> Result<ListOptions, JsValue> {
             .get("includeDeleted")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
+        include_archived: val
+            .get("includeArchived")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
         limit: val
             .get("limit")
             .and_then(|v| v.as_f64())
@@ -792,6 +844,7 @@ fn parse_list_options(js: JsValue) -> Result<ListOptions, JsValue> {
             .get("offset")
             .and_then(|v| v.as_f64())
             .map(|n| n as usize),
+        context: Default::default(),
     })
 }
 
@@ -799,7 +852,7 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
     let val = js_to_value(js)?;
     let obj = val
         .as_object()
-        .ok_or_else(|| JsValue::from_str("Query must be an object"))?;
+        .ok_or_else(|| js_error(INVALID_ARGUMENT, "Query must be an object"))?;
 
     let filter = obj.get("filter").cloned();
 
@@ -810,13 +863,15 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
             let entries: Result<Vec<SortEntry>, JsValue> = arr
                 .iter()
                 .map(|entry| {
-                    let entry_obj = entry
-                        .as_object()
-                        .ok_or_else(|| JsValue::from_str("Sort entry must be an object"))?;
+                    let entry_obj = entry.as_object().ok_or_else(|| {
+                        js_error(INVALID_ARGUMENT, "Sort entry must be an object")
+                    })?;
                     let field = entry_obj
                         .get("field")
                         .and_then(|v| v.as_str())
-                        .ok_or_else(|| JsValue::from_str("Sort entry must have a \"field\""))?
+                        .ok_or_else(|| {
+                            js_error(INVALID_ARGUMENT, "Sort entry must have a \"field\"")
+                        })?
                         .to_string();
                     let direction = match entry_obj
                         .get("direction")
@@ -826,7 +881,16 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
                         "desc" => SortDirection::Desc,
                         _ => SortDirection::Asc,
                     };
-                    Ok(SortEntry { field, direction })
+                    let nulls = match entry_obj.get("nulls").and_then(|v| v.as_str()) {
+                        Some("first") => Some(NullsOrder::First),
+                        Some("last") => Some(NullsOrder::Last),
+                        _ => None,
+                    };
+                    Ok(SortEntry {
+                        field,
+                        direction,
+                        nulls,
+                    })
                 })
                 .collect();
             Some(SortInput::Entries(entries?))
@@ -842,6 +906,7 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
                     SortEntry {
                         field: field.clone(),
                         direction,
+                        nulls: None,
                     }
                 })
                 .collect();
@@ -864,5 +929,6 @@ fn parse_query(js: JsValue) -> Result<Query, JsValue> {
         sort,
         limit,
         offset,
+        context: Default::default(),
     })
 }
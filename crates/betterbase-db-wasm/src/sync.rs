@@ -92,6 +92,7 @@ impl betterbase_db::sync::types::SyncTransport for JsSyncTransport {
                     }
                 }
                 obj.insert("deleted".to_string(), Value::Bool(r.deleted));
+                obj.insert("archived".to_string(), Value::Bool(r.archived));
                 obj.insert(
                     "sequence".to_string(),
                     Value::Number(serde_json::Number::from(r.sequence)),
@@ -16,6 +16,7 @@ use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
 use std::os::raw::{c_char, c_int};
 
+use serde_json::Value;
 use sqlite_wasm_rs as ffi;
 
 // ============================================================================
@@ -119,6 +120,15 @@ impl<'conn> RawStatement<'conn> {
         check_bind(rc, self.conn)
     }
 
+    /// Serialize `val` as JSON text and bind it.
+    pub(crate) fn bind_json(&mut self, idx: c_int, val: &Value) -> Result<()> {
+        let text = serde_json::to_string(val).map_err(|e| SqliteError {
+            code: ffi::SQLITE_ERROR,
+            message: format!("Failed to serialize JSON: {e}"),
+        })?;
+        self.bind_text(idx, &text)
+    }
+
     pub(crate) fn step(&mut self) -> Result<StepResult> {
         let rc = unsafe { ffi::sqlite3_step(self.raw) };
         match rc {
@@ -147,6 +157,14 @@ impl<'conn> RawStatement<'conn> {
         }
     }
 
+    /// Get a TEXT column parsed as JSON. `idx` is 0-based.
+    pub(crate) fn column_json(&self, idx: c_int) -> Result<Value> {
+        serde_json::from_str(&self.column_text(idx)).map_err(|e| SqliteError {
+            code: ffi::SQLITE_ERROR,
+            message: format!("Failed to parse JSON column {idx}: {e}"),
+        })
+    }
+
     pub(crate) fn column_int64(&self, idx: c_int) -> i64 {
         unsafe { ffi::sqlite3_column_int64(self.raw, idx) }
     }
@@ -479,12 +497,18 @@ impl<'conn> Statement<'conn> {
     pub fn bind_null(&mut self, idx: c_int) -> Result<()> {
         self.0.bind_null(idx)
     }
+    pub fn bind_json(&mut self, idx: c_int, val: &Value) -> Result<()> {
+        self.0.bind_json(idx, val)
+    }
     pub fn step(&mut self) -> Result<StepResult> {
         self.0.step()
     }
     pub fn column_text(&self, idx: c_int) -> String {
         self.0.column_text(idx)
     }
+    pub fn column_json(&self, idx: c_int) -> Result<Value> {
+        self.0.column_json(idx)
+    }
     pub fn column_int64(&self, idx: c_int) -> i64 {
         self.0.column_int64(idx)
     }
@@ -548,12 +572,18 @@ impl<'conn> CachedStatement<'conn> {
     pub fn bind_null(&mut self, idx: c_int) -> Result<()> {
         self.0.bind_null(idx)
     }
+    pub fn bind_json(&mut self, idx: c_int, val: &Value) -> Result<()> {
+        self.0.bind_json(idx, val)
+    }
     pub fn step(&mut self) -> Result<StepResult> {
         self.0.step()
     }
     pub fn column_text(&self, idx: c_int) -> String {
         self.0.column_text(idx)
     }
+    pub fn column_json(&self, idx: c_int) -> Result<Value> {
+        self.0.column_json(idx)
+    }
     pub fn column_int64(&self, idx: c_int) -> i64 {
         self.0.column_int64(idx)
     }
@@ -599,3 +629,42 @@ fn check_bind(rc: c_int, conn: &Connection) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn json_round_trips_through_bind_and_column() {
+        let conn = Connection::open(":memory:").unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT)").unwrap();
+
+        let value = serde_json::json!({
+            "name": "Alice",
+            "tags": ["a", "b"],
+            "nested": { "n": 42, "f": 1.5, "ok": true, "none": null }
+        });
+
+        let mut insert = conn.prepare("INSERT INTO t (v) VALUES (?1)").unwrap();
+        insert.bind_json(1, &value).unwrap();
+        assert_eq!(insert.step().unwrap(), StepResult::Done);
+
+        let mut select = conn.prepare_cached("SELECT v FROM t").unwrap();
+        assert_eq!(select.step().unwrap(), StepResult::Row);
+        assert_eq!(select.column_json(0).unwrap(), value);
+    }
+
+    #[wasm_bindgen_test]
+    fn column_json_reports_parse_errors() {
+        let conn = Connection::open(":memory:").unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t (v) VALUES ('{not json');")
+            .unwrap();
+
+        let mut select = conn.prepare("SELECT v FROM t").unwrap();
+        assert_eq!(select.step().unwrap(), StepResult::Row);
+        let err = select.column_json(0).unwrap_err();
+        assert_eq!(err.code, ffi::SQLITE_ERROR);
+        assert!(err.message.contains("Failed to parse JSON column 0"));
+    }
+}
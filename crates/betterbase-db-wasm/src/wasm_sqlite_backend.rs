@@ -10,6 +10,7 @@
 //! The `RefCell` + `Cell` pattern handles reentrancy for nested transactions.
 
 use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 
 use serde_json::Value;
 
@@ -70,7 +71,7 @@ fn json_value_to_sql(v: &Value) -> SqlParam {
 }
 
 const SELECT_COLS: &str = "id, collection, version, data, crdt, pending_patches, \
-    sequence, dirty, deleted, deleted_at, meta, computed";
+    sequence, dirty, deleted, deleted_at, meta, computed, archived";
 
 /// Validate that a name is a safe SQL identifier (alphanumeric + underscore).
 /// Field names, index names, and collection names from schema definitions are
@@ -168,6 +169,7 @@ impl WasmSqliteBackend {
                 deleted_at      TEXT,
                 meta            TEXT,
                 computed        TEXT,
+                archived        INTEGER NOT NULL DEFAULT 0,
                 PRIMARY KEY (collection, id)
             );
             CREATE INDEX IF NOT EXISTS idx_records_dirty
@@ -178,7 +180,25 @@ impl WasmSqliteBackend {
             );
             INSERT OR IGNORE INTO meta (key, value) VALUES ('schema:version', '1');",
         )
-        .map_err(storage_err)
+        .map_err(storage_err)?;
+
+        // Databases created before archival deletes lack the column.
+        let has_archived = {
+            let mut stmt = conn
+                .prepare_cached(
+                    "SELECT COUNT(*) FROM pragma_table_info('records') WHERE name = 'archived'",
+                )
+                .map_err(storage_err)?;
+            stmt.step().map_err(storage_err)?;
+            stmt.column_int64(0) > 0
+        };
+        if !has_archived {
+            conn.execute_batch(
+                "ALTER TABLE records ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+            )
+            .map_err(storage_err)?;
+        }
+        Ok(())
     }
 
     /// Create SQL indexes for all indexes in a collection definition.
@@ -243,18 +263,19 @@ impl WasmSqliteBackend {
     /// Accepts `&RawStatement` so it works with both `Statement` (dynamic SQL)
     /// and `CachedStatement` (cached SQL) via their `.raw()` accessor.
     fn read_record(stmt: &RawStatement<'_>) -> betterbase_db::error::Result<SerializedRecord> {
-        let data: Value = serde_json::from_str(&stmt.column_text(3))
+        let data = stmt
+            .column_json(3)
             .map_err(|e| LessDbError::Internal(format!("Failed to parse record data: {e}")))?;
-        let meta: Option<Value> =
+        let meta =
             match stmt.column_type(10) {
                 ColumnType::Null => None,
-                _ => Some(serde_json::from_str(&stmt.column_text(10)).map_err(|e| {
+                _ => Some(stmt.column_json(10).map_err(|e| {
                     LessDbError::Internal(format!("Failed to parse record meta: {e}"))
                 })?),
             };
-        let computed: Option<Value> = match stmt.column_type(11) {
+        let computed = match stmt.column_type(11) {
             ColumnType::Null => None,
-            _ => Some(serde_json::from_str(&stmt.column_text(11)).map_err(|e| {
+            _ => Some(stmt.column_json(11).map_err(|e| {
                 LessDbError::Internal(format!("Failed to parse record computed: {e}"))
             })?),
         };
@@ -271,6 +292,7 @@ impl WasmSqliteBackend {
             ColumnType::Null => None,
             _ => Some(stmt.column_text(9)),
         };
+        let archived = stmt.column_int64(12) != 0;
 
         Ok(SerializedRecord {
             id,
@@ -283,6 +305,7 @@ impl WasmSqliteBackend {
             dirty,
             deleted,
             deleted_at,
+            archived,
             meta,
             computed,
         })
@@ -294,34 +317,19 @@ impl WasmSqliteBackend {
 
     const PUT_SQL: &str = "INSERT OR REPLACE INTO records \
         (id, collection, version, data, crdt, pending_patches, sequence, dirty, \
-         deleted, deleted_at, meta, computed) \
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)";
+         deleted, deleted_at, meta, computed, archived) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)";
 
     /// Bind a record's fields to an INSERT statement and step it.
     fn bind_and_step_put(
         stmt: &mut RawStatement<'_>,
         record: &SerializedRecord,
     ) -> betterbase_db::error::Result<()> {
-        let data_str = serde_json::to_string(&record.data)
-            .map_err(|e| LessDbError::Internal(format!("serialize data: {e}")))?;
-        let meta_str = record
-            .meta
-            .as_ref()
-            .map(serde_json::to_string)
-            .transpose()
-            .map_err(|e| LessDbError::Internal(format!("serialize meta: {e}")))?;
-        let computed_str = record
-            .computed
-            .as_ref()
-            .map(serde_json::to_string)
-            .transpose()
-            .map_err(|e| LessDbError::Internal(format!("serialize computed: {e}")))?;
-
         stmt.bind_text(1, &record.id).map_err(storage_err)?;
         stmt.bind_text(2, &record.collection).map_err(storage_err)?;
         stmt.bind_int64(3, record.version as i64)
             .map_err(storage_err)?;
-        stmt.bind_text(4, &data_str).map_err(storage_err)?;
+        stmt.bind_json(4, &record.data).map_err(storage_err)?;
         stmt.bind_blob(5, &record.crdt).map_err(storage_err)?;
         stmt.bind_blob(6, &record.pending_patches)
             .map_err(storage_err)?;
@@ -334,14 +342,16 @@ impl WasmSqliteBackend {
             Some(dt) => stmt.bind_text(10, dt).map_err(storage_err)?,
             None => stmt.bind_null(10).map_err(storage_err)?,
         }
-        match &meta_str {
-            Some(s) => stmt.bind_text(11, s).map_err(storage_err)?,
+        match &record.meta {
+            Some(meta) => stmt.bind_json(11, meta).map_err(storage_err)?,
             None => stmt.bind_null(11).map_err(storage_err)?,
         }
-        match &computed_str {
-            Some(s) => stmt.bind_text(12, s).map_err(storage_err)?,
+        match &record.computed {
+            Some(computed) => stmt.bind_json(12, computed).map_err(storage_err)?,
             None => stmt.bind_null(12).map_err(storage_err)?,
         }
+        stmt.bind_int64(13, if record.archived { 1 } else { 0 })
+            .map_err(storage_err)?;
 
         stmt.step().map_err(storage_err)?;
         Ok(())
@@ -385,8 +395,11 @@ impl WasmSqliteBackend {
         scan: &IndexScan,
         index_provides_sort: bool,
     ) -> betterbase_db::error::Result<Option<(String, Vec<SqlParam>)>> {
-        let mut conditions: Vec<String> =
-            vec!["collection = ?".to_string(), "deleted = 0".to_string()];
+        let mut conditions: Vec<String> = vec![
+            "collection = ?".to_string(),
+            "deleted = 0".to_string(),
+            "archived = 0".to_string(),
+        ];
         let mut params: Vec<SqlParam> = vec![SqlParam::Text(collection.to_string())];
 
         match &scan.index {
@@ -457,9 +470,10 @@ impl WasmSqliteBackend {
                         .fields
                         .iter()
                         .map(|f| {
+                            // Nulls sort as the greatest value, as in the in-memory sort.
                             let dir = match f.order {
-                                IndexSortOrder::Asc => "ASC",
-                                IndexSortOrder::Desc => "DESC",
+                                IndexSortOrder::Asc => "ASC NULLS LAST",
+                                IndexSortOrder::Desc => "DESC NULLS FIRST",
                             };
                             format!("json_extract(data, '$.{}') {}", f.field, dir)
                         })
@@ -595,14 +609,13 @@ impl StorageBackend for WasmSqliteBackend {
         collection: &str,
         options: &ScanOptions,
     ) -> betterbase_db::error::Result<RawBatchResult> {
-        let mut sql = if options.include_deleted {
-            format!("SELECT {} FROM records WHERE collection = ?", SELECT_COLS)
-        } else {
-            format!(
-                "SELECT {} FROM records WHERE collection = ? AND deleted = 0",
-                SELECT_COLS
-            )
-        };
+        let mut sql = format!("SELECT {} FROM records WHERE collection = ?", SELECT_COLS);
+        if !options.include_deleted {
+            sql.push_str(" AND deleted = 0");
+        }
+        if !options.include_archived {
+            sql.push_str(" AND archived = 0");
+        }
 
         let mut params: Vec<SqlParam> = vec![SqlParam::Text(collection.to_string())];
 
@@ -638,7 +651,10 @@ impl StorageBackend for WasmSqliteBackend {
     fn count_raw(&self, collection: &str) -> betterbase_db::error::Result<usize> {
         let conn = self.borrow_conn()?;
         let mut stmt = conn
-            .prepare_cached("SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 0")
+            .prepare_cached(
+                "SELECT COUNT(*) FROM records \
+                 WHERE collection = ?1 AND deleted = 0 AND archived = 0",
+            )
             .map_err(storage_err)?;
         stmt.bind_text(1, collection).map_err(storage_err)?;
         stmt.step().map_err(storage_err)?;
@@ -665,51 +681,103 @@ impl StorageBackend for WasmSqliteBackend {
     ) -> betterbase_db::error::Result<usize> {
         let conn = self.borrow_conn()?;
 
-        if options.dry_run {
-            let (sql, bind_modifier) = if let Some(secs) = options.older_than_seconds {
-                (
-                    "SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 1 \
-                     AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)"
-                        .to_string(),
-                    Some(format!("-{secs} seconds")),
-                )
-            } else {
-                (
-                    "SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 1"
-                        .to_string(),
-                    None,
-                )
-            };
+        let mut filter = String::from("collection = ?1 AND deleted = 1");
+        if options.skip_dirty {
+            filter.push_str(" AND dirty = 0");
+        }
+        let age = options
+            .older_than_seconds
+            .map(|secs| format!("-{secs} seconds"));
+        if age.is_some() {
+            filter.push_str(" AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)");
+        }
 
-            let mut stmt = conn.prepare_cached(&sql).map_err(storage_err)?;
-            stmt.bind_text(1, collection).map_err(storage_err)?;
-            if let Some(ref modifier) = bind_modifier {
-                stmt.bind_text(2, modifier).map_err(storage_err)?;
-            }
-            stmt.step().map_err(storage_err)?;
+        let sql = if options.dry_run {
+            format!("SELECT COUNT(*) FROM records WHERE {filter}")
+        } else {
+            format!("DELETE FROM records WHERE {filter}")
+        };
+        let mut stmt = conn.prepare_cached(&sql).map_err(storage_err)?;
+        stmt.bind_text(1, collection).map_err(storage_err)?;
+        if let Some(ref age) = age {
+            stmt.bind_text(2, age).map_err(storage_err)?;
+        }
+        stmt.step().map_err(storage_err)?;
+
+        if options.dry_run {
             return Ok(stmt.column_int64(0) as usize);
         }
+        Ok(conn.changes() as usize)
+    }
 
-        if let Some(secs) = options.older_than_seconds {
-            let mut stmt = conn
-                .prepare_cached(
-                    "DELETE FROM records WHERE collection = ?1 AND deleted = 1 \
-                     AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)",
-                )
-                .map_err(storage_err)?;
-            stmt.bind_text(1, collection).map_err(storage_err)?;
-            stmt.bind_text(2, &format!("-{secs} seconds"))
+    fn vacuum(&self) -> betterbase_db::error::Result<Option<u64>> {
+        let conn = self.borrow_conn()?;
+        let size = || -> betterbase_db::error::Result<i64> {
+            let mut pages = conn
+                .prepare_cached("PRAGMA page_count")
                 .map_err(storage_err)?;
-            stmt.step().map_err(storage_err)?;
-        } else {
-            let mut stmt = conn
-                .prepare_cached("DELETE FROM records WHERE collection = ?1 AND deleted = 1")
+            pages.step().map_err(storage_err)?;
+            let mut page_size = conn
+                .prepare_cached("PRAGMA page_size")
                 .map_err(storage_err)?;
-            stmt.bind_text(1, collection).map_err(storage_err)?;
-            stmt.step().map_err(storage_err)?;
-        }
+            page_size.step().map_err(storage_err)?;
+            let bytes = pages.column_int64(0) * page_size.column_int64(0);
+            // Cached statements stay active until reset, and VACUUM refuses
+            // to run while any statement is in progress.
+            pages.reset().map_err(storage_err)?;
+            page_size.reset().map_err(storage_err)?;
+            Ok(bytes)
+        };
+        let before = size()?;
+        conn.execute_batch("VACUUM").map_err(storage_err)?;
+        let after = size()?;
+        Ok(Some(before.saturating_sub(after).max(0) as u64))
+    }
 
-        Ok(conn.changes() as usize)
+    fn rebuild_indexes(
+        &self,
+        def: &CollectionDef,
+        registered: &[&CollectionDef],
+    ) -> betterbase_db::error::Result<()> {
+        // Index names are `idx_{collection}_{index}`, so another collection's
+        // indexes (or the built-in `idx_records_*`) can share the prefix.
+        let prefix = format!("idx_{}_", def.name);
+        let mut keep: HashSet<String> = registered
+            .iter()
+            .filter(|other| other.name != def.name)
+            .flat_map(|other| {
+                other
+                    .indexes
+                    .iter()
+                    .map(|index| format!("idx_{}_{}", other.name, index.name()))
+            })
+            .collect();
+        keep.insert("idx_records_collection".to_string());
+        keep.insert("idx_records_dirty".to_string());
+
+        {
+            let conn = self.borrow_conn()?;
+            let mut names = Vec::new();
+            {
+                let mut stmt = conn
+                    .prepare_cached(
+                        "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'records'",
+                    )
+                    .map_err(storage_err)?;
+                while stmt.step().map_err(storage_err)? == StepResult::Row {
+                    names.push(stmt.column_text(0));
+                }
+                // DROP INDEX refuses to run while the cached statement is active.
+                stmt.reset().map_err(storage_err)?;
+            }
+            for name in names {
+                if name.starts_with(&prefix) && !keep.contains(&name) {
+                    conn.execute_batch(&format!("DROP INDEX IF EXISTS \"{name}\""))
+                        .map_err(storage_err)?;
+                }
+            }
+        }
+        self.create_collection_indexes(def)
     }
 
     fn get_meta(&self, key: &str) -> betterbase_db::error::Result<Option<String>> {
@@ -886,27 +954,11 @@ impl StorageBackend for WasmSqliteBackend {
 
                 if let StepResult::Row = stmt.raw_mut().step().map_err(storage_err)? {
                     let eid = stmt.raw().column_text(0);
-                    let conflict_value = if fi.fields.len() == 1 {
-                        obj.and_then(|o| o.get(&fi.fields[0].field))
-                            .cloned()
-                            .unwrap_or(Value::Null)
-                    } else {
-                        Value::Array(
-                            fi.fields
-                                .iter()
-                                .map(|f| {
-                                    obj.and_then(|o| o.get(&f.field))
-                                        .cloned()
-                                        .unwrap_or(Value::Null)
-                                })
-                                .collect(),
-                        )
-                    };
                     return Err(StorageError::UniqueConstraint {
                         collection: collection.to_string(),
                         index: fi.name.clone(),
                         existing_id: eid,
-                        value: conflict_value,
+                        fields: fi.values_of(data),
                     }
                     .into());
                 }
@@ -966,7 +1018,7 @@ impl StorageBackend for WasmSqliteBackend {
                         collection: collection.to_string(),
                         index: ci.name.clone(),
                         existing_id: eid,
-                        value: conflict_value,
+                        fields: [(ci.name.clone(), conflict_value)].into_iter().collect(),
                     }
                     .into());
                 }
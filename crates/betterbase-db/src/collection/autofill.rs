@@ -18,6 +18,89 @@ pub fn generate_uuid() -> String {
     uuid::Uuid::new_v4().to_string()
 }
 
+/// Generate a time-ordered UUID (v7).
+pub fn generate_uuid_v7() -> String {
+    uuid::Uuid::now_v7().to_string()
+}
+
+const NANOID_ALPHABET: &[u8; 64] =
+    b"useandom-26T198340PX75pxJACKVERYMINDBUSHWOLF_GQZbfghjklqvwyzrict";
+
+/// Generate a URL-safe random id of `len` characters (nanoid alphabet).
+///
+/// Randomness is drawn from v4 UUIDs, skipping the two bytes that carry
+/// version/variant bits, so every character is uniform over the alphabet.
+pub fn generate_nanoid(len: usize) -> String {
+    let mut out = String::with_capacity(len);
+    while out.len() < len {
+        let bytes = uuid::Uuid::new_v4().into_bytes();
+        for (i, b) in bytes.iter().enumerate() {
+            if i == 6 || i == 8 || out.len() == len {
+                continue;
+            }
+            out.push(NANOID_ALPHABET[(b & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Derive an id from record content: hex SHA-256 of the canonical JSON
+/// (object keys sorted), truncated to 128 bits.
+pub fn generate_content_hash(data: &Value) -> String {
+    use sha2::{Digest, Sha256};
+
+    let canonical = serde_json::to_vec(&canonicalize(data)).unwrap_or_default();
+    let digest = Sha256::digest(&canonical);
+    digest[..16].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            let sorted: Map<String, Value> = keys
+                .into_iter()
+                .map(|k| (k.clone(), canonicalize(&obj[k])))
+                .collect();
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+// ============================================================================
+// IdStrategy
+// ============================================================================
+
+/// How a collection mints record ids when the caller doesn't supply one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IdStrategy {
+    /// Random UUID v4.
+    #[default]
+    Uuid4,
+    /// Time-ordered UUID v7; ids sort by creation time.
+    Uuid7,
+    /// Random URL-safe id of the given length, which must be positive.
+    Nanoid(usize),
+    /// Hash of the record content. Inserting identical content twice is an
+    /// error (`StorageError::DuplicateContent`) rather than a silent upsert.
+    ContentHash,
+}
+
+impl IdStrategy {
+    /// Mint an id for `data` (the record as supplied, without an id).
+    pub fn mint(&self, data: &Value) -> String {
+        match self {
+            IdStrategy::Uuid4 => generate_uuid(),
+            IdStrategy::Uuid7 => generate_uuid_v7(),
+            IdStrategy::Nanoid(len) => generate_nanoid(*len),
+            IdStrategy::ContentHash => generate_content_hash(data),
+        }
+    }
+}
+
 // ============================================================================
 // AutofillOptions
 // ============================================================================
@@ -7,15 +7,23 @@
 use std::{
     collections::BTreeMap,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 
 use serde_json::Value;
 
 use crate::{
-    index::types::{
-        ComputedIndex, FieldIndex, IndexDefinition, IndexField, IndexSortOrder, IndexableValue,
+    collection::autofill::IdStrategy,
+    index::{
+        geo::{encode_geohash, point_from_value, MAX_GEOHASH_PRECISION},
+        types::{
+            ComputedIndex, FieldIndex, GeoIndexSpec, IndexDefinition, IndexField, IndexSortOrder,
+            IndexableValue,
+        },
     },
+    query::operators::get_field_value,
     schema::node::{is_indexable_node, SchemaNode},
+    types::{AccessPredicateFn, OperationContext},
 };
 
 // ============================================================================
@@ -70,6 +78,33 @@ pub struct CollectionDef {
     pub current_version: u32,
     /// Full schema including auto-fields (id, createdAt, updatedAt).
     pub current_schema: BTreeMap<String, SchemaNode>,
+    /// How ids are minted for inserts that don't supply one.
+    pub id_strategy: IdStrategy,
+    /// Minimum age before acknowledged tombstones are garbage-collected.
+    /// `None` disables scheduled purging for this collection.
+    pub tombstone_retention: Option<Duration>,
+    /// Row-level read rule; records it rejects are invisible to the actor.
+    pub read_predicate: Option<Arc<AccessPredicateFn>>,
+    /// Row-level write rule; writes it rejects fail with `Forbidden`.
+    pub write_predicate: Option<Arc<AccessPredicateFn>>,
+}
+
+impl CollectionDef {
+    /// Whether the actor in `ctx` may read a record holding `data`.
+    pub fn can_read(&self, ctx: &OperationContext, data: &Value) -> bool {
+        match &self.read_predicate {
+            Some(predicate) => predicate(ctx, data),
+            None => true,
+        }
+    }
+
+    /// Whether the actor in `ctx` may write a record holding `data`.
+    pub fn can_write(&self, ctx: &OperationContext, data: &Value) -> bool {
+        match &self.write_predicate {
+            Some(predicate) => predicate(ctx, data),
+            None => true,
+        }
+    }
 }
 
 impl std::fmt::Debug for CollectionDef {
@@ -80,6 +115,16 @@ impl std::fmt::Debug for CollectionDef {
             .field("indexes", &self.indexes)
             .field("current_version", &self.current_version)
             .field("current_schema", &self.current_schema)
+            .field("id_strategy", &self.id_strategy)
+            .field("tombstone_retention", &self.tombstone_retention)
+            .field(
+                "read_predicate",
+                &self.read_predicate.as_ref().map(|_| "<fn>"),
+            )
+            .field(
+                "write_predicate",
+                &self.write_predicate.as_ref().map(|_| "<fn>"),
+            )
             .finish()
     }
 }
@@ -115,6 +160,10 @@ impl CollectionBuilderNoVersions {
             versions: vec![version_def],
             indexes: vec![],
             current_user_schema: schema,
+            id_strategy: IdStrategy::default(),
+            tombstone_retention: None,
+            read_predicate: None,
+            write_predicate: None,
         }
     }
 }
@@ -130,6 +179,10 @@ pub struct CollectionBuilderWithVersions {
     indexes: Vec<IndexDefinition>,
     /// Current user schema (without auto-fields), used for index validation.
     current_user_schema: BTreeMap<String, SchemaNode>,
+    id_strategy: IdStrategy,
+    tombstone_retention: Option<Duration>,
+    read_predicate: Option<Arc<AccessPredicateFn>>,
+    write_predicate: Option<Arc<AccessPredicateFn>>,
 }
 
 impl CollectionBuilderWithVersions {
@@ -215,6 +268,10 @@ impl CollectionBuilderWithVersions {
             },
             indexes: vec![], // indexes reset on new version (matches JS behavior)
             current_user_schema: schema,
+            id_strategy: self.id_strategy,
+            tombstone_retention: self.tombstone_retention,
+            read_predicate: self.read_predicate,
+            write_predicate: self.write_predicate,
         }
     }
 
@@ -318,6 +375,13 @@ impl CollectionBuilderWithVersions {
         }
     }
 
+    /// Define a named unique index over `fields`, e.g.
+    /// `.unique(&["orgId", "email"], "org_email_unique")`.
+    /// Panics on the same validation errors as [`Self::index_with`].
+    pub fn unique(self, fields: &[&str], name: &str) -> Self {
+        self.index_with(fields, Some(name), true, false)
+    }
+
     /// Define a computed index with a derive function.
     /// Panics on invalid name or duplicate.
     pub fn computed<F>(self, name: &str, compute: F) -> Self
@@ -345,6 +409,7 @@ impl CollectionBuilderWithVersions {
             compute: Arc::new(compute),
             unique: false,
             sparse: false,
+            geo: None,
         };
 
         CollectionBuilderWithVersions {
@@ -357,6 +422,79 @@ impl CollectionBuilderWithVersions {
         }
     }
 
+    /// Define a geohash index over a `t::geopoint()` field.
+    ///
+    /// Records whose `field` is not a valid `{lat, lng}` point are left out of
+    /// the index. `$geoBox` filters on `field` are planned against it.
+    /// Panics on invalid name, duplicate, or precision outside `1..=12`.
+    pub fn geo_index(self, name: &str, field: &str, precision: usize) -> Self {
+        if !(1..=MAX_GEOHASH_PRECISION).contains(&precision) {
+            panic!(
+                "Geo index \"{name}\" in collection \"{}\" has precision {precision}; \
+                 expected 1..={MAX_GEOHASH_PRECISION}",
+                self.name
+            );
+        }
+
+        let path = field.to_string();
+        let mut builder = self.computed(name, move |data| {
+            let (lat, lng) = get_field_value(data, &path).and_then(point_from_value)?;
+            Some(IndexableValue::String(encode_geohash(lat, lng, precision)))
+        });
+        if let Some(IndexDefinition::Computed(c)) = builder.indexes.last_mut() {
+            c.geo = Some(GeoIndexSpec {
+                field: field.to_string(),
+                precision,
+            });
+        }
+        builder
+    }
+
+    /// Set how ids are minted for inserts that don't supply one.
+    /// Defaults to [`IdStrategy::Uuid4`].
+    pub fn id_strategy(mut self, strategy: IdStrategy) -> Self {
+        if let IdStrategy::Nanoid(len) = strategy {
+            assert!(
+                len > 0,
+                "Nanoid length for collection \"{}\" must be positive",
+                self.name
+            );
+        }
+        self.id_strategy = strategy;
+        self
+    }
+
+    /// Keep tombstones for at least `retention` after deletion, then let
+    /// scheduled purges drop the ones the server has acknowledged.
+    /// Without a retention, tombstones are only removed by explicit
+    /// `purge_tombstones` calls.
+    pub fn tombstone_retention(mut self, retention: Duration) -> Self {
+        self.tombstone_retention = Some(retention);
+        self
+    }
+
+    /// Restrict which records an actor can see. Records for which
+    /// `predicate(ctx, data)` returns `false` are omitted from `get`,
+    /// `get_all`, `query` and `count`.
+    pub fn read_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&OperationContext, &Value) -> bool + Send + Sync + 'static,
+    {
+        self.read_predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Restrict which records an actor can modify. The predicate sees both
+    /// the stored record and the record about to be written; if either is
+    /// rejected the write fails with `StorageError::Forbidden`.
+    pub fn write_predicate<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&OperationContext, &Value) -> bool + Send + Sync + 'static,
+    {
+        self.write_predicate = Some(Arc::new(predicate));
+        self
+    }
+
     /// Finalize the collection definition.
     /// Validates computed index names don't conflict with field names.
     /// Adds auto-fields to the schema.
@@ -386,6 +524,10 @@ impl CollectionBuilderWithVersions {
             indexes: self.indexes,
             current_version,
             current_schema: full_schema,
+            id_strategy: self.id_strategy,
+            tombstone_retention: self.tombstone_retention,
+            read_predicate: self.read_predicate,
+            write_predicate: self.write_predicate,
         }
     }
 }
@@ -2,11 +2,11 @@ pub mod patch_log;
 pub mod schema_aware;
 
 use json_joy::json_crdt::codec::structural::binary;
-use json_joy::json_crdt::nodes::TsKey;
+use json_joy::json_crdt::nodes::{CrdtNode, IndexExt, TsKey};
 use json_joy::json_crdt::Model;
 use json_joy::json_crdt::ModelApi;
 use json_joy::json_crdt_diff::diff_node;
-use json_joy::json_crdt_patch::Patch;
+use json_joy::json_crdt_patch::{Patch, Ts};
 use serde_json::Value;
 
 use crate::error::{LessDbError, Result};
@@ -149,6 +149,93 @@ pub fn merge_with_pending_patches(remote_model: &mut Model, pending_patches: &[P
     }
 }
 
+/// An object key written concurrently by both sides of a merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcurrentWrite {
+    /// Object keys from the root down to the conflicting key.
+    pub path: Vec<String>,
+    /// Whether the local write survived the merge.
+    pub local_won: bool,
+}
+
+/// Find object keys that `local` and `remote` each wrote without having seen
+/// the other's write.
+///
+/// `merged` must be `remote` with the local pending patches applied. json-joy
+/// resolves such keys last-writer-wins (logical time, then session ID); this
+/// reports which side that picked. Nested objects shared by both sides are
+/// walked; text and array nodes merge without conflict and are not reported.
+/// Results are sorted by path.
+pub fn concurrent_writes(local: &Model, remote: &Model, merged: &Model) -> Vec<ConcurrentWrite> {
+    let mut out = Vec::new();
+    collect_concurrent_writes(
+        local,
+        remote,
+        merged,
+        &merged.root.val,
+        &mut Vec::new(),
+        &mut out,
+    );
+    out
+}
+
+fn collect_concurrent_writes(
+    local: &Model,
+    remote: &Model,
+    merged: &Model,
+    node_id: &Ts,
+    path: &mut Vec<String>,
+    out: &mut Vec<ConcurrentWrite>,
+) {
+    let (
+        Some(CrdtNode::Obj(local_obj)),
+        Some(CrdtNode::Obj(remote_obj)),
+        Some(CrdtNode::Obj(merged_obj)),
+    ) = (
+        IndexExt::get(&local.index, node_id),
+        IndexExt::get(&remote.index, node_id),
+        IndexExt::get(&merged.index, node_id),
+    )
+    else {
+        return;
+    };
+
+    let mut keys: Vec<&String> = merged_obj.keys.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        // A key only one side has was added by that side alone.
+        let (Some(local_ts), Some(remote_ts)) = (local_obj.keys.get(key), remote_obj.keys.get(key))
+        else {
+            continue;
+        };
+        let merged_ts = &merged_obj.keys[key];
+
+        path.push(key.clone());
+        if local_ts == remote_ts {
+            collect_concurrent_writes(local, remote, merged, merged_ts, path, out);
+        } else if !has_seen(local, remote_ts) && !has_seen(remote, local_ts) {
+            out.push(ConcurrentWrite {
+                path: path.clone(),
+                local_won: merged_ts == local_ts,
+            });
+        }
+        path.pop();
+    }
+}
+
+/// Whether `model`'s clock has observed the operation with timestamp `ts`.
+fn has_seen(model: &Model, ts: &Ts) -> bool {
+    if ts.sid == model.clock.sid {
+        return ts.time < model.clock.time;
+    }
+    model
+        .clock
+        .peers
+        .get(&ts.sid)
+        .is_some_and(|peer| ts.time <= peer.time)
+}
+
 // ── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -433,6 +520,66 @@ mod tests {
         assert_eq!(view_model(&model), view_before);
     }
 
+    // ── concurrent_writes ────────────────────────────────────────────────────
+
+    /// Edit a replica of `base` under `sid`, returning the binary-decoded
+    /// replica and the patch that produced the edit.
+    fn edit_replica(base: &[u8], sid: u64, data: &Value) -> (Model, Patch) {
+        let mut model = model_load(base, sid).expect("load replica");
+        let patch = diff_model(&model, data).expect("edit should produce a patch");
+        apply_patch(&mut model, &patch);
+        let model = model_from_binary(&model_to_binary(&model)).expect("round trip");
+        (model, patch)
+    }
+
+    #[test]
+    fn concurrent_writes_ignores_disjoint_keys() {
+        let base = model_to_binary(
+            &create_model(&json!({"a": 1, "b": 1}), MIN_SESSION_ID).expect("create"),
+        );
+        let (local, local_patch) =
+            edit_replica(&base, MIN_SESSION_ID + 1, &json!({"a": 2, "b": 1}));
+        let (remote, _) = edit_replica(&base, MIN_SESSION_ID + 2, &json!({"a": 1, "b": 3}));
+
+        let mut merged = remote.clone();
+        merge_with_pending_patches(&mut merged, &[local_patch]);
+
+        assert_eq!(view_model(&merged), json!({"a": 2, "b": 3}));
+        assert!(concurrent_writes(&local, &remote, &merged).is_empty());
+    }
+
+    #[test]
+    fn concurrent_writes_reports_same_key_winner() {
+        let base = model_to_binary(
+            &create_model(&json!({"a": 1, "b": 1}), MIN_SESSION_ID).expect("create"),
+        );
+        let (local, local_patch) =
+            edit_replica(&base, MIN_SESSION_ID + 1, &json!({"a": 2, "b": 1}));
+        let (remote, _) = edit_replica(&base, MIN_SESSION_ID + 2, &json!({"a": 3, "b": 1}));
+
+        let mut merged = remote.clone();
+        merge_with_pending_patches(&mut merged, &[local_patch]);
+
+        let writes = concurrent_writes(&local, &remote, &merged);
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].path, vec!["a".to_string()]);
+        assert_eq!(writes[0].local_won, view_model(&merged)["a"] == json!(2));
+    }
+
+    #[test]
+    fn concurrent_writes_ignores_writes_the_other_side_has_seen() {
+        let base =
+            model_to_binary(&create_model(&json!({"a": 1}), MIN_SESSION_ID).expect("create"));
+        let (local, local_patch) = edit_replica(&base, MIN_SESSION_ID + 1, &json!({"a": 2}));
+
+        // The remote is untouched: its value is the base the local edit replaced.
+        let remote = model_from_binary(&base).expect("decode");
+        let mut merged = remote.clone();
+        merge_with_pending_patches(&mut merged, &[local_patch]);
+
+        assert!(concurrent_writes(&local, &remote, &merged).is_empty());
+    }
+
     // ── Clock monotonicity ──────────────────────────────────────────────────
 
     #[test]
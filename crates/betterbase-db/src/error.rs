@@ -1,6 +1,8 @@
 use std::fmt;
 use thiserror::Error;
 
+use crate::types::UniqueConflict;
+
 // ---------------------------------------------------------------------------
 // ValidationError / ValidationErrors
 // ---------------------------------------------------------------------------
@@ -77,6 +79,8 @@ pub enum StorageError {
         field: String,
     },
 
+    /// `fields` maps each indexed field (the index name, for computed
+    /// indexes) to the value that collided, in index order.
     #[error(
         "Unique constraint violation on index \"{index}\" in collection \"{collection}\": \
          value already exists in record \"{existing_id}\""
@@ -85,9 +89,25 @@ pub enum StorageError {
         collection: String,
         index: String,
         existing_id: String,
-        value: serde_json::Value,
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+
+    #[error(
+        "Unique index \"{index}\" in collection \"{collection}\" references unknown field \
+         \"{field}\""
+    )]
+    UnknownIndexField {
+        collection: String,
+        index: String,
+        field: String,
     },
 
+    #[error("Record with identical content already exists: {collection}/{id}")]
+    DuplicateContent { collection: String, id: String },
+
+    #[error("Every id minted for collection \"{collection}\" collided with an existing record ({attempts} attempts)")]
+    IdCollision { collection: String, attempts: usize },
+
     #[error("Storage corruption in {collection}/{id}: failed to parse \"{field}\" field")]
     Corruption {
         collection: String,
@@ -97,12 +117,21 @@ pub enum StorageError {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    #[error("Write to {collection}/{id} denied by the collection's write predicate")]
+    Forbidden { collection: String, id: String },
+
     #[error("Storage adapter not initialized. Call initialize() first.")]
     NotInitialized,
 
+    #[error("Snapshot is read-only: cannot {0}")]
+    ReadOnly(String),
+
     #[error("Collection \"{0}\" was not registered during initialization.")]
     CollectionNotRegistered(String),
 
+    #[error("Collection \"{0}\" already holds records.")]
+    CollectionNotEmpty(String),
+
     #[error("Transaction error: {message}")]
     Transaction {
         message: String,
@@ -115,6 +144,60 @@ pub enum StorageError {
     Sqlite(#[from] rusqlite::Error),
 }
 
+impl StorageError {
+    /// Stable machine-readable code, e.g. `"STORAGE_UNIQUE"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StorageError::NotFound { .. } => "STORAGE_NOT_FOUND",
+            StorageError::Deleted { .. } => "STORAGE_DELETED",
+            StorageError::ImmutableField { .. } => "STORAGE_IMMUTABLE_FIELD",
+            StorageError::UniqueConstraint { .. } => "STORAGE_UNIQUE",
+            StorageError::UnknownIndexField { .. } => "STORAGE_UNKNOWN_INDEX_FIELD",
+            StorageError::DuplicateContent { .. } => "STORAGE_DUPLICATE_CONTENT",
+            StorageError::IdCollision { .. } => "STORAGE_ID_COLLISION",
+            StorageError::Corruption { .. } => "STORAGE_CORRUPTION",
+            StorageError::Forbidden { .. } => "STORAGE_FORBIDDEN",
+            StorageError::NotInitialized => "STORAGE_NOT_INITIALIZED",
+            StorageError::CollectionNotRegistered(_) => "STORAGE_COLLECTION_NOT_REGISTERED",
+            StorageError::CollectionNotEmpty(_) => "STORAGE_COLLECTION_NOT_EMPTY",
+            StorageError::ReadOnly(_) => "STORAGE_READ_ONLY",
+            StorageError::Transaction { .. } => "STORAGE_TRANSACTION",
+            #[cfg(feature = "sqlite")]
+            StorageError::Sqlite(_) => "STORAGE_SQLITE",
+        }
+    }
+
+    /// The collection the error concerns, if it names one.
+    pub fn collection(&self) -> Option<&str> {
+        match self {
+            StorageError::NotFound { collection, .. }
+            | StorageError::Deleted { collection, .. }
+            | StorageError::ImmutableField { collection, .. }
+            | StorageError::UniqueConstraint { collection, .. }
+            | StorageError::UnknownIndexField { collection, .. }
+            | StorageError::DuplicateContent { collection, .. }
+            | StorageError::IdCollision { collection, .. }
+            | StorageError::Corruption { collection, .. }
+            | StorageError::Forbidden { collection, .. } => Some(collection),
+            StorageError::CollectionNotRegistered(collection)
+            | StorageError::CollectionNotEmpty(collection) => Some(collection),
+            _ => None,
+        }
+    }
+
+    /// The field the error concerns, if it names one. For unique-constraint
+    /// violations this is the index name.
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            StorageError::ImmutableField { field, .. }
+            | StorageError::Corruption { field, .. }
+            | StorageError::UnknownIndexField { field, .. } => Some(field),
+            StorageError::UniqueConstraint { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // MigrationError
 // ---------------------------------------------------------------------------
@@ -145,6 +228,15 @@ pub enum QueryError {
 
     #[error("Invalid regex: {0}")]
     InvalidRegex(String),
+
+    #[error("Invalid $geoBox: {0}")]
+    InvalidGeoBox(String),
+
+    #[error("Invalid $box: {0}")]
+    InvalidBox(String),
+
+    #[error("Raw SQL rejected: {0}")]
+    RawSqlRejected(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -190,6 +282,17 @@ pub enum SyncError {
     Storage(Box<StorageError>),
 }
 
+impl SyncError {
+    /// Stable machine-readable code; wrapped storage errors keep their own.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SyncError::Transport(_) => "SYNC_TRANSPORT",
+            SyncError::Disposed => "SYNC_DISPOSED",
+            SyncError::Storage(e) => e.code(),
+        }
+    }
+}
+
 impl From<StorageError> for SyncError {
     fn from(e: StorageError) -> Self {
         SyncError::Storage(Box::new(e))
@@ -226,10 +329,96 @@ pub enum LessDbError {
     #[error("CRDT error: {0}")]
     Crdt(String),
 
+    #[error("Subscription limit reached: {cap} subscriptions are already active")]
+    SubscriptionLimit { cap: usize },
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+impl LessDbError {
+    /// Stable machine-readable code for callers that branch on the kind of
+    /// failure (e.g. the WASM layer's `error.code`). Codes are
+    /// `SCREAMING_SNAKE_CASE`, prefixed by the error family.
+    pub fn code(&self) -> &'static str {
+        match self {
+            LessDbError::Schema(SchemaError::Validation(_)) => "SCHEMA_VALIDATION",
+            LessDbError::Schema(SchemaError::Serialization(_)) => "SCHEMA_SERIALIZATION",
+            LessDbError::Storage(e) => e.code(),
+            LessDbError::Migration(_) => "MIGRATION_FAILED",
+            LessDbError::Query(e) => match e {
+                QueryError::UnknownOperator(_) => "QUERY_UNKNOWN_OPERATOR",
+                QueryError::InvalidRegex(_) => "QUERY_INVALID_REGEX",
+                QueryError::InvalidGeoBox(_) => "QUERY_INVALID_GEO_BOX",
+                QueryError::InvalidBox(_) => "QUERY_INVALID_BOX",
+                QueryError::RawSqlRejected(_) => "QUERY_RAW_SQL_REJECTED",
+            },
+            LessDbError::Merge(_) => "MERGE_CONFLICT",
+            LessDbError::Sync(e) => e.code(),
+            LessDbError::DiffDepth(_) => "DIFF_DEPTH",
+            LessDbError::Crdt(_) => "CRDT",
+            LessDbError::SubscriptionLimit { .. } => "SUBSCRIPTION_LIMIT",
+            LessDbError::Internal(_) => "INTERNAL",
+        }
+    }
+
+    /// The collection the error concerns, if it names one.
+    pub fn collection(&self) -> Option<&str> {
+        match self {
+            LessDbError::Storage(e) => e.collection(),
+            LessDbError::Sync(e) => match e.as_ref() {
+                SyncError::Storage(e) => e.collection(),
+                _ => None,
+            },
+            LessDbError::Migration(e) => Some(&e.collection),
+            LessDbError::Merge(e) => Some(&e.collection),
+            _ => None,
+        }
+    }
+
+    /// The field the error concerns, if it names exactly one. Validation
+    /// failures report their first failing path.
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            LessDbError::Storage(e) => e.field(),
+            LessDbError::Sync(e) => match e.as_ref() {
+                SyncError::Storage(e) => e.field(),
+                _ => None,
+            },
+            LessDbError::Schema(SchemaError::Validation(errs)) => {
+                errs.0.first().map(|e| e.path.as_str())
+            }
+            _ => None,
+        }
+    }
+
+    /// The index, colliding values and existing record of a unique-constraint
+    /// violation.
+    pub fn unique_conflict(&self) -> Option<UniqueConflict> {
+        let storage = match self {
+            LessDbError::Storage(e) => e.as_ref(),
+            LessDbError::Sync(e) => match e.as_ref() {
+                SyncError::Storage(e) => e.as_ref(),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        match storage {
+            StorageError::UniqueConstraint {
+                index,
+                existing_id,
+                fields,
+                ..
+            } => Some(UniqueConflict {
+                index: index.clone(),
+                fields: fields.clone(),
+                existing_id: existing_id.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
 impl From<StorageError> for LessDbError {
     fn from(e: StorageError) -> Self {
         LessDbError::Storage(Box::new(e))
@@ -357,7 +546,10 @@ mod tests {
             collection: "users".to_string(),
             index: "email_idx".to_string(),
             existing_id: "existing-123".to_string(),
-            value: serde_json::json!("test@example.com"),
+            fields: serde_json::json!({ "email": "test@example.com" })
+                .as_object()
+                .unwrap()
+                .clone(),
         };
         let msg = e.to_string();
         assert!(msg.contains("email_idx"), "index missing: {msg}");
@@ -466,4 +658,48 @@ mod tests {
         let db_err: LessDbError = q_err.into();
         assert!(matches!(db_err, LessDbError::Query(_)));
     }
+
+    // --- Error codes ---
+
+    #[test]
+    fn unique_constraint_code_and_context() {
+        let e: LessDbError = StorageError::UniqueConstraint {
+            collection: "users".to_string(),
+            index: "idx_email".to_string(),
+            existing_id: "u1".to_string(),
+            fields: serde_json::Map::new(),
+        }
+        .into();
+        assert_eq!(e.code(), "STORAGE_UNIQUE");
+        assert_eq!(e.collection(), Some("users"));
+        assert_eq!(e.field(), Some("idx_email"));
+        assert_eq!(e.unique_conflict().unwrap().existing_id, "u1");
+    }
+
+    #[test]
+    fn sync_wrapped_storage_error_keeps_storage_code() {
+        let e: LessDbError = SyncError::from(StorageError::NotFound {
+            collection: "tasks".to_string(),
+            id: "t1".to_string(),
+        })
+        .into();
+        assert_eq!(e.code(), "STORAGE_NOT_FOUND");
+        assert_eq!(e.collection(), Some("tasks"));
+
+        let e: LessDbError = SyncError::Transport("offline".to_string()).into();
+        assert_eq!(e.code(), "SYNC_TRANSPORT");
+        assert_eq!(e.collection(), None);
+    }
+
+    #[test]
+    fn validation_error_reports_first_path() {
+        let e: LessDbError = SchemaError::Validation(ValidationErrors(vec![ValidationError {
+            path: "email".to_string(),
+            expected: "string".to_string(),
+            received: "number".to_string(),
+        }]))
+        .into();
+        assert_eq!(e.code(), "SCHEMA_VALIDATION");
+        assert_eq!(e.field(), Some("email"));
+    }
 }
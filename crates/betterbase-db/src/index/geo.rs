@@ -0,0 +1,442 @@
+//! Geohash helpers for bounding-box queries over `t::geopoint()` fields.
+//!
+//! A geo index is a computed index whose value is the geohash of a
+//! `{lat, lng}` field at a fixed precision. A `$geoBox` filter is planned as
+//! a union of scans over the geohash cells that cover the box, and every
+//! candidate is then checked against the exact box in the post-filter.
+
+use serde_json::Value;
+
+use crate::error::{QueryError, Result};
+use crate::query::operators::get_field_value;
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Top-level filter key for bounding-box queries.
+pub const GEO_BOX_OPERATOR: &str = "$geoBox";
+
+/// Field-level bounding-box operator over a `{lat, lng}` field:
+/// `{location: {$box: [[minLat, minLng], [maxLat, maxLng]]}}`.
+pub const BOX_OPERATOR: &str = "$box";
+
+/// Maximum geohash precision (characters) accepted for a geo index.
+pub const MAX_GEOHASH_PRECISION: usize = 12;
+
+/// Maximum number of covering cells the planner will scan for one query.
+/// Wider boxes are covered at a coarser precision instead.
+pub const MAX_GEO_CELLS: usize = 16;
+
+const BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+// ============================================================================
+// Geohash encoding
+// ============================================================================
+
+/// Number of (longitude, latitude) bits in a geohash of `precision` chars.
+fn axis_bits(precision: usize) -> (u32, u32) {
+    let total = 5 * precision as u32;
+    (total.div_ceil(2), total / 2)
+}
+
+/// Index of the cell containing `v` when `[min, max]` is split into `2^bits`
+/// equal cells. `max` itself falls in the last cell.
+fn cell_index(v: f64, min: f64, max: f64, bits: u32) -> u64 {
+    let cells = 1u64 << bits;
+    let scaled = ((v - min) / (max - min) * cells as f64).floor();
+    (scaled.max(0.0) as u64).min(cells - 1)
+}
+
+/// Interleave longitude/latitude cell indices into a geohash string.
+fn cell_hash(lng_idx: u64, lat_idx: u64, precision: usize) -> String {
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    let (mut lng_left, mut lat_left) = (lng_bits, lat_bits);
+    let mut out = String::with_capacity(precision);
+    let mut ch = 0usize;
+    for bit in 0..5 * precision {
+        // Even bits (counting from the most significant) carry longitude.
+        let b = if bit % 2 == 0 {
+            lng_left -= 1;
+            (lng_idx >> lng_left) & 1
+        } else {
+            lat_left -= 1;
+            (lat_idx >> lat_left) & 1
+        };
+        ch = (ch << 1) | b as usize;
+        if bit % 5 == 4 {
+            out.push(BASE32[ch] as char);
+            ch = 0;
+        }
+    }
+    out
+}
+
+/// Geohash of a point at `precision` characters (1..=12).
+pub fn encode_geohash(lat: f64, lng: f64, precision: usize) -> String {
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    cell_hash(
+        cell_index(lng, -180.0, 180.0, lng_bits),
+        cell_index(lat, -90.0, 90.0, lat_bits),
+        precision,
+    )
+}
+
+/// Read a `{lat, lng}` point. Returns `None` unless both are finite numbers
+/// within range.
+pub fn point_from_value(value: &Value) -> Option<(f64, f64)> {
+    let lat = value.get("lat")?.as_f64()?;
+    let lng = value.get("lng")?.as_f64()?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+        return None;
+    }
+    Some((lat, lng))
+}
+
+// ============================================================================
+// Bounding boxes
+// ============================================================================
+
+/// A `$geoBox` filter: `{field, min_lat, min_lng, max_lat, max_lng}`.
+///
+/// `min_lng > max_lng` denotes a box crossing the antimeridian. Latitudes
+/// past ±90 continue over the pole onto the opposite meridian.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoBox {
+    pub field: String,
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+/// A box that neither wraps the antimeridian nor crosses a pole.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimpleBox {
+    pub min_lat: f64,
+    pub min_lng: f64,
+    pub max_lat: f64,
+    pub max_lng: f64,
+}
+
+impl SimpleBox {
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        lat >= self.min_lat && lat <= self.max_lat && lng >= self.min_lng && lng <= self.max_lng
+    }
+}
+
+/// Parse a `$box` operand, `[[minLat, minLng], [maxLat, maxLng]]`.
+///
+/// Unlike `$geoBox` there is no antimeridian wrapping: `$box` plans as two
+/// plain ranges, so each corner pair must be ordered.
+pub fn parse_box_operand(operand: &Value) -> Result<SimpleBox> {
+    let invalid = |msg: &str| QueryError::InvalidBox(msg.to_string());
+    let corner = |v: Option<&Value>| -> Result<(f64, f64)> {
+        let pair = v
+            .and_then(|c| c.as_array())
+            .filter(|c| c.len() == 2)
+            .ok_or_else(|| invalid("expected [[minLat, minLng], [maxLat, maxLng]]"))?;
+        let num = |v: &Value| v.as_f64().filter(|f| f.is_finite());
+        match (num(&pair[0]), num(&pair[1])) {
+            (Some(lat), Some(lng)) => Ok((lat, lng)),
+            _ => Err(invalid("corners must be finite numbers").into()),
+        }
+    };
+    let corners = operand
+        .as_array()
+        .filter(|c| c.len() == 2)
+        .ok_or_else(|| invalid("expected [[minLat, minLng], [maxLat, maxLng]]"))?;
+    let (min_lat, min_lng) = corner(corners.first())?;
+    let (max_lat, max_lng) = corner(corners.get(1))?;
+    if min_lat > max_lat || min_lng > max_lng {
+        return Err(invalid("min corner must not exceed max corner").into());
+    }
+    Ok(SimpleBox {
+        min_lat,
+        min_lng,
+        max_lat,
+        max_lng,
+    })
+}
+
+/// Wrap a longitude into `[-180, 180]`, keeping 180 itself.
+fn wrap_lng(lng: f64) -> f64 {
+    if (-180.0..=180.0).contains(&lng) {
+        lng
+    } else {
+        (lng + 180.0).rem_euclid(360.0) - 180.0
+    }
+}
+
+/// Split a longitude span into non-wrapping spans.
+fn split_lng(min_lng: f64, max_lng: f64) -> Vec<(f64, f64)> {
+    if max_lng - min_lng >= 360.0 {
+        return vec![(-180.0, 180.0)];
+    }
+    let (lo, hi) = (wrap_lng(min_lng), wrap_lng(max_lng));
+    if lo <= hi {
+        vec![(lo, hi)]
+    } else {
+        vec![(lo, 180.0), (-180.0, hi)]
+    }
+}
+
+impl GeoBox {
+    /// Parse the operand of a `$geoBox` filter.
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let invalid = |msg: &str| QueryError::InvalidGeoBox(msg.to_string());
+        let obj = value
+            .as_object()
+            .ok_or_else(|| invalid("expected an object"))?;
+        let field = obj
+            .get("field")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| invalid("missing field"))?
+            .to_string();
+        let num = |key: &'static str| {
+            obj.get(key)
+                .and_then(|v| v.as_f64())
+                .filter(|f| f.is_finite())
+                .ok_or_else(|| QueryError::InvalidGeoBox(format!("missing or invalid {key}")))
+        };
+        let geo_box = Self {
+            field,
+            min_lat: num("min_lat")?,
+            min_lng: num("min_lng")?,
+            max_lat: num("max_lat")?,
+            max_lng: num("max_lng")?,
+        };
+        if geo_box.min_lat > geo_box.max_lat {
+            return Err(invalid("min_lat must not exceed max_lat").into());
+        }
+        Ok(geo_box)
+    }
+
+    /// Split into boxes that neither wrap the antimeridian nor cross a pole.
+    pub fn split(&self) -> Vec<SimpleBox> {
+        // Latitude bands, each with a longitude shift (180 for the part that
+        // continues over a pole onto the opposite meridian).
+        let mut bands = vec![(self.min_lat.max(-90.0), self.max_lat.min(90.0), 0.0)];
+        if self.max_lat > 90.0 {
+            bands.push(((180.0 - self.max_lat).max(-90.0), 90.0, 180.0));
+        }
+        if self.min_lat < -90.0 {
+            bands.push((-90.0, (-180.0 - self.min_lat).min(90.0), 180.0));
+        }
+
+        let mut boxes = Vec::new();
+        for (min_lat, max_lat, shift) in bands {
+            if min_lat > max_lat {
+                continue;
+            }
+            for (min_lng, max_lng) in split_lng(self.min_lng + shift, self.max_lng + shift) {
+                boxes.push(SimpleBox {
+                    min_lat,
+                    min_lng,
+                    max_lat,
+                    max_lng,
+                });
+            }
+        }
+        boxes
+    }
+
+    /// Whether the point stored at `self.field` in `record` lies in the box.
+    pub fn matches(&self, record: &Value) -> bool {
+        let Some((lat, lng)) = get_field_value(record, &self.field).and_then(point_from_value)
+        else {
+            return false;
+        };
+        self.split().iter().any(|b| b.contains(lat, lng))
+    }
+}
+
+// ============================================================================
+// Covering cells
+// ============================================================================
+
+/// Geohash cells covering a box, all at the same precision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoCover {
+    pub precision: usize,
+    pub cells: Vec<String>,
+}
+
+/// Inclusive cell index ranges covering `b` at `precision`.
+fn cell_ranges(b: &SimpleBox, precision: usize) -> ((u64, u64), (u64, u64)) {
+    let (lng_bits, lat_bits) = axis_bits(precision);
+    (
+        (
+            cell_index(b.min_lng, -180.0, 180.0, lng_bits),
+            cell_index(b.max_lng, -180.0, 180.0, lng_bits),
+        ),
+        (
+            cell_index(b.min_lat, -90.0, 90.0, lat_bits),
+            cell_index(b.max_lat, -90.0, 90.0, lat_bits),
+        ),
+    )
+}
+
+/// Cover `geo_box` with geohash cells at the finest precision up to
+/// `max_precision` that needs no more than `max_cells` cells.
+///
+/// Returns `None` if even a single-character cover exceeds `max_cells`.
+pub fn cover_box(geo_box: &GeoBox, max_precision: usize, max_cells: usize) -> Option<GeoCover> {
+    let boxes = geo_box.split();
+    for precision in (1..=max_precision.min(MAX_GEOHASH_PRECISION)).rev() {
+        let count: u64 = boxes
+            .iter()
+            .map(|b| {
+                let ((lng_lo, lng_hi), (lat_lo, lat_hi)) = cell_ranges(b, precision);
+                (lng_hi - lng_lo + 1) * (lat_hi - lat_lo + 1)
+            })
+            .sum();
+        if count > max_cells as u64 {
+            continue;
+        }
+
+        let mut cells = Vec::with_capacity(count as usize);
+        for b in &boxes {
+            let ((lng_lo, lng_hi), (lat_lo, lat_hi)) = cell_ranges(b, precision);
+            for lng_idx in lng_lo..=lng_hi {
+                for lat_idx in lat_lo..=lat_hi {
+                    cells.push(cell_hash(lng_idx, lat_idx, precision));
+                }
+            }
+        }
+        cells.sort();
+        cells.dedup();
+        return Some(GeoCover { precision, cells });
+    }
+    None
+}
+
+/// Exclusive upper bound for a prefix range scan: every geohash starting
+/// with `prefix` sorts below `prefix + "{"` (`'{'` follows `'z'`).
+pub fn prefix_upper_bound(prefix: &str) -> String {
+    format!("{prefix}{{")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn encodes_known_geohashes() {
+        // Reference values from the original geohash.org implementation.
+        assert_eq!(encode_geohash(57.64911, 10.40744, 11), "u4pruydqqvj");
+        assert_eq!(encode_geohash(-25.382708, -49.265506, 8), "6gkzwgjz");
+        assert_eq!(encode_geohash(0.0, 0.0, 1), "s");
+    }
+
+    #[test]
+    fn coarser_hash_is_prefix_of_finer() {
+        let fine = encode_geohash(37.7749, -122.4194, 9);
+        for p in 1..9 {
+            assert_eq!(encode_geohash(37.7749, -122.4194, p), fine[..p]);
+        }
+    }
+
+    #[test]
+    fn extreme_coordinates_stay_in_range() {
+        assert_eq!(encode_geohash(90.0, 180.0, 4), "zzzz");
+        assert_eq!(encode_geohash(-90.0, -180.0, 4), "0000");
+    }
+
+    #[test]
+    fn parse_rejects_bad_operands() {
+        assert!(GeoBox::from_value(&json!("nope")).is_err());
+        assert!(GeoBox::from_value(
+            &json!({"min_lat": 0, "min_lng": 0, "max_lat": 1, "max_lng": 1})
+        )
+        .is_err());
+        assert!(GeoBox::from_value(
+            &json!({"field": "loc", "min_lat": 2, "min_lng": 0, "max_lat": 1, "max_lng": 1})
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn antimeridian_box_splits_in_two() {
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: -10.0,
+            min_lng: 170.0,
+            max_lat: 10.0,
+            max_lng: -170.0,
+        };
+        let parts = b.split();
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].contains(0.0, 175.0));
+        assert!(parts[1].contains(0.0, -175.0));
+        assert!(!parts.iter().any(|p| p.contains(0.0, 0.0)));
+    }
+
+    #[test]
+    fn pole_crossing_box_continues_on_opposite_meridian() {
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: 80.0,
+            min_lng: -10.0,
+            max_lat: 95.0,
+            max_lng: 10.0,
+        };
+        let point = |lat: f64, lng: f64| json!({ "loc": { "lat": lat, "lng": lng } });
+        assert!(b.matches(&point(85.0, 0.0)));
+        // 95°N along lng 0 is 85°N along lng 180.
+        assert!(b.matches(&point(86.0, 180.0)));
+        assert!(!b.matches(&point(84.0, 180.0)));
+        assert!(!b.matches(&point(86.0, 90.0)));
+    }
+
+    #[test]
+    fn cover_respects_cell_budget_and_prefers_finest_precision() {
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: 37.70,
+            min_lng: -122.52,
+            max_lat: 37.82,
+            max_lng: -122.35,
+        };
+        for max_cells in [1, 4, 16, 64] {
+            let cover = cover_box(&b, 8, max_cells).unwrap();
+            assert!(cover.cells.len() <= max_cells);
+            assert!(cover.cells.iter().all(|c| c.len() == cover.precision));
+            if cover.precision < 8 {
+                let finer = cover_box(&b, cover.precision + 1, usize::MAX).unwrap();
+                assert!(finer.cells.len() > max_cells);
+            }
+        }
+    }
+
+    #[test]
+    fn cover_contains_every_point_in_box() {
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: -5.0,
+            min_lng: 175.0,
+            max_lat: 5.0,
+            max_lng: -175.0,
+        };
+        let cover = cover_box(&b, 6, MAX_GEO_CELLS).unwrap();
+        for (lat, lng) in [(0.0, 179.9), (-5.0, 175.0), (5.0, -175.0), (1.0, -180.0)] {
+            let hash = encode_geohash(lat, lng, cover.precision);
+            assert!(cover.cells.contains(&hash), "{lat},{lng} -> {hash}");
+        }
+    }
+
+    #[test]
+    fn whole_world_needs_more_than_a_tiny_budget() {
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: -90.0,
+            min_lng: -180.0,
+            max_lat: 90.0,
+            max_lng: 180.0,
+        };
+        assert!(cover_box(&b, 6, 4).is_none());
+        assert_eq!(cover_box(&b, 6, 32).unwrap().cells.len(), 32);
+    }
+}
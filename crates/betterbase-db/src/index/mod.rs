@@ -1,2 +1,4 @@
+pub mod geo;
 pub mod planner;
+pub mod stats;
 pub mod types;
@@ -5,6 +5,11 @@ use std::collections::{HashMap, HashSet};
 
 use serde_json::Value;
 
+use crate::index::geo::{
+    cover_box, parse_box_operand, prefix_upper_bound, GeoBox, BOX_OPERATOR, GEO_BOX_OPERATOR,
+    MAX_GEO_CELLS,
+};
+use crate::index::stats::IndexStats;
 use crate::index::types::{
     ComputedIndex, FieldIndex, IndexDefinition, IndexScan, IndexScanType, IndexSortOrder,
     IndexableValue, RangeBound,
@@ -19,6 +24,19 @@ use crate::query::types::{SortDirection, SortEntry};
 /// Maximum number of $in values before falling back to a full scan.
 const MAX_IN_VALUES: usize = 20;
 
+/// Maximum number of `$or` branches planned as a union of index scans.
+const MAX_OR_BRANCHES: usize = MAX_IN_VALUES;
+
+/// Score of a full table scan.
+const FULL_SCAN_SCORE: f64 = 6.0;
+
+/// Score of a single-column range scan when nothing is known about the data.
+const RANGE_SCORE: f64 = 5.0;
+
+/// Score of a sampled range scan expected to match nothing. Stays above
+/// equality scans (4.0), which sampling says nothing about.
+const MIN_SAMPLED_RANGE_SCORE: f64 = 4.1;
+
 // ============================================================================
 // QueryPlan
 // ============================================================================
@@ -28,6 +46,10 @@ const MAX_IN_VALUES: usize = 20;
 pub struct QueryPlan {
     /// Index scan to execute (None = full table scan).
     pub scan: Option<IndexScan>,
+    /// Further scans whose results are unioned with `scan` (deduplicated by
+    /// id). Used for `$geoBox` covers with several cells, `$in` over a range,
+    /// and a top-level `$or` whose branches each use an index.
+    pub union_scans: Vec<IndexScan>,
     /// Conditions not covered by the index (applied after index scan).
     pub post_filter: Option<Value>,
     /// Whether the index provides the required sort order.
@@ -54,6 +76,13 @@ pub struct ExtractedConditions {
     pub ranges: HashMap<String, (Option<RangeBound>, Option<RangeBound>)>,
     pub ins: HashMap<String, Vec<IndexableValue>>,
     pub computed: HashMap<String, ComputedCondition>,
+    /// Parsed `$geoBox` condition. The box itself stays in `residual`: geohash
+    /// cells over-approximate it, so the exact test always runs as a post-filter.
+    pub geo_box: Option<GeoBox>,
+    /// Fields whose condition matches only non-null values (`$ne: null`,
+    /// `$regex`). A sparse index on the field holds a superset of the matches
+    /// in its keys; the condition itself stays in `residual`.
+    pub non_null: HashSet<String>,
     pub residual: Option<Value>,
 }
 
@@ -95,6 +124,8 @@ pub fn extract_conditions(filter: Option<&Value>) -> ExtractedConditions {
         ranges: HashMap::new(),
         ins: HashMap::new(),
         computed: HashMap::new(),
+        geo_box: None,
+        non_null: HashSet::new(),
         residual: None,
     };
 
@@ -131,6 +162,14 @@ pub fn extract_conditions(filter: Option<&Value>) -> ExtractedConditions {
             continue;
         }
 
+        // $geoBox → residual, remembered for geo index selection
+        if key == GEO_BOX_OPERATOR {
+            result.geo_box = GeoBox::from_value(value).ok();
+            residual_parts.insert(key.clone(), value.clone());
+            has_residual = true;
+            continue;
+        }
+
         // Null/undefined direct value → residual (not indexable, must be preserved)
         if value.is_null() {
             residual_parts.insert(key.clone(), value.clone());
@@ -158,74 +197,84 @@ pub fn extract_conditions(filter: Option<&Value>) -> ExtractedConditions {
         // Operator object
         let ops = value.as_object().unwrap();
 
-        // $eq with an indexable value is extracted; non-indexable values (arrays,
-        // objects) fall through to the residual path at the end of the loop.
-        if let Some(eq_val) = ops.get("$eq") {
-            if is_indexable_value(eq_val) {
-                if let Some(iv) = value_to_indexable(eq_val) {
-                    result.equalities.insert(key.clone(), iv);
-                    continue;
-                }
+        // $box → ranges on `<field>.lat` and `<field>.lng`, so a compound
+        // (lat, lng) index can range-scan it. The box stays in the residual
+        // because the scan only narrows on its leading field.
+        if let Some(box_val) = ops.get(BOX_OPERATOR) {
+            if let Ok(bounds) = parse_box_operand(box_val) {
+                let inclusive = |v: f64| {
+                    Some(RangeBound {
+                        value: IndexableValue::Number(v),
+                        inclusive: true,
+                    })
+                };
+                result.ranges.insert(
+                    format!("{key}.lat"),
+                    (inclusive(bounds.min_lat), inclusive(bounds.max_lat)),
+                );
+                result.ranges.insert(
+                    format!("{key}.lng"),
+                    (inclusive(bounds.min_lng), inclusive(bounds.max_lng)),
+                );
             }
+            residual_parts.insert(key.clone(), value.clone());
+            has_residual = true;
+            continue;
         }
 
-        // $in
-        if let Some(in_val) = ops.get("$in") {
-            if let Some(arr) = in_val.as_array() {
-                let values: Vec<IndexableValue> =
-                    arr.iter().filter_map(value_to_indexable).collect();
-                if values.len() == arr.len() && values.len() <= MAX_IN_VALUES && !values.is_empty()
-                {
-                    result.ins.insert(key.clone(), values);
-                    continue;
-                }
-            }
+        // Records store every schema field, null when unset, so `$exists: true`
+        // alone also matches nulls and is not a non-null condition.
+        if ops.get("$ne") == Some(&Value::Null) || ops.contains_key("$regex") {
+            result.non_null.insert(key.clone());
         }
 
-        // Range operators
-        let has_range = ops.contains_key("$gt")
-            || ops.contains_key("$gte")
-            || ops.contains_key("$lt")
-            || ops.contains_key("$lte");
-
-        if has_range {
-            let lower = if let Some(v) = ops.get("$gt").filter(|v| is_indexable_value(v)) {
-                value_to_indexable(v).map(|iv| RangeBound {
-                    value: iv,
-                    inclusive: false,
-                })
-            } else if let Some(v) = ops.get("$gte").filter(|v| is_indexable_value(v)) {
-                value_to_indexable(v).map(|iv| RangeBound {
-                    value: iv,
-                    inclusive: true,
-                })
-            } else {
-                None
-            };
+        // Operators turned into index bounds below. Any other operator on the
+        // same field ($ne, $nin, $regex, $exists, $contains, etc.) keeps the
+        // whole condition in the residual: the chosen index covers the field,
+        // and covered fields are otherwise left out of the post-filter.
+        let mut extracted: Vec<&str> = Vec::new();
+
+        let eq_val = ops
+            .get("$eq")
+            .filter(|v| is_indexable_value(v))
+            .and_then(value_to_indexable);
+        let in_vals = ops.get("$in").and_then(Value::as_array).and_then(|arr| {
+            let values: Vec<IndexableValue> = arr.iter().filter_map(value_to_indexable).collect();
+            (values.len() == arr.len() && values.len() <= MAX_IN_VALUES && !values.is_empty())
+                .then_some(values)
+        });
 
-            let upper = if let Some(v) = ops.get("$lt").filter(|v| is_indexable_value(v)) {
-                value_to_indexable(v).map(|iv| RangeBound {
-                    value: iv,
-                    inclusive: false,
-                })
-            } else if let Some(v) = ops.get("$lte").filter(|v| is_indexable_value(v)) {
-                value_to_indexable(v).map(|iv| RangeBound {
-                    value: iv,
-                    inclusive: true,
-                })
-            } else {
-                None
+        // $eq with an indexable value is extracted; non-indexable values (arrays,
+        // objects) fall through to $in, ranges and the residual.
+        if let Some(iv) = eq_val {
+            result.equalities.insert(key.clone(), iv);
+            extracted.push("$eq");
+        } else if let Some(values) = in_vals {
+            result.ins.insert(key.clone(), values);
+            extracted.push("$in");
+        } else {
+            let bound = |op: &'static str, inclusive: bool| {
+                ops.get(op)
+                    .filter(|v| is_indexable_value(v))
+                    .and_then(value_to_indexable)
+                    .map(|value| (op, RangeBound { value, inclusive }))
             };
+            let lower = bound("$gt", false).or_else(|| bound("$gte", true));
+            let upper = bound("$lt", false).or_else(|| bound("$lte", true));
 
             if lower.is_some() || upper.is_some() {
-                result.ranges.insert(key.clone(), (lower, upper));
-                continue;
+                extracted.extend(lower.as_ref().map(|(op, _)| *op));
+                extracted.extend(upper.as_ref().map(|(op, _)| *op));
+                result
+                    .ranges
+                    .insert(key.clone(), (lower.map(|(_, b)| b), upper.map(|(_, b)| b)));
             }
         }
 
-        // Other operators ($ne, $nin, $contains, etc.) → residual
-        residual_parts.insert(key.clone(), value.clone());
-        has_residual = true;
+        if extracted.is_empty() || ops.keys().any(|op| !extracted.contains(&op.as_str())) {
+            residual_parts.insert(key.clone(), value.clone());
+            has_residual = true;
+        }
     }
 
     if has_residual {
@@ -335,6 +384,7 @@ fn extract_computed_condition(index_name: &str, condition: &Value) -> Option<Com
 
 struct IndexScore {
     scan: IndexScan,
+    union_scans: Vec<IndexScan>,
     score: f64,
     covered_conditions: HashSet<String>,
     provides_sort: bool,
@@ -347,7 +397,9 @@ fn score_index(
 ) -> Option<IndexScore> {
     match index {
         IndexDefinition::Field(fi) => score_field_index(fi, conditions, sort),
-        IndexDefinition::Computed(ci) => score_computed_index(ci, conditions),
+        IndexDefinition::Computed(ci) => {
+            score_computed_index(ci, conditions).or_else(|| score_geo_index(ci, conditions))
+        }
     }
 }
 
@@ -362,7 +414,7 @@ fn score_field_index(
     let mut in_values: Option<Vec<IndexableValue>> = None;
 
     // Walk index fields in order
-    for index_field in &index.fields {
+    for (position, index_field) in index.fields.iter().enumerate() {
         let field_name = &index_field.field;
 
         // Check equality first
@@ -377,7 +429,17 @@ fn score_field_index(
             if values.len() <= MAX_IN_VALUES {
                 in_values = Some(values.clone());
                 covered_conditions.insert(field_name.clone());
-                // After $in, can't use more index fields
+                // A range on the next field is still usable: the scan is
+                // split into one bounded range per $in value below.
+                let next_range = index
+                    .fields
+                    .get(position + 1)
+                    .and_then(|next| Some((next, conditions.ranges.get(&next.field)?)));
+                if let Some((next, bounds)) = next_range {
+                    range_bounds = Some(bounds.clone());
+                    covered_conditions.insert(next.field.clone());
+                }
+                // After $in (and its range), can't use more index fields
                 break;
             }
         }
@@ -390,13 +452,23 @@ fn score_field_index(
             break;
         }
 
+        // Non-null condition on a sparse index: an unbounded range over its keys
+        if index.sparse && conditions.non_null.contains(field_name) {
+            range_bounds = Some((None, None));
+            covered_conditions.insert(field_name.clone());
+            break;
+        }
+
         // Field not in conditions — stop traversal
         break;
     }
 
+    // One range per $in value: the union of those scans is not in index order
+    let in_over_range = in_values.is_some() && range_bounds.is_some();
+
     // Check if index provides sort order
     let sort_match = check_sort_match(index, conditions, sort);
-    let provides_sort = sort_match != SortMatch::None;
+    let provides_sort = sort_match != SortMatch::None && !in_over_range;
 
     // If no conditions covered and no sort match, index is not useful
     if covered_conditions.is_empty() && !provides_sort {
@@ -422,6 +494,7 @@ fn score_field_index(
         };
         return Some(IndexScore {
             scan,
+            union_scans: Vec::new(),
             score: 5.5,
             covered_conditions,
             provides_sort: true,
@@ -447,7 +520,7 @@ fn score_field_index(
     } else if scan_type == IndexScanType::Exact || scan_type == IndexScanType::Prefix {
         4.0
     } else {
-        5.0
+        RANGE_SCORE
     };
 
     // direction means "scan direction relative to index": Asc = forward, Desc = backward
@@ -470,15 +543,48 @@ fn score_field_index(
         in_values,
         direction,
     };
+    let (scan, union_scans) = if in_over_range {
+        split_in_over_range(scan)
+    } else {
+        (scan, Vec::new())
+    };
 
     Some(IndexScore {
         scan,
+        union_scans,
         score,
         covered_conditions,
         provides_sort,
     })
 }
 
+/// Split a scan with both `$in` values and a range on the following field
+/// into one range scan per `$in` value, each value appended to the equality
+/// prefix. Returns the first scan and the rest to union with it.
+fn split_in_over_range(scan: IndexScan) -> (IndexScan, Vec<IndexScan>) {
+    let prefix = scan.equality_values.clone().unwrap_or_default();
+    let mut scans: Vec<IndexScan> = scan
+        .in_values
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|value| {
+            let mut equality_values = prefix.clone();
+            equality_values.push(value);
+            IndexScan {
+                equality_values: Some(equality_values),
+                in_values: None,
+                ..scan.clone()
+            }
+        })
+        .collect();
+    if scans.is_empty() {
+        return (scan, Vec::new());
+    }
+    let first = scans.remove(0);
+    (first, scans)
+}
+
 fn score_computed_index(
     index: &ComputedIndex,
     conditions: &ExtractedConditions,
@@ -508,7 +614,7 @@ fn score_computed_index(
         } else if let Some(ref range) = computed_cond.range {
             (
                 IndexScanType::Range,
-                5.0,
+                RANGE_SCORE,
                 None,
                 range.0.clone(),
                 range.1.clone(),
@@ -530,12 +636,73 @@ fn score_computed_index(
 
     Some(IndexScore {
         scan,
+        union_scans: Vec::new(),
         score,
         covered_conditions,
         provides_sort: false,
     })
 }
 
+/// Score a geohash index against a `$geoBox` condition on its source field.
+///
+/// The box is covered by at most `MAX_GEO_CELLS` cells. At the index's own
+/// precision the cells are matched with a single `$in`-style scan; a coarser
+/// cover becomes one prefix range scan per cell.
+fn score_geo_index(index: &ComputedIndex, conditions: &ExtractedConditions) -> Option<IndexScore> {
+    let spec = index.geo.as_ref()?;
+    let geo_box = conditions.geo_box.as_ref()?;
+    if geo_box.field != spec.field {
+        return None;
+    }
+    let cover = cover_box(geo_box, spec.precision, MAX_GEO_CELLS)?;
+
+    let scan_for = |in_values, range_lower, range_upper| IndexScan {
+        scan_type: IndexScanType::Range,
+        index: IndexDefinition::Computed(index.clone()),
+        equality_values: None,
+        range_lower,
+        range_upper,
+        in_values,
+        direction: IndexSortOrder::Asc,
+    };
+
+    let mut scans: Vec<IndexScan> = if cover.precision == spec.precision {
+        let cells = cover
+            .cells
+            .into_iter()
+            .map(IndexableValue::String)
+            .collect();
+        vec![scan_for(Some(cells), None, None)]
+    } else {
+        cover
+            .cells
+            .iter()
+            .map(|cell| {
+                scan_for(
+                    None,
+                    Some(RangeBound {
+                        value: IndexableValue::String(cell.clone()),
+                        inclusive: true,
+                    }),
+                    Some(RangeBound {
+                        value: IndexableValue::String(prefix_upper_bound(cell)),
+                        inclusive: false,
+                    }),
+                )
+            })
+            .collect()
+    };
+    let scan = scans.remove(0);
+
+    Some(IndexScore {
+        scan,
+        union_scans: scans,
+        score: 4.5,
+        covered_conditions: HashSet::new(),
+        provides_sort: false,
+    })
+}
+
 /// Whether the index can satisfy the requested sort order.
 ///
 /// "Forward" and "Reverse" refer to *scan direction relative to the index's
@@ -565,6 +732,15 @@ fn check_sort_match(
         _ => return SortMatch::None,
     };
 
+    // Index scans order nulls as the greatest value; any other placement is
+    // left to the in-memory post-sort.
+    if sort
+        .iter()
+        .any(|e| e.nulls_order() != SortEntry::default_nulls(&e.direction))
+    {
+        return SortMatch::None;
+    }
+
     // Count equality prefix (these fields are fixed and don't affect sort)
     let equality_prefix_len = index
         .fields
@@ -617,6 +793,18 @@ pub fn plan_query(
     filter: Option<&Value>,
     sort: Option<&[SortEntry]>,
     indexes: &[IndexDefinition],
+) -> QueryPlan {
+    plan_query_with_stats(filter, sort, indexes, &[])
+}
+
+/// Like [`plan_query`], but ranks single-column range scans by the
+/// selectivity `stats` estimates for them, so a narrow range beats a wide
+/// one. Indexes without stats keep the fixed range score.
+pub fn plan_query_with_stats(
+    filter: Option<&Value>,
+    sort: Option<&[SortEntry]>,
+    indexes: &[IndexDefinition],
+    stats: &[IndexStats],
 ) -> QueryPlan {
     let conditions = extract_conditions(filter);
 
@@ -625,6 +813,9 @@ pub fn plan_query(
         .iter()
         .filter_map(|idx| score_index(idx, &conditions, sort))
         .collect();
+    for score in &mut scores {
+        apply_range_stats(score, stats);
+    }
 
     // Select best (lowest score)
     scores.sort_by(|a, b| {
@@ -633,15 +824,35 @@ pub fn plan_query(
             .unwrap_or(std::cmp::Ordering::Equal)
     });
 
-    let best = match scores.into_iter().next() {
+    let best = scores.into_iter().next();
+
+    // A top-level $or can beat the best single index as a union of scans
+    let single_cost = best.as_ref().map_or(FULL_SCAN_SCORE, |s| s.score);
+    if let Some((mut scans, cost)) = plan_or_union(filter, indexes, stats) {
+        if cost < single_cost {
+            let scan = scans.remove(0);
+            return QueryPlan {
+                scan: Some(scan),
+                union_scans: scans,
+                // Scans only narrow the candidates; the whole filter still applies
+                post_filter: filter.cloned(),
+                index_provides_sort: false,
+                post_sort: sort.map(|s| s.to_vec()),
+                estimated_cost: cost,
+            };
+        }
+    }
+
+    let best = match best {
         None => {
             // Full table scan
             return QueryPlan {
                 scan: None,
+                union_scans: Vec::new(),
                 post_filter: filter.cloned(),
                 index_provides_sort: false,
                 post_sort: sort.map(|s| s.to_vec()),
-                estimated_cost: 6.0,
+                estimated_cost: FULL_SCAN_SCORE,
             };
         }
         Some(s) => s,
@@ -656,6 +867,7 @@ pub fn plan_query(
 
     QueryPlan {
         scan: Some(best.scan),
+        union_scans: best.union_scans,
         post_filter,
         index_provides_sort: best.provides_sort,
         post_sort,
@@ -663,6 +875,60 @@ pub fn plan_query(
     }
 }
 
+/// Plan a top-level `$or` as one index scan per branch, each branch on the
+/// best index for its own conditions. Returns the scans, to be unioned, and
+/// the sum of their scores, or `None` if any branch can't use an index.
+fn plan_or_union(
+    filter: Option<&Value>,
+    indexes: &[IndexDefinition],
+    stats: &[IndexStats],
+) -> Option<(Vec<IndexScan>, f64)> {
+    let branches = filter?.get("$or")?.as_array()?;
+    if branches.is_empty() || branches.len() > MAX_OR_BRANCHES {
+        return None;
+    }
+
+    let mut scans = Vec::new();
+    let mut cost = 0.0;
+    for branch in branches {
+        let conditions = extract_conditions(Some(branch));
+        let best = indexes
+            .iter()
+            .filter_map(|idx| score_index(idx, &conditions, None))
+            .map(|mut score| {
+                apply_range_stats(&mut score, stats);
+                score
+            })
+            .min_by(|a, b| {
+                a.score
+                    .partial_cmp(&b.score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })?;
+        cost += best.score;
+        scans.push(best.scan);
+        scans.extend(best.union_scans);
+    }
+    Some((scans, cost))
+}
+
+/// Rescore a plain range scan within [`MIN_SAMPLED_RANGE_SCORE`, `RANGE_SCORE`]
+/// by its sampled selectivity. Only ranges on the leading index column
+/// qualify, since that is the column the stats describe. An unbounded range
+/// over a sparse index is rated by its non-null fraction.
+fn apply_range_stats(score: &mut IndexScore, stats: &[IndexStats]) {
+    let scan = &score.scan;
+    if score.score != RANGE_SCORE || scan.equality_values.is_some() || scan.in_values.is_some() {
+        return;
+    }
+    let Some(index_stats) = stats.iter().find(|s| s.index_name == scan.index.name()) else {
+        return;
+    };
+    let selectivity =
+        index_stats.range_selectivity(scan.range_lower.as_ref(), scan.range_upper.as_ref());
+    score.score = MIN_SAMPLED_RANGE_SCORE
+        + (RANGE_SCORE - MIN_SAMPLED_RANGE_SCORE) * selectivity.clamp(0.0, 1.0);
+}
+
 /// Build the residual filter — conditions not covered by the chosen index.
 fn build_residual_filter(
     original_filter: Option<&Value>,
@@ -785,6 +1051,19 @@ pub fn explain_plan(plan: &QueryPlan) -> String {
                 IndexSortOrder::Desc => "desc",
             }
         ));
+
+        if !plan.union_scans.is_empty() {
+            lines.push(format!("Union scans: {}", plan.union_scans.len()));
+            let mut names: Vec<&str> = vec![scan.index.name()];
+            for union_scan in &plan.union_scans {
+                if !names.contains(&union_scan.index.name()) {
+                    names.push(union_scan.index.name());
+                }
+            }
+            if names.len() > 1 {
+                lines.push(format!("Union indexes: {}", names.join(", ")));
+            }
+        }
     } else {
         lines.push("Full table scan".to_string());
     }
@@ -871,6 +1150,7 @@ mod tests {
             compute: Arc::new(compute),
             unique,
             sparse,
+            geo: None,
         })
     }
 
@@ -0,0 +1,195 @@
+//! Sampled index statistics for range selectivity.
+//!
+//! There is no `ANALYZE` pass, so the planner knows nothing about how keys
+//! are distributed. [`IndexStats::from_sample`] reads a bounded sample of
+//! records, takes each one's leading index key, and keeps a crude equi-depth
+//! histogram: the min, the max, and evenly spaced interior quantiles. The
+//! planner uses it to rank a narrow range scan below a wide one.
+
+use std::cmp::Ordering;
+
+use crate::error::Result;
+use crate::index::planner::value_to_indexable;
+use crate::index::types::{IndexDefinition, IndexableValue, RangeBound};
+use crate::query::operators::get_field_value;
+use crate::storage::traits::StorageBackend;
+use crate::types::{ScanOptions, SerializedRecord};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Equi-depth buckets kept per index.
+const HISTOGRAM_BUCKETS: usize = 8;
+
+/// Evenly spaced windows a sample is read from, so it is not just the
+/// oldest records.
+const SAMPLE_WINDOWS: usize = 4;
+
+// ============================================================================
+// IndexStats
+// ============================================================================
+
+/// Sampled key distribution of one index.
+#[derive(Debug, Clone)]
+pub struct IndexStats {
+    /// Name of the sampled index.
+    pub index_name: String,
+    /// Number of records read.
+    pub sampled: usize,
+    /// Fraction of sampled records with a non-null leading key.
+    pub non_null_fraction: f64,
+    /// Sorted histogram bounds from min to max; each adjacent pair holds an
+    /// equal share of the sampled keys. Empty when no key was sampled.
+    pub bounds: Vec<IndexableValue>,
+}
+
+impl IndexStats {
+    /// Build stats for `index` from at most `sample_size` live records.
+    ///
+    /// Collections no larger than `sample_size` are read in full; larger ones
+    /// are read in a few windows spread across the collection.
+    pub fn from_sample<B: StorageBackend>(
+        backend: &B,
+        collection: &str,
+        index: &IndexDefinition,
+        sample_size: usize,
+    ) -> Result<Self> {
+        let records = sample_records(backend, collection, sample_size)?;
+        let keys: Vec<IndexableValue> = records
+            .iter()
+            .filter_map(|r| leading_key(index, r))
+            .collect();
+        Ok(Self::from_keys(index.name(), records.len(), keys))
+    }
+
+    /// Build stats from the leading keys of `sampled` records (nulls omitted).
+    pub fn from_keys(index_name: &str, sampled: usize, mut keys: Vec<IndexableValue>) -> Self {
+        keys.sort_by(compare_keys);
+
+        let non_null_fraction = if sampled == 0 {
+            0.0
+        } else {
+            keys.len() as f64 / sampled as f64
+        };
+        let bounds = if keys.is_empty() {
+            Vec::new()
+        } else {
+            let buckets = HISTOGRAM_BUCKETS.min(keys.len());
+            let last = keys.len() - 1;
+            (0..=buckets)
+                .map(|i| keys[i * last / buckets].clone())
+                .collect()
+        };
+
+        Self {
+            index_name: index_name.to_string(),
+            sampled,
+            non_null_fraction,
+            bounds,
+        }
+    }
+
+    /// Estimated fraction of records whose leading key falls between
+    /// `lower` and `upper` (`None` = unbounded). Bound inclusivity is
+    /// ignored. Returns 1.0 when nothing was sampled.
+    pub fn range_selectivity(&self, lower: Option<&RangeBound>, upper: Option<&RangeBound>) -> f64 {
+        if self.sampled == 0 {
+            return 1.0;
+        }
+        if self.bounds.is_empty() {
+            return 0.0;
+        }
+        let start = lower.map_or(0.0, |b| self.position(&b.value));
+        let end = upper.map_or(1.0, |b| self.position(&b.value));
+        (end - start).max(0.0) * self.non_null_fraction
+    }
+
+    /// Estimated fraction of keys below `value`, interpolating linearly
+    /// inside a numeric bucket and taking the midpoint otherwise.
+    fn position(&self, value: &IndexableValue) -> f64 {
+        let bounds = &self.bounds;
+        let last = bounds.len() - 1;
+        if compare_keys(value, &bounds[0]) != Ordering::Greater {
+            return 0.0;
+        }
+        if compare_keys(value, &bounds[last]) != Ordering::Less {
+            return 1.0;
+        }
+
+        // First bound >= value; value lies in bucket (i - 1, i]
+        let i = bounds.partition_point(|b| compare_keys(b, value) == Ordering::Less);
+        let within = match (&bounds[i - 1], &bounds[i], value) {
+            (IndexableValue::Number(lo), IndexableValue::Number(hi), IndexableValue::Number(v))
+                if hi > lo =>
+            {
+                ((v - lo) / (hi - lo)).clamp(0.0, 1.0)
+            }
+            _ => 0.5,
+        };
+        ((i - 1) as f64 + within) / last as f64
+    }
+}
+
+// ============================================================================
+// Sampling
+// ============================================================================
+
+fn sample_records<B: StorageBackend>(
+    backend: &B,
+    collection: &str,
+    sample_size: usize,
+) -> Result<Vec<SerializedRecord>> {
+    if sample_size == 0 {
+        return Ok(Vec::new());
+    }
+    let total = backend.count_raw(collection)?;
+    if total <= sample_size {
+        return Ok(backend
+            .scan_raw(collection, &ScanOptions::default())?
+            .records);
+    }
+
+    let windows = SAMPLE_WINDOWS.min(sample_size);
+    let per_window = sample_size / windows;
+    let stride = total / windows;
+    let mut records = Vec::with_capacity(per_window * windows);
+    for window in 0..windows {
+        let options = ScanOptions {
+            limit: Some(per_window),
+            offset: Some(window * stride),
+            ..Default::default()
+        };
+        records.extend(backend.scan_raw(collection, &options)?.records);
+    }
+    Ok(records)
+}
+
+/// The value a record contributes to the first column of `index`.
+fn leading_key(index: &IndexDefinition, record: &SerializedRecord) -> Option<IndexableValue> {
+    let value = match index {
+        IndexDefinition::Field(fi) => get_field_value(&record.data, &fi.fields.first()?.field)?,
+        IndexDefinition::Computed(ci) => record.computed.as_ref()?.get(&ci.name)?,
+    };
+    value_to_indexable(value)
+}
+
+/// Order keys like `compare_values`: numbers, then strings, then booleans.
+fn compare_keys(a: &IndexableValue, b: &IndexableValue) -> Ordering {
+    fn rank(v: &IndexableValue) -> u8 {
+        match v {
+            IndexableValue::Number(_) => 0,
+            IndexableValue::String(_) => 1,
+            IndexableValue::Bool(_) => 2,
+            IndexableValue::Null => 3,
+        }
+    }
+    match (a, b) {
+        (IndexableValue::Number(x), IndexableValue::Number(y)) => {
+            x.partial_cmp(y).unwrap_or(Ordering::Equal)
+        }
+        (IndexableValue::String(x), IndexableValue::String(y)) => x.cmp(y),
+        (IndexableValue::Bool(x), IndexableValue::Bool(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
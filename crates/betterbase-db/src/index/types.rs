@@ -36,6 +36,20 @@ pub struct FieldIndex {
     pub sparse: bool,
 }
 
+impl FieldIndex {
+    /// The indexed fields of `data` in index order, missing ones as null.
+    /// Used to report which values collided on a unique index.
+    pub fn values_of(&self, data: &Value) -> serde_json::Map<String, Value> {
+        self.fields
+            .iter()
+            .map(|f| {
+                let value = data.get(&f.field).cloned().unwrap_or(Value::Null);
+                (f.field.clone(), value)
+            })
+            .collect()
+    }
+}
+
 // ============================================================================
 // Indexable Values
 // ============================================================================
@@ -102,6 +116,16 @@ pub struct ComputedIndex {
     pub compute: Arc<ComputeIndexFn>,
     pub unique: bool,
     pub sparse: bool,
+    /// Set when the index stores the geohash of a `t::geopoint()` field, so the
+    /// planner can serve `$geoBox` filters from it.
+    pub geo: Option<GeoIndexSpec>,
+}
+
+/// Source field and precision of a geohash computed index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoIndexSpec {
+    pub field: String,
+    pub precision: usize,
 }
 
 impl std::fmt::Debug for ComputedIndex {
@@ -111,6 +135,7 @@ impl std::fmt::Debug for ComputedIndex {
             .field("compute", &"<fn>")
             .field("unique", &self.unique)
             .field("sparse", &self.sparse)
+            .field("geo", &self.geo)
             .finish()
     }
 }
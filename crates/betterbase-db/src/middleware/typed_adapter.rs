@@ -113,6 +113,7 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
             should_reset_sync_state: Some(Arc::new(move |old, new| {
                 mw.should_reset_sync_state(old, new)
             })),
+            context: base.map(|b| b.context.clone()).unwrap_or_default(),
         }
     }
 
@@ -133,6 +134,7 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
             should_reset_sync_state: Some(Arc::new(move |old, new| {
                 mw.should_reset_sync_state(old, new)
             })),
+            context: base.map(|b| b.context.clone()).unwrap_or_default(),
         }
     }
 
@@ -148,6 +150,8 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
             id: id.to_string(),
             session_id: base.and_then(|b| b.session_id),
             meta,
+            kind: base.map(|b| b.kind).unwrap_or_default(),
+            context: base.map(|b| b.context.clone()).unwrap_or_default(),
         }
     }
 
@@ -316,6 +320,18 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
         self.inner.delete(def, id, &opts)
     }
 
+    /// Convert an archived record into a tombstone (space admins only).
+    pub fn tombstone_archived(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        write_opts: Option<&Value>,
+        delete_opts: Option<&DeleteOptions>,
+    ) -> Result<bool> {
+        let opts = self.resolve_delete_options(id, write_opts, delete_opts);
+        self.inner.tombstone_archived(def, id, &opts)
+    }
+
     /// Bulk put, returning enriched records.
     pub fn bulk_put(
         &self,
@@ -413,14 +429,16 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
     /// Observe a single record. The callback receives enriched data (via `on_read`).
     ///
     /// On each notification, a secondary `get()` is performed to retrieve the
-    /// record's stored metadata for enrichment.
+    /// record's stored metadata for enrichment. Fails with
+    /// [`LessDbError::SubscriptionLimit`] when the inner adapter's
+    /// subscription cap is reached.
     pub fn observe(
         &self,
         def: Arc<CollectionDef>,
         id: impl Into<String>,
         callback: Arc<dyn Fn(Option<Value>) + Send + Sync>,
         on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
-    ) -> Unsubscribe {
+    ) -> Result<Unsubscribe> {
         let id_str: String = id.into();
         let inner_clone = Arc::clone(&self.inner);
         let mw = Arc::clone(&self.middleware);
@@ -454,7 +472,8 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
     }
 
     /// Observe query results. The callback receives enriched results (via `on_read`),
-    /// optionally filtered by `on_query`.
+    /// optionally filtered by `on_query`. Fails like [`observe`](Self::observe)
+    /// at the subscription cap.
     pub fn observe_query(
         &self,
         def: Arc<CollectionDef>,
@@ -462,7 +481,7 @@ impl<B: StorageBackend + 'static> TypedAdapter<B> {
         callback: Arc<dyn Fn(MiddlewareQueryResult) + Send + Sync>,
         on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
         query_opts: Option<Value>,
-    ) -> Unsubscribe {
+    ) -> Result<Unsubscribe> {
         let inner_clone = Arc::clone(&self.inner);
         let mw = Arc::clone(&self.middleware);
         let def_clone = Arc::clone(&def);
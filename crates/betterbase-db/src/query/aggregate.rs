@@ -0,0 +1,226 @@
+//! Grouped aggregation: `sum` / `avg` / `min` / `max` / `count` per group.
+//!
+//! [`Aggregator`] folds records one at a time, so the adapter computes every
+//! metric for every group in a single pass over the collection.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::operators::{compare_values, get_field_value};
+use crate::types::OperationContext;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A value computed per group.
+///
+/// Field metrics only consider records where the field holds a JSON number.
+/// There is no coercion: strings such as `"12"`, booleans, arrays and
+/// objects are skipped and tallied in [`AggregateRow::skipped`]; missing and
+/// null fields are skipped without being tallied. Skipped records still
+/// count towards [`Metric::Count`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", content = "field", rename_all = "lowercase")]
+pub enum Metric {
+    /// Number of records in the group.
+    Count,
+    Sum(String),
+    Avg(String),
+    Min(String),
+    Max(String),
+}
+
+impl Metric {
+    /// Field path the metric reads, `None` for [`Metric::Count`].
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Metric::Count => None,
+            Metric::Sum(f) | Metric::Avg(f) | Metric::Min(f) | Metric::Max(f) => Some(f),
+        }
+    }
+}
+
+/// What to group by and which metrics to compute.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateSpec {
+    /// Dot-separated field paths forming the group key. Empty means a single
+    /// group over the whole collection.
+    #[serde(default)]
+    pub group_by: Vec<String>,
+    pub metrics: Vec<Metric>,
+    /// Only aggregate records matching this filter, in the same language as
+    /// `Query::filter`. `None` aggregates the whole collection.
+    #[serde(default)]
+    pub filter: Option<Value>,
+    /// Actor checked against the collection's read predicate; records it
+    /// can't read are left out of every group.
+    #[serde(default)]
+    pub context: OperationContext,
+}
+
+/// One output row of an aggregation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AggregateRow {
+    /// Group key values, in `group_by` order. Missing fields are `null`.
+    pub group: Vec<Value>,
+    /// Metric results, in `metrics` order. `None` when no record in the group
+    /// had a numeric value for the metric's field.
+    pub values: Vec<Option<f64>>,
+    /// Per metric, how many records in the group had a non-null,
+    /// non-numeric value for its field. Always 0 for [`Metric::Count`].
+    pub skipped: Vec<usize>,
+}
+
+// ============================================================================
+// Aggregator
+// ============================================================================
+
+#[derive(Clone)]
+struct MetricState {
+    sum: f64,
+    seen: usize,
+    skipped: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for MetricState {
+    fn default() -> Self {
+        Self {
+            sum: 0.0,
+            seen: 0,
+            skipped: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+struct Group {
+    key: Vec<Value>,
+    count: usize,
+    metrics: Vec<MetricState>,
+}
+
+/// Running state of one aggregation.
+pub struct Aggregator<'a> {
+    spec: &'a AggregateSpec,
+    groups: Vec<Group>,
+    /// Serialized group key → position in `groups`.
+    positions: HashMap<String, usize>,
+}
+
+impl<'a> Aggregator<'a> {
+    pub fn new(spec: &'a AggregateSpec) -> Self {
+        Self {
+            spec,
+            groups: Vec::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Fold one record's data into its group.
+    pub fn add(&mut self, record: &Value) {
+        let key: Vec<Value> = self
+            .spec
+            .group_by
+            .iter()
+            .map(|path| {
+                get_field_value(record, path)
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            })
+            .collect();
+        // Vec<Value> serialization cannot fail
+        let lookup = serde_json::to_string(&key).unwrap_or_default();
+
+        let pos = match self.positions.get(&lookup) {
+            Some(&pos) => pos,
+            None => {
+                self.groups.push(Group {
+                    key,
+                    count: 0,
+                    metrics: vec![MetricState::default(); self.spec.metrics.len()],
+                });
+                self.positions.insert(lookup, self.groups.len() - 1);
+                self.groups.len() - 1
+            }
+        };
+
+        let group = &mut self.groups[pos];
+        group.count += 1;
+        for (metric, state) in self.spec.metrics.iter().zip(&mut group.metrics) {
+            let Some(field) = metric.field() else {
+                continue;
+            };
+            let n = match get_field_value(record, field) {
+                Some(Value::Number(n)) => n.as_f64().unwrap_or_default(),
+                None | Some(Value::Null) => continue,
+                Some(_) => {
+                    state.skipped += 1;
+                    continue;
+                }
+            };
+            state.sum += n;
+            state.seen += 1;
+            state.min = state.min.min(n);
+            state.max = state.max.max(n);
+        }
+    }
+
+    /// Produce one row per group, ordered by group key ascending.
+    ///
+    /// Pass `presorted` when records were added in group-key order (e.g.
+    /// from an index scan); groups then already appear in order and the
+    /// final sort is skipped.
+    pub fn finish(self, presorted: bool) -> Vec<AggregateRow> {
+        let mut groups = self.groups;
+        if !presorted {
+            groups.sort_by(|a, b| compare_keys(&a.key, &b.key));
+        }
+        groups
+            .into_iter()
+            .map(|group| AggregateRow {
+                values: self
+                    .spec
+                    .metrics
+                    .iter()
+                    .zip(&group.metrics)
+                    .map(|(metric, state)| finish_metric(metric, state, group.count))
+                    .collect(),
+                skipped: group.metrics.iter().map(|state| state.skipped).collect(),
+                group: group.key,
+            })
+            .collect()
+    }
+}
+
+fn finish_metric(metric: &Metric, state: &MetricState, count: usize) -> Option<f64> {
+    let seen = state.seen > 0;
+    match metric {
+        Metric::Count => Some(count as f64),
+        Metric::Sum(_) => seen.then_some(state.sum),
+        Metric::Avg(_) => seen.then(|| state.sum / state.seen as f64),
+        Metric::Min(_) => seen.then_some(state.min),
+        Metric::Max(_) => seen.then_some(state.max),
+    }
+}
+
+/// Order rows by group key ascending, as [`Aggregator::finish`] does. For
+/// rows a backend computed itself.
+pub fn sort_rows(rows: &mut [AggregateRow]) {
+    rows.sort_by(|a, b| compare_keys(&a.group, &b.group));
+}
+
+fn compare_keys(a: &[Value], b: &[Value]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| compare_values(x, y))
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
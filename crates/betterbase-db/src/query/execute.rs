@@ -1,16 +1,43 @@
 //! Query execution engine — scan-and-filter with sorting and pagination.
 
+use std::cmp::Ordering;
+
 use serde_json::Value;
 
 use crate::error::Result;
 
 use super::operators::{compare_values, filter_records, get_field_value};
-use super::types::{normalize_sort, ExecuteQueryResult, Query, SortDirection, SortEntry};
+use super::types::{
+    normalize_sort, ExecuteQueryResult, NullsOrder, Query, SortDirection, SortEntry,
+};
 
 // ============================================================================
 // Sorting
 // ============================================================================
 
+/// Compare two records on one sort entry, honoring its direction and
+/// null placement. Missing fields compare as null.
+pub fn compare_for_sort(a: &Value, b: &Value, entry: &SortEntry) -> Ordering {
+    let va = get_field_value(a, &entry.field).unwrap_or(&Value::Null);
+    let vb = get_field_value(b, &entry.field).unwrap_or(&Value::Null);
+    let nulls_first = entry.nulls_order() == NullsOrder::First;
+    match (va.is_null(), vb.is_null()) {
+        (true, true) => Ordering::Equal,
+        (true, false) if nulls_first => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, true) if nulls_first => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => {
+            let cmp = compare_values(va, vb);
+            if entry.direction == SortDirection::Desc {
+                cmp.reverse()
+            } else {
+                cmp
+            }
+        }
+    }
+}
+
 /// Sort records by multiple fields with cascading priority.
 /// Returns a sorted copy; does not mutate the input.
 pub fn sort_records(mut records: Vec<Value>, sort: &[SortEntry]) -> Vec<Value> {
@@ -20,18 +47,12 @@ pub fn sort_records(mut records: Vec<Value>, sort: &[SortEntry]) -> Vec<Value> {
 
     records.sort_by(|a, b| {
         for entry in sort {
-            let va = get_field_value(a, &entry.field).unwrap_or(&Value::Null);
-            let vb = get_field_value(b, &entry.field).unwrap_or(&Value::Null);
-            let cmp = compare_values(va, vb);
-            if cmp != std::cmp::Ordering::Equal {
-                return if entry.direction == SortDirection::Desc {
-                    cmp.reverse()
-                } else {
-                    cmp
-                };
+            let cmp = compare_for_sort(a, b, entry);
+            if cmp != Ordering::Equal {
+                return cmp;
             }
         }
-        std::cmp::Ordering::Equal
+        Ordering::Equal
     });
 
     records
@@ -106,6 +127,7 @@ pub fn find_first(records: Vec<Value>, query: &Query) -> Result<Option<Value>> {
         sort: query.sort.clone(),
         limit: Some(1),
         offset: query.offset,
+        context: query.context.clone(),
     };
     let result = execute_query(records, &limited)?;
     Ok(result.records.into_iter().next())
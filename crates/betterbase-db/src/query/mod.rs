@@ -1,5 +1,6 @@
-//! Query engine: filter evaluation, sorting, pagination, and execution.
+//! Query engine: filter evaluation, sorting, pagination, aggregation, and execution.
 
+pub mod aggregate;
 pub mod execute;
 pub mod operators;
 pub mod types;
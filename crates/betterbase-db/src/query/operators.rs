@@ -1,11 +1,16 @@
 //! Filter operator evaluation for the query engine.
 //! Implements MongoDB-style filter semantics with array lifting.
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
+use regex::{Regex, RegexBuilder};
 use serde_json::{Map, Value};
 
 use crate::error::{LessDbError, QueryError, Result};
+use crate::index::geo::{parse_box_operand, point_from_value, GeoBox, GEO_BOX_OPERATOR};
 
 // ============================================================================
 // Value Comparison
@@ -81,6 +86,109 @@ pub fn get_field_value<'a>(record: &'a Value, path: &str) -> Option<&'a Value> {
     Some(current)
 }
 
+// ============================================================================
+// Regex Compilation
+// ============================================================================
+
+/// `$regex` patterns of one filter, compiled once up front so evaluating the
+/// filter against many records does not recompile them per record.
+#[derive(Debug, Default)]
+struct FilterRegexes {
+    /// Keyed by `(pattern, options)`.
+    compiled: HashMap<(String, String), Regex>,
+}
+
+impl FilterRegexes {
+    /// Compile every `$regex` condition anywhere in `filter`.
+    ///
+    /// Fails with [`QueryError::InvalidRegex`] on a pattern that does not
+    /// compile, a non-string operand, an unsupported `$options` flag, or
+    /// `$options` without `$regex`.
+    fn compile(filter: &Value) -> Result<Self> {
+        let mut regexes = Self::default();
+        regexes.collect(filter)?;
+        Ok(regexes)
+    }
+
+    fn collect(&mut self, value: &Value) -> Result<()> {
+        match value {
+            Value::Object(obj) => {
+                if obj.contains_key("$regex") || obj.contains_key("$options") {
+                    let (pattern, options) = regex_operands(obj)?;
+                    let key = (pattern.to_string(), options.to_string());
+                    if let Entry::Vacant(slot) = self.compiled.entry(key) {
+                        slot.insert(build_regex(pattern, options)?);
+                    }
+                }
+                obj.values().try_for_each(|v| self.collect(v))
+            }
+            Value::Array(items) => items.iter().try_for_each(|v| self.collect(v)),
+            _ => Ok(()),
+        }
+    }
+
+    /// The compiled regex for a `$regex` operator object. Compiles on the
+    /// spot if the object was not part of the filter this set was built from.
+    fn get(&self, ops: &Map<String, Value>) -> Result<Cow<'_, Regex>> {
+        let (pattern, options) = regex_operands(ops)?;
+        match self
+            .compiled
+            .get(&(pattern.to_string(), options.to_string()))
+        {
+            Some(re) => Ok(Cow::Borrowed(re)),
+            None => build_regex(pattern, options).map(Cow::Owned),
+        }
+    }
+}
+
+fn invalid_regex(message: impl Into<String>) -> LessDbError {
+    LessDbError::Query(QueryError::InvalidRegex(message.into()))
+}
+
+/// The `$regex` pattern and `$options` flags (empty if absent) of an
+/// operator object.
+fn regex_operands(ops: &Map<String, Value>) -> Result<(&str, &str)> {
+    let pattern = match ops.get("$regex") {
+        Some(Value::String(p)) => p.as_str(),
+        Some(other) => {
+            return Err(invalid_regex(format!(
+                "$regex operand must be a string, got {other}"
+            )))
+        }
+        None => return Err(invalid_regex("$options requires $regex")),
+    };
+    let options = match ops.get("$options") {
+        None => "",
+        Some(Value::String(o)) => o.as_str(),
+        Some(other) => {
+            return Err(invalid_regex(format!(
+                "$options must be a string, got {other}"
+            )))
+        }
+    };
+    Ok((pattern, options))
+}
+
+/// Compile `pattern` with `$options` flags: `i` (case-insensitive), `m`
+/// (multi-line), `s` (dot matches newline), `x` (ignore whitespace).
+fn build_regex(pattern: &str, options: &str) -> Result<Regex> {
+    let mut builder = RegexBuilder::new(pattern);
+    for flag in options.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'x' => builder.ignore_whitespace(true),
+            other => {
+                return Err(invalid_regex(format!(
+                    "unsupported $options flag '{other}'"
+                )))
+            }
+        };
+    }
+    builder.build().map_err(|e| invalid_regex(e.to_string()))
+}
+
 // ============================================================================
 // Operator Evaluation
 // ============================================================================
@@ -150,19 +258,6 @@ fn evaluate_scalar_operator(value: &Value, op: &str, operand: &Value) -> Result<
             Ok(!items.iter().any(|item| deep_equals(value, item)))
         }
 
-        "$regex" => {
-            if !value.is_string() {
-                return Ok(false);
-            }
-            let pattern = match operand.as_str() {
-                Some(p) => p,
-                None => return Ok(false),
-            };
-            let re = regex::Regex::new(pattern)
-                .map_err(|e| LessDbError::Query(QueryError::InvalidRegex(e.to_string())))?;
-            Ok(re.is_match(value.as_str().unwrap()))
-        }
-
         "$size" => {
             let arr = match value.as_array() {
                 Some(a) => a,
@@ -175,6 +270,11 @@ fn evaluate_scalar_operator(value: &Value, op: &str, operand: &Value) -> Result<
             Ok(arr.len() == expected)
         }
 
+        "$box" => {
+            let bounds = parse_box_operand(operand)?;
+            Ok(point_from_value(value).is_some_and(|(lat, lng)| bounds.contains(lat, lng)))
+        }
+
         other => Err(LessDbError::Query(QueryError::UnknownOperator(
             other.to_string(),
         ))),
@@ -258,8 +358,29 @@ fn evaluate_array_operator(value: &Value, op: &str, operand: &Value) -> Option<R
 }
 
 /// Evaluate an operator object `{ $op: operand, ... }` against a value.
-fn evaluate_operators(value: &Value, ops: &Map<String, Value>) -> Result<bool> {
+fn evaluate_operators(
+    value: &Value,
+    ops: &Map<String, Value>,
+    regexes: &FilterRegexes,
+) -> Result<bool> {
     for (op, operand) in ops {
+        // `$regex` matches strings only; `$options` is read alongside it
+        if op == "$regex" {
+            let matched = match value.as_str() {
+                Some(s) => regexes.get(ops)?.is_match(s),
+                None => false,
+            };
+            if !matched {
+                return Ok(false);
+            }
+            continue;
+        }
+        if op == "$options" {
+            if !ops.contains_key("$regex") {
+                return Err(invalid_regex("$options requires $regex"));
+            }
+            continue;
+        }
         // Try array operator first
         if let Some(result) = evaluate_array_operator(value, op, operand) {
             if !result? {
@@ -280,9 +401,9 @@ fn evaluate_operators(value: &Value, ops: &Map<String, Value>) -> Result<bool> {
 // ============================================================================
 
 /// Evaluate a field condition against a value (either direct equality or operator object).
-fn evaluate_field_filter(value: &Value, filter: &Value) -> Result<bool> {
+fn evaluate_field_filter(value: &Value, filter: &Value, regexes: &FilterRegexes) -> Result<bool> {
     if is_operator(filter) {
-        evaluate_operators(value, filter.as_object().unwrap())
+        evaluate_operators(value, filter.as_object().unwrap(), regexes)
     } else {
         // Direct value: shorthand for $eq (with array lifting via evaluate_single_operator)
         evaluate_single_operator(value, "$eq", filter)
@@ -292,9 +413,17 @@ fn evaluate_field_filter(value: &Value, filter: &Value) -> Result<bool> {
 /// Evaluate a MongoDB-style filter against a record.
 ///
 /// The filter is a JSON Object. Logical operators (`$and`, `$or`, `$not`) are
-/// evaluated first; then field conditions are evaluated (implicit AND).
-/// Keys starting with `$` that are not recognized logical ops are skipped.
+/// evaluated first, then a top-level `$geoBox` bounding-box condition; then
+/// field conditions are evaluated (implicit AND). Keys starting with `$` that
+/// are not recognized logical ops are skipped.
+///
+/// `$regex` patterns are compiled on every call; use [`filter_records`] to
+/// match many records against one filter.
 pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
+    matches_filter_with(record, filter, &FilterRegexes::compile(filter)?)
+}
+
+fn matches_filter_with(record: &Value, filter: &Value, regexes: &FilterRegexes) -> Result<bool> {
     let filter_obj = match filter.as_object() {
         Some(o) => o,
         None => return Ok(true),
@@ -304,7 +433,7 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
     if let Some(and_val) = filter_obj.get("$and") {
         if let Some(sub_filters) = and_val.as_array() {
             for sub in sub_filters {
-                if !matches_filter(record, sub)? {
+                if !matches_filter_with(record, sub, regexes)? {
                     return Ok(false);
                 }
             }
@@ -316,7 +445,7 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
         if let Some(sub_filters) = or_val.as_array() {
             let mut any_match = false;
             for sub in sub_filters {
-                if matches_filter(record, sub)? {
+                if matches_filter_with(record, sub, regexes)? {
                     any_match = true;
                     break;
                 }
@@ -329,7 +458,14 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
 
     // $not
     if let Some(not_val) = filter_obj.get("$not") {
-        if matches_filter(record, not_val)? {
+        if matches_filter_with(record, not_val, regexes)? {
+            return Ok(false);
+        }
+    }
+
+    // $geoBox
+    if let Some(box_val) = filter_obj.get(GEO_BOX_OPERATOR) {
+        if !GeoBox::from_value(box_val)?.matches(record) {
             return Ok(false);
         }
     }
@@ -360,7 +496,7 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
                     .collect();
                 if !remaining.is_empty() {
                     let value = value_opt.unwrap_or(&Value::Null);
-                    if !evaluate_operators(value, &remaining)? {
+                    if !evaluate_operators(value, &remaining, regexes)? {
                         return Ok(false);
                     }
                 }
@@ -369,7 +505,7 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
         }
 
         let value = get_field_value(record, key).unwrap_or(&Value::Null);
-        if !evaluate_field_filter(value, field_filter)? {
+        if !evaluate_field_filter(value, field_filter, regexes)? {
             return Ok(false);
         }
     }
@@ -377,11 +513,35 @@ pub fn matches_filter(record: &Value, filter: &Value) -> Result<bool> {
     Ok(true)
 }
 
+/// A filter prepared for matching many records: its `$regex` patterns are
+/// compiled once, in [`FilterMatcher::new`], so an invalid pattern fails the
+/// query even when no record reaches it.
+#[derive(Debug)]
+pub struct FilterMatcher<'a> {
+    filter: &'a Value,
+    regexes: FilterRegexes,
+}
+
+impl<'a> FilterMatcher<'a> {
+    pub fn new(filter: &'a Value) -> Result<Self> {
+        Ok(Self {
+            filter,
+            regexes: FilterRegexes::compile(filter)?,
+        })
+    }
+
+    /// Same semantics as [`matches_filter`].
+    pub fn matches(&self, record: &Value) -> Result<bool> {
+        matches_filter_with(record, self.filter, &self.regexes)
+    }
+}
+
 /// Filter a slice of records, returning those that match the filter (cloned).
 pub fn filter_records(records: &[Value], filter: &Value) -> Result<Vec<Value>> {
+    let matcher = FilterMatcher::new(filter)?;
     let mut result = Vec::new();
     for record in records {
-        if matches_filter(record, filter)? {
+        if matcher.matches(record)? {
             result.push(record.clone());
         }
     }
@@ -399,6 +559,10 @@ pub fn matches_computed_filter(
     computed: Option<&Map<String, Value>>,
     filter: &Map<String, Value>,
 ) -> Result<bool> {
+    let mut regexes = FilterRegexes::default();
+    for condition in filter.values() {
+        regexes.collect(condition)?;
+    }
     for (index_name, condition) in filter {
         let value_opt: Option<&Value> = computed.and_then(|m| m.get(index_name));
 
@@ -430,7 +594,7 @@ pub fn matches_computed_filter(
                     .collect();
                 if !remaining.is_empty() {
                     let value = value_opt.unwrap_or(&Value::Null);
-                    if !evaluate_operators(value, &remaining)? {
+                    if !evaluate_operators(value, &remaining, &regexes)? {
                         return Ok(false);
                     }
                 }
@@ -438,7 +602,7 @@ pub fn matches_computed_filter(
             }
 
             let value = value_opt.unwrap_or(&Value::Null);
-            if !evaluate_operators(value, ops_obj)? {
+            if !evaluate_operators(value, ops_obj, &regexes)? {
                 return Ok(false);
             }
         } else {
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::types::OperationContext;
+
 // ============================================================================
 // Sort Types
 // ============================================================================
@@ -15,11 +17,39 @@ pub enum SortDirection {
     Desc,
 }
 
+/// Placement of null/missing values in a sort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
 /// A sort specification for a single field.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SortEntry {
     pub field: String,
     pub direction: SortDirection,
+    /// Where null/missing values go. `None` treats null as greater than any
+    /// value: last for `Asc`, first for `Desc`. See [`SortEntry::nulls_order`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nulls: Option<NullsOrder>,
+}
+
+impl SortEntry {
+    /// Null placement when `nulls` is unset: null sorts as the greatest value.
+    pub fn default_nulls(direction: &SortDirection) -> NullsOrder {
+        match direction {
+            SortDirection::Asc => NullsOrder::Last,
+            SortDirection::Desc => NullsOrder::First,
+        }
+    }
+
+    /// Resolved null placement (explicit `nulls`, else the direction default).
+    pub fn nulls_order(&self) -> NullsOrder {
+        self.nulls
+            .unwrap_or_else(|| Self::default_nulls(&self.direction))
+    }
 }
 
 /// Sort input — either a shorthand field name (ascending) or explicit entries.
@@ -38,6 +68,7 @@ pub fn normalize_sort(sort: Option<SortInput>) -> Option<Vec<SortEntry>> {
         Some(SortInput::Field(f)) => Some(vec![SortEntry {
             field: f,
             direction: SortDirection::Asc,
+            nulls: None,
         }]),
         Some(SortInput::Entries(e)) => Some(e),
     }
@@ -58,6 +89,8 @@ pub struct Query {
     pub limit: Option<usize>,
     /// Number of results to skip.
     pub offset: Option<usize>,
+    /// Actor checked against the collection's read predicate.
+    pub context: OperationContext,
 }
 
 // ============================================================================
@@ -159,10 +192,12 @@ mod tests {
             SortEntry {
                 field: "age".to_string(),
                 direction: SortDirection::Desc,
+                nulls: None,
             },
             SortEntry {
                 field: "name".to_string(),
                 direction: SortDirection::Asc,
+                nulls: None,
             },
         ];
         let result = normalize_sort(Some(SortInput::Entries(entries.clone()))).unwrap();
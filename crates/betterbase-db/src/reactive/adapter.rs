@@ -4,18 +4,20 @@
 //!
 //! # Threading model
 //!
-//! `ReactiveAdapter<B>` is `Send + Sync`. Three independent locks are used:
+//! `ReactiveAdapter<B>` is `Send + Sync`. Four independent locks are used:
 //!   - `inner` — the wrapped `Adapter<B>` (`parking_lot::Mutex`).
 //!   - `state` — all reactive subscription state (`Arc<Mutex<..>>`; cloned
 //!     into unsubscribe closures).
-//!   - `emitter` — the global change-event emitter (`EventEmitter` uses its
-//!     own internal `parking_lot::Mutex`).
+//!   - `emitter` / `lifecycle` — the global change-event and lifecycle-event
+//!     emitters (`EventEmitter` uses its own internal `parking_lot::Mutex`).
 //!
 //! The critical rule is **never hold both `inner` and `state` simultaneously**.
-//! `emitter` is safe to call at any time because `EventEmitter` releases its
-//! lock before firing callbacks.
+//! The emitters are safe to call at any time because `EventEmitter` releases
+//! its lock before firing callbacks, but `inner` must be released first so
+//! that listeners can re-enter the adapter.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use parking_lot::Mutex;
@@ -24,21 +26,36 @@ use serde_json::Value;
 use crate::{
     collection::builder::CollectionDef,
     error::{LessDbError, Result},
-    query::types::Query,
+    query::{
+        aggregate::{AggregateRow, AggregateSpec},
+        types::Query,
+    },
     storage::{
-        adapter::Adapter,
+        adapter::{Adapter, StaleMigration, REINDEX_BATCH_SIZE},
+        record_manager::key_field,
+        snapshot::SnapshotHandle,
         traits::{
             QueryPlan, StorageBackend, StorageLifecycle, StorageRead, StorageSync, StorageWrite,
         },
     },
     types::{
         ApplyRemoteOptions, ApplyRemoteResult, BatchResult, BulkDeleteResult, BulkPatchResult,
-        DeleteOptions, GetOptions, ListOptions, PatchManyResult, PatchOptions, PushSnapshot,
-        PutOptions, QueryResult, RemoteRecord, StoredRecordWithMeta,
+        CompactOptions, CompactReport, DeleteOptions, GetOptions, ListOptions, MigrationReport,
+        PatchManyResult, PatchOptions, PurgeTombstonesOptions, PushQueueState, PushSnapshot,
+        PutOptions, QueryResult, RemoteRecord, ResetOptions, StoredRecordWithMeta, TouchOptions,
     },
 };
 
-use super::{event::ChangeEvent, event_emitter::EventEmitter, query_fields::extract_query_fields};
+use super::{
+    diagnostics::{
+        now_ms, ObserveOptions, SubStats, SubscriptionDiagnostics, SubscriptionInfo,
+        SubscriptionKind, SubscriptionReport,
+    },
+    event::{ChangeEvent, IndexRebuildStats, LifecycleEvent, MaintenanceStats},
+    event_emitter::EventEmitter,
+    query_fields::extract_query_fields,
+    transaction::{coalesce_changes, Change, ReactiveTransaction},
+};
 
 // ============================================================================
 // Public result type for reactive queries
@@ -66,6 +83,132 @@ impl ReactiveQueryResult {
     }
 }
 
+/// The result type delivered to `observe_window` callbacks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowResult {
+    /// Records at positions `offset..offset + records.len()` of the sorted
+    /// result. Shorter than the window limit near the end.
+    pub records: Vec<Value>,
+    /// Position of the first record in the full result.
+    pub offset: usize,
+    /// Total count of matching records across the whole result.
+    pub total: usize,
+}
+
+/// A record whose `data` changed between two deliveries of
+/// [`ReactiveAdapter::observe_query_diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdatedRecord {
+    pub old: Value,
+    pub new: Value,
+}
+
+/// The change in a query result since the previous delivery, delivered to
+/// [`ReactiveAdapter::observe_query_diff`] callbacks.
+///
+/// Records are keyed by the collection's key field (usually `id`). The
+/// first delivery lists the whole result as `added`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryDiff {
+    /// Records that entered the result (new, or now matching the filter),
+    /// in result order.
+    pub added: Vec<Value>,
+    /// Ids of records that left the result (deleted, or no longer matching).
+    pub removed: Vec<String>,
+    /// Records in both results whose `data` changed, in result order.
+    pub updated: Vec<UpdatedRecord>,
+    /// Ids of records in both results whose order relative to the others
+    /// changed: everything outside the longest run that kept its old order.
+    pub moved: Vec<String>,
+    /// Total count of matching records (before pagination).
+    pub total: usize,
+}
+
+impl QueryDiff {
+    /// True when no record was added, removed, updated or moved.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.updated.is_empty()
+            && self.moved.is_empty()
+    }
+}
+
+fn record_id<'a>(key: &str, record: &'a Value) -> &'a str {
+    record.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// Diff two query results by the id stored under `key`.
+fn diff_query_results(key: &str, old: &[Value], new: &[Value], total: usize) -> QueryDiff {
+    let old_by_id: HashMap<&str, (usize, &Value)> = old
+        .iter()
+        .enumerate()
+        .map(|(i, record)| (record_id(key, record), (i, record)))
+        .collect();
+
+    let mut diff = QueryDiff {
+        total,
+        ..Default::default()
+    };
+    // (old position, id) of records in both results, in new order
+    let mut retained: Vec<(usize, &str)> = Vec::new();
+    for record in new {
+        let id = record_id(key, record);
+        match old_by_id.get(id) {
+            Some(&(position, old_record)) => {
+                retained.push((position, id));
+                if old_record != record {
+                    diff.updated.push(UpdatedRecord {
+                        old: old_record.clone(),
+                        new: record.clone(),
+                    });
+                }
+            }
+            None => diff.added.push(record.clone()),
+        }
+    }
+
+    let new_ids: HashSet<&str> = new.iter().map(|record| record_id(key, record)).collect();
+    diff.removed = old
+        .iter()
+        .map(|record| record_id(key, record))
+        .filter(|id| !new_ids.contains(id))
+        .map(str::to_string)
+        .collect();
+    diff.moved = moved_ids(&retained);
+    diff
+}
+
+/// Ids in `retained` outside a longest subsequence whose old positions are
+/// increasing, i.e. the fewest records that must move to restore the order.
+fn moved_ids(retained: &[(usize, &str)]) -> Vec<String> {
+    // tails[k]: index into `retained` ending the best increasing run of length k + 1
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; retained.len()];
+    for (i, &(position, _)) in retained.iter().enumerate() {
+        let k = tails.partition_point(|&t| retained[t].0 < position);
+        prev[i] = k.checked_sub(1).map(|k| tails[k]);
+        if k == tails.len() {
+            tails.push(i);
+        } else {
+            tails[k] = i;
+        }
+    }
+
+    let mut in_order = vec![false; retained.len()];
+    let mut cursor = tails.last().copied();
+    while let Some(i) = cursor {
+        in_order[i] = true;
+        cursor = prev[i];
+    }
+    retained
+        .iter()
+        .zip(in_order)
+        .filter(|(_, kept)| !kept)
+        .map(|((_, id), _)| id.to_string())
+        .collect()
+}
+
 // ============================================================================
 // Unsubscribe handle type alias
 // ============================================================================
@@ -83,15 +226,78 @@ struct RecordSub {
     def: Arc<CollectionDef>,
     callback: Arc<dyn Fn(Option<Value>) + Send + Sync>,
     on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    stats: SubStats,
+}
+
+impl RecordSub {
+    fn info(&self) -> SubscriptionInfo {
+        SubscriptionInfo {
+            id: self.id,
+            kind: SubscriptionKind::Record,
+            collection: self.def.name.clone(),
+            record_id: Some(self.record_id.clone()),
+            label: self.stats.label.clone(),
+            created_at_ms: self.stats.created_at_ms,
+            last_fired_at_ms: self.stats.last_fired_at_ms(),
+            fire_count: self.stats.fire_count(),
+        }
+    }
+}
+
+/// What a query sub re-runs on flush.
+enum QueryTarget {
+    Records {
+        query: Query,
+        callback: Arc<dyn Fn(ReactiveQueryResult) + Send + Sync>,
+    },
+    Aggregate {
+        spec: AggregateSpec,
+        callback: Arc<dyn Fn(Vec<AggregateRow>) + Send + Sync>,
+    },
 }
 
 struct QuerySub {
     id: u64,
     collection: String,
-    query: Query,
+    target: QueryTarget,
     def: Arc<CollectionDef>,
-    callback: Arc<dyn Fn(ReactiveQueryResult) + Send + Sync>,
     on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    stats: SubStats,
+}
+
+impl QuerySub {
+    fn info(&self) -> SubscriptionInfo {
+        SubscriptionInfo {
+            id: self.id,
+            kind: match self.target {
+                QueryTarget::Records { .. } => SubscriptionKind::Query,
+                QueryTarget::Aggregate { .. } => SubscriptionKind::Aggregate,
+            },
+            collection: self.collection.clone(),
+            record_id: None,
+            label: self.stats.label.clone(),
+            created_at_ms: self.stats.created_at_ms,
+            last_fired_at_ms: self.stats.last_fired_at_ms(),
+            fire_count: self.stats.fire_count(),
+        }
+    }
+}
+
+/// Hand a flushed result to a query sub's callback via `deliver`, or its
+/// error to `on_error`; subs without an error handler get `empty` instead.
+fn deliver_query_result<T>(
+    sub: &QuerySub,
+    result: Result<T>,
+    deliver: impl FnOnce(T),
+    empty: impl FnOnce(),
+) {
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match result {
+        Ok(value) => deliver(value),
+        Err(e) => match &sub.on_error {
+            Some(on_err) => on_err(e),
+            None => empty(),
+        },
+    }));
 }
 
 // ============================================================================
@@ -118,6 +324,9 @@ struct ReactiveState {
     pending_record_subs: Vec<(String, Arc<RecordSub>)>,
     /// Query subs registered before init — queued for initial flush after init.
     pending_query_subs: Vec<Arc<QuerySub>>,
+
+    /// Subscription cap and staleness check (both off by default).
+    diagnostics: SubscriptionDiagnostics,
 }
 
 impl ReactiveState {
@@ -131,9 +340,60 @@ impl ReactiveState {
             initialized: false,
             pending_record_subs: Vec::new(),
             pending_query_subs: Vec::new(),
+            diagnostics: SubscriptionDiagnostics::default(),
         }
     }
 
+    /// Live subscriptions, including those waiting for `initialize()`.
+    fn subscription_count(&self) -> usize {
+        self.record_subs.values().map(Vec::len).sum::<usize>()
+            + self.query_subs.len()
+            + self.pending_record_subs.len()
+            + self.pending_query_subs.len()
+    }
+
+    fn check_subscription_cap(&self) -> Result<()> {
+        match self.diagnostics.max_subscriptions {
+            Some(cap) if self.subscription_count() >= cap => {
+                Err(LessDbError::SubscriptionLimit { cap })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Charge `changed` to every record sub in `collection` that will not
+    /// fire for it, returning events for subs that just became stale.
+    ///
+    /// Query subs are skipped: every change in their collection refires them.
+    fn stale_subscriptions<S: AsRef<str>>(
+        &self,
+        collection: &str,
+        changed: &[S],
+    ) -> Vec<LifecycleEvent> {
+        let Some(threshold) = self.diagnostics.stale else {
+            return Vec::new();
+        };
+        let now = now_ms();
+        let mut events = Vec::new();
+        for sub in self.record_subs.values().flatten() {
+            if sub.def.name != collection || changed.iter().any(|c| c.as_ref() == sub.record_id) {
+                continue;
+            }
+            if let Some((idle_ms, missed_changes)) =
+                sub.stats.miss(changed.len() as u64, now, threshold)
+            {
+                events.push(LifecycleEvent::SubscriptionStale {
+                    collection: collection.to_string(),
+                    subscription_id: sub.id,
+                    label: sub.stats.label.clone(),
+                    idle_ms,
+                    missed_changes,
+                });
+            }
+        }
+        events
+    }
+
     fn next_id(&mut self) -> u64 {
         let id = self.next_id;
         self.next_id += 1;
@@ -199,6 +459,11 @@ pub struct ReactiveAdapter<B: StorageBackend> {
     /// Global change-event emitter — separate from `state` so that
     /// `on_change` callbacks can safely re-enter the adapter.
     emitter: Arc<EventEmitter<ChangeEvent>>,
+    /// Collection lifecycle emitter — kept apart from `emitter` so data
+    /// listeners are not woken for schema and maintenance events.
+    lifecycle: Arc<EventEmitter<LifecycleEvent>>,
+    /// `tx_id` for the next commit that changes data.
+    next_tx_id: AtomicU64,
 }
 
 impl<B: StorageBackend> ReactiveAdapter<B> {
@@ -210,6 +475,8 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
             inner: Mutex::new(adapter),
             state: Arc::new(Mutex::new(ReactiveState::new())),
             emitter: Arc::new(EventEmitter::new()),
+            lifecycle: Arc::new(EventEmitter::new()),
+            next_tx_id: AtomicU64::new(1),
         }
     }
 
@@ -220,7 +487,47 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
         F: FnOnce(&B) -> T,
     {
         let guard = self.inner.lock();
-        f(&guard.backend)
+        f(guard.backend.inner())
+    }
+
+    /// Run several writes as one atomic commit.
+    ///
+    /// `f` reads and writes through the [`ReactiveTransaction`] it is given;
+    /// calling this adapter directly from inside `f` deadlocks. If `f` returns
+    /// an error, every write is rolled back and no events fire. Otherwise each
+    /// write's [`ChangeEvent`] is emitted with the same `tx_id`, followed by a
+    /// single flush.
+    ///
+    /// The MemoryMapped backend does not nest transactions, so bulk writes
+    /// fail inside `f` there.
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&ReactiveTransaction<'_, B>) -> Result<T>,
+    {
+        self.write(|tx| Self::run_atomic(tx, f))
+    }
+
+    /// [`transaction`](Self::transaction), but on commit the writes are
+    /// announced as one [`ChangeEvent::Bulk`] per touched collection instead
+    /// of one event per write. Suited to applying a batch of unrelated
+    /// operations that listeners should see as a single update.
+    pub fn batch_transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&ReactiveTransaction<'_, B>) -> Result<T>,
+    {
+        self.write_with(|tx| Self::run_atomic(tx, f), true)
+    }
+
+    fn run_atomic<T, F>(tx: &ReactiveTransaction<'_, B>, f: F) -> Result<T>
+    where
+        F: FnOnce(&ReactiveTransaction<'_, B>) -> Result<T>,
+    {
+        let result = tx.adapter().backend.transaction(|_| f(tx));
+        if result.is_err() {
+            // Queries inside `f` may have cached rows that were rolled back
+            tx.adapter().clear_query_cache();
+        }
+        result
     }
 
     // -----------------------------------------------------------------------
@@ -236,21 +543,40 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
     /// If the adapter has already been initialized the callback will fire
     /// immediately on the next [`flush`]. If not yet initialized, it will fire
     /// after [`initialize`] + flush.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
     pub fn observe(
         &self,
         def: Arc<CollectionDef>,
         id: impl Into<String>,
         callback: Arc<dyn Fn(Option<Value>) + Send + Sync>,
         on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
-    ) -> Unsubscribe {
+    ) -> Result<Unsubscribe> {
+        self.observe_with_options(def, id, callback, on_error, &ObserveOptions::default())
+    }
+
+    /// [`observe`](Self::observe) with a diagnostics label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_with_options(
+        &self,
+        def: Arc<CollectionDef>,
+        id: impl Into<String>,
+        callback: Arc<dyn Fn(Option<Value>) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
         let id = id.into();
         let collection = def.name.clone();
         let key = format!("{collection}:{id}");
 
         let sub_id;
-        // Single lock acquisition: allocate ID, build sub, register.
+        // Single lock acquisition: check the cap, allocate ID, build sub, register.
         {
             let mut st = self.state.lock();
+            st.check_subscription_cap()?;
             let new_id = st.next_id();
             sub_id = new_id;
             let sub = Arc::new(RecordSub {
@@ -259,6 +585,7 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
                 def: Arc::clone(&def),
                 callback,
                 on_error,
+                stats: SubStats::new(opts.label.clone(), now_ms()),
             });
 
             if st.initialized {
@@ -276,7 +603,7 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
         let state_arc = Arc::clone(&self.state);
         let key_clone = key.clone();
 
-        Box::new(move || {
+        Ok(Box::new(move || {
             let mut st = state_arc.lock();
 
             // Remove from active subs
@@ -298,37 +625,135 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
             // Remove from pending (if not yet initialized)
             st.pending_record_subs
                 .retain(|(k, s)| !(k == &key_clone && s.id == sub_id));
-        })
+        }))
+    }
+
+    /// Observe a value derived from record `id` by `selector`.
+    ///
+    /// The selector runs on every change to the record (it receives `None`
+    /// while the record is missing or deleted), and `callback` fires only
+    /// when its output differs from the last one delivered. The first flush
+    /// always delivers.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_select<S, F>(
+        &self,
+        def: Arc<CollectionDef>,
+        id: impl Into<String>,
+        selector: F,
+        callback: Arc<dyn Fn(S) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    ) -> Result<Unsubscribe>
+    where
+        S: PartialEq + Clone + Send + 'static,
+        F: Fn(Option<&Value>) -> S + Send + Sync + 'static,
+    {
+        self.observe_select_with_options(
+            def,
+            id,
+            selector,
+            callback,
+            on_error,
+            &ObserveOptions::default(),
+        )
+    }
+
+    /// [`observe_select`](Self::observe_select) with a diagnostics label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_select_with_options<S, F>(
+        &self,
+        def: Arc<CollectionDef>,
+        id: impl Into<String>,
+        selector: F,
+        callback: Arc<dyn Fn(S) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe>
+    where
+        S: PartialEq + Clone + Send + 'static,
+        F: Fn(Option<&Value>) -> S + Send + Sync + 'static,
+    {
+        // Last delivered selection, to skip refires that don't change it.
+        let last: Mutex<Option<S>> = Mutex::new(None);
+        let select_callback = Arc::new(move |record: Option<Value>| {
+            let selected = selector(record.as_ref());
+            {
+                let mut last = last.lock();
+                if last.as_ref() == Some(&selected) {
+                    return;
+                }
+                *last = Some(selected.clone());
+            }
+            callback(selected);
+        });
+        self.observe_with_options(def, id, select_callback, on_error, opts)
     }
 
     /// Register a callback to be called whenever query results for `def` change.
     ///
     /// Returns an [`Unsubscribe`] closure.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
     pub fn observe_query(
         &self,
         def: Arc<CollectionDef>,
         query: Query,
         callback: Arc<dyn Fn(ReactiveQueryResult) + Send + Sync>,
         on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
-    ) -> Unsubscribe {
-        let collection = def.name.clone();
+    ) -> Result<Unsubscribe> {
+        self.observe_query_with_options(def, query, callback, on_error, &ObserveOptions::default())
+    }
+
+    /// [`observe_query`](Self::observe_query) with a diagnostics label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_query_with_options(
+        &self,
+        def: Arc<CollectionDef>,
+        query: Query,
+        callback: Arc<dyn Fn(ReactiveQueryResult) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
         // Extract field info for future precise invalidation (currently unused;
         // conservative invalidation marks all collection query subs dirty).
         let _field_info = extract_query_fields(&query);
+        self.register_query_sub(
+            def,
+            QueryTarget::Records { query, callback },
+            on_error,
+            opts,
+        )
+    }
 
+    /// Register a query sub and return its unsubscribe closure.
+    fn register_query_sub(
+        &self,
+        def: Arc<CollectionDef>,
+        target: QueryTarget,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
+        let collection = def.name.clone();
         let sub_id;
-        // Single lock acquisition: allocate ID, build sub, register.
+        // Single lock acquisition: check the cap, allocate ID, build sub, register.
         {
             let mut st = self.state.lock();
+            st.check_subscription_cap()?;
             let new_id = st.next_id();
             sub_id = new_id;
             let sub = Arc::new(QuerySub {
                 id: new_id,
                 collection: collection.clone(),
-                query,
+                target,
                 def: Arc::clone(&def),
-                callback,
                 on_error,
+                stats: SubStats::new(opts.label.clone(), now_ms()),
             });
 
             if st.initialized {
@@ -343,13 +768,153 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
 
         let state_arc = Arc::clone(&self.state);
 
-        Box::new(move || {
+        Ok(Box::new(move || {
             let mut st = state_arc.lock();
             st.query_subs.retain(|s| s.id != sub_id);
             st.dirty_queries.retain(|s| s.id != sub_id);
             st.pending_query_subs.retain(|s| s.id != sub_id);
             let _ = collection; // keep alive
-        })
+        }))
+    }
+
+    /// Observe a fixed window (`offset..offset + limit`) of a query's sorted
+    /// result, for virtual scrolling.
+    ///
+    /// Only the window and the total are delivered, and the callback fires
+    /// only when one of them changes: a write that moves records into or
+    /// out of the window refires it, a write past the window fires with the
+    /// same records and a new total, and a write that changes neither is
+    /// swallowed. Any `offset`/`limit` on `query` is replaced. Give the query
+    /// a sort so positions are stable.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_window(
+        &self,
+        def: Arc<CollectionDef>,
+        query: Query,
+        offset: usize,
+        limit: usize,
+        callback: Arc<dyn Fn(WindowResult) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    ) -> Result<Unsubscribe> {
+        self.observe_window_with_options(
+            def,
+            query,
+            offset,
+            limit,
+            callback,
+            on_error,
+            &ObserveOptions::default(),
+        )
+    }
+
+    /// [`observe_window`](Self::observe_window) with a diagnostics label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    #[allow(clippy::too_many_arguments)]
+    pub fn observe_window_with_options(
+        &self,
+        def: Arc<CollectionDef>,
+        query: Query,
+        offset: usize,
+        limit: usize,
+        callback: Arc<dyn Fn(WindowResult) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
+        let query = Query {
+            offset: Some(offset),
+            limit: Some(limit),
+            ..query
+        };
+        // Last delivered (records, total), to skip refires that change neither.
+        let last: Mutex<Option<(Vec<Value>, usize)>> = Mutex::new(None);
+        let window_callback = Arc::new(move |result: ReactiveQueryResult| {
+            {
+                let mut last = last.lock();
+                if let Some((records, total)) = last.as_ref() {
+                    if *total == result.total && *records == result.records {
+                        return;
+                    }
+                }
+                *last = Some((result.records.clone(), result.total));
+            }
+            callback(WindowResult {
+                records: result.records,
+                offset,
+                total: result.total,
+            });
+        });
+        self.observe_query_with_options(def, query, window_callback, on_error, opts)
+    }
+
+    /// Observe a query, receiving only what changed since the last delivery.
+    ///
+    /// Each subscription keeps its previous result and hands the callback a
+    /// [`QueryDiff`] keyed by record id, so a list UI can patch one row
+    /// instead of re-rendering thousands. The first delivery has the whole
+    /// result in `added`; after that, re-runs that change nothing (not even
+    /// the total) are swallowed.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_query_diff(
+        &self,
+        def: Arc<CollectionDef>,
+        query: Query,
+        callback: Arc<dyn Fn(QueryDiff) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    ) -> Result<Unsubscribe> {
+        self.observe_query_diff_with_options(
+            def,
+            query,
+            callback,
+            on_error,
+            &ObserveOptions::default(),
+        )
+    }
+
+    /// [`observe_query_diff`](Self::observe_query_diff) with a diagnostics
+    /// label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_query_diff_with_options(
+        &self,
+        def: Arc<CollectionDef>,
+        query: Query,
+        callback: Arc<dyn Fn(QueryDiff) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
+        let key = key_field(&def.current_schema).to_string();
+        // Last delivered (records, total); None until the first delivery.
+        let last: Mutex<Option<(Vec<Value>, usize)>> = Mutex::new(None);
+        let diff_callback = Arc::new(move |result: ReactiveQueryResult| {
+            let diff = {
+                let mut last = last.lock();
+                let diff = match last.as_ref() {
+                    Some((records, total)) => {
+                        let diff = diff_query_results(&key, records, &result.records, result.total);
+                        if diff.is_empty() && *total == result.total {
+                            return;
+                        }
+                        diff
+                    }
+                    None => QueryDiff {
+                        added: result.records.clone(),
+                        total: result.total,
+                        ..Default::default()
+                    },
+                };
+                *last = Some((result.records, result.total));
+                diff
+            };
+            callback(diff);
+        });
+        self.observe_query_with_options(def, query, diff_callback, on_error, opts)
     }
 
     /// Register a callback to be called on every [`ChangeEvent`].
@@ -367,6 +932,405 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
         })
     }
 
+    /// Register a callback to be called on every [`LifecycleEvent`].
+    ///
+    /// Delivery follows the same rules as [`on_change`](Self::on_change):
+    /// callbacks run synchronously with no adapter lock held, may re-enter
+    /// the adapter, and a panicking callback does not affect other listeners.
+    ///
+    /// Returns an [`Unsubscribe`] closure.
+    pub fn on_lifecycle(
+        &self,
+        callback: impl Fn(&LifecycleEvent) + Send + Sync + 'static,
+    ) -> Unsubscribe {
+        let listener_id = self.lifecycle.on(callback);
+        let emitter = Arc::clone(&self.lifecycle);
+
+        Box::new(move || {
+            emitter.off(listener_id);
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Subscription diagnostics
+    // -----------------------------------------------------------------------
+
+    /// Replace the subscription cap and staleness check.
+    ///
+    /// Lowering the cap below the current count does not drop anything; it
+    /// only rejects further subscriptions.
+    pub fn set_subscription_diagnostics(&self, diagnostics: SubscriptionDiagnostics) {
+        self.state.lock().diagnostics = diagnostics;
+    }
+
+    /// The current subscription cap and staleness check.
+    pub fn subscription_diagnostics(&self) -> SubscriptionDiagnostics {
+        self.state.lock().diagnostics.clone()
+    }
+
+    /// Snapshot every live subscription with its label, age, and fire
+    /// statistics, plus counts per label.
+    pub fn subscription_report(&self) -> SubscriptionReport {
+        let st = self.state.lock();
+        let subscriptions = st
+            .record_subs
+            .values()
+            .flatten()
+            .map(|s| s.info())
+            .chain(st.pending_record_subs.iter().map(|(_, s)| s.info()))
+            .chain(st.query_subs.iter().map(|s| s.info()))
+            .chain(st.pending_query_subs.iter().map(|s| s.info()))
+            .collect();
+        SubscriptionReport::new(subscriptions)
+    }
+
+    // -----------------------------------------------------------------------
+    // Singletons
+    // -----------------------------------------------------------------------
+
+    /// Fetch or insert the record `id` (see [`Adapter::get_or_create`]).
+    /// Emits a put event only when the record was created.
+    pub fn get_or_create(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        default_data: Value,
+        opts: &PutOptions,
+    ) -> Result<(StoredRecordWithMeta, bool)> {
+        self.write(|tx| {
+            let (record, created) = tx.adapter().get_or_create(def, id, default_data, opts)?;
+            if created {
+                tx.record(Change::Put {
+                    collection: def.name.clone(),
+                    id: record.id.clone(),
+                });
+            }
+            Ok((record, created))
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Aggregation
+    // -----------------------------------------------------------------------
+
+    /// Grouped metrics over `def` (see [`Adapter::aggregate`]).
+    pub fn aggregate(
+        &self,
+        def: &CollectionDef,
+        spec: &AggregateSpec,
+    ) -> Result<Vec<AggregateRow>> {
+        self.inner.lock().aggregate(def, spec)
+    }
+
+    /// Keep an aggregate live, e.g. a dashboard's count of open tickets.
+    ///
+    /// `filter` narrows the aggregated records on top of `spec.filter` (both
+    /// must match). Like a query subscription, the aggregate is recomputed
+    /// after every change to the collection, using the backend pushdown when
+    /// it applies, and the rows are compared with the last delivery:
+    /// `callback` fires only when a group appears, disappears, or any of its
+    /// metrics change. The first flush always delivers.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_aggregate(
+        &self,
+        def: Arc<CollectionDef>,
+        spec: AggregateSpec,
+        filter: Option<Value>,
+        callback: Arc<dyn Fn(Vec<AggregateRow>) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+    ) -> Result<Unsubscribe> {
+        self.observe_aggregate_with_options(
+            def,
+            spec,
+            filter,
+            callback,
+            on_error,
+            &ObserveOptions::default(),
+        )
+    }
+
+    /// [`observe_aggregate`](Self::observe_aggregate) with a diagnostics
+    /// label.
+    ///
+    /// Fails with [`LessDbError::SubscriptionLimit`] when
+    /// [`SubscriptionDiagnostics::max_subscriptions`] is reached.
+    pub fn observe_aggregate_with_options(
+        &self,
+        def: Arc<CollectionDef>,
+        spec: AggregateSpec,
+        filter: Option<Value>,
+        callback: Arc<dyn Fn(Vec<AggregateRow>) + Send + Sync>,
+        on_error: Option<Arc<dyn Fn(LessDbError) + Send + Sync>>,
+        opts: &ObserveOptions,
+    ) -> Result<Unsubscribe> {
+        let filter = match (spec.filter, filter) {
+            (Some(a), Some(b)) => Some(serde_json::json!({ "$and": [a, b] })),
+            (a, b) => a.or(b),
+        };
+        let spec = AggregateSpec { filter, ..spec };
+        // Last delivered rows, to skip recomputes that change no group.
+        let last: Mutex<Option<Vec<AggregateRow>>> = Mutex::new(None);
+        let diff_callback = Arc::new(move |rows: Vec<AggregateRow>| {
+            {
+                let mut last = last.lock();
+                if last.as_ref() == Some(&rows) {
+                    return;
+                }
+                *last = Some(rows.clone());
+            }
+            callback(rows);
+        });
+        self.register_query_sub(
+            def,
+            QueryTarget::Aggregate {
+                spec,
+                callback: diff_callback,
+            },
+            on_error,
+            opts,
+        )
+    }
+
+    // -----------------------------------------------------------------------
+    // Snapshots
+    // -----------------------------------------------------------------------
+
+    /// Take a read-only view of the store (see [`Adapter::snapshot`]).
+    ///
+    /// Reads through the handle bypass subscriptions entirely: they neither
+    /// register observers nor see writes made after this call.
+    pub fn snapshot(&self) -> Result<SnapshotHandle<B>> {
+        self.inner.lock().snapshot()
+    }
+
+    // -----------------------------------------------------------------------
+    // Maintenance
+    // -----------------------------------------------------------------------
+
+    /// Eagerly migrate all stale records in `def` to the current schema
+    /// version. Returns the number of records migrated, or the first
+    /// record's migration error once the rest are written (see
+    /// [`Adapter::migrate_collection`]).
+    ///
+    /// Emits [`LifecycleEvent::MigrationStarted`] / `MigrationFinished` when
+    /// there is anything to migrate; `MigrationFinished` fires after observers
+    /// have been flushed with the migrated data.
+    pub fn migrate_collection(&self, def: &CollectionDef) -> Result<usize> {
+        Ok(self.migrate_stale(def)?.into_ids()?.len())
+    }
+
+    /// Like [`migrate_collection`](Self::migrate_collection), but records that
+    /// fail to migrate are reported in the result instead of aborting.
+    ///
+    /// Emits the same lifecycle events; `migrated` counts only the records
+    /// that were rewritten.
+    pub fn migrate_all(&self, def: &CollectionDef) -> Result<MigrationReport> {
+        Ok(self.migrate_stale(def)?.into_report(&def.name))
+    }
+
+    /// Run [`Adapter::migrate_stale`] between the migration lifecycle events,
+    /// flushing observers with whatever was rewritten before `Finished`.
+    fn migrate_stale(&self, def: &CollectionDef) -> Result<StaleMigration> {
+        let from_version = match self.inner.lock().oldest_stale_version(def)? {
+            Some(v) => v,
+            None => return Ok(StaleMigration::default()),
+        };
+
+        self.emit_lifecycle(LifecycleEvent::MigrationStarted {
+            collection: def.name.clone(),
+            from_version,
+            to_version: def.current_version,
+        });
+
+        let outcome = self.inner.lock().migrate_stale(def)?;
+        if !outcome.migrated.is_empty() {
+            self.mark_dirty_collection(&def.name, &outcome.migrated);
+            self.flush();
+        }
+
+        self.emit_lifecycle(LifecycleEvent::MigrationFinished {
+            collection: def.name.clone(),
+            from_version,
+            to_version: def.current_version,
+            migrated: outcome.migrated.len(),
+        });
+        Ok(outcome)
+    }
+
+    /// Rebuild every index of `def` (see [`Adapter::reindex`]) and re-run its
+    /// query subscriptions, since results may change once old records gain
+    /// computed values.
+    ///
+    /// Records are recomputed in batches of [`REINDEX_BATCH_SIZE`], each
+    /// committed together with its progress record in one transaction, with
+    /// the adapter unlocked in between. Emits
+    /// [`LifecycleEvent::IndexRebuildStarted`] for each index, then
+    /// [`LifecycleEvent::IndexRebuildProgress`] for each index after every
+    /// batch, and [`LifecycleEvent::IndexRebuildFinished`] for each index
+    /// once every batch is written and observers are flushed. Until then,
+    /// computed indexes may miss records not yet recomputed.
+    pub fn reindex(&self, def: &CollectionDef) -> Result<usize> {
+        for index in &def.indexes {
+            self.emit_lifecycle(LifecycleEvent::IndexRebuildStarted {
+                collection: def.name.clone(),
+                index: index.name().to_string(),
+            });
+        }
+
+        let started = now_ms();
+        let ids = self.inner.lock().begin_reindex(def)?;
+        let total = ids.len();
+        let mut count = 0;
+        let mut processed = 0;
+        for batch in ids.chunks(REINDEX_BATCH_SIZE) {
+            processed += batch.len();
+            count += self
+                .inner
+                .lock()
+                .reindex_records(def, batch, processed, total)?;
+            for index in &def.indexes {
+                self.emit_lifecycle(LifecycleEvent::IndexRebuildProgress {
+                    collection: def.name.clone(),
+                    index: index.name().to_string(),
+                    processed,
+                    total,
+                });
+            }
+        }
+        let duration_ms = now_ms().saturating_sub(started).max(0) as u64;
+        self.mark_dirty_collection(&def.name, &[]);
+        self.flush();
+
+        for index in &def.indexes {
+            self.emit_lifecycle(LifecycleEvent::IndexRebuildFinished {
+                collection: def.name.clone(),
+                index: index.name().to_string(),
+                stats: IndexRebuildStats {
+                    records_scanned: count,
+                    entries_written: count,
+                    duration_ms,
+                },
+            });
+        }
+        Ok(count)
+    }
+
+    /// Progress of an unfinished [`reindex`](Self::reindex) of `collection`
+    /// (see [`Adapter::reindex_progress`]).
+    pub fn reindex_progress(&self, collection: &str) -> Result<Option<(usize, usize)>> {
+        self.inner.lock().reindex_progress(collection)
+    }
+
+    /// Remove tombstones from `def` and emit a
+    /// [`LifecycleEvent::MaintenanceRun`] with the result.
+    ///
+    /// Tombstones are invisible to reads, so no change event is emitted.
+    pub fn purge_tombstones(
+        &self,
+        def: &CollectionDef,
+        opts: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        let purged = self.inner.lock().purge_tombstones(def, opts)?;
+
+        self.emit_lifecycle(LifecycleEvent::MaintenanceRun {
+            collection: def.name.clone(),
+            task: "purge_tombstones".to_string(),
+            stats: MaintenanceStats {
+                records_affected: purged,
+                dry_run: opts.dry_run,
+            },
+        });
+        Ok(purged)
+    }
+
+    /// Wipe every record of `def` (see [`Adapter::reset_collection`]) and
+    /// announce the removed ids as one [`ChangeEvent::Bulk`].
+    pub fn reset_collection(&self, def: &CollectionDef, opts: &ResetOptions) -> Result<usize> {
+        self.write(|tx| {
+            let ids = tx.adapter().reset_collection_ids(def, opts)?;
+            let count = ids.len();
+            if !ids.is_empty() {
+                tx.record(Change::Bulk {
+                    collection: def.name.clone(),
+                    ids,
+                });
+            }
+            Ok(count)
+        })
+    }
+
+    /// Move the records of `from` to `to` (see [`Adapter::rename_collection`]),
+    /// announcing the ids as one [`ChangeEvent::Bulk`] per collection, then
+    /// emit [`LifecycleEvent::CollectionRenamed`]. Returns the number of
+    /// records moved.
+    pub fn rename_collection(&self, from: &str, to: &CollectionDef) -> Result<usize> {
+        let count = self.write(|tx| {
+            let ids = tx.adapter().rename_collection(from, to)?;
+            let count = ids.len();
+            if !ids.is_empty() {
+                tx.record(Change::Bulk {
+                    collection: from.to_string(),
+                    ids: ids.clone(),
+                });
+                tx.record(Change::Bulk {
+                    collection: to.name.clone(),
+                    ids,
+                });
+            }
+            Ok(count)
+        })?;
+
+        self.emit_lifecycle(LifecycleEvent::CollectionRenamed {
+            from: from.to_string(),
+            to: to.name.clone(),
+        });
+        Ok(count)
+    }
+
+    /// Remove every record of `def` (see [`Adapter::drop_collection`]),
+    /// announcing the ids as one [`ChangeEvent::Bulk`], then emit
+    /// [`LifecycleEvent::CollectionDropped`]. Returns the number of records
+    /// removed.
+    pub fn drop_collection(&self, def: &CollectionDef) -> Result<usize> {
+        let count = self.write(|tx| {
+            let ids = tx.adapter().drop_collection(def)?;
+            let count = ids.len();
+            if !ids.is_empty() {
+                tx.record(Change::Bulk {
+                    collection: def.name.clone(),
+                    ids,
+                });
+            }
+            Ok(count)
+        })?;
+
+        self.emit_lifecycle(LifecycleEvent::CollectionDropped {
+            collection: def.name.clone(),
+        });
+        Ok(count)
+    }
+
+    /// Compact `def` (see [`Adapter::compact`]) and emit a
+    /// [`LifecycleEvent::MaintenanceRun`] with the records touched.
+    ///
+    /// Compaction only drops tombstones and history, neither of which reads
+    /// expose, so no change event is emitted.
+    pub fn compact(&self, def: &CollectionDef, opts: &CompactOptions) -> Result<CompactReport> {
+        let report = self.inner.lock().compact(def, opts)?;
+
+        self.emit_lifecycle(LifecycleEvent::MaintenanceRun {
+            collection: def.name.clone(),
+            task: "compact".to_string(),
+            stats: MaintenanceStats {
+                records_affected: report.tombstones_removed + report.histories_trimmed,
+                dry_run: false,
+            },
+        });
+        Ok(report)
+    }
+
     // -----------------------------------------------------------------------
     // Flush
     // -----------------------------------------------------------------------
@@ -374,7 +1338,8 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
     /// Run all dirty subscriptions synchronously.
     ///
     /// For each dirty record sub: call `inner.get()` then the callback.
-    /// For each dirty query sub: call `inner.query()` then the callback.
+    /// For each dirty query sub: call `inner.query()` (or `inner.aggregate()`)
+    /// then the callback.
     /// Callbacks are invoked outside any lock to prevent deadlocks.
     ///
     /// **Snapshot semantics:** Dirty subs are snapshotted and drained under
@@ -394,6 +1359,8 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
             (records, queries)
         };
 
+        let now = now_ms();
+
         // Flush record subs — no locks held during callbacks.
         for (_key, sub) in dirty_record_subs {
             let result = {
@@ -420,38 +1387,35 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
                     }
                 }
             }
+            sub.stats.fired(now);
         }
 
         // Flush query subs — no locks held during callbacks.
         for sub in dirty_query_subs {
-            let result = {
-                let inner = self.inner.lock();
-                inner.query(sub.def.as_ref(), &sub.query)
-            };
-
-            match result {
-                Ok(query_result) => {
-                    let reactive_result = ReactiveQueryResult {
+            match &sub.target {
+                QueryTarget::Records { query, callback } => {
+                    let result = {
+                        let inner = self.inner.lock();
+                        inner.query(sub.def.as_ref(), query)
+                    };
+                    let result = result.map(|query_result| ReactiveQueryResult {
                         records: query_result.records.into_iter().map(|r| r.data).collect(),
                         total: query_result.total.unwrap_or(0),
                         errors: Vec::new(),
-                    };
-                    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        (sub.callback)(reactive_result);
-                    }));
+                    });
+                    deliver_query_result(&sub, result, callback.as_ref(), || {
+                        callback(ReactiveQueryResult::empty())
+                    });
                 }
-                Err(e) => {
-                    if let Some(on_err) = &sub.on_error {
-                        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            on_err(e);
-                        }));
-                    } else {
-                        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            (sub.callback)(ReactiveQueryResult::empty());
-                        }));
-                    }
+                QueryTarget::Aggregate { spec, callback } => {
+                    let result = {
+                        let inner = self.inner.lock();
+                        inner.aggregate(sub.def.as_ref(), spec)
+                    };
+                    deliver_query_result(&sub, result, callback.as_ref(), || callback(Vec::new()));
                 }
             }
+            sub.stats.fired(now);
         }
     }
 
@@ -469,20 +1433,101 @@ impl<B: StorageBackend> ReactiveAdapter<B> {
     /// Panics from listeners are caught so that a misbehaving `on_change`
     /// callback can never prevent `mark_dirty` + `flush` from running after
     /// a committed write.
-    fn emit_event(&self, event: ChangeEvent) {
+    fn emit_event(&self, event: &ChangeEvent) {
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.emitter.emit(event);
+        }));
+    }
+
+    /// Run `op` against a [`ReactiveTransaction`] on the inner adapter, then
+    /// publish the changes it recorded under a single `tx_id`.
+    fn write<T, F>(&self, op: F) -> Result<T>
+    where
+        F: FnOnce(&ReactiveTransaction<'_, B>) -> Result<T>,
+    {
+        self.write_with(op, false)
+    }
+
+    /// [`write`](Self::write), optionally merging the committed changes into
+    /// one bulk change per collection before they are published.
+    fn write_with<T, F>(&self, op: F, coalesce: bool) -> Result<T>
+    where
+        F: FnOnce(&ReactiveTransaction<'_, B>) -> Result<T>,
+    {
+        let (value, changes, tx_id) = {
+            let inner = self.inner.lock();
+            let tx = ReactiveTransaction::new(&inner);
+            let value = op(&tx)?;
+            let changes = if coalesce {
+                coalesce_changes(tx.into_changes())
+            } else {
+                tx.into_changes()
+            };
+            // Allocated under the lock so ids increase in commit order
+            let tx_id = if changes.is_empty() {
+                0
+            } else {
+                self.next_tx_id.fetch_add(1, Ordering::Relaxed)
+            };
+            (value, changes, tx_id)
+        };
+        if !changes.is_empty() {
+            self.publish(changes, tx_id);
+        }
+        Ok(value)
+    }
+
+    /// Emit each change as an event, mark affected subscriptions dirty, and
+    /// flush once.
+    fn publish(&self, changes: Vec<Change>, tx_id: u64) {
+        for change in changes {
+            let event = change.into_event(tx_id);
+            self.emit_event(&event);
+            match &event {
+                ChangeEvent::Put { collection, id, .. }
+                | ChangeEvent::Delete { collection, id, .. } => {
+                    self.mark_dirty_record(collection, id);
+                }
+                ChangeEvent::Bulk {
+                    collection, ids, ..
+                }
+                | ChangeEvent::Remote {
+                    collection, ids, ..
+                } => {
+                    self.mark_dirty_collection(collection, ids);
+                }
+            }
+        }
+        self.flush();
+    }
+
+    /// Emit a lifecycle event to all `on_lifecycle` listeners.
+    fn emit_lifecycle(&self, event: LifecycleEvent) {
         let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            self.emitter.emit(&event);
+            self.lifecycle.emit(&event);
         }));
     }
 
     fn mark_dirty_record(&self, collection: &str, id: &str) {
-        let mut st = self.state.lock();
-        st.mark_dirty_record(collection, id);
+        let stale = {
+            let mut st = self.state.lock();
+            st.mark_dirty_record(collection, id);
+            st.stale_subscriptions(collection, &[id])
+        };
+        for event in stale {
+            self.emit_lifecycle(event);
+        }
     }
 
     fn mark_dirty_collection(&self, collection: &str, ids: &[String]) {
-        let mut st = self.state.lock();
-        st.mark_dirty_for_collection(collection, ids);
+        let stale = {
+            let mut st = self.state.lock();
+            st.mark_dirty_for_collection(collection, ids);
+            st.stale_subscriptions(collection, ids)
+        };
+        for event in stale {
+            self.emit_lifecycle(event);
+        }
     }
 }
 
@@ -528,6 +1573,14 @@ impl<B: StorageBackend> StorageLifecycle for ReactiveAdapter<B> {
         }
 
         self.flush();
+
+        for def in collections {
+            self.emit_lifecycle(LifecycleEvent::CollectionRegistered {
+                collection: def.name.clone(),
+                version: def.current_version,
+                indexes: def.indexes.iter().map(|i| i.name().to_string()).collect(),
+            });
+        }
         Ok(())
     }
 
@@ -582,16 +1635,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         data: Value,
         opts: &PutOptions,
     ) -> Result<StoredRecordWithMeta> {
-        let record = self.inner.lock().put(def, data, opts)?;
-        let id = record.id.clone();
-        let collection = def.name.clone();
-        self.emit_event(ChangeEvent::Put {
-            collection: collection.clone(),
-            id: id.clone(),
-        });
-        self.mark_dirty_record(&collection, &id);
-        self.flush();
-        Ok(record)
+        self.write(|tx| tx.put(def, data, opts))
     }
 
     fn patch(
@@ -600,31 +1644,29 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         data: Value,
         opts: &PatchOptions,
     ) -> Result<StoredRecordWithMeta> {
-        let record = self.inner.lock().patch(def, data, opts)?;
-        let id = record.id.clone();
-        let collection = def.name.clone();
-        self.emit_event(ChangeEvent::Put {
-            collection: collection.clone(),
-            id: id.clone(),
-        });
-        self.mark_dirty_record(&collection, &id);
-        self.flush();
-        Ok(record)
+        self.write(|tx| tx.patch(def, data, opts))
     }
 
     fn delete(&self, def: &CollectionDef, id: &str, opts: &DeleteOptions) -> Result<bool> {
-        let deleted = self.inner.lock().delete(def, id, opts)?;
-        if deleted {
-            let collection = def.name.clone();
-            let id_str = id.to_string();
-            self.emit_event(ChangeEvent::Delete {
-                collection: collection.clone(),
-                id: id_str.clone(),
-            });
-            self.mark_dirty_record(&collection, &id_str);
-            self.flush();
-        }
-        Ok(deleted)
+        self.write(|tx| tx.delete(def, id, opts))
+    }
+
+    fn tombstone_archived(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &DeleteOptions,
+    ) -> Result<bool> {
+        self.write(|tx| tx.tombstone_archived(def, id, opts))
+    }
+
+    fn touch(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &TouchOptions,
+    ) -> Result<StoredRecordWithMeta> {
+        self.write(|tx| tx.touch(def, id, opts))
     }
 
     fn bulk_put(
@@ -633,18 +1675,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         records: Vec<Value>,
         opts: &PutOptions,
     ) -> Result<BatchResult> {
-        let result = self.inner.lock().bulk_put(def, records, opts)?;
-        let ids: Vec<String> = result.records.iter().map(|r| r.id.clone()).collect();
-        if !ids.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Bulk {
-                collection: collection.clone(),
-                ids: ids.clone(),
-            });
-            self.mark_dirty_collection(&collection, &ids);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| tx.bulk_put(def, records, opts))
     }
 
     fn bulk_delete(
@@ -653,18 +1684,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         ids: &[&str],
         opts: &DeleteOptions,
     ) -> Result<BulkDeleteResult> {
-        let result = self.inner.lock().bulk_delete(def, ids, opts)?;
-        let deleted = result.deleted_ids.clone();
-        if !deleted.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Bulk {
-                collection: collection.clone(),
-                ids: deleted.clone(),
-            });
-            self.mark_dirty_collection(&collection, &deleted);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| tx.bulk_delete(def, ids, opts))
     }
 
     fn bulk_patch(
@@ -673,18 +1693,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         patches: Vec<Value>,
         opts: &PatchOptions,
     ) -> Result<BulkPatchResult> {
-        let result = self.inner.lock().bulk_patch(def, patches, opts)?;
-        let ids: Vec<String> = result.records.iter().map(|r| r.id.clone()).collect();
-        if !ids.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Bulk {
-                collection: collection.clone(),
-                ids: ids.clone(),
-            });
-            self.mark_dirty_collection(&collection, &ids);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| tx.bulk_patch(def, patches, opts))
     }
 
     fn delete_many(
@@ -693,18 +1702,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         filter: &Value,
         opts: &DeleteOptions,
     ) -> Result<BulkDeleteResult> {
-        let result = self.inner.lock().delete_many(def, filter, opts)?;
-        let deleted = result.deleted_ids.clone();
-        if !deleted.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Bulk {
-                collection: collection.clone(),
-                ids: deleted.clone(),
-            });
-            self.mark_dirty_collection(&collection, &deleted);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| tx.delete_many(def, filter, opts))
     }
 
     fn patch_many(
@@ -714,18 +1712,7 @@ impl<B: StorageBackend> StorageWrite for ReactiveAdapter<B> {
         patch: &Value,
         opts: &PatchOptions,
     ) -> Result<PatchManyResult> {
-        let result = self.inner.lock().patch_many(def, filter, patch, opts)?;
-        let ids: Vec<String> = result.records.iter().map(|r| r.id.clone()).collect();
-        if !ids.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Bulk {
-                collection: collection.clone(),
-                ids: ids.clone(),
-            });
-            self.mark_dirty_collection(&collection, &ids);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| tx.patch_many(def, filter, patch, opts))
     }
 }
 
@@ -754,18 +1741,17 @@ impl<B: StorageBackend> StorageSync for ReactiveAdapter<B> {
         records: &[RemoteRecord],
         opts: &ApplyRemoteOptions,
     ) -> Result<ApplyRemoteResult> {
-        let result = self.inner.lock().apply_remote_changes(def, records, opts)?;
-        let ids: Vec<String> = result.applied.iter().map(|r| r.id.clone()).collect();
-        if !ids.is_empty() {
-            let collection = def.name.clone();
-            self.emit_event(ChangeEvent::Remote {
-                collection: collection.clone(),
-                ids: ids.clone(),
-            });
-            self.mark_dirty_collection(&collection, &ids);
-            self.flush();
-        }
-        Ok(result)
+        self.write(|tx| {
+            let result = tx.adapter().apply_remote_changes(def, records, opts)?;
+            let ids: Vec<String> = result.applied.iter().map(|r| r.id.clone()).collect();
+            if !ids.is_empty() {
+                tx.record(Change::Remote {
+                    collection: def.name.clone(),
+                    ids,
+                });
+            }
+            Ok(result)
+        })
     }
 
     fn get_last_sequence(&self, collection: &str) -> Result<i64> {
@@ -775,4 +1761,28 @@ impl<B: StorageBackend> StorageSync for ReactiveAdapter<B> {
     fn set_last_sequence(&self, collection: &str, sequence: i64) -> Result<()> {
         self.inner.lock().set_last_sequence(collection, sequence)
     }
+
+    fn get_push_state(&self, collection: &str) -> Result<Option<PushQueueState>> {
+        self.inner.lock().get_push_state(collection)
+    }
+
+    fn set_push_state(&self, collection: &str, state: Option<&PushQueueState>) -> Result<()> {
+        self.inner.lock().set_push_state(collection, state)
+    }
+
+    /// Purged tombstones are announced as a [`ChangeEvent::Bulk`] with no
+    /// ids, refreshing every query on the collection so `include_deleted`
+    /// results drop them.
+    fn purge_expired_tombstones(&self, def: &CollectionDef) -> Result<usize> {
+        self.write(|tx| {
+            let purged = tx.adapter().purge_expired_tombstones(def)?;
+            if purged > 0 {
+                tx.record(Change::Bulk {
+                    collection: def.name.clone(),
+                    ids: Vec::new(),
+                });
+            }
+            Ok(purged)
+        })
+    }
 }
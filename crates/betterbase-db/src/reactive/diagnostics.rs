@@ -0,0 +1,319 @@
+//! Subscription diagnostics — bookkeeping for finding leaked observers.
+//!
+//! Every `observe` / `observe_query` subscription carries a `SubStats`
+//! recording who created it, when, and how often it has fired. The counters
+//! are atomics updated in place, so the flush path neither allocates nor
+//! takes the state lock for them.
+//!
+//! Two opt-in checks sit on top, configured with
+//! [`SubscriptionDiagnostics`]:
+//!
+//! - a hard cap on live subscriptions, which makes new subscriptions fail
+//!   with [`LessDbError::SubscriptionLimit`](crate::error::LessDbError::SubscriptionLimit);
+//! - a staleness heuristic, which emits
+//!   [`LifecycleEvent::SubscriptionStale`](super::LifecycleEvent::SubscriptionStale)
+//!   for subscriptions that have sat idle while their collection kept
+//!   changing. Stale subscriptions are only reported, never removed.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+
+use serde::Serialize;
+
+/// Per-subscription options for `observe_with_options` /
+/// `observe_query_with_options`.
+#[derive(Debug, Clone, Default)]
+pub struct ObserveOptions {
+    /// Free-form owner tag (e.g. a component name) used to group
+    /// subscriptions in [`SubscriptionReport::by_label`].
+    pub label: Option<String>,
+}
+
+/// Leak checks for a `ReactiveAdapter`. The default disables both.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionDiagnostics {
+    /// Reject new subscriptions once this many are live.
+    pub max_subscriptions: Option<usize>,
+    /// Report subscriptions that look abandoned.
+    pub stale: Option<StaleThreshold>,
+}
+
+/// A subscription is reported stale when it has not fired for `idle_ms`
+/// while its collection saw at least `min_changes` changes that did not
+/// reach it.
+///
+/// Query subscriptions refire on every change to their collection, so in
+/// practice this flags record subscriptions whose record is no longer
+/// touched — the typical shape of a missed effect cleanup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaleThreshold {
+    pub idle_ms: u64,
+    pub min_changes: u64,
+}
+
+/// Which `observe` variant created a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubscriptionKind {
+    Record,
+    Query,
+    Aggregate,
+}
+
+/// One live subscription in a [`SubscriptionReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionInfo {
+    pub id: u64,
+    pub kind: SubscriptionKind,
+    pub collection: String,
+    /// The observed record, for record subscriptions.
+    pub record_id: Option<String>,
+    pub label: Option<String>,
+    /// Unix milliseconds.
+    pub created_at_ms: i64,
+    /// Unix milliseconds of the last callback, or `None` if it never fired.
+    pub last_fired_at_ms: Option<i64>,
+    pub fire_count: u64,
+}
+
+/// Number of live subscriptions sharing a label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelCount {
+    /// `None` groups the unlabeled subscriptions.
+    pub label: Option<String>,
+    pub count: usize,
+}
+
+/// Snapshot of every live subscription, returned by
+/// `ReactiveAdapter::subscription_report`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionReport {
+    /// Ordered by subscription ID (creation order).
+    pub subscriptions: Vec<SubscriptionInfo>,
+    /// Largest group first, so a leaking owner is at the top.
+    pub by_label: Vec<LabelCount>,
+}
+
+impl SubscriptionReport {
+    pub(crate) fn new(mut subscriptions: Vec<SubscriptionInfo>) -> Self {
+        subscriptions.sort_by_key(|s| s.id);
+
+        let mut counts: HashMap<Option<&str>, usize> = HashMap::new();
+        for sub in &subscriptions {
+            *counts.entry(sub.label.as_deref()).or_default() += 1;
+        }
+        let mut by_label: Vec<LabelCount> = counts
+            .into_iter()
+            .map(|(label, count)| LabelCount {
+                label: label.map(str::to_string),
+                count,
+            })
+            .collect();
+        by_label.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.label.cmp(&b.label)));
+
+        Self {
+            subscriptions,
+            by_label,
+        }
+    }
+
+    /// Total live subscriptions.
+    pub fn total(&self) -> usize {
+        self.subscriptions.len()
+    }
+}
+
+/// Sentinel for "never fired" in [`SubStats::last_fired_at_ms`].
+const NEVER: i64 = i64::MIN;
+
+/// Bookkeeping attached to each subscription.
+pub(crate) struct SubStats {
+    pub(crate) label: Option<String>,
+    pub(crate) created_at_ms: i64,
+    fire_count: AtomicU64,
+    last_fired_at_ms: AtomicI64,
+    /// Collection changes that did not reach this subscription since it
+    /// last fired. Only maintained while a stale threshold is configured.
+    missed_changes: AtomicU64,
+    /// Set once a stale event has been emitted; cleared when it fires again.
+    stale_reported: AtomicBool,
+}
+
+impl SubStats {
+    pub(crate) fn new(label: Option<String>, now_ms: i64) -> Self {
+        Self {
+            label,
+            created_at_ms: now_ms,
+            fire_count: AtomicU64::new(0),
+            last_fired_at_ms: AtomicI64::new(NEVER),
+            missed_changes: AtomicU64::new(0),
+            stale_reported: AtomicBool::new(false),
+        }
+    }
+
+    /// Record a callback delivery.
+    pub(crate) fn fired(&self, now_ms: i64) {
+        self.fire_count.fetch_add(1, Ordering::Relaxed);
+        self.last_fired_at_ms.store(now_ms, Ordering::Relaxed);
+        self.missed_changes.store(0, Ordering::Relaxed);
+        self.stale_reported.store(false, Ordering::Relaxed);
+    }
+
+    pub(crate) fn fire_count(&self) -> u64 {
+        self.fire_count.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn last_fired_at_ms(&self) -> Option<i64> {
+        match self.last_fired_at_ms.load(Ordering::Relaxed) {
+            NEVER => None,
+            ms => Some(ms),
+        }
+    }
+
+    /// Count `changes` missed changes and decide whether this subscription
+    /// has just become stale. Returns `(idle_ms, missed)` the first time it
+    /// crosses `threshold`.
+    pub(crate) fn miss(
+        &self,
+        changes: u64,
+        now_ms: i64,
+        threshold: StaleThreshold,
+    ) -> Option<(u64, u64)> {
+        let missed = self.missed_changes.fetch_add(changes, Ordering::Relaxed) + changes;
+        let since = self.last_fired_at_ms().unwrap_or(self.created_at_ms);
+        let idle_ms = now_ms.saturating_sub(since).max(0) as u64;
+        if missed < threshold.min_changes || idle_ms < threshold.idle_ms {
+            return None;
+        }
+        if self.stale_reported.swap(true, Ordering::Relaxed) {
+            return None;
+        }
+        Some((idle_ms, missed))
+    }
+}
+
+/// Current wall-clock time in Unix milliseconds.
+pub(crate) fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// Counts allocations made by the current thread, so parallel tests do
+    /// not disturb each other's counts.
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc;
+
+    fn allocations_in(f: impl FnOnce()) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    #[test]
+    fn fire_bookkeeping_does_not_allocate() {
+        let stats = SubStats::new(Some("Sidebar".to_string()), now_ms());
+        let allocations = allocations_in(|| {
+            for _ in 0..1_000 {
+                stats.fired(now_ms());
+            }
+            assert_eq!(stats.fire_count(), 1_000);
+            assert!(stats.last_fired_at_ms().is_some());
+        });
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn miss_reports_once_per_quiet_spell() {
+        let threshold = StaleThreshold {
+            idle_ms: 0,
+            min_changes: 3,
+        };
+        let stats = SubStats::new(None, 1_000);
+
+        assert_eq!(stats.miss(2, 1_000, threshold), None);
+        assert_eq!(stats.miss(1, 1_500, threshold), Some((500, 3)));
+        assert_eq!(stats.miss(5, 2_000, threshold), None);
+
+        stats.fired(2_000);
+        assert_eq!(stats.miss(3, 2_100, threshold), Some((100, 3)));
+    }
+
+    #[test]
+    fn miss_waits_for_idle_time() {
+        let threshold = StaleThreshold {
+            idle_ms: 60_000,
+            min_changes: 1,
+        };
+        let stats = SubStats::new(None, 0);
+
+        assert_eq!(stats.miss(10, 59_999, threshold), None);
+        assert_eq!(stats.miss(1, 60_000, threshold), Some((60_000, 11)));
+    }
+
+    #[test]
+    fn report_groups_labels_largest_first() {
+        let info = |id, label: Option<&str>| SubscriptionInfo {
+            id,
+            kind: SubscriptionKind::Record,
+            collection: "notes".to_string(),
+            record_id: Some(id.to_string()),
+            label: label.map(str::to_string),
+            created_at_ms: 0,
+            last_fired_at_ms: None,
+            fire_count: 0,
+        };
+        let report = SubscriptionReport::new(vec![
+            info(3, Some("b")),
+            info(1, None),
+            info(2, Some("b")),
+            info(4, Some("a")),
+        ]);
+
+        let ids: Vec<u64> = report.subscriptions.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        assert_eq!(
+            report.by_label,
+            vec![
+                LabelCount {
+                    label: Some("b".to_string()),
+                    count: 2
+                },
+                LabelCount {
+                    label: None,
+                    count: 1
+                },
+                LabelCount {
+                    label: Some("a".to_string()),
+                    count: 1
+                },
+            ]
+        );
+        assert_eq!(report.total(), 4);
+    }
+}
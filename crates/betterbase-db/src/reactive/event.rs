@@ -2,23 +2,44 @@
 //!
 //! Emitted by `ReactiveAdapter` after each write operation so that subscribers
 //! know which collection/record(s) changed.
+//!
+//! LifecycleEvent — schema, index, and maintenance milestones for a
+//! collection, emitted on a separate stream so that data listeners are not
+//! woken for structural changes.
+
+use serde::Serialize;
 
 /// A change event emitted by the reactive adapter after any mutation.
+///
+/// Every variant carries the `tx_id` of the commit that produced it: a
+/// per-adapter counter that increases with each commit. All events from one
+/// [`ReactiveAdapter::transaction`](super::ReactiveAdapter::transaction)
+/// share a `tx_id`, so subscribers can apply them to the UI together.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChangeEvent {
     /// A single record was inserted or replaced.
-    Put { collection: String, id: String },
+    Put {
+        collection: String,
+        id: String,
+        tx_id: u64,
+    },
     /// A single record was deleted (soft-deleted / tombstoned).
-    Delete { collection: String, id: String },
+    Delete {
+        collection: String,
+        id: String,
+        tx_id: u64,
+    },
     /// Multiple records in a collection were written in bulk.
     Bulk {
         collection: String,
         ids: Vec<String>,
+        tx_id: u64,
     },
     /// Remote changes were applied to a collection.
     Remote {
         collection: String,
         ids: Vec<String>,
+        tx_id: u64,
     },
 }
 
@@ -33,6 +54,16 @@ impl ChangeEvent {
         }
     }
 
+    /// The commit this event belongs to.
+    pub fn tx_id(&self) -> u64 {
+        match self {
+            Self::Put { tx_id, .. }
+            | Self::Delete { tx_id, .. }
+            | Self::Bulk { tx_id, .. }
+            | Self::Remote { tx_id, .. } => *tx_id,
+        }
+    }
+
     /// IDs of the records that were affected.
     pub fn ids(&self) -> Vec<&str> {
         match self {
@@ -43,3 +74,124 @@ impl ChangeEvent {
         }
     }
 }
+
+// ============================================================================
+// Lifecycle events
+// ============================================================================
+
+/// Counters reported when an index rebuild finishes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexRebuildStats {
+    /// Records visited by the rebuild (tombstones excluded).
+    pub records_scanned: usize,
+    /// Index entries written.
+    pub entries_written: usize,
+    /// Wall-clock duration of the rebuild in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// Counters reported by a maintenance task.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceStats {
+    /// Records removed or rewritten by the task (or that would be, on a dry run).
+    pub records_affected: usize,
+    /// Whether the task only counted and left storage untouched.
+    pub dry_run: bool,
+}
+
+/// A collection-level lifecycle event emitted by the reactive adapter.
+///
+/// `*Finished` events are emitted only after the operation's effects are
+/// visible to reads through the adapter. Serializes as an object tagged by
+/// `type` with camelCase keys, which is the shape the WASM layer hands to JS.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LifecycleEvent {
+    /// A collection definition was registered during `initialize()`.
+    #[serde(rename_all = "camelCase")]
+    CollectionRegistered {
+        collection: String,
+        version: u32,
+        indexes: Vec<String>,
+    },
+    /// A collection's records were moved to a new name.
+    #[serde(rename_all = "camelCase")]
+    CollectionRenamed { from: String, to: String },
+    /// A collection and all of its records were removed.
+    #[serde(rename_all = "camelCase")]
+    CollectionDropped { collection: String },
+    /// An index rebuild started.
+    #[serde(rename_all = "camelCase")]
+    IndexRebuildStarted { collection: String, index: String },
+    /// Periodic progress for a running index rebuild, once per batch of
+    /// records recomputed.
+    #[serde(rename_all = "camelCase")]
+    IndexRebuildProgress {
+        collection: String,
+        index: String,
+        processed: usize,
+        total: usize,
+    },
+    /// An index rebuild finished and the index is usable by queries.
+    #[serde(rename_all = "camelCase")]
+    IndexRebuildFinished {
+        collection: String,
+        index: String,
+        stats: IndexRebuildStats,
+    },
+    /// Stored records are about to be migrated to the current schema version.
+    ///
+    /// `from_version` is the oldest version found in storage.
+    #[serde(rename_all = "camelCase")]
+    MigrationStarted {
+        collection: String,
+        from_version: u32,
+        to_version: u32,
+    },
+    /// Migration finished; `migrated` records were rewritten.
+    #[serde(rename_all = "camelCase")]
+    MigrationFinished {
+        collection: String,
+        from_version: u32,
+        to_version: u32,
+        migrated: usize,
+    },
+    /// A maintenance task ran (e.g. `"purge_tombstones"`).
+    #[serde(rename_all = "camelCase")]
+    MaintenanceRun {
+        collection: String,
+        task: String,
+        stats: MaintenanceStats,
+    },
+    /// A subscription has gone quiet while its collection kept changing,
+    /// which suggests its consumer is gone without unsubscribing. Reported
+    /// once per quiet spell; the subscription itself is left in place.
+    #[serde(rename_all = "camelCase")]
+    SubscriptionStale {
+        collection: String,
+        subscription_id: u64,
+        label: Option<String>,
+        idle_ms: u64,
+        missed_changes: u64,
+    },
+}
+
+impl LifecycleEvent {
+    /// The collection the event refers to. For renames this is the new name.
+    pub fn collection(&self) -> &str {
+        match self {
+            Self::CollectionRegistered { collection, .. } => collection,
+            Self::CollectionRenamed { to, .. } => to,
+            Self::CollectionDropped { collection } => collection,
+            Self::IndexRebuildStarted { collection, .. } => collection,
+            Self::IndexRebuildProgress { collection, .. } => collection,
+            Self::IndexRebuildFinished { collection, .. } => collection,
+            Self::MigrationStarted { collection, .. } => collection,
+            Self::MigrationFinished { collection, .. } => collection,
+            Self::MaintenanceRun { collection, .. } => collection,
+            Self::SubscriptionStale { collection, .. } => collection,
+        }
+    }
+}
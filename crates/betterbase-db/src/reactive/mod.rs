@@ -3,22 +3,34 @@
 //! # Overview
 //!
 //! [`ReactiveAdapter`] wraps an [`Adapter`] and adds `observe` / `observe_query`
-//! / `on_change` subscriptions. Callbacks fire synchronously during `flush()`,
-//! which is called automatically after every write.
+//! / `on_change` / `on_lifecycle` subscriptions. Callbacks fire synchronously
+//! during `flush()`, which is called automatically after every write.
 //!
 //! # Modules
 //!
-//! - [`event`] — [`ChangeEvent`] enum.
+//! - [`event`] — [`ChangeEvent`] and [`LifecycleEvent`] enums.
+//! - [`diagnostics`] — subscription bookkeeping, leak report, and limits.
 //! - [`event_emitter`] — Generic typed pub/sub ([`EventEmitter<T>`]).
 //! - [`query_fields`] — [`extract_query_fields`] helper.
-//! - [`adapter`] — [`ReactiveAdapter<B>`] and [`ReactiveQueryResult`].
+//! - [`transaction`] — [`ReactiveTransaction`], the write handle for
+//!   [`ReactiveAdapter::transaction`].
+//! - [`adapter`] — [`ReactiveAdapter<B>`], [`ReactiveQueryResult`], and [`WindowResult`].
 
 pub mod adapter;
+pub mod diagnostics;
 pub mod event;
 pub mod event_emitter;
 pub mod query_fields;
+pub mod transaction;
 
-pub use adapter::{ReactiveAdapter, ReactiveQueryResult, Unsubscribe};
-pub use event::ChangeEvent;
+pub use adapter::{
+    QueryDiff, ReactiveAdapter, ReactiveQueryResult, Unsubscribe, UpdatedRecord, WindowResult,
+};
+pub use diagnostics::{
+    LabelCount, ObserveOptions, StaleThreshold, SubscriptionDiagnostics, SubscriptionInfo,
+    SubscriptionKind, SubscriptionReport,
+};
+pub use event::{ChangeEvent, IndexRebuildStats, LifecycleEvent, MaintenanceStats};
 pub use event_emitter::{EventEmitter, ListenerId};
 pub use query_fields::{extract_query_fields, QueryFieldInfo};
+pub use transaction::ReactiveTransaction;
@@ -51,10 +51,8 @@ fn extract_filter_fields(filter: &Value, fields: &mut HashSet<String>) -> bool {
                     }
                 }
             }
-            "$not" => {
-                if extract_filter_fields(value, fields) {
-                    has_computed = true;
-                }
+            "$not" if extract_filter_fields(value, fields) => {
+                has_computed = true;
             }
             "$computed" => {
                 has_computed = true;
@@ -0,0 +1,323 @@
+//! ReactiveTransaction — the write handle behind `ReactiveAdapter` commits.
+//!
+//! Every write through a `ReactiveAdapter` runs against a transaction handle
+//! that forwards to the inner `Adapter` and records which records changed.
+//! Once the commit succeeds, the adapter turns those records into
+//! [`ChangeEvent`]s stamped with one `tx_id`.
+
+use std::cell::RefCell;
+
+use serde_json::Value;
+
+use crate::{
+    collection::builder::CollectionDef,
+    error::Result,
+    query::types::Query,
+    storage::{
+        adapter::Adapter,
+        traits::{QueryPlan, StorageBackend, StorageRead, StorageWrite},
+    },
+    types::{
+        BatchResult, BulkDeleteResult, BulkPatchResult, DeleteOptions, GetOptions, ListOptions,
+        PatchManyResult, PatchOptions, PutOptions, QueryResult, StoredRecordWithMeta, TouchOptions,
+    },
+};
+
+use super::event::ChangeEvent;
+
+// ============================================================================
+// Change
+// ============================================================================
+
+/// A committed write that has not been assigned its `tx_id` yet.
+pub(crate) enum Change {
+    Put {
+        collection: String,
+        id: String,
+    },
+    Delete {
+        collection: String,
+        id: String,
+    },
+    Bulk {
+        collection: String,
+        ids: Vec<String>,
+    },
+    Remote {
+        collection: String,
+        ids: Vec<String>,
+    },
+}
+
+impl Change {
+    pub(crate) fn into_event(self, tx_id: u64) -> ChangeEvent {
+        match self {
+            Self::Put { collection, id } => ChangeEvent::Put {
+                collection,
+                id,
+                tx_id,
+            },
+            Self::Delete { collection, id } => ChangeEvent::Delete {
+                collection,
+                id,
+                tx_id,
+            },
+            Self::Bulk { collection, ids } => ChangeEvent::Bulk {
+                collection,
+                ids,
+                tx_id,
+            },
+            Self::Remote { collection, ids } => ChangeEvent::Remote {
+                collection,
+                ids,
+                tx_id,
+            },
+        }
+    }
+}
+
+/// Merge local changes into one `Bulk` per collection, in the order each
+/// collection was first touched. Ids keep their first-seen order and appear
+/// once. `Remote` changes pass through unmerged.
+pub(crate) fn coalesce_changes(changes: Vec<Change>) -> Vec<Change> {
+    let mut merged: Vec<Change> = Vec::new();
+    for change in changes {
+        let (collection, ids) = match change {
+            Change::Put { collection, id } | Change::Delete { collection, id } => {
+                (collection, vec![id])
+            }
+            Change::Bulk { collection, ids } => (collection, ids),
+            remote @ Change::Remote { .. } => {
+                merged.push(remote);
+                continue;
+            }
+        };
+        let existing = merged.iter_mut().find_map(|c| match c {
+            Change::Bulk {
+                collection: name,
+                ids,
+            } if *name == collection => Some(ids),
+            _ => None,
+        });
+        match existing {
+            Some(existing) => {
+                for id in ids {
+                    if !existing.contains(&id) {
+                        existing.push(id);
+                    }
+                }
+            }
+            None => {
+                let mut unique = Vec::with_capacity(ids.len());
+                for id in ids {
+                    if !unique.contains(&id) {
+                        unique.push(id);
+                    }
+                }
+                merged.push(Change::Bulk {
+                    collection,
+                    ids: unique,
+                });
+            }
+        }
+    }
+    merged
+}
+
+// ============================================================================
+// ReactiveTransaction
+// ============================================================================
+
+/// Read/write handle passed to the closure of
+/// [`ReactiveAdapter::transaction`](super::ReactiveAdapter::transaction).
+///
+/// Reads see the transaction's own earlier writes. Change events for the
+/// writes are held back until the transaction commits.
+pub struct ReactiveTransaction<'a, B: StorageBackend> {
+    adapter: &'a Adapter<B>,
+    changes: RefCell<Vec<Change>>,
+}
+
+impl<'a, B: StorageBackend> ReactiveTransaction<'a, B> {
+    pub(crate) fn new(adapter: &'a Adapter<B>) -> Self {
+        Self {
+            adapter,
+            changes: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn adapter(&self) -> &'a Adapter<B> {
+        self.adapter
+    }
+
+    pub(crate) fn record(&self, change: Change) {
+        self.changes.borrow_mut().push(change);
+    }
+
+    pub(crate) fn into_changes(self) -> Vec<Change> {
+        self.changes.into_inner()
+    }
+
+    fn record_bulk(&self, def: &CollectionDef, ids: Vec<String>) {
+        if !ids.is_empty() {
+            self.record(Change::Bulk {
+                collection: def.name.clone(),
+                ids,
+            });
+        }
+    }
+}
+
+impl<B: StorageBackend> StorageRead for ReactiveTransaction<'_, B> {
+    fn get(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &GetOptions,
+    ) -> Result<Option<StoredRecordWithMeta>> {
+        self.adapter.get(def, id, opts)
+    }
+
+    fn get_all(&self, def: &CollectionDef, opts: &ListOptions) -> Result<BatchResult> {
+        self.adapter.get_all(def, opts)
+    }
+
+    fn query(&self, def: &CollectionDef, query: &Query) -> Result<QueryResult> {
+        self.adapter.query(def, query)
+    }
+
+    fn count(&self, def: &CollectionDef, query: Option<&Query>) -> Result<usize> {
+        self.adapter.count(def, query)
+    }
+
+    fn explain_query(&self, def: &CollectionDef, query: &Query) -> QueryPlan {
+        self.adapter.explain_query(def, query)
+    }
+}
+
+impl<B: StorageBackend> StorageWrite for ReactiveTransaction<'_, B> {
+    fn put(
+        &self,
+        def: &CollectionDef,
+        data: Value,
+        opts: &PutOptions,
+    ) -> Result<StoredRecordWithMeta> {
+        let record = self.adapter.put(def, data, opts)?;
+        self.record(Change::Put {
+            collection: def.name.clone(),
+            id: record.id.clone(),
+        });
+        Ok(record)
+    }
+
+    fn patch(
+        &self,
+        def: &CollectionDef,
+        data: Value,
+        opts: &PatchOptions,
+    ) -> Result<StoredRecordWithMeta> {
+        let record = self.adapter.patch(def, data, opts)?;
+        self.record(Change::Put {
+            collection: def.name.clone(),
+            id: record.id.clone(),
+        });
+        Ok(record)
+    }
+
+    fn delete(&self, def: &CollectionDef, id: &str, opts: &DeleteOptions) -> Result<bool> {
+        let deleted = self.adapter.delete(def, id, opts)?;
+        if deleted {
+            self.record(Change::Delete {
+                collection: def.name.clone(),
+                id: id.to_string(),
+            });
+        }
+        Ok(deleted)
+    }
+
+    fn tombstone_archived(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &DeleteOptions,
+    ) -> Result<bool> {
+        let converted = self.adapter.tombstone_archived(def, id, opts)?;
+        if converted {
+            self.record(Change::Delete {
+                collection: def.name.clone(),
+                id: id.to_string(),
+            });
+        }
+        Ok(converted)
+    }
+
+    fn touch(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &TouchOptions,
+    ) -> Result<StoredRecordWithMeta> {
+        let record = self.adapter.touch(def, id, opts)?;
+        self.record(Change::Put {
+            collection: def.name.clone(),
+            id: record.id.clone(),
+        });
+        Ok(record)
+    }
+
+    fn bulk_put(
+        &self,
+        def: &CollectionDef,
+        records: Vec<Value>,
+        opts: &PutOptions,
+    ) -> Result<BatchResult> {
+        let result = self.adapter.bulk_put(def, records, opts)?;
+        self.record_bulk(def, result.records.iter().map(|r| r.id.clone()).collect());
+        Ok(result)
+    }
+
+    fn bulk_delete(
+        &self,
+        def: &CollectionDef,
+        ids: &[&str],
+        opts: &DeleteOptions,
+    ) -> Result<BulkDeleteResult> {
+        let result = self.adapter.bulk_delete(def, ids, opts)?;
+        self.record_bulk(def, result.deleted_ids.clone());
+        Ok(result)
+    }
+
+    fn bulk_patch(
+        &self,
+        def: &CollectionDef,
+        patches: Vec<Value>,
+        opts: &PatchOptions,
+    ) -> Result<BulkPatchResult> {
+        let result = self.adapter.bulk_patch(def, patches, opts)?;
+        self.record_bulk(def, result.records.iter().map(|r| r.id.clone()).collect());
+        Ok(result)
+    }
+
+    fn delete_many(
+        &self,
+        def: &CollectionDef,
+        filter: &Value,
+        opts: &DeleteOptions,
+    ) -> Result<BulkDeleteResult> {
+        let result = self.adapter.delete_many(def, filter, opts)?;
+        self.record_bulk(def, result.deleted_ids.clone());
+        Ok(result)
+    }
+
+    fn patch_many(
+        &self,
+        def: &CollectionDef,
+        filter: &Value,
+        patch: &Value,
+        opts: &PatchOptions,
+    ) -> Result<PatchManyResult> {
+        let result = self.adapter.patch_many(def, filter, patch, opts)?;
+        self.record_bulk(def, result.records.iter().map(|r| r.id.clone()).collect());
+        Ok(result)
+    }
+}
@@ -98,6 +98,15 @@ pub mod t {
         SchemaNode::Object(properties)
     }
 
+    /// A `{lat, lng}` point in degrees. Pair with `geo_index` on the
+    /// collection builder to make it queryable with `$geoBox`.
+    pub fn geopoint() -> SchemaNode {
+        SchemaNode::Object(BTreeMap::from([
+            ("lat".to_string(), SchemaNode::Number),
+            ("lng".to_string(), SchemaNode::Number),
+        ]))
+    }
+
     pub fn literal_str(s: impl Into<String>) -> SchemaNode {
         SchemaNode::Literal(LiteralValue::String(s.into()))
     }
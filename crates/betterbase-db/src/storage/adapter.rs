@@ -4,33 +4,45 @@
 //! The adapter handles CRUD, query execution, migration, unique-constraint checks,
 //! and sync operations. All raw I/O is delegated to the backend.
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
+use betterbase_sync_core::SpaceDeletePolicy;
 use parking_lot::Mutex;
 use serde_json::Value;
 
 use crate::{
-    collection::builder::CollectionDef,
+    collection::{autofill::IdStrategy, builder::CollectionDef},
     crdt,
     error::{LessDbError, Result, StorageError},
-    index::planner::{plan_query, QueryPlan},
+    index::{
+        planner::{plan_query, QueryPlan},
+        stats::IndexStats,
+        types::{IndexDefinition, IndexScan},
+    },
     query::{
-        operators::{compare_values, filter_records, get_field_value, matches_filter},
-        types::{normalize_sort, Query, SortDirection},
+        aggregate::{sort_rows, AggregateRow, AggregateSpec, Aggregator},
+        execute::compare_for_sort,
+        operators::{filter_records, FilterMatcher},
+        types::{normalize_sort, Query, SortDirection, SortEntry},
     },
     storage::{
+        query_cache::{query_cache_key, QueryCache},
         record_manager::{
-            migrate_and_deserialize, prepare_delete, prepare_mark_synced, prepare_new,
-            prepare_patch, prepare_update,
+            compute_index_values, migrate_and_deserialize, prepare_delete, prepare_mark_synced,
+            prepare_new, prepare_patch, prepare_touch, prepare_update, utc_now_z, META_EDIT_CHAIN,
+            META_EDIT_CHAIN_TRIMMED,
         },
         remote_changes::{apply_remote_decisions, process_remote_record, RemoteDecision},
+        snapshot::{SnapshotHandle, VersionedBackend},
         traits::{StorageBackend, StorageLifecycle, StorageRead, StorageSync, StorageWrite},
     },
     types::{
         ApplyRemoteOptions, ApplyRemoteResult, BatchResult, BulkDeleteResult, BulkPatchResult,
-        DeleteConflictStrategy, DeleteConflictStrategyName, DeleteOptions, GetOptions, ListOptions,
-        PatchManyResult, PatchOptions, PushSnapshot, PutOptions, QueryResult, RecordError,
-        RemoteRecord, ScanOptions, SerializedRecord, StoredRecordWithMeta,
+        CompactOptions, CompactReport, DeleteConflictStrategy, DeleteConflictStrategyName,
+        DeleteKind, DeleteOptions, GetOptions, ListOptions, MigrationReport, OperationContext,
+        PatchManyResult, PatchOptions, PurgeTombstonesOptions, PushQueueState, PushSnapshot,
+        PutOptions, QueryResult, RecordError, RemoteAction, RemoteRecord, ResetOptions,
+        ScanOptions, SerializedRecord, StoredRecordWithMeta, TouchOptions,
     },
 };
 
@@ -44,6 +56,72 @@ const META_SESSION_ID: &str = "session_id";
 /// Prefix for per-collection sync sequence cursors (formatted as `"seq:{collection}"`).
 const META_SEQ_PREFIX: &str = "seq:";
 
+/// Prefix for per-collection push retry state (formatted as `"push:{collection}"`).
+const META_PUSH_PREFIX: &str = "push:";
+
+/// Prefix for per-collection batched reindex progress (formatted as
+/// `"reindex:{collection}"`, value `"{processed}/{total}"`).
+const META_REINDEX_PREFIX: &str = "reindex:";
+
+/// Ids minted for one insert before giving up on finding an unused one (see
+/// [`Adapter::mint_id`]).
+const MAX_ID_MINT_ATTEMPTS: usize = 8;
+
+/// Records written per backend batch by eager migration.
+const MIGRATION_BATCH_SIZE: usize = 500;
+
+/// Records recomputed per backend transaction by a batched reindex (see
+/// [`Adapter::reindex_records`]).
+pub(crate) const REINDEX_BATCH_SIZE: usize = 500;
+
+/// What [`Adapter::migrate_stale`] did: the ids it rewrote and the records
+/// it left as stored, with their migration errors.
+#[derive(Debug, Default)]
+pub(crate) struct StaleMigration {
+    pub(crate) migrated: Vec<String>,
+    pub(crate) failed: Vec<(String, LessDbError)>,
+}
+
+impl StaleMigration {
+    /// The migrated ids, or the first failure's error.
+    pub(crate) fn into_ids(self) -> Result<Vec<String>> {
+        match self.failed.into_iter().next() {
+            Some((_, error)) => Err(error),
+            None => Ok(self.migrated),
+        }
+    }
+
+    /// A [`MigrationReport`] with failures rendered per record.
+    pub(crate) fn into_report(self, collection: &str) -> MigrationReport {
+        MigrationReport {
+            migrated: self.migrated,
+            failed: self
+                .failed
+                .into_iter()
+                .map(|(id, error)| RecordError {
+                    id,
+                    collection: collection.to_string(),
+                    error: error_chain(&error),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `error` followed by each of its sources, joined with `": "`, so a failed
+/// migration reports what the migration function returned.
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
 // ============================================================================
 // Adapter Struct
 // ============================================================================
@@ -51,10 +129,24 @@ const META_SEQ_PREFIX: &str = "seq:";
 /// Orchestration layer that wraps a `StorageBackend` with full CRUD, query,
 /// migration, and sync semantics.
 pub struct Adapter<B: StorageBackend> {
-    pub(crate) backend: B,
+    pub(crate) backend: VersionedBackend<B>,
     collections: Vec<Arc<CollectionDef>>,
     initialized: bool,
     session_id: Mutex<Option<u64>>,
+    query_cache: Mutex<QueryCache>,
+}
+
+/// Invalidates a collection's cached queries when dropped, i.e. once the
+/// write it guards has finished, failed, or returned early.
+struct InvalidateQueries<'a> {
+    cache: &'a Mutex<QueryCache>,
+    collection: &'a str,
+}
+
+impl Drop for InvalidateQueries<'_> {
+    fn drop(&mut self) {
+        self.cache.lock().invalidate(self.collection);
+    }
 }
 
 impl<B: StorageBackend> Adapter<B> {
@@ -63,10 +155,45 @@ impl<B: StorageBackend> Adapter<B> {
     /// `initialize()` must be called before any read/write operations.
     pub fn new(backend: B) -> Self {
         Self {
-            backend,
+            backend: VersionedBackend::new(backend),
             collections: Vec::new(),
             initialized: false,
             session_id: Mutex::new(None),
+            query_cache: Mutex::new(QueryCache::default()),
+        }
+    }
+
+    // -----------------------------------------------------------------------
+    // Query cache
+    // -----------------------------------------------------------------------
+
+    /// Cache up to `capacity` query results, least recently used evicted
+    /// first. 0 (the default) disables the cache.
+    ///
+    /// Identical queries (same filter up to key order, sort, limit and
+    /// offset) on a collection with no writes in between return a clone of
+    /// the earlier result without touching the backend. Any write through
+    /// this adapter invalidates the collection's entries; writes made
+    /// directly on the backend do not, so call
+    /// [`clear_query_cache`](Self::clear_query_cache) after those.
+    pub fn set_query_cache_capacity(&self, capacity: usize) {
+        self.query_cache.lock().set_capacity(capacity);
+    }
+
+    /// The query cache bound; 0 when disabled.
+    pub fn query_cache_capacity(&self) -> usize {
+        self.query_cache.lock().capacity()
+    }
+
+    /// Drop every cached query result.
+    pub fn clear_query_cache(&self) {
+        self.query_cache.lock().clear();
+    }
+
+    fn invalidate_queries_after<'a>(&'a self, collection: &'a str) -> InvalidateQueries<'a> {
+        InvalidateQueries {
+            cache: &self.query_cache,
+            collection,
         }
     }
 
@@ -126,6 +253,7 @@ impl<B: StorageBackend> Adapter<B> {
             dirty: record.dirty,
             deleted: record.deleted,
             deleted_at: record.deleted_at,
+            archived: record.archived,
             meta: record.meta,
             was_migrated,
             original_version,
@@ -233,10 +361,87 @@ impl<B: StorageBackend> Adapter<B> {
         Ok(())
     }
 
+    /// Mint an id for an insert into `def` that doesn't supply one.
+    ///
+    /// A random id that collides with a stored record (tombstones included)
+    /// is re-minted, up to [`MAX_ID_MINT_ATTEMPTS`] times, so an insert never
+    /// overwrites another record. A content-hash id that exists means
+    /// identical content was inserted before, which is
+    /// `StorageError::DuplicateContent` rather than an update.
+    fn mint_id(&self, def: &CollectionDef, data: &Value) -> Result<String> {
+        for _ in 0..MAX_ID_MINT_ATTEMPTS {
+            let id = def.id_strategy.mint(data);
+            let Some(existing) = self.backend.get_raw(&def.name, &id)? else {
+                return Ok(id);
+            };
+            if def.id_strategy == IdStrategy::ContentHash {
+                return Err(StorageError::DuplicateContent {
+                    collection: def.name.clone(),
+                    id: existing.id,
+                }
+                .into());
+            }
+        }
+        Err(StorageError::IdCollision {
+            collection: def.name.clone(),
+            attempts: MAX_ID_MINT_ATTEMPTS,
+        }
+        .into())
+    }
+
+    /// Fail with [`StorageError::Forbidden`] unless the collection's write
+    /// predicate allows the actor to write a record holding `data`.
+    fn check_write_access(
+        def: &CollectionDef,
+        ctx: &OperationContext,
+        id: &str,
+        data: &Value,
+    ) -> Result<()> {
+        if def.can_write(ctx, data) {
+            return Ok(());
+        }
+        Err(StorageError::Forbidden {
+            collection: def.name.clone(),
+            id: id.to_string(),
+        }
+        .into())
+    }
+
     // -----------------------------------------------------------------------
     // Internal query helper
     // -----------------------------------------------------------------------
 
+    /// Run `scan` followed by `union_scans`, keeping the first copy of each id.
+    /// Returns `None` if the backend can't serve any one of the scans.
+    fn scan_index_union(
+        &self,
+        collection: &str,
+        scan: &IndexScan,
+        union_scans: &[IndexScan],
+    ) -> Result<Option<Vec<SerializedRecord>>> {
+        let Some(first) = self.backend.scan_index_raw(collection, scan)? else {
+            return Ok(None);
+        };
+        if union_scans.is_empty() {
+            return Ok(Some(first.records));
+        }
+
+        let mut records = first.records;
+        let mut seen: HashSet<String> = records.iter().map(|r| r.id.clone()).collect();
+        for union_scan in union_scans {
+            let Some(batch) = self.backend.scan_index_raw(collection, union_scan)? else {
+                return Ok(None);
+            };
+            records.extend(
+                batch
+                    .records
+                    .into_iter()
+                    .filter(|r| seen.insert(r.id.clone())),
+            );
+        }
+        Ok(Some(records))
+    }
+
     /// Execute a query and return matching `SerializedRecord`s (pre-pagination).
     ///
     /// Returns `(records, errors, total_before_pagination)`.
@@ -253,10 +458,10 @@ impl<B: StorageBackend> Adapter<B> {
         // post-filtering is needed even when the planner produced a scan.
         let mut index_scan_used = false;
         let raw_records = if let Some(ref scan) = plan.scan {
-            match self.backend.scan_index_raw(&def.name, scan)? {
-                Some(result) => {
+            match self.scan_index_union(&def.name, scan, &plan.union_scans)? {
+                Some(records) => {
                     index_scan_used = true;
-                    result.records
+                    records
                 }
                 None => {
                     self.backend
@@ -275,8 +480,8 @@ impl<B: StorageBackend> Adapter<B> {
         let mut errors: Vec<Value> = Vec::new();
 
         for raw in raw_records {
-            // Skip deleted records in queries
-            if raw.deleted {
+            // Skip deleted and archived records in queries
+            if raw.deleted || raw.archived {
                 continue;
             }
             let id = raw.id.clone();
@@ -285,6 +490,9 @@ impl<B: StorageBackend> Adapter<B> {
             let computed = raw.computed.clone();
 
             match self.process_record(raw, true) {
+                // Unreadable records drop out before filtering so `total`
+                // doesn't leak how many the actor can't see
+                Ok(stored) if !def.can_read(&query.context, &stored.data) => {}
                 Ok(stored) => {
                     migrated_records.push(SerializedRecord {
                         id: stored.id,
@@ -297,6 +505,7 @@ impl<B: StorageBackend> Adapter<B> {
                         dirty: stored.dirty,
                         deleted: stored.deleted,
                         deleted_at: stored.deleted_at,
+                        archived: stored.archived,
                         meta: stored.meta,
                         computed,
                     });
@@ -331,9 +540,10 @@ impl<B: StorageBackend> Adapter<B> {
                     plan.post_filter.as_ref().or(query.filter.as_ref()).unwrap()
                 };
 
+                let matcher = FilterMatcher::new(filter)?;
                 let mut fr = Vec::new();
                 for r in migrated_records {
-                    if matches_filter(&r.data, filter)? {
+                    if matcher.matches(&r.data)? {
                         fr.push(r);
                     }
                 }
@@ -352,15 +562,9 @@ impl<B: StorageBackend> Adapter<B> {
                 let a = &filtered_records[i].data;
                 let b = &filtered_records[j].data;
                 for entry in sort {
-                    let va = get_field_value(a, &entry.field).unwrap_or(&Value::Null);
-                    let vb = get_field_value(b, &entry.field).unwrap_or(&Value::Null);
-                    let cmp = compare_values(va, vb);
+                    let cmp = compare_for_sort(a, b, entry);
                     if cmp != std::cmp::Ordering::Equal {
-                        return if entry.direction == SortDirection::Desc {
-                            cmp.reverse()
-                        } else {
-                            cmp
-                        };
+                        return cmp;
                     }
                 }
                 std::cmp::Ordering::Equal
@@ -386,6 +590,673 @@ impl<B: StorageBackend> Adapter<B> {
 
         Ok((paginated_records, errors, total))
     }
+
+    // -----------------------------------------------------------------------
+    // Singletons
+    // -----------------------------------------------------------------------
+
+    /// Return the record `id`, inserting `default_data` under that id first
+    /// if it does not exist. The flag is `true` when the record was created.
+    ///
+    /// The lookup and insert run in one backend transaction, so concurrent
+    /// callers never overwrite each other's defaults. Archived records count
+    /// as existing; a tombstoned id fails with [`StorageError::Deleted`].
+    pub fn get_or_create(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        default_data: Value,
+        opts: &PutOptions,
+    ) -> Result<(StoredRecordWithMeta, bool)> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        self.backend.transaction(|_| {
+            let get_opts = GetOptions {
+                include_archived: true,
+                context: opts.context.clone(),
+                ..Default::default()
+            };
+            if let Some(record) = self.get(def, id, &get_opts)? {
+                return Ok((record, false));
+            }
+
+            let put_opts = PutOptions {
+                id: Some(id.to_string()),
+                session_id: opts.session_id,
+                skip_unique_check: opts.skip_unique_check,
+                meta: opts.meta.clone(),
+                should_reset_sync_state: opts.should_reset_sync_state.clone(),
+                context: opts.context.clone(),
+            };
+            let record = self.put(def, default_data, &put_opts)?;
+            Ok((record, true))
+        })
+    }
+
+    // -----------------------------------------------------------------------
+    // Aggregation
+    // -----------------------------------------------------------------------
+
+    /// Compute `spec.metrics` for each distinct `spec.group_by` key over the
+    /// live records of `def` matching `spec.filter`, in one scan.
+    ///
+    /// The backend aggregates without loading records when the filter is
+    /// absent or fully served by a field index and the collection is fully
+    /// migrated. Otherwise records are folded here: when an index leads with
+    /// the group-by fields they are read in index order so groups arrive
+    /// already sorted, else the rows are sorted by group key at the end.
+    /// Records that fail to migrate are skipped, as in `query`. A collection
+    /// with a read predicate is aggregated over what `query` returns to
+    /// `spec.context`, never through the backend.
+    pub fn aggregate(
+        &self,
+        def: &CollectionDef,
+        spec: &AggregateSpec,
+    ) -> Result<Vec<AggregateRow>> {
+        self.check_initialized()?;
+
+        // Backend aggregates can't see the read predicate; fold what `query`
+        // returns instead
+        if def.read_predicate.is_some() {
+            let query = Query {
+                filter: spec.filter.clone(),
+                context: spec.context.clone(),
+                ..Default::default()
+            };
+            let (records, _, _) = self.run_query(def, &query)?;
+            let mut aggregator = Aggregator::new(spec);
+            for record in &records {
+                aggregator.add(&record.data);
+            }
+            return Ok(aggregator.finish(false));
+        }
+
+        let group_sort: Vec<SortEntry> = spec
+            .group_by
+            .iter()
+            .map(|field| SortEntry {
+                field: field.clone(),
+                direction: SortDirection::Asc,
+                nulls: None,
+            })
+            .collect();
+        let plan = plan_query(
+            spec.filter.as_ref(),
+            (!group_sort.is_empty()).then_some(group_sort.as_slice()),
+            &def.indexes,
+        );
+
+        // Computed index values and residual conditions need the records
+        let pushdown = match (&spec.filter, &plan.scan) {
+            (None, _) => Some(None),
+            (Some(_), Some(scan))
+                if plan.post_filter.is_none()
+                    && plan.union_scans.is_empty()
+                    && matches!(scan.index, IndexDefinition::Field(_)) =>
+            {
+                Some(Some(scan))
+            }
+            _ => None,
+        };
+        if let Some(scan) = pushdown {
+            if let Some(mut rows) =
+                self.backend
+                    .aggregate_raw(&def.name, def.current_version, scan, spec)?
+            {
+                sort_rows(&mut rows);
+                return Ok(rows);
+            }
+        }
+
+        let mut index_scan_used = false;
+        let raw_records = match plan.scan {
+            Some(ref scan) => match self.scan_index_union(&def.name, scan, &plan.union_scans)? {
+                Some(records) => {
+                    index_scan_used = true;
+                    records
+                }
+                None => {
+                    self.backend
+                        .scan_raw(&def.name, &ScanOptions::default())?
+                        .records
+                }
+            },
+            None => {
+                self.backend
+                    .scan_raw(&def.name, &ScanOptions::default())?
+                    .records
+            }
+        };
+        let presorted = index_scan_used && plan.index_provides_sort && plan.union_scans.is_empty();
+        let filter = if index_scan_used {
+            plan.post_filter.as_ref()
+        } else {
+            spec.filter.as_ref()
+        };
+        let matcher = filter.map(FilterMatcher::new).transpose()?;
+
+        let mut aggregator = Aggregator::new(spec);
+        for raw in raw_records {
+            if raw.deleted || raw.archived {
+                continue;
+            }
+            let Ok(record) = self.process_record(raw, true) else {
+                continue;
+            };
+            if let Some(ref matcher) = matcher {
+                if !matcher.matches(&record.data)? {
+                    continue;
+                }
+            }
+            aggregator.add(&record.data);
+        }
+        Ok(aggregator.finish(presorted))
+    }
+
+    // -----------------------------------------------------------------------
+    // Maintenance
+    // -----------------------------------------------------------------------
+
+    /// Oldest schema version among live records that predate
+    /// `def.current_version`, or `None` if the collection is fully migrated.
+    pub fn oldest_stale_version(&self, def: &CollectionDef) -> Result<Option<u32>> {
+        self.check_initialized()?;
+
+        let raw = self.backend.scan_raw(&def.name, &ScanOptions::default())?;
+        Ok(raw
+            .records
+            .iter()
+            .map(|r| r.version)
+            .filter(|v| *v < def.current_version)
+            .min())
+    }
+
+    /// Sample every index of `def` for
+    /// [`plan_query_with_stats`](crate::index::planner::plan_query_with_stats),
+    /// reading at most `sample_size` records per index.
+    pub fn sample_index_stats(
+        &self,
+        def: &CollectionDef,
+        sample_size: usize,
+    ) -> Result<Vec<IndexStats>> {
+        self.check_initialized()?;
+        def.indexes
+            .iter()
+            .map(|index| IndexStats::from_sample(&self.backend, &def.name, index, sample_size))
+            .collect()
+    }
+
+    /// Eagerly migrate every stale live record in the collection.
+    ///
+    /// Reads already migrate lazily; this exists so callers can pay the cost
+    /// up front (e.g. behind a progress indicator). Returns the migrated ids,
+    /// or the first record's migration error. Records that did migrate stay
+    /// written either way; see [`migrate_all`](Self::migrate_all).
+    pub fn migrate_collection(&self, def: &CollectionDef) -> Result<Vec<String>> {
+        self.migrate_stale(def)?.into_ids()
+    }
+
+    /// Eagerly migrate every stale live record in the collection, writing
+    /// the results back in batches.
+    ///
+    /// Unlike `migrate_collection`, a record that fails to migrate doesn't
+    /// abort the run: it is reported with its error and left as stored.
+    /// Batches written before a backend error stay written.
+    pub fn migrate_all(&self, def: &CollectionDef) -> Result<MigrationReport> {
+        Ok(self.migrate_stale(def)?.into_report(&def.name))
+    }
+
+    /// Migrate stale live records in batches of [`MIGRATION_BATCH_SIZE`],
+    /// recomputing their index values, and collect the records that fail.
+    pub(crate) fn migrate_stale(&self, def: &CollectionDef) -> Result<StaleMigration> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        let raw = self.backend.scan_raw(&def.name, &ScanOptions::default())?;
+        let mut outcome = StaleMigration::default();
+        let mut batch = Vec::with_capacity(MIGRATION_BATCH_SIZE);
+        for record in raw.records {
+            if record.version >= def.current_version {
+                continue;
+            }
+            match migrate_and_deserialize(def, &record) {
+                Ok(mig) => batch.push(SerializedRecord {
+                    computed: compute_index_values(&mig.data, &def.indexes),
+                    data: mig.data,
+                    crdt: mig.crdt,
+                    version: mig.version,
+                    ..record
+                }),
+                Err(e) => outcome.failed.push((record.id, e)),
+            }
+            if batch.len() == MIGRATION_BATCH_SIZE {
+                self.backend.batch_put_raw(&batch)?;
+                outcome.migrated.extend(batch.drain(..).map(|r| r.id));
+            }
+        }
+
+        if !batch.is_empty() {
+            self.backend.batch_put_raw(&batch)?;
+            outcome.migrated.extend(batch.into_iter().map(|r| r.id));
+        }
+        Ok(outcome)
+    }
+
+    /// Drop and recreate every index of `def`, then recompute the stored
+    /// computed-index values of its records. Returns the number of records
+    /// reindexed.
+    ///
+    /// Computed values are written when a record is, so an index added to
+    /// the definition later sees nothing for older records until this runs.
+    /// Everything happens in one backend transaction, which also clears any
+    /// progress left by an interrupted batched rebuild. Tombstones carry no
+    /// index values and are skipped.
+    pub fn reindex(&self, def: &CollectionDef) -> Result<usize> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+        let registered: Vec<&CollectionDef> = self.collections.iter().map(|c| &**c).collect();
+
+        self.backend.transaction(|backend| {
+            backend.rebuild_indexes(def, &registered)?;
+            let scan = ScanOptions {
+                include_archived: true,
+                ..Default::default()
+            };
+            let raw = backend.scan_raw(&def.name, &scan)?;
+            let mut count = 0;
+            for mut record in raw.records {
+                record.computed = compute_index_values(&record.data, &def.indexes);
+                backend.put_raw(&record)?;
+                count += 1;
+            }
+            set_reindex_progress(backend, &def.name, count, count)?;
+            Ok(count)
+        })
+    }
+
+    /// First step of a batched [`reindex`](Self::reindex): drop and recreate
+    /// every index of `def` and return the ids of the records whose
+    /// computed values need recomputing, for
+    /// [`reindex_records`](Self::reindex_records).
+    ///
+    /// Until every id has been through `reindex_records`, computed indexes
+    /// miss the records not yet recomputed, and
+    /// [`reindex_progress`](Self::reindex_progress) reports the rebuild as
+    /// unfinished.
+    pub(crate) fn begin_reindex(&self, def: &CollectionDef) -> Result<Vec<String>> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+        let registered: Vec<&CollectionDef> = self.collections.iter().map(|c| &**c).collect();
+
+        self.backend.transaction(|backend| {
+            backend.rebuild_indexes(def, &registered)?;
+            let scan = ScanOptions {
+                include_archived: true,
+                ..Default::default()
+            };
+            let raw = backend.scan_raw(&def.name, &scan)?;
+            let ids: Vec<String> = raw.records.into_iter().map(|r| r.id).collect();
+            set_reindex_progress(backend, &def.name, 0, ids.len())?;
+            Ok(ids)
+        })
+    }
+
+    /// Recompute the stored computed-index values of `ids` and record
+    /// `processed` of `total` ids as done, in one backend transaction, so
+    /// the stored progress never covers records that were not rewritten.
+    /// Records deleted since [`begin_reindex`](Self::begin_reindex) are
+    /// skipped. Returns the number of records rewritten.
+    pub(crate) fn reindex_records(
+        &self,
+        def: &CollectionDef,
+        ids: &[String],
+        processed: usize,
+        total: usize,
+    ) -> Result<usize> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        self.backend.transaction(|backend| {
+            let mut batch = Vec::with_capacity(ids.len());
+            for id in ids {
+                let Some(mut record) = backend.get_raw(&def.name, id)? else {
+                    continue;
+                };
+                if record.deleted {
+                    continue;
+                }
+                record.computed = compute_index_values(&record.data, &def.indexes);
+                batch.push(record);
+            }
+            if !batch.is_empty() {
+                backend.batch_put_raw(&batch)?;
+            }
+            set_reindex_progress(backend, &def.name, processed, total)?;
+            Ok(batch.len())
+        })
+    }
+
+    /// Progress of an unfinished batched reindex of `collection`, as
+    /// `(processed, total)` record counts, or `None` if no rebuild is in
+    /// flight. A rebuild interrupted part-way stays reported here until
+    /// the collection is reindexed again.
+    pub fn reindex_progress(&self, collection: &str) -> Result<Option<(usize, usize)>> {
+        let key = format!("{META_REINDEX_PREFIX}{collection}");
+        let Some(value) = self.backend.get_meta(&key)? else {
+            return Ok(None);
+        };
+        if value.is_empty() {
+            return Ok(None);
+        }
+        value
+            .split_once('/')
+            .and_then(|(processed, total)| Some((processed.parse().ok()?, total.parse().ok()?)))
+            .map(Some)
+            .ok_or_else(|| {
+                LessDbError::Internal(format!("Invalid reindex progress stored for {collection}"))
+            })
+    }
+
+    /// Remove tombstones for a collection. Returns the number purged (or
+    /// that would be purged, on a dry run).
+    pub fn purge_tombstones(
+        &self,
+        def: &CollectionDef,
+        opts: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+        self.backend.purge_tombstones_raw(&def.name, opts)
+    }
+
+    /// Hard-remove every record of `def` (live, archived and tombstoned,
+    /// with their edit history and index entries), leaving other collections
+    /// alone. Returns the number of records removed.
+    ///
+    /// Records are tombstoned and purged in one backend transaction, with
+    /// the sync state reset alongside, so an interrupted reset leaves the
+    /// collection as it was. Nothing is pushed: the server
+    /// copy is untouched. Push retry state is always dropped, since the
+    /// records it refers to are gone; `reset_sync_state` also clears the
+    /// pull cursor.
+    pub fn reset_collection(&self, def: &CollectionDef, opts: &ResetOptions) -> Result<usize> {
+        self.reset_collection_ids(def, opts).map(|ids| ids.len())
+    }
+
+    /// [`reset_collection`](Self::reset_collection), returning the ids
+    /// removed.
+    pub(crate) fn reset_collection_ids(
+        &self,
+        def: &CollectionDef,
+        opts: &ResetOptions,
+    ) -> Result<Vec<String>> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        self.backend.transaction(|backend| {
+            let scan = ScanOptions {
+                include_deleted: true,
+                include_archived: true,
+                ..Default::default()
+            };
+            let mut ids = Vec::new();
+            for record in backend.scan_raw(&def.name, &scan)?.records {
+                ids.push(record.id.clone());
+                // Not dirty, so nothing is left to push a delete for
+                backend.put_raw(&SerializedRecord {
+                    deleted: true,
+                    deleted_at: record.deleted_at.clone().or_else(|| Some(utc_now_z())),
+                    archived: false,
+                    dirty: false,
+                    pending_patches: Vec::new(),
+                    meta: None,
+                    computed: None,
+                    ..record
+                })?;
+            }
+            backend.set_meta(&format!("{META_PUSH_PREFIX}{}", def.name), "")?;
+            if opts.reset_sync_state {
+                backend.set_meta(&format!("{META_SEQ_PREFIX}{}", def.name), "0")?;
+            }
+            backend.purge_tombstones_raw(
+                &def.name,
+                &PurgeTombstonesOptions {
+                    older_than_seconds: None,
+                    dry_run: false,
+                    skip_dirty: false,
+                },
+            )?;
+            Ok(ids)
+        })
+    }
+
+    /// Move every record stored under `from` (live, archived and
+    /// tombstoned, with its edit history) to the registered collection
+    /// `to`, then hard-remove the originals. Returns the ids moved.
+    ///
+    /// Index values are recomputed for `to`'s indexes, and records keep
+    /// their version, so `to`'s migrations still apply on read. Dirty
+    /// records stay dirty and are pushed under the new name. `from`'s push
+    /// retry state is dropped; its pull cursor is left as it was, and `to`
+    /// pulls from its own. Fails if `to` is not registered or already holds
+    /// records.
+    pub fn rename_collection(&self, from: &str, to: &CollectionDef) -> Result<Vec<String>> {
+        self.check_initialized()?;
+        if self.collection_def_for(&to.name).is_none() {
+            return Err(StorageError::CollectionNotRegistered(to.name.clone()).into());
+        }
+        let _invalidate_from = self.invalidate_queries_after(from);
+        let _invalidate_to = self.invalidate_queries_after(&to.name);
+        let scan = ScanOptions {
+            include_deleted: true,
+            include_archived: true,
+            ..Default::default()
+        };
+
+        let ids = self.backend.transaction(|backend| {
+            if !backend.scan_raw(&to.name, &scan)?.records.is_empty() {
+                return Err(StorageError::CollectionNotEmpty(to.name.clone()).into());
+            }
+            let mut ids = Vec::new();
+            for record in backend.scan_raw(from, &scan)?.records {
+                ids.push(record.id.clone());
+                let computed = if record.deleted {
+                    None
+                } else {
+                    compute_index_values(&record.data, &to.indexes)
+                };
+                backend.put_raw(&SerializedRecord {
+                    collection: to.name.clone(),
+                    computed,
+                    ..record.clone()
+                })?;
+                // Not dirty, so the original is never pushed as a delete
+                backend.put_raw(&SerializedRecord {
+                    deleted: true,
+                    deleted_at: record.deleted_at.clone().or_else(|| Some(utc_now_z())),
+                    archived: false,
+                    dirty: false,
+                    pending_patches: Vec::new(),
+                    meta: None,
+                    computed: None,
+                    ..record
+                })?;
+            }
+            backend.set_meta(&format!("{META_PUSH_PREFIX}{from}"), "")?;
+            Ok(ids)
+        })?;
+
+        self.backend.purge_tombstones_raw(
+            from,
+            &PurgeTombstonesOptions {
+                older_than_seconds: None,
+                dry_run: false,
+                skip_dirty: false,
+            },
+        )?;
+        Ok(ids)
+    }
+
+    /// Hard-remove every record of `def` along with its push retry state and
+    /// pull cursor, as [`reset_collection`](Self::reset_collection) with
+    /// `reset_sync_state` does. Returns the ids removed.
+    ///
+    /// `def` stays registered; the collection is simply empty afterwards.
+    pub fn drop_collection(&self, def: &CollectionDef) -> Result<Vec<String>> {
+        self.reset_collection_ids(
+            def,
+            &ResetOptions {
+                reset_sync_state: true,
+            },
+        )
+    }
+
+    /// Shrink storage for `def`. Trims edit chains, hard-removes old
+    /// tombstones, then vacuums the backend.
+    ///
+    /// Dirty records are never touched, so nothing unpushed is lost. A
+    /// trimmed chain keeps its newest entries and their hash links, but its
+    /// first entry still points at a dropped one: check it with
+    /// `verify_edit_chain_tail`, not `verify_edit_chain`. Trimmed chains are
+    /// flagged `_editChainTrimmed` and never pushed, so other devices only
+    /// ever see chains that verify from the start. Trims and the tombstone
+    /// purge commit together in one transaction.
+    pub fn compact(&self, def: &CollectionDef, opts: &CompactOptions) -> Result<CompactReport> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        let mut report = self.backend.transaction(|backend| {
+            let mut report = CompactReport::default();
+
+            if let Some(keep_last) = opts.trim_history_keep_last {
+                let scan = ScanOptions {
+                    include_archived: true,
+                    ..Default::default()
+                };
+                let mut trimmed = Vec::new();
+                for record in backend.scan_raw(&def.name, &scan)?.records {
+                    if record.dirty {
+                        continue;
+                    }
+                    let Some((meta, dropped)) = trim_edit_chain(record.meta.as_ref(), keep_last)
+                    else {
+                        continue;
+                    };
+                    report.histories_trimmed += 1;
+                    report.history_entries_dropped += dropped;
+                    trimmed.push(SerializedRecord {
+                        meta: Some(meta),
+                        ..record
+                    });
+                }
+                if !trimmed.is_empty() {
+                    backend.batch_put_raw(&trimmed)?;
+                }
+            }
+
+            if let Some(age) = opts.drop_tombstones_older_than {
+                report.tombstones_removed = backend.purge_tombstones_raw(
+                    &def.name,
+                    &PurgeTombstonesOptions {
+                        older_than_seconds: Some(age.as_secs()),
+                        dry_run: false,
+                        skip_dirty: true,
+                    },
+                )?;
+            }
+            Ok(report)
+        })?;
+
+        if report.tombstones_removed > 0 || report.histories_trimmed > 0 {
+            report.bytes_reclaimed = self.backend.vacuum()?;
+        }
+        Ok(report)
+    }
+
+    // -----------------------------------------------------------------------
+    // Snapshots
+    // -----------------------------------------------------------------------
+
+    /// Take a read-only view of the store as it is now.
+    ///
+    /// Every read through the returned handle sees the same state, regardless
+    /// of writes made to this adapter afterwards. Nothing is copied up front;
+    /// instead, while the handle is open, each write keeps the version it
+    /// replaces. The handle is meant for short multi-query reads (rendering
+    /// one screen), not long-lived caching.
+    pub fn snapshot(&self) -> Result<SnapshotHandle<B>> {
+        self.check_initialized()?;
+        Ok(SnapshotHandle::new(Adapter {
+            backend: VersionedBackend::new(self.backend.snapshot()),
+            collections: self.collections.clone(),
+            initialized: true,
+            session_id: Mutex::new(*self.session_id.lock()),
+            query_cache: Mutex::new(QueryCache::default()),
+        }))
+    }
+}
+
+/// Store the progress of a batched reindex of `collection`, clearing it once
+/// every record has been processed.
+fn set_reindex_progress<B: StorageBackend>(
+    backend: &B,
+    collection: &str,
+    processed: usize,
+    total: usize,
+) -> Result<()> {
+    let key = format!("{META_REINDEX_PREFIX}{collection}");
+    if processed >= total {
+        backend.set_meta(&key, "")
+    } else {
+        backend.set_meta(&key, &format!("{processed}/{total}"))
+    }
+}
+
+/// Keep the newest `keep_last` entries of `meta._editChain` and flag the
+/// chain as trimmed. Returns the new meta and the number of entries dropped,
+/// or `None` if there was nothing to trim (no chain, unparseable, or already
+/// short enough).
+fn trim_edit_chain(meta: Option<&Value>, keep_last: usize) -> Option<(Value, usize)> {
+    let meta = meta?.as_object()?;
+    let chain: Vec<Value> = serde_json::from_str(meta.get(META_EDIT_CHAIN)?.as_str()?).ok()?;
+    if chain.len() <= keep_last {
+        return None;
+    }
+    let dropped = chain.len() - keep_last;
+    let tail = serde_json::to_string(&chain[dropped..]).ok()?;
+    let mut meta = meta.clone();
+    meta.insert(META_EDIT_CHAIN.to_string(), Value::String(tail));
+    meta.insert(META_EDIT_CHAIN_TRIMMED.to_string(), Value::Bool(true));
+    Some((Value::Object(meta), dropped))
+}
+
+/// Reject unique field indexes over fields the current schema lacks. The
+/// builder checks this, but definitions can be assembled or edited without it,
+/// and such an index would reject every record after the first as a
+/// duplicate of null.
+fn check_unique_index_fields(def: &CollectionDef) -> Result<()> {
+    for idx in &def.indexes {
+        let IndexDefinition::Field(fi) = idx else {
+            continue;
+        };
+        if !fi.unique {
+            continue;
+        }
+        if let Some(f) = fi
+            .fields
+            .iter()
+            .find(|f| !def.current_schema.contains_key(&f.field))
+        {
+            return Err(StorageError::UnknownIndexField {
+                collection: def.name.clone(),
+                index: fi.name.clone(),
+                field: f.field.clone(),
+            }
+            .into());
+        }
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -398,8 +1269,12 @@ impl<B: StorageBackend> StorageLifecycle for Adapter<B> {
     /// The backend's own table initialization (`SqliteBackend::initialize`)
     /// must be called by the caller before creating the `Adapter`.
     fn initialize(&mut self, collections: &[Arc<CollectionDef>]) -> Result<()> {
+        for def in collections {
+            check_unique_index_fields(def)?;
+        }
         self.collections = collections.to_vec();
         self.initialized = true;
+        self.query_cache.get_mut().clear();
 
         // Eagerly load/create session ID
         let _ = self.get_or_create_session_id()?;
@@ -435,22 +1310,32 @@ impl<B: StorageBackend> StorageRead for Adapter<B> {
             None => return Ok(None),
         };
 
-        // Filter tombstones unless caller wants them
+        // Filter tombstones and archives unless caller wants them
         if raw.deleted && !opts.include_deleted {
             return Ok(None);
         }
+        if raw.archived && !opts.include_archived {
+            return Ok(None);
+        }
 
         let result = self.process_record(raw, opts.migrate)?;
+        if !def.can_read(&opts.context, &result.data) {
+            return Ok(None);
+        }
         Ok(Some(result))
     }
 
     fn get_all(&self, def: &CollectionDef, opts: &ListOptions) -> Result<BatchResult> {
         self.check_initialized()?;
 
+        // With a read predicate the backend can't page for us: hidden
+        // records must not count toward the offset or limit.
+        let restricted = def.read_predicate.is_some();
         let scan_opts = ScanOptions {
             include_deleted: opts.include_deleted,
-            limit: opts.limit,
-            offset: opts.offset,
+            include_archived: opts.include_archived,
+            limit: if restricted { None } else { opts.limit },
+            offset: if restricted { None } else { opts.offset },
         };
 
         let raw_result = self.backend.scan_raw(&def.name, &scan_opts)?;
@@ -462,32 +1347,68 @@ impl<B: StorageBackend> StorageRead for Adapter<B> {
             let id = raw.id.clone();
             let collection = raw.collection.clone();
             match self.process_record(raw, true) {
+                Ok(record) if !def.can_read(&opts.context, &record.data) => {}
                 Ok(record) => records.push(record),
                 Err(e) => errors.push(RecordError {
                     id,
                     collection,
                     error: e.to_string(),
+                    ..Default::default()
                 }),
             }
         }
 
+        if restricted {
+            records = records
+                .into_iter()
+                .skip(opts.offset.unwrap_or(0))
+                .take(opts.limit.unwrap_or(usize::MAX))
+                .collect();
+        }
+
         Ok(BatchResult { records, errors })
     }
 
     fn query(&self, def: &CollectionDef, query: &Query) -> Result<QueryResult> {
         self.check_initialized()?;
 
-        let (records, _errors, total) = self.run_query(def, query)?;
+        let cached = {
+            let mut cache = self.query_cache.lock();
+            if cache.capacity() == 0 {
+                None
+            } else {
+                let key = query_cache_key(query);
+                match cache.get(&def.name, &key) {
+                    Some(hit) => return Ok(hit),
+                    None => Some((key, cache.version(&def.name))),
+                }
+            }
+        };
 
-        Ok(QueryResult {
+        let (records, _errors, total) = self.run_query(def, query)?;
+        let result = QueryResult {
             records,
             total: Some(total),
-        })
+        };
+
+        if let Some((key, version)) = cached {
+            self.query_cache
+                .lock()
+                .insert(&def.name, key, version, &result);
+        }
+        Ok(result)
     }
 
     fn count(&self, def: &CollectionDef, query: Option<&Query>) -> Result<usize> {
         self.check_initialized()?;
 
+        // Raw counts can't see the read predicate; count what `query` returns
+        if def.read_predicate.is_some() {
+            let query = query.cloned().unwrap_or_default();
+            let (_, _, total) = self.run_query(def, &query)?;
+            return Ok(total);
+        }
+
         let filter = query.and_then(|q| q.filter.as_ref());
 
         if filter.is_none() {
@@ -499,12 +1420,25 @@ impl<B: StorageBackend> StorageRead for Adapter<B> {
         let plan = plan_query(Some(filter), sort_entries.as_deref(), &def.indexes);
 
         if let Some(ref scan) = plan.scan {
-            if plan.post_filter.is_none() {
+            if plan.post_filter.is_none() && plan.union_scans.is_empty() {
                 // Index can satisfy the full count
                 if let Some(count) = self.backend.count_index_raw(&def.name, scan)? {
                     return Ok(count);
                 }
             }
+
+            // Filter only the index's candidates against the residual
+            if let Some(records) = self.scan_index_union(&def.name, scan, &plan.union_scans)? {
+                let data_records: Vec<Value> = records
+                    .into_iter()
+                    .filter(|r| !r.deleted && !r.archived)
+                    .map(|r| r.data)
+                    .collect();
+                return match plan.post_filter {
+                    Some(ref residual) => Ok(filter_records(&data_records, residual)?.len()),
+                    None => Ok(data_records.len()),
+                };
+            }
         }
 
         // Fall back: full scan + filter
@@ -515,7 +1449,7 @@ impl<B: StorageBackend> StorageRead for Adapter<B> {
 
         let data_records: Vec<Value> = raw_records
             .into_iter()
-            .filter(|r| !r.deleted)
+            .filter(|r| !r.deleted && !r.archived)
             .map(|r| r.data)
             .collect();
 
@@ -540,9 +1474,10 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         data: Value,
         opts: &PutOptions,
     ) -> Result<StoredRecordWithMeta> {
-        use crate::storage::record_manager::try_extract_id;
+        use crate::storage::record_manager::{key_field, try_extract_id};
 
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let session_id = if let Some(sid) = opts.session_id {
             sid
@@ -551,12 +1486,25 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         };
 
         // Upsert: if data contains an ID and that record exists, update instead
+        let mut data = data;
         let id = opts
             .id
             .clone()
             .or_else(|| try_extract_id(&def.current_schema, &data));
 
-        let existing = if let Some(ref id) = id {
+        // No caller-supplied id: mint one per the collection's strategy.
+        // Uuid4 is left to autofill, which fills every key field. A minted
+        // id is never one already stored, so there is nothing to update.
+        let existing = if id.is_none() && def.id_strategy != IdStrategy::Uuid4 {
+            let minted_id = self.mint_id(def, &data)?;
+            if let Some(obj) = data.as_object_mut() {
+                obj.insert(
+                    key_field(&def.current_schema).to_string(),
+                    Value::String(minted_id),
+                );
+            }
+            None
+        } else if let Some(ref id) = id {
             self.backend.get_raw(&def.name, id)?
         } else {
             None
@@ -574,6 +1522,8 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         }
 
         if let Some(ref existing) = existing {
+            Self::check_write_access(def, &opts.context, &existing.id, &existing.data)?;
+
             // Update existing record — merge auto-fields from existing data so
             // callers don't need to echo back id/createdAt in the new document.
             let merged_data = {
@@ -591,8 +1541,10 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                 skip_unique_check: opts.skip_unique_check,
                 meta: opts.meta.clone(),
                 should_reset_sync_state: opts.should_reset_sync_state.clone(),
+                context: opts.context.clone(),
             };
             let result = prepare_update(def, existing, merged_data, session_id, &patch_opts)?;
+            Self::check_write_access(def, &opts.context, &existing.id, &result.record.data)?;
 
             if result.has_changes {
                 if !opts.skip_unique_check {
@@ -617,6 +1569,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         } else {
             // Insert new record
             let result = prepare_new(def, data, session_id, opts)?;
+            Self::check_write_access(def, &opts.context, &result.record.id, &result.record.data)?;
 
             if !opts.skip_unique_check {
                 self.check_unique_constraints(
@@ -646,6 +1599,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &PatchOptions,
     ) -> Result<StoredRecordWithMeta> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let existing = self.backend.get_raw(&def.name, &opts.id)?.ok_or_else(|| {
             LessDbError::from(StorageError::NotFound {
@@ -668,7 +1622,9 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
             self.get_or_create_session_id()?
         };
 
+        Self::check_write_access(def, &opts.context, &opts.id, &existing.data)?;
         let result = prepare_patch(def, &existing, data, session_id, opts)?;
+        Self::check_write_access(def, &opts.context, &opts.id, &result.record.data)?;
 
         if result.has_changes {
             if !opts.skip_unique_check {
@@ -694,21 +1650,90 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
 
     fn delete(&self, def: &CollectionDef, id: &str, opts: &DeleteOptions) -> Result<bool> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let existing = match self.backend.get_raw(&def.name, id)? {
             Some(r) => r,
             None => return Ok(false),
         };
 
-        if existing.deleted {
+        // Archived records are already hidden; only `tombstone_archived` may
+        // turn them into tombstones.
+        if existing.deleted || existing.archived {
             return Ok(false);
         }
+        Self::check_write_access(def, &opts.context, id, &existing.data)?;
 
         let deleted_record = prepare_delete(&existing, opts);
         self.backend.put_raw(&deleted_record)?;
         Ok(true)
     }
 
+    fn tombstone_archived(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &DeleteOptions,
+    ) -> Result<bool> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        let existing = match self.backend.get_raw(&def.name, id)? {
+            Some(r) if r.archived && !r.deleted => r,
+            _ => return Ok(false),
+        };
+
+        let opts = DeleteOptions {
+            kind: DeleteKind::Tombstone,
+            ..opts.clone()
+        };
+        self.backend.put_raw(&prepare_delete(&existing, &opts))?;
+        Ok(true)
+    }
+
+    fn touch(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &TouchOptions,
+    ) -> Result<StoredRecordWithMeta> {
+        self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
+
+        let existing = self.backend.get_raw(&def.name, id)?.ok_or_else(|| {
+            LessDbError::from(StorageError::NotFound {
+                collection: def.name.clone(),
+                id: id.to_string(),
+            })
+        })?;
+
+        if existing.deleted {
+            return Err(StorageError::Deleted {
+                collection: def.name.clone(),
+                id: id.to_string(),
+            }
+            .into());
+        }
+
+        let session_id = if let Some(sid) = opts.session_id {
+            sid
+        } else {
+            self.get_or_create_session_id()?
+        };
+
+        let touched = prepare_touch(def, &existing, session_id, opts)?;
+        self.backend.put_raw(&touched)?;
+
+        let was_migrated = touched.version != existing.version;
+        let data = touched.data.clone();
+        Ok(Self::to_stored_record_with_meta(
+            touched,
+            data,
+            was_migrated,
+            was_migrated.then_some(existing.version),
+        ))
+    }
+
     fn bulk_put(
         &self,
         def: &CollectionDef,
@@ -716,18 +1741,26 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &PutOptions,
     ) -> Result<BatchResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         self.backend.transaction(|_| {
             let mut result_records = Vec::new();
             let mut errors = Vec::new();
 
-            for data in records {
+            for (input_index, data) in records.into_iter().enumerate() {
+                let id = data
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
                 match self.put(def, data, opts) {
                     Ok(record) => result_records.push(record),
                     Err(e) => errors.push(RecordError {
-                        id: String::new(),
+                        id,
                         collection: def.name.clone(),
                         error: e.to_string(),
+                        input_index: Some(input_index),
+                        unique: e.unique_conflict(),
                     }),
                 }
             }
@@ -746,6 +1779,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &DeleteOptions,
     ) -> Result<BulkDeleteResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         self.backend.transaction(|_| {
             let mut deleted_ids = Vec::new();
@@ -761,6 +1795,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                         id: id.to_string(),
                         collection: def.name.clone(),
                         error: e.to_string(),
+                        ..Default::default()
                     }),
                 }
             }
@@ -779,6 +1814,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &PatchOptions,
     ) -> Result<BulkPatchResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         self.backend.transaction(|_| {
             let mut records = Vec::new();
@@ -799,6 +1835,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                             id: String::new(),
                             collection: def.name.clone(),
                             error: "patch missing 'id' field".to_string(),
+                            ..Default::default()
                         });
                         continue;
                     }
@@ -810,6 +1847,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                     skip_unique_check: opts.skip_unique_check,
                     meta: opts.meta.clone(),
                     should_reset_sync_state: opts.should_reset_sync_state.clone(),
+                    context: opts.context.clone(),
                 };
 
                 match self.patch(def, patch_data, &patch_opts) {
@@ -818,6 +1856,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                         id,
                         collection: def.name.clone(),
                         error: e.to_string(),
+                        ..Default::default()
                     }),
                 }
             }
@@ -833,14 +1872,39 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &DeleteOptions,
     ) -> Result<BulkDeleteResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let query = Query {
             filter: Some(filter.clone()),
+            context: opts.context.clone(),
             ..Default::default()
         };
 
         // Query inside the transaction so the matched set and the writes are atomic.
         self.backend.transaction(|_| {
+            // Let the backend delete an index-planned match set in one step
+            // The backend can't evaluate the read or write predicate, so
+            // restricted collections go through per-record deletes
+            let plan = plan_query(Some(filter), None, &def.indexes);
+            if let Some(ref scan) = plan.scan {
+                if plan.union_scans.is_empty()
+                    && def.read_predicate.is_none()
+                    && def.write_predicate.is_none()
+                {
+                    if let Some(deleted_ids) = self.backend.delete_where_raw(
+                        &def.name,
+                        scan,
+                        plan.post_filter.as_ref(),
+                        opts,
+                    )? {
+                        return Ok(BulkDeleteResult {
+                            deleted_ids,
+                            errors: Vec::new(),
+                        });
+                    }
+                }
+            }
+
             let query_result = self.query(def, &query)?;
 
             let mut deleted_ids = Vec::new();
@@ -855,6 +1919,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                         id,
                         collection: def.name.clone(),
                         error: e.to_string(),
+                        ..Default::default()
                     }),
                 }
             }
@@ -874,9 +1939,11 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
         opts: &PatchOptions,
     ) -> Result<PatchManyResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let query = Query {
             filter: Some(filter.clone()),
+            context: opts.context.clone(),
             ..Default::default()
         };
 
@@ -897,6 +1964,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                     skip_unique_check: opts.skip_unique_check,
                     meta: opts.meta.clone(),
                     should_reset_sync_state: opts.should_reset_sync_state.clone(),
+                    context: opts.context.clone(),
                 };
 
                 match self.patch(def, patch.clone(), &patch_opts) {
@@ -905,6 +1973,7 @@ impl<B: StorageBackend> StorageWrite for Adapter<B> {
                         id,
                         collection: def.name.clone(),
                         error: e.to_string(),
+                        ..Default::default()
                     }),
                 }
             }
@@ -952,6 +2021,7 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
         snapshot: Option<&PushSnapshot>,
     ) -> Result<()> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         let existing = self.backend.get_raw(&def.name, id)?.ok_or_else(|| {
             LessDbError::from(StorageError::NotFound {
@@ -972,6 +2042,7 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
         opts: &ApplyRemoteOptions,
     ) -> Result<ApplyRemoteResult> {
         self.check_initialized()?;
+        let _invalidate = self.invalidate_queries_after(&def.name);
 
         // Wrap in a transaction so all record writes in this batch are atomic.
         // Note: set_last_sequence is updated separately by the caller after
@@ -980,10 +2051,14 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
         self.backend.transaction(|backend| {
             let strategy = Self::resolve_strategy(opts);
             let received_at = opts.received_at.as_deref();
+            let policy = opts
+                .writer_delete
+                .map(|writer_delete| SpaceDeletePolicy { writer_delete });
 
             let mut decisions = Vec::new();
             let mut new_sequence: i64 = 0;
             let mut merged_count: usize = 0;
+            let mut conflicts = Vec::new();
             // Track previous data for remote delete events
             let mut previous_data_map: std::collections::HashMap<String, Value> =
                 std::collections::HashMap::new();
@@ -996,8 +2071,18 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
 
                 let local = backend.get_raw(&def.name, &remote.id)?;
 
+                let mut decision = process_remote_record(
+                    def,
+                    local.as_ref(),
+                    remote,
+                    &strategy,
+                    received_at,
+                    policy.as_ref(),
+                    opts.author_permission,
+                )?;
+
                 // Capture previous data before applying tombstones
-                if remote.deleted {
+                if matches!(decision.1, Some(RemoteAction::Deleted)) {
                     if let Some(ref local_rec) = local {
                         if !local_rec.deleted {
                             previous_data_map.insert(remote.id.clone(), local_rec.data.clone());
@@ -1005,12 +2090,10 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
                     }
                 }
 
-                let decision =
-                    process_remote_record(def, local.as_ref(), remote, &strategy, received_at)?;
-
                 // Track merges (Case 10: dirty alive + remote live → CRDT merge)
-                if matches!(&decision.0, RemoteDecision::Merge(_)) {
+                if let RemoteDecision::Merge(_, ref mut merge_conflicts) = decision.0 {
                     merged_count += 1;
+                    conflicts.append(merge_conflicts);
                 }
 
                 decisions.push(decision);
@@ -1019,6 +2102,9 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
             let mut put_fn = |record: &SerializedRecord| backend.put_raw(record);
             let (mut applied, errors) = apply_remote_decisions(decisions, &mut put_fn);
 
+            // A merge that failed to persist didn't resolve anything
+            conflicts.retain(|c| !errors.iter().any(|e| e.id == c.id));
+
             // Populate previous_data for delete results
             for result in &mut applied {
                 if let Some(prev) = previous_data_map.remove(&result.id) {
@@ -1031,6 +2117,7 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
                 errors,
                 new_sequence,
                 merged_count,
+                conflicts,
             })
         })
     }
@@ -1049,4 +2136,39 @@ impl<B: StorageBackend> StorageSync for Adapter<B> {
         let key = format!("{META_SEQ_PREFIX}{collection}");
         self.backend.set_meta(&key, &sequence.to_string())
     }
+
+    fn get_push_state(&self, collection: &str) -> Result<Option<PushQueueState>> {
+        let key = format!("{META_PUSH_PREFIX}{collection}");
+        match self.backend.get_meta(&key)? {
+            Some(s) if !s.is_empty() => serde_json::from_str(&s).map(Some).map_err(|_| {
+                LessDbError::Internal(format!("Invalid push state stored for {collection}"))
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    fn set_push_state(&self, collection: &str, state: Option<&PushQueueState>) -> Result<()> {
+        let key = format!("{META_PUSH_PREFIX}{collection}");
+        let value = match state {
+            Some(state) => serde_json::to_string(state)
+                .map_err(|e| LessDbError::Internal(format!("Failed to encode push state: {e}")))?,
+            None => String::new(),
+        };
+        self.backend.set_meta(&key, &value)
+    }
+
+    fn purge_expired_tombstones(&self, def: &CollectionDef) -> Result<usize> {
+        let Some(retention) = def.tombstone_retention else {
+            return Ok(0);
+        };
+        self.purge_tombstones(
+            def,
+            &PurgeTombstonesOptions {
+                older_than_seconds: Some(retention.as_secs()),
+                dry_run: false,
+                // Unpushed tombstones must reach the server before they go
+                skip_dirty: true,
+            },
+        )
+    }
 }
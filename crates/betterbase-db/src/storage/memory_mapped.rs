@@ -12,7 +12,10 @@ use serde_json::Value;
 
 use crate::error::{Result, StorageError};
 use crate::index::types::{IndexDefinition, IndexScan};
-use crate::types::{PurgeTombstonesOptions, RawBatchResult, ScanOptions, SerializedRecord};
+use crate::query::aggregate::{AggregateRow, AggregateSpec};
+use crate::types::{
+    DeleteOptions, PurgeTombstonesOptions, RawBatchResult, ScanOptions, SerializedRecord,
+};
 
 use super::traits::StorageBackend;
 
@@ -39,7 +42,18 @@ pub enum PersistOp {
 // ============================================================================
 
 /// Transaction buffer type for records: collection → (id → record).
-type TxRecordBuffer = HashMap<String, HashMap<String, SerializedRecord>>;
+/// `None` marks a tombstone purged inside the transaction.
+type TxRecordBuffer = HashMap<String, HashMap<String, Option<SerializedRecord>>>;
+
+/// A tombstone purge made inside a transaction, replayed on commit.
+#[derive(Debug)]
+struct TxPurge {
+    collection: String,
+    options: PurgeTombstonesOptions,
+    /// The purged tombstones, written first so the inner backend holds
+    /// them as tombstones when it runs the purge
+    tombstones: Vec<SerializedRecord>,
+}
 
 /// In-memory storage wrapper that reads from HashMaps and batches writes.
 ///
@@ -54,7 +68,7 @@ type TxRecordBuffer = HashMap<String, HashMap<String, SerializedRecord>>;
 /// When multiple locks are needed, they must be acquired in this order to
 /// prevent deadlocks:
 ///
-/// 1. `tx_records` / `tx_meta` (transaction buffers)
+/// 1. `tx_records` / `tx_meta` / `tx_purges` (transaction buffers)
 /// 2. `records` / `meta` (main store)
 /// 3. `pending_ops` (persistence queue)
 ///
@@ -71,6 +85,8 @@ pub struct MemoryMapped<B: StorageBackend> {
     tx_records: Mutex<Option<TxRecordBuffer>>,
     /// Transaction buffer for metadata: key → value
     tx_meta: Mutex<Option<HashMap<String, String>>>,
+    /// Tombstone purges made in the open transaction, in order
+    tx_purges: Mutex<Vec<TxPurge>>,
 }
 
 impl<B: StorageBackend> MemoryMapped<B> {
@@ -84,6 +100,7 @@ impl<B: StorageBackend> MemoryMapped<B> {
             pending_ops: Mutex::new(Vec::new()),
             tx_records: Mutex::new(None),
             tx_meta: Mutex::new(None),
+            tx_purges: Mutex::new(Vec::new()),
         }
     }
 
@@ -212,7 +229,7 @@ impl<B: StorageBackend> MemoryMapped<B> {
         if let Some(ref tx_map) = *tx {
             if let Some(col_buf) = tx_map.get(collection) {
                 if let Some(record) = col_buf.get(id) {
-                    return Some(record.clone());
+                    return record.clone();
                 }
             }
         }
@@ -244,15 +261,13 @@ impl<B: StorageBackend> MemoryMapped<B> {
         }
 
         if let Some(tx_map) = tx_col {
-            for record in tx_map.values() {
-                results.push(record.clone());
-            }
+            results.extend(tx_map.values().flatten().cloned());
         }
 
         results
     }
 
-    /// Count non-deleted records in-place without cloning.
+    /// Count live (non-deleted, non-archived) records in-place without cloning.
     fn count_collection(&self, collection: &str) -> usize {
         let tx = self.tx_records.lock();
         let tx_col = tx.as_ref().and_then(|m| m.get(collection));
@@ -267,15 +282,15 @@ impl<B: StorageBackend> MemoryMapped<B> {
                         continue;
                     }
                 }
-                if !record.deleted {
+                if !record.deleted && !record.archived {
                     count += 1;
                 }
             }
         }
 
         if let Some(tx_map) = tx_col {
-            for record in tx_map.values() {
-                if !record.deleted {
+            for record in tx_map.values().flatten() {
+                if !record.deleted && !record.archived {
                     count += 1;
                 }
             }
@@ -327,16 +342,16 @@ impl<B: StorageBackend> MemoryMapped<B> {
                     continue;
                 }
                 if let Some(existing_id) = check_record(record) {
-                    return Err(self.unique_error(collection, &fi.name, &existing_id, new_values));
+                    return Err(self.unique_error(collection, fi, &existing_id, new_values));
                 }
             }
         }
 
         // Check tx buffer
         if let Some(tx_map) = tx_col {
-            for record in tx_map.values() {
+            for record in tx_map.values().flatten() {
                 if let Some(existing_id) = check_record(record) {
-                    return Err(self.unique_error(collection, &fi.name, &existing_id, new_values));
+                    return Err(self.unique_error(collection, fi, &existing_id, new_values));
                 }
             }
         }
@@ -388,7 +403,7 @@ impl<B: StorageBackend> MemoryMapped<B> {
                         collection: collection.to_string(),
                         index: ci.name.clone(),
                         existing_id,
-                        value: conflict_value,
+                        fields: [(ci.name.clone(), conflict_value)].into_iter().collect(),
                     }
                     .into());
                 }
@@ -396,14 +411,14 @@ impl<B: StorageBackend> MemoryMapped<B> {
         }
 
         if let Some(tx_map) = tx_col {
-            for record in tx_map.values() {
+            for record in tx_map.values().flatten() {
                 if let Some(existing_id) = check_record(record) {
                     let conflict_value = field_val.cloned().unwrap_or(Value::Null);
                     return Err(StorageError::UniqueConstraint {
                         collection: collection.to_string(),
                         index: ci.name.clone(),
                         existing_id,
-                        value: conflict_value,
+                        fields: [(ci.name.clone(), conflict_value)].into_iter().collect(),
                     }
                     .into());
                 }
@@ -413,29 +428,82 @@ impl<B: StorageBackend> MemoryMapped<B> {
         Ok(())
     }
 
+    /// Tombstones of `collection` that `options` selects for purging.
+    fn purgeable(
+        &self,
+        collection: &str,
+        options: &PurgeTombstonesOptions,
+    ) -> Vec<SerializedRecord> {
+        let now_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+
+        let mut to_purge = Vec::new();
+        for record in self.iter_collection(collection) {
+            if !record.deleted || (options.skip_dirty && record.dirty) {
+                continue;
+            }
+            if let Some(secs) = options.older_than_seconds {
+                if let Some(ref deleted_at) = record.deleted_at {
+                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(deleted_at) {
+                        let deleted_ms = dt.timestamp_millis();
+                        if now_ms - deleted_ms < (secs as i64) * 1000 {
+                            continue;
+                        }
+                    }
+                }
+            }
+            to_purge.push(record);
+        }
+        to_purge
+    }
+
+    /// Purge tombstones from the main store and queue the purge for the
+    /// inner backend. Must not run with a transaction open.
+    fn purge_committed(&self, collection: &str, options: &PurgeTombstonesOptions) -> usize {
+        let to_purge = self.purgeable(collection, options);
+
+        if !options.dry_run && !to_purge.is_empty() {
+            let mut records = self.records.lock();
+            if let Some(col_map) = records.get_mut(collection) {
+                for record in &to_purge {
+                    col_map.remove(&record.id);
+                }
+            }
+            drop(records);
+            // Forward the original options so the inner backend applies its own
+            // time-based filtering. This may purge slightly more records than memory
+            // did (if time passed since we checked), which is safe — memory already
+            // removed its subset, and the inner backend removes its own.
+            self.enqueue(PersistOp::PurgeTombstones {
+                collection: collection.to_string(),
+                options: options.clone(),
+            });
+        }
+
+        to_purge.len()
+    }
+
     /// Build a UniqueConstraint error for field indexes.
     fn unique_error(
         &self,
         collection: &str,
-        index_name: &str,
+        fi: &crate::index::types::FieldIndex,
         existing_id: &str,
         new_values: &[Option<&Value>],
     ) -> crate::error::LessDbError {
-        let conflict_value = if new_values.len() == 1 {
-            new_values[0].cloned().unwrap_or(Value::Null)
-        } else {
-            Value::Array(
-                new_values
-                    .iter()
-                    .map(|v| v.cloned().unwrap_or(Value::Null))
-                    .collect(),
-            )
-        };
+        let fields = fi
+            .fields
+            .iter()
+            .zip(new_values)
+            .map(|(f, v)| (f.field.clone(), v.cloned().unwrap_or(Value::Null)))
+            .collect();
         StorageError::UniqueConstraint {
             collection: collection.to_string(),
-            index: index_name.to_string(),
+            index: fi.name.clone(),
             existing_id: existing_id.to_string(),
-            value: conflict_value,
+            fields,
         }
         .into()
     }
@@ -456,7 +524,7 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
             tx_map
                 .entry(record.collection.clone())
                 .or_default()
-                .insert(record.id.clone(), record.clone());
+                .insert(record.id.clone(), Some(record.clone()));
         } else {
             drop(tx);
             self.put_in_memory(record.clone());
@@ -467,6 +535,7 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
 
     fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
         let include_deleted = options.include_deleted;
+        let include_archived = options.include_archived;
         let limit = options.limit;
         let offset = options.offset.unwrap_or(0);
 
@@ -481,6 +550,9 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
             if !include_deleted && record.deleted {
                 continue;
             }
+            if !include_archived && record.archived {
+                continue;
+            }
             if skipped < offset {
                 skipped += 1;
                 continue;
@@ -513,62 +585,48 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
         Ok(())
     }
 
+    /// Inside a transaction the purged tombstones are hidden from reads and
+    /// the purge itself runs on commit.
     fn purge_tombstones_raw(
         &self,
         collection: &str,
         options: &PurgeTombstonesOptions,
     ) -> Result<usize> {
-        // Purge writes directly to the main store (not tx-aware), so reject in-tx calls
-        if self.tx_records.lock().is_some() {
-            return Err(StorageError::Transaction {
-                message: "purge_tombstones_raw must not be called inside a transaction".to_string(),
-                source: None,
-            }
-            .into());
+        if self.tx_records.lock().is_none() {
+            return Ok(self.purge_committed(collection, options));
         }
 
-        let all = self.iter_collection(collection);
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_millis() as i64)
-            .unwrap_or(0);
-
-        let mut to_purge = Vec::new();
-        for record in &all {
-            if !record.deleted {
-                continue;
-            }
-            if let Some(secs) = options.older_than_seconds {
-                if let Some(ref deleted_at) = record.deleted_at {
-                    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(deleted_at) {
-                        let deleted_ms = dt.timestamp_millis();
-                        if now_ms - deleted_ms < (secs as i64) * 1000 {
-                            continue;
-                        }
-                    }
-                }
-            }
-            to_purge.push(record.id.clone());
-        }
-
-        if !options.dry_run && !to_purge.is_empty() {
-            let mut records = self.records.lock();
-            if let Some(col_map) = records.get_mut(collection) {
-                for id in &to_purge {
-                    col_map.remove(id);
+        let tombstones = self.purgeable(collection, options);
+        let purged = tombstones.len();
+        if !options.dry_run && !tombstones.is_empty() {
+            let mut tx = self.tx_records.lock();
+            if let Some(ref mut tx_map) = *tx {
+                let col_buf = tx_map.entry(collection.to_string()).or_default();
+                for record in &tombstones {
+                    col_buf.insert(record.id.clone(), None);
                 }
             }
-            // Forward the original options so the inner backend applies its own
-            // time-based filtering. This may purge slightly more records than memory
-            // did (if time passed since we checked), which is safe — memory already
-            // removed its subset, and the inner backend removes its own.
-            self.enqueue(PersistOp::PurgeTombstones {
+            self.tx_purges.lock().push(TxPurge {
                 collection: collection.to_string(),
                 options: options.clone(),
+                tombstones,
             });
         }
+        Ok(purged)
+    }
 
-        Ok(to_purge.len())
+    /// Flush pending ops so the inner backend sees the removals, then
+    /// vacuum it.
+    fn vacuum(&self) -> Result<Option<u64>> {
+        if self.tx_records.lock().is_some() {
+            return Err(StorageError::Transaction {
+                message: "vacuum must not be called inside a transaction".to_string(),
+                source: None,
+            }
+            .into());
+        }
+        self.flush()?;
+        self.inner.vacuum()
     }
 
     fn get_meta(&self, key: &str) -> Result<Option<String>> {
@@ -620,11 +678,23 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
                 // Commit: merge buffers into main store
                 let record_buf = self.tx_records.lock().take();
                 let meta_buf = self.tx_meta.lock().take();
-
-                if let Some(record_map) = record_buf {
+                let purges = std::mem::take(&mut *self.tx_purges.lock());
+
+                if let Some(mut record_map) = record_buf {
+                    // Purged tombstones land as tombstones; the purge replay
+                    // below removes them on both sides
+                    for purge in &purges {
+                        if let Some(col_buf) = record_map.get_mut(&purge.collection) {
+                            for record in &purge.tombstones {
+                                if let Some(entry @ None) = col_buf.get_mut(&record.id) {
+                                    *entry = Some(record.clone());
+                                }
+                            }
+                        }
+                    }
                     let mut records = self.records.lock();
                     for (_col, col_buf) in record_map {
-                        for (_id, record) in col_buf {
+                        for record in col_buf.into_values().flatten() {
                             records
                                 .entry(record.collection.clone())
                                 .or_default()
@@ -642,12 +712,17 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
                     }
                 }
 
+                for purge in purges {
+                    self.purge_committed(&purge.collection, &purge.options);
+                }
+
                 Ok(v)
             }
             Err(e) => {
                 // Rollback: discard buffers
                 *self.tx_records.lock() = None;
                 *self.tx_meta.lock() = None;
+                self.tx_purges.lock().clear();
                 Err(e)
             }
         }
@@ -666,6 +741,28 @@ impl<B: StorageBackend> StorageBackend for MemoryMapped<B> {
         Ok(None)
     }
 
+    fn delete_where_raw(
+        &self,
+        _collection: &str,
+        _scan: &IndexScan,
+        _residual: Option<&Value>,
+        _opts: &DeleteOptions,
+    ) -> Result<Option<Vec<String>>> {
+        // Return None — Adapter deletes record by record, which is fast in memory
+        Ok(None)
+    }
+
+    fn aggregate_raw(
+        &self,
+        _collection: &str,
+        _version: u32,
+        _scan: Option<&IndexScan>,
+        _spec: &AggregateSpec,
+    ) -> Result<Option<Vec<AggregateRow>>> {
+        // Return None — Adapter folds the records itself, which is fast in memory
+        Ok(None)
+    }
+
     fn check_unique(
         &self,
         collection: &str,
@@ -751,6 +848,7 @@ mod tests {
             dirty: false,
             deleted: false,
             deleted_at: None,
+            archived: false,
             meta: None,
             computed: None,
         }
@@ -1129,6 +1227,7 @@ mod tests {
                 &PurgeTombstonesOptions {
                     older_than_seconds: None,
                     dry_run: true,
+                    skip_dirty: false,
                 },
             )
             .unwrap();
@@ -1153,6 +1252,7 @@ mod tests {
                 &PurgeTombstonesOptions {
                     older_than_seconds: None,
                     dry_run: false,
+                    skip_dirty: false,
                 },
             )
             .unwrap();
@@ -1176,6 +1276,7 @@ mod tests {
                 &PurgeTombstonesOptions {
                     older_than_seconds: Some(3600), // 1 hour
                     dry_run: false,
+                    skip_dirty: false,
                 },
             )
             .unwrap();
@@ -1201,6 +1302,7 @@ mod tests {
             &PurgeTombstonesOptions {
                 older_than_seconds: None,
                 dry_run: false,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -1212,19 +1314,76 @@ mod tests {
     }
 
     #[test]
-    fn purge_tombstones_rejected_in_transaction() {
+    fn purge_tombstones_in_transaction_applies_on_commit() {
         let mm = setup();
+        let r1 = make_record("users", "u1", serde_json::json!({}));
+        let r2 = make_record("users", "u2", serde_json::json!({}));
+        mm.put_raw(&r1).unwrap();
+        mm.put_raw(&r2).unwrap();
+        mm.flush().unwrap();
+
+        let purge = PurgeTombstonesOptions {
+            older_than_seconds: None,
+            dry_run: false,
+            skip_dirty: false,
+        };
+        let purged = mm
+            .transaction(|backend| {
+                // Tombstoned and purged in the same transaction
+                let mut tombstone = r1.clone();
+                tombstone.deleted = true;
+                tombstone.deleted_at = Some("2020-01-01T00:00:00Z".to_string());
+                backend.put_raw(&tombstone)?;
+                let purged = backend.purge_tombstones_raw("users", &purge)?;
+                assert!(backend.get_raw("users", "u1")?.is_none());
+                assert_eq!(
+                    backend
+                        .scan_raw("users", &ScanOptions::default())?
+                        .records
+                        .len(),
+                    1
+                );
+                Ok(purged)
+            })
+            .unwrap();
+        assert_eq!(purged, 1);
+
+        assert!(mm.get_raw("users", "u1").unwrap().is_none());
+        assert!(mm.get_raw("users", "u2").unwrap().is_some());
+        mm.flush().unwrap();
+        assert!(mm.inner().get_raw("users", "u1").unwrap().is_none());
+        assert!(mm.inner().get_raw("users", "u2").unwrap().is_some());
+    }
+
+    #[test]
+    fn purge_tombstones_in_transaction_rolls_back() {
+        let mm = setup();
+        let mut r1 = make_record("users", "u1", serde_json::json!({}));
+        r1.deleted = true;
+        r1.deleted_at = Some("2020-01-01T00:00:00Z".to_string());
+        mm.put_raw(&r1).unwrap();
+        mm.flush().unwrap();
+
         let result: Result<()> = mm.transaction(|backend| {
             backend.purge_tombstones_raw(
                 "users",
                 &PurgeTombstonesOptions {
                     older_than_seconds: None,
                     dry_run: false,
+                    skip_dirty: false,
                 },
             )?;
-            Ok(())
+            Err(StorageError::Transaction {
+                message: "abort".to_string(),
+                source: None,
+            }
+            .into())
         });
         assert!(result.is_err());
+
+        assert!(mm.get_raw("users", "u1").unwrap().is_some());
+        assert!(!mm.has_pending_changes());
+        assert!(mm.inner().get_raw("users", "u1").unwrap().is_some());
     }
 
     // ---- Unique constraint ----
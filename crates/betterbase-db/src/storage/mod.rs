@@ -1,7 +1,9 @@
 pub mod adapter;
 pub mod memory_mapped;
+pub(crate) mod query_cache;
 pub mod record_manager;
 pub mod remote_changes;
+pub mod snapshot;
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 pub mod traits;
@@ -0,0 +1,147 @@
+//! Bounded LRU cache of query results for `Adapter`.
+//!
+//! Entries are keyed by collection and a normalized form of the query, and
+//! stamped with the collection's write version. Every adapter write bumps
+//! that version and drops the collection's entries, so a hit always reflects
+//! the latest write made through the adapter. Writes that bypass it (raw
+//! SQL, another adapter on the same database) are not seen.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::{
+    query::types::{normalize_sort, Query},
+    types::QueryResult,
+};
+
+struct CachedQuery {
+    version: u64,
+    result: QueryResult,
+    last_used: u64,
+}
+
+/// LRU query result cache. A capacity of 0 disables it.
+#[derive(Default)]
+pub(crate) struct QueryCache {
+    capacity: usize,
+    /// Write version per collection; a missing entry is version 0.
+    versions: HashMap<String, u64>,
+    entries: HashMap<(String, String), CachedQuery>,
+    /// Monotonic use counter for LRU ordering.
+    clock: u64,
+}
+
+impl QueryCache {
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the bound, evicting least recently used entries to fit.
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict_lru();
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Current write version of `collection`.
+    pub(crate) fn version(&self, collection: &str) -> u64 {
+        self.versions.get(collection).copied().unwrap_or(0)
+    }
+
+    /// Record a write to `collection`: bump its version and drop its entries.
+    pub(crate) fn invalidate(&mut self, collection: &str) {
+        *self.versions.entry(collection.to_string()).or_insert(0) += 1;
+        self.entries.retain(|(c, _), _| c != collection);
+    }
+
+    pub(crate) fn get(&mut self, collection: &str, key: &str) -> Option<QueryResult> {
+        let version = self.version(collection);
+        self.clock += 1;
+        let entry = self
+            .entries
+            .get_mut(&(collection.to_string(), key.to_string()))?;
+        if entry.version != version {
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(entry.result.clone())
+    }
+
+    /// Store `result`, computed against `version` of `collection`.
+    ///
+    /// Skipped if the collection was written since, so a query that raced a
+    /// write never caches what it read.
+    pub(crate) fn insert(
+        &mut self,
+        collection: &str,
+        key: String,
+        version: u64,
+        result: &QueryResult,
+    ) {
+        if self.capacity == 0 || version != self.version(collection) {
+            return;
+        }
+        self.clock += 1;
+        let cached = CachedQuery {
+            version,
+            result: result.clone(),
+            last_used: self.clock,
+        };
+        if self
+            .entries
+            .insert((collection.to_string(), key), cached)
+            .is_none()
+            && self.entries.len() > self.capacity
+        {
+            self.evict_lru();
+        }
+    }
+
+    fn evict_lru(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, e)| e.last_used)
+            .map(|(k, _)| k.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+/// Cache key for `query`: filter object keys sorted, sort input expanded to
+/// entries. Array order is kept, since it can matter (`$in` does not care,
+/// but sort entries do). The actor is included because read predicates make
+/// results actor-specific.
+pub(crate) fn query_cache_key(query: &Query) -> String {
+    serde_json::json!({
+        "filter": query.filter.as_ref().map(sort_keys),
+        "sort": normalize_sort(query.sort.clone()),
+        "limit": query.limit,
+        "offset": query.offset,
+        "actor": query.context.actor_did,
+    })
+    .to_string()
+}
+
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            let sorted: Map<String, Value> = keys
+                .into_iter()
+                .map(|k| (k.clone(), sort_keys(&obj[k])))
+                .collect();
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
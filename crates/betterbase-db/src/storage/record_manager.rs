@@ -17,6 +17,7 @@ use crate::{
         self,
         patch_log::{append_patch, deserialize_patches, serialize_patches, EMPTY_PATCH_LOG},
         schema_aware::{create_model_with_schema, deserialize_from_crdt, diff_model_with_schema},
+        ConcurrentWrite,
     },
     error::{LessDbError, MigrationError, Result, StorageError},
     index::types::{IndexDefinition, IndexableValue},
@@ -25,8 +26,9 @@ use crate::{
         validate::validate,
     },
     types::{
-        DeleteConflictStrategy, DeleteOptions, DeleteResolution, PatchOptions, PushSnapshot,
-        PutOptions, RemoteRecord, SerializedRecord,
+        ConflictWinner, DeleteConflictStrategy, DeleteKind, DeleteOptions, DeleteResolution,
+        MergeConflict, PatchOptions, PushSnapshot, PutOptions, RemoteRecord, SerializedRecord,
+        TouchOptions,
     },
 };
 
@@ -66,6 +68,8 @@ pub struct MigrateAndDeserializeResult {
 pub struct MergeRecordsResult {
     pub record: SerializedRecord,
     pub had_local_changes: bool,
+    /// Fields both sides wrote concurrently, with the write the merge kept
+    pub conflicts: Vec<MergeConflict>,
 }
 
 // ============================================================================
@@ -74,7 +78,7 @@ pub struct MergeRecordsResult {
 
 /// Generate the current UTC time as a Z-format ISO 8601 string.
 /// The format matches the schema validator's regex: .
-fn utc_now_z() -> String {
+pub(crate) fn utc_now_z() -> String {
     chrono::Utc::now()
         .format("%Y-%m-%dT%H:%M:%S%.6fZ")
         .to_string()
@@ -84,6 +88,15 @@ fn utc_now_z() -> String {
 // ID Extraction
 // ============================================================================
 
+/// Name of the top-level Key field, falling back to `"id"`.
+pub fn key_field(schema: &std::collections::BTreeMap<String, SchemaNode>) -> &str {
+    schema
+        .iter()
+        .find(|(_, node)| matches!(node, SchemaNode::Key))
+        .map(|(field, _)| field.as_str())
+        .unwrap_or("id")
+}
+
 /// Find the Key field's value in data. Returns None if not found or empty.
 pub fn try_extract_id(
     schema: &std::collections::BTreeMap<String, SchemaNode>,
@@ -174,6 +187,7 @@ pub fn prepare_new(
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: opts.meta.clone(),
         computed,
     };
@@ -359,6 +373,7 @@ pub fn prepare_update(
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: existing.archived,
         meta: merged_meta,
         computed,
     };
@@ -412,25 +427,114 @@ pub fn prepare_patch(
     prepare_update(def, existing, Value::Object(merged), session_id, opts)
 }
 
+// ============================================================================
+// Touch Preparation
+// ============================================================================
+
+/// Meta key holding the record's touch revision (see [`prepare_touch`]).
+pub const META_REVISION: &str = "_revision";
+
+/// Meta key holding the record's serialized edit chain (a JSON array string).
+pub const META_EDIT_CHAIN: &str = "_editChain";
+
+/// Meta flag set when compaction dropped the head of the edit chain. A
+/// trimmed chain no longer verifies from its first entry, so it stays on this
+/// device: pushes leave it out and the transport starts a fresh chain.
+pub const META_EDIT_CHAIN_TRIMMED: &str = "_editChainTrimmed";
+
+/// Prepare a touch: mark an existing record changed without altering user fields.
+///
+/// Re-stamps the record at the current schema version (migrating it if stale),
+/// bumps `updatedAt` through the CRDT, increments the `_revision` counter in
+/// its meta, and marks it dirty so the next push re-sends it. The revision
+/// changes even when the schema has no `updatedAt` field, so every touch is
+/// observable. All other fields, and the sync sequence, are left as they are.
+pub fn prepare_touch(
+    def: &CollectionDef,
+    existing: &SerializedRecord,
+    session_id: u64,
+    opts: &TouchOptions,
+) -> Result<SerializedRecord> {
+    debug_assert!(
+        !existing.deleted,
+        "prepare_touch called on a tombstone record"
+    );
+
+    let migrated = migrate_and_deserialize(def, existing)?;
+
+    let autofill_opts = AutofillOptions {
+        now: Some(utc_now_z()),
+        is_new: false,
+        generate_key: None,
+    };
+    let touched = autofill_for_update(&def.current_schema, &migrated.data, &autofill_opts);
+
+    let mut model = crdt::model_load(&migrated.crdt, session_id)?;
+    let (crdt_binary, pending_patches) =
+        match diff_model_with_schema(&model, &touched, &def.current_schema) {
+            Some(p) => {
+                crdt::apply_patch(&mut model, &p);
+                (
+                    crdt::model_to_binary(&model),
+                    append_patch(&existing.pending_patches, &p),
+                )
+            }
+            None => (migrated.crdt, existing.pending_patches.clone()),
+        };
+
+    Ok(SerializedRecord {
+        version: def.current_version,
+        computed: compute_index_values(&touched, &def.indexes),
+        data: touched,
+        crdt: crdt_binary,
+        pending_patches,
+        dirty: true,
+        meta: Some(bump_revision(merge_meta(&existing.meta, &opts.meta))),
+        ..existing.clone()
+    })
+}
+
+/// Increment the `_revision` counter in `meta`, starting from 0 if unset.
+fn bump_revision(meta: Option<Value>) -> Value {
+    let mut obj = meta
+        .and_then(|m| m.as_object().cloned())
+        .unwrap_or_default();
+    let revision = obj.get(META_REVISION).and_then(Value::as_u64).unwrap_or(0);
+    obj.insert(META_REVISION.to_string(), Value::from(revision + 1));
+    Value::Object(obj)
+}
+
 // ============================================================================
 // Delete Preparation
 // ============================================================================
 
-/// Prepare a soft-delete tombstone from an existing record.
+/// Prepare a soft-delete tombstone (or archive) from an existing record.
 ///
 /// Marks the record as deleted and dirty. CRDT state is retained for resurrection.
 /// If `opts.meta` is provided, it is shallow-merged onto the existing meta.
+///
+/// With `DeleteKind::Archive` the record is only flagged `archived`: it stays
+/// live for sync purposes so other devices receive an archive, not a tombstone.
+/// Tombstoning an archived record clears the flag.
 pub fn prepare_delete(existing: &SerializedRecord, opts: &DeleteOptions) -> SerializedRecord {
-    let now = utc_now_z();
     let merged_meta = merge_meta(&existing.meta, &opts.meta);
 
-    SerializedRecord {
-        deleted: true,
-        deleted_at: Some(now),
-        dirty: true,
-        meta: merged_meta,
-        // Keep existing CRDT state
-        ..existing.clone()
+    match opts.kind {
+        DeleteKind::Tombstone => SerializedRecord {
+            deleted: true,
+            deleted_at: Some(utc_now_z()),
+            archived: false,
+            dirty: true,
+            meta: merged_meta,
+            // Keep existing CRDT state
+            ..existing.clone()
+        },
+        DeleteKind::Archive => SerializedRecord {
+            archived: true,
+            dirty: true,
+            meta: merged_meta,
+            ..existing.clone()
+        },
     }
 }
 
@@ -450,7 +554,8 @@ pub fn prepare_mark_synced(
     let stay_dirty = if let Some(snap) = snapshot {
         let patches_grew = record.pending_patches.len() > snap.pending_patches_length;
         let deleted_changed = record.deleted != snap.deleted;
-        patches_grew || deleted_changed
+        let archived_changed = record.archived != snap.archived;
+        patches_grew || deleted_changed || archived_changed
     } else {
         false
     };
@@ -655,9 +760,11 @@ pub fn merge_records(
         );
     }
 
+    let local_model = crdt::model_from_binary(&local.crdt)?;
     let mut remote_model = crdt::model_from_binary(remote_crdt)?;
 
-    // Snapshot remote view before applying local patches
+    // Snapshot remote state before applying local patches
+    let remote_only_model = remote_model.clone();
     let remote_only_view =
         deserialize_from_crdt(&def.current_schema, &crdt::view_model(&remote_model));
 
@@ -679,6 +786,13 @@ pub fn merge_records(
     // Check if local still has changes beyond what the remote already contains
     let had_local_changes = !local_patches.is_empty() && raw_merged_view != remote_only_view;
 
+    let conflicts = merge_conflicts(
+        def,
+        local,
+        &remote_only_view,
+        crdt::concurrent_writes(&local_model, &remote_only_model, &remote_model),
+    );
+
     let record = SerializedRecord {
         id: local.id.clone(),
         collection: def.name.clone(),
@@ -694,6 +808,7 @@ pub fn merge_records(
         dirty: had_local_changes,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: local.meta.clone(),
         computed,
     };
@@ -701,9 +816,53 @@ pub fn merge_records(
     Ok(MergeRecordsResult {
         record,
         had_local_changes,
+        conflicts,
     })
 }
 
+/// Describe a merge's concurrent writes as field conflicts.
+///
+/// Skips `updatedAt`, which every edit on either side touches, and writes
+/// where both sides chose the same value.
+fn merge_conflicts(
+    def: &CollectionDef,
+    local: &SerializedRecord,
+    remote_view: &Value,
+    writes: Vec<ConcurrentWrite>,
+) -> Vec<MergeConflict> {
+    writes
+        .into_iter()
+        .filter_map(|write| {
+            if let Some(SchemaNode::UpdatedAt) = def.current_schema.get(&write.path[0]) {
+                return None;
+            }
+            let local_value = value_at(&local.data, &write.path);
+            let remote_value = value_at(remote_view, &write.path);
+            if local_value == remote_value {
+                return None;
+            }
+            Some(MergeConflict {
+                id: local.id.clone(),
+                path: write.path.join("."),
+                local_value,
+                remote_value,
+                winner: if write.local_won {
+                    ConflictWinner::Local
+                } else {
+                    ConflictWinner::Remote
+                },
+            })
+        })
+        .collect()
+}
+
+fn value_at(data: &Value, path: &[String]) -> Value {
+    path.iter()
+        .try_fold(data, |value, key| value.get(key))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
 /// Cross-version merge: migrate remote data, then reapply local edits via diff.
 ///
 /// **Assumption:** `local` is already at the current schema version. This is
@@ -712,6 +871,9 @@ pub fn merge_records(
 /// migrated record. If this function were called with a non-migrated local
 /// record, the diff in Step 3 would compare mismatched schema versions and
 /// produce incorrect results.
+///
+/// Local edits are re-derived as a diff against the migrated remote, so they
+/// always win and no conflicts are reported.
 fn merge_with_migrated_remote(
     def: &CollectionDef,
     local: &SerializedRecord,
@@ -795,6 +957,7 @@ fn merge_with_migrated_remote(
         dirty: had_local_changes,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: local.meta.clone(),
         computed,
     };
@@ -802,6 +965,7 @@ fn merge_with_migrated_remote(
     Ok(MergeRecordsResult {
         record,
         had_local_changes,
+        conflicts: Vec::new(),
     })
 }
 
@@ -899,6 +1063,7 @@ pub fn prepare_remote_insert(
         dirty: false,
         deleted: false,
         deleted_at: None,
+        archived: remote.archived,
         meta: remote.meta.clone(),
         computed,
     };
@@ -930,6 +1095,7 @@ pub fn prepare_remote_tombstone(
         dirty: false,
         deleted: true,
         deleted_at: Some(deleted_at),
+        archived: false,
         meta,
         computed: None,
     }
@@ -971,6 +1137,7 @@ pub fn resolve_delete_conflict(
                 dirty: local.dirty,
                 deleted: local.deleted,
                 deleted_at: local.deleted_at.clone(),
+                archived: local.archived,
                 meta: local.meta.clone(),
             };
             f(&stored, remote)
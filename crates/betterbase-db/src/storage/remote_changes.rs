@@ -2,13 +2,21 @@
 //!
 //! Implements the 10-case conflict matrix for applying remote records
 //! against local state, with CRDT merge for dirty live conflicts.
+//!
+//! Archived records are live for the purposes of the matrix: a remote
+//! archive carries CRDT state and lands with `archived` set. Under a space
+//! delete policy, a tombstone whose author is not known to be an admin lands
+//! as an archive of the local record instead.
+
+use betterbase_crypto::UCANPermission;
+use betterbase_sync_core::SpaceDeletePolicy;
 
 use crate::{
     collection::builder::CollectionDef,
     error::{LessDbError, Result},
     types::{
-        ApplyRemoteRecordResult, DeleteConflictStrategy, RecordError, RemoteAction, RemoteRecord,
-        SerializedRecord,
+        ApplyRemoteRecordResult, DeleteConflictStrategy, DeleteKind, MergeConflict, RecordError,
+        RemoteAction, RemoteRecord, SerializedRecord,
     },
 };
 
@@ -28,8 +36,9 @@ pub enum RemoteDecision {
     Update(SerializedRecord),
     /// Create a tombstone (remote deleted, local alive + clean).
     Delete(SerializedRecord),
-    /// Merge local dirty changes into remote CRDT state.
-    Merge(SerializedRecord),
+    /// Merge local dirty changes into remote CRDT state, with the fields both
+    /// sides wrote concurrently.
+    Merge(SerializedRecord, Vec<MergeConflict>),
     /// Nothing to do.
     Skip,
     /// Use local as-is (conflict resolution kept local).
@@ -44,12 +53,19 @@ pub enum RemoteDecision {
 ///
 /// Returns `(decision, action)` where `action` describes what happened for
 /// caller reporting. The decision indicates what record to persist (if any).
+///
+/// With a `policy`, a remote tombstone archives a live local record instead
+/// of deleting it unless `author_permission` (the caller's verified bound on
+/// who wrote it) is one the policy lets tombstone. An unknown permission
+/// archives.
 pub fn process_remote_record(
     def: &CollectionDef,
     local: Option<&SerializedRecord>,
     remote: &RemoteRecord,
     strategy: &DeleteConflictStrategy,
     received_at: Option<&str>,
+    policy: Option<&SpaceDeletePolicy>,
+    author_permission: Option<UCANPermission>,
 ) -> Result<(RemoteDecision, Option<RemoteAction>)> {
     // Skip stale remote records for dirty locals. With pull-first sync, the
     // pull cursor lags the push cursor — a pull can return records we already
@@ -61,6 +77,20 @@ pub fn process_remote_record(
         }
     }
 
+    if let Some(local_rec) = local.filter(|r| !r.deleted) {
+        if remote.deleted && !may_tombstone(policy, author_permission) {
+            let archived = SerializedRecord {
+                archived: true,
+                sequence: remote.sequence,
+                ..local_rec.clone()
+            };
+            return Ok((
+                RemoteDecision::Update(archived),
+                Some(RemoteAction::Updated),
+            ));
+        }
+    }
+
     // Exhaustive match on (local state, remote deleted) ensures all cases are
     // covered at compile time. Local state is encoded as:
     //   None           → no local record
@@ -98,12 +128,14 @@ pub fn process_remote_record(
             ))
         }
         (Some((false, false)), false) => {
-            // Case 4: Clean alive + remote live → overwrite
+            // Case 4: Clean alive + remote live → overwrite, keeping a local
+            // archive the remote predates
             let result = prepare_remote_insert(def, remote, received_at)?;
-            Ok((
-                RemoteDecision::Update(result.record),
-                Some(RemoteAction::Updated),
-            ))
+            let record = SerializedRecord {
+                archived: local.is_some_and(|r| r.archived) || remote.archived,
+                ..result.record
+            };
+            Ok((RemoteDecision::Update(record), Some(RemoteAction::Updated)))
         }
         (Some((false, true)), false) => {
             // Case 5: Clean deleted + remote live → resurrect
@@ -192,8 +224,13 @@ pub fn process_remote_record(
                 received_at,
             )?;
 
+            // A pending local archive survives the merge, as does a remote one.
+            let record = SerializedRecord {
+                archived: local.archived || remote.archived,
+                ..merge_result.record
+            };
             Ok((
-                RemoteDecision::Merge(merge_result.record),
+                RemoteDecision::Merge(record, merge_result.conflicts),
                 Some(RemoteAction::Updated),
             ))
         }
@@ -204,6 +241,17 @@ pub fn process_remote_record(
 // Helpers
 // ============================================================================
 
+/// Whether a remote tombstone by an author holding `permission` may
+/// hard-delete under `policy`.
+fn may_tombstone(policy: Option<&SpaceDeletePolicy>, permission: Option<UCANPermission>) -> bool {
+    match policy {
+        Some(policy) => {
+            permission.and_then(|p| policy.delete_kind_for(p)) == Some(DeleteKind::Tombstone)
+        }
+        None => true,
+    }
+}
+
 /// Build a tombstone from a remote record.
 fn make_tombstone(
     def: &CollectionDef,
@@ -242,7 +290,7 @@ pub fn apply_remote_decisions(
             RemoteDecision::Insert(record)
             | RemoteDecision::Update(record)
             | RemoteDecision::Delete(record)
-            | RemoteDecision::Merge(record)
+            | RemoteDecision::Merge(record, _)
             | RemoteDecision::Conflict(record) => {
                 let id = record.id.clone();
                 let collection = record.collection.clone();
@@ -262,6 +310,7 @@ pub fn apply_remote_decisions(
                             id,
                             collection,
                             error: e.to_string(),
+                            ..Default::default()
                         });
                     }
                 }
@@ -0,0 +1,536 @@
+//! Read-only, point-in-time views of an adapter's data.
+//!
+//! Snapshots are copy-on-write. The adapter keeps its backend behind a
+//! [`VersionedBackend`], which, while any snapshot is open, saves the
+//! version of each record and metadata key a write is about to replace.
+//! A snapshot reads the live backend and puts those saved versions back, so
+//! taking one copies nothing and it only holds on to what changed since.
+//! Writes to the live adapter after the snapshot is taken are never visible
+//! through it, so a UI can run several queries against one handle and render
+//! a coherent frame.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+use serde_json::Value;
+
+use crate::collection::builder::CollectionDef;
+use crate::error::{Result, StorageError};
+use crate::index::types::{IndexDefinition, IndexScan};
+use crate::query::aggregate::{AggregateRow, AggregateSpec};
+use crate::query::types::Query;
+use crate::types::{
+    BatchResult, DeleteOptions, GetOptions, ListOptions, PurgeTombstonesOptions, QueryResult,
+    RawBatchResult, ScanOptions, SerializedRecord, StoredRecordWithMeta,
+};
+
+use super::adapter::Adapter;
+use super::traits::{QueryPlan, StorageBackend, StorageRead};
+
+// ============================================================================
+// Overlay
+// ============================================================================
+
+/// What one snapshot saw of every record and metadata key written since it
+/// was taken. `None` means the entry did not exist yet.
+#[derive(Default)]
+struct Overlay {
+    /// collection name → (record id → record as of the snapshot)
+    records: HashMap<String, HashMap<String, Option<SerializedRecord>>>,
+    meta: HashMap<String, Option<String>>,
+}
+
+impl Overlay {
+    fn has_record(&self, collection: &str, id: &str) -> bool {
+        self.records
+            .get(collection)
+            .is_some_and(|col| col.contains_key(id))
+    }
+
+    /// Put the saved versions of `collection` back into `live`, a full scan
+    /// of it, and return the result sorted by id.
+    fn restore(&self, collection: &str, live: Vec<SerializedRecord>) -> Vec<SerializedRecord> {
+        let Some(saved) = self.records.get(collection).filter(|col| !col.is_empty()) else {
+            return live;
+        };
+        let mut by_id: BTreeMap<String, SerializedRecord> =
+            live.into_iter().map(|r| (r.id.clone(), r)).collect();
+        for (id, record) in saved {
+            match record {
+                Some(record) => by_id.insert(id.clone(), record.clone()),
+                None => by_id.remove(id),
+            };
+        }
+        by_id.into_values().collect()
+    }
+}
+
+// ============================================================================
+// VersionedBackend
+// ============================================================================
+
+/// Live side of snapshots: a pass-through wrapper that saves what each write
+/// replaces into every open snapshot's overlay before writing.
+///
+/// Saving and writing happen under one lock that taking a snapshot also
+/// takes, so a write lands either wholly before a snapshot or is undone in
+/// it. With no snapshot open, writes cost one uncontended lock.
+pub(crate) struct VersionedBackend<B: StorageBackend> {
+    inner: Arc<B>,
+    snapshots: Mutex<Vec<Weak<Mutex<Overlay>>>>,
+}
+
+impl<B: StorageBackend> VersionedBackend<B> {
+    pub(crate) fn new(inner: B) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            snapshots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The wrapped backend. Writes made directly through it bypass open
+    /// snapshots.
+    pub(crate) fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Open a snapshot of the backend as it is now.
+    pub(crate) fn snapshot(&self) -> SnapshotBackend<B> {
+        let overlay = Arc::new(Mutex::new(Overlay::default()));
+        self.snapshots.lock().push(Arc::downgrade(&overlay));
+        SnapshotBackend {
+            live: Arc::clone(&self.inner),
+            overlay,
+        }
+    }
+
+    /// Run `write` against the inner backend, first letting `save` record
+    /// the current versions of what it will replace into the open overlays.
+    /// `save` is skipped when no snapshot is open.
+    fn write<T>(
+        &self,
+        save: impl FnOnce(&B, &[Arc<Mutex<Overlay>>]) -> Result<()>,
+        write: impl FnOnce(&B) -> Result<T>,
+    ) -> Result<T> {
+        let mut snapshots = self.snapshots.lock();
+        snapshots.retain(|s| s.strong_count() > 0);
+        let open: Vec<Arc<Mutex<Overlay>>> = snapshots.iter().filter_map(Weak::upgrade).collect();
+        if !open.is_empty() {
+            save(&self.inner, &open)?;
+        }
+        write(&self.inner)
+    }
+
+    fn has_open_snapshots(&self) -> bool {
+        self.snapshots.lock().iter().any(|s| s.strong_count() > 0)
+    }
+}
+
+/// Save the current version of each record in `keys` into every overlay
+/// that has no version of it yet.
+fn save_records<B: StorageBackend>(
+    backend: &B,
+    overlays: &[Arc<Mutex<Overlay>>],
+    keys: &[(&str, &str)],
+) -> Result<()> {
+    for &(collection, id) in keys {
+        let mut current = None;
+        for overlay in overlays {
+            let mut overlay = overlay.lock();
+            if overlay.has_record(collection, id) {
+                continue;
+            }
+            if current.is_none() {
+                current = Some(backend.get_raw(collection, id)?);
+            }
+            overlay
+                .records
+                .entry(collection.to_string())
+                .or_default()
+                .insert(id.to_string(), current.clone().flatten());
+        }
+    }
+    Ok(())
+}
+
+impl<B: StorageBackend> StorageBackend for VersionedBackend<B> {
+    fn get_raw(&self, collection: &str, id: &str) -> Result<Option<SerializedRecord>> {
+        self.inner.get_raw(collection, id)
+    }
+
+    fn put_raw(&self, record: &SerializedRecord) -> Result<()> {
+        self.write(
+            |backend, overlays| {
+                save_records(
+                    backend,
+                    overlays,
+                    &[(record.collection.as_str(), record.id.as_str())],
+                )
+            },
+            |backend| backend.put_raw(record),
+        )
+    }
+
+    fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
+        self.inner.scan_raw(collection, options)
+    }
+
+    fn scan_dirty_raw(&self, collection: &str) -> Result<RawBatchResult> {
+        self.inner.scan_dirty_raw(collection)
+    }
+
+    fn count_raw(&self, collection: &str) -> Result<usize> {
+        self.inner.count_raw(collection)
+    }
+
+    fn batch_put_raw(&self, records: &[SerializedRecord]) -> Result<()> {
+        self.write(
+            |backend, overlays| {
+                let keys: Vec<(&str, &str)> = records
+                    .iter()
+                    .map(|r| (r.collection.as_str(), r.id.as_str()))
+                    .collect();
+                save_records(backend, overlays, &keys)
+            },
+            |backend| backend.batch_put_raw(records),
+        )
+    }
+
+    /// Saves every tombstone of `collection`, purgeable or not; saving one
+    /// that stays is harmless.
+    fn purge_tombstones_raw(
+        &self,
+        collection: &str,
+        options: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        self.write(
+            |backend, overlays| {
+                if options.dry_run {
+                    return Ok(());
+                }
+                let scan = ScanOptions {
+                    include_deleted: true,
+                    include_archived: true,
+                    ..Default::default()
+                };
+                let tombstones: Vec<SerializedRecord> = backend
+                    .scan_raw(collection, &scan)?
+                    .records
+                    .into_iter()
+                    .filter(|r| r.deleted)
+                    .collect();
+                let keys: Vec<(&str, &str)> = tombstones
+                    .iter()
+                    .map(|r| (collection, r.id.as_str()))
+                    .collect();
+                save_records(backend, overlays, &keys)
+            },
+            |backend| backend.purge_tombstones_raw(collection, options),
+        )
+    }
+
+    fn vacuum(&self) -> Result<Option<u64>> {
+        self.inner.vacuum()
+    }
+
+    fn rebuild_indexes(&self, def: &CollectionDef, registered: &[&CollectionDef]) -> Result<()> {
+        self.inner.rebuild_indexes(def, registered)
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_meta(key)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.write(
+            |backend, overlays| {
+                let mut current = None;
+                for overlay in overlays {
+                    let mut overlay = overlay.lock();
+                    if overlay.meta.contains_key(key) {
+                        continue;
+                    }
+                    if current.is_none() {
+                        current = Some(backend.get_meta(key)?);
+                    }
+                    overlay
+                        .meta
+                        .insert(key.to_string(), current.clone().flatten());
+                }
+                Ok(())
+            },
+            |backend| backend.set_meta(key, value),
+        )
+    }
+
+    fn transaction<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        self.inner.transaction(|_| f(self))
+    }
+
+    fn scan_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<RawBatchResult>> {
+        self.inner.scan_index_raw(collection, scan)
+    }
+
+    fn count_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<usize>> {
+        self.inner.count_index_raw(collection, scan)
+    }
+
+    /// Declined while a snapshot is open: the changed ids are only known
+    /// afterwards, too late to save them. The adapter then deletes record by
+    /// record through `put_raw`.
+    fn delete_where_raw(
+        &self,
+        collection: &str,
+        scan: &IndexScan,
+        residual: Option<&Value>,
+        opts: &DeleteOptions,
+    ) -> Result<Option<Vec<String>>> {
+        if self.has_open_snapshots() {
+            return Ok(None);
+        }
+        self.inner
+            .delete_where_raw(collection, scan, residual, opts)
+    }
+
+    fn aggregate_raw(
+        &self,
+        collection: &str,
+        version: u32,
+        scan: Option<&IndexScan>,
+        spec: &AggregateSpec,
+    ) -> Result<Option<Vec<AggregateRow>>> {
+        self.inner.aggregate_raw(collection, version, scan, spec)
+    }
+
+    fn check_unique(
+        &self,
+        collection: &str,
+        index: &IndexDefinition,
+        data: &Value,
+        computed: Option<&Value>,
+        exclude_id: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .check_unique(collection, index, data, computed, exclude_id)
+    }
+
+    fn scan_all_raw(&self) -> Result<Vec<SerializedRecord>> {
+        self.inner.scan_all_raw()
+    }
+
+    fn scan_all_meta(&self) -> Result<Vec<(String, String)>> {
+        self.inner.scan_all_meta()
+    }
+}
+
+// ============================================================================
+// SnapshotBackend
+// ============================================================================
+
+/// Read side of a snapshot: the live backend with the overlay's saved
+/// versions put back.
+///
+/// Every read hits the live backend first and the overlay second. A write
+/// saves into the overlay before it lands, so anything the live read already
+/// shows of a later write is undone by the overlay.
+pub(crate) struct SnapshotBackend<B: StorageBackend> {
+    live: Arc<B>,
+    overlay: Arc<Mutex<Overlay>>,
+}
+
+impl<B: StorageBackend> SnapshotBackend<B> {
+    /// Every record of `collection` as of the snapshot, sorted by id once
+    /// anything in it has changed.
+    fn scan_all_of(&self, collection: &str) -> Result<Vec<SerializedRecord>> {
+        let scan = ScanOptions {
+            include_deleted: true,
+            include_archived: true,
+            ..Default::default()
+        };
+        let live = self.live.scan_raw(collection, &scan)?.records;
+        Ok(self.overlay.lock().restore(collection, live))
+    }
+}
+
+impl<B: StorageBackend> StorageBackend for SnapshotBackend<B> {
+    fn get_raw(&self, collection: &str, id: &str) -> Result<Option<SerializedRecord>> {
+        let live = self.live.get_raw(collection, id)?;
+        let overlay = self.overlay.lock();
+        match overlay.records.get(collection).and_then(|col| col.get(id)) {
+            Some(saved) => Ok(saved.clone()),
+            None => Ok(live),
+        }
+    }
+
+    /// Only reached when a read migrates a stale record and tries to persist
+    /// it. The live adapter persists its own migrations, so drop the write.
+    fn put_raw(&self, _record: &SerializedRecord) -> Result<()> {
+        Ok(())
+    }
+
+    fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
+        let records = self
+            .scan_all_of(collection)?
+            .into_iter()
+            .filter(|r| options.include_deleted || !r.deleted)
+            .filter(|r| options.include_archived || !r.archived)
+            .skip(options.offset.unwrap_or(0))
+            .take(options.limit.unwrap_or(usize::MAX))
+            .collect();
+        Ok(RawBatchResult { records })
+    }
+
+    fn scan_dirty_raw(&self, collection: &str) -> Result<RawBatchResult> {
+        let records = self
+            .scan_all_of(collection)?
+            .into_iter()
+            .filter(|r| r.dirty)
+            .collect();
+        Ok(RawBatchResult { records })
+    }
+
+    fn count_raw(&self, collection: &str) -> Result<usize> {
+        Ok(self
+            .scan_all_of(collection)?
+            .iter()
+            .filter(|r| !r.deleted && !r.archived)
+            .count())
+    }
+
+    fn batch_put_raw(&self, _records: &[SerializedRecord]) -> Result<()> {
+        Ok(())
+    }
+
+    fn purge_tombstones_raw(
+        &self,
+        _collection: &str,
+        _options: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        Err(StorageError::ReadOnly("purge tombstones".to_string()).into())
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>> {
+        let live = self.live.get_meta(key)?;
+        match self.overlay.lock().meta.get(key) {
+            Some(saved) => Ok(saved.clone()),
+            None => Ok(live),
+        }
+    }
+
+    fn set_meta(&self, key: &str, _value: &str) -> Result<()> {
+        Err(StorageError::ReadOnly(format!("set meta \"{key}\"")).into())
+    }
+
+    fn transaction<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        f(self)
+    }
+
+    fn scan_index_raw(
+        &self,
+        _collection: &str,
+        _scan: &IndexScan,
+    ) -> Result<Option<RawBatchResult>> {
+        // The live indexes already reflect later writes; the adapter falls
+        // back to a full scan, which goes through the overlay
+        Ok(None)
+    }
+
+    fn count_index_raw(&self, _collection: &str, _scan: &IndexScan) -> Result<Option<usize>> {
+        Ok(None)
+    }
+
+    fn check_unique(
+        &self,
+        _collection: &str,
+        _index: &IndexDefinition,
+        _data: &Value,
+        _computed: Option<&Value>,
+        _exclude_id: Option<&str>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn scan_all_raw(&self) -> Result<Vec<SerializedRecord>> {
+        let live = self.live.scan_all_raw()?;
+        let mut by_collection: BTreeMap<String, Vec<SerializedRecord>> = BTreeMap::new();
+        for record in live {
+            by_collection
+                .entry(record.collection.clone())
+                .or_default()
+                .push(record);
+        }
+        let overlay = self.overlay.lock();
+        for collection in overlay.records.keys() {
+            by_collection.entry(collection.clone()).or_default();
+        }
+        Ok(by_collection
+            .into_iter()
+            .flat_map(|(collection, records)| overlay.restore(&collection, records))
+            .collect())
+    }
+
+    fn scan_all_meta(&self) -> Result<Vec<(String, String)>> {
+        let mut meta: BTreeMap<String, String> = self.live.scan_all_meta()?.into_iter().collect();
+        for (key, saved) in &self.overlay.lock().meta {
+            match saved {
+                Some(value) => meta.insert(key.clone(), value.clone()),
+                None => meta.remove(key),
+            };
+        }
+        Ok(meta.into_iter().collect())
+    }
+}
+
+// ============================================================================
+// SnapshotHandle
+// ============================================================================
+
+/// Read-only view of an adapter pinned to the moment `snapshot()` was called.
+///
+/// Reads go through the same query, migration, and filtering code as the live
+/// adapter. The handle stays valid (and unchanged) for as long as the caller
+/// holds it; while it is open, the live adapter keeps the old version of
+/// everything it overwrites, so drop it once the reads are done.
+pub struct SnapshotHandle<B: StorageBackend> {
+    adapter: Adapter<SnapshotBackend<B>>,
+}
+
+impl<B: StorageBackend> SnapshotHandle<B> {
+    pub(crate) fn new(adapter: Adapter<SnapshotBackend<B>>) -> Self {
+        Self { adapter }
+    }
+}
+
+impl<B: StorageBackend> StorageRead for SnapshotHandle<B> {
+    fn get(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &GetOptions,
+    ) -> Result<Option<StoredRecordWithMeta>> {
+        self.adapter.get(def, id, opts)
+    }
+
+    fn get_all(&self, def: &CollectionDef, opts: &ListOptions) -> Result<BatchResult> {
+        self.adapter.get_all(def, opts)
+    }
+
+    fn query(&self, def: &CollectionDef, query: &Query) -> Result<QueryResult> {
+        self.adapter.query(def, query)
+    }
+
+    fn count(&self, def: &CollectionDef, query: Option<&Query>) -> Result<usize> {
+        self.adapter.count(def, query)
+    }
+
+    fn explain_query(&self, def: &CollectionDef, query: &Query) -> QueryPlan {
+        self.adapter.explain_query(def, query)
+    }
+}
@@ -6,16 +6,25 @@
 //! needs to lock in order to execute SQL.
 
 use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
 
 use parking_lot::ReentrantMutex;
 use rusqlite::{params, OptionalExtension};
 use serde_json::Value;
 
 use crate::collection::builder::CollectionDef;
+#[cfg(feature = "raw-sql")]
+use crate::error::QueryError;
 use crate::error::{LessDbError, Result, StorageError};
 use crate::index::types::{IndexDefinition, IndexScan, IndexScanType, IndexableValue};
-use crate::types::{PurgeTombstonesOptions, RawBatchResult, ScanOptions, SerializedRecord};
-
+use crate::query::aggregate::{AggregateRow, AggregateSpec, Metric};
+use crate::query::operators::FilterMatcher;
+use crate::types::{
+    DeleteKind, DeleteOptions, PurgeTombstonesOptions, RawBatchResult, ScanOptions,
+    SerializedRecord,
+};
+
+use super::record_manager::utc_now_z;
 use super::traits::StorageBackend;
 
 // ============================================================================
@@ -50,6 +59,62 @@ fn json_value_to_sql(v: &Value) -> rusqlite::types::Value {
     }
 }
 
+/// Convert a SQLite column value to JSON. Blobs become byte arrays.
+fn sql_to_json_value(v: rusqlite::types::ValueRef<'_>) -> Value {
+    use rusqlite::types::ValueRef;
+    match v {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Value::from(b.to_vec()),
+    }
+}
+
+/// Rebuild the JSON value `json_extract` returned, given its `json_type`.
+/// `json_extract` flattens booleans to integers and containers to text.
+fn typed_json_value(v: rusqlite::types::ValueRef<'_>, json_type: &str) -> Value {
+    match (json_type, v) {
+        ("true", _) => Value::Bool(true),
+        ("false", _) => Value::Bool(false),
+        ("object" | "array", rusqlite::types::ValueRef::Text(t)) => {
+            serde_json::from_slice(t).unwrap_or(Value::Null)
+        }
+        _ => sql_to_json_value(v),
+    }
+}
+
+/// Whether `path` can be embedded in a `'$.…'` JSON path literal and means
+/// the same there as in `get_field_value`: dot-separated identifiers only.
+fn is_plain_field_path(path: &str) -> bool {
+    path.split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// First keyword of `sql`, skipping whitespace and comments.
+#[cfg(feature = "raw-sql")]
+fn leading_keyword(sql: &str) -> &str {
+    let mut rest = sql;
+    loop {
+        rest = rest.trim_start();
+        if let Some(line) = rest.strip_prefix("--") {
+            rest = line.split_once('\n').map_or("", |(_, tail)| tail);
+        } else if let Some(block) = rest.strip_prefix("/*") {
+            rest = block.split_once("*/").map_or("", |(_, tail)| tail);
+        } else {
+            break;
+        }
+    }
+    let end = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    &rest[..end]
+}
+
 /// Map a rusqlite error to a `LessDbError`.
 fn storage_err(e: rusqlite::Error) -> LessDbError {
     StorageError::Sqlite(e).into()
@@ -114,6 +179,7 @@ impl SqliteBackend {
                     deleted_at      TEXT,
                     meta            TEXT,
                     computed        TEXT,
+                    archived        INTEGER NOT NULL DEFAULT 0,
                     PRIMARY KEY (collection, id)
                 );
                 CREATE INDEX IF NOT EXISTS idx_records_collection
@@ -127,6 +193,21 @@ impl SqliteBackend {
             )
             .map_err(storage_err)?;
 
+            // Databases created before archival deletes lack the column.
+            let has_archived: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM pragma_table_info('records') WHERE name = 'archived'",
+                    [],
+                    |row| row.get::<_, i64>(0).map(|n| n > 0),
+                )
+                .map_err(storage_err)?;
+            if !has_archived {
+                conn.execute_batch(
+                    "ALTER TABLE records ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+                )
+                .map_err(storage_err)?;
+            }
+
             conn.execute(
                 "INSERT OR IGNORE INTO meta (key, value) VALUES ('schema:version', '1')",
                 [],
@@ -147,6 +228,56 @@ impl SqliteBackend {
         self.initialized
     }
 
+    /// Run an arbitrary read-only statement and return its rows as JSON
+    /// objects keyed by column name.
+    ///
+    /// This bypasses the query planner and the record layer entirely: rows
+    /// come straight from the `records` table, including tombstones and
+    /// archived records, with `data` as a JSON string. Only a single
+    /// `SELECT` or `WITH` statement that SQLite reports as read-only is
+    /// accepted.
+    ///
+    /// `sql` is executed verbatim. Callers are responsible for never
+    /// interpolating untrusted input into it; pass values through `params`
+    /// (bound to `?` / `?N` placeholders) instead.
+    #[cfg(feature = "raw-sql")]
+    pub fn query_raw(&self, sql: &str, params: &[Value]) -> Result<Vec<Value>> {
+        let keyword = leading_keyword(sql);
+        if !keyword.eq_ignore_ascii_case("select") && !keyword.eq_ignore_ascii_case("with") {
+            return Err(QueryError::RawSqlRejected(format!(
+                "expected SELECT or WITH, got \"{}\"",
+                keyword
+            ))
+            .into());
+        }
+
+        let guard = self.conn.lock();
+        let conn = guard.borrow();
+        let mut stmt = conn.prepare(sql).map_err(storage_err)?;
+        // `WITH ... DELETE` passes the keyword check; SQLite knows better.
+        if !stmt.readonly() {
+            return Err(
+                QueryError::RawSqlRejected("statement is not read-only".to_string()).into(),
+            );
+        }
+
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let rows = stmt
+            .query_map(
+                rusqlite::params_from_iter(params.iter().map(json_value_to_sql)),
+                |row| {
+                    let mut obj = serde_json::Map::with_capacity(columns.len());
+                    for (i, name) in columns.iter().enumerate() {
+                        obj.insert(name.clone(), sql_to_json_value(row.get_ref(i)?));
+                    }
+                    Ok(Value::Object(obj))
+                },
+            )
+            .map_err(storage_err)?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(storage_err)
+    }
+
     // -----------------------------------------------------------------------
     // Internal helpers
     // -----------------------------------------------------------------------
@@ -207,7 +338,10 @@ impl SqliteBackend {
         let deleted_at: Option<String> = row.get(9)?;
         let meta_str: Option<String> = row.get(10)?;
         let computed_str: Option<String> = row.get(11)?;
+        let archived_i: i64 = row.get(12)?;
 
+        // serde_json parses integer literals straight into i64/u64, never via
+        // f64, so integers beyond 2^53 read back exactly as they were written.
         let data: Value = serde_json::from_str(&data_str)
             .map_err(|e| rusqlite::Error::InvalidParameterName(format!("data: {e}")))?;
 
@@ -238,6 +372,7 @@ impl SqliteBackend {
             deleted_at,
             meta,
             computed,
+            archived: archived_i != 0,
         })
     }
 
@@ -273,8 +408,8 @@ impl SqliteBackend {
         conn.execute(
             "INSERT OR REPLACE INTO records \
              (id, collection, version, data, crdt, pending_patches, sequence, dirty, \
-              deleted, deleted_at, meta, computed) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+              deleted, deleted_at, meta, computed, archived) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 record.id,
                 record.collection,
@@ -288,6 +423,7 @@ impl SqliteBackend {
                 record.deleted_at,
                 meta_str,
                 computed_str,
+                record.archived as i64,
             ],
         )?;
         Ok(())
@@ -302,13 +438,16 @@ impl SqliteBackend {
         scan: &IndexScan,
         index_provides_sort: bool,
     ) -> Option<(String, Vec<rusqlite::types::Value>)> {
-        let mut conditions: Vec<String> =
-            vec!["collection = ?".to_string(), "deleted = 0".to_string()];
+        let mut conditions: Vec<String> = vec![
+            "collection = ?".to_string(),
+            "deleted = 0".to_string(),
+            "archived = 0".to_string(),
+        ];
         let mut params: Vec<rusqlite::types::Value> =
             vec![rusqlite::types::Value::Text(collection.to_string())];
 
         const SELECT_COLS: &str = "SELECT id, collection, version, data, crdt, pending_patches, \
-             sequence, dirty, deleted, deleted_at, meta, computed FROM records";
+             sequence, dirty, deleted, deleted_at, meta, computed, archived FROM records";
 
         match &scan.index {
             IndexDefinition::Field(fi) => {
@@ -346,6 +485,17 @@ impl SqliteBackend {
                     }
                 }
 
+                // A sparse index holds no null keys. Bounded scans exclude
+                // them anyway; an unbounded one must say so.
+                if fi.sparse && scan.scan_type == IndexScanType::Range {
+                    if let Some(range_field) = fi.fields.get(range_idx) {
+                        conditions.push(format!(
+                            "json_extract(data, '$.{}') IS NOT NULL",
+                            range_field.field
+                        ));
+                    }
+                }
+
                 // $in condition on the field after equality prefix
                 if let Some(in_vals) = &scan.in_values {
                     if !in_vals.is_empty() {
@@ -376,16 +526,18 @@ impl SqliteBackend {
                         .iter()
                         .skip(eq_len)
                         .map(|f| {
+                            // Nulls sort as the greatest value, matching the
+                            // default `SortEntry::nulls_order` of the in-memory sort.
                             let effective_dir = if backward {
                                 // Backward scan: flip each field's declared direction
                                 match f.order {
-                                    IndexSortOrder::Asc => "DESC",
-                                    IndexSortOrder::Desc => "ASC",
+                                    IndexSortOrder::Asc => "DESC NULLS FIRST",
+                                    IndexSortOrder::Desc => "ASC NULLS LAST",
                                 }
                             } else {
                                 match f.order {
-                                    IndexSortOrder::Asc => "ASC",
-                                    IndexSortOrder::Desc => "DESC",
+                                    IndexSortOrder::Asc => "ASC NULLS LAST",
+                                    IndexSortOrder::Desc => "DESC NULLS FIRST",
                                 }
                             };
                             format!("json_extract(data, '$.{}') {}", f.field, effective_dir)
@@ -436,7 +588,16 @@ impl SqliteBackend {
                     }
                 }
 
-                let sql = format!("{} WHERE {}", SELECT_COLS, conditions.join(" AND "));
+                let mut sql = format!("{} WHERE {}", SELECT_COLS, conditions.join(" AND "));
+
+                if index_provides_sort {
+                    use crate::index::types::IndexSortOrder;
+                    let order = match scan.direction {
+                        IndexSortOrder::Asc => "ASC NULLS LAST",
+                        IndexSortOrder::Desc => "DESC NULLS FIRST",
+                    };
+                    sql.push_str(&format!(" ORDER BY {} {}", computed_path, order));
+                }
 
                 Some((sql, params))
             }
@@ -477,7 +638,7 @@ impl StorageBackend for SqliteBackend {
         let mut stmt = conn
             .prepare_cached(
                 "SELECT id, collection, version, data, crdt, pending_patches, \
-                 sequence, dirty, deleted, deleted_at, meta, computed \
+                 sequence, dirty, deleted, deleted_at, meta, computed, archived \
                  FROM records WHERE collection = ?1 AND id = ?2",
             )
             .map_err(storage_err)?;
@@ -504,17 +665,16 @@ impl StorageBackend for SqliteBackend {
     }
 
     fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
-        let base = if options.include_deleted {
-            "SELECT id, collection, version, data, crdt, pending_patches, \
-             sequence, dirty, deleted, deleted_at, meta, computed \
+        let mut sql = "SELECT id, collection, version, data, crdt, pending_patches, \
+             sequence, dirty, deleted, deleted_at, meta, computed, archived \
              FROM records WHERE collection = ?1"
-        } else {
-            "SELECT id, collection, version, data, crdt, pending_patches, \
-             sequence, dirty, deleted, deleted_at, meta, computed \
-             FROM records WHERE collection = ?1 AND deleted = 0"
-        };
-
-        let mut sql = base.to_string();
+            .to_string();
+        if !options.include_deleted {
+            sql.push_str(" AND deleted = 0");
+        }
+        if !options.include_archived {
+            sql.push_str(" AND archived = 0");
+        }
         let mut extra: Vec<i64> = Vec::new();
 
         if let Some(limit) = options.limit {
@@ -552,7 +712,7 @@ impl StorageBackend for SqliteBackend {
         let mut stmt = conn
             .prepare_cached(
                 "SELECT id, collection, version, data, crdt, pending_patches, \
-                 sequence, dirty, deleted, deleted_at, meta, computed \
+                 sequence, dirty, deleted, deleted_at, meta, computed, archived \
                  FROM records WHERE collection = ?1 AND dirty = 1",
             )
             .map_err(storage_err)?;
@@ -568,7 +728,8 @@ impl StorageBackend for SqliteBackend {
     fn count_raw(&self, collection: &str) -> Result<usize> {
         self.with_conn(|conn| {
             conn.query_row(
-                "SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 0",
+                "SELECT COUNT(*) FROM records \
+                 WHERE collection = ?1 AND deleted = 0 AND archived = 0",
                 params![collection],
                 |row| row.get::<_, i64>(0),
             )
@@ -579,7 +740,9 @@ impl StorageBackend for SqliteBackend {
     fn batch_put_raw(&self, records: &[SerializedRecord]) -> Result<()> {
         let guard = self.conn.lock();
         let mut conn = guard.borrow_mut();
-        let tx = conn.transaction().map_err(storage_err)?;
+        // A savepoint rather than a transaction, so the batch composes with
+        // an enclosing `transaction` call.
+        let tx = conn.savepoint().map_err(storage_err)?;
 
         for record in records {
             let data_str = serde_json::to_string(&record.data)
@@ -615,45 +778,87 @@ impl StorageBackend for SqliteBackend {
         collection: &str,
         options: &PurgeTombstonesOptions,
     ) -> Result<usize> {
-        if options.dry_run {
-            return if let Some(secs) = options.older_than_seconds {
-                self.with_conn(|conn| {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 1 \
-                         AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)",
-                        params![collection, format!("-{secs} seconds")],
-                        |row| row.get::<_, i64>(0),
-                    )
-                    .map(|n| n as usize)
-                })
-            } else {
-                self.with_conn(|conn| {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM records WHERE collection = ?1 AND deleted = 1",
-                        params![collection],
-                        |row| row.get::<_, i64>(0),
-                    )
-                    .map(|n| n as usize)
-                })
-            };
+        let mut filter = String::from("collection = ?1 AND deleted = 1");
+        if options.skip_dirty {
+            filter.push_str(" AND dirty = 0");
+        }
+        let age = options
+            .older_than_seconds
+            .map(|secs| format!("-{secs} seconds"));
+        if age.is_some() {
+            filter.push_str(" AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)");
+        }
+        let mut args: Vec<&dyn rusqlite::ToSql> = vec![&collection];
+        if let Some(ref age) = age {
+            args.push(age);
         }
 
-        if let Some(secs) = options.older_than_seconds {
-            self.with_conn(|conn| {
-                conn.execute(
-                    "DELETE FROM records WHERE collection = ?1 AND deleted = 1 \
-                     AND deleted_at < strftime('%Y-%m-%dT%H:%M:%fZ', 'now', ?2)",
-                    params![collection, format!("-{secs} seconds")],
-                )
-            })
-        } else {
-            self.with_conn(|conn| {
-                conn.execute(
-                    "DELETE FROM records WHERE collection = ?1 AND deleted = 1",
-                    params![collection],
+        if options.dry_run {
+            return self.with_conn(|conn| {
+                conn.query_row(
+                    &format!("SELECT COUNT(*) FROM records WHERE {filter}"),
+                    args.as_slice(),
+                    |row| row.get::<_, i64>(0),
                 )
-            })
+                .map(|n| n as usize)
+            });
         }
+
+        self.with_conn(|conn| {
+            conn.execute(
+                &format!("DELETE FROM records WHERE {filter}"),
+                args.as_slice(),
+            )
+        })
+    }
+
+    /// `VACUUM`, returning how many bytes the database file shrank by.
+    fn vacuum(&self) -> Result<Option<u64>> {
+        let size = |conn: &rusqlite::Connection| -> rusqlite::Result<i64> {
+            let pages: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+            let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+            Ok(pages * page_size)
+        };
+        self.with_conn(|conn| {
+            let before = size(conn)?;
+            conn.execute_batch("VACUUM")?;
+            let after = size(conn)?;
+            Ok(Some(before.saturating_sub(after).max(0) as u64))
+        })
+    }
+
+    fn rebuild_indexes(&self, def: &CollectionDef, registered: &[&CollectionDef]) -> Result<()> {
+        // Index names are `idx_{collection}_{index}`, so another collection's
+        // indexes (or the built-in `idx_records_*`) can share the prefix.
+        let prefix = format!("idx_{}_", def.name);
+        let mut keep: HashSet<String> = registered
+            .iter()
+            .filter(|other| other.name != def.name)
+            .flat_map(|other| {
+                other
+                    .indexes
+                    .iter()
+                    .map(|index| format!("idx_{}_{}", other.name, index.name()))
+            })
+            .collect();
+        keep.insert("idx_records_collection".to_string());
+        keep.insert("idx_records_dirty".to_string());
+
+        self.with_conn(|conn| {
+            let names = conn
+                .prepare(
+                    "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = 'records'",
+                )?
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+            for name in names {
+                if name.starts_with(&prefix) && !keep.contains(&name) {
+                    conn.execute_batch(&format!("DROP INDEX IF EXISTS \"{name}\""))?;
+                }
+            }
+            Ok(())
+        })?;
+        self.create_collection_indexes(def)
     }
 
     fn get_meta(&self, key: &str) -> Result<Option<String>> {
@@ -753,11 +958,11 @@ impl StorageBackend for SqliteBackend {
             return Ok(None);
         };
 
-        // Replace the SELECT column list with COUNT(*).
-        // build_index_scan_sql always produces "SELECT ... FROM records WHERE ..."
-        let from_idx = data_sql
-            .find(" FROM ")
-            .expect("build_index_scan_sql always produces a FROM clause");
+        // Replace the SELECT column list with COUNT(*). If the builder ever
+        // stops producing "SELECT ... FROM ...", fall back to a scan.
+        let Some(from_idx) = data_sql.find(" FROM ") else {
+            return Ok(None);
+        };
         let count_sql = format!("SELECT COUNT(*){}", &data_sql[from_idx..]);
 
         let guard = self.conn.lock();
@@ -771,13 +976,216 @@ impl StorageBackend for SqliteBackend {
         Ok(Some(count as usize))
     }
 
+    /// One `UPDATE ... RETURNING id` over the scan's conditions. With a
+    /// residual, the candidates are read and filtered first and the update
+    /// then targets their ids.
+    fn delete_where_raw(
+        &self,
+        collection: &str,
+        scan: &IndexScan,
+        residual: Option<&Value>,
+        opts: &DeleteOptions,
+    ) -> Result<Option<Vec<String>>> {
+        // Merging middleware meta into each record's own meta needs the record
+        if opts.meta.is_some() {
+            return Ok(None);
+        }
+
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        let set = match opts.kind {
+            DeleteKind::Tombstone => {
+                params.push(rusqlite::types::Value::Text(utc_now_z()));
+                "deleted = 1, deleted_at = ?, archived = 0, dirty = 1"
+            }
+            DeleteKind::Archive => "archived = 1, dirty = 1",
+        };
+
+        let filter = match residual {
+            None => {
+                let Some((data_sql, scan_params)) =
+                    self.build_index_scan_sql(collection, scan, false)
+                else {
+                    return Ok(None);
+                };
+                // Reuse the "WHERE ..." tail; without one, use the generic path
+                let Some(where_idx) = data_sql.find(" WHERE ") else {
+                    return Ok(None);
+                };
+                params.extend(scan_params);
+                data_sql[where_idx..].to_string()
+            }
+            Some(residual) => {
+                let Some(candidates) = self.execute_index_scan_inner(collection, scan, false)?
+                else {
+                    return Ok(None);
+                };
+                let matcher = FilterMatcher::new(residual)?;
+                let mut ids = Vec::new();
+                for record in candidates {
+                    if matcher.matches(&record.data)? {
+                        ids.push(Value::String(record.id));
+                    }
+                }
+                if ids.is_empty() {
+                    return Ok(Some(Vec::new()));
+                }
+                params.push(rusqlite::types::Value::Text(collection.to_string()));
+                params.push(rusqlite::types::Value::Text(Value::Array(ids).to_string()));
+                " WHERE collection = ? AND deleted = 0 AND archived = 0 \
+                 AND id IN (SELECT value FROM json_each(?))"
+                    .to_string()
+            }
+        };
+
+        let sql = format!("UPDATE records SET {set}{filter} RETURNING id");
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| row.get(0))?;
+            rows.collect()
+        })
+        .map(Some)
+    }
+
+    /// One `GROUP BY` over `json_extract` of the stored data. Each group-by
+    /// path contributes its value and its `json_type`, so `1`, `1.0` and
+    /// `true` stay distinct groups as they do in `Aggregator`.
+    fn aggregate_raw(
+        &self,
+        collection: &str,
+        version: u32,
+        scan: Option<&IndexScan>,
+        spec: &AggregateSpec,
+    ) -> Result<Option<Vec<AggregateRow>>> {
+        let mut fields = spec
+            .group_by
+            .iter()
+            .map(String::as_str)
+            .chain(spec.metrics.iter().filter_map(Metric::field));
+        if !fields.all(is_plain_field_path) {
+            return Ok(None);
+        }
+
+        let (filter, params) = match scan {
+            None => (
+                " WHERE collection = ? AND deleted = 0 AND archived = 0".to_string(),
+                vec![rusqlite::types::Value::Text(collection.to_string())],
+            ),
+            Some(scan) => {
+                let Some((data_sql, params)) = self.build_index_scan_sql(collection, scan, false)
+                else {
+                    return Ok(None);
+                };
+                // Reuse the "WHERE ..." tail; without one, use the generic path
+                let Some(where_idx) = data_sql.find(" WHERE ") else {
+                    return Ok(None);
+                };
+                (data_sql[where_idx..].to_string(), params)
+            }
+        };
+
+        let mut columns = Vec::new();
+        let mut group_cols = Vec::new();
+        for path in &spec.group_by {
+            columns.push(format!("json_extract(data, '$.{path}')"));
+            columns.push(format!("COALESCE(json_type(data, '$.{path}'), 'null')"));
+            group_cols.push(format!("{}, {}", columns.len() - 1, columns.len()));
+        }
+        columns.push("COUNT(*)".to_string());
+        for field in spec.metrics.iter().filter_map(Metric::field) {
+            let number = format!(
+                "CASE WHEN json_type(data, '$.{field}') IN ('integer', 'real') \
+                 THEN json_extract(data, '$.{field}') END"
+            );
+            columns.push(format!("COUNT({number})"));
+            columns.push(format!("TOTAL({number})"));
+            columns.push(format!("MIN({number})"));
+            columns.push(format!("MAX({number})"));
+            columns.push(format!(
+                "COUNT(CASE WHEN json_type(data, '$.{field}') \
+                 NOT IN ('integer', 'real', 'null') THEN 1 END)"
+            ));
+        }
+        let mut sql = format!("SELECT {} FROM records{filter}", columns.join(", "));
+        if !group_cols.is_empty() {
+            sql.push_str(&format!(" GROUP BY {}", group_cols.join(", ")));
+        }
+
+        self.with_conn(|conn| {
+            let stale: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM records WHERE collection = ? \
+                 AND deleted = 0 AND archived = 0 AND version != ?)",
+                params![collection, version],
+                |row| row.get(0),
+            )?;
+            if stale {
+                return Ok(None);
+            }
+
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params), |row| {
+                let mut col = 0;
+                let mut group = Vec::with_capacity(spec.group_by.len());
+                for _ in &spec.group_by {
+                    let json_type: String = row.get(col + 1)?;
+                    group.push(typed_json_value(row.get_ref(col)?, &json_type));
+                    col += 2;
+                }
+                let count: i64 = row.get(col)?;
+                col += 1;
+
+                let mut values = Vec::with_capacity(spec.metrics.len());
+                let mut skipped = Vec::with_capacity(spec.metrics.len());
+                for metric in &spec.metrics {
+                    if let Metric::Count = metric {
+                        values.push(Some(count as f64));
+                        skipped.push(0);
+                        continue;
+                    }
+                    let seen: i64 = row.get(col)?;
+                    let total: f64 = row.get(col + 1)?;
+                    let min: Option<f64> = row.get(col + 2)?;
+                    let max: Option<f64> = row.get(col + 3)?;
+                    let non_numeric: i64 = row.get(col + 4)?;
+                    col += 5;
+                    values.push(match metric {
+                        _ if seen == 0 => None,
+                        Metric::Count => Some(count as f64),
+                        Metric::Sum(_) => Some(total),
+                        Metric::Avg(_) => Some(total / seen as f64),
+                        Metric::Min(_) => min,
+                        Metric::Max(_) => max,
+                    });
+                    skipped.push(non_numeric as usize);
+                }
+                Ok((
+                    count,
+                    AggregateRow {
+                        group,
+                        values,
+                        skipped,
+                    },
+                ))
+            })?;
+
+            // Without GROUP BY an empty match set still yields one row
+            let mut out = Vec::new();
+            for row in rows {
+                let (count, row) = row?;
+                if count > 0 {
+                    out.push(row);
+                }
+            }
+            Ok(Some(out))
+        })
+    }
+
     fn scan_all_raw(&self) -> Result<Vec<SerializedRecord>> {
         let guard = self.conn.lock();
         let conn = guard.borrow();
         let mut stmt = conn
             .prepare_cached(
                 "SELECT id, collection, version, data, crdt, pending_patches, \
-                 sequence, dirty, deleted, deleted_at, meta, computed \
+                 sequence, dirty, deleted, deleted_at, meta, computed, archived \
                  FROM records",
             )
             .map_err(storage_err)?;
@@ -860,27 +1268,11 @@ impl StorageBackend for SqliteBackend {
                     .map_err(storage_err)?;
 
                 if let Some(eid) = existing_id {
-                    let conflict_value = if fi.fields.len() == 1 {
-                        obj.and_then(|o| o.get(&fi.fields[0].field))
-                            .cloned()
-                            .unwrap_or(Value::Null)
-                    } else {
-                        Value::Array(
-                            fi.fields
-                                .iter()
-                                .map(|f| {
-                                    obj.and_then(|o| o.get(&f.field))
-                                        .cloned()
-                                        .unwrap_or(Value::Null)
-                                })
-                                .collect(),
-                        )
-                    };
                     return Err(StorageError::UniqueConstraint {
                         collection: collection.to_string(),
                         index: fi.name.clone(),
                         existing_id: eid,
-                        value: conflict_value,
+                        fields: fi.values_of(data),
                     }
                     .into());
                 }
@@ -943,7 +1335,7 @@ impl StorageBackend for SqliteBackend {
                         collection: collection.to_string(),
                         index: ci.name.clone(),
                         existing_id: eid,
-                        value: conflict_value,
+                        fields: [(ci.name.clone(), conflict_value)].into_iter().collect(),
                     }
                     .into());
                 }
@@ -10,12 +10,13 @@ use serde_json::Value;
 use crate::collection::builder::CollectionDef;
 use crate::error::Result;
 use crate::index::types::{IndexDefinition, IndexScan};
+use crate::query::aggregate::{AggregateRow, AggregateSpec};
 use crate::query::types::Query;
 use crate::types::{
     ApplyRemoteOptions, ApplyRemoteResult, BatchResult, BulkDeleteResult, BulkPatchResult,
     DeleteOptions, GetOptions, ListOptions, PatchManyResult, PatchOptions, PurgeTombstonesOptions,
-    PushSnapshot, PutOptions, QueryResult, RawBatchResult, RemoteRecord, ScanOptions,
-    SerializedRecord, StoredRecordWithMeta,
+    PushQueueState, PushSnapshot, PutOptions, QueryResult, RawBatchResult, RemoteRecord,
+    ScanOptions, SerializedRecord, StoredRecordWithMeta, TouchOptions,
 };
 
 // Re-export QueryPlan so adapter code can use it via traits module.
@@ -53,6 +54,23 @@ pub trait StorageBackend: Send + Sync {
         options: &PurgeTombstonesOptions,
     ) -> Result<usize>;
 
+    /// Give free space back to the filesystem after bulk removals (SQLite
+    /// `VACUUM`). Returns the bytes reclaimed, or `None` if the backend has
+    /// no such step or can't measure it. Must not be called inside a
+    /// transaction.
+    fn vacuum(&self) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Drop the physical indexes this backend keeps for `def` and recreate
+    /// them from its current index definitions, so indexes that were removed
+    /// or redefined since they were created don't linger. `registered` is
+    /// every collection the adapter knows; their indexes are left alone.
+    /// Default: no-op, for backends without physical indexes.
+    fn rebuild_indexes(&self, _def: &CollectionDef, _registered: &[&CollectionDef]) -> Result<()> {
+        Ok(())
+    }
+
     /// Read a metadata key-value pair (used for sequence numbers, schema versions, etc.).
     fn get_meta(&self, key: &str) -> Result<Option<String>>;
 
@@ -75,6 +93,43 @@ pub trait StorageBackend: Send + Sync {
     /// Count records using an index scan. Returns `None` if unsupported.
     fn count_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<usize>>;
 
+    /// Tombstone (or archive, per `opts.kind`) every live record matched by
+    /// `scan` and, if given, by `residual`, without a read-modify-write per
+    /// record. Returns the ids changed, or `None` if the backend cannot run
+    /// this scan with these options (the adapter then deletes record by
+    /// record).
+    ///
+    /// `residual` is matched against stored `data`, like the index scan.
+    /// Default: `None`.
+    fn delete_where_raw(
+        &self,
+        _collection: &str,
+        _scan: &IndexScan,
+        _residual: Option<&Value>,
+        _opts: &DeleteOptions,
+    ) -> Result<Option<Vec<String>>> {
+        Ok(None)
+    }
+
+    /// Compute `spec`'s groups and metrics over the live records of
+    /// `collection` matched by `scan` (all of them when `None`), without
+    /// returning the records. `spec.filter` is ignored; the adapter only
+    /// passes a scan that covers it. Rows may come back in any order.
+    ///
+    /// Stored `data` is aggregated as is, so this must return `None` if any
+    /// live record of the collection is not at `version`, and whenever the
+    /// backend cannot run the spec (the adapter then folds the records
+    /// itself). Default: `None`.
+    fn aggregate_raw(
+        &self,
+        _collection: &str,
+        _version: u32,
+        _scan: Option<&IndexScan>,
+        _spec: &AggregateSpec,
+    ) -> Result<Option<Vec<AggregateRow>>> {
+        Ok(None)
+    }
+
     /// Check that a unique constraint is not violated.
     ///
     /// Returns `Ok(())` if no existing record has the same value,
@@ -134,7 +189,25 @@ pub trait StorageWrite {
         data: Value,
         opts: &PatchOptions,
     ) -> Result<StoredRecordWithMeta>;
+    /// Tombstone or archive a live record, per `opts.kind`. Returns `false`
+    /// if the record is missing, already tombstoned, or archived.
     fn delete(&self, def: &CollectionDef, id: &str, opts: &DeleteOptions) -> Result<bool>;
+    /// Convert an archived record into a tombstone that propagates on the
+    /// next push. Space admins only — gate on the space delete policy before
+    /// calling. Returns `false` if the record is not archived.
+    fn tombstone_archived(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &DeleteOptions,
+    ) -> Result<bool>;
+    /// Mark a record dirty and bump its `updatedAt` without changing user fields.
+    fn touch(
+        &self,
+        def: &CollectionDef,
+        id: &str,
+        opts: &TouchOptions,
+    ) -> Result<StoredRecordWithMeta>;
     fn bulk_put(
         &self,
         def: &CollectionDef,
@@ -186,6 +259,12 @@ pub trait StorageSync {
     ) -> Result<ApplyRemoteResult>;
     fn get_last_sequence(&self, collection: &str) -> Result<i64>;
     fn set_last_sequence(&self, collection: &str, sequence: i64) -> Result<()>;
+    fn get_push_state(&self, collection: &str) -> Result<Option<PushQueueState>>;
+    /// Persist push retry state; `None` clears it.
+    fn set_push_state(&self, collection: &str, state: Option<&PushQueueState>) -> Result<()>;
+    /// Purge tombstones of `def` older than its `tombstone_retention` that
+    /// the server has acknowledged. Returns 0 when no retention is set.
+    fn purge_expired_tombstones(&self, def: &CollectionDef) -> Result<usize>;
 }
 
 /// Lifecycle operations for the storage backend.
@@ -6,12 +6,18 @@
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use betterbase_crypto::UCANPermission;
+use betterbase_sync_core::{MembershipState, SpaceDeletePolicy};
 use parking_lot::Mutex;
+use serde_json::Value;
 use tokio::sync::Mutex as TokioMutex;
 
 use crate::{
     collection::builder::CollectionDef,
-    types::{ApplyRemoteOptions, PushSnapshot, RemoteAction, RemoteRecord},
+    storage::record_manager::{META_EDIT_CHAIN, META_EDIT_CHAIN_TRIMMED},
+    types::{
+        ApplyRemoteOptions, DeleteKind, PushQueueState, PushSnapshot, RemoteAction, RemoteRecord,
+    },
 };
 
 use super::types::*;
@@ -24,12 +30,20 @@ pub struct SyncManager {
     transport: Arc<dyn SyncTransport>,
     adapter: Arc<dyn SyncAdapter>,
     collections: HashMap<String, Arc<CollectionDef>>,
+    /// Collection names in sync order (priorities first, then alphabetical)
+    sync_order: Vec<String>,
     delete_strategy: Option<crate::types::DeleteConflictStrategyName>,
     push_batch_size: Option<usize>,
     quarantine_threshold: usize,
+    push_backoff: PushBackoff,
+    delete_policy: Option<SpaceDeletePolicy>,
+    permission: UCANPermission,
+    /// Permission assumed for the authors of pulled tombstones
+    tombstone_author: Option<UCANPermission>,
     on_error: Option<Arc<SyncErrorCallback>>,
     on_progress: Option<Arc<SyncProgressCallback>>,
     on_remote_delete: Option<Arc<RemoteDeleteCallback>>,
+    on_conflict: Option<Arc<SyncConflictCallback>>,
     /// Per-collection async locks for serializing concurrent sync calls
     locks: Mutex<HashMap<String, Arc<TokioMutex<()>>>>,
     /// Consecutive failure counts per `"collection:id"`
@@ -44,17 +58,27 @@ impl SyncManager {
         for def in &options.collections {
             collections.insert(def.name.clone(), Arc::clone(def));
         }
+        let sync_order = sync_order(&collections, &options.collection_priority);
 
         Self {
             transport: options.transport,
             adapter: options.adapter,
             collections,
+            sync_order,
             delete_strategy: options.delete_strategy,
             push_batch_size: options.push_batch_size,
             quarantine_threshold: options.quarantine_threshold.unwrap_or(3).max(1),
+            push_backoff: options.push_backoff.unwrap_or_default(),
+            delete_policy: options.delete_policy,
+            permission: options.permission.unwrap_or(UCANPermission::Admin),
+            tombstone_author: options
+                .membership
+                .as_ref()
+                .map(MembershipState::least_writer_permission),
             on_error: options.on_error,
             on_progress: options.on_progress,
             on_remote_delete: options.on_remote_delete,
+            on_conflict: options.on_conflict,
             locks: Mutex::new(HashMap::new()),
             failure_counts: Mutex::new(HashMap::new()),
             quarantined: Mutex::new(HashSet::new()),
@@ -69,7 +93,14 @@ impl SyncManager {
     pub async fn sync(&self, def: &CollectionDef) -> SyncResult {
         let collection = def.name.clone();
         self.with_lock(&collection, async {
+            if let Some(result) = self.backoff_result(SyncPhase::Pull, &collection) {
+                return result;
+            }
             let mut result = self.pull_impl(def).await;
+            // A failed pull starts a backoff; don't hit the transport again
+            if self.is_backing_off(&collection) {
+                return result;
+            }
             let push_result = self.push_impl(def).await;
             result.merge(push_result);
             result
@@ -77,9 +108,9 @@ impl SyncManager {
         .await
     }
 
-    /// Sync all registered collections sequentially.
+    /// Sync all registered collections sequentially, in priority order.
     pub async fn sync_all(&self) -> HashMap<String, SyncResult> {
-        let defs: Vec<Arc<CollectionDef>> = self.collections.values().cloned().collect();
+        let defs = self.get_collections();
         let mut results = HashMap::new();
         for def in defs {
             let result = self.sync(&def).await;
@@ -88,18 +119,90 @@ impl SyncManager {
         results
     }
 
+    /// Purge acknowledged tombstones past each collection's
+    /// `tombstone_retention`, skipping collections without one. Returns the
+    /// purged count per collection.
+    ///
+    /// Runs under each collection's sync lock so a purge never races a pull
+    /// or push. Failures are logged and leave the collection out of the
+    /// report.
+    pub async fn purge_tombstones(&self) -> HashMap<String, usize> {
+        let mut report = HashMap::new();
+        for def in self.get_collections() {
+            if def.tombstone_retention.is_none() {
+                continue;
+            }
+            let result = self
+                .with_lock(&def.name, async {
+                    self.adapter.purge_expired_tombstones(&def)
+                })
+                .await;
+            match result {
+                Ok(purged) => {
+                    report.insert(def.name.clone(), purged);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        collection = %def.name,
+                        error = %e,
+                        "failed to purge expired tombstones"
+                    );
+                }
+            }
+        }
+        report
+    }
+
+    /// Catch up after being offline.
+    ///
+    /// For each collection in sync order: pull every page of remote changes
+    /// since its last sequence, applying them with the configured delete
+    /// strategy, then push local dirty records. Once converged, another call
+    /// pulls an empty page and pushes nothing.
+    pub async fn resync(&self) -> SyncResult {
+        let mut result = SyncResult::default();
+        for def in self.get_collections() {
+            let collection_result = self
+                .with_lock(&def.name, async {
+                    if let Some(result) = self.backoff_result(SyncPhase::Pull, &def.name) {
+                        return result;
+                    }
+                    let mut result = self.pull_all_impl(&def).await;
+                    if self.is_backing_off(&def.name) {
+                        return result;
+                    }
+                    let push_result = self.push_impl(&def).await;
+                    result.merge(push_result);
+                    result
+                })
+                .await;
+            result.merge(collection_result);
+        }
+        result
+    }
+
     /// Push only (under per-collection lock).
     pub async fn push(&self, def: &CollectionDef) -> SyncResult {
         let collection = def.name.clone();
-        self.with_lock(&collection, async { self.push_impl(def).await })
-            .await
+        self.with_lock(&collection, async {
+            match self.backoff_result(SyncPhase::Push, &collection) {
+                Some(result) => result,
+                None => self.push_impl(def).await,
+            }
+        })
+        .await
     }
 
     /// Pull only (under per-collection lock).
     pub async fn pull(&self, def: &CollectionDef) -> SyncResult {
         let collection = def.name.clone();
-        self.with_lock(&collection, async { self.pull_impl(def).await })
-            .await
+        self.with_lock(&collection, async {
+            match self.backoff_result(SyncPhase::Pull, &collection) {
+                Some(result) => result,
+                None => self.pull_impl(def).await,
+            }
+        })
+        .await
     }
 
     /// Apply pre-converted remote records directly (for real-time transports).
@@ -125,9 +228,18 @@ impl SyncManager {
         self.adapter.get_last_sequence(collection).unwrap_or(0)
     }
 
-    /// Return all registered collection definitions.
+    /// When `collection` may next contact the transport (ms since epoch), if
+    /// it is backing off after failed transport calls.
+    pub fn next_retry_at(&self, collection: &str) -> Option<i64> {
+        self.retry_state(collection).next_attempt_at
+    }
+
+    /// Return all registered collection definitions, in sync order.
     pub fn get_collections(&self) -> Vec<Arc<CollectionDef>> {
-        self.collections.values().cloned().collect()
+        self.sync_order
+            .iter()
+            .filter_map(|name| self.collections.get(name).cloned())
+            .collect()
     }
 
     /// Clear quarantine for all records in a collection, allowing retry.
@@ -162,8 +274,12 @@ impl SyncManager {
             return result;
         }
 
+        // Callers check the backoff first; the state is read here for the
+        // ids of the batch that failed last
+        let mut state = self.retry_state(&collection);
+
         // Get dirty records
-        let dirty = match self.adapter.get_dirty(def) {
+        let mut dirty = match self.adapter.get_dirty(def) {
             Ok(batch) => {
                 // Map getDirty errors to sync errors
                 for err in &batch.errors {
@@ -189,34 +305,65 @@ impl SyncManager {
             }
         };
 
+        let pending_retry = state != PushQueueState::default();
         if dirty.is_empty() {
+            if pending_retry {
+                self.save_push_state(SyncPhase::Push, &collection, &mut result, None);
+            }
             return result;
         }
 
+        // Records from the previously failed batch go out first
+        if !state.batch_ids.is_empty() {
+            let retry: HashSet<&str> = state.batch_ids.iter().map(String::as_str).collect();
+            dirty.sort_by_key(|record| !retry.contains(record.id.as_str()));
+        }
+
         // Snapshot phase: capture TOCTOU guard for each record
         let mut snapshots: HashMap<String, PushSnapshot> = HashMap::new();
         let mut outbound: Vec<OutboundRecord> = Vec::new();
 
+        // `None` when the policy doesn't let this member delete at all
+        let delete_kind = match &self.delete_policy {
+            Some(policy) => policy.delete_kind_for(self.permission),
+            None => Some(DeleteKind::Tombstone),
+        };
+
         for record in &dirty {
+            if record.deleted && delete_kind.is_none() {
+                result.errors.push(self.make_sync_error(
+                    SyncPhase::Push,
+                    &collection,
+                    Some(&record.id),
+                    "Space delete policy does not allow this member to delete",
+                    SyncErrorKind::Permanent,
+                ));
+                continue;
+            }
+            // Deletes the policy restricts to archiving go out as archives
+            let archive = record.deleted && delete_kind == Some(DeleteKind::Archive);
+
             snapshots.insert(
                 record.id.clone(),
                 PushSnapshot {
                     pending_patches_length: record.pending_patches.len(),
                     deleted: record.deleted,
+                    archived: record.archived,
                 },
             );
 
             outbound.push(OutboundRecord {
                 id: record.id.clone(),
                 version: record.version,
-                crdt: if record.deleted {
+                crdt: if record.deleted && !archive {
                     None
                 } else {
                     Some(record.crdt.clone())
                 },
-                deleted: record.deleted,
+                deleted: record.deleted && !archive,
+                archived: archive || (record.archived && !record.deleted),
                 sequence: record.sequence,
-                meta: record.meta.clone(),
+                meta: outbound_meta(record.meta.as_ref()),
             });
         }
 
@@ -224,13 +371,25 @@ impl SyncManager {
 
         // Process in batches
         let mut pushed = 0;
+        let mut completed = true;
+        let mut back_off = false;
         for chunk_start in (0..total).step_by(batch_size) {
             let chunk_end = (chunk_start + batch_size).min(total);
             let batch = &outbound[chunk_start..chunk_end];
 
             let acks = match self.transport.push(&collection, batch).await {
-                Ok(acks) => acks,
+                Ok(acks) => {
+                    // The first success resets the schedule
+                    state = PushQueueState::default();
+                    acks
+                }
                 Err(e) => {
+                    if is_retryable(&e.kind) {
+                        state.batch_ids = batch.iter().map(|r| r.id.clone()).collect();
+                        self.schedule_retry(&mut state);
+                        back_off = true;
+                    }
+                    completed = false;
                     result.errors.push(self.make_sync_error(
                         SyncPhase::Push,
                         &collection,
@@ -267,10 +426,36 @@ impl SyncManager {
             self.report_progress(SyncPhase::Push, &collection, chunk_end, total);
         }
 
+        if back_off {
+            self.save_push_state(SyncPhase::Push, &collection, &mut result, Some(&state));
+            // Let progress listeners show when the next attempt is due
+            self.report_progress(SyncPhase::Push, &collection, 0, 0);
+        } else if pending_retry && (completed || state == PushQueueState::default()) {
+            self.save_push_state(SyncPhase::Push, &collection, &mut result, None);
+        }
         result.pushed = pushed;
         result
     }
 
+    /// Persist (or clear, with `None`) the retry state for a collection.
+    fn save_push_state(
+        &self,
+        phase: SyncPhase,
+        collection: &str,
+        result: &mut SyncResult,
+        state: Option<&PushQueueState>,
+    ) {
+        if let Err(e) = self.adapter.set_push_state(collection, state) {
+            result.errors.push(self.make_sync_error(
+                phase,
+                collection,
+                None,
+                &e.to_string(),
+                SyncErrorKind::Transient,
+            ));
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Pull Implementation
     // -----------------------------------------------------------------------
@@ -296,8 +481,18 @@ impl SyncManager {
 
         // Pull from transport
         let pull_result = match self.transport.pull(&collection, since).await {
-            Ok(pr) => pr,
+            Ok(pr) => {
+                self.reset_retry(&collection, &mut result);
+                pr
+            }
             Err(e) => {
+                if is_retryable(&e.kind) {
+                    let mut state = self.retry_state(&collection);
+                    self.schedule_retry(&mut state);
+                    self.save_push_state(SyncPhase::Pull, &collection, &mut result, Some(&state));
+                    // Let progress listeners show when the next attempt is due
+                    self.report_progress(SyncPhase::Pull, &collection, 0, 0);
+                }
                 result.errors.push(self.make_sync_error(
                     SyncPhase::Pull,
                     &collection,
@@ -337,6 +532,8 @@ impl SyncManager {
             let apply_opts = ApplyRemoteOptions {
                 delete_conflict_strategy: self.delete_strategy.clone(),
                 received_at: None,
+                writer_delete: self.delete_policy.map(|policy| policy.writer_delete),
+                author_permission: self.tombstone_author,
             };
 
             match self
@@ -349,6 +546,7 @@ impl SyncManager {
 
                     // Fire onRemoteDelete callbacks
                     self.fire_remote_tombstones(&collection, &apply_result.applied);
+                    self.fire_conflicts(&collection, &apply_result.conflicts);
 
                     // Track failures from apply errors
                     for err in &apply_result.errors {
@@ -407,6 +605,19 @@ impl SyncManager {
         result
     }
 
+    /// Pull page after page until the cursor stops advancing.
+    async fn pull_all_impl(&self, def: &CollectionDef) -> SyncResult {
+        let mut result = SyncResult::default();
+        loop {
+            let before = self.get_last_sequence(&def.name);
+            let page = self.pull_impl(def).await;
+            result.merge(page);
+            if self.get_last_sequence(&def.name) <= before {
+                return result;
+            }
+        }
+    }
+
     // -----------------------------------------------------------------------
     // applyRemoteRecords Implementation
     // -----------------------------------------------------------------------
@@ -461,6 +672,8 @@ impl SyncManager {
             let apply_opts = ApplyRemoteOptions {
                 delete_conflict_strategy: self.delete_strategy.clone(),
                 received_at: None,
+                writer_delete: self.delete_policy.map(|policy| policy.writer_delete),
+                author_permission: self.tombstone_author,
             };
 
             match self
@@ -472,6 +685,7 @@ impl SyncManager {
                     result.merged = apply_result.merged_count;
 
                     self.fire_remote_tombstones(&collection, &apply_result.applied);
+                    self.fire_conflicts(&collection, &apply_result.conflicts);
 
                     for err in &apply_result.errors {
                         result.errors.push(self.make_sync_error(
@@ -523,11 +737,7 @@ impl SyncManager {
     // Lock Management
     // -----------------------------------------------------------------------
 
-    async fn with_lock<F: std::future::Future<Output = SyncResult>>(
-        &self,
-        collection: &str,
-        f: F,
-    ) -> SyncResult {
+    async fn with_lock<T, F: std::future::Future<Output = T>>(&self, collection: &str, f: F) -> T {
         let lock = {
             let mut locks = self.locks.lock();
             locks
@@ -539,6 +749,73 @@ impl SyncManager {
         f.await
     }
 
+    // -----------------------------------------------------------------------
+    // Retry Backoff
+    // -----------------------------------------------------------------------
+
+    /// The result to return instead of calling the transport, if `collection`
+    /// is still backing off.
+    fn backoff_result(&self, phase: SyncPhase, collection: &str) -> Option<SyncResult> {
+        let state = self.retry_state(collection);
+        let next_attempt_at = state.next_attempt_at?;
+        let now = chrono::Utc::now().timestamp_millis();
+        if next_attempt_at <= now {
+            return None;
+        }
+        let error = self.make_sync_error(
+            phase,
+            collection,
+            None,
+            &format!(
+                "Sync backing off for {}ms after {} failed attempt(s)",
+                next_attempt_at - now,
+                state.attempts
+            ),
+            SyncErrorKind::Transient,
+        );
+        Some(SyncResult {
+            errors: vec![error],
+            ..SyncResult::default()
+        })
+    }
+
+    fn is_backing_off(&self, collection: &str) -> bool {
+        let now = chrono::Utc::now().timestamp_millis();
+        matches!(self.next_retry_at(collection), Some(at) if at > now)
+    }
+
+    /// Persisted retry state for `collection`. It survives restarts; an
+    /// unreadable entry just resets the schedule.
+    fn retry_state(&self, collection: &str) -> PushQueueState {
+        self.adapter
+            .get_push_state(collection)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Count one more failed transport call in `state` and schedule the
+    /// next attempt.
+    fn schedule_retry(&self, state: &mut PushQueueState) {
+        state.attempts = state.attempts.saturating_add(1);
+        let delay = self.push_backoff.jittered_delay_ms(state.attempts);
+        let now = chrono::Utc::now().timestamp_millis();
+        state.next_attempt_at = Some(now.saturating_add(i64::try_from(delay).unwrap_or(i64::MAX)));
+    }
+
+    /// Reset the backoff schedule after a successful pull. The ids of a
+    /// failed push batch are kept, so that batch still goes out first.
+    fn reset_retry(&self, collection: &str, result: &mut SyncResult) {
+        let mut state = self.retry_state(collection);
+        if state.attempts == 0 && state.next_attempt_at.is_none() {
+            return;
+        }
+        state.attempts = 0;
+        state.next_attempt_at = None;
+        let state = (state != PushQueueState::default()).then_some(state);
+        self.save_push_state(SyncPhase::Pull, collection, result, state.as_ref());
+    }
+
     // -----------------------------------------------------------------------
     // Quarantine
     // -----------------------------------------------------------------------
@@ -608,6 +885,23 @@ impl SyncManager {
         }
     }
 
+    /// Fire `on_conflict` for each field a merge resolved between concurrent
+    /// local and remote writes.
+    fn fire_conflicts(&self, collection: &str, conflicts: &[crate::types::MergeConflict]) {
+        if let Some(ref on_conflict) = self.on_conflict {
+            for conflict in conflicts {
+                let event = SyncConflictEvent {
+                    collection: collection.to_string(),
+                    conflict: conflict.clone(),
+                };
+                // Swallow callback errors — must not break sync
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    on_conflict(&event);
+                }));
+            }
+        }
+    }
+
     fn report_progress(&self, phase: SyncPhase, collection: &str, processed: usize, total: usize) {
         if let Some(ref on_progress) = self.on_progress {
             let progress = SyncProgress {
@@ -615,6 +909,7 @@ impl SyncManager {
                 collection: collection.to_string(),
                 processed,
                 total,
+                next_retry_at: self.next_retry_at(collection),
             };
             let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
                 on_progress(&progress);
@@ -645,3 +940,77 @@ impl SyncManager {
         event
     }
 }
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// First run of sequences missing from `incoming` after `last_seen`, as an
+/// inclusive `(start, end)` range, or `None` if the batch continues the
+/// stream without a hole.
+///
+/// Order and duplicates in `incoming` don't matter; sequences at or below
+/// `last_seen` are ignored. Only meaningful against a log that assigns
+/// every sequence to the collection being pulled: a pull that collapses
+/// superseded writes into the newest one skips sequences legitimately.
+pub fn detect_sequence_gap(last_seen: i64, incoming: &[RemoteRecord]) -> Option<(i64, i64)> {
+    let mut sequences: Vec<i64> = incoming
+        .iter()
+        .map(|r| r.sequence)
+        .filter(|&seq| seq > last_seen)
+        .collect();
+    sequences.sort_unstable();
+    sequences.dedup();
+
+    let mut expected = last_seen + 1;
+    for seq in sequences {
+        if seq > expected {
+            return Some((expected, seq - 1));
+        }
+        expected = seq + 1;
+    }
+    None
+}
+
+/// Prioritized collections (registered ones only, first occurrence wins),
+/// followed by the rest in alphabetical order.
+fn sync_order(
+    collections: &HashMap<String, Arc<CollectionDef>>,
+    priority: &[String],
+) -> Vec<String> {
+    let mut order: Vec<String> = Vec::with_capacity(collections.len());
+    for name in priority {
+        if collections.contains_key(name) && !order.contains(name) {
+            order.push(name.clone());
+        }
+    }
+    let mut rest: Vec<String> = collections
+        .keys()
+        .filter(|name| !order.contains(name))
+        .cloned()
+        .collect();
+    rest.sort();
+    order.extend(rest);
+    order
+}
+
+/// Whether a failed transport call of this kind backs the collection off.
+fn is_retryable(kind: &SyncErrorKind) -> bool {
+    matches!(kind, SyncErrorKind::Transient | SyncErrorKind::Capacity)
+}
+
+/// Meta to push for a record. A chain trimmed by compaction stays local: it
+/// is left out along with its flag, so the transport starts a fresh chain
+/// instead of extending one that no longer verifies.
+fn outbound_meta(meta: Option<&Value>) -> Option<Value> {
+    let obj = meta?.as_object();
+    match obj {
+        Some(obj) if obj.contains_key(META_EDIT_CHAIN_TRIMMED) => {
+            let mut obj = obj.clone();
+            obj.remove(META_EDIT_CHAIN);
+            obj.remove(META_EDIT_CHAIN_TRIMMED);
+            Some(Value::Object(obj))
+        }
+        _ => meta.cloned(),
+    }
+}
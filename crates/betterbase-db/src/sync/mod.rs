@@ -3,11 +3,12 @@ pub mod manager;
 pub mod scheduler;
 pub mod types;
 
-pub use manager::SyncManager;
+pub use manager::{detect_sequence_gap, SyncManager};
 #[cfg(not(target_arch = "wasm32"))]
 pub use scheduler::SyncScheduler;
 pub use types::{
-    PullFailure, PullResult, PushAck, RemoteDeleteCallback, RemoteDeleteEvent, SyncAdapter,
-    SyncErrorCallback, SyncErrorEvent, SyncErrorKind, SyncManagerOptions, SyncPhase, SyncProgress,
-    SyncProgressCallback, SyncResult, SyncTransport, SyncTransportError,
+    PullFailure, PullResult, PushAck, PushBackoff, RemoteDeleteCallback, RemoteDeleteEvent,
+    SyncAdapter, SyncConflictCallback, SyncConflictEvent, SyncErrorCallback, SyncErrorEvent,
+    SyncErrorKind, SyncManagerOptions, SyncPhase, SyncProgress, SyncProgressCallback, SyncResult,
+    SyncTransport, SyncTransportError,
 };
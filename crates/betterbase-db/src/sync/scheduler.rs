@@ -98,6 +98,8 @@ impl SyncScheduler {
     /// Uses a separate throttle slot from per-collection `schedule_sync` calls.
     /// Concurrent `schedule_sync("x")` and `schedule_sync_all()` will both run
     /// (SyncManager's per-collection locks prevent data races).
+    ///
+    /// Each cycle ends with [`purge_tombstones`](Self::purge_tombstones).
     pub async fn schedule_sync_all(&self) -> Result<SyncResult, String> {
         self.check_disposed()?;
         let sm = self.sync_manager.clone();
@@ -112,12 +114,25 @@ impl SyncScheduler {
                     merged.merged += r.merged;
                     merged.errors.extend(r.errors.clone());
                 }
+                // Freshly pushed deletes are now acknowledged; drop the
+                // ones past their collection's retention
+                sm.purge_tombstones().await;
                 merged
             }
         })
         .await
     }
 
+    /// Purge acknowledged tombstones for every collection with a
+    /// `tombstone_retention` (see [`SyncManager::purge_tombstones`]).
+    ///
+    /// Not throttled: purges are local-only and cheap when nothing expired,
+    /// so hosts can call this on whatever timer suits them.
+    pub async fn purge_tombstones(&self) -> Result<HashMap<String, usize>, String> {
+        self.check_disposed()?;
+        Ok(self.sync_manager.purge_tombstones().await)
+    }
+
     /// Bypass throttle and run sync immediately.
     pub async fn flush(&self, def: &CollectionDef) -> SyncResult {
         self.sync_manager.sync(def).await
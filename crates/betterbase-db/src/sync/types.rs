@@ -4,6 +4,8 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use betterbase_crypto::UCANPermission;
+use betterbase_sync_core::{MembershipState, SpaceDeletePolicy};
 use serde_json::Value;
 
 use crate::{
@@ -12,7 +14,7 @@ use crate::{
     storage::traits::StorageSync,
     types::{
         ApplyRemoteOptions, ApplyRemoteResult, BatchResult, DeleteConflictStrategyName,
-        PushSnapshot, RemoteRecord,
+        MergeConflict, PushQueueState, PushSnapshot, RemoteRecord,
     },
 };
 
@@ -105,6 +107,23 @@ pub trait SyncAdapter: Send + Sync {
     ) -> Result<ApplyRemoteResult>;
     fn get_last_sequence(&self, collection: &str) -> Result<i64>;
     fn set_last_sequence(&self, collection: &str, sequence: i64) -> Result<()>;
+
+    /// Load persisted push retry state. Adapters without durable meta storage
+    /// can keep the default, which disables cross-restart backoff.
+    fn get_push_state(&self, _collection: &str) -> Result<Option<PushQueueState>> {
+        Ok(None)
+    }
+
+    /// Persist push retry state; `None` clears it.
+    fn set_push_state(&self, _collection: &str, _state: Option<&PushQueueState>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Drop acknowledged tombstones past `def.tombstone_retention`. Adapters
+    /// that don't keep tombstones can keep the default no-op.
+    fn purge_expired_tombstones(&self, _def: &CollectionDef) -> Result<usize> {
+        Ok(0)
+    }
 }
 
 /// Blanket implementation: any type implementing `StorageSync + Send + Sync`
@@ -147,6 +166,18 @@ impl<T: StorageSync + Send + Sync> SyncAdapter for T {
     fn set_last_sequence(&self, collection: &str, sequence: i64) -> Result<()> {
         StorageSync::set_last_sequence(self, collection, sequence)
     }
+
+    fn get_push_state(&self, collection: &str) -> Result<Option<PushQueueState>> {
+        StorageSync::get_push_state(self, collection)
+    }
+
+    fn set_push_state(&self, collection: &str, state: Option<&PushQueueState>) -> Result<()> {
+        StorageSync::set_push_state(self, collection, state)
+    }
+
+    fn purge_expired_tombstones(&self, def: &CollectionDef) -> Result<usize> {
+        StorageSync::purge_expired_tombstones(self, def)
+    }
 }
 
 // ============================================================================
@@ -159,9 +190,11 @@ pub struct OutboundRecord {
     pub id: String,
     /// Current schema version (JS: `_v`)
     pub version: u32,
-    /// CRDT binary for live records, `None` for tombstones
+    /// CRDT binary for live and archived records, `None` for tombstones
     pub crdt: Option<Vec<u8>>,
     pub deleted: bool,
+    /// Archival delete: other devices hide the record instead of tombstoning it
+    pub archived: bool,
     /// Last-known server sequence (0 for new records)
     pub sequence: i64,
     pub meta: Option<Value>,
@@ -254,6 +287,9 @@ pub struct SyncProgress {
     pub collection: String,
     pub processed: usize,
     pub total: usize,
+    /// When the collection may next contact the transport (ms since epoch),
+    /// if it is backing off after failed pushes or pulls
+    pub next_retry_at: Option<i64>,
 }
 
 /// Fired when a remote tombstone deletes a record that had local data.
@@ -264,6 +300,13 @@ pub struct RemoteDeleteEvent {
     pub previous_data: Option<Value>,
 }
 
+/// Fired when a pull merges a field both devices wrote concurrently.
+#[derive(Debug, Clone)]
+pub struct SyncConflictEvent {
+    pub collection: String,
+    pub conflict: MergeConflict,
+}
+
 // ============================================================================
 // SyncManager Options
 // ============================================================================
@@ -277,6 +320,9 @@ pub type SyncProgressCallback = dyn Fn(&SyncProgress) + Send + Sync;
 /// Callback type for remote delete events.
 pub type RemoteDeleteCallback = dyn Fn(&RemoteDeleteEvent) + Send + Sync;
 
+/// Callback type for merge conflict events.
+pub type SyncConflictCallback = dyn Fn(&SyncConflictEvent) + Send + Sync;
+
 /// Configuration for `SyncManager`.
 pub struct SyncManagerOptions {
     pub transport: Arc<dyn SyncTransport>,
@@ -294,4 +340,84 @@ pub struct SyncManagerOptions {
     pub on_progress: Option<Arc<SyncProgressCallback>>,
     /// Called when a remote tombstone deletes a local record
     pub on_remote_delete: Option<Arc<RemoteDeleteCallback>>,
+    /// Called for each field both sides wrote concurrently during a merge
+    pub on_conflict: Option<Arc<SyncConflictCallback>>,
+    /// Collection names to sync first, in order. Remaining collections follow
+    /// alphabetically; unknown names are ignored.
+    pub collection_priority: Vec<String>,
+    /// Retry schedule after a failed push or pull (default:
+    /// `PushBackoff::default()`)
+    pub push_backoff: Option<PushBackoff>,
+    /// Space delete policy. Outbound deletes take the kind it allows
+    /// `permission`, and pulled tombstones are applied as archives unless
+    /// `membership` shows only admins can have sent them (default: deletes
+    /// propagate as written)
+    pub delete_policy: Option<SpaceDeletePolicy>,
+    /// The local member's permission in the space (default: admin)
+    pub permission: Option<UCANPermission>,
+    /// The space's membership, replayed from its verified log. Tombstones
+    /// don't say who sent them, so under `delete_policy` the authors are
+    /// assumed to hold `MembershipState::least_writer_permission` (default:
+    /// unknown, so every pulled tombstone archives)
+    pub membership: Option<MembershipState>,
+}
+
+/// Exponential backoff applied to a collection after its push or pull
+/// transport calls fail.
+///
+/// The delay after the n-th consecutive failure is
+/// `base_ms * multiplier^(n-1)`, capped at `max_ms`, then shortened by a
+/// random fraction of up to `jitter` so clients that failed together don't
+/// retry together. Only transient and capacity failures back off; permanent
+/// and auth failures are left to the caller. The first successful push or
+/// pull for the collection resets it, and each collection backs off
+/// independently. The schedule is persisted as the collection's
+/// [`PushQueueState`], so it survives a restart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PushBackoff {
+    pub base_ms: u64,
+    pub max_ms: u64,
+    /// Growth factor per failure; values below 1 are treated as 1
+    pub multiplier: f64,
+    /// Fraction of the delay that may be cut at random, clamped to `0..=1`
+    pub jitter: f64,
+}
+
+impl Default for PushBackoff {
+    fn default() -> Self {
+        Self {
+            base_ms: 1_000,
+            max_ms: 60_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl PushBackoff {
+    /// Delay before the next attempt after `attempts` consecutive failures,
+    /// before jitter.
+    pub fn delay_ms(&self, attempts: u32) -> u64 {
+        let exponent = i32::try_from(attempts.saturating_sub(1)).unwrap_or(i32::MAX);
+        let delay = self.base_ms as f64 * self.multiplier.max(1.0).powi(exponent);
+        if delay >= self.max_ms as f64 {
+            self.max_ms
+        } else {
+            delay as u64
+        }
+    }
+
+    /// `delay_ms(attempts)` with jitter applied: somewhere in
+    /// `[delay * (1 - jitter), delay]`.
+    pub fn jittered_delay_ms(&self, attempts: u32) -> u64 {
+        let delay = self.delay_ms(attempts);
+        let jitter = if self.jitter.is_nan() {
+            0.0
+        } else {
+            self.jitter.clamp(0.0, 1.0)
+        };
+        // The leading 48 bits of a v4 UUID are random → uniform in [0, 1)
+        let unit = (uuid::Uuid::new_v4().as_u128() >> 80) as f64 / (1u64 << 48) as f64;
+        delay.saturating_sub((delay as f64 * jitter * unit) as u64)
+    }
 }
@@ -1,8 +1,13 @@
 use std::sync::Arc;
 
+use betterbase_crypto::UCANPermission;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// How a delete propagates to other devices; shared with sync-core's space
+/// delete policy, so the caller picks the kind for its own role.
+pub use betterbase_sync_core::DeleteKind;
+
 /// Stored record — the shape kept in the persistence layer.
 /// `data` is the materialized JSON (from model.view()) for queryability.
 /// `crdt` is the source of truth — full json-joy Model binary.
@@ -20,6 +25,9 @@ pub struct StoredRecord {
     pub dirty: bool,
     pub deleted: bool,
     pub deleted_at: Option<String>, // ISO string (JS has Date | null)
+    /// Hidden by an archival delete; data and CRDT stay live
+    #[serde(default)]
+    pub archived: bool,
     pub meta: Option<Value>,
 }
 
@@ -36,6 +44,8 @@ pub struct SerializedRecord {
     pub dirty: bool,
     pub deleted: bool,
     pub deleted_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
     pub meta: Option<Value>,
     pub computed: Option<Value>, // computed index values
 }
@@ -54,6 +64,8 @@ pub struct StoredRecordWithMeta {
     pub dirty: bool,
     pub deleted: bool,
     pub deleted_at: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
     pub meta: Option<Value>,
     // migration metadata
     pub was_migrated: bool,
@@ -67,16 +79,36 @@ pub struct RemoteRecord {
     pub version: u32,
     pub crdt: Option<Vec<u8>>,
     pub deleted: bool,
+    /// Archival delete: `crdt` is still present but the record is hidden
+    /// from default reads. Ignored when `deleted` is set.
+    #[serde(default)]
+    pub archived: bool,
     pub sequence: i64,
     pub meta: Option<Value>,
 }
 
 /// Error associated with a specific record
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RecordError {
     pub id: String,
     pub collection: String,
     pub error: String,
+    /// Position of the failing record in the input of a bulk write.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_index: Option<usize>,
+    /// Set when the write collided with a unique index.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unique: Option<UniqueConflict>,
+}
+
+/// Details of a unique-constraint violation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniqueConflict {
+    pub index: String,
+    /// Indexed fields and the values that collided, in index order.
+    pub fields: serde_json::Map<String, Value>,
+    /// Id of the record already holding these values.
+    pub existing_id: String,
 }
 
 /// Batch result from multi-record operations
@@ -123,6 +155,9 @@ pub struct ApplyRemoteResult {
     pub new_sequence: i64,
     /// Number of records that required CRDT merge (dirty local + live remote)
     pub merged_count: usize,
+    /// Fields both sides wrote concurrently during those merges
+    #[serde(default)]
+    pub conflicts: Vec<MergeConflict>,
 }
 
 /// Individual record result from applying remote changes
@@ -135,6 +170,24 @@ pub struct ApplyRemoteRecordResult {
     pub previous_data: Option<Value>,
 }
 
+/// A field written by both the local pending patches and the incoming remote
+/// state. The CRDT keeps the later write (logical time, then session ID).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeConflict {
+    pub id: String,
+    /// Dotted path of the field, e.g. `"address.city"`
+    pub path: String,
+    pub local_value: Value,
+    pub remote_value: Value,
+    pub winner: ConflictWinner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictWinner {
+    Local,
+    Remote,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RemoteAction {
     Inserted,
@@ -149,6 +202,22 @@ pub enum RemoteAction {
 pub struct PushSnapshot {
     pub pending_patches_length: usize,
     pub deleted: bool,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+/// Persisted retry state for one collection's push and pull transport calls,
+/// kept in backend meta so a restarted `SyncManager` resumes the backoff
+/// schedule instead of resetting it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushQueueState {
+    /// Record ids of the batch that last failed; pushed first on retry
+    #[serde(default)]
+    pub batch_ids: Vec<String>,
+    /// Consecutive failed push or pull attempts
+    pub attempts: u32,
+    /// Unix epoch millis before which no push is attempted
+    pub next_attempt_at: Option<i64>,
 }
 
 /// Migration tracking status
@@ -161,6 +230,18 @@ pub struct MigrationStatus {
     pub complete: bool,
 }
 
+/// Who is performing an operation, checked against a collection's
+/// `read_predicate` / `write_predicate`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationContext {
+    /// DID of the acting user; `None` for an anonymous or system caller.
+    pub actor_did: Option<String>,
+}
+
+/// Closure type for row-level access rules: may the actor in the context
+/// see (or write) a record with this data?
+pub type AccessPredicateFn = dyn Fn(&OperationContext, &Value) -> bool + Send + Sync;
+
 /// Closure type for deciding whether to reset sync state on metadata change.
 pub type ShouldResetSyncStateFn = dyn Fn(Option<&Value>, &Value) -> bool + Send + Sync;
 
@@ -221,6 +302,8 @@ pub struct PutOptions {
     pub meta: Option<Value>,
     /// Middleware hook: returns true → sequence resets to 0, pending_patches cleared.
     pub should_reset_sync_state: Option<Arc<ShouldResetSyncStateFn>>,
+    /// Actor checked against the collection's write predicate
+    pub context: OperationContext,
 }
 
 impl std::fmt::Debug for PutOptions {
@@ -234,6 +317,7 @@ impl std::fmt::Debug for PutOptions {
                 "should_reset_sync_state",
                 &self.should_reset_sync_state.as_ref().map(|_| "..."),
             )
+            .field("context", &self.context)
             .finish()
     }
 }
@@ -246,6 +330,7 @@ impl Clone for PutOptions {
             skip_unique_check: self.skip_unique_check,
             meta: self.meta.clone(),
             should_reset_sync_state: self.should_reset_sync_state.clone(),
+            context: self.context.clone(),
         }
     }
 }
@@ -259,6 +344,8 @@ pub struct PatchOptions {
     pub meta: Option<Value>,
     /// Middleware hook: returns true → sequence resets to 0, pending_patches cleared.
     pub should_reset_sync_state: Option<Arc<ShouldResetSyncStateFn>>,
+    /// Actor checked against the collection's write predicate
+    pub context: OperationContext,
 }
 
 impl std::fmt::Debug for PatchOptions {
@@ -272,6 +359,7 @@ impl std::fmt::Debug for PatchOptions {
                 "should_reset_sync_state",
                 &self.should_reset_sync_state.as_ref().map(|_| "..."),
             )
+            .field("context", &self.context)
             .finish()
     }
 }
@@ -284,6 +372,7 @@ impl Clone for PatchOptions {
             skip_unique_check: self.skip_unique_check,
             meta: self.meta.clone(),
             should_reset_sync_state: self.should_reset_sync_state.clone(),
+            context: self.context.clone(),
         }
     }
 }
@@ -295,6 +384,18 @@ pub struct DeleteOptions {
     pub session_id: Option<u64>,
     /// Middleware metadata to merge onto the tombstone
     pub meta: Option<Value>,
+    /// Tombstone (default) or archive the record
+    pub kind: DeleteKind,
+    /// Actor checked against the collection's write predicate
+    pub context: OperationContext,
+}
+
+/// Options for touch() operation
+#[derive(Debug, Clone, Default)]
+pub struct TouchOptions {
+    pub session_id: Option<u64>,
+    /// Middleware metadata to merge onto the record
+    pub meta: Option<Value>,
 }
 
 /// Options for get() operation
@@ -302,15 +403,23 @@ pub struct DeleteOptions {
 pub struct GetOptions {
     /// If true, return deleted (tombstoned) records too
     pub include_deleted: bool,
+    /// If true, return archived records too
+    #[serde(default)]
+    pub include_archived: bool,
     /// If false, return raw data without migration (default: true = migrate)
     pub migrate: bool,
+    /// Actor checked against the collection's read predicate
+    #[serde(default)]
+    pub context: OperationContext,
 }
 
 impl Default for GetOptions {
     fn default() -> Self {
         Self {
             include_deleted: false,
+            include_archived: false,
             migrate: true,
+            context: OperationContext::default(),
         }
     }
 }
@@ -319,8 +428,13 @@ impl Default for GetOptions {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ListOptions {
     pub include_deleted: bool,
+    #[serde(default)]
+    pub include_archived: bool,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    /// Actor checked against the collection's read predicate
+    #[serde(default)]
+    pub context: OperationContext,
 }
 
 /// Options for purge_tombstones
@@ -330,12 +444,56 @@ pub struct PurgeTombstonesOptions {
     pub older_than_seconds: Option<u64>,
     /// Dry run (count but don't delete)
     pub dry_run: bool,
+    /// Keep tombstones whose deletion hasn't been pushed yet
+    #[serde(default)]
+    pub skip_dirty: bool,
+}
+
+/// Options for `Adapter::compact`. `None` leaves that part alone.
+#[derive(Debug, Clone, Default)]
+pub struct CompactOptions {
+    /// Hard-remove synced tombstones deleted longer ago than this.
+    pub drop_tombstones_older_than: Option<std::time::Duration>,
+    /// Trim each synced record's edit chain (`meta._editChain`) to its
+    /// newest N entries. Trimmed chains are never pushed.
+    pub trim_history_keep_last: Option<usize>,
+}
+
+/// Options for `Adapter::reset_collection`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResetOptions {
+    /// Also clear the collection's pull cursor, so the next pull fetches it
+    /// from the start.
+    #[serde(default)]
+    pub reset_sync_state: bool,
+}
+
+/// What `Adapter::compact` reclaimed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactReport {
+    pub tombstones_removed: usize,
+    pub histories_trimmed: usize,
+    pub history_entries_dropped: usize,
+    /// Bytes the backend gave back, if it can tell.
+    pub bytes_reclaimed: Option<u64>,
+}
+
+/// What `Adapter::migrate_all` rewrote. Failed records keep their stored
+/// version, so the next run (or a lazy read) tries them again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Ids now stored at the current schema version.
+    pub migrated: Vec<String>,
+    /// Records whose migration failed, with the error.
+    pub failed: Vec<RecordError>,
 }
 
 /// Options for scan_raw backend method
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ScanOptions {
     pub include_deleted: bool,
+    #[serde(default)]
+    pub include_archived: bool,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
@@ -351,6 +509,18 @@ pub struct RawBatchResult {
 pub struct ApplyRemoteOptions {
     pub delete_conflict_strategy: Option<DeleteConflictStrategyName>,
     pub received_at: Option<String>, // ISO timestamp
+    /// The space policy's delete kind for writers
+    /// (`SpaceDeletePolicy::writer_delete`); `None` applies remote deletes
+    /// as sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub writer_delete: Option<DeleteKind>,
+    /// Permission assumed for the authors of remote tombstones, worked out
+    /// from the space's verified membership log
+    /// (`MembershipState::least_writer_permission`). With `writer_delete`
+    /// set, a tombstone is applied only when this is admin and archives the
+    /// record otherwise, including when it is unknown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_permission: Option<UCANPermission>,
 }
 
 /// Serializable name-only version of `DeleteConflictStrategy` (no closure variant).
@@ -383,6 +553,7 @@ mod tests {
             dirty: false,
             deleted: false,
             deleted_at: None,
+            archived: false,
             meta: None,
         };
         assert_eq!(r.id, "x");
@@ -435,6 +606,16 @@ mod tests {
         let opts = GetOptions::default();
         assert!(opts.migrate, "migrate should default to true");
         assert!(!opts.include_deleted);
+        assert!(!opts.include_archived);
+    }
+
+    #[test]
+    fn delete_kind_defaults_to_tombstone() {
+        assert_eq!(DeleteOptions::default().kind, DeleteKind::Tombstone);
+        assert_eq!(
+            serde_json::to_value(DeleteKind::Archive).unwrap(),
+            serde_json::json!("archive")
+        );
     }
 
     #[test]
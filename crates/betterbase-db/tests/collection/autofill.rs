@@ -4,7 +4,9 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use betterbase_db::{
-    collection::autofill::{autofill, autofill_for_update, generate_uuid, AutofillOptions},
+    collection::autofill::{
+        autofill, autofill_for_update, generate_uuid, AutofillOptions, IdStrategy,
+    },
     schema::node::{created_at_schema, key_schema, t, updated_at_schema, SchemaNode},
 };
 use serde_json::json;
@@ -47,6 +49,63 @@ fn generates_unique_values() {
     assert_eq!(uuids.len(), 100);
 }
 
+// ============================================================================
+// IdStrategy
+// ============================================================================
+
+fn mint_many(strategy: IdStrategy, n: usize) -> Vec<String> {
+    (0..n).map(|_| strategy.mint(&json!({}))).collect()
+}
+
+#[test]
+fn uuid7_ids_are_v7_unique_and_time_ordered() {
+    let re =
+        regex::Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-7[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}$")
+            .unwrap();
+    let ids = mint_many(IdStrategy::Uuid7, 100);
+    for id in &ids {
+        assert!(re.is_match(id), "Not a valid UUIDv7: {id}");
+    }
+    let unique: std::collections::HashSet<&String> = ids.iter().collect();
+    assert_eq!(unique.len(), 100);
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(sorted, ids);
+}
+
+#[test]
+fn nanoid_ids_have_requested_length_and_url_safe_alphabet() {
+    let re = regex::Regex::new(r"^[A-Za-z0-9_-]+$").unwrap();
+    for len in [1, 10, 21, 40] {
+        let ids = mint_many(IdStrategy::Nanoid(len), 100);
+        for id in &ids {
+            assert_eq!(id.len(), len, "{id}");
+            assert!(re.is_match(id), "Not URL-safe: {id}");
+        }
+        if len >= 10 {
+            let unique: std::collections::HashSet<&String> = ids.iter().collect();
+            assert_eq!(unique.len(), 100);
+        }
+    }
+}
+
+#[test]
+fn content_hash_is_stable_across_key_order_and_distinct_per_content() {
+    let a = IdStrategy::ContentHash.mint(&json!({ "name": "Ann", "tags": [{ "x": 1, "y": 2 }] }));
+    let b = IdStrategy::ContentHash.mint(&json!({ "tags": [{ "y": 2, "x": 1 }], "name": "Ann" }));
+    let c = IdStrategy::ContentHash.mint(&json!({ "name": "Bob", "tags": [{ "x": 1, "y": 2 }] }));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(a.len(), 32);
+    assert!(a.chars().all(|ch| ch.is_ascii_hexdigit()));
+}
+
+#[test]
+fn uuid4_is_the_default_strategy() {
+    assert_eq!(IdStrategy::default(), IdStrategy::Uuid4);
+}
+
 // ============================================================================
 // Key Field
 // ============================================================================
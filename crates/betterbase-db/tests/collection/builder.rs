@@ -3,7 +3,10 @@
 use std::collections::BTreeMap;
 
 use betterbase_db::{
-    collection::builder::{collection, get_version_schema, to_object_schema},
+    collection::{
+        autofill::IdStrategy,
+        builder::{collection, get_version_schema, to_object_schema},
+    },
     index::types::IndexableValue,
     schema::node::{t, SchemaNode},
 };
@@ -120,6 +123,14 @@ fn panics_on_non_sequential_version_numbers() {
         .v(3, schema(&[("name", t::string())]), Ok);
 }
 
+#[test]
+#[should_panic(expected = "Nanoid length for collection \"users\" must be positive")]
+fn panics_on_zero_length_nanoid_strategy() {
+    collection("users")
+        .v(1, schema(&[("name", t::string())]))
+        .id_strategy(IdStrategy::Nanoid(0));
+}
+
 #[test]
 #[should_panic(expected = "Collection name cannot be empty")]
 fn panics_on_empty_collection_name() {
@@ -379,6 +390,27 @@ fn can_index_auto_fields() {
     assert_eq!(coll.indexes.len(), 2);
 }
 
+#[test]
+fn unique_declares_named_compound_unique_index() {
+    let coll = collection("members")
+        .v(1, schema(&[("orgId", t::string()), ("email", t::string())]))
+        .unique(&["orgId", "email"], "org_email_unique")
+        .build();
+
+    let idx = &coll.indexes[0];
+    assert_eq!(idx.name(), "org_email_unique");
+    assert!(idx.unique());
+    assert!(!idx.sparse());
+}
+
+#[test]
+#[should_panic(expected = "unknown field")]
+fn unique_rejects_unknown_field() {
+    collection("members")
+        .v(1, schema(&[("email", t::string())]))
+        .unique(&["orgId", "email"], "org_email_unique");
+}
+
 // ============================================================================
 // get_version_schema and to_object_schema
 // ============================================================================
@@ -1,3 +1,7 @@
 mod index {
+    #[cfg(feature = "sqlite")]
+    mod geo;
     mod planner;
+    #[cfg(feature = "sqlite")]
+    mod stats;
 }
@@ -0,0 +1,341 @@
+//! Tests for geohash indexes and `$geoBox` queries.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    index::{
+        geo::{cover_box, encode_geohash, GeoBox, MAX_GEO_CELLS},
+        planner::{explain_plan, plan_query},
+        types::{IndexDefinition, IndexScanType, IndexableValue},
+    },
+    query::{operators::matches_filter, types::Query},
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::PutOptions,
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn places_def(precision: usize) -> CollectionDef {
+    collection("places")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("name".to_string(), t::string());
+            s.insert("loc".to_string(), t::geopoint());
+            s
+        })
+        .geo_index("loc_geo", "loc", precision)
+        .build()
+}
+
+/// Build an initialized in-memory adapter over `places_def(6)`.
+fn setup() -> (Arc<CollectionDef>, Adapter<SqliteBackend>) {
+    let def = Arc::new(places_def(6));
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(&def))
+        .expect("adapter initialize");
+    (def, adapter)
+}
+
+fn put_opts() -> PutOptions {
+    PutOptions {
+        session_id: Some(MIN_SESSION_ID),
+        ..Default::default()
+    }
+}
+
+fn geo_box(min_lat: f64, min_lng: f64, max_lat: f64, max_lng: f64) -> Value {
+    json!({
+        "field": "loc",
+        "min_lat": min_lat,
+        "min_lng": min_lng,
+        "max_lat": max_lat,
+        "max_lng": max_lng,
+    })
+}
+
+fn query_names(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    filter: Value,
+) -> Vec<String> {
+    let query = Query {
+        filter: Some(filter),
+        ..Default::default()
+    };
+    let mut names: Vec<String> = adapter
+        .query(def, &query)
+        .expect("query")
+        .records
+        .iter()
+        .map(|r| r.data["name"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Deterministic point generator (64-bit LCG) so tests need no RNG crate.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_unit(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_unit()
+    }
+}
+
+// ============================================================================
+// Builder
+// ============================================================================
+
+#[test]
+fn geo_index_computes_geohash_of_point() {
+    let def = places_def(5);
+    let IndexDefinition::Computed(ci) = &def.indexes[0] else {
+        panic!("expected a computed index");
+    };
+    assert_eq!(ci.geo.as_ref().unwrap().precision, 5);
+    let value = (ci.compute)(&json!({ "loc": { "lat": 57.64911, "lng": 10.40744 } }));
+    assert_eq!(value, Some(IndexableValue::String("u4pru".to_string())));
+    assert_eq!(
+        (ci.compute)(&json!({ "loc": { "lat": 91.0, "lng": 0.0 } })),
+        None
+    );
+    assert_eq!((ci.compute)(&json!({ "name": "nowhere" })), None);
+}
+
+#[test]
+#[should_panic(expected = "precision")]
+fn geo_index_rejects_out_of_range_precision() {
+    places_def(13);
+}
+
+// ============================================================================
+// Planner
+// ============================================================================
+
+#[test]
+fn planner_uses_geo_index_with_exact_post_filter() {
+    let def = places_def(6);
+    let filter = json!({ "$geoBox": geo_box(37.70, -122.52, 37.82, -122.35) });
+    let plan = plan_query(Some(&filter), None, &def.indexes);
+
+    let scan = plan.scan.as_ref().expect("geo index selected");
+    assert_eq!(scan.index.name(), "loc_geo");
+    assert_eq!(scan.scan_type, IndexScanType::Range);
+    assert!(plan.union_scans.len() < MAX_GEO_CELLS);
+    assert_eq!(plan.post_filter, Some(filter));
+}
+
+#[test]
+fn planner_uses_single_in_scan_when_cover_matches_index_precision() {
+    let def = places_def(2);
+    let filter = json!({ "$geoBox": geo_box(37.70, -122.52, 37.82, -122.35) });
+    let plan = plan_query(Some(&filter), None, &def.indexes);
+
+    let scan = plan.scan.as_ref().unwrap();
+    assert!(plan.union_scans.is_empty());
+    assert!(scan.in_values.is_some());
+}
+
+#[test]
+fn planner_ignores_geo_box_on_other_field() {
+    let def = places_def(6);
+    let mut geo = geo_box(0.0, 0.0, 1.0, 1.0);
+    geo["field"] = json!("other");
+    let filter = json!({ "$geoBox": geo });
+    let plan = plan_query(Some(&filter), None, &def.indexes);
+    assert!(plan.scan.is_none());
+}
+
+#[test]
+fn explain_reports_union_scans() {
+    let def = places_def(8);
+    let filter = json!({ "$geoBox": geo_box(-1.0, 179.0, 1.0, -179.0) });
+    let plan = plan_query(Some(&filter), None, &def.indexes);
+    assert!(!plan.union_scans.is_empty());
+    assert!(explain_plan(&plan).contains("Union scans:"));
+}
+
+// ============================================================================
+// Covering
+// ============================================================================
+
+#[test]
+fn cover_never_exceeds_budget_for_random_boxes() {
+    let mut rng = Lcg(7);
+    for _ in 0..200 {
+        let lat = rng.range(-90.0, 90.0);
+        let lng = rng.range(-180.0, 180.0);
+        let b = GeoBox {
+            field: "loc".to_string(),
+            min_lat: lat,
+            min_lng: lng,
+            max_lat: lat + rng.range(0.0, 40.0),
+            max_lng: lng + rng.range(0.0, 90.0),
+        };
+        let cover = cover_box(&b, 9, MAX_GEO_CELLS).expect("cover");
+        assert!(cover.cells.len() <= MAX_GEO_CELLS);
+    }
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+#[test]
+fn geo_box_query_matches_brute_force() {
+    let (def, adapter) = setup();
+
+    let mut rng = Lcg(42);
+    let mut points = Vec::new();
+    for i in 0..400 {
+        // Cluster half the points so small boxes have hits.
+        let (lat, lng) = if i % 2 == 0 {
+            (rng.range(37.0, 38.5), rng.range(-123.0, -121.5))
+        } else {
+            (rng.range(-90.0, 90.0), rng.range(-180.0, 180.0))
+        };
+        let data = json!({ "name": format!("p{i}"), "loc": { "lat": lat, "lng": lng } });
+        adapter.put(&def, data.clone(), &put_opts()).unwrap();
+        points.push(data);
+    }
+
+    let boxes = [
+        geo_box(37.70, -122.52, 37.82, -122.35),
+        geo_box(37.0, -123.0, 38.5, -121.5),
+        geo_box(-30.0, -60.0, 10.0, 20.0),
+        geo_box(-10.0, 170.0, 10.0, -170.0),
+        geo_box(80.0, -20.0, 95.0, 20.0),
+        geo_box(-90.0, -180.0, 90.0, 180.0),
+    ];
+    for b in boxes {
+        let filter = json!({ "$geoBox": b });
+        let mut expected: Vec<String> = points
+            .iter()
+            .filter(|p| matches_filter(p, &filter).unwrap())
+            .map(|p| p["name"].as_str().unwrap().to_string())
+            .collect();
+        expected.sort();
+        assert_eq!(
+            query_names(&adapter, &def, filter.clone()),
+            expected,
+            "{filter}"
+        );
+    }
+}
+
+#[test]
+fn geo_box_query_across_antimeridian_and_pole() {
+    let (def, adapter) = setup();
+    for (name, lat, lng) in [
+        ("fiji", -17.7, 178.0),
+        ("samoa", -13.8, -172.1),
+        ("greenwich", 51.5, 0.0),
+        ("pole_near", 88.0, 5.0),
+        ("pole_far", 88.0, -175.0),
+        ("pole_side", 88.0, 90.0),
+    ] {
+        adapter
+            .put(
+                &def,
+                json!({ "name": name, "loc": { "lat": lat, "lng": lng } }),
+                &put_opts(),
+            )
+            .unwrap();
+    }
+
+    assert_eq!(
+        query_names(
+            &adapter,
+            &def,
+            json!({ "$geoBox": geo_box(-20.0, 175.0, -10.0, -170.0) })
+        ),
+        vec!["fiji", "samoa"]
+    );
+    // 93°N along lng 0..10 reaches 87°N along lng -180..-170.
+    assert_eq!(
+        query_names(
+            &adapter,
+            &def,
+            json!({ "$geoBox": geo_box(85.0, 0.0, 93.0, 10.0) })
+        ),
+        vec!["pole_far", "pole_near"]
+    );
+}
+
+#[test]
+fn geo_box_combines_with_field_conditions_and_count() {
+    let (def, adapter) = setup();
+    for (name, lat, lng) in [("a", 10.0, 10.0), ("b", 10.1, 10.1), ("c", 50.0, 50.0)] {
+        adapter
+            .put(
+                &def,
+                json!({ "name": name, "loc": { "lat": lat, "lng": lng } }),
+                &put_opts(),
+            )
+            .unwrap();
+    }
+    let filter = json!({ "name": "b", "$geoBox": geo_box(9.0, 9.0, 11.0, 11.0) });
+    assert_eq!(query_names(&adapter, &def, filter.clone()), vec!["b"]);
+
+    let count = adapter
+        .count(
+            &def,
+            Some(&Query {
+                filter: Some(json!({ "$geoBox": geo_box(9.0, 9.0, 11.0, 11.0) })),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn invalid_geo_box_is_a_query_error() {
+    let (def, adapter) = setup();
+    adapter
+        .put(
+            &def,
+            json!({ "name": "a", "loc": { "lat": 0.0, "lng": 0.0 } }),
+            &put_opts(),
+        )
+        .unwrap();
+    let query = Query {
+        filter: Some(json!({ "$geoBox": { "field": "loc" } })),
+        ..Default::default()
+    };
+    assert!(adapter.query(&def, &query).is_err());
+}
+
+#[test]
+fn prime_meridian_separates_first_level_cells() {
+    let cells: BTreeSet<String> = [(0.0, 0.0), (0.0, -0.0001)]
+        .iter()
+        .map(|(lat, lng)| encode_geohash(*lat, *lng, 1))
+        .collect();
+    // The prime meridian separates the first-level cells "s" and "e".
+    assert_eq!(cells, BTreeSet::from(["e".to_string(), "s".to_string()]));
+}
@@ -5,7 +5,7 @@ use betterbase_db::index::types::{
     ComputedIndex, FieldIndex, IndexDefinition, IndexField, IndexScanType, IndexSortOrder,
     IndexableValue,
 };
-use betterbase_db::query::types::{SortDirection, SortEntry};
+use betterbase_db::query::types::{NullsOrder, SortDirection, SortEntry};
 use serde_json::json;
 use std::sync::Arc;
 
@@ -39,6 +39,7 @@ fn computed_index(
         compute: Arc::new(compute),
         unique,
         sparse,
+        geo: None,
     })
 }
 
@@ -46,6 +47,7 @@ fn sort_entry(field: &str, direction: SortDirection) -> SortEntry {
     SortEntry {
         field: field.to_string(),
         direction,
+        nulls: None,
     }
 }
 
@@ -159,6 +161,43 @@ fn extract_ne_and_nin_go_to_residual() {
     assert!(residual.get("role").is_some());
 }
 
+#[test]
+fn extract_exists_and_regex_go_to_residual() {
+    let filter = json!({
+        "email": {"$exists": true},
+        "name": {"$regex": "^al", "$options": "i"},
+        "nick": {"$ne": null},
+    });
+    let conds = extract_conditions(Some(&filter));
+    assert!(conds.equalities.is_empty() && conds.ranges.is_empty());
+    let residual = conds.residual.as_ref().unwrap();
+    assert_eq!(residual, &filter);
+
+    // `$exists: true` also matches stored nulls, so only the others are
+    // non-null conditions
+    assert!(!conds.non_null.contains("email"));
+    assert!(conds.non_null.contains("name"));
+    assert!(conds.non_null.contains("nick"));
+}
+
+#[test]
+fn extract_mixed_operator_object_keeps_residual() {
+    let filter = json!({"name": {"$gte": "a", "$regex": "^ab"}, "age": {"$gt": 1, "$lt": 9}});
+    let conds = extract_conditions(Some(&filter));
+    assert!(conds.ranges.contains_key("name"));
+    assert!(conds.ranges.contains_key("age"));
+    // The range covers `name`, but `$regex` must still be post-filtered
+    let residual = conds.residual.as_ref().unwrap();
+    assert_eq!(residual.get("name"), filter.get("name"));
+    assert!(residual.get("age").is_none());
+
+    let indexes = vec![field_index("idx_name", &["name"], false, false)];
+    let plan = plan_query(Some(&filter), None, &indexes);
+    assert_eq!(plan.scan.as_ref().unwrap().index.name(), "idx_name");
+    let post = plan.post_filter.unwrap();
+    assert_eq!(post.get("name"), filter.get("name"));
+}
+
 #[test]
 fn extract_empty_filter() {
     let conds = extract_conditions(None);
@@ -211,10 +250,59 @@ fn extract_mixed_dollar_and_non_dollar_keys_treated_as_direct_value() {
     assert!(conds.residual.is_some());
 }
 
+#[test]
+fn extract_box_expands_to_lat_lng_ranges() {
+    let filter = json!({"location": {"$box": [[10.0, 20.0], [11.0, 21.0]]}});
+    let conds = extract_conditions(Some(&filter));
+
+    let lat = conds.ranges.get("location.lat").unwrap();
+    assert_eq!(lat.0.as_ref().unwrap().value, IndexableValue::Number(10.0));
+    assert_eq!(lat.1.as_ref().unwrap().value, IndexableValue::Number(11.0));
+    assert!(lat.0.as_ref().unwrap().inclusive && lat.1.as_ref().unwrap().inclusive);
+
+    let lng = conds.ranges.get("location.lng").unwrap();
+    assert_eq!(lng.0.as_ref().unwrap().value, IndexableValue::Number(20.0));
+    assert_eq!(lng.1.as_ref().unwrap().value, IndexableValue::Number(21.0));
+
+    // The exact box is still checked after the scan
+    assert!(conds.residual.unwrap().get("location").is_some());
+}
+
+#[test]
+fn extract_malformed_box_is_residual_only() {
+    let filter = json!({"location": {"$box": [[10.0, 20.0]]}});
+    let conds = extract_conditions(Some(&filter));
+    assert!(conds.ranges.is_empty());
+    assert!(conds.residual.unwrap().get("location").is_some());
+}
+
 // ============================================================================
 // planQuery — index selection
 // ============================================================================
 
+#[test]
+fn plan_box_uses_compound_lat_lng_index_as_range() {
+    let indexes = vec![
+        field_index("status", &["status"], false, false),
+        field_index("location", &["location.lat", "location.lng"], false, false),
+    ];
+    let filter = json!({"location": {"$box": [[10.0, 20.0], [11.0, 21.0]]}});
+    let plan = plan_query(Some(&filter), None, &indexes);
+
+    let scan = plan.scan.as_ref().unwrap();
+    assert_eq!(scan.index.name(), "location");
+    assert_eq!(scan.scan_type, IndexScanType::Range);
+    assert_eq!(
+        scan.range_lower.as_ref().unwrap().value,
+        IndexableValue::Number(10.0)
+    );
+    assert_eq!(
+        scan.range_upper.as_ref().unwrap().value,
+        IndexableValue::Number(11.0)
+    );
+    assert_eq!(plan.post_filter, Some(filter));
+}
+
 #[test]
 fn plan_unique_exact_match_best_cost() {
     let indexes = vec![
@@ -297,6 +385,35 @@ fn plan_computed_index_for_computed_filter() {
     assert_eq!(plan.scan.as_ref().unwrap().scan_type, IndexScanType::Exact);
 }
 
+#[test]
+fn plan_non_null_condition_scans_sparse_index() {
+    let indexes = vec![
+        field_index("idx_nick", &["nick"], false, true),
+        field_index("idx_name", &["name"], false, false),
+    ];
+
+    for filter in [
+        json!({"nick": {"$ne": null}}),
+        json!({"nick": {"$regex": "^a"}}),
+    ] {
+        let plan = plan_query(Some(&filter), None, &indexes);
+        let scan = plan.scan.as_ref().expect("index scan");
+        assert_eq!(scan.index.name(), "idx_nick");
+        assert_eq!(scan.scan_type, IndexScanType::Range);
+        assert!(scan.range_lower.is_none() && scan.range_upper.is_none());
+        assert_eq!(plan.post_filter.as_ref(), Some(&filter));
+    }
+
+    // Non-sparse indexes hold null keys; `$exists` matches nulls
+    for filter in [
+        json!({"name": {"$ne": null}}),
+        json!({"nick": {"$exists": true}}),
+    ] {
+        let plan = plan_query(Some(&filter), None, &indexes);
+        assert!(plan.scan.is_none(), "{filter}");
+    }
+}
+
 // ============================================================================
 // $in handling
 // ============================================================================
@@ -335,6 +452,138 @@ fn plan_equality_prefix_with_in() {
     assert_eq!(in_vals.len(), 2);
 }
 
+#[test]
+fn plan_in_then_range_splits_into_one_range_per_value() {
+    let indexes = vec![field_index(
+        "status_created",
+        &["status", "createdAt"],
+        false,
+        false,
+    )];
+    let filter = json!({
+        "status": {"$in": ["a", "b"]},
+        "createdAt": {"$gte": "2024-01-01"}
+    });
+    let plan = plan_query(Some(&filter), None, &indexes);
+
+    let scans: Vec<_> = plan.scan.iter().chain(plan.union_scans.iter()).collect();
+    assert_eq!(scans.len(), 2);
+    for (scan, status) in scans.iter().zip(["a", "b"]) {
+        assert_eq!(scan.index.name(), "status_created");
+        assert_eq!(scan.scan_type, IndexScanType::Range);
+        assert!(scan.in_values.is_none());
+        assert_eq!(
+            scan.equality_values.as_deref(),
+            Some(&[IndexableValue::String(status.to_string())][..])
+        );
+        let lower = scan.range_lower.as_ref().unwrap();
+        assert_eq!(
+            lower.value,
+            IndexableValue::String("2024-01-01".to_string())
+        );
+        assert!(lower.inclusive);
+        assert!(scan.range_upper.is_none());
+    }
+    assert!(plan.post_filter.is_none(), "both fields are covered");
+}
+
+#[test]
+fn plan_in_then_range_does_not_claim_index_sort() {
+    let indexes = vec![field_index(
+        "status_created",
+        &["status", "createdAt"],
+        false,
+        false,
+    )];
+    let filter = json!({
+        "status": {"$in": ["a", "b"]},
+        "createdAt": {"$gte": "2024-01-01"}
+    });
+    let sort = vec![sort_entry("createdAt", SortDirection::Asc)];
+    let plan = plan_query(Some(&filter), Some(&sort), &indexes);
+    assert_eq!(plan.union_scans.len(), 1);
+    assert!(!plan.index_provides_sort);
+    assert!(plan.post_sort.is_some());
+}
+
+// ============================================================================
+// $or index union
+// ============================================================================
+
+#[test]
+fn plan_or_on_unique_index_unions_exact_scans() {
+    let indexes = vec![field_index("email", &["email"], true, false)];
+    let filter = json!({"$or": [{"email": "a@x.com"}, {"email": "b@x.com"}]});
+    let sort = vec![sort_entry("email", SortDirection::Asc)];
+    let plan = plan_query(Some(&filter), Some(&sort), &indexes);
+
+    let scan = plan.scan.as_ref().expect("union plan");
+    assert_eq!(scan.scan_type, IndexScanType::Exact);
+    assert_eq!(
+        scan.equality_values,
+        Some(vec![IndexableValue::String("a@x.com".to_string())])
+    );
+    assert_eq!(plan.union_scans.len(), 1);
+    assert_eq!(
+        plan.union_scans[0].equality_values,
+        Some(vec![IndexableValue::String("b@x.com".to_string())])
+    );
+    assert_eq!(plan.estimated_cost, 2.0);
+    assert_eq!(plan.post_filter, Some(filter));
+    assert!(!plan.index_provides_sort);
+    assert!(plan.post_sort.is_some());
+}
+
+#[test]
+fn plan_or_unions_scans_on_different_indexes() {
+    let indexes = vec![
+        field_index("email", &["email"], true, false),
+        field_index("handle", &["handle"], true, false),
+    ];
+    let filter = json!({"$or": [{"email": "a@x.com"}, {"handle": "ann"}]});
+    let plan = plan_query(Some(&filter), None, &indexes);
+    assert_eq!(plan.scan.as_ref().unwrap().index.name(), "email");
+    assert_eq!(plan.union_scans[0].index.name(), "handle");
+
+    let output = explain_plan(&plan);
+    assert!(output.contains("Union scans: 1"));
+    assert!(output.contains("Union indexes: email, handle"));
+}
+
+#[test]
+fn plan_or_with_unindexed_branch_falls_back_to_full_scan() {
+    let indexes = vec![field_index("email", &["email"], true, false)];
+    let filter = json!({"$or": [{"email": "a@x.com"}, {"name": "Ann"}]});
+    let plan = plan_query(Some(&filter), None, &indexes);
+    assert!(plan.scan.is_none());
+    assert_eq!(plan.post_filter, Some(filter));
+}
+
+#[test]
+fn plan_or_loses_to_a_cheaper_single_index() {
+    let indexes = vec![
+        field_index("email", &["email"], true, false),
+        field_index("name", &["name"], false, false),
+    ];
+    let filter = json!({
+        "email": "a@x.com",
+        "$or": [{"name": "Ann"}, {"name": "Bea"}]
+    });
+    let plan = plan_query(Some(&filter), None, &indexes);
+    assert_eq!(plan.scan.as_ref().unwrap().index.name(), "email");
+    assert!(plan.union_scans.is_empty());
+    assert_eq!(plan.estimated_cost, 1.0);
+}
+
+#[test]
+fn plan_or_costlier_than_full_scan_is_not_used() {
+    let indexes = vec![field_index("name", &["name"], false, false)];
+    let filter = json!({"$or": [{"name": "Ann"}, {"name": "Bea"}]});
+    let plan = plan_query(Some(&filter), None, &indexes);
+    assert!(plan.scan.is_none());
+    assert_eq!(plan.estimated_cost, 6.0);
+}
+
 // ============================================================================
 // Sort handling
 // ============================================================================
@@ -369,6 +618,29 @@ fn plan_sort_only_uses_index_when_sort_matches() {
     assert!(plan.estimated_cost < 6.0);
 }
 
+#[test]
+fn plan_explicit_default_nulls_still_uses_index_sort() {
+    let indexes = vec![field_index("age", &["age"], false, false)];
+    let sort = vec![SortEntry {
+        nulls: Some(NullsOrder::Last),
+        ..sort_entry("age", SortDirection::Asc)
+    }];
+    let plan = plan_query(None, Some(&sort), &indexes);
+    assert!(plan.index_provides_sort);
+}
+
+#[test]
+fn plan_non_default_nulls_falls_back_to_post_sort() {
+    let indexes = vec![field_index("age", &["age"], false, false)];
+    let sort = vec![SortEntry {
+        nulls: Some(NullsOrder::First),
+        ..sort_entry("age", SortDirection::Asc)
+    }];
+    let plan = plan_query(None, Some(&sort), &indexes);
+    assert!(!plan.index_provides_sort);
+    assert!(plan.post_sort.is_some());
+}
+
 #[test]
 fn plan_no_index_for_sort_when_field_mismatched() {
     let indexes = vec![field_index("status", &["status"], false, false)];
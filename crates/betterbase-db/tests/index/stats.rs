@@ -0,0 +1,231 @@
+//! Tests for sampled index statistics and stats-aware range planning.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    index::{
+        planner::{plan_query, plan_query_with_stats, QueryPlan},
+        stats::IndexStats,
+        types::{IndexableValue, RangeBound},
+    },
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageWrite},
+    },
+    types::PutOptions,
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn events_def() -> CollectionDef {
+    collection("events")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("age".to_string(), t::number());
+            s.insert("score".to_string(), t::number());
+            s
+        })
+        .index(&["age"])
+        .index(&["score"])
+        .build()
+}
+
+fn setup() -> (Arc<CollectionDef>, Adapter<SqliteBackend>) {
+    let def = Arc::new(events_def());
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(&def))
+        .expect("adapter initialize");
+    (def, adapter)
+}
+
+/// Deterministic generator (64-bit LCG) so tests need no RNG crate.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next_unit(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// 400 records: `age` uniform in [0, 100); `score` in [0, 100) except for a
+/// ~5% tail of huge outliers, so min/max interpolation badly misjudges it.
+fn setup_skewed() -> (Arc<CollectionDef>, Adapter<SqliteBackend>) {
+    let (def, adapter) = setup();
+    let mut rng = Lcg(7);
+    let records: Vec<Value> = (0..400)
+        .map(|_| {
+            let age = (rng.next_unit() * 100.0).floor();
+            let score = if rng.next_unit() < 0.05 {
+                1_000_000.0 * (1.0 + rng.next_unit())
+            } else {
+                (rng.next_unit() * 100.0).floor()
+            };
+            json!({ "age": age, "score": score })
+        })
+        .collect();
+    adapter
+        .bulk_put(
+            &def,
+            records,
+            &PutOptions {
+                session_id: Some(MIN_SESSION_ID),
+                ..Default::default()
+            },
+        )
+        .expect("bulk_put");
+    (def, adapter)
+}
+
+fn gte(n: f64) -> RangeBound {
+    RangeBound {
+        value: IndexableValue::Number(n),
+        inclusive: true,
+    }
+}
+
+fn chosen_index(plan: &QueryPlan) -> &str {
+    plan.scan.as_ref().expect("index scan").index.name()
+}
+
+// ============================================================================
+// IndexStats
+// ============================================================================
+
+#[test]
+fn histogram_spans_min_to_max() {
+    let keys = (0..100).map(|n| IndexableValue::Number(n as f64)).collect();
+    let stats = IndexStats::from_keys("idx", 100, keys);
+    assert_eq!(stats.bounds.first(), Some(&IndexableValue::Number(0.0)));
+    assert_eq!(stats.bounds.last(), Some(&IndexableValue::Number(99.0)));
+    assert_eq!(stats.non_null_fraction, 1.0);
+
+    let half = stats.range_selectivity(Some(&gte(49.5)), None);
+    assert!((half - 0.5).abs() < 0.05, "{half}");
+    assert_eq!(stats.range_selectivity(Some(&gte(500.0)), None), 0.0);
+    assert_eq!(stats.range_selectivity(None, None), 1.0);
+}
+
+#[test]
+fn nulls_and_empty_samples() {
+    let keys = vec![IndexableValue::Number(1.0), IndexableValue::Number(2.0)];
+    let stats = IndexStats::from_keys("idx", 4, keys);
+    assert_eq!(stats.non_null_fraction, 0.5);
+    assert_eq!(stats.range_selectivity(None, None), 0.5);
+
+    let all_null = IndexStats::from_keys("idx", 3, Vec::new());
+    assert_eq!(all_null.range_selectivity(None, None), 0.0);
+
+    let unsampled = IndexStats::from_keys("idx", 0, Vec::new());
+    assert_eq!(unsampled.range_selectivity(Some(&gte(1.0)), None), 1.0);
+}
+
+#[test]
+fn string_keys_order_lexicographically() {
+    let keys = ["2024-01", "2024-02", "2024-03", "2024-04", "2025-01"]
+        .iter()
+        .map(|s| IndexableValue::String(s.to_string()))
+        .collect();
+    let stats = IndexStats::from_keys("idx", 5, keys);
+    let recent = RangeBound {
+        value: IndexableValue::String("2024-06".to_string()),
+        inclusive: true,
+    };
+    let all = RangeBound {
+        value: IndexableValue::String("2000".to_string()),
+        inclusive: true,
+    };
+    assert!(stats.range_selectivity(Some(&recent), None) < 0.5);
+    assert_eq!(stats.range_selectivity(Some(&all), None), 1.0);
+}
+
+#[test]
+fn from_sample_reads_bounded_sample() {
+    let (def, adapter) = setup_skewed();
+    let stats = adapter.sample_index_stats(&def, 40).unwrap();
+    assert_eq!(stats.len(), 2);
+    assert!(stats.iter().all(|s| s.sampled == 40));
+    assert_eq!(stats[0].index_name, "idx_age");
+    assert_eq!(stats[1].index_name, "idx_score");
+}
+
+#[test]
+fn from_sample_of_empty_collection_is_uninformative() {
+    let (def, adapter) = setup();
+    let stats = adapter.sample_index_stats(&def, 40).unwrap();
+    assert!(stats.iter().all(|s| s.sampled == 0 && s.bounds.is_empty()));
+    assert_eq!(stats[0].range_selectivity(Some(&gte(1.0)), None), 1.0);
+}
+
+#[test]
+fn sampled_histogram_sees_through_skew() {
+    let (def, adapter) = setup_skewed();
+    let stats = adapter.sample_index_stats(&def, 400).unwrap();
+    let score = &stats[1];
+
+    // Linear min/max interpolation would call this ~99% of the table
+    let tail = score.range_selectivity(Some(&gte(1000.0)), None);
+    assert!(tail < 0.2, "tail selectivity {tail}");
+    let body = score.range_selectivity(None, Some(&gte(1000.0)));
+    assert!(body > 0.8, "body selectivity {body}");
+}
+
+// ============================================================================
+// Planning with stats
+// ============================================================================
+
+#[test]
+fn narrow_sampled_range_is_ranked_cheaper() {
+    let (def, adapter) = setup_skewed();
+    let stats = adapter.sample_index_stats(&def, 400).unwrap();
+
+    // The score range is numerically huge but holds only the outlier tail;
+    // the age range holds ~90% of records
+    let filter = json!({"age": {"$gte": 10}, "score": {"$gte": 1000}});
+
+    let blind = plan_query(Some(&filter), None, &def.indexes);
+    assert_eq!(chosen_index(&blind), "idx_age");
+    assert_eq!(blind.estimated_cost, 5.0);
+
+    let informed = plan_query_with_stats(Some(&filter), None, &def.indexes, &stats);
+    assert_eq!(chosen_index(&informed), "idx_score");
+    assert!(informed.estimated_cost < 5.0);
+    assert_eq!(informed.post_filter, Some(json!({"age": {"$gte": 10}})));
+}
+
+#[test]
+fn wide_sampled_range_loses_to_narrow_one() {
+    let (def, adapter) = setup_skewed();
+    let stats = adapter.sample_index_stats(&def, 400).unwrap();
+
+    let filter = json!({"age": {"$gte": 95}, "score": {"$lt": 50}});
+    let plan = plan_query_with_stats(Some(&filter), None, &def.indexes, &stats);
+    assert_eq!(chosen_index(&plan), "idx_age");
+}
+
+#[test]
+fn stats_do_not_outrank_equality_scans() {
+    let (def, adapter) = setup_skewed();
+    let stats = adapter.sample_index_stats(&def, 400).unwrap();
+
+    let filter = json!({"age": 42, "score": {"$gte": 1000}});
+    let plan = plan_query_with_stats(Some(&filter), None, &def.indexes, &stats);
+    assert_eq!(chosen_index(&plan), "idx_age");
+    assert_eq!(plan.estimated_cost, 4.0);
+}
@@ -587,12 +587,14 @@ fn existing_observe_works_without_middleware() {
     let observed: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = adapter.observe(
-        Arc::new(todos_def()),
-        record.id.clone(),
-        Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = adapter
+        .observe(
+            Arc::new(todos_def()),
+            record.id.clone(),
+            Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     adapter.wait_for_flush();
 
@@ -624,12 +626,14 @@ fn observe_enriches_records_with_space_id() {
     let observed: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = typed.observe(
-        Arc::new(todos_def()),
-        id.to_string(),
-        Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = typed
+        .observe(
+            Arc::new(todos_def()),
+            id.to_string(),
+            Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     typed.wait_for_flush();
 
@@ -658,13 +662,15 @@ fn observe_query_enriches_records_with_space_id() {
         Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = typed.observe_query(
-        Arc::new(todos_def()),
-        betterbase_db::query::types::Query::default(),
-        Arc::new(move |result| obs_clone.lock().unwrap().push(result)),
-        None,
-        None,
-    );
+    let _unsub = typed
+        .observe_query(
+            Arc::new(todos_def()),
+            betterbase_db::query::types::Query::default(),
+            Arc::new(move |result| obs_clone.lock().unwrap().push(result)),
+            None,
+            None,
+        )
+        .expect("observe");
 
     typed.wait_for_flush();
 
@@ -706,7 +712,9 @@ fn delete_processes_middleware_write_options() {
             id,
             &GetOptions {
                 include_deleted: true,
+                include_archived: false,
                 migrate: true,
+                ..Default::default()
             },
         )
         .expect("get")
@@ -756,7 +764,9 @@ fn bulk_delete_processes_middleware_write_options() {
                 id,
                 &GetOptions {
                     include_deleted: true,
+                    include_archived: false,
                     migrate: true,
+                    ..Default::default()
                 },
             )
             .expect("get")
@@ -814,7 +824,9 @@ fn delete_many_processes_middleware_write_options() {
                 id,
                 &GetOptions {
                     include_deleted: true,
+                    include_archived: false,
                     migrate: true,
+                    ..Default::default()
                 },
             )
             .expect("get")
@@ -1175,13 +1187,15 @@ fn observe_query_filters_by_space() {
         Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = typed.observe_query(
-        Arc::new(todos_def()),
-        betterbase_db::query::types::Query::default(),
-        Arc::new(move |result| obs_clone.lock().unwrap().push(result)),
-        None,
-        Some(json!({"space": "space-1"})),
-    );
+    let _unsub = typed
+        .observe_query(
+            Arc::new(todos_def()),
+            betterbase_db::query::types::Query::default(),
+            Arc::new(move |result| obs_clone.lock().unwrap().push(result)),
+            None,
+            Some(json!({"space": "space-1"})),
+        )
+        .expect("observe");
 
     typed.wait_for_flush();
 
@@ -1215,12 +1229,14 @@ fn observe_fires_callback_when_record_updated() {
     let observed: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = typed.observe(
-        Arc::new(todos_def()),
-        id.clone(),
-        Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = typed
+        .observe(
+            Arc::new(todos_def()),
+            id.clone(),
+            Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     typed.wait_for_flush();
 
@@ -1260,12 +1276,14 @@ fn observe_fires_none_when_record_deleted() {
     let observed: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let obs_clone = Arc::clone(&observed);
 
-    let _unsub = typed.observe(
-        Arc::new(todos_def()),
-        id.clone(),
-        Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = typed
+        .observe(
+            Arc::new(todos_def()),
+            id.clone(),
+            Arc::new(move |data| obs_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     typed.wait_for_flush();
 
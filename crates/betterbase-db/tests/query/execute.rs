@@ -4,7 +4,8 @@ use betterbase_db::query::execute::{
     count_matching, execute_query, find_first, paginate_records, sort_records,
 };
 use betterbase_db::query::types::{
-    normalize_computed_filter, normalize_sort, Query, SortDirection, SortEntry, SortInput,
+    normalize_computed_filter, normalize_sort, NullsOrder, Query, SortDirection, SortEntry,
+    SortInput,
 };
 use serde_json::{json, Value};
 
@@ -22,6 +23,7 @@ fn sort_entry(field: &str, direction: SortDirection) -> SortEntry {
     SortEntry {
         field: field.to_string(),
         direction,
+        nulls: None,
     }
 }
 
@@ -77,6 +79,56 @@ fn sort_by_boolean_asc() {
     assert!(actives[2..].iter().all(|&b| b));
 }
 
+// ============================================================================
+// sort_records — null placement
+// ============================================================================
+
+fn ranked() -> Vec<Value> {
+    vec![
+        json!({"id": "a", "rank": 2}),
+        json!({"id": "b"}),
+        json!({"id": "c", "rank": 1}),
+        json!({"id": "d", "rank": null}),
+        json!({"id": "e", "rank": 3}),
+    ]
+}
+
+fn ranked_ids(direction: SortDirection, nulls: Option<NullsOrder>) -> Vec<String> {
+    let entry = SortEntry {
+        field: "rank".to_string(),
+        direction,
+        nulls,
+    };
+    sort_records(ranked(), &[entry, sort_entry("id", SortDirection::Asc)])
+        .iter()
+        .map(|r| r["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn nulls_default_last_for_asc_first_for_desc() {
+    assert_eq!(
+        ranked_ids(SortDirection::Asc, None),
+        ["c", "a", "e", "b", "d"]
+    );
+    assert_eq!(
+        ranked_ids(SortDirection::Desc, None),
+        ["b", "d", "e", "a", "c"]
+    );
+}
+
+#[test]
+fn explicit_nulls_order_overrides_direction_default() {
+    assert_eq!(
+        ranked_ids(SortDirection::Asc, Some(NullsOrder::First)),
+        ["b", "d", "c", "a", "e"]
+    );
+    assert_eq!(
+        ranked_ids(SortDirection::Desc, Some(NullsOrder::Last)),
+        ["e", "a", "c", "b", "d"]
+    );
+}
+
 // ============================================================================
 // sort_records — multi-field
 // ============================================================================
@@ -290,6 +342,7 @@ fn execute_query_filter_sort_offset_limit() {
         )])),
         offset: Some(1),
         limit: Some(1),
+        ..Default::default()
     };
     let result = execute_query(users(), &query).unwrap();
     // Active users sorted by name: Alice, Bob, Diana
@@ -584,10 +637,12 @@ fn normalize_sort_entries_unchanged() {
         SortEntry {
             field: "name".to_string(),
             direction: SortDirection::Desc,
+            nulls: None,
         },
         SortEntry {
             field: "age".to_string(),
             direction: SortDirection::Asc,
+            nulls: None,
         },
     ];
     let result = normalize_sort(Some(SortInput::Entries(entries.clone()))).unwrap();
@@ -875,6 +875,84 @@ fn regex_returns_false_for_non_string_value() {
     .unwrap());
 }
 
+#[test]
+fn regex_options_case_insensitive() {
+    let filter = json!({"name": {"$regex": "^alice$", "$options": "i"}});
+    assert!(matches_filter(&json!({"name": "ALICE"}), &filter).unwrap());
+    assert!(!matches_filter(&json!({"name": "Alicea"}), &filter).unwrap());
+}
+
+#[test]
+fn regex_options_multi_line_and_dot_all() {
+    let record = json!({"bio": "line one\nline two"});
+    assert!(!matches_filter(&record, &json!({"bio": {"$regex": "^line two"}})).unwrap());
+    assert!(matches_filter(
+        &record,
+        &json!({"bio": {"$regex": "^line two", "$options": "m"}})
+    )
+    .unwrap());
+    assert!(matches_filter(
+        &record,
+        &json!({"bio": {"$regex": "one.line", "$options": "s"}})
+    )
+    .unwrap());
+}
+
+#[test]
+fn regex_malformed_operands_return_error() {
+    let record = json!({"name": "Alice"});
+    for filter in [
+        json!({"name": {"$regex": "a", "$options": "q"}}),
+        json!({"name": {"$regex": "a", "$options": 1}}),
+        json!({"name": {"$regex": 42}}),
+        json!({"name": {"$options": "i"}}),
+    ] {
+        match matches_filter(&record, &filter) {
+            Err(LessDbError::Query(QueryError::InvalidRegex(_))) => {}
+            other => panic!("{filter}: expected InvalidRegex, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn regex_invalid_pattern_fails_without_records() {
+    // Compiled up front, so no record has to reach the condition
+    let filter = json!({"$or": [{"name": "Bob"}, {"name": {"$regex": "[invalid"}}]});
+    assert!(matches!(
+        filter_records(&[], &filter),
+        Err(LessDbError::Query(QueryError::InvalidRegex(_)))
+    ));
+    assert!(matches!(
+        filter_records(&[json!({"name": "Bob"})], &filter),
+        Err(LessDbError::Query(QueryError::InvalidRegex(_)))
+    ));
+}
+
+#[test]
+fn regex_in_logical_and_computed_filters() {
+    let records = vec![
+        json!({"name": "Alice"}),
+        json!({"name": "alfred"}),
+        json!({"name": "Bob"}),
+    ];
+    let filter = json!({"$or": [{"name": {"$regex": "^al", "$options": "i"}}, {"name": "Bob"}]});
+    assert_eq!(filter_records(&records, &filter).unwrap().len(), 3);
+    let filter = json!({"$not": {"name": {"$regex": "^A"}}});
+    assert_eq!(filter_records(&records, &filter).unwrap().len(), 2);
+
+    let computed = make_computed(&[("slug", json!("hello-world"))]);
+    let filter = make_computed(&[("slug", json!({"$regex": "^HELLO", "$options": "i"}))]);
+    assert!(matches_computed_filter(Some(&computed), &filter).unwrap());
+}
+
+#[test]
+fn regex_combined_with_exists() {
+    let filter = json!({"name": {"$exists": true, "$regex": "^A"}});
+    assert!(matches_filter(&json!({"name": "Alice"}), &filter).unwrap());
+    assert!(!matches_filter(&json!({"name": "Bob"}), &filter).unwrap());
+    assert!(!matches_filter(&json!({}), &filter).unwrap());
+}
+
 // ============================================================================
 // $size operator
 // ============================================================================
@@ -941,6 +1019,39 @@ fn all_uses_deep_equality_for_objects() {
     assert!(!matches_filter(&record, &json!({"items": {"$all": [{"id": 1}, {"id": 4}]}})).unwrap());
 }
 
+// ============================================================================
+// $box
+// ============================================================================
+
+#[test]
+fn box_matches_points_inside_inclusive_bounds() {
+    let filter = json!({"location": {"$box": [[10.0, 20.0], [11.0, 21.0]]}});
+    let at = |lat: f64, lng: f64| json!({"location": {"lat": lat, "lng": lng}});
+
+    assert!(matches_filter(&at(10.5, 20.5), &filter).unwrap());
+    assert!(matches_filter(&at(10.0, 21.0), &filter).unwrap());
+    assert!(!matches_filter(&at(9.9, 20.5), &filter).unwrap());
+    assert!(!matches_filter(&at(10.5, 21.1), &filter).unwrap());
+    assert!(!matches_filter(&json!({"location": null}), &filter).unwrap());
+    assert!(!matches_filter(&json!({}), &filter).unwrap());
+}
+
+#[test]
+fn box_rejects_malformed_operand() {
+    let point = json!({"location": {"lat": 1.0, "lng": 1.0}});
+    for operand in [
+        json!([[0.0, 0.0]]),
+        json!([[0.0, "x"], [1.0, 1.0]]),
+        json!([[2.0, 0.0], [1.0, 1.0]]),
+        json!({"min": [0, 0]}),
+    ] {
+        match matches_filter(&point, &json!({"location": {"$box": operand}})) {
+            Err(LessDbError::Query(QueryError::InvalidBox(_))) => {}
+            other => panic!("Expected InvalidBox for {operand}, got: {other:?}"),
+        }
+    }
+}
+
 // ============================================================================
 // filter_records
 // ============================================================================
@@ -1,6 +1,10 @@
 mod reactive {
     #[cfg(feature = "sqlite")]
     mod adapter;
+    #[cfg(feature = "sqlite")]
+    mod diagnostics;
     mod event_emitter;
+    #[cfg(feature = "sqlite")]
+    mod lifecycle;
     mod query_fields;
 }
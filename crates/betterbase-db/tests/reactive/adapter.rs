@@ -15,6 +15,7 @@ use betterbase_db::{
     },
     types::{
         ApplyRemoteOptions, DeleteOptions, GetOptions, PatchOptions, PutOptions, RemoteRecord,
+        ResetOptions, TouchOptions,
     },
 };
 use serde_json::{json, Value};
@@ -79,12 +80,14 @@ fn observe_fires_callback_after_flush_with_current_record() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        record.id.clone(),
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush();
 
@@ -102,12 +105,14 @@ fn observe_fires_none_for_nonexistent_record() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "does-not-exist",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "does-not-exist",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush();
 
@@ -133,12 +138,14 @@ fn observe_fires_after_put_to_same_id() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        record.id.clone(),
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial callback — "Bob"
 
@@ -188,12 +195,14 @@ fn observe_unsubscribe_stops_notifications() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let unsub = ra.observe(
-        Arc::new(users_def()),
-        record.id.clone(),
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial callback
     let count_after_initial = calls.lock().unwrap().len();
@@ -250,12 +259,14 @@ fn observe_query_fires_callback_after_flush_with_current_results() {
     let calls_clone = Arc::clone(&calls);
 
     let query = Query::default();
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        query,
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        None,
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            query,
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush();
 
@@ -276,12 +287,14 @@ fn observe_query_fires_after_write_to_same_collection() {
     let calls_clone = Arc::clone(&calls);
 
     let query = Query::default();
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        query,
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        None,
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            query,
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial: 0 records
 
@@ -313,12 +326,14 @@ fn observe_query_unsubscribe_stops_notifications() {
     let calls_clone = Arc::clone(&calls);
 
     let query = Query::default();
-    let unsub = ra.observe_query(
-        Arc::new(users_def()),
-        query,
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        None,
-    );
+    let unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            query,
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial
     let initial_count = calls.lock().unwrap().len();
@@ -441,6 +456,298 @@ fn on_change_unsubscribe_stops_events() {
     assert_eq!(events.lock().unwrap().len(), 1);
 }
 
+// ============================================================================
+// Transactions — tx_id grouping
+// ============================================================================
+
+#[test]
+fn transaction_events_share_one_tx_id() {
+    let def = users_def();
+    let ra = make_adapter(&def);
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    ra.transaction(|tx| {
+        let a = tx.put(
+            &def,
+            json!({ "name": "Judy", "email": "j@x.com" }),
+            &put_opts(),
+        )?;
+        tx.put(
+            &def,
+            json!({ "name": "Karl", "email": "k@x.com" }),
+            &put_opts(),
+        )?;
+        tx.delete(&def, &a.id, &DeleteOptions::default())?;
+        Ok(())
+    })
+    .expect("transaction");
+
+    let log = events.lock().unwrap();
+    assert_eq!(log.len(), 3);
+    assert!(matches!(log[2], ChangeEvent::Delete { .. }));
+    assert!(log.iter().all(|e| e.tx_id() == log[0].tx_id()));
+}
+
+#[test]
+fn separate_commits_get_increasing_tx_ids() {
+    let def = users_def();
+    let ra = make_adapter(&def);
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    for name in ["Liam", "Mia"] {
+        ra.put(
+            &def,
+            json!({ "name": name, "email": "x@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    }
+    ra.transaction(|tx| {
+        tx.put(
+            &def,
+            json!({ "name": "Noah", "email": "n@x.com" }),
+            &put_opts(),
+        )
+    })
+    .expect("transaction");
+
+    let ids: Vec<u64> = events.lock().unwrap().iter().map(|e| e.tx_id()).collect();
+    assert_eq!(ids.len(), 3);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]), "tx_ids: {ids:?}");
+}
+
+#[test]
+fn failed_transaction_rolls_back_and_emits_nothing() {
+    let def = users_def();
+    let ra = make_adapter(&def);
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    let result: betterbase_db::error::Result<()> = ra.transaction(|tx| {
+        tx.put(
+            &def,
+            json!({ "name": "Olga", "email": "o@x.com" }),
+            &put_opts(),
+        )?;
+        tx.patch(
+            &def,
+            json!({ "name": "nobody" }),
+            &PatchOptions {
+                id: "no-such-id".to_string(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )?;
+        Ok(())
+    });
+
+    assert!(result.is_err());
+    assert!(events.lock().unwrap().is_empty());
+    assert_eq!(ra.count(&def, None).expect("count"), 0);
+}
+
+// ============================================================================
+// Batch transactions — one Bulk per collection
+// ============================================================================
+
+fn unique_email_def() -> CollectionDef {
+    collection("users")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("name".to_string(), t::string());
+            s.insert("email".to_string(), t::string());
+            s
+        })
+        .index_with(&["email"], Some("idx_email"), true, false)
+        .build()
+}
+
+fn unique_email_adapter() -> (CollectionDef, ReactiveAdapter<SqliteBackend>) {
+    let def = unique_email_def();
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory SQLite");
+    backend.initialize(&[&def]).expect("backend initialize");
+    let mut ra = ReactiveAdapter::new(Adapter::new(backend));
+    ra.initialize(&[Arc::new(unique_email_def())])
+        .expect("reactive adapter initialize");
+    (def, ra)
+}
+
+#[test]
+fn batch_transaction_emits_one_bulk_per_collection() {
+    let (def, ra) = unique_email_adapter();
+    let existing = ra
+        .put(
+            &def,
+            json!({ "name": "Pia", "email": "p@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    let (a, b) = ra
+        .batch_transaction(|tx| {
+            tx.delete(&def, &existing.id, &DeleteOptions::default())?;
+            let a = tx.put(
+                &def,
+                json!({ "name": "Quin", "email": "q@x.com" }),
+                &put_opts(),
+            )?;
+            let b = tx.put(
+                &def,
+                json!({ "name": "Rae", "email": "r@x.com" }),
+                &put_opts(),
+            )?;
+            Ok((a, b))
+        })
+        .expect("batch");
+
+    let log = events.lock().unwrap();
+    assert_eq!(log.len(), 1);
+    match &log[0] {
+        ChangeEvent::Bulk {
+            collection, ids, ..
+        } => {
+            assert_eq!(collection, "users");
+            assert_eq!(ids, &vec![existing.id.clone(), a.id, b.id]);
+        }
+        other => panic!("expected one Bulk event, got {other:?}"),
+    }
+}
+
+#[test]
+fn batch_transaction_unique_violation_rolls_back_earlier_ops() {
+    let (def, ra) = unique_email_adapter();
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    let result: betterbase_db::error::Result<()> = ra.batch_transaction(|tx| {
+        tx.put(
+            &def,
+            json!({ "name": "Sam", "email": "s@x.com" }),
+            &put_opts(),
+        )?;
+        tx.put(
+            &def,
+            json!({ "name": "Tia", "email": "t@x.com" }),
+            &put_opts(),
+        )?;
+        tx.put(
+            &def,
+            json!({ "name": "Sal", "email": "s@x.com" }),
+            &put_opts(),
+        )?;
+        Ok(())
+    });
+
+    let err = result.expect_err("duplicate email must fail");
+    assert_eq!(err.code(), "STORAGE_UNIQUE");
+    assert!(events.lock().unwrap().is_empty());
+    assert_eq!(ra.count(&def, None).expect("count"), 0);
+}
+
+#[test]
+fn reset_collection_emits_one_bulk_with_removed_ids() {
+    let (def, ra) = unique_email_adapter();
+    let a = ra
+        .put(
+            &def,
+            json!({ "name": "Uma", "email": "u@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    let b = ra
+        .put(
+            &def,
+            json!({ "name": "Vic", "email": "v@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    let removed = ra
+        .reset_collection(&def, &ResetOptions::default())
+        .expect("reset");
+    assert_eq!(removed, 2);
+    assert_eq!(ra.count(&def, None).expect("count"), 0);
+
+    let log = events.lock().unwrap();
+    assert_eq!(log.len(), 1);
+    match &log[0] {
+        ChangeEvent::Bulk {
+            collection, ids, ..
+        } => {
+            assert_eq!(collection, "users");
+            let mut ids = ids.clone();
+            ids.sort();
+            let mut expected = vec![a.id, b.id];
+            expected.sort();
+            assert_eq!(ids, expected);
+        }
+        other => panic!("expected one Bulk event, got {other:?}"),
+    }
+}
+
+#[test]
+fn purge_expired_tombstones_emits_collection_bulk() {
+    let def = collection("users")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("name".to_string(), t::string());
+            s.insert("email".to_string(), t::string());
+            s
+        })
+        .tombstone_retention(std::time::Duration::ZERO)
+        .build();
+    let ra = make_adapter(&def);
+    let rec = ra
+        .put(
+            &def,
+            json!({ "name": "Wes", "email": "w@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    ra.delete(&def, &rec.id, &DeleteOptions::default())
+        .expect("delete");
+    ra.mark_synced(&def, &rec.id, 1, None).expect("mark_synced");
+    // Tombstones must be strictly older than the cutoff
+    std::thread::sleep(std::time::Duration::from_millis(5));
+
+    let events: Arc<Mutex<Vec<ChangeEvent>>> = make_log();
+    let events_clone = Arc::clone(&events);
+    let _unsub = ra.on_change(move |e| events_clone.lock().unwrap().push(e.clone()));
+
+    assert_eq!(ra.purge_expired_tombstones(&def).expect("purge"), 1);
+    assert_eq!(ra.purge_expired_tombstones(&def).expect("purge"), 0);
+
+    let log = events.lock().unwrap();
+    assert_eq!(log.len(), 1, "nothing emitted when nothing was purged");
+    match &log[0] {
+        ChangeEvent::Bulk {
+            collection, ids, ..
+        } => {
+            assert_eq!(collection, "users");
+            assert!(ids.is_empty());
+        }
+        other => panic!("expected one Bulk event, got {other:?}"),
+    }
+}
+
 // ============================================================================
 // Proxy — reads delegate to inner
 // ============================================================================
@@ -479,12 +786,14 @@ fn double_flush_is_safe_second_flush_is_no_op() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "some-id",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "some-id",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.flush(); // first flush — callback fires once
     let count = calls.lock().unwrap().len();
@@ -505,12 +814,14 @@ fn wait_for_flush_is_equivalent_to_flush() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "no-id",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "no-id",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush();
 
@@ -535,12 +846,14 @@ fn observe_before_initialize_fires_only_after_initialize_and_flush() {
     let calls_clone = Arc::clone(&calls);
 
     // Register before initialize — should NOT fire yet
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "test-id",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "test-id",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     assert!(
         calls.lock().unwrap().is_empty(),
@@ -570,12 +883,14 @@ fn unsubscribe_before_initialize_prevents_callback_from_ever_firing() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let unsub = ra.observe(
-        Arc::new(users_def()),
-        "some-id",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "some-id",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     // Unsubscribe before init
     unsub();
@@ -607,12 +922,14 @@ fn observe_query_before_initialize_fires_after_init() {
     let calls: Arc<Mutex<Vec<ReactiveQueryResult>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        Query::default(),
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        None,
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            Query::default(),
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            None,
+        )
+        .expect("observe");
 
     assert!(
         calls.lock().unwrap().is_empty(),
@@ -686,12 +1003,14 @@ fn panicking_on_change_does_not_prevent_flush() {
         )
         .expect("put should succeed");
 
-    let _unsub2 = ra.observe(
-        Arc::new(users_def()),
-        record.id.clone(),
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub2 = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
     ra.flush();
 
     // Observer should have received data despite on_change panicking
@@ -712,20 +1031,22 @@ fn reentrant_write_from_observe_callback_does_not_deadlock() {
     let rc = Arc::clone(&reentrant_calls);
 
     // Observer callback that writes back into the adapter
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "trigger-id",
-        Arc::new(move |_data| {
-            rc.lock().unwrap().push("callback".to_string());
-            // Re-entrant write: should not deadlock
-            let _ = ra_clone.put(
-                &users_def(),
-                json!({ "name": "Reentrant", "email": "re@x.com" }),
-                &put_opts(),
-            );
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "trigger-id",
+            Arc::new(move |_data| {
+                rc.lock().unwrap().push("callback".to_string());
+                // Re-entrant write: should not deadlock
+                let _ = ra_clone.put(
+                    &users_def(),
+                    json!({ "name": "Reentrant", "email": "re@x.com" }),
+                    &put_opts(),
+                );
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush(); // triggers callback which calls put() which calls flush() recursively
 
@@ -741,22 +1062,26 @@ fn panicking_observe_callback_does_not_prevent_subsequent_callbacks() {
     let ra = make_adapter(&def);
 
     // First observer panics
-    let _unsub1 = ra.observe(
-        Arc::new(users_def()),
-        "test-id",
-        Arc::new(|_data: Option<Value>| panic!("callback panic")),
-        None,
-    );
+    let _unsub1 = ra
+        .observe(
+            Arc::new(users_def()),
+            "test-id",
+            Arc::new(|_data: Option<Value>| panic!("callback panic")),
+            None,
+        )
+        .expect("observe");
 
     // Second observer should still fire
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
-    let _unsub2 = ra.observe(
-        Arc::new(users_def()),
-        "test-id",
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub2 = ra
+        .observe(
+            Arc::new(users_def()),
+            "test-id",
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     // Flush — first callback panics (caught by catch_unwind), second should still run.
     ra.flush();
@@ -782,14 +1107,16 @@ fn apply_remote_changes_notifies_observe_callback() {
     // Observe a specific ID
     let log: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let log_c = log.clone();
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "r1".to_string(),
-        Arc::new(move |val: Option<Value>| {
-            log_c.lock().unwrap().push(val);
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "r1".to_string(),
+            Arc::new(move |val: Option<Value>| {
+                log_c.lock().unwrap().push(val);
+            }),
+            None,
+        )
+        .expect("observe");
 
     // Initial flush gives None (record doesn't exist yet)
     ra.wait_for_flush();
@@ -811,6 +1138,7 @@ fn apply_remote_changes_notifies_observe_callback() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 100,
         meta: None,
     };
@@ -861,6 +1189,7 @@ fn apply_remote_changes_emits_remote_change_event() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 100,
         meta: None,
     };
@@ -878,7 +1207,10 @@ fn apply_remote_changes_emits_remote_change_event() {
         1,
         "should emit exactly one Remote change event"
     );
-    if let ChangeEvent::Remote { collection, ids } = &remote_events[0] {
+    if let ChangeEvent::Remote {
+        collection, ids, ..
+    } = &remote_events[0]
+    {
         assert_eq!(collection, "users");
         assert_eq!(ids, &vec!["r1".to_string()]);
     }
@@ -924,6 +1256,64 @@ fn patch_proxies_and_emits_change() {
     assert!(!events.is_empty(), "patch should emit a change event");
 }
 
+#[test]
+fn touch_emits_change_and_notifies_observers_with_same_fields() {
+    let def = users_def();
+    let ra = make_adapter(&def);
+
+    let record = ra
+        .put(
+            &def,
+            json!({ "name": "Alice", "email": "a@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+
+    let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let _unsub_observe = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
+    ra.flush();
+    calls.lock().unwrap().clear();
+
+    let events = make_log::<ChangeEvent>();
+    let events_clone = events.clone();
+    let _unsub = ra.on_change(Box::new(move |event: &ChangeEvent| {
+        events_clone.lock().unwrap().push(event.clone());
+    }));
+
+    let touch_opts = TouchOptions {
+        session_id: Some(SID),
+        ..Default::default()
+    };
+    let touched = ra.touch(&def, &record.id, &touch_opts).expect("touch");
+    assert!(touched.dirty);
+
+    let events = events.lock().unwrap();
+    assert_eq!(
+        *events,
+        vec![ChangeEvent::Put {
+            collection: "users".to_string(),
+            id: record.id.clone(),
+            tx_id: 2,
+        }]
+    );
+
+    let log = calls.lock().unwrap();
+    assert_eq!(log.len(), 1, "observer should fire once after touch");
+    let data = log[0].as_ref().expect("observer should receive the record");
+    assert_eq!(data["name"], json!("Alice"));
+    assert_eq!(data["email"], json!("a@x.com"));
+    assert_eq!(data["id"], record.data["id"]);
+    assert_eq!(data["createdAt"], record.data["createdAt"]);
+}
+
 #[test]
 fn bulk_patch_proxies_and_returns_results() {
     let def = users_def();
@@ -1164,12 +1554,14 @@ fn observe_fires_none_after_delete() {
     let calls: Arc<Mutex<Vec<Option<Value>>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        record.id.clone(),
-        Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            record.id.clone(),
+            Arc::new(move |data| calls_clone.lock().unwrap().push(data)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial callback — Some(data)
 
@@ -1216,12 +1608,14 @@ fn observe_query_count_decreases_after_delete() {
     let calls: Arc<Mutex<Vec<ReactiveQueryResult>>> = make_log();
     let calls_clone = Arc::clone(&calls);
 
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        Query::default(),
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        None,
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            Query::default(),
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            None,
+        )
+        .expect("observe");
 
     ra.wait_for_flush(); // initial: 2 records
 
@@ -1302,14 +1696,16 @@ fn observe_on_error_fires_on_failure() {
     let errors: Arc<Mutex<Vec<String>>> = make_log();
     let errors_clone = Arc::clone(&errors);
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        "some-id",
-        Arc::new(|_data| panic!("callback should not fire on error")),
-        Some(Arc::new(move |e: betterbase_db::error::LessDbError| {
-            errors_clone.lock().unwrap().push(e.to_string());
-        })),
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            "some-id",
+            Arc::new(|_data| panic!("callback should not fire on error")),
+            Some(Arc::new(move |e: betterbase_db::error::LessDbError| {
+                errors_clone.lock().unwrap().push(e.to_string());
+            })),
+        )
+        .expect("observe");
 
     // Initialize — this promotes pending subs and flushes
     // But inner adapter is not initialized, so get() will fail
@@ -1361,14 +1757,16 @@ fn observe_query_on_error_path_wired_up() {
     let errors: Arc<Mutex<Vec<String>>> = make_log();
     let errors_clone = Arc::clone(&errors);
 
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        Query::default(),
-        Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
-        Some(Arc::new(move |e: betterbase_db::error::LessDbError| {
-            errors_clone.lock().unwrap().push(e.to_string());
-        })),
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            Query::default(),
+            Arc::new(move |result| calls_clone.lock().unwrap().push(result)),
+            Some(Arc::new(move |e: betterbase_db::error::LessDbError| {
+                errors_clone.lock().unwrap().push(e.to_string());
+            })),
+        )
+        .expect("observe");
 
     ra.wait_for_flush();
 
@@ -1379,3 +1777,340 @@ fn observe_query_on_error_path_wired_up() {
         "on_error should not fire on successful query"
     );
 }
+
+// ============================================================================
+// observe_select — derived value from a record
+// ============================================================================
+
+#[test]
+fn observe_select_fires_only_when_selection_changes() {
+    let def = users_def();
+    let ra = make_adapter(&def);
+    let id = put_named(&ra, &def, "ann");
+
+    let calls: Arc<Mutex<Vec<Option<usize>>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let _unsub = ra
+        .observe_select(
+            Arc::new(users_def()),
+            id.clone(),
+            |record: Option<&Value>| record.and_then(|r| r["name"].as_str()).map(str::len),
+            Arc::new(move |len| calls_clone.lock().unwrap().push(len)),
+            None,
+        )
+        .expect("observe");
+    ra.wait_for_flush();
+    assert_eq!(*calls.lock().unwrap(), vec![Some(3)]);
+
+    // Unrelated field
+    ra.patch(
+        &def,
+        json!({ "email": "other@x.com" }),
+        &PatchOptions {
+            id: id.clone(),
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("patch email");
+    // Selector input changes, output doesn't
+    ra.patch(
+        &def,
+        json!({ "name": "bob" }),
+        &PatchOptions {
+            id: id.clone(),
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("patch same-length name");
+    assert_eq!(calls.lock().unwrap().len(), 1);
+
+    ra.patch(
+        &def,
+        json!({ "name": "alice" }),
+        &PatchOptions {
+            id: id.clone(),
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("patch name");
+    assert_eq!(*calls.lock().unwrap(), vec![Some(3), Some(5)]);
+
+    ra.delete(&def, &id, &DeleteOptions::default())
+        .expect("delete");
+    assert_eq!(*calls.lock().unwrap(), vec![Some(3), Some(5), None]);
+}
+
+// ============================================================================
+// observe_window — sorted window over a query
+// ============================================================================
+
+fn put_named(ra: &ReactiveAdapter<SqliteBackend>, def: &CollectionDef, name: &str) -> String {
+    ra.put(
+        def,
+        json!({ "name": name, "email": format!("{name}@x.com") }),
+        &put_opts(),
+    )
+    .expect("put")
+    .id
+}
+
+fn window_names(window: &betterbase_db::reactive::WindowResult) -> Vec<&str> {
+    window
+        .records
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn observe_window_shifts_on_insert_before_and_counts_insert_after() {
+    use betterbase_db::query::types::{Query, SortInput};
+    use betterbase_db::reactive::WindowResult;
+
+    let def = users_def();
+    let ra = make_adapter(&def);
+    for name in ["b", "d", "f", "h", "j"] {
+        put_named(&ra, &def, name);
+    }
+
+    let calls: Arc<Mutex<Vec<WindowResult>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let query = Query {
+        sort: Some(SortInput::Field("name".to_string())),
+        // Replaced by the window.
+        limit: Some(100),
+        ..Default::default()
+    };
+    let _unsub = ra
+        .observe_window(
+            Arc::new(users_def()),
+            query,
+            1,
+            2,
+            Arc::new(move |window| calls_clone.lock().unwrap().push(window)),
+            None,
+        )
+        .expect("observe");
+    ra.wait_for_flush();
+
+    {
+        let log = calls.lock().unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(window_names(&log[0]), vec!["d", "f"]);
+        assert_eq!(log[0].offset, 1);
+        assert_eq!(log[0].total, 5);
+    }
+
+    // Before the window: contents shift down by one.
+    put_named(&ra, &def, "a");
+    {
+        let log = calls.lock().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(window_names(&log[1]), vec!["b", "d"]);
+        assert_eq!(log[1].total, 6);
+    }
+
+    // After the window: same contents, new total.
+    let z = put_named(&ra, &def, "z");
+    {
+        let log = calls.lock().unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(window_names(&log[2]), vec!["b", "d"]);
+        assert_eq!(log[2].total, 7);
+    }
+
+    // Neither window nor total changes: no callback.
+    ra.put(
+        &def,
+        json!({ "id": z, "name": "zz", "email": "zz@x.com" }),
+        &put_opts(),
+    )
+    .expect("update past the window");
+    assert_eq!(calls.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn observe_window_near_the_end_is_short() {
+    use betterbase_db::query::types::{Query, SortInput};
+    use betterbase_db::reactive::WindowResult;
+
+    let def = users_def();
+    let ra = make_adapter(&def);
+    for name in ["a", "b", "c"] {
+        put_named(&ra, &def, name);
+    }
+
+    let calls: Arc<Mutex<Vec<WindowResult>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let _unsub = ra
+        .observe_window(
+            Arc::new(users_def()),
+            Query {
+                sort: Some(SortInput::Field("name".to_string())),
+                ..Default::default()
+            },
+            2,
+            10,
+            Arc::new(move |window| calls_clone.lock().unwrap().push(window)),
+            None,
+        )
+        .expect("observe");
+    ra.wait_for_flush();
+
+    let log = calls.lock().unwrap();
+    assert_eq!(window_names(&log[0]), vec!["c"]);
+    assert_eq!(log[0].total, 3);
+}
+
+// ============================================================================
+// observe_query_diff — incremental query deltas
+// ============================================================================
+
+#[test]
+fn observe_query_diff_reports_adds_removes_updates_and_moves() {
+    use betterbase_db::query::types::{Query, SortInput};
+    use betterbase_db::reactive::QueryDiff;
+
+    let def = users_def();
+    let ra = make_adapter(&def);
+    let a = put_named(&ra, &def, "a");
+    let c = put_named(&ra, &def, "c");
+    let e = put_named(&ra, &def, "e");
+
+    let calls: Arc<Mutex<Vec<QueryDiff>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let _unsub = ra
+        .observe_query_diff(
+            Arc::new(users_def()),
+            Query {
+                filter: Some(json!({ "email": { "$ne": "hidden" } })),
+                sort: Some(SortInput::Field("name".to_string())),
+                ..Default::default()
+            },
+            Arc::new(move |diff| calls_clone.lock().unwrap().push(diff)),
+            None,
+        )
+        .expect("observe");
+    ra.wait_for_flush();
+    let last = || calls.lock().unwrap().last().cloned().unwrap();
+    let patch = |id: &str, data: Value| {
+        ra.patch(
+            &def,
+            data,
+            &PatchOptions {
+                id: id.to_string(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+    };
+
+    // Initial snapshot arrives as additions
+    let diff = last();
+    assert_eq!(diff.added.len(), 3);
+    assert_eq!(diff.total, 3);
+
+    let b = put_named(&ra, &def, "b");
+    let diff = last();
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0]["id"], json!(b));
+    assert!(diff.removed.is_empty() && diff.updated.is_empty() && diff.moved.is_empty());
+    assert_eq!(diff.total, 4);
+
+    // In-place update
+    patch(&c, json!({ "email": "c2@x.com" }));
+    let diff = last();
+    assert_eq!(diff.updated.len(), 1);
+    assert_eq!(diff.updated[0].old["email"], json!("c@x.com"));
+    assert_eq!(diff.updated[0].new["email"], json!("c2@x.com"));
+    assert!(diff.added.is_empty() && diff.moved.is_empty());
+
+    // Sort key change: only the record that jumped is reported as moved
+    patch(&e, json!({ "name": "0e" }));
+    let diff = last();
+    assert_eq!(diff.updated.len(), 1);
+    assert_eq!(diff.moved, vec![e.clone()]);
+
+    // Leaving and re-entering the filter
+    patch(&a, json!({ "email": "hidden" }));
+    let diff = last();
+    assert_eq!(diff.removed, vec![a.clone()]);
+    assert!(diff.updated.is_empty());
+    assert_eq!(diff.total, 3);
+
+    patch(&a, json!({ "email": "a@x.com" }));
+    let diff = last();
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0]["id"], json!(a));
+    assert_eq!(diff.total, 4);
+
+    // A write outside the result changes nothing and is swallowed
+    let before = calls.lock().unwrap().len();
+    ra.put(&def, json!({ "name": "z", "email": "hidden" }), &put_opts())
+        .expect("put hidden");
+    assert_eq!(calls.lock().unwrap().len(), before);
+}
+
+// ============================================================================
+// observe_aggregate — live grouped metrics
+// ============================================================================
+
+#[test]
+fn observe_aggregate_updates_only_the_affected_group_once() {
+    use betterbase_db::query::aggregate::{AggregateRow, AggregateSpec, Metric};
+
+    let def = users_def();
+    let ra = make_adapter(&def);
+    put_named(&ra, &def, "a");
+    put_named(&ra, &def, "b");
+
+    let calls: Arc<Mutex<Vec<Vec<AggregateRow>>>> = make_log();
+    let calls_clone = Arc::clone(&calls);
+    let _unsub = ra
+        .observe_aggregate(
+            Arc::new(users_def()),
+            AggregateSpec {
+                group_by: vec!["name".to_string()],
+                metrics: vec![Metric::Count],
+                ..Default::default()
+            },
+            Some(json!({ "email": { "$ne": "hidden" } })),
+            Arc::new(move |rows| calls_clone.lock().unwrap().push(rows)),
+            None,
+        )
+        .expect("observe");
+    ra.wait_for_flush();
+    let counts = |rows: &[AggregateRow]| -> Vec<(Value, Option<f64>)> {
+        rows.iter()
+            .map(|r| (r.group[0].clone(), r.values[0]))
+            .collect()
+    };
+
+    assert_eq!(calls.lock().unwrap().len(), 1);
+    assert_eq!(
+        counts(&calls.lock().unwrap()[0]),
+        [(json!("a"), Some(1.0)), (json!("b"), Some(1.0))]
+    );
+
+    // A matching insert bumps only group "a", in one delivery
+    ra.put(
+        &def,
+        json!({ "name": "a", "email": "a2@x.com" }),
+        &put_opts(),
+    )
+    .expect("put");
+    assert_eq!(calls.lock().unwrap().len(), 2);
+    assert_eq!(
+        counts(&calls.lock().unwrap()[1]),
+        [(json!("a"), Some(2.0)), (json!("b"), Some(1.0))]
+    );
+
+    // Filtered-out records leave every metric alone, so nothing fires
+    ra.put(&def, json!({ "name": "a", "email": "hidden" }), &put_opts())
+        .expect("put hidden");
+    assert_eq!(calls.lock().unwrap().len(), 2);
+}
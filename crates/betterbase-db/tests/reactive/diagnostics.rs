@@ -0,0 +1,376 @@
+//! Integration tests for subscription diagnostics on `ReactiveAdapter`.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    error::LessDbError,
+    query::types::Query,
+    reactive::{
+        LabelCount, LifecycleEvent, ObserveOptions, ReactiveAdapter, StaleThreshold,
+        SubscriptionDiagnostics, SubscriptionKind, Unsubscribe,
+    },
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageWrite},
+    },
+    types::PutOptions,
+};
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn notes_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s
+            })
+            .build(),
+    )
+}
+
+fn make_adapter(def: &Arc<CollectionDef>) -> ReactiveAdapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory SQLite");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut ra = ReactiveAdapter::new(Adapter::new(backend));
+    ra.initialize(std::slice::from_ref(def))
+        .expect("reactive adapter initialize");
+    ra
+}
+
+fn put_note(ra: &ReactiveAdapter<SqliteBackend>, def: &CollectionDef, title: &str) -> String {
+    ra.put(
+        def,
+        json!({ "title": title }),
+        &PutOptions {
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("put")
+    .id
+}
+
+fn labeled(label: &str) -> ObserveOptions {
+    ObserveOptions {
+        label: Some(label.to_string()),
+    }
+}
+
+fn observe_labeled(
+    ra: &ReactiveAdapter<SqliteBackend>,
+    def: &Arc<CollectionDef>,
+    id: &str,
+    label: &str,
+) -> Unsubscribe {
+    ra.observe_with_options(def.clone(), id, Arc::new(|_| {}), None, &labeled(label))
+        .expect("observe")
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+#[test]
+fn report_tracks_subscribe_fire_and_unsubscribe() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    let id = put_note(&ra, &def, "Plan");
+
+    let record_unsub = observe_labeled(&ra, &def, &id, "Editor");
+    let query_unsub = ra
+        .observe_query_with_options(
+            def.clone(),
+            Query::default(),
+            Arc::new(|_| {}),
+            None,
+            &labeled("List"),
+        )
+        .expect("observe_query");
+
+    let report = ra.subscription_report();
+    assert_eq!(report.total(), 2);
+    let record = &report.subscriptions[0];
+    assert_eq!(record.kind, SubscriptionKind::Record);
+    assert_eq!(record.collection, "notes");
+    assert_eq!(record.record_id.as_deref(), Some(id.as_str()));
+    assert_eq!(record.label.as_deref(), Some("Editor"));
+    assert_eq!(record.fire_count, 0);
+    assert_eq!(record.last_fired_at_ms, None);
+    assert!(record.created_at_ms > 0);
+    let query = &report.subscriptions[1];
+    assert_eq!(query.kind, SubscriptionKind::Query);
+    assert_eq!(query.record_id, None);
+    assert_eq!(query.label.as_deref(), Some("List"));
+
+    // Both fire for the initial snapshot; a write elsewhere in the
+    // collection refires only the query.
+    ra.flush();
+    put_note(&ra, &def, "Other");
+    let report = ra.subscription_report();
+    let record = &report.subscriptions[0];
+    assert_eq!(record.fire_count, 1);
+    assert!(record.last_fired_at_ms.unwrap() >= record.created_at_ms);
+    assert_eq!(report.subscriptions[1].fire_count, 2);
+
+    record_unsub();
+    let report = ra.subscription_report();
+    assert_eq!(report.total(), 1);
+    assert_eq!(report.subscriptions[0].kind, SubscriptionKind::Query);
+
+    query_unsub();
+    assert_eq!(ra.subscription_report().total(), 0);
+}
+
+#[test]
+fn report_includes_subscriptions_made_before_initialize() {
+    let def = notes_def();
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory SQLite");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut ra = ReactiveAdapter::new(Adapter::new(backend));
+
+    let _unsub = observe_labeled(&ra, &def, "early", "Boot");
+    assert_eq!(ra.subscription_report().total(), 1);
+
+    ra.initialize(std::slice::from_ref(&def))
+        .expect("initialize");
+    let report = ra.subscription_report();
+    assert_eq!(report.total(), 1);
+    assert_eq!(report.subscriptions[0].fire_count, 1);
+}
+
+#[test]
+fn report_aggregates_by_label() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+
+    let mut unsubs: Vec<Unsubscribe> = (0..3)
+        .map(|i| observe_labeled(&ra, &def, &format!("n{i}"), "Row"))
+        .collect();
+    unsubs.push(observe_labeled(&ra, &def, "n9", "Header"));
+    unsubs.push(
+        ra.observe(def.clone(), "n10", Arc::new(|_| {}), None)
+            .expect("observe"),
+    );
+
+    let report = ra.subscription_report();
+    assert_eq!(
+        report.by_label,
+        vec![
+            LabelCount {
+                label: Some("Row".to_string()),
+                count: 3,
+            },
+            LabelCount {
+                label: None,
+                count: 1,
+            },
+            LabelCount {
+                label: Some("Header".to_string()),
+                count: 1,
+            },
+        ]
+    );
+
+    // Leaked rows disappear from the aggregate once cleaned up.
+    for unsub in unsubs.drain(..3) {
+        unsub();
+    }
+    let report = ra.subscription_report();
+    assert!(report
+        .by_label
+        .iter()
+        .all(|c| c.label.as_deref() != Some("Row")));
+}
+
+// ============================================================================
+// Staleness
+// ============================================================================
+
+type EventLog = Arc<Mutex<Vec<LifecycleEvent>>>;
+
+fn collect_stale(ra: &ReactiveAdapter<SqliteBackend>) -> (EventLog, Unsubscribe) {
+    let events: EventLog = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let unsub = ra.on_lifecycle(move |event| {
+        if matches!(event, LifecycleEvent::SubscriptionStale { .. }) {
+            sink.lock().unwrap().push(event.clone());
+        }
+    });
+    (events, unsub)
+}
+
+#[test]
+fn stale_subscription_is_reported_once() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    let id = put_note(&ra, &def, "Plan");
+    let _unsub = observe_labeled(&ra, &def, &id, "Editor");
+    ra.flush();
+
+    ra.set_subscription_diagnostics(SubscriptionDiagnostics {
+        stale: Some(StaleThreshold {
+            idle_ms: 0,
+            min_changes: 3,
+        }),
+        ..Default::default()
+    });
+    let (stale, _off) = collect_stale(&ra);
+
+    put_note(&ra, &def, "a");
+    put_note(&ra, &def, "b");
+    assert!(stale.lock().unwrap().is_empty());
+
+    put_note(&ra, &def, "c");
+    put_note(&ra, &def, "d");
+    let events = stale.lock().unwrap().clone();
+    assert_eq!(events.len(), 1, "reported once per quiet spell");
+    match &events[0] {
+        LifecycleEvent::SubscriptionStale {
+            collection,
+            label,
+            missed_changes,
+            ..
+        } => {
+            assert_eq!(collection, "notes");
+            assert_eq!(label.as_deref(), Some("Editor"));
+            assert_eq!(*missed_changes, 3);
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+
+    // The subscription is reported, not removed.
+    assert_eq!(ra.subscription_report().total(), 1);
+}
+
+#[test]
+fn firing_resets_staleness() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    let id = put_note(&ra, &def, "Plan");
+    let _unsub = observe_labeled(&ra, &def, &id, "Editor");
+    ra.flush();
+    ra.set_subscription_diagnostics(SubscriptionDiagnostics {
+        stale: Some(StaleThreshold {
+            idle_ms: 0,
+            min_changes: 2,
+        }),
+        ..Default::default()
+    });
+    let (stale, _off) = collect_stale(&ra);
+
+    put_note(&ra, &def, "a");
+    ra.put(
+        &def,
+        json!({ "id": id, "title": "Plan v2" }),
+        &PutOptions {
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("update observed record");
+    put_note(&ra, &def, "b");
+    assert!(stale.lock().unwrap().is_empty());
+
+    put_note(&ra, &def, "c");
+    assert_eq!(stale.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn stale_check_waits_for_idle_time() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    let id = put_note(&ra, &def, "Plan");
+    let _unsub = observe_labeled(&ra, &def, &id, "Editor");
+    ra.flush();
+    ra.set_subscription_diagnostics(SubscriptionDiagnostics {
+        stale: Some(StaleThreshold {
+            idle_ms: 3_600_000,
+            min_changes: 1,
+        }),
+        ..Default::default()
+    });
+    let (stale, _off) = collect_stale(&ra);
+
+    for i in 0..5 {
+        put_note(&ra, &def, &format!("n{i}"));
+    }
+    assert!(stale.lock().unwrap().is_empty());
+}
+
+// ============================================================================
+// Cap
+// ============================================================================
+
+#[test]
+fn cap_rejects_subscriptions_beyond_limit() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    ra.set_subscription_diagnostics(SubscriptionDiagnostics {
+        max_subscriptions: Some(2),
+        ..Default::default()
+    });
+
+    let first = observe_labeled(&ra, &def, "a", "Row");
+    let _second = ra
+        .observe_query_with_options(
+            def.clone(),
+            Query::default(),
+            Arc::new(|_| {}),
+            None,
+            &ObserveOptions::default(),
+        )
+        .expect("second subscription fits");
+
+    let err = ra
+        .observe_with_options(def.clone(), "c", Arc::new(|_| {}), None, &labeled("Row"))
+        .err()
+        .expect("third subscription is rejected");
+    assert!(matches!(err, LessDbError::SubscriptionLimit { cap: 2 }));
+    assert!(ra
+        .observe_query_with_options(
+            def.clone(),
+            Query::default(),
+            Arc::new(|_| {}),
+            None,
+            &ObserveOptions::default(),
+        )
+        .is_err());
+    assert_eq!(ra.subscription_report().total(), 2);
+
+    // Unsubscribing frees a slot.
+    first();
+    assert!(ra
+        .observe_with_options(def.clone(), "c", Arc::new(|_| {}), None, &labeled("Row"))
+        .is_ok());
+}
+
+#[test]
+fn observe_errors_at_cap() {
+    let def = notes_def();
+    let ra = make_adapter(&def);
+    ra.set_subscription_diagnostics(SubscriptionDiagnostics {
+        max_subscriptions: Some(0),
+        ..Default::default()
+    });
+    let err = ra
+        .observe(def.clone(), "a", Arc::new(|_| {}), None)
+        .err()
+        .expect("observe at the cap should fail");
+    assert!(matches!(err, LessDbError::SubscriptionLimit { cap: 0 }));
+}
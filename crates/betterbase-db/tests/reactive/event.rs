@@ -11,6 +11,7 @@ fn put_event_collection() {
     let event = ChangeEvent::Put {
         collection: "users".to_string(),
         id: "u1".to_string(),
+        tx_id: 1,
     };
     assert_eq!(event.collection(), "users");
 }
@@ -20,6 +21,7 @@ fn delete_event_collection() {
     let event = ChangeEvent::Delete {
         collection: "users".to_string(),
         id: "u1".to_string(),
+        tx_id: 1,
     };
     assert_eq!(event.collection(), "users");
 }
@@ -29,6 +31,7 @@ fn bulk_event_collection() {
     let event = ChangeEvent::Bulk {
         collection: "items".to_string(),
         ids: vec!["a".to_string(), "b".to_string()],
+        tx_id: 1,
     };
     assert_eq!(event.collection(), "items");
 }
@@ -38,6 +41,7 @@ fn remote_event_collection() {
     let event = ChangeEvent::Remote {
         collection: "docs".to_string(),
         ids: vec!["d1".to_string()],
+        tx_id: 1,
     };
     assert_eq!(event.collection(), "docs");
 }
@@ -51,6 +55,7 @@ fn put_event_ids() {
     let event = ChangeEvent::Put {
         collection: "users".to_string(),
         id: "u1".to_string(),
+        tx_id: 1,
     };
     assert_eq!(event.ids(), vec!["u1"]);
 }
@@ -60,6 +65,7 @@ fn delete_event_ids() {
     let event = ChangeEvent::Delete {
         collection: "users".to_string(),
         id: "u1".to_string(),
+        tx_id: 1,
     };
     assert_eq!(event.ids(), vec!["u1"]);
 }
@@ -69,6 +75,7 @@ fn bulk_event_ids() {
     let event = ChangeEvent::Bulk {
         collection: "items".to_string(),
         ids: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        tx_id: 1,
     };
     assert_eq!(event.ids(), vec!["a", "b", "c"]);
 }
@@ -78,6 +85,7 @@ fn remote_event_ids() {
     let event = ChangeEvent::Remote {
         collection: "docs".to_string(),
         ids: vec!["d1".to_string(), "d2".to_string()],
+        tx_id: 1,
     };
     assert_eq!(event.ids(), vec!["d1", "d2"]);
 }
@@ -91,10 +99,12 @@ fn events_are_eq() {
     let a = ChangeEvent::Put {
         collection: "x".to_string(),
         id: "1".to_string(),
+        tx_id: 1,
     };
     let b = ChangeEvent::Put {
         collection: "x".to_string(),
         id: "1".to_string(),
+        tx_id: 1,
     };
     assert_eq!(a, b);
 }
@@ -104,7 +114,41 @@ fn events_are_clone() {
     let event = ChangeEvent::Bulk {
         collection: "x".to_string(),
         ids: vec!["a".to_string()],
+        tx_id: 1,
     };
     let cloned = event.clone();
     assert_eq!(event, cloned);
 }
+
+// ============================================================================
+// tx_id() accessor
+// ============================================================================
+
+#[test]
+fn tx_id_accessor_covers_every_variant() {
+    let events = [
+        ChangeEvent::Put {
+            collection: "x".to_string(),
+            id: "1".to_string(),
+            tx_id: 7,
+        },
+        ChangeEvent::Delete {
+            collection: "x".to_string(),
+            id: "1".to_string(),
+            tx_id: 7,
+        },
+        ChangeEvent::Bulk {
+            collection: "x".to_string(),
+            ids: vec!["1".to_string()],
+            tx_id: 7,
+        },
+        ChangeEvent::Remote {
+            collection: "x".to_string(),
+            ids: vec!["1".to_string()],
+            tx_id: 7,
+        },
+    ];
+    for event in &events {
+        assert_eq!(event.tx_id(), 7);
+    }
+}
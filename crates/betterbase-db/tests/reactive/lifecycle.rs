@@ -0,0 +1,612 @@
+//! Integration tests for `ReactiveAdapter::on_lifecycle`.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::{self, MIN_SESSION_ID},
+    reactive::{ChangeEvent, LifecycleEvent, MaintenanceStats, ReactiveAdapter},
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{DeleteOptions, GetOptions, PurgeTombstonesOptions, PutOptions, SerializedRecord},
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn docs_def() -> CollectionDef {
+    collection("docs")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("title".to_string(), t::string());
+            s
+        })
+        .v(
+            2,
+            {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s.insert("body".to_string(), t::string());
+                s
+            },
+            |mut data| {
+                if let Value::Object(ref mut m) = data {
+                    m.entry("body").or_insert(Value::String(String::new()));
+                }
+                Ok(data)
+            },
+        )
+        .build()
+}
+
+fn notes_def() -> CollectionDef {
+    text_def("notes")
+}
+
+/// `notes` under another name, as a rename target.
+fn memos_def() -> CollectionDef {
+    text_def("memos")
+}
+
+fn text_def(name: &str) -> CollectionDef {
+    collection(name)
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("text".to_string(), t::string());
+            s
+        })
+        .index_with(&["text"], Some("by_text"), false, false)
+        .build()
+}
+
+fn put_notes(ra: &ReactiveAdapter<SqliteBackend>, def: &CollectionDef, count: usize) {
+    let put_opts = PutOptions {
+        session_id: Some(SID),
+        ..Default::default()
+    };
+    for i in 0..count {
+        ra.put(def, json!({ "text": format!("note {i}") }), &put_opts)
+            .expect("put");
+    }
+}
+
+fn make_uninitialized(defs: &[&CollectionDef]) -> ReactiveAdapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory SQLite");
+    backend.initialize(defs).expect("backend initialize");
+    ReactiveAdapter::new(Adapter::new(backend))
+}
+
+fn make_adapter() -> Arc<ReactiveAdapter<SqliteBackend>> {
+    let mut ra = make_uninitialized(&[&docs_def()]);
+    ra.initialize(&[Arc::new(docs_def())])
+        .expect("reactive adapter initialize");
+    Arc::new(ra)
+}
+
+/// Write a v1 "docs" record straight to the backend, bypassing migration.
+fn put_v1_doc(ra: &ReactiveAdapter<SqliteBackend>, id: &str, title: &str) {
+    let data = json!({
+        "id": id,
+        "title": title,
+        "createdAt": "2024-01-01T00:00:00Z",
+        "updatedAt": "2024-01-01T00:00:00Z",
+    });
+    let model = crdt::create_model(&data, SID).expect("create model");
+    let record = SerializedRecord {
+        id: id.to_string(),
+        collection: "docs".to_string(),
+        version: 1,
+        data,
+        crdt: crdt::model_to_binary(&model),
+        pending_patches: vec![],
+        sequence: 0,
+        dirty: false,
+        deleted: false,
+        deleted_at: None,
+        archived: false,
+        meta: None,
+        computed: None,
+    };
+    ra.with_backend(|b| b.put_raw(&record)).expect("put_raw");
+}
+
+fn collect_lifecycle(ra: &ReactiveAdapter<SqliteBackend>) -> Arc<Mutex<Vec<LifecycleEvent>>> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let log_clone = Arc::clone(&log);
+    // Leak the unsubscribe handle: listeners live for the whole test.
+    std::mem::forget(ra.on_lifecycle(move |event: &LifecycleEvent| {
+        log_clone.lock().unwrap().push(event.clone());
+    }));
+    log
+}
+
+// ============================================================================
+// CollectionRegistered
+// ============================================================================
+
+#[test]
+fn initialize_emits_collection_registered_per_collection() {
+    let docs = docs_def();
+    let notes = notes_def();
+    let mut ra = make_uninitialized(&[&docs, &notes]);
+    let events = collect_lifecycle(&ra);
+
+    ra.initialize(&[Arc::new(docs_def()), Arc::new(notes_def())])
+        .expect("initialize");
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            LifecycleEvent::CollectionRegistered {
+                collection: "docs".to_string(),
+                version: 2,
+                indexes: vec![],
+            },
+            LifecycleEvent::CollectionRegistered {
+                collection: "notes".to_string(),
+                version: 1,
+                indexes: vec!["by_text".to_string()],
+            },
+        ]
+    );
+}
+
+// ============================================================================
+// Index rebuild
+// ============================================================================
+
+#[test]
+fn reindex_emits_started_progress_and_finished_per_index() {
+    let def = notes_def();
+    let mut ra = make_uninitialized(&[&def]);
+    ra.initialize(&[Arc::new(notes_def())]).expect("initialize");
+    put_notes(&ra, &def, 2);
+    let events = collect_lifecycle(&ra);
+
+    assert_eq!(ra.reindex(&def).expect("reindex"), 2);
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0],
+        LifecycleEvent::IndexRebuildStarted {
+            collection: "notes".to_string(),
+            index: "by_text".to_string(),
+        }
+    );
+    assert_eq!(
+        events[1],
+        LifecycleEvent::IndexRebuildProgress {
+            collection: "notes".to_string(),
+            index: "by_text".to_string(),
+            processed: 2,
+            total: 2,
+        }
+    );
+    let LifecycleEvent::IndexRebuildFinished {
+        collection,
+        index,
+        stats,
+    } = &events[2]
+    else {
+        panic!("expected IndexRebuildFinished, got {:?}", events[2]);
+    };
+    assert_eq!((collection.as_str(), index.as_str()), ("notes", "by_text"));
+    assert_eq!(stats.records_scanned, 2);
+}
+
+#[test]
+fn reindex_reports_progress_once_per_batch() {
+    let def = notes_def();
+    let mut ra = make_uninitialized(&[&def]);
+    ra.initialize(&[Arc::new(notes_def())]).expect("initialize");
+    // One more than a full batch of 500
+    put_notes(&ra, &def, 501);
+    let events = collect_lifecycle(&ra);
+
+    assert_eq!(ra.reindex(&def).expect("reindex"), 501);
+
+    let progress: Vec<(usize, usize)> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            LifecycleEvent::IndexRebuildProgress {
+                processed, total, ..
+            } => Some((*processed, *total)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(progress, vec![(500, 501), (501, 501)]);
+}
+
+#[test]
+fn reindex_stores_progress_with_each_batch() {
+    let def = notes_def();
+    let mut ra = make_uninitialized(&[&def]);
+    ra.initialize(&[Arc::new(notes_def())]).expect("initialize");
+    put_notes(&ra, &def, 501);
+    let ra = Arc::new(ra);
+
+    // Read the stored progress as each batch reports in: it must already
+    // cover the batch, and be cleared once the last one commits.
+    let stored = Arc::new(Mutex::new(Vec::new()));
+    let stored_clone = Arc::clone(&stored);
+    let weak = Arc::downgrade(&ra);
+    std::mem::forget(ra.on_lifecycle(move |event: &LifecycleEvent| {
+        if let LifecycleEvent::IndexRebuildProgress { collection, .. } = event {
+            let ra = weak.upgrade().expect("adapter alive");
+            let progress = ra.reindex_progress(collection).expect("progress");
+            stored_clone.lock().unwrap().push(progress);
+        }
+    }));
+
+    assert_eq!(ra.reindex_progress("notes").expect("progress"), None);
+    ra.reindex(&def).expect("reindex");
+    assert_eq!(*stored.lock().unwrap(), vec![Some((500, 501)), None]);
+    assert_eq!(ra.reindex_progress("notes").expect("progress"), None);
+}
+
+// ============================================================================
+// Rename and drop
+// ============================================================================
+
+#[test]
+fn rename_collection_moves_records_then_emits_renamed() {
+    let notes = notes_def();
+    let memos = memos_def();
+    let mut ra = make_uninitialized(&[&notes, &memos]);
+    ra.initialize(&[Arc::new(notes_def()), Arc::new(memos_def())])
+        .expect("initialize");
+    put_notes(&ra, &notes, 2);
+    let events = collect_lifecycle(&ra);
+
+    assert_eq!(ra.rename_collection("notes", &memos).expect("rename"), 2);
+
+    assert_eq!(ra.count(&notes, None).expect("count notes"), 0);
+    assert_eq!(ra.count(&memos, None).expect("count memos"), 2);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![LifecycleEvent::CollectionRenamed {
+            from: "notes".to_string(),
+            to: "memos".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn rename_into_non_empty_collection_fails_without_event() {
+    let notes = notes_def();
+    let memos = memos_def();
+    let mut ra = make_uninitialized(&[&notes, &memos]);
+    ra.initialize(&[Arc::new(notes_def()), Arc::new(memos_def())])
+        .expect("initialize");
+    put_notes(&ra, &notes, 1);
+    put_notes(&ra, &memos, 1);
+    let events = collect_lifecycle(&ra);
+
+    let err = ra.rename_collection("notes", &memos).unwrap_err();
+    assert_eq!(err.code(), "STORAGE_COLLECTION_NOT_EMPTY");
+    assert_eq!(ra.count(&notes, None).expect("count notes"), 1);
+    assert!(events.lock().unwrap().is_empty());
+}
+
+#[test]
+fn drop_collection_removes_records_then_emits_dropped() {
+    let def = notes_def();
+    let mut ra = make_uninitialized(&[&def]);
+    ra.initialize(&[Arc::new(notes_def())]).expect("initialize");
+    put_notes(&ra, &def, 2);
+    let events = collect_lifecycle(&ra);
+
+    assert_eq!(ra.drop_collection(&def).expect("drop"), 2);
+
+    assert_eq!(ra.count(&def, None).expect("count"), 0);
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![LifecycleEvent::CollectionDropped {
+            collection: "notes".to_string(),
+        }]
+    );
+}
+
+// ============================================================================
+// Migration
+// ============================================================================
+
+#[test]
+fn migrate_collection_emits_started_and_finished() {
+    let def = docs_def();
+    let ra = make_adapter();
+    put_v1_doc(&ra, "doc-1", "One");
+    put_v1_doc(&ra, "doc-2", "Two");
+    let events = collect_lifecycle(&ra);
+
+    let migrated = ra.migrate_collection(&def).expect("migrate_collection");
+    assert_eq!(migrated, 2);
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            LifecycleEvent::MigrationStarted {
+                collection: "docs".to_string(),
+                from_version: 1,
+                to_version: 2,
+            },
+            LifecycleEvent::MigrationFinished {
+                collection: "docs".to_string(),
+                from_version: 1,
+                to_version: 2,
+                migrated: 2,
+            },
+        ]
+    );
+}
+
+#[test]
+fn migration_finished_fires_after_records_are_persisted() {
+    let def = docs_def();
+    let ra = make_adapter();
+    put_v1_doc(&ra, "doc-1", "One");
+
+    // Re-enter the adapter from the listener and read the raw stored version.
+    let seen: Arc<Mutex<Vec<(&'static str, u32)>>> = Arc::new(Mutex::new(Vec::new()));
+    let seen_clone = Arc::clone(&seen);
+    let ra_clone = Arc::clone(&ra);
+    let raw_opts = GetOptions {
+        migrate: false,
+        ..Default::default()
+    };
+    let _unsub = ra.on_lifecycle(move |event: &LifecycleEvent| {
+        let label = match event {
+            LifecycleEvent::MigrationStarted { .. } => "started",
+            LifecycleEvent::MigrationFinished { .. } => "finished",
+            _ => return,
+        };
+        let stored = ra_clone
+            .get(&docs_def(), "doc-1", &raw_opts)
+            .expect("get")
+            .expect("record exists");
+        seen_clone.lock().unwrap().push((label, stored.version));
+    });
+
+    ra.migrate_collection(&def).expect("migrate_collection");
+
+    assert_eq!(*seen.lock().unwrap(), vec![("started", 1), ("finished", 2)]);
+}
+
+#[test]
+fn migrate_collection_flushes_observers_before_finished() {
+    let def = docs_def();
+    let ra = make_adapter();
+
+    let order: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+    let order_observe = Arc::clone(&order);
+    let _unsub_observe = ra
+        .observe(
+            Arc::new(docs_def()),
+            "doc-1",
+            Arc::new(move |data: Option<Value>| {
+                let body = data.and_then(|d| d.get("body").cloned());
+                order_observe
+                    .lock()
+                    .unwrap()
+                    .push(format!("observe:{}", body.is_some()));
+            }),
+            None,
+        )
+        .expect("observe");
+    ra.flush();
+    order.lock().unwrap().clear();
+    // Written after the initial observe flush so no read has migrated it yet.
+    put_v1_doc(&ra, "doc-1", "One");
+
+    let order_lifecycle = Arc::clone(&order);
+    let _unsub = ra.on_lifecycle(move |event: &LifecycleEvent| {
+        if let LifecycleEvent::MigrationFinished { .. } = event {
+            order_lifecycle.lock().unwrap().push("finished".to_string());
+        }
+    });
+
+    ra.migrate_collection(&def).expect("migrate_collection");
+
+    assert_eq!(*order.lock().unwrap(), vec!["observe:true", "finished"]);
+}
+
+#[test]
+fn migrate_collection_is_silent_when_nothing_is_stale() {
+    let def = docs_def();
+    let ra = make_adapter();
+    ra.put(
+        &def,
+        json!({ "title": "Fresh", "body": "" }),
+        &PutOptions {
+            session_id: Some(SID),
+            ..Default::default()
+        },
+    )
+    .expect("put");
+    let events = collect_lifecycle(&ra);
+
+    assert_eq!(ra.migrate_collection(&def).expect("migrate_collection"), 0);
+    assert!(events.lock().unwrap().is_empty());
+}
+
+// ============================================================================
+// Maintenance
+// ============================================================================
+
+#[test]
+fn purge_tombstones_emits_maintenance_run() {
+    let def = docs_def();
+    let ra = make_adapter();
+    let put_opts = PutOptions {
+        session_id: Some(SID),
+        ..Default::default()
+    };
+    for title in ["a", "b"] {
+        let rec = ra
+            .put(&def, json!({ "title": title, "body": "" }), &put_opts)
+            .expect("put");
+        ra.delete(&def, &rec.id, &DeleteOptions::default())
+            .expect("delete");
+    }
+    let events = collect_lifecycle(&ra);
+
+    let dry = ra
+        .purge_tombstones(
+            &def,
+            &PurgeTombstonesOptions {
+                older_than_seconds: None,
+                dry_run: true,
+                skip_dirty: false,
+            },
+        )
+        .expect("dry run");
+    let purged = ra
+        .purge_tombstones(
+            &def,
+            &PurgeTombstonesOptions {
+                older_than_seconds: None,
+                dry_run: false,
+                skip_dirty: false,
+            },
+        )
+        .expect("purge");
+    assert_eq!((dry, purged), (2, 2));
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            LifecycleEvent::MaintenanceRun {
+                collection: "docs".to_string(),
+                task: "purge_tombstones".to_string(),
+                stats: MaintenanceStats {
+                    records_affected: 2,
+                    dry_run: true,
+                },
+            },
+            LifecycleEvent::MaintenanceRun {
+                collection: "docs".to_string(),
+                task: "purge_tombstones".to_string(),
+                stats: MaintenanceStats {
+                    records_affected: 2,
+                    dry_run: false,
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn lifecycle_events_do_not_reach_change_listeners() {
+    let def = docs_def();
+    let ra = make_adapter();
+    put_v1_doc(&ra, "doc-1", "One");
+
+    let changes: Arc<Mutex<Vec<ChangeEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let changes_clone = Arc::clone(&changes);
+    let _unsub = ra.on_change(move |event: &ChangeEvent| {
+        changes_clone.lock().unwrap().push(event.clone());
+    });
+
+    ra.migrate_collection(&def).expect("migrate_collection");
+    assert!(changes.lock().unwrap().is_empty());
+}
+
+// ============================================================================
+// Delivery rules
+// ============================================================================
+
+#[test]
+fn panicking_lifecycle_listener_does_not_block_others() {
+    let def = docs_def();
+    let ra = make_adapter();
+    put_v1_doc(&ra, "doc-1", "One");
+
+    let _unsub_panic = ra.on_lifecycle(|_event: &LifecycleEvent| {
+        panic!("listener failure");
+    });
+    let events = collect_lifecycle(&ra);
+
+    let migrated = ra.migrate_collection(&def).expect("migrate_collection");
+    assert_eq!(migrated, 1);
+    assert_eq!(events.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn unsubscribed_lifecycle_listener_is_not_called() {
+    let def = docs_def();
+    let ra = make_adapter();
+    put_v1_doc(&ra, "doc-1", "One");
+
+    let count = Arc::new(Mutex::new(0usize));
+    let count_clone = Arc::clone(&count);
+    let unsub = ra.on_lifecycle(move |_event: &LifecycleEvent| {
+        *count_clone.lock().unwrap() += 1;
+    });
+    unsub();
+
+    ra.migrate_collection(&def).expect("migrate_collection");
+    assert_eq!(*count.lock().unwrap(), 0);
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+#[test]
+fn lifecycle_event_serializes_with_type_tag_and_camel_case_keys() {
+    let event = LifecycleEvent::MigrationFinished {
+        collection: "docs".to_string(),
+        from_version: 1,
+        to_version: 2,
+        migrated: 3,
+    };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        json!({
+            "type": "migrationFinished",
+            "collection": "docs",
+            "fromVersion": 1,
+            "toVersion": 2,
+            "migrated": 3,
+        })
+    );
+
+    let event = LifecycleEvent::CollectionRenamed {
+        from: "old".to_string(),
+        to: "new".to_string(),
+    };
+    assert_eq!(event.collection(), "new");
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        json!({ "type": "collectionRenamed", "from": "old", "to": "new" })
+    );
+
+    let event = LifecycleEvent::IndexRebuildProgress {
+        collection: "notes".to_string(),
+        index: "by_text".to_string(),
+        processed: 500,
+        total: 501,
+    };
+    assert_eq!(
+        serde_json::to_value(&event).unwrap(),
+        json!({
+            "type": "indexRebuildProgress",
+            "collection": "notes",
+            "index": "by_text",
+            "processed": 500,
+            "total": 501,
+        })
+    );
+}
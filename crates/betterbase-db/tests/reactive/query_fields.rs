@@ -143,10 +143,12 @@ fn extracts_sort_fields_sort_entry_array() {
             SortEntry {
                 field: "createdAt".to_string(),
                 direction: SortDirection::Desc,
+                nulls: None,
             },
             SortEntry {
                 field: "name".to_string(),
                 direction: SortDirection::Asc,
+                nulls: None,
             },
         ])),
         ..Default::default()
@@ -188,6 +188,7 @@ fn query_with_filter_sort_pagination() {
         sort: Some(SortInput::Entries(vec![SortEntry {
             field: "name".to_string(),
             direction: SortDirection::Asc,
+            nulls: None,
         }])),
         ..Default::default()
     };
@@ -224,6 +225,7 @@ fn query_with_filter_sort_pagination() {
                 sort: Some(SortInput::Entries(vec![SortEntry {
                     field: "name".to_string(),
                     direction: SortDirection::Asc,
+                    nulls: None,
                 }])),
                 limit: Some(2),
                 offset: Some(1),
@@ -318,6 +320,7 @@ fn remote_merge_preserves_both_changes() {
         version: 1,
         crdt: Some(remote_crdt),
         deleted: false,
+        archived: false,
         sequence: 50,
         meta: None,
     };
@@ -364,14 +367,16 @@ fn reactive_observe_through_write_and_flush() {
     let log: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let log_clone = log.clone();
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |data: Option<Value>| {
-            log_clone.lock().unwrap().push(data);
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |data: Option<Value>| {
+                log_clone.lock().unwrap().push(data);
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
 
@@ -435,6 +440,7 @@ fn migration_on_read() {
         dirty: false,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         computed: None,
     };
@@ -760,6 +766,7 @@ fn sort_on_optional_field_with_nulls() {
                 sort: Some(SortInput::Entries(vec![SortEntry {
                     field: "age".to_string(),
                     direction: SortDirection::Asc,
+                    nulls: None,
                 }])),
                 ..Default::default()
             },
@@ -849,7 +856,9 @@ fn get_with_include_deleted() {
             &created.id,
             &GetOptions {
                 include_deleted: true,
+                include_archived: false,
                 migrate: true,
+                ..Default::default()
             },
         )
         .expect("get");
@@ -874,6 +883,7 @@ fn remote_delete_nonexistent_record() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -893,7 +903,9 @@ fn remote_delete_nonexistent_record() {
             "does-not-exist",
             &GetOptions {
                 include_deleted: true,
+                include_archived: false,
                 migrate: true,
+                ..Default::default()
             },
         )
         .expect("get")
@@ -924,6 +936,7 @@ fn idempotent_remote_apply() {
         version: 1,
         crdt: Some(crdt_binary.clone()),
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -1004,6 +1017,7 @@ fn remote_delete_update_wins_preserves_local() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 50,
         meta: None,
     };
@@ -1065,6 +1079,7 @@ fn remote_delete_delete_wins_kills_local() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 50,
         meta: None,
     };
@@ -1105,14 +1120,16 @@ fn observe_record_delete_fires_none() {
     let log: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let log_clone = log.clone();
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |data: Option<Value>| {
-            log_clone.lock().unwrap().push(data);
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |data: Option<Value>| {
+                log_clone.lock().unwrap().push(data);
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
 
@@ -1162,14 +1179,16 @@ fn unsubscribe_stops_callbacks() {
     let count = Arc::new(Mutex::new(0u32));
     let count_clone = count.clone();
 
-    let unsub = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |_data: Option<Value>| {
-            *count_clone.lock().unwrap() += 1;
-        }),
-        None,
-    );
+    let unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |_data: Option<Value>| {
+                *count_clone.lock().unwrap() += 1;
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
     let after_first_flush = *count.lock().unwrap();
@@ -1210,20 +1229,23 @@ fn observe_query_tracks_changes() {
     let log: Arc<Mutex<Vec<ReactiveQueryResult>>> = Arc::new(Mutex::new(Vec::new()));
     let log_clone = log.clone();
 
-    let _unsub = ra.observe_query(
-        Arc::new(users_def()),
-        Query {
-            sort: Some(SortInput::Entries(vec![SortEntry {
-                field: "name".to_string(),
-                direction: SortDirection::Asc,
-            }])),
-            ..Default::default()
-        },
-        Arc::new(move |result: ReactiveQueryResult| {
-            log_clone.lock().unwrap().push(result);
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe_query(
+            Arc::new(users_def()),
+            Query {
+                sort: Some(SortInput::Entries(vec![SortEntry {
+                    field: "name".to_string(),
+                    direction: SortDirection::Asc,
+                    nulls: None,
+                }])),
+                ..Default::default()
+            },
+            Arc::new(move |result: ReactiveQueryResult| {
+                log_clone.lock().unwrap().push(result);
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
 
@@ -1306,23 +1328,27 @@ fn multiple_observers_same_record() {
     let count_a_clone = count_a.clone();
     let count_b_clone = count_b.clone();
 
-    let _unsub_a = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |_data: Option<Value>| {
-            *count_a_clone.lock().unwrap() += 1;
-        }),
-        None,
-    );
-
-    let _unsub_b = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |_data: Option<Value>| {
-            *count_b_clone.lock().unwrap() += 1;
-        }),
-        None,
-    );
+    let _unsub_a = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |_data: Option<Value>| {
+                *count_a_clone.lock().unwrap() += 1;
+            }),
+            None,
+        )
+        .expect("observe");
+
+    let _unsub_b = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |_data: Option<Value>| {
+                *count_b_clone.lock().unwrap() += 1;
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
 
@@ -1452,6 +1478,7 @@ fn remote_insert_new_record() {
         version: 1,
         crdt: Some(crdt_binary),
         deleted: false,
+        archived: false,
         sequence: 100,
         meta: None,
     };
@@ -1517,6 +1544,7 @@ fn batch_remote_changes_mixed() {
             version: 1,
             crdt: Some(crdt::model_to_binary(&insert_model)),
             deleted: false,
+            archived: false,
             sequence: 50,
             meta: None,
         },
@@ -1526,6 +1554,7 @@ fn batch_remote_changes_mixed() {
             version: 1,
             crdt: None,
             deleted: true,
+            archived: false,
             sequence: 51,
             meta: None,
         },
@@ -1789,14 +1818,16 @@ fn reactive_remote_changes_trigger_observe() {
     let log: Arc<Mutex<Vec<Option<Value>>>> = Arc::new(Mutex::new(Vec::new()));
     let log_clone = log.clone();
 
-    let _unsub = ra.observe(
-        Arc::new(users_def()),
-        created.id.clone(),
-        Arc::new(move |data: Option<Value>| {
-            log_clone.lock().unwrap().push(data);
-        }),
-        None,
-    );
+    let _unsub = ra
+        .observe(
+            Arc::new(users_def()),
+            created.id.clone(),
+            Arc::new(move |data: Option<Value>| {
+                log_clone.lock().unwrap().push(data);
+            }),
+            None,
+        )
+        .expect("observe");
 
     ra.flush();
     let initial_count = log.lock().unwrap().len();
@@ -1818,6 +1849,7 @@ fn reactive_remote_changes_trigger_observe() {
             version: 1,
             crdt: Some(crdt::model_to_binary(&model)),
             deleted: false,
+            archived: false,
             sequence: 50,
             meta: None,
         }],
@@ -1,8 +1,32 @@
 mod storage {
+    #[cfg(feature = "sqlite")]
+    mod access_control;
     #[cfg(feature = "sqlite")]
     mod adapter;
+    #[cfg(feature = "sqlite")]
+    mod aggregate;
+    #[cfg(feature = "sqlite")]
+    mod archive;
+    #[cfg(feature = "sqlite")]
+    mod compact;
+    #[cfg(feature = "sqlite")]
+    mod concurrent_merge;
+    #[cfg(feature = "sqlite")]
+    mod index_scan;
+    #[cfg(feature = "sqlite")]
+    mod migrate_all;
+    #[cfg(feature = "raw-sql")]
+    mod raw_sql;
     mod record_manager;
+    #[cfg(feature = "sqlite")]
+    mod reindex;
     mod remote_changes;
     #[cfg(feature = "sqlite")]
+    mod reset_collection;
+    #[cfg(feature = "sqlite")]
+    mod snapshot;
+    #[cfg(feature = "sqlite")]
     mod sqlite;
+    #[cfg(feature = "sqlite")]
+    mod tombstone_retention;
 }
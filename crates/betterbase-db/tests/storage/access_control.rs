@@ -0,0 +1,351 @@
+//! Tests for collection `read_predicate` / `write_predicate`: records the
+//! actor may not see are filtered from reads, and writes to records the actor
+//! may not modify fail with `StorageError::Forbidden`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    error::{LessDbError, StorageError},
+    query::{
+        aggregate::{AggregateSpec, Metric},
+        types::{Query, SortInput},
+    },
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{DeleteOptions, GetOptions, ListOptions, OperationContext, PatchOptions, PutOptions},
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const ALICE: &str = "did:key:alice";
+const BOB: &str = "did:key:bob";
+
+fn is_owner(ctx: &OperationContext, data: &Value) -> bool {
+    ctx.actor_did.as_deref() == data["owner"].as_str()
+}
+
+/// Notes readable by their owner or when shared, writable only by their owner.
+fn notes_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("owner".to_string(), t::string());
+                s.insert("title".to_string(), t::string());
+                s.insert("shared".to_string(), t::boolean());
+                s
+            })
+            .read_predicate(|ctx, data| is_owner(ctx, data) || data["shared"] == json!(true))
+            .write_predicate(is_owner)
+            .build(),
+    )
+}
+
+fn make_adapter(def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn as_actor(did: &str) -> OperationContext {
+    OperationContext {
+        actor_did: Some(did.to_string()),
+    }
+}
+
+fn put_as(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, actor: &str, data: Value) {
+    let opts = PutOptions {
+        id: data["title"].as_str().map(String::from),
+        context: as_actor(actor),
+        ..Default::default()
+    };
+    adapter.put(def, data, &opts).expect("put");
+}
+
+/// Alice owns "private" and "shared" (shared with everyone); Bob owns "bobs".
+fn seed(adapter: &Adapter<SqliteBackend>, def: &CollectionDef) {
+    put_as(
+        adapter,
+        def,
+        ALICE,
+        json!({ "owner": ALICE, "title": "private", "shared": false }),
+    );
+    put_as(
+        adapter,
+        def,
+        ALICE,
+        json!({ "owner": ALICE, "title": "shared", "shared": true }),
+    );
+    put_as(
+        adapter,
+        def,
+        BOB,
+        json!({ "owner": BOB, "title": "bobs", "shared": false }),
+    );
+}
+
+fn assert_forbidden(result: betterbase_db::error::Result<impl std::fmt::Debug>, id: &str) {
+    match result {
+        Err(LessDbError::Storage(inner)) => match *inner {
+            StorageError::Forbidden { id: denied, .. } => assert_eq!(denied, id),
+            other => panic!("expected Forbidden, got: {other:?}"),
+        },
+        other => panic!("expected Forbidden, got: {other:?}"),
+    }
+}
+
+// ============================================================================
+// Reads
+// ============================================================================
+
+#[test]
+fn get_hides_records_the_read_predicate_denies() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let bob = GetOptions {
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert!(adapter.get(&def, "private", &bob).unwrap().is_none());
+    assert!(adapter.get(&def, "shared", &bob).unwrap().is_some());
+    assert!(adapter.get(&def, "bobs", &bob).unwrap().is_some());
+
+    let alice = GetOptions {
+        context: as_actor(ALICE),
+        ..Default::default()
+    };
+    assert!(adapter.get(&def, "private", &alice).unwrap().is_some());
+}
+
+#[test]
+fn query_count_and_get_all_only_see_readable_records() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let query = Query {
+        sort: Some(SortInput::Field("title".to_string())),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    let result = adapter.query(&def, &query).unwrap();
+    let ids: Vec<&str> = result.records.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, ["bobs", "shared"]);
+    assert_eq!(result.total, Some(2));
+    assert_eq!(adapter.count(&def, Some(&query)).unwrap(), 2);
+
+    // Hidden records don't take up offset/limit slots
+    let list = ListOptions {
+        limit: Some(2),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_eq!(adapter.get_all(&def, &list).unwrap().records.len(), 2);
+
+    let alice = Query {
+        context: as_actor(ALICE),
+        ..Default::default()
+    };
+    assert_eq!(adapter.query(&def, &alice).unwrap().records.len(), 2);
+    assert_eq!(adapter.count(&def, Some(&alice)).unwrap(), 2);
+}
+
+#[test]
+fn aggregate_only_folds_readable_records() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let by_owner = |context: OperationContext| AggregateSpec {
+        group_by: vec!["owner".to_string()],
+        metrics: vec![Metric::Count],
+        context,
+        ..Default::default()
+    };
+    let counts = |spec: &AggregateSpec| -> Vec<(Value, Option<f64>)> {
+        adapter
+            .aggregate(&def, spec)
+            .unwrap()
+            .into_iter()
+            .map(|row| (row.group[0].clone(), row.values[0]))
+            .collect()
+    };
+
+    // Bob sees his own note and Alice's shared one, not her private one
+    assert_eq!(
+        counts(&by_owner(as_actor(BOB))),
+        [(json!(ALICE), Some(1.0)), (json!(BOB), Some(1.0))]
+    );
+    assert_eq!(
+        counts(&by_owner(as_actor(ALICE))),
+        [(json!(ALICE), Some(2.0))]
+    );
+    // An anonymous caller only sees shared notes
+    assert_eq!(
+        counts(&by_owner(OperationContext::default())),
+        [(json!(ALICE), Some(1.0))]
+    );
+}
+
+// ============================================================================
+// Writes
+// ============================================================================
+
+#[test]
+fn writes_to_denied_records_fail_with_forbidden() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    // Bob can read "shared" but not modify it
+    let put = PutOptions {
+        id: Some("shared".to_string()),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_forbidden(
+        adapter.put(&def, json!({ "title": "shared", "shared": false }), &put),
+        "shared",
+    );
+
+    let patch = PatchOptions {
+        id: "shared".to_string(),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_forbidden(
+        adapter.patch(&def, json!({ "shared": false }), &patch),
+        "shared",
+    );
+
+    let delete = DeleteOptions {
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_forbidden(adapter.delete(&def, "shared", &delete), "shared");
+
+    // Nor create a record for another owner, or give one away
+    let forged = PutOptions {
+        id: Some("forged".to_string()),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_forbidden(
+        adapter.put(
+            &def,
+            json!({ "owner": ALICE, "title": "forged", "shared": false }),
+            &forged,
+        ),
+        "forged",
+    );
+    let give_away = PatchOptions {
+        id: "bobs".to_string(),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert_forbidden(
+        adapter.patch(&def, json!({ "owner": ALICE }), &give_away),
+        "bobs",
+    );
+
+    // Nothing changed
+    let alice = GetOptions {
+        context: as_actor(ALICE),
+        ..Default::default()
+    };
+    let shared = adapter.get(&def, "shared", &alice).unwrap().unwrap();
+    assert_eq!(shared.data["shared"], json!(true));
+    assert!(adapter.get(&def, "forged", &alice).unwrap().is_none());
+}
+
+#[test]
+fn owners_can_write_their_own_records() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let patch = PatchOptions {
+        id: "bobs".to_string(),
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    let patched = adapter
+        .patch(&def, json!({ "shared": true }), &patch)
+        .unwrap();
+    assert_eq!(patched.data["shared"], json!(true));
+
+    let delete = DeleteOptions {
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    assert!(adapter.delete(&def, "bobs", &delete).unwrap());
+}
+
+#[test]
+fn delete_many_skips_records_the_actor_cannot_write() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let opts = DeleteOptions {
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    let result = adapter.delete_many(&def, &json!({}), &opts).unwrap();
+    assert_eq!(result.deleted_ids, ["bobs"]);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].id, "shared");
+}
+
+#[test]
+fn delete_many_by_index_skips_records_the_actor_cannot_read() {
+    // Read-restricted only, and indexed so the filter plans an index scan
+    let def = Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("owner".to_string(), t::string());
+                s.insert("title".to_string(), t::string());
+                s.insert("shared".to_string(), t::boolean());
+                s
+            })
+            .index(&["owner"])
+            .read_predicate(|ctx, data| is_owner(ctx, data) || data["shared"] == json!(true))
+            .build(),
+    );
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let opts = DeleteOptions {
+        context: as_actor(BOB),
+        ..Default::default()
+    };
+    let result = adapter
+        .delete_many(&def, &json!({ "owner": ALICE }), &opts)
+        .unwrap();
+    assert_eq!(result.deleted_ids, ["shared"]);
+    assert!(result.errors.is_empty());
+
+    let alice = GetOptions {
+        context: as_actor(ALICE),
+        ..Default::default()
+    };
+    assert!(adapter.get(&def, "private", &alice).unwrap().is_some());
+}
@@ -7,8 +7,12 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use betterbase_db::{
-    collection::builder::{collection, CollectionDef},
+    collection::{
+        autofill::IdStrategy,
+        builder::{collection, CollectionDef},
+    },
     crdt::MIN_SESSION_ID,
+    index::types::IndexDefinition,
     schema::node::t,
     storage::{
         adapter::Adapter,
@@ -16,8 +20,8 @@ use betterbase_db::{
         traits::{StorageLifecycle, StorageRead, StorageSync, StorageWrite},
     },
     types::{
-        ApplyRemoteOptions, DeleteOptions, GetOptions, ListOptions, PatchOptions, PushSnapshot,
-        PutOptions, RemoteRecord,
+        ApplyRemoteOptions, DeleteOptions, GetOptions, ListOptions, PatchOptions, PushQueueState,
+        PushSnapshot, PutOptions, RemoteRecord, TouchOptions,
     },
 };
 use serde_json::json;
@@ -174,6 +178,117 @@ fn put_with_explicit_id() {
     assert_eq!(record.id, "custom-id-123");
 }
 
+fn notes_def(strategy: IdStrategy) -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("body".to_string(), t::string());
+                s
+            })
+            .id_strategy(strategy)
+            .build(),
+    )
+}
+
+#[test]
+fn put_mints_ids_with_collection_strategy() {
+    let def = notes_def(IdStrategy::Nanoid(12));
+    let adapter = make_adapter_arc(def.clone());
+
+    let a = adapter
+        .put(&def, json!({ "body": "one" }), &put_opts())
+        .expect("put");
+    let b = adapter
+        .put(&def, json!({ "body": "one" }), &put_opts())
+        .expect("put");
+
+    assert_eq!(a.id.len(), 12);
+    assert_ne!(a.id, b.id);
+    assert_eq!(a.data["id"], json!(a.id));
+
+    // An explicit id still wins over the strategy
+    let opts = PutOptions {
+        id: Some("explicit".to_string()),
+        session_id: Some(SID),
+        ..Default::default()
+    };
+    let c = adapter
+        .put(&def, json!({ "body": "two" }), &opts)
+        .expect("put");
+    assert_eq!(c.id, "explicit");
+}
+
+#[test]
+fn put_never_overwrites_a_record_on_a_minted_id_collision() {
+    // Nanoid(1) has only 64 possible ids; take every one of them
+    let def = notes_def(IdStrategy::Nanoid(1));
+    let adapter = make_adapter_arc(def.clone());
+    let alphabet = "useandom-26T198340PX75pxJACKVERYMINDBUSHWOLF_GQZbfghjklqvwyzrict";
+    for c in alphabet.chars() {
+        let opts = PutOptions {
+            id: Some(c.to_string()),
+            session_id: Some(SID),
+            ..Default::default()
+        };
+        adapter
+            .put(&def, json!({ "body": "taken" }), &opts)
+            .expect("put");
+    }
+
+    let err = adapter
+        .put(&def, json!({ "body": "new" }), &put_opts())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("collided"),
+        "unexpected error: {err}"
+    );
+
+    let all = adapter
+        .get_all(&def, &ListOptions::default())
+        .expect("get_all");
+    assert_eq!(all.records.len(), 64);
+    assert!(all.records.iter().all(|r| r.data["body"] == json!("taken")));
+}
+
+#[test]
+fn put_with_content_hash_rejects_identical_content() {
+    let def = notes_def(IdStrategy::ContentHash);
+    let adapter = make_adapter_arc(def.clone());
+
+    let first = adapter
+        .put(&def, json!({ "body": "same" }), &put_opts())
+        .expect("put");
+    assert_eq!(
+        first.id,
+        IdStrategy::ContentHash.mint(&json!({ "body": "same" }))
+    );
+
+    let err = adapter
+        .put(&def, json!({ "body": "same" }), &put_opts())
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("identical content"),
+        "unexpected error: {err}"
+    );
+
+    let other = adapter
+        .put(&def, json!({ "body": "different" }), &put_opts())
+        .expect("put");
+    assert_ne!(other.id, first.id);
+
+    // Updating by id is still an upsert
+    let updated = adapter
+        .put(
+            &def,
+            json!({ "id": first.id, "body": "edited" }),
+            &put_opts(),
+        )
+        .expect("upsert");
+    assert_eq!(updated.id, first.id);
+    assert_eq!(updated.data["body"], json!("edited"));
+}
+
 #[test]
 fn get_returns_record_by_id() {
     let def = users_def();
@@ -248,7 +363,9 @@ fn get_returns_deleted_record_with_include_deleted() {
 
     let opts = GetOptions {
         include_deleted: true,
+        include_archived: false,
         migrate: true,
+        ..Default::default()
     };
     let fetched = adapter
         .get(&def, &record.id, &opts)
@@ -259,6 +376,83 @@ fn get_returns_deleted_record_with_include_deleted() {
     assert!(fetched.deleted_at.is_some());
 }
 
+/// Build a collection with a numeric field, for integer precision tests.
+fn counters_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("counters")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("label".to_string(), t::string());
+                s.insert("value".to_string(), t::number());
+                s
+            })
+            .build(),
+    )
+}
+
+/// 2^53 + 1: the smallest positive integer an f64 cannot represent.
+const BEYOND_F64: i64 = 9_007_199_254_740_993;
+
+#[test]
+fn put_get_preserves_integers_beyond_f64_precision() {
+    let def = counters_def();
+    let adapter = make_adapter_arc(def.clone());
+
+    for (id, value) in [
+        ("pos", json!(BEYOND_F64)),
+        ("neg", json!(-BEYOND_F64)),
+        ("max", json!(u64::MAX)),
+    ] {
+        adapter
+            .put(
+                &def,
+                json!({ "label": id, "value": value }),
+                &PutOptions {
+                    id: Some(id.to_string()),
+                    ..put_opts()
+                },
+            )
+            .expect("put");
+
+        let fetched = adapter
+            .get(&def, id, &get_opts())
+            .expect("get")
+            .expect("record should exist");
+        assert_eq!(fetched.data["value"], value, "{id} was coerced");
+    }
+}
+
+#[test]
+fn patch_preserves_untouched_large_integer() {
+    let def = counters_def();
+    let adapter = make_adapter_arc(def.clone());
+
+    let record = adapter
+        .put(
+            &def,
+            json!({ "label": "a", "value": BEYOND_F64 }),
+            &put_opts(),
+        )
+        .expect("put");
+    adapter
+        .patch(
+            &def,
+            json!({ "label": "b" }),
+            &PatchOptions {
+                id: record.id.clone(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+
+    let fetched = adapter
+        .get(&def, &record.id, &get_opts())
+        .expect("get")
+        .expect("record should exist");
+    assert_eq!(fetched.data["value"].as_i64(), Some(BEYOND_F64));
+}
+
 // ============================================================================
 // patch
 // ============================================================================
@@ -403,6 +597,180 @@ fn delete_returns_false_for_already_deleted_record() {
     assert!(!second, "should return false for already-deleted record");
 }
 
+// ============================================================================
+// touch
+// ============================================================================
+
+#[test]
+fn touch_marks_record_dirty_without_changing_fields() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let record = adapter
+        .put(
+            &def,
+            json!({ "name": "Alice", "email": "alice@example.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    adapter
+        .mark_synced(&def, &record.id, 7, None)
+        .expect("mark_synced");
+    // updatedAt has microsecond resolution; make sure the clock moves.
+    std::thread::sleep(std::time::Duration::from_millis(2));
+
+    let touch_opts = TouchOptions {
+        session_id: Some(SID),
+        ..Default::default()
+    };
+    let touched = adapter.touch(&def, &record.id, &touch_opts).expect("touch");
+
+    assert!(touched.dirty, "touch should mark the record dirty");
+    assert_eq!(touched.version, def.current_version);
+    assert_eq!(touched.sequence, 7, "touch should keep the sync cursor");
+    assert_eq!(touched.data["name"], json!("Alice"));
+    assert_eq!(touched.data["email"], json!("alice@example.com"));
+    assert_eq!(touched.data["createdAt"], record.data["createdAt"]);
+    assert_ne!(
+        touched.data["updatedAt"], record.data["updatedAt"],
+        "touch should bump updatedAt"
+    );
+
+    let dirty = adapter.get_dirty(&def).expect("get_dirty");
+    assert_eq!(dirty.records.len(), 1);
+    assert_eq!(dirty.records[0].id, record.id);
+}
+
+#[test]
+fn touch_increments_the_record_revision() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let record = adapter
+        .put(
+            &def,
+            json!({ "name": "Alice", "email": "alice@example.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    adapter
+        .mark_synced(&def, &record.id, 7, None)
+        .expect("mark_synced");
+
+    let touch_opts = TouchOptions {
+        session_id: Some(SID),
+        meta: Some(json!({ "source": "rotation" })),
+    };
+    let first = adapter.touch(&def, &record.id, &touch_opts).expect("touch");
+    let second = adapter.touch(&def, &record.id, &touch_opts).expect("touch");
+
+    let first_meta = first.meta.expect("touch should stamp meta");
+    let second_meta = second.meta.expect("touch should stamp meta");
+    assert_eq!(first_meta["_revision"], json!(1));
+    assert_eq!(second_meta["_revision"], json!(2));
+    assert_eq!(second_meta["source"], json!("rotation"));
+    assert_eq!(second.sequence, 7, "touch should keep the sync cursor");
+}
+
+#[test]
+fn touch_errors_for_missing_record() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let result = adapter.touch(&def, "nonexistent", &TouchOptions::default());
+    assert!(result.is_err(), "touch on missing record should error");
+}
+
+#[test]
+fn touch_errors_for_deleted_record() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let record = adapter
+        .put(
+            &def,
+            json!({ "name": "Ghost", "email": "ghost@example.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    adapter
+        .delete(&def, &record.id, &DeleteOptions::default())
+        .expect("delete");
+
+    let result = adapter.touch(&def, &record.id, &TouchOptions::default());
+    assert!(result.is_err(), "touch on deleted record should error");
+}
+
+// ============================================================================
+// get_or_create
+// ============================================================================
+
+#[test]
+fn get_or_create_inserts_defaults_once() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let (created, was_created) = adapter
+        .get_or_create(
+            &def,
+            "settings",
+            json!({ "name": "Default", "email": "default@example.com" }),
+            &put_opts(),
+        )
+        .expect("first get_or_create");
+    assert!(was_created);
+    assert_eq!(created.id, "settings");
+    assert_eq!(created.data["name"], "Default");
+
+    adapter
+        .patch(
+            &def,
+            json!({ "name": "Customized" }),
+            &PatchOptions {
+                id: "settings".to_string(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+
+    let (existing, was_created) = adapter
+        .get_or_create(
+            &def,
+            "settings",
+            json!({ "name": "Default", "email": "default@example.com" }),
+            &put_opts(),
+        )
+        .expect("second get_or_create");
+    assert!(!was_created);
+    assert_eq!(existing.data["name"], "Customized");
+    assert_eq!(
+        adapter
+            .get_all(&def, &ListOptions::default())
+            .unwrap()
+            .records
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn get_or_create_errors_for_deleted_record() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    let data = json!({ "name": "Default", "email": "default@example.com" });
+    adapter
+        .get_or_create(&def, "settings", data.clone(), &put_opts())
+        .expect("get_or_create");
+    adapter
+        .delete(&def, "settings", &DeleteOptions::default())
+        .expect("delete");
+
+    let result = adapter.get_or_create(&def, "settings", data, &put_opts());
+    assert!(result.is_err(), "tombstoned id should not be recreated");
+}
+
 // ============================================================================
 // get_all
 // ============================================================================
@@ -544,6 +912,7 @@ fn query_with_sort_returns_sorted_records() {
         sort: Some(SortInput::Entries(vec![SortEntry {
             field: "name".to_string(),
             direction: SortDirection::Asc,
+            nulls: None,
         }])),
         ..Default::default()
     };
@@ -585,6 +954,109 @@ fn query_with_limit_and_offset_paginates() {
     assert_eq!(result.total, Some(5));
 }
 
+fn profiles_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("profiles")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("name".to_string(), t::string());
+                s.insert("nickname".to_string(), t::optional(t::string()));
+                s
+            })
+            .index_with(&["nickname"], Some("idx_nickname"), false, true)
+            .build(),
+    )
+}
+
+#[test]
+fn query_non_null_conditions_scan_sparse_index() {
+    use betterbase_db::query::types::Query;
+
+    let def = profiles_def();
+    let adapter = make_adapter_arc(def.clone());
+    for data in [
+        json!({ "name": "Alice", "nickname": "Al" }),
+        json!({ "name": "Bob", "nickname": "Bobby" }),
+        json!({ "name": "Carol" }),
+    ] {
+        adapter.put(&def, data, &put_opts()).expect("put");
+    }
+
+    let query = |filter| Query {
+        filter: Some(filter),
+        ..Default::default()
+    };
+    let names = |filter| {
+        let mut names: Vec<String> = adapter
+            .query(&def, &query(filter))
+            .expect("query")
+            .records
+            .iter()
+            .map(|r| r.data["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    let non_null = json!({ "nickname": { "$ne": null } });
+    let plan = adapter.explain_query(&def, &query(non_null.clone()));
+    assert_eq!(plan.scan.as_ref().unwrap().index.name(), "idx_nickname");
+    assert_eq!(names(non_null), vec!["Alice", "Bob"]);
+
+    let pattern = json!({ "nickname": { "$regex": "^b", "$options": "i" } });
+    let plan = adapter.explain_query(&def, &query(pattern.clone()));
+    assert_eq!(plan.scan.as_ref().unwrap().index.name(), "idx_nickname");
+    assert_eq!(names(pattern), vec!["Bob"]);
+
+    // Unset optional fields are stored as null, which `$exists` counts as
+    // present, so it cannot narrow to the sparse index
+    let exists = json!({ "nickname": { "$exists": true } });
+    assert!(adapter
+        .explain_query(&def, &query(exists.clone()))
+        .scan
+        .is_none());
+    assert_eq!(names(exists).len(), 3);
+}
+
+#[test]
+fn query_regex_with_options() {
+    use betterbase_db::error::{LessDbError, QueryError};
+    use betterbase_db::query::types::Query;
+
+    let def = users_def();
+    let adapter = make_adapter(&def);
+    for name in ["Alice", "alicia", "Bob"] {
+        adapter
+            .put(
+                &def,
+                json!({ "name": name, "email": "x@x.com" }),
+                &put_opts(),
+            )
+            .expect("put");
+    }
+
+    let query = |filter| Query {
+        filter: Some(filter),
+        ..Default::default()
+    };
+    let result = adapter
+        .query(
+            &def,
+            &query(json!({ "name": { "$regex": "^ali", "$options": "i" } })),
+        )
+        .expect("query");
+    assert_eq!(result.records.len(), 2);
+
+    // An invalid pattern fails the query rather than matching nothing
+    let err = adapter
+        .query(&def, &query(json!({ "name": { "$regex": "(" } })))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        LessDbError::Query(QueryError::InvalidRegex(_))
+    ));
+}
+
 // ============================================================================
 // count
 // ============================================================================
@@ -800,6 +1272,7 @@ fn mark_synced_with_snapshot_stays_dirty_if_patches_grew() {
     let snapshot = PushSnapshot {
         pending_patches_length: 0,
         deleted: false,
+        archived: false,
     };
 
     adapter
@@ -847,6 +1320,27 @@ fn set_and_get_last_sequence_round_trip() {
     assert_eq!(seq, 999);
 }
 
+#[test]
+fn push_state_round_trips_and_clears() {
+    let def = users_def();
+    let adapter = make_adapter(&def);
+
+    assert!(adapter.get_push_state("users").expect("get").is_none());
+
+    let state = PushQueueState {
+        batch_ids: vec!["a".to_string(), "b".to_string()],
+        attempts: 2,
+        next_attempt_at: Some(1_700_000_000_000),
+    };
+    adapter
+        .set_push_state("users", Some(&state))
+        .expect("set_push_state");
+    assert_eq!(adapter.get_push_state("users").expect("get"), Some(state));
+
+    adapter.set_push_state("users", None).expect("clear");
+    assert!(adapter.get_push_state("users").expect("get").is_none());
+}
+
 // ============================================================================
 // apply_remote_changes
 // ============================================================================
@@ -871,6 +1365,7 @@ fn apply_remote_changes_inserts_new_record() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 100,
         meta: None,
     };
@@ -927,6 +1422,7 @@ fn apply_remote_changes_updates_existing_record() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 200,
         meta: None,
     };
@@ -971,6 +1467,7 @@ fn apply_remote_changes_handles_tombstone() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 300,
         meta: None,
     };
@@ -1094,6 +1591,110 @@ fn unique_constraint_allows_self_patch() {
     );
 }
 
+/// Members collection unique on (orgId, email).
+fn members_def() -> CollectionDef {
+    collection("members")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("orgId".to_string(), t::string());
+            s.insert("email".to_string(), t::string());
+            s
+        })
+        .unique(&["orgId", "email"], "org_email_unique")
+        .build()
+}
+
+#[test]
+fn compound_unique_error_carries_fields_and_existing_id() {
+    let def = members_def();
+    let adapter = make_adapter_arc(Arc::new(members_def()));
+
+    let existing = adapter
+        .put(
+            &def,
+            json!({ "orgId": "o1", "email": "a@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+    adapter
+        .put(
+            &def,
+            json!({ "orgId": "o2", "email": "a@x.com" }),
+            &put_opts(),
+        )
+        .expect("same email in another org");
+
+    let err = adapter
+        .put(
+            &def,
+            json!({ "orgId": "o1", "email": "a@x.com" }),
+            &put_opts(),
+        )
+        .expect_err("duplicate pair");
+    let conflict = err.unique_conflict().expect("unique conflict");
+    assert_eq!(conflict.index, "org_email_unique");
+    assert_eq!(conflict.existing_id, existing.id);
+    // Index order, not alphabetical
+    let keys: Vec<&str> = conflict.fields.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["orgId", "email"]);
+    assert_eq!(
+        serde_json::Value::Object(conflict.fields),
+        json!({ "orgId": "o1", "email": "a@x.com" })
+    );
+}
+
+#[test]
+fn bulk_put_attributes_unique_errors_to_input_index() {
+    let def = members_def();
+    let adapter = make_adapter_arc(Arc::new(members_def()));
+
+    let existing = adapter
+        .put(
+            &def,
+            json!({ "orgId": "o1", "email": "a@x.com" }),
+            &put_opts(),
+        )
+        .expect("put");
+
+    let result = adapter
+        .bulk_put(
+            &def,
+            vec![
+                json!({ "orgId": "o1", "email": "b@x.com" }),
+                json!({ "id": "dup", "orgId": "o1", "email": "a@x.com" }),
+                json!({ "orgId": "o2", "email": "a@x.com" }),
+            ],
+            &put_opts(),
+        )
+        .expect("bulk_put");
+
+    assert_eq!(result.records.len(), 2);
+    assert_eq!(result.errors.len(), 1);
+    let error = &result.errors[0];
+    assert_eq!(error.input_index, Some(1));
+    assert_eq!(error.id, "dup");
+    let conflict = error.unique.as_ref().expect("unique details");
+    assert_eq!(conflict.index, "org_email_unique");
+    assert_eq!(conflict.existing_id, existing.id);
+}
+
+#[test]
+fn initialize_rejects_unique_index_on_unknown_field() {
+    let mut def = members_def();
+    if let IndexDefinition::Field(fi) = &mut def.indexes[0] {
+        fi.fields[1].field = "mail".to_string();
+    }
+
+    let backend = SqliteBackend::open_in_memory().expect("open");
+    let mut adapter = Adapter::new(backend);
+    let err = adapter
+        .initialize(&[Arc::new(def)])
+        .expect_err("unknown field");
+    assert_eq!(err.code(), "STORAGE_UNKNOWN_INDEX_FIELD");
+    assert_eq!(err.field(), Some("mail"));
+    assert!(!adapter.is_initialized());
+}
+
 // ============================================================================
 // bulk_put — error handling
 // ============================================================================
@@ -1499,10 +2100,12 @@ fn query_sort_multi_field_tie_breaking() {
             SortEntry {
                 field: "name".to_string(),
                 direction: SortDirection::Asc,
+                nulls: None,
             },
             SortEntry {
                 field: "email".to_string(),
                 direction: SortDirection::Desc,
+                nulls: None,
             },
         ])),
         ..Default::default()
@@ -1563,6 +2166,7 @@ fn mark_synced_with_snapshot_patches_grew_stays_dirty() {
     let snapshot = PushSnapshot {
         pending_patches_length: 0, // pretend no patches at snapshot time
         deleted: false,
+        archived: false,
     };
 
     // Patch the record (this grows pending_patches)
@@ -1591,3 +2195,140 @@ fn mark_synced_with_snapshot_patches_grew_stays_dirty() {
     );
     assert_eq!(fetched.sequence, 50, "sequence should still be updated");
 }
+
+// ============================================================================
+// Sort null placement — SQLite index order vs MemoryMapped in-memory sort
+// ============================================================================
+
+fn ranked_def() -> CollectionDef {
+    collection("ranked")
+        .v(1, {
+            let mut s = BTreeMap::new();
+            s.insert("name".to_string(), t::string());
+            s.insert("rank".to_string(), t::optional(t::number()));
+            s
+        })
+        .index_with(&["rank", "name"], Some("by_rank"), false, false)
+        .build()
+}
+
+fn seed_ranked<B: betterbase_db::storage::traits::StorageBackend>(
+    adapter: &Adapter<B>,
+    def: &CollectionDef,
+) {
+    for data in [
+        json!({ "name": "a", "rank": 2 }),
+        json!({ "name": "b" }),
+        json!({ "name": "c", "rank": 1 }),
+        json!({ "name": "d", "rank": null }),
+        json!({ "name": "e", "rank": 3 }),
+        json!({ "name": "f" }),
+    ] {
+        adapter.put(def, data, &put_opts()).expect("put");
+    }
+}
+
+fn ranked_names<B: betterbase_db::storage::traits::StorageBackend>(
+    adapter: &Adapter<B>,
+    def: &CollectionDef,
+    direction: betterbase_db::query::types::SortDirection,
+    nulls: Option<betterbase_db::query::types::NullsOrder>,
+) -> Vec<String> {
+    use betterbase_db::query::types::{Query, SortEntry, SortInput};
+
+    let query = Query {
+        sort: Some(SortInput::Entries(vec![
+            SortEntry {
+                field: "rank".to_string(),
+                direction: direction.clone(),
+                nulls,
+            },
+            SortEntry {
+                field: "name".to_string(),
+                direction,
+                nulls: None,
+            },
+        ])),
+        ..Default::default()
+    };
+    adapter
+        .query(def, &query)
+        .expect("query")
+        .records
+        .iter()
+        .map(|r| r.data["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn sort_nulls_order_matches_between_sqlite_and_memory_mapped() {
+    use betterbase_db::query::types::{NullsOrder, Query, SortDirection, SortEntry, SortInput};
+    use betterbase_db::storage::memory_mapped::MemoryMapped;
+
+    let def = Arc::new(ranked_def());
+
+    let mut sqlite = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    sqlite.initialize(&[def.as_ref()]).expect("backend init");
+    let sqlite_adapter = {
+        let mut a = Adapter::new(sqlite);
+        a.initialize(std::slice::from_ref(&def))
+            .expect("adapter init");
+        a
+    };
+
+    let mut inner = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    inner.initialize(&[def.as_ref()]).expect("backend init");
+    let mut mm = MemoryMapped::new(inner);
+    mm.load_from_inner().expect("load");
+    let mm_adapter = {
+        let mut a = Adapter::new(mm);
+        a.initialize(std::slice::from_ref(&def))
+            .expect("adapter init");
+        a
+    };
+
+    seed_ranked(&sqlite_adapter, &def);
+    seed_ranked(&mm_adapter, &def);
+
+    // Default placement is served by the SQLite index order.
+    let plan = sqlite_adapter.explain_query(
+        &def,
+        &Query {
+            sort: Some(SortInput::Entries(vec![
+                SortEntry {
+                    field: "rank".to_string(),
+                    direction: SortDirection::Asc,
+                    nulls: None,
+                },
+                SortEntry {
+                    field: "name".to_string(),
+                    direction: SortDirection::Asc,
+                    nulls: None,
+                },
+            ])),
+            ..Default::default()
+        },
+    );
+    assert!(plan.index_provides_sort);
+
+    let cases = [
+        (SortDirection::Asc, None, ["c", "a", "e", "b", "d", "f"]),
+        (SortDirection::Desc, None, ["f", "d", "b", "e", "a", "c"]),
+        (
+            SortDirection::Asc,
+            Some(NullsOrder::First),
+            ["b", "d", "f", "c", "a", "e"],
+        ),
+        (
+            SortDirection::Desc,
+            Some(NullsOrder::Last),
+            ["e", "a", "c", "f", "d", "b"],
+        ),
+    ];
+    for (direction, nulls, expected) in cases {
+        let from_sqlite = ranked_names(&sqlite_adapter, &def, direction.clone(), nulls);
+        let from_memory = ranked_names(&mm_adapter, &def, direction.clone(), nulls);
+        assert_eq!(from_sqlite, expected, "sqlite {direction:?} {nulls:?}");
+        assert_eq!(from_memory, expected, "memory {direction:?} {nulls:?}");
+    }
+}
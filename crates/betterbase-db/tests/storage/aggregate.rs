@@ -0,0 +1,452 @@
+//! Tests for `Adapter::aggregate`: grouped sum/avg/min/max/count over seeded
+//! data, with and without an index on the group-by field, both when SQLite
+//! aggregates the stored data and when the adapter folds the records.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    query::aggregate::{AggregateRow, AggregateSpec, Metric},
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageWrite},
+    },
+    types::{DeleteOptions, PutOptions},
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn expenses_def(indexed: bool) -> Arc<CollectionDef> {
+    let builder = collection("expenses").v(1, {
+        let mut s = BTreeMap::new();
+        s.insert("category".to_string(), t::string());
+        s.insert("amount".to_string(), t::optional(t::number()));
+        s
+    });
+    let builder = if indexed {
+        builder.index(&["category"])
+    } else {
+        builder
+    };
+    Arc::new(builder.build())
+}
+
+fn make_adapter(def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn put<B: StorageBackend>(adapter: &Adapter<B>, def: &CollectionDef, id: &str, data: Value) {
+    adapter
+        .put(
+            def,
+            data,
+            &PutOptions {
+                id: Some(id.to_string()),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("put");
+}
+
+/// travel: 100, 300 · food: 12, 30, (no amount) · misc: (no amount)
+fn seed<B: StorageBackend>(adapter: &Adapter<B>, def: &CollectionDef) {
+    put(
+        adapter,
+        def,
+        "e1",
+        json!({ "category": "travel", "amount": 100 }),
+    );
+    put(
+        adapter,
+        def,
+        "e2",
+        json!({ "category": "food", "amount": 12 }),
+    );
+    put(
+        adapter,
+        def,
+        "e3",
+        json!({ "category": "travel", "amount": 300 }),
+    );
+    put(
+        adapter,
+        def,
+        "e4",
+        json!({ "category": "food", "amount": 30 }),
+    );
+    put(adapter, def, "e5", json!({ "category": "food" }));
+    put(adapter, def, "e6", json!({ "category": "misc" }));
+}
+
+fn by_category() -> AggregateSpec {
+    AggregateSpec {
+        group_by: vec!["category".to_string()],
+        metrics: vec![
+            Metric::Count,
+            Metric::Sum("amount".to_string()),
+            Metric::Avg("amount".to_string()),
+            Metric::Min("amount".to_string()),
+            Metric::Max("amount".to_string()),
+        ],
+        ..Default::default()
+    }
+}
+
+fn row(category: &str, values: &[Option<f64>]) -> AggregateRow {
+    AggregateRow {
+        group: vec![json!(category)],
+        values: values.to_vec(),
+        skipped: vec![0; values.len()],
+    }
+}
+
+fn expected_by_category() -> Vec<AggregateRow> {
+    vec![
+        row(
+            "food",
+            &[Some(3.0), Some(42.0), Some(21.0), Some(12.0), Some(30.0)],
+        ),
+        row("misc", &[Some(1.0), None, None, None, None]),
+        row(
+            "travel",
+            &[
+                Some(2.0),
+                Some(400.0),
+                Some(200.0),
+                Some(100.0),
+                Some(300.0),
+            ],
+        ),
+    ]
+}
+
+// ============================================================================
+// Grouping
+// ============================================================================
+
+#[test]
+fn sums_and_averages_per_group() {
+    let def = expenses_def(false);
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let rows = adapter.aggregate(&def, &by_category()).expect("aggregate");
+    assert_eq!(rows, expected_by_category());
+}
+
+#[test]
+fn indexed_group_by_matches_full_scan() {
+    let def = expenses_def(true);
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let rows = adapter.aggregate(&def, &by_category()).expect("aggregate");
+    assert_eq!(rows, expected_by_category());
+}
+
+#[test]
+fn empty_group_by_aggregates_whole_collection() {
+    let def = expenses_def(false);
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+
+    let spec = AggregateSpec {
+        group_by: Vec::new(),
+        metrics: vec![Metric::Count, Metric::Sum("amount".to_string())],
+        ..Default::default()
+    };
+    let rows = adapter.aggregate(&def, &spec).expect("aggregate");
+    assert_eq!(
+        rows,
+        vec![AggregateRow {
+            group: Vec::new(),
+            values: vec![Some(6.0), Some(442.0)],
+            skipped: vec![0, 0],
+        }]
+    );
+}
+
+#[test]
+fn deleted_records_are_excluded() {
+    let def = expenses_def(true);
+    let adapter = make_adapter(&def);
+    seed(&adapter, &def);
+    adapter
+        .delete(&def, "e3", &DeleteOptions::default())
+        .expect("delete");
+
+    let spec = AggregateSpec {
+        group_by: vec!["category".to_string()],
+        metrics: vec![Metric::Sum("amount".to_string())],
+        ..Default::default()
+    };
+    let rows = adapter.aggregate(&def, &spec).expect("aggregate");
+    assert_eq!(rows[2], row("travel", &[Some(100.0)]));
+}
+
+#[test]
+fn empty_collection_yields_no_rows() {
+    let def = expenses_def(false);
+    let adapter = make_adapter(&def);
+
+    let rows = adapter.aggregate(&def, &by_category()).expect("aggregate");
+    assert!(rows.is_empty());
+}
+
+#[test]
+fn filter_restricts_aggregated_records() {
+    for indexed in [false, true] {
+        let def = expenses_def(indexed);
+        let adapter = make_adapter(&def);
+        seed(&adapter, &def);
+
+        // Served by the category index when there is one
+        let spec = AggregateSpec {
+            filter: Some(json!({ "category": "food" })),
+            ..by_category()
+        };
+        let rows = adapter.aggregate(&def, &spec).expect("aggregate");
+        assert_eq!(
+            rows,
+            expected_by_category()[..1].to_vec(),
+            "indexed: {indexed}"
+        );
+
+        // Never index-served: the adapter filters the records itself
+        let spec = AggregateSpec {
+            filter: Some(json!({ "amount": { "$gte": 30 } })),
+            ..by_category()
+        };
+        let rows = adapter.aggregate(&def, &spec).expect("aggregate");
+        assert_eq!(
+            rows,
+            vec![
+                row(
+                    "food",
+                    &[Some(1.0), Some(30.0), Some(30.0), Some(30.0), Some(30.0)],
+                ),
+                row(
+                    "travel",
+                    &[
+                        Some(2.0),
+                        Some(400.0),
+                        Some(200.0),
+                        Some(100.0),
+                        Some(300.0),
+                    ],
+                ),
+            ],
+            "indexed: {indexed}"
+        );
+    }
+}
+
+#[test]
+fn groups_by_nested_paths() {
+    let def = Arc::new(
+        collection("trips")
+            .v(1, {
+                let mut address = BTreeMap::new();
+                address.insert("country".to_string(), t::string());
+                let mut s = BTreeMap::new();
+                s.insert("address".to_string(), t::object(address));
+                s.insert("nights".to_string(), t::number());
+                s
+            })
+            .build(),
+    );
+    let adapter = make_adapter(&def);
+    put(
+        &adapter,
+        &def,
+        "t1",
+        json!({ "address": { "country": "NO" }, "nights": 3 }),
+    );
+    put(
+        &adapter,
+        &def,
+        "t2",
+        json!({ "address": { "country": "FR" }, "nights": 2 }),
+    );
+    put(
+        &adapter,
+        &def,
+        "t3",
+        json!({ "address": { "country": "NO" }, "nights": 4 }),
+    );
+
+    let spec = AggregateSpec {
+        group_by: vec!["address.country".to_string()],
+        metrics: vec![Metric::Sum("nights".to_string())],
+        ..Default::default()
+    };
+    let rows = adapter.aggregate(&def, &spec).expect("aggregate");
+    assert_eq!(rows, vec![row("FR", &[Some(2.0)]), row("NO", &[Some(7.0)])]);
+}
+
+#[test]
+fn non_numeric_values_are_skipped_and_counted() {
+    let def = Arc::new(
+        collection("readings")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("sensor".to_string(), t::string());
+                s.insert(
+                    "value".to_string(),
+                    t::optional(t::union(vec![t::number(), t::string(), t::boolean()])),
+                );
+                s
+            })
+            .build(),
+    );
+    let adapter = make_adapter(&def);
+    put(&adapter, &def, "r1", json!({ "sensor": "a", "value": 1.5 }));
+    put(
+        &adapter,
+        &def,
+        "r2",
+        json!({ "sensor": "a", "value": "12" }),
+    );
+    put(
+        &adapter,
+        &def,
+        "r3",
+        json!({ "sensor": "a", "value": true }),
+    );
+    put(&adapter, &def, "r4", json!({ "sensor": "a" }));
+    put(
+        &adapter,
+        &def,
+        "r5",
+        json!({ "sensor": "b", "value": "n/a" }),
+    );
+
+    let spec = AggregateSpec {
+        group_by: vec!["sensor".to_string()],
+        metrics: vec![Metric::Count, Metric::Sum("value".to_string())],
+        ..Default::default()
+    };
+    let expected = vec![
+        AggregateRow {
+            group: vec![json!("a")],
+            values: vec![Some(4.0), Some(1.5)],
+            skipped: vec![0, 2],
+        },
+        AggregateRow {
+            group: vec![json!("b")],
+            values: vec![Some(1.0), None],
+            skipped: vec![0, 1],
+        },
+    ];
+    assert_eq!(adapter.aggregate(&def, &spec).expect("aggregate"), expected);
+
+    // The adapter's own fold applies the same rules
+    let folded = AggregateSpec {
+        filter: Some(json!({ "sensor": { "$regex": "^[ab]$" } })),
+        ..spec
+    };
+    assert_eq!(
+        adapter.aggregate(&def, &folded).expect("aggregate"),
+        expected
+    );
+}
+
+#[test]
+fn records_awaiting_migration_are_aggregated_migrated() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("aggregate.db");
+    let path = path.to_str().unwrap();
+
+    let v1 = expenses_def(false);
+    {
+        let mut backend = SqliteBackend::open(path).expect("open DB");
+        backend
+            .initialize(&[v1.as_ref()])
+            .expect("backend initialize");
+        let mut adapter = Adapter::new(backend);
+        adapter
+            .initialize(std::slice::from_ref(&v1))
+            .expect("adapter initialize");
+        seed(&adapter, &v1);
+    }
+
+    // v2 stores amounts in cents
+    let v2 = Arc::new(
+        collection("expenses")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("category".to_string(), t::string());
+                s.insert("amount".to_string(), t::optional(t::number()));
+                s
+            })
+            .v(
+                2,
+                {
+                    let mut s = BTreeMap::new();
+                    s.insert("category".to_string(), t::string());
+                    s.insert("amount".to_string(), t::optional(t::number()));
+                    s
+                },
+                |mut data| {
+                    if let Some(amount) = data["amount"].as_f64() {
+                        data["amount"] = json!(amount * 100.0);
+                    }
+                    Ok(data)
+                },
+            )
+            .build(),
+    );
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    backend
+        .initialize(&[v2.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(&v2))
+        .expect("adapter initialize");
+
+    let spec = AggregateSpec {
+        metrics: vec![Metric::Sum("amount".to_string())],
+        ..Default::default()
+    };
+    let rows = adapter.aggregate(&v2, &spec).expect("aggregate");
+    assert_eq!(rows[0].values, vec![Some(44_200.0)]);
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+#[test]
+fn spec_deserializes_from_js_shape() {
+    let spec: AggregateSpec = serde_json::from_value(json!({
+        "groupBy": ["category"],
+        "metrics": [{ "op": "count" }, { "op": "avg", "field": "amount" }],
+        "filter": { "amount": { "$gt": 10 } },
+    }))
+    .expect("deserialize");
+    assert_eq!(spec.group_by, vec!["category".to_string()]);
+    assert_eq!(
+        spec.metrics,
+        vec![Metric::Count, Metric::Avg("amount".to_string())]
+    );
+    assert_eq!(spec.filter, Some(json!({ "amount": { "$gt": 10 } })));
+}
@@ -0,0 +1,464 @@
+//! Tests for archival deletes: hidden on every device, kept until an admin
+//! tombstones them.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_crypto::UCANPermission;
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    query::types::Query,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageRead, StorageSync, StorageWrite},
+    },
+    types::{
+        ApplyRemoteOptions, DeleteKind, DeleteOptions, GetOptions, ListOptions, PatchOptions,
+        PurgeTombstonesOptions, PushSnapshot, PutOptions, RemoteRecord, StoredRecordWithMeta,
+    },
+};
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn notes_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s
+            })
+            .build(),
+    )
+}
+
+fn make_adapter(def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn put_opts() -> PutOptions {
+    PutOptions {
+        session_id: Some(SID),
+        ..Default::default()
+    }
+}
+
+fn archive_opts() -> DeleteOptions {
+    DeleteOptions {
+        session_id: Some(SID),
+        kind: DeleteKind::Archive,
+        ..Default::default()
+    }
+}
+
+/// Put a synced record titled `title` and return its id.
+fn put_synced(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, title: &str) -> String {
+    let record = adapter
+        .put(def, json!({ "title": title }), &put_opts())
+        .expect("put");
+    adapter
+        .mark_synced(def, &record.id, 1, None)
+        .expect("mark_synced");
+    record.id
+}
+
+fn dirty_record(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    id: &str,
+) -> StoredRecordWithMeta {
+    adapter
+        .get_dirty(def)
+        .expect("get_dirty")
+        .records
+        .into_iter()
+        .find(|r| r.id == id)
+        .expect("record is dirty")
+}
+
+/// Build the remote record another device would pull for a dirty record.
+fn to_remote(record: &StoredRecordWithMeta, sequence: i64) -> RemoteRecord {
+    RemoteRecord {
+        id: record.id.clone(),
+        version: record.version,
+        crdt: (!record.deleted).then(|| record.crdt.clone()),
+        deleted: record.deleted,
+        archived: record.archived,
+        sequence,
+        meta: None,
+    }
+}
+
+fn live_count(adapter: &Adapter<SqliteBackend>, def: &CollectionDef) -> usize {
+    adapter.count(def, None).expect("count")
+}
+
+// ============================================================================
+// Local archive
+// ============================================================================
+
+#[test]
+fn archive_hides_record_but_keeps_data() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+
+    assert!(adapter.delete(&def, &id, &archive_opts()).expect("archive"));
+
+    assert!(adapter
+        .get(&def, &id, &GetOptions::default())
+        .expect("get")
+        .is_none());
+    assert_eq!(live_count(&adapter, &def), 0);
+    assert!(adapter
+        .query(&def, &Query::default())
+        .expect("query")
+        .records
+        .is_empty());
+
+    let archived = adapter
+        .get(
+            &def,
+            &id,
+            &GetOptions {
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .expect("get")
+        .expect("archived record is kept");
+    assert!(archived.archived);
+    assert!(!archived.deleted);
+    assert_eq!(archived.data["title"], "Plan");
+}
+
+#[test]
+fn get_all_honours_include_flags() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let _live = put_synced(&adapter, &def, "live");
+    let archived = put_synced(&adapter, &def, "archived");
+    let deleted = put_synced(&adapter, &def, "deleted");
+    adapter.delete(&def, &archived, &archive_opts()).unwrap();
+    adapter
+        .delete(&def, &deleted, &DeleteOptions::default())
+        .unwrap();
+
+    let titles = |include_deleted, include_archived| {
+        let mut titles: Vec<String> = adapter
+            .get_all(
+                &def,
+                &ListOptions {
+                    include_deleted,
+                    include_archived,
+                    ..Default::default()
+                },
+            )
+            .expect("get_all")
+            .records
+            .iter()
+            .map(|r| r.data["title"].as_str().unwrap_or("").to_string())
+            .collect();
+        titles.sort();
+        titles
+    };
+
+    assert_eq!(titles(false, false), vec!["live"]);
+    assert_eq!(titles(false, true), vec!["archived", "live"]);
+    assert_eq!(titles(true, false).len(), 2);
+    assert_eq!(titles(true, true).len(), 3);
+}
+
+#[test]
+fn delete_ignores_archived_record() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+    adapter.delete(&def, &id, &archive_opts()).unwrap();
+
+    assert!(!adapter
+        .delete(&def, &id, &DeleteOptions::default())
+        .expect("delete"));
+    assert!(!adapter.delete(&def, &id, &archive_opts()).expect("archive"));
+}
+
+#[test]
+fn edits_keep_record_archived() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+    adapter.delete(&def, &id, &archive_opts()).unwrap();
+
+    let patched = adapter
+        .patch(
+            &def,
+            json!({ "title": "Plan v2" }),
+            &PatchOptions {
+                id: id.clone(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+    assert!(patched.archived);
+    assert_eq!(live_count(&adapter, &def), 0);
+}
+
+#[test]
+fn purge_never_removes_archives() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+    adapter.delete(&def, &id, &archive_opts()).unwrap();
+    adapter.mark_synced(&def, &id, 2, None).unwrap();
+
+    let purged = adapter
+        .purge_tombstones(
+            &def,
+            &PurgeTombstonesOptions {
+                older_than_seconds: None,
+                dry_run: false,
+                skip_dirty: false,
+            },
+        )
+        .expect("purge");
+    assert_eq!(purged, 0);
+    assert!(adapter
+        .get(
+            &def,
+            &id,
+            &GetOptions {
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .is_some());
+}
+
+// ============================================================================
+// Sync
+// ============================================================================
+
+#[test]
+fn archive_is_pushed_with_crdt_and_stays_dirty_until_synced() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+    adapter.delete(&def, &id, &archive_opts()).unwrap();
+
+    let dirty = dirty_record(&adapter, &def, &id);
+    assert!(dirty.archived);
+    assert!(!dirty.deleted);
+    assert!(!dirty.crdt.is_empty());
+
+    // A snapshot taken before the archive does not clear the dirty flag.
+    let stale = PushSnapshot {
+        pending_patches_length: dirty.pending_patches.len(),
+        deleted: false,
+        archived: false,
+    };
+    adapter.mark_synced(&def, &id, 2, Some(&stale)).unwrap();
+    assert!(dirty_record(&adapter, &def, &id).archived);
+
+    let current = PushSnapshot {
+        archived: true,
+        ..stale
+    };
+    adapter.mark_synced(&def, &id, 3, Some(&current)).unwrap();
+    assert!(adapter.get_dirty(&def).unwrap().records.is_empty());
+}
+
+#[test]
+fn remote_archive_hides_record_on_other_device() {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+
+    let id = put_synced(&device_a, &def, "Plan");
+    let created = device_a
+        .get(&def, &id, &GetOptions::default())
+        .unwrap()
+        .unwrap();
+    let initial = RemoteRecord {
+        id: id.clone(),
+        version: 1,
+        crdt: Some(created.crdt.clone()),
+        deleted: false,
+        archived: false,
+        sequence: 1,
+        meta: None,
+    };
+    device_b
+        .apply_remote_changes(&def, &[initial], &ApplyRemoteOptions::default())
+        .unwrap();
+    assert_eq!(live_count(&device_b, &def), 1);
+
+    // A writer on device A archives; device B hides it but keeps the data.
+    device_a.delete(&def, &id, &archive_opts()).unwrap();
+    let archive = to_remote(&dirty_record(&device_a, &def, &id), 2);
+    device_b
+        .apply_remote_changes(&def, &[archive], &ApplyRemoteOptions::default())
+        .unwrap();
+
+    assert_eq!(live_count(&device_b, &def), 0);
+    let kept = device_b
+        .get(
+            &def,
+            &id,
+            &GetOptions {
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .expect("archived record is kept");
+    assert_eq!(kept.data["title"], "Plan");
+}
+
+#[test]
+fn admin_tombstone_of_archive_propagates() {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+
+    let id = put_synced(&device_a, &def, "Plan");
+    device_a.delete(&def, &id, &archive_opts()).unwrap();
+    let archive = to_remote(&dirty_record(&device_a, &def, &id), 2);
+    device_a.mark_synced(&def, &id, 2, None).unwrap();
+    device_b
+        .apply_remote_changes(&def, &[archive], &ApplyRemoteOptions::default())
+        .unwrap();
+
+    // The admin on device B turns the archive into a tombstone.
+    assert!(device_b
+        .tombstone_archived(&def, &id, &DeleteOptions::default())
+        .expect("tombstone_archived"));
+    assert!(!device_b
+        .tombstone_archived(&def, &id, &DeleteOptions::default())
+        .expect("already tombstoned"));
+
+    let tombstone = dirty_record(&device_b, &def, &id);
+    assert!(tombstone.deleted);
+    assert!(!tombstone.archived);
+
+    device_a
+        .apply_remote_changes(
+            &def,
+            &[to_remote(&tombstone, 3)],
+            &ApplyRemoteOptions::default(),
+        )
+        .unwrap();
+    let gone = device_a
+        .get(
+            &def,
+            &id,
+            &GetOptions {
+                include_deleted: true,
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .expect("tombstone");
+    assert!(gone.deleted);
+}
+
+/// Pull a tombstone for a synced note into a second device under a policy
+/// that makes writers archive, and check the note is kept archived.
+fn assert_tombstone_lands_as_archive(author_permission: Option<UCANPermission>) {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+
+    let id = put_synced(&device_a, &def, "Plan");
+    let created = device_a
+        .get(&def, &id, &GetOptions::default())
+        .unwrap()
+        .unwrap();
+    let initial = RemoteRecord {
+        id: id.clone(),
+        version: 1,
+        crdt: Some(created.crdt.clone()),
+        deleted: false,
+        archived: false,
+        sequence: 1,
+        meta: None,
+    };
+    device_b
+        .apply_remote_changes(&def, &[initial], &ApplyRemoteOptions::default())
+        .unwrap();
+
+    device_a
+        .delete(&def, &id, &DeleteOptions::default())
+        .unwrap();
+    let tombstone = to_remote(&dirty_record(&device_a, &def, &id), 2);
+    let result = device_b
+        .apply_remote_changes(
+            &def,
+            &[tombstone],
+            &ApplyRemoteOptions {
+                writer_delete: Some(DeleteKind::Archive),
+                author_permission,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert!(result.applied[0].previous_data.is_none());
+    assert_eq!(live_count(&device_b, &def), 0);
+    let kept = device_b
+        .get(
+            &def,
+            &id,
+            &GetOptions {
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .expect("archived record is kept");
+    assert!(kept.archived);
+    assert!(!kept.deleted);
+    assert_eq!(kept.data["title"], "Plan");
+}
+
+#[test]
+fn writer_tombstone_lands_as_archive_under_policy() {
+    // A writer's client sent a hard delete the space policy doesn't allow.
+    assert_tombstone_lands_as_archive(Some(UCANPermission::Write));
+}
+
+#[test]
+fn unattributed_tombstone_lands_as_archive_under_policy() {
+    // Nothing verified says who sent it, so it may be a writer's.
+    assert_tombstone_lands_as_archive(None);
+}
+
+#[test]
+fn tombstone_archived_ignores_live_records() {
+    let def = notes_def();
+    let adapter = make_adapter(&def);
+    let id = put_synced(&adapter, &def, "Plan");
+
+    assert!(!adapter
+        .tombstone_archived(&def, &id, &DeleteOptions::default())
+        .expect("tombstone_archived"));
+    assert_eq!(live_count(&adapter, &def), 1);
+}
@@ -0,0 +1,415 @@
+//! Tests for `Adapter::compact`: tombstone removal, edit-chain trimming,
+//! and leaving unsynced or recent data alone.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use betterbase_crypto::{
+    encode_did_key, export_public_key_jwk, generate_p256_keypair, parse_edit_chain,
+    serialize_edit_chain, sign_edit_entry, verify_edit_chain, verify_edit_chain_tail, EditEntry,
+};
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    reactive::adapter::ReactiveAdapter,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        memory_mapped::MemoryMapped,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageSync, StorageWrite},
+    },
+    sync::{
+        types::{
+            OutboundRecord, PullResult, PushAck, SyncManagerOptions, SyncTransport,
+            SyncTransportError,
+        },
+        SyncManager,
+    },
+    types::{CompactOptions, DeleteOptions, GetOptions, PatchOptions, PutOptions},
+};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn notes_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s
+            })
+            .build(),
+    )
+}
+
+fn open_backend(path: &str, def: &CollectionDef) -> SqliteBackend {
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    backend.initialize(&[def]).expect("backend initialize");
+    backend
+}
+
+fn make_adapter(path: &str, def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut adapter = Adapter::new(open_backend(path, def));
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+/// Serialized edit chain of `n` placeholder entries, oldest first.
+fn chain(n: u64) -> String {
+    let entries: Vec<Value> = (1..=n).map(|i| json!({ "t": i, "d": [] })).collect();
+    serde_json::to_string(&entries).unwrap()
+}
+
+fn chain_timestamps(meta: &Option<Value>) -> Vec<u64> {
+    let raw = meta.as_ref().unwrap()["_editChain"].as_str().unwrap();
+    serde_json::from_str::<Vec<Value>>(raw)
+        .unwrap()
+        .iter()
+        .map(|e| e["t"].as_u64().unwrap())
+        .collect()
+}
+
+fn put(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    id: &str,
+    history: Option<u64>,
+    synced: bool,
+) {
+    let opts = PutOptions {
+        id: Some(id.to_string()),
+        session_id: Some(SID),
+        meta: history.map(|n| json!({ "_editChain": chain(n), "spaceId": "s1" })),
+        ..Default::default()
+    };
+    adapter
+        .put(def, json!({ "title": id }), &opts)
+        .expect("put");
+    if synced {
+        adapter.mark_synced(def, id, 1, None).expect("mark_synced");
+    }
+}
+
+fn tombstone(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, id: &str, synced: bool) {
+    put(adapter, def, id, None, true);
+    adapter
+        .delete(def, id, &DeleteOptions::default())
+        .expect("delete");
+    if synced {
+        adapter.mark_synced(def, id, 2, None).expect("mark_synced");
+    }
+}
+
+fn backdate_deletion(path: &str, def: &CollectionDef, id: &str) {
+    let backend = open_backend(path, def);
+    let mut record = backend.get_raw(&def.name, id).unwrap().unwrap();
+    record.deleted_at = Some("2000-01-01T00:00:00Z".to_string());
+    backend.put_raw(&record).unwrap();
+}
+
+fn get(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    id: &str,
+) -> Option<betterbase_db::types::StoredRecordWithMeta> {
+    adapter
+        .get(
+            def,
+            id,
+            &GetOptions {
+                include_deleted: true,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+}
+
+// ============================================================================
+// compact
+// ============================================================================
+
+#[test]
+fn compact_drops_old_tombstones_and_trims_synced_histories() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("compact.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def();
+    let adapter = make_adapter(path, &def);
+
+    put(&adapter, &def, "long", Some(5), true);
+    put(&adapter, &def, "unsynced", Some(5), false);
+    put(&adapter, &def, "short", Some(2), true);
+    put(&adapter, &def, "plain", None, true);
+    tombstone(&adapter, &def, "old", true);
+    tombstone(&adapter, &def, "recent", true);
+    tombstone(&adapter, &def, "old-unpushed", false);
+    backdate_deletion(path, &def, "old");
+    backdate_deletion(path, &def, "old-unpushed");
+
+    let before_long = get(&adapter, &def, "long").unwrap();
+    let before_plain = get(&adapter, &def, "plain").unwrap();
+
+    let report = adapter
+        .compact(
+            &def,
+            &CompactOptions {
+                drop_tombstones_older_than: Some(Duration::from_secs(86_400)),
+                trim_history_keep_last: Some(2),
+            },
+        )
+        .expect("compact");
+
+    assert_eq!(report.tombstones_removed, 1);
+    assert_eq!(report.histories_trimmed, 1);
+    assert_eq!(report.history_entries_dropped, 3);
+    assert!(report.bytes_reclaimed.is_some());
+
+    // Tombstones: only the old, pushed one is gone
+    assert!(get(&adapter, &def, "old").is_none());
+    assert!(get(&adapter, &def, "recent").unwrap().deleted);
+    assert!(get(&adapter, &def, "old-unpushed").unwrap().deleted);
+
+    // Histories: newest entries kept, state and other meta untouched
+    let long = get(&adapter, &def, "long").unwrap();
+    assert_eq!(chain_timestamps(&long.meta), vec![4, 5]);
+    assert_eq!(long.meta.as_ref().unwrap()["spaceId"], json!("s1"));
+    assert_eq!(long.data, before_long.data);
+    assert_eq!(long.crdt, before_long.crdt);
+    assert_eq!(long.sequence, before_long.sequence);
+    assert!(!long.dirty);
+    assert_eq!(
+        long.meta.as_ref().unwrap()["_editChainTrimmed"],
+        json!(true)
+    );
+
+    let unsynced = get(&adapter, &def, "unsynced").unwrap();
+    assert_eq!(chain_timestamps(&unsynced.meta), vec![1, 2, 3, 4, 5]);
+    assert!(unsynced.dirty);
+    let short = get(&adapter, &def, "short").unwrap();
+    assert_eq!(chain_timestamps(&short.meta), vec![1, 2]);
+    assert!(short
+        .meta
+        .as_ref()
+        .unwrap()
+        .get("_editChainTrimmed")
+        .is_none());
+    assert_eq!(
+        get(&adapter, &def, "plain").unwrap().data,
+        before_plain.data
+    );
+
+    // Running again finds nothing left to do
+    let again = adapter
+        .compact(
+            &def,
+            &CompactOptions {
+                drop_tombstones_older_than: Some(Duration::from_secs(86_400)),
+                trim_history_keep_last: Some(2),
+            },
+        )
+        .expect("compact");
+    assert_eq!(again.tombstones_removed, 0);
+    assert_eq!(again.histories_trimmed, 0);
+}
+
+#[test]
+fn compact_with_default_options_changes_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("compact.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def();
+    let adapter = make_adapter(path, &def);
+
+    put(&adapter, &def, "long", Some(5), true);
+    tombstone(&adapter, &def, "old", true);
+    backdate_deletion(path, &def, "old");
+
+    let report = adapter
+        .compact(&def, &CompactOptions::default())
+        .expect("compact");
+    assert_eq!(report, Default::default());
+    assert!(get(&adapter, &def, "old").is_some());
+    let long = get(&adapter, &def, "long").unwrap();
+    assert_eq!(chain_timestamps(&long.meta).len(), 5);
+}
+
+#[test]
+fn compact_on_memory_mapped_reaches_the_inner_backend() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("compact.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def();
+    {
+        let adapter = make_adapter(path, &def);
+        put(&adapter, &def, "long", Some(5), true);
+        tombstone(&adapter, &def, "old", true);
+    }
+    backdate_deletion(path, &def, "old");
+
+    let mut mm = MemoryMapped::new(open_backend(path, &def));
+    mm.load_from_inner().expect("load");
+    let mut adapter = ReactiveAdapter::new(Adapter::new(mm));
+    adapter
+        .initialize(std::slice::from_ref(&def))
+        .expect("adapter initialize");
+
+    let report = adapter
+        .compact(
+            &def,
+            &CompactOptions {
+                drop_tombstones_older_than: Some(Duration::from_secs(86_400)),
+                trim_history_keep_last: Some(2),
+            },
+        )
+        .expect("compact");
+    assert_eq!(report.tombstones_removed, 1);
+    assert_eq!(report.histories_trimmed, 1);
+    adapter.with_backend(|mm| mm.flush()).expect("flush");
+
+    let adapter = make_adapter(path, &def);
+    assert!(get(&adapter, &def, "old").is_none());
+    let long = get(&adapter, &def, "long").unwrap();
+    assert_eq!(chain_timestamps(&long.meta), vec![4, 5]);
+}
+
+// ============================================================================
+// Trimmed chains and sync
+// ============================================================================
+
+/// Transport that records pushed records and acks them all.
+#[derive(Default)]
+struct RecordingTransport {
+    pushed: Mutex<Vec<OutboundRecord>>,
+}
+
+#[async_trait]
+impl SyncTransport for RecordingTransport {
+    async fn push(
+        &self,
+        _collection: &str,
+        records: &[OutboundRecord],
+    ) -> Result<Vec<PushAck>, SyncTransportError> {
+        self.pushed.lock().extend_from_slice(records);
+        Ok(records
+            .iter()
+            .map(|r| PushAck {
+                id: r.id.clone(),
+                sequence: 10,
+            })
+            .collect())
+    }
+
+    async fn pull(&self, _collection: &str, _since: i64) -> Result<PullResult, SyncTransportError> {
+        Ok(PullResult {
+            records: Vec::new(),
+            latest_sequence: None,
+            failures: Vec::new(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn trimmed_chain_stays_local_and_the_next_push_verifies() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("compact.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def();
+    let adapter = Arc::new(make_adapter(path, &def));
+
+    let key = generate_p256_keypair();
+    let jwk = export_public_key_jwk(key.verifying_key());
+    let did = encode_did_key(&key).unwrap();
+    // Append a signed entry, as the transport does on push
+    let append = |chain: &mut Vec<EditEntry>, t: u64| {
+        let entry =
+            sign_edit_entry(&key, &jwk, "notes", "n1", &did, t, Vec::new(), chain.last()).unwrap();
+        chain.push(entry);
+    };
+
+    let mut chain = Vec::new();
+    for t in 1..=3 {
+        append(&mut chain, t);
+    }
+    let opts = PutOptions {
+        id: Some("n1".to_string()),
+        session_id: Some(SID),
+        meta: Some(json!({ "_editChain": serialize_edit_chain(&chain) })),
+        ..Default::default()
+    };
+    adapter.put(&def, json!({ "title": "n1" }), &opts).unwrap();
+    adapter.mark_synced(&def, "n1", 1, None).unwrap();
+
+    adapter
+        .compact(
+            &def,
+            &CompactOptions {
+                trim_history_keep_last: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    // The trimmed chain still verifies locally, but only as a tail
+    let stored = get(&adapter, &def, "n1").unwrap().meta.unwrap();
+    let mut trimmed = parse_edit_chain(stored["_editChain"].as_str().unwrap()).unwrap();
+    assert!(verify_edit_chain_tail(&trimmed, "notes", "n1"));
+    assert!(!verify_edit_chain(&trimmed, "notes", "n1"));
+
+    // Extending it would push a chain no other device accepts
+    append(&mut trimmed, 4);
+    assert!(!verify_edit_chain(&trimmed, "notes", "n1"));
+
+    adapter
+        .patch(
+            &def,
+            json!({ "title": "edited" }),
+            &PatchOptions {
+                id: "n1".to_string(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let transport = Arc::new(RecordingTransport::default());
+    let manager = SyncManager::new(SyncManagerOptions {
+        transport: transport.clone(),
+        adapter: adapter.clone(),
+        collections: vec![def.clone()],
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    });
+    let result = manager.push(&def).await;
+    assert_eq!(result.pushed, 1);
+
+    // The push leaves the trimmed chain out, so the transport starts afresh
+    let pushed = transport.pushed.lock()[0].meta.clone().unwrap_or_default();
+    assert!(pushed.get("_editChain").is_none());
+    assert!(pushed.get("_editChainTrimmed").is_none());
+    let mut appended = match pushed.get("_editChain").and_then(Value::as_str) {
+        Some(raw) => parse_edit_chain(raw).unwrap(),
+        None => Vec::new(),
+    };
+    append(&mut appended, 4);
+    assert!(verify_edit_chain(&appended, "notes", "n1"));
+}
@@ -0,0 +1,215 @@
+//! Tests for concurrent offline edits from two devices meeting in
+//! `apply_remote_changes`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageRead, StorageSync, StorageWrite},
+    },
+    types::{
+        ApplyRemoteOptions, ApplyRemoteResult, ConflictWinner, GetOptions, PatchOptions,
+        PutOptions, RemoteRecord, StoredRecordWithMeta,
+    },
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID_A: u64 = MIN_SESSION_ID;
+const SID_B: u64 = MIN_SESSION_ID + 1;
+
+fn notes_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s.insert("color".to_string(), t::string());
+                s
+            })
+            .build(),
+    )
+}
+
+fn make_adapter(def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend
+        .initialize(&[def.as_ref()])
+        .expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn get(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, id: &str) -> StoredRecordWithMeta {
+    adapter
+        .get(def, id, &GetOptions::default())
+        .expect("get")
+        .expect("record exists")
+}
+
+fn edit(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, id: &str, sid: u64, changes: Value) {
+    adapter
+        .patch(
+            def,
+            changes,
+            &PatchOptions {
+                id: id.to_string(),
+                session_id: Some(sid),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+}
+
+/// Push `id` from `adapter` as the server's record at `sequence`.
+fn push(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    id: &str,
+    sequence: i64,
+) -> RemoteRecord {
+    let record = get(adapter, def, id);
+    adapter
+        .mark_synced(def, id, sequence, None)
+        .expect("mark_synced");
+    RemoteRecord {
+        id: record.id.clone(),
+        version: record.version,
+        crdt: Some(record.crdt.clone()),
+        deleted: false,
+        archived: false,
+        sequence,
+        meta: None,
+    }
+}
+
+fn pull(
+    adapter: &Adapter<SqliteBackend>,
+    def: &CollectionDef,
+    remote: RemoteRecord,
+) -> ApplyRemoteResult {
+    adapter
+        .apply_remote_changes(def, &[remote], &ApplyRemoteOptions::default())
+        .expect("apply_remote_changes")
+}
+
+/// Create a note on device A and sync it to device B at sequence 1.
+fn shared_note(
+    def: &CollectionDef,
+    device_a: &Adapter<SqliteBackend>,
+    device_b: &Adapter<SqliteBackend>,
+) -> String {
+    let created = device_a
+        .put(
+            def,
+            json!({"title": "Plan", "color": "red"}),
+            &PutOptions {
+                session_id: Some(SID_A),
+                ..Default::default()
+            },
+        )
+        .expect("put");
+    let initial = push(device_a, def, &created.id, 1);
+    pull(device_b, def, initial);
+    created.id
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn concurrent_edits_to_disjoint_fields_both_survive() {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+    let id = shared_note(&def, &device_a, &device_b);
+
+    edit(&device_a, &def, &id, SID_A, json!({"title": "Launch"}));
+    edit(&device_b, &def, &id, SID_B, json!({"color": "blue"}));
+
+    // A pushes first; B merges it into its own pending edit.
+    let from_a = push(&device_a, &def, &id, 2);
+    let result = pull(&device_b, &def, from_a);
+    assert_eq!(result.merged_count, 1);
+    assert!(result.conflicts.is_empty());
+
+    let merged = get(&device_b, &def, &id);
+    assert_eq!(merged.data["title"], "Launch");
+    assert_eq!(merged.data["color"], "blue");
+    assert!(merged.dirty, "B's color edit still needs pushing");
+
+    // B pushes the merge back; A converges.
+    let from_b = push(&device_b, &def, &id, 3);
+    pull(&device_a, &def, from_b);
+
+    let on_a = get(&device_a, &def, &id);
+    assert_eq!(on_a.data["title"], "Launch");
+    assert_eq!(on_a.data["color"], "blue");
+}
+
+#[test]
+fn same_field_conflict_picks_newer_write_on_both_devices() {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+    let id = shared_note(&def, &device_a, &device_b);
+
+    edit(&device_a, &def, &id, SID_A, json!({"title": "From A"}));
+    // B edits twice, so its final title write is the later one.
+    edit(&device_b, &def, &id, SID_B, json!({"title": "Draft"}));
+    edit(&device_b, &def, &id, SID_B, json!({"title": "From B"}));
+
+    let from_a = push(&device_a, &def, &id, 2);
+    let result = pull(&device_b, &def, from_a);
+
+    assert_eq!(result.conflicts.len(), 1);
+    let conflict = &result.conflicts[0];
+    assert_eq!(conflict.id, id);
+    assert_eq!(conflict.path, "title");
+    assert_eq!(conflict.local_value, json!("From B"));
+    assert_eq!(conflict.remote_value, json!("From A"));
+    assert_eq!(conflict.winner, ConflictWinner::Local);
+
+    let merged = get(&device_b, &def, &id);
+    assert_eq!(merged.data["title"], "From B");
+    assert!(merged.dirty, "the winning local write must be re-pushed");
+
+    let from_b = push(&device_b, &def, &id, 3);
+    pull(&device_a, &def, from_b);
+    assert_eq!(get(&device_a, &def, &id).data["title"], "From B");
+}
+
+#[test]
+fn losing_local_write_is_not_re_pushed() {
+    let def = notes_def();
+    let device_a = make_adapter(&def);
+    let device_b = make_adapter(&def);
+    let id = shared_note(&def, &device_a, &device_b);
+
+    edit(&device_b, &def, &id, SID_B, json!({"title": "From B"}));
+    edit(&device_a, &def, &id, SID_A, json!({"title": "Draft"}));
+    edit(&device_a, &def, &id, SID_A, json!({"title": "From A"}));
+
+    let from_a = push(&device_a, &def, &id, 2);
+    let result = pull(&device_b, &def, from_a);
+
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].winner, ConflictWinner::Remote);
+
+    let merged = get(&device_b, &def, &id);
+    assert_eq!(merged.data["title"], "From A");
+    assert!(!merged.dirty, "nothing of B's edit survived the merge");
+}
@@ -0,0 +1,577 @@
+//! Index-scan tests for `Adapter<SqliteBackend>` on a large collection.
+//!
+//! The backend is wrapped in a counter of rows it hands back to the adapter,
+//! so each indexed query can be checked against an unindexed copy of the
+//! same data both for results and for rows read.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    error::Result,
+    index::types::{IndexDefinition, IndexScan},
+    query::types::{Query, SortDirection, SortEntry, SortInput},
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{
+        DeleteKind, DeleteOptions, GetOptions, PurgeTombstonesOptions, PutOptions, RawBatchResult,
+        ScanOptions, SerializedRecord,
+    },
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Counting backend
+// ============================================================================
+
+/// `SqliteBackend` that counts the records its scans return.
+struct CountingBackend {
+    inner: SqliteBackend,
+    rows_read: Arc<AtomicUsize>,
+}
+
+impl CountingBackend {
+    fn tally(&self, batch: &RawBatchResult) {
+        self.rows_read
+            .fetch_add(batch.records.len(), Ordering::Relaxed);
+    }
+}
+
+impl StorageBackend for CountingBackend {
+    fn get_raw(&self, collection: &str, id: &str) -> Result<Option<SerializedRecord>> {
+        self.inner.get_raw(collection, id)
+    }
+
+    fn put_raw(&self, record: &SerializedRecord) -> Result<()> {
+        self.inner.put_raw(record)
+    }
+
+    fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
+        let batch = self.inner.scan_raw(collection, options)?;
+        self.tally(&batch);
+        Ok(batch)
+    }
+
+    fn scan_dirty_raw(&self, collection: &str) -> Result<RawBatchResult> {
+        self.inner.scan_dirty_raw(collection)
+    }
+
+    fn count_raw(&self, collection: &str) -> Result<usize> {
+        self.inner.count_raw(collection)
+    }
+
+    fn batch_put_raw(&self, records: &[SerializedRecord]) -> Result<()> {
+        self.inner.batch_put_raw(records)
+    }
+
+    fn purge_tombstones_raw(
+        &self,
+        collection: &str,
+        options: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        self.inner.purge_tombstones_raw(collection, options)
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_meta(key)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.inner.set_meta(key, value)
+    }
+
+    fn transaction<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        self.inner.transaction(|_| f(self))
+    }
+
+    fn scan_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<RawBatchResult>> {
+        let batch = self.inner.scan_index_raw(collection, scan)?;
+        if let Some(ref batch) = batch {
+            self.tally(batch);
+        }
+        Ok(batch)
+    }
+
+    fn count_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<usize>> {
+        self.inner.count_index_raw(collection, scan)
+    }
+
+    fn delete_where_raw(
+        &self,
+        collection: &str,
+        scan: &IndexScan,
+        residual: Option<&Value>,
+        opts: &DeleteOptions,
+    ) -> Result<Option<Vec<String>>> {
+        self.inner
+            .delete_where_raw(collection, scan, residual, opts)
+    }
+
+    fn check_unique(
+        &self,
+        collection: &str,
+        index: &IndexDefinition,
+        data: &Value,
+        computed: Option<&Value>,
+        exclude_id: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .check_unique(collection, index, data, computed, exclude_id)
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const RECORDS: usize = 10_000;
+
+fn events_def(name: &str, indexed: bool) -> Arc<CollectionDef> {
+    let builder = collection(name).v(1, {
+        let mut s = BTreeMap::new();
+        s.insert("group".to_string(), t::string());
+        s.insert("n".to_string(), t::number());
+        s.insert("key".to_string(), t::string());
+        s
+    });
+    let builder = if indexed {
+        builder
+            .index(&["group"])
+            .index(&["n"])
+            .index(&["group", "n"])
+            .index_with(&["key"], None, true, false)
+    } else {
+        builder
+    };
+    Arc::new(builder.build())
+}
+
+struct Fixture {
+    adapter: Adapter<CountingBackend>,
+    rows_read: Arc<AtomicUsize>,
+    indexed: Arc<CollectionDef>,
+    plain: Arc<CollectionDef>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let indexed = events_def("indexed", true);
+        let plain = events_def("plain", false);
+        let mut inner = SqliteBackend::open_in_memory().expect("open in-memory DB");
+        inner
+            .initialize(&[indexed.as_ref(), plain.as_ref()])
+            .expect("backend initialize");
+        let rows_read = Arc::new(AtomicUsize::new(0));
+        let mut adapter = Adapter::new(CountingBackend {
+            inner,
+            rows_read: rows_read.clone(),
+        });
+        adapter
+            .initialize(&[indexed.clone(), plain.clone()])
+            .expect("adapter initialize");
+
+        let records: Vec<Value> = (0..RECORDS)
+            .map(|i| {
+                json!({
+                    "id": format!("r{i:05}"),
+                    "group": format!("g{}", i % 50),
+                    "n": i,
+                    "key": format!("k{i:05}"),
+                })
+            })
+            .collect();
+        let opts = PutOptions {
+            session_id: Some(MIN_SESSION_ID),
+            ..Default::default()
+        };
+        for def in [&indexed, &plain] {
+            let result = adapter
+                .bulk_put(def, records.clone(), &opts)
+                .expect("bulk_put");
+            assert!(result.errors.is_empty());
+        }
+
+        Self {
+            adapter,
+            rows_read,
+            indexed,
+            plain,
+        }
+    }
+
+    /// Ids returned by `query` and the number of rows the backend read.
+    fn run(&self, def: &CollectionDef, query: &Query) -> (Vec<String>, usize) {
+        self.rows_read.store(0, Ordering::Relaxed);
+        let result = self.adapter.query(def, query).expect("query");
+        let ids = result.records.into_iter().map(|r| r.id).collect();
+        (ids, self.rows_read.load(Ordering::Relaxed))
+    }
+
+    fn count(&self, def: &CollectionDef, query: &Query) -> (usize, usize) {
+        self.rows_read.store(0, Ordering::Relaxed);
+        let count = self.adapter.count(def, Some(query)).expect("count");
+        (count, self.rows_read.load(Ordering::Relaxed))
+    }
+
+    /// Assert the indexed and full-scan paths agree on `query`, and that the
+    /// indexed path read at most `max_rows` rows.
+    fn assert_same(&self, query: &Query, max_rows: usize) -> Vec<String> {
+        let plan = self.adapter.explain_query(&self.indexed, query);
+        assert!(plan.scan.is_some(), "expected an index plan for {query:?}");
+
+        let (indexed_ids, indexed_rows) = self.run(&self.indexed, query);
+        let (plain_ids, plain_rows) = self.run(&self.plain, query);
+        assert_eq!(indexed_ids, plain_ids);
+        assert_eq!(plain_rows, RECORDS);
+        assert!(
+            indexed_rows <= max_rows,
+            "index scan read {indexed_rows} rows for {query:?}"
+        );
+
+        let (indexed_count, _) = self.count(&self.indexed, query);
+        let (plain_count, _) = self.count(&self.plain, query);
+        assert_eq!(indexed_count, plain_count);
+        assert_eq!(indexed_count, indexed_ids.len());
+        indexed_ids
+    }
+}
+
+fn sorted_by(field: &str, direction: SortDirection) -> Option<SortInput> {
+    Some(SortInput::Entries(vec![SortEntry {
+        field: field.to_string(),
+        direction,
+        nulls: None,
+    }]))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn index_scans_match_full_scans_and_read_fewer_rows() {
+    let fx = Fixture::new();
+
+    // Exact
+    let ids = fx.assert_same(
+        &Query {
+            filter: Some(json!({ "group": "g7" })),
+            sort: sorted_by("n", SortDirection::Asc),
+            ..Default::default()
+        },
+        RECORDS / 50,
+    );
+    assert_eq!(ids.len(), RECORDS / 50);
+    assert_eq!(ids[0], "r00007");
+
+    // Range, scanned in reverse for a descending sort
+    let ids = fx.assert_same(
+        &Query {
+            filter: Some(json!({ "n": { "$gte": 9_900 } })),
+            sort: sorted_by("n", SortDirection::Desc),
+            ..Default::default()
+        },
+        100,
+    );
+    assert_eq!(ids.first().map(String::as_str), Some("r09999"));
+    assert_eq!(ids.last().map(String::as_str), Some("r09900"));
+
+    // Multi-point $in
+    let ids = fx.assert_same(
+        &Query {
+            filter: Some(json!({ "group": { "$in": ["g1", "g2"] } })),
+            sort: sorted_by("n", SortDirection::Asc),
+            ..Default::default()
+        },
+        2 * RECORDS / 50,
+    );
+    assert_eq!(ids.len(), 2 * RECORDS / 50);
+
+    // $in then a range on the next compound field: one range per value
+    let query = Query {
+        filter: Some(json!({ "group": { "$in": ["g1", "g2"] }, "n": { "$gte": 9_000 } })),
+        sort: sorted_by("n", SortDirection::Asc),
+        ..Default::default()
+    };
+    assert_eq!(
+        fx.adapter
+            .explain_query(&fx.indexed, &query)
+            .union_scans
+            .len(),
+        1
+    );
+    let ids = fx.assert_same(&query, 2 * 1_000 / 50);
+    assert_eq!(ids.len(), 2 * 1_000 / 50);
+    assert_eq!(ids[0], "r09001");
+
+    // $or over unique keys: one exact scan per branch
+    let query = Query {
+        filter: Some(json!({ "$or": [{ "key": "k00042" }, { "key": "k09000" }] })),
+        sort: sorted_by("n", SortDirection::Asc),
+        ..Default::default()
+    };
+    let ids = fx.assert_same(&query, 2);
+    assert_eq!(ids, vec!["r00042", "r09000"]);
+
+    // $or across two different indexes, overlapping in one record
+    let query = Query {
+        filter: Some(json!({ "$or": [{ "key": "k00042" }, { "group": "g42" }] })),
+        sort: sorted_by("n", SortDirection::Asc),
+        ..Default::default()
+    };
+    let plan = fx.adapter.explain_query(&fx.indexed, &query);
+    assert_eq!(plan.union_scans.len(), 1);
+    let ids = fx.assert_same(&query, 1 + RECORDS / 50);
+    assert_eq!(ids.len(), RECORDS / 50);
+    assert_eq!(ids[0], "r00042");
+
+    // Index scan plus a residual filter
+    fx.assert_same(
+        &Query {
+            filter: Some(json!({ "n": { "$lt": 500 }, "group": { "$ne": "g3" } })),
+            sort: sorted_by("n", SortDirection::Asc),
+            ..Default::default()
+        },
+        500,
+    );
+}
+
+#[test]
+fn indexed_count_reads_no_rows() {
+    let fx = Fixture::new();
+
+    let query = Query {
+        filter: Some(json!({ "n": { "$gte": 1_000, "$lt": 3_000 } })),
+        ..Default::default()
+    };
+    assert_eq!(fx.count(&fx.indexed, &query), (2_000, 0));
+    assert_eq!(fx.count(&fx.plain, &query), (2_000, RECORDS));
+
+    // A residual filter is applied to the index's candidates only
+    let query = Query {
+        filter: Some(json!({ "group": "g5", "n": { "$gte": 5_000 } })),
+        ..Default::default()
+    };
+    let (count, rows) = fx.count(&fx.indexed, &query);
+    assert_eq!(count, 100);
+    assert!(rows <= RECORDS / 2, "count read {rows} rows");
+}
+
+#[test]
+fn delete_many_by_index_matches_id_list_delete() {
+    let fx = Fixture::new();
+    let opts = DeleteOptions::default();
+
+    for filter in [
+        json!({ "n": { "$gte": 9_000 } }),
+        json!({ "group": "g3", "n": { "$lt": 5_000 } }),
+    ] {
+        let query = Query {
+            filter: Some(filter.clone()),
+            ..Default::default()
+        };
+        let (ids, _) = fx.run(&fx.plain, &query);
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let mut by_id = fx
+            .adapter
+            .bulk_delete(&fx.plain, &id_refs, &opts)
+            .expect("bulk_delete")
+            .deleted_ids;
+
+        fx.rows_read.store(0, Ordering::Relaxed);
+        let mut by_scan = fx
+            .adapter
+            .delete_many(&fx.indexed, &filter, &opts)
+            .expect("delete_many")
+            .deleted_ids;
+        // The backend deleted the match set without handing rows back
+        assert_eq!(fx.rows_read.load(Ordering::Relaxed), 0);
+
+        by_id.sort();
+        by_scan.sort();
+        assert!(!by_scan.is_empty());
+        assert_eq!(by_scan, by_id);
+
+        let (mut indexed_left, _) = fx.run(&fx.indexed, &Query::default());
+        let (mut plain_left, _) = fx.run(&fx.plain, &Query::default());
+        indexed_left.sort();
+        plain_left.sort();
+        assert_eq!(indexed_left, plain_left);
+    }
+
+    let tombstone = fx
+        .adapter
+        .get(
+            &fx.indexed,
+            "r09000",
+            &GetOptions {
+                include_deleted: true,
+                ..Default::default()
+            },
+        )
+        .expect("get")
+        .expect("tombstone kept");
+    assert!(tombstone.deleted && tombstone.dirty && !tombstone.archived);
+    assert!(tombstone.deleted_at.is_some());
+}
+
+#[test]
+fn delete_many_by_index_archives() {
+    let fx = Fixture::new();
+    let opts = DeleteOptions {
+        kind: DeleteKind::Archive,
+        ..Default::default()
+    };
+    let filter = json!({ "group": "g9" });
+
+    let result = fx
+        .adapter
+        .delete_many(&fx.indexed, &filter, &opts)
+        .expect("delete_many");
+    assert_eq!(result.deleted_ids.len(), RECORDS / 50);
+
+    let record = fx
+        .adapter
+        .get(
+            &fx.indexed,
+            "r00009",
+            &GetOptions {
+                include_archived: true,
+                ..Default::default()
+            },
+        )
+        .expect("get")
+        .expect("archived record kept");
+    assert!(record.archived && record.dirty && !record.deleted);
+
+    // Already-archived records are not matched again
+    let again = fx
+        .adapter
+        .delete_many(&fx.indexed, &filter, &DeleteOptions::default())
+        .expect("delete_many");
+    assert!(again.deleted_ids.is_empty());
+}
+
+// ============================================================================
+// Query cache
+// ============================================================================
+
+#[test]
+fn query_cache_serves_repeated_queries_without_reading() {
+    let fx = Fixture::new();
+    let query = Query {
+        filter: Some(json!({ "group": "g3", "n": { "$lt": 5000 } })),
+        sort: sorted_by("n", SortDirection::Desc),
+        limit: Some(10),
+        ..Default::default()
+    };
+
+    // Disabled by default
+    assert_eq!(fx.adapter.query_cache_capacity(), 0);
+    let (first, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+    let (_, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+
+    fx.adapter.set_query_cache_capacity(8);
+    let (cold, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+    let (warm, rows) = fx.run(&fx.indexed, &query);
+    assert_eq!(rows, 0);
+    assert_eq!(warm, cold);
+    assert_eq!(warm, first);
+
+    // Filter key order does not matter
+    let reordered = Query {
+        filter: Some(json!({ "n": { "$lt": 5000 }, "group": "g3" })),
+        ..query.clone()
+    };
+    let (ids, rows) = fx.run(&fx.indexed, &reordered);
+    assert_eq!(rows, 0);
+    assert_eq!(ids, cold);
+
+    // A different page is a different entry
+    let next_page = Query {
+        offset: Some(10),
+        ..query.clone()
+    };
+    let (_, rows) = fx.run(&fx.indexed, &next_page);
+    assert!(rows > 0);
+
+    fx.adapter.clear_query_cache();
+    let (_, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+}
+
+#[test]
+fn query_cache_is_invalidated_by_writes_to_the_collection() {
+    let fx = Fixture::new();
+    fx.adapter.set_query_cache_capacity(8);
+    let query = Query {
+        filter: Some(json!({ "group": "g3" })),
+        ..Default::default()
+    };
+    let (before, _) = fx.run(&fx.indexed, &query);
+    let (_, rows) = fx.run(&fx.indexed, &query);
+    assert_eq!(rows, 0);
+
+    // Writes to another collection leave the entry alone
+    fx.adapter
+        .put(
+            &fx.plain,
+            json!({ "id": "extra", "group": "g3", "n": -1, "key": "kextra" }),
+            &PutOptions::default(),
+        )
+        .expect("put");
+    let (_, rows) = fx.run(&fx.indexed, &query);
+    assert_eq!(rows, 0);
+
+    fx.adapter
+        .put(
+            &fx.indexed,
+            json!({ "id": "extra", "group": "g3", "n": -1, "key": "kextra" }),
+            &PutOptions::default(),
+        )
+        .expect("put");
+    let (after, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+    assert_eq!(after.len(), before.len() + 1);
+    assert!(after.contains(&"extra".to_string()));
+
+    fx.adapter
+        .delete(&fx.indexed, "extra", &DeleteOptions::default())
+        .expect("delete");
+    let (after_delete, rows) = fx.run(&fx.indexed, &query);
+    assert!(rows > 0);
+    assert_eq!(after_delete, before);
+}
+
+#[test]
+fn query_cache_evicts_least_recently_used() {
+    let fx = Fixture::new();
+    fx.adapter.set_query_cache_capacity(2);
+    let by_group = |g: &str| Query {
+        filter: Some(json!({ "group": g })),
+        ..Default::default()
+    };
+
+    fx.run(&fx.indexed, &by_group("g1"));
+    fx.run(&fx.indexed, &by_group("g2"));
+    // Touch g1 so g2 is the least recently used
+    assert_eq!(fx.run(&fx.indexed, &by_group("g1")).1, 0);
+    fx.run(&fx.indexed, &by_group("g3"));
+
+    assert_eq!(fx.run(&fx.indexed, &by_group("g1")).1, 0);
+    assert_eq!(fx.run(&fx.indexed, &by_group("g3")).1, 0);
+    assert!(fx.run(&fx.indexed, &by_group("g2")).1 > 0);
+}
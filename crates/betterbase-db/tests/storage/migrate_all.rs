@@ -0,0 +1,287 @@
+//! Tests for `Adapter::migrate_all`: eager, batched migration that reports
+//! failing records instead of aborting, and `Adapter::migrate_collection`,
+//! which shares its loop but fails on the first bad record.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::{self, MIN_SESSION_ID},
+    error::LessDbError,
+    index::types::IndexableValue,
+    query::types::Query,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{GetOptions, PutOptions, SerializedRecord},
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+/// v2 adds `body`; the migration refuses titles starting with "bad". The
+/// `lower_body` computed index reads the field the migration fills in.
+fn docs_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("docs")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s
+            })
+            .v(
+                2,
+                {
+                    let mut s = BTreeMap::new();
+                    s.insert("title".to_string(), t::string());
+                    s.insert("body".to_string(), t::string());
+                    s
+                },
+                |mut data| {
+                    let title = data["title"].as_str().unwrap_or_default().to_string();
+                    if title.starts_with("bad") {
+                        return Err(format!("cannot migrate {title}").into());
+                    }
+                    if let Value::Object(ref mut m) = data {
+                        m.insert("body".to_string(), json!(format!("about {title}")));
+                    }
+                    Ok(data)
+                },
+            )
+            .computed("lower_body", |doc| {
+                doc.get("body")
+                    .and_then(|v| v.as_str())
+                    .map(|s| IndexableValue::String(s.to_lowercase()))
+            })
+            .build(),
+    )
+}
+
+fn open_backend(path: &str, def: &CollectionDef) -> SqliteBackend {
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    backend.initialize(&[def]).expect("backend initialize");
+    backend
+}
+
+fn make_adapter(path: &str, def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut adapter = Adapter::new(open_backend(path, def));
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn v1_doc(id: &str, title: &str) -> SerializedRecord {
+    let data = json!({
+        "id": id,
+        "title": title,
+        "createdAt": "2024-01-01T00:00:00Z",
+        "updatedAt": "2024-01-01T00:00:00Z",
+    });
+    let model = crdt::create_model(&data, SID).expect("create model");
+    SerializedRecord {
+        id: id.to_string(),
+        collection: "docs".to_string(),
+        version: 1,
+        data,
+        crdt: crdt::model_to_binary(&model),
+        pending_patches: vec![],
+        sequence: 0,
+        dirty: false,
+        deleted: false,
+        deleted_at: None,
+        archived: false,
+        meta: None,
+        computed: None,
+    }
+}
+
+fn stored_version(backend: &SqliteBackend, id: &str) -> u32 {
+    backend.get_raw("docs", id).unwrap().unwrap().version
+}
+
+fn lower_body_query(value: &str) -> Query {
+    Query {
+        filter: Some(json!({ "$computed": { "lower_body": value } })),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// migrate_all
+// ============================================================================
+
+#[test]
+fn migrate_all_rewrites_stale_records_across_batches() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("migrate.db");
+    let path = path.to_str().unwrap();
+    let def = docs_def();
+
+    let raw = open_backend(path, &def);
+    let docs: Vec<SerializedRecord> = (0..1_200)
+        .map(|i| v1_doc(&format!("doc-{i:04}"), &format!("Doc {i}")))
+        .collect();
+    raw.batch_put_raw(&docs).unwrap();
+
+    let adapter = make_adapter(path, &def);
+    let current = adapter
+        .put(
+            &def,
+            json!({ "title": "Current", "body": "already v2" }),
+            &PutOptions {
+                id: Some("current".to_string()),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let report = adapter.migrate_all(&def).expect("migrate_all");
+    assert_eq!(report.migrated.len(), 1_200);
+    assert!(report.failed.is_empty());
+    assert!(!report.migrated.contains(&current.id));
+
+    assert_eq!(stored_version(&raw, "doc-0000"), 2);
+    assert_eq!(stored_version(&raw, "doc-1199"), 2);
+    let stored = raw.get_raw("docs", "doc-0042").unwrap().unwrap();
+    assert_eq!(stored.data["body"], json!("about Doc 42"));
+
+    // Nothing left to do
+    let again = adapter.migrate_all(&def).expect("migrate_all");
+    assert!(again.migrated.is_empty());
+    assert!(again.failed.is_empty());
+}
+
+#[test]
+fn migrate_all_reports_failures_and_keeps_going() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("migrate.db");
+    let path = path.to_str().unwrap();
+    let def = docs_def();
+
+    let raw = open_backend(path, &def);
+    raw.batch_put_raw(&[
+        v1_doc("a", "Alpha"),
+        v1_doc("b", "bad one"),
+        v1_doc("c", "Gamma"),
+    ])
+    .unwrap();
+
+    let adapter = make_adapter(path, &def);
+    let report = adapter.migrate_all(&def).expect("migrate_all");
+
+    let mut migrated = report.migrated.clone();
+    migrated.sort();
+    assert_eq!(migrated, vec!["a", "c"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].id, "b");
+    assert_eq!(report.failed[0].collection, "docs");
+    assert!(report.failed[0].error.contains("cannot migrate bad one"));
+
+    // The failed record is left as stored, not dropped
+    assert_eq!(stored_version(&raw, "a"), 2);
+    assert_eq!(stored_version(&raw, "b"), 1);
+    assert_eq!(
+        raw.get_raw("docs", "b").unwrap().unwrap().data["title"],
+        "bad one"
+    );
+
+    // A later run retries it
+    let again = adapter.migrate_all(&def).expect("migrate_all");
+    assert!(again.migrated.is_empty());
+    assert_eq!(again.failed.len(), 1);
+}
+
+#[test]
+fn reads_migrate_lazily_and_persist() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("migrate.db");
+    let path = path.to_str().unwrap();
+    let def = docs_def();
+
+    let raw = open_backend(path, &def);
+    raw.put_raw(&v1_doc("a", "Alpha")).unwrap();
+
+    let adapter = make_adapter(path, &def);
+    let record = adapter
+        .get(&def, "a", &GetOptions::default())
+        .unwrap()
+        .unwrap();
+    assert!(record.was_migrated);
+    assert_eq!(record.data["body"], json!("about Alpha"));
+    assert_eq!(stored_version(&raw, "a"), 2);
+
+    let report = adapter.migrate_all(&def).expect("migrate_all");
+    assert!(report.migrated.is_empty());
+}
+
+#[test]
+fn migration_recomputes_index_values_from_migrated_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("migrate.db");
+    let path = path.to_str().unwrap();
+    let def = docs_def();
+
+    let raw = open_backend(path, &def);
+    let mut stale = v1_doc("a", "Alpha");
+    stale.computed = Some(json!({ "lower_body": "stale" }));
+    raw.batch_put_raw(&[stale, v1_doc("b", "Beta")]).unwrap();
+
+    let adapter = make_adapter(path, &def);
+    assert_eq!(adapter.migrate_all(&def).unwrap().migrated.len(), 2);
+
+    let result = adapter
+        .query(&def, &lower_body_query("about alpha"))
+        .unwrap();
+    assert_eq!(result.records.len(), 1);
+    assert_eq!(result.records[0].id, "a");
+    assert!(adapter
+        .query(&def, &lower_body_query("stale"))
+        .unwrap()
+        .records
+        .is_empty());
+    assert_eq!(
+        adapter
+            .query(&def, &lower_body_query("about beta"))
+            .unwrap()
+            .records
+            .len(),
+        1
+    );
+}
+
+// ============================================================================
+// migrate_collection
+// ============================================================================
+
+#[test]
+fn migrate_collection_fails_on_bad_record_after_writing_the_rest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("migrate.db");
+    let path = path.to_str().unwrap();
+    let def = docs_def();
+
+    let raw = open_backend(path, &def);
+    raw.batch_put_raw(&[v1_doc("a", "Alpha"), v1_doc("b", "bad one")])
+        .unwrap();
+
+    let adapter = make_adapter(path, &def);
+    let err = adapter.migrate_collection(&def).unwrap_err();
+    assert!(matches!(err, LessDbError::Migration(_)), "{err}");
+
+    assert_eq!(stored_version(&raw, "a"), 2);
+    assert_eq!(stored_version(&raw, "b"), 1);
+    let result = adapter
+        .query(&def, &lower_body_query("about alpha"))
+        .unwrap();
+    assert_eq!(result.records.len(), 1);
+}
@@ -0,0 +1,109 @@
+//! Tests for `SqliteBackend::query_raw`.
+
+use betterbase_db::error::{LessDbError, QueryError};
+use betterbase_db::storage::sqlite::SqliteBackend;
+use betterbase_db::storage::traits::StorageBackend;
+use betterbase_db::types::SerializedRecord;
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn make_backend() -> SqliteBackend {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend.initialize(&[]).expect("initialize");
+    backend
+}
+
+fn put_order(backend: &SqliteBackend, id: &str, customer: &str, total: f64) {
+    backend
+        .put_raw(&SerializedRecord {
+            id: id.to_string(),
+            collection: "orders".to_string(),
+            version: 1,
+            data: json!({ "customer": customer, "total": total }),
+            crdt: vec![],
+            pending_patches: vec![],
+            sequence: -1,
+            dirty: false,
+            deleted: false,
+            deleted_at: None,
+            archived: false,
+            meta: None,
+            computed: None,
+        })
+        .expect("put_raw");
+}
+
+fn assert_rejected(result: betterbase_db::error::Result<Vec<serde_json::Value>>) {
+    match result {
+        Err(LessDbError::Query(QueryError::RawSqlRejected(_))) => {}
+        other => panic!("expected RawSqlRejected, got {other:?}"),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[test]
+fn aggregate_select_returns_rows_as_objects() {
+    let backend = make_backend();
+    put_order(&backend, "o1", "ann", 10.0);
+    put_order(&backend, "o2", "ann", 5.5);
+    put_order(&backend, "o3", "bob", 7.0);
+
+    let rows = backend
+        .query_raw(
+            "SELECT json_extract(data, '$.customer') AS customer,
+                    COUNT(*) AS orders,
+                    SUM(json_extract(data, '$.total')) AS spent
+             FROM records
+             WHERE collection = ?1 AND deleted = 0
+             GROUP BY customer
+             ORDER BY customer",
+            &[json!("orders")],
+        )
+        .expect("query_raw");
+
+    assert_eq!(
+        rows,
+        vec![
+            json!({ "customer": "ann", "orders": 2, "spent": 15.5 }),
+            json!({ "customer": "bob", "orders": 1, "spent": 7.0 }),
+        ]
+    );
+}
+
+#[test]
+fn with_clause_and_leading_comment_are_accepted() {
+    let backend = make_backend();
+    put_order(&backend, "o1", "ann", 10.0);
+
+    let rows = backend
+        .query_raw(
+            "-- ids only\nWITH o AS (SELECT id FROM records) SELECT id FROM o",
+            &[],
+        )
+        .expect("query_raw");
+    assert_eq!(rows, vec![json!({ "id": "o1" })]);
+}
+
+#[test]
+fn non_read_statements_are_rejected() {
+    let backend = make_backend();
+    put_order(&backend, "o1", "ann", 10.0);
+
+    assert_rejected(backend.query_raw("DELETE FROM records", &[]));
+    assert_rejected(backend.query_raw("/* sneaky */ UPDATE records SET dirty = 1", &[]));
+    assert_rejected(backend.query_raw(
+        "WITH doomed AS (SELECT id FROM records) DELETE FROM records WHERE id IN doomed",
+        &[],
+    ));
+
+    let rows = backend
+        .query_raw("SELECT COUNT(*) AS n FROM records", &[])
+        .expect("query_raw");
+    assert_eq!(rows, vec![json!({ "n": 1 })]);
+}
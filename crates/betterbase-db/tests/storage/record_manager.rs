@@ -11,11 +11,12 @@ use betterbase_db::{
     storage::record_manager::{
         compute_index_values, merge_records, migrate_and_deserialize, normalize_index_value,
         prepare_delete, prepare_mark_synced, prepare_new, prepare_patch, prepare_remote_insert,
-        prepare_remote_tombstone, prepare_update, resolve_delete_conflict, try_extract_id,
+        prepare_remote_tombstone, prepare_touch, prepare_update, resolve_delete_conflict,
+        try_extract_id,
     },
     types::{
         DeleteConflictStrategy, DeleteOptions, DeleteResolution, PatchOptions, PushSnapshot,
-        PutOptions, RemoteRecord, SerializedRecord,
+        PutOptions, RemoteRecord, SerializedRecord, TouchOptions,
     },
 };
 use serde_json::{json, Value};
@@ -326,6 +327,86 @@ fn prepare_patch_skips_auto_fields() {
     assert_eq!(result.record.data["name"], json!("Bob"));
 }
 
+// ============================================================================
+// prepare_touch
+// ============================================================================
+
+#[test]
+fn prepare_touch_bumps_updated_at_and_marks_dirty() {
+    let def = users_def();
+    let original = make_record(&def, "user-1", json!({"name": "Alice", "email": "a@b.com"}));
+    let clean = SerializedRecord {
+        dirty: false,
+        data: {
+            let mut data = original.data.clone();
+            data["updatedAt"] = json!("2024-01-01T00:00:00.000000Z");
+            data
+        },
+        ..original.clone()
+    };
+
+    let touched =
+        prepare_touch(&def, &clean, SID, &TouchOptions::default()).expect("prepare_touch");
+
+    assert!(touched.dirty, "touched record should be dirty");
+    assert_eq!(touched.version, 1);
+    assert_eq!(touched.data["name"], json!("Alice"));
+    assert_eq!(touched.data["email"], json!("a@b.com"));
+    assert_eq!(touched.data["createdAt"], clean.data["createdAt"]);
+    assert_ne!(touched.data["updatedAt"], clean.data["updatedAt"]);
+}
+
+#[test]
+fn prepare_touch_restamps_stale_record_at_current_version() {
+    let def = versioned_def();
+    let v1_data = json!({
+        "id": "doc-1",
+        "title": "Hello",
+        "createdAt": "2024-01-01T00:00:00Z",
+        "updatedAt": "2024-01-01T00:00:00Z",
+    });
+    let model = crdt::create_model(&v1_data, SID).expect("create model");
+    let rec = SerializedRecord {
+        id: "doc-1".to_string(),
+        collection: "docs".to_string(),
+        version: 1,
+        data: v1_data,
+        crdt: crdt::model_to_binary(&model),
+        pending_patches: vec![],
+        sequence: 3,
+        dirty: false,
+        deleted: false,
+        deleted_at: None,
+        archived: false,
+        meta: None,
+        computed: None,
+    };
+
+    let touched = prepare_touch(&def, &rec, SID, &TouchOptions::default()).expect("prepare_touch");
+
+    assert_eq!(touched.version, 2, "touch should bump a stale version");
+    assert!(touched.dirty);
+    assert_eq!(touched.sequence, 3);
+    assert_eq!(touched.data["title"], json!("Hello"));
+    assert_eq!(touched.data["body"], json!(""));
+}
+
+#[test]
+fn prepare_touch_merges_meta() {
+    let def = users_def();
+    let original = make_record(&def, "user-1", json!({"name": "Alice", "email": "a@b.com"}));
+    let opts = TouchOptions {
+        meta: Some(json!({"reason": "rotation"})),
+        ..Default::default()
+    };
+
+    let touched = prepare_touch(&def, &original, SID, &opts).expect("prepare_touch");
+    assert_eq!(
+        touched.meta,
+        Some(json!({"reason": "rotation", "_revision": 1}))
+    );
+}
+
 // ============================================================================
 // prepare_delete
 // ============================================================================
@@ -377,6 +458,7 @@ fn prepare_mark_synced_clears_dirty_when_snapshot_matches() {
     let snapshot = PushSnapshot {
         pending_patches_length: rec.pending_patches.len(),
         deleted: rec.deleted,
+        archived: false,
     };
 
     let synced = prepare_mark_synced(&rec, 42, Some(&snapshot));
@@ -397,6 +479,7 @@ fn prepare_mark_synced_stays_dirty_when_patches_grew() {
     let snapshot = PushSnapshot {
         pending_patches_length: 0,
         deleted: false,
+        archived: false,
     };
 
     // Add an update (which grows pending_patches)
@@ -470,6 +553,7 @@ fn migrate_and_deserialize_applies_migration_from_v1() {
         dirty: false,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         computed: None,
     };
@@ -498,6 +582,7 @@ fn migrate_and_deserialize_tombstone_skips_migration() {
         dirty: false,
         deleted: true,
         deleted_at: Some("2024-01-01T00:00:00Z".to_string()),
+        archived: false,
         meta: None,
         computed: None,
     };
@@ -607,6 +692,7 @@ fn prepare_remote_insert_creates_record_from_remote() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 100,
         meta: None,
     };
@@ -627,6 +713,7 @@ fn prepare_remote_insert_returns_error_when_crdt_missing() {
         version: 1,
         crdt: None, // missing!
         deleted: false,
+        archived: false,
         sequence: 1,
         meta: None,
     };
@@ -755,6 +842,7 @@ fn merge_records_cross_version_migrates_remote_and_preserves_local_edits() {
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         computed: None,
     };
@@ -813,6 +901,7 @@ fn merge_records_cross_version_with_local_title_change() {
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         computed: None,
     };
@@ -850,6 +939,7 @@ fn prepare_remote_insert_migrates_older_version() {
         version: 1, // older than def.current_version (2)
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 50,
         meta: None,
     };
@@ -889,6 +979,7 @@ fn prepare_remote_insert_rejects_future_version() {
         version: 99, // far in the future
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 1,
         meta: None,
     };
@@ -921,6 +1012,7 @@ fn prepare_remote_insert_preserves_meta() {
         version: 1,
         crdt: Some(crdt_bytes),
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: Some(json!({"spaceId": "workspace-1"})),
     };
@@ -946,6 +1038,7 @@ fn resolve_delete_conflict_remote_wins_remote_deleted() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -962,6 +1055,7 @@ fn resolve_delete_conflict_remote_wins_both_alive() {
         version: 1,
         crdt: None,
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -979,6 +1073,7 @@ fn resolve_delete_conflict_local_wins_local_deleted() {
         version: 1,
         crdt: None,
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -995,6 +1090,7 @@ fn resolve_delete_conflict_local_wins_local_alive() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -1011,6 +1107,7 @@ fn resolve_delete_conflict_delete_wins() {
         version: 1,
         crdt: None,
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -1028,6 +1125,7 @@ fn resolve_delete_conflict_update_wins() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -1054,6 +1152,7 @@ fn resolve_delete_conflict_custom_strategy() {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: 10,
         meta: None,
     };
@@ -1067,6 +1166,7 @@ fn resolve_delete_conflict_custom_strategy() {
         version: 1,
         crdt: None,
         deleted: false,
+        archived: false,
         sequence: 10,
         meta: None,
     };
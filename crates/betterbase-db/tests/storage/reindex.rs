@@ -0,0 +1,129 @@
+//! Tests for `Adapter::reindex`: rebuilding indexes and computed values for
+//! records written before an index existed.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    index::types::IndexableValue,
+    query::types::Query,
+    schema::node::{t, SchemaNode},
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{DeleteOptions, PutOptions},
+};
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn schema() -> BTreeMap<String, SchemaNode> {
+    let mut s = BTreeMap::new();
+    s.insert("title".to_string(), t::string());
+    s
+}
+
+fn plain_def() -> Arc<CollectionDef> {
+    Arc::new(collection("notes").v(1, schema()).build())
+}
+
+/// Same collection, with a computed index added after records exist.
+fn computed_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("notes")
+            .v(1, schema())
+            .computed("lower_title", |doc| {
+                doc.get("title")
+                    .and_then(|v| v.as_str())
+                    .map(|s| IndexableValue::String(s.to_lowercase()))
+            })
+            .build(),
+    )
+}
+
+fn make_adapter(path: &str, def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    backend.initialize(&[def]).expect("backend initialize");
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn lower_title_query(value: &str) -> Query {
+    Query {
+        filter: Some(json!({ "$computed": { "lower_title": value } })),
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// reindex
+// ============================================================================
+
+#[test]
+fn reindex_makes_new_computed_index_queryable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reindex.db");
+    let path = path.to_str().unwrap();
+
+    {
+        let adapter = make_adapter(path, &plain_def());
+        for title in ["Alpha", "Beta"] {
+            adapter
+                .put(
+                    &plain_def(),
+                    json!({ "title": title }),
+                    &PutOptions::default(),
+                )
+                .unwrap();
+        }
+    }
+
+    let def = computed_def();
+    let adapter = make_adapter(path, &def);
+    let query = lower_title_query("alpha");
+    assert_eq!(
+        adapter.query(&def, &query).unwrap().records.len(),
+        0,
+        "records written before the index have no computed values yet"
+    );
+
+    assert_eq!(adapter.reindex(&def).unwrap(), 2);
+
+    let result = adapter.query(&def, &query).unwrap();
+    assert_eq!(result.records.len(), 1);
+    assert_eq!(result.records[0].data["title"], "Alpha");
+}
+
+#[test]
+fn reindex_skips_tombstones_and_is_idempotent() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reindex.db");
+    let path = path.to_str().unwrap();
+    let def = computed_def();
+    let adapter = make_adapter(path, &def);
+
+    let kept = adapter
+        .put(&def, json!({ "title": "Kept" }), &PutOptions::default())
+        .unwrap();
+    let gone = adapter
+        .put(&def, json!({ "title": "Gone" }), &PutOptions::default())
+        .unwrap();
+    adapter
+        .delete(&def, &gone.id, &DeleteOptions::default())
+        .unwrap();
+
+    assert_eq!(adapter.reindex(&def).unwrap(), 1);
+    assert_eq!(adapter.reindex(&def).unwrap(), 1);
+
+    let result = adapter.query(&def, &lower_title_query("kept")).unwrap();
+    assert_eq!(result.records.len(), 1);
+    assert_eq!(result.records[0].id, kept.id);
+}
@@ -2,6 +2,7 @@
 
 use std::collections::BTreeMap;
 
+use betterbase_crypto::UCANPermission;
 use betterbase_db::{
     collection::builder::{collection, CollectionDef},
     crdt::{self, MIN_SESSION_ID},
@@ -10,8 +11,9 @@ use betterbase_db::{
         record_manager::prepare_new,
         remote_changes::{apply_remote_decisions, process_remote_record, RemoteDecision},
     },
-    types::{DeleteConflictStrategy, PutOptions, RemoteRecord, SerializedRecord},
+    types::{DeleteConflictStrategy, DeleteKind, PutOptions, RemoteRecord, SerializedRecord},
 };
+use betterbase_sync_core::SpaceDeletePolicy;
 use serde_json::json;
 
 const SID: u64 = MIN_SESSION_ID;
@@ -43,6 +45,7 @@ fn make_remote_record(id: &str, seq: i64, deleted: bool) -> RemoteRecord {
         version: 1,
         crdt: if deleted { None } else { Some(crdt_bytes) },
         deleted,
+        archived: false,
         sequence: seq,
         meta: None,
     }
@@ -71,7 +74,8 @@ fn case1_no_local_remote_tombstone_inserts_tombstone() {
     let strategy = DeleteConflictStrategy::RemoteWins;
 
     let (decision, _action) =
-        process_remote_record(&def, None, &remote, &strategy, None).expect("should succeed");
+        process_remote_record(&def, None, &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Insert(rec) => {
@@ -89,8 +93,8 @@ fn case2_no_local_remote_live_inserts() {
     let remote = make_remote_record("y", 10, false);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) =
-        process_remote_record(&def, None, &remote, &strategy, None).expect("should succeed");
+    let (decision, _) = process_remote_record(&def, None, &remote, &strategy, None, None, None)
+        .expect("should succeed");
 
     match decision {
         RemoteDecision::Insert(rec) => {
@@ -114,8 +118,9 @@ fn case3_clean_alive_remote_tombstone_creates_tombstone() {
     let remote = make_remote_record("user-1", 20, true);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Delete(rec) => {
@@ -138,8 +143,9 @@ fn case4_clean_alive_remote_live_updates() {
     let remote = make_remote_record("user-1", 30, false);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Update(rec) => {
@@ -163,8 +169,9 @@ fn case5_clean_deleted_remote_live_resurrects() {
     let remote = make_remote_record("user-1", 40, false);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Update(rec) => {
@@ -187,8 +194,9 @@ fn case6_clean_deleted_remote_tombstone_updates_sequence() {
     let remote = make_remote_record("user-1", 50, true);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Update(rec) => {
@@ -212,8 +220,9 @@ fn case7_dirty_deleted_remote_tombstone_applies_tombstone() {
     let remote = make_remote_record("user-1", 60, true);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Update(rec) => {
@@ -236,8 +245,9 @@ fn case8_dirty_alive_remote_tombstone_delete_wins() {
     let remote = make_remote_record("user-1", 70, true);
     let strategy = DeleteConflictStrategy::DeleteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Delete(rec) => {
@@ -260,8 +270,9 @@ fn case8_dirty_alive_remote_tombstone_update_wins() {
     let remote = make_remote_record("user-1", 70, true);
     let strategy = DeleteConflictStrategy::UpdateWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Conflict(rec) => {
@@ -285,8 +296,9 @@ fn case9_dirty_deleted_remote_live_delete_wins() {
     let remote = make_remote_record("user-1", 80, false);
     let strategy = DeleteConflictStrategy::DeleteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Conflict(rec) => {
@@ -310,8 +322,9 @@ fn case9_dirty_deleted_remote_live_update_wins() {
     let remote = make_remote_record("user-1", 80, false);
     let strategy = DeleteConflictStrategy::UpdateWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Update(rec) => {
@@ -334,11 +347,12 @@ fn case10_dirty_alive_remote_live_merges() {
     let remote = make_remote_record("user-1", 90, false);
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
-        RemoteDecision::Merge(_rec) => {
+        RemoteDecision::Merge(_rec, _) => {
             // merge occurred — just verify we got a Merge decision
         }
         _other => panic!("expected Merge, got other"),
@@ -359,8 +373,9 @@ fn skip_when_dirty_local_sequence_gte_remote() {
     let remote = make_remote_record("user-1", 50, false); // remote is behind
     let strategy = DeleteConflictStrategy::RemoteWins;
 
-    let (decision, _) = process_remote_record(&def, Some(&local), &remote, &strategy, None)
-        .expect("should succeed");
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     match decision {
         RemoteDecision::Skip => {}
@@ -368,6 +383,176 @@ fn skip_when_dirty_local_sequence_gte_remote() {
     }
 }
 
+// ============================================================================
+// process_remote_record — space delete policy
+// ============================================================================
+
+const ARCHIVING_POLICY: SpaceDeletePolicy = SpaceDeletePolicy {
+    writer_delete: DeleteKind::Archive,
+};
+
+fn clean_local(def: &CollectionDef, id: &str) -> SerializedRecord {
+    let mut rec = make_local_record(def, id);
+    rec.dirty = false;
+    rec
+}
+
+fn remote_tombstone() -> RemoteRecord {
+    make_remote_record("user-1", 20, true)
+}
+
+#[test]
+fn writer_tombstone_archives_under_archiving_policy() {
+    let def = users_def();
+    let local = clean_local(&def, "user-1");
+    let remote = remote_tombstone();
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    let (decision, action) = process_remote_record(
+        &def,
+        Some(&local),
+        &remote,
+        &strategy,
+        None,
+        Some(&ARCHIVING_POLICY),
+        Some(UCANPermission::Write),
+    )
+    .expect("should succeed");
+
+    assert!(matches!(
+        action,
+        Some(betterbase_db::types::RemoteAction::Updated)
+    ));
+    match decision {
+        RemoteDecision::Update(rec) => {
+            assert!(!rec.deleted);
+            assert!(rec.archived);
+            assert_eq!(rec.sequence, 20);
+            assert_eq!(rec.crdt, local.crdt);
+        }
+        _other => panic!("expected Update(archive), got other"),
+    }
+}
+
+#[test]
+fn reader_tombstone_archives_under_any_policy() {
+    let def = users_def();
+    let local = clean_local(&def, "user-1");
+    let remote = remote_tombstone();
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    let (decision, _) = process_remote_record(
+        &def,
+        Some(&local),
+        &remote,
+        &strategy,
+        None,
+        Some(&SpaceDeletePolicy::default()),
+        Some(UCANPermission::Read),
+    )
+    .expect("should succeed");
+
+    match decision {
+        RemoteDecision::Update(rec) => assert!(rec.archived && !rec.deleted),
+        _other => panic!("expected Update(archive), got other"),
+    }
+}
+
+#[test]
+fn admin_tombstone_applies_as_sent() {
+    let def = users_def();
+    let local = clean_local(&def, "user-1");
+    let remote = remote_tombstone();
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    let (decision, _) = process_remote_record(
+        &def,
+        Some(&local),
+        &remote,
+        &strategy,
+        None,
+        Some(&ARCHIVING_POLICY),
+        Some(UCANPermission::Admin),
+    )
+    .expect("should succeed");
+
+    match decision {
+        RemoteDecision::Delete(rec) => assert!(rec.deleted),
+        _other => panic!("expected Delete, got other"),
+    }
+}
+
+#[test]
+fn unattributed_tombstone_archives_under_policy() {
+    let def = users_def();
+    let local = clean_local(&def, "user-1");
+    let remote = remote_tombstone();
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    for policy in [ARCHIVING_POLICY, SpaceDeletePolicy::default()] {
+        let (decision, _) = process_remote_record(
+            &def,
+            Some(&local),
+            &remote,
+            &strategy,
+            None,
+            Some(&policy),
+            None,
+        )
+        .expect("should succeed");
+
+        match decision {
+            RemoteDecision::Update(rec) => assert!(rec.archived && !rec.deleted),
+            _other => panic!("expected Update(archive), got other"),
+        }
+    }
+}
+
+#[test]
+fn writer_tombstone_applies_without_policy() {
+    let def = users_def();
+    let local = clean_local(&def, "user-1");
+    let remote = remote_tombstone();
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    let (decision, _) = process_remote_record(
+        &def,
+        Some(&local),
+        &remote,
+        &strategy,
+        None,
+        None,
+        Some(UCANPermission::Write),
+    )
+    .expect("should succeed");
+
+    assert!(matches!(decision, RemoteDecision::Delete(_)));
+}
+
+#[test]
+fn clean_archived_local_stays_archived_on_remote_update() {
+    let def = users_def();
+    let local = {
+        let mut rec = clean_local(&def, "user-1");
+        rec.archived = true;
+        rec
+    };
+    let remote = make_remote_record("user-1", 30, false);
+    let strategy = DeleteConflictStrategy::RemoteWins;
+
+    let (decision, _) =
+        process_remote_record(&def, Some(&local), &remote, &strategy, None, None, None)
+            .expect("should succeed");
+
+    match decision {
+        RemoteDecision::Update(rec) => {
+            assert!(rec.archived);
+            assert_eq!(rec.sequence, 30);
+        }
+        _other => panic!("expected Update, got other"),
+    }
+}
+
 // ============================================================================
 // apply_remote_decisions
 // ============================================================================
@@ -380,7 +565,8 @@ fn apply_remote_decisions_calls_put_fn_for_writes() {
     let strategy = DeleteConflictStrategy::RemoteWins;
 
     let (decision, action) =
-        process_remote_record(&def, None, &remote, &strategy, None).expect("should succeed");
+        process_remote_record(&def, None, &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     let mut persisted: Vec<String> = Vec::new();
     let decisions = vec![(decision, action)];
@@ -417,7 +603,8 @@ fn apply_remote_decisions_collects_put_errors() {
     let strategy = DeleteConflictStrategy::RemoteWins;
 
     let (decision, action) =
-        process_remote_record(&def, None, &remote, &strategy, None).expect("should succeed");
+        process_remote_record(&def, None, &remote, &strategy, None, None, None)
+            .expect("should succeed");
 
     let decisions = vec![(decision, action)];
     let (results, errors) = apply_remote_decisions(decisions, &mut |_rec| {
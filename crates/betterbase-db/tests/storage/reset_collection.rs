@@ -0,0 +1,410 @@
+//! Tests for `Adapter::reset_collection`: wiping one collection's records,
+//! index entries and sync state without touching the others. Also covers
+//! `drop_collection` and `rename_collection`, which are built on it.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    error::{Result, StorageError},
+    index::types::{IndexDefinition, IndexScan},
+    query::types::Query,
+    reactive::adapter::ReactiveAdapter,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        memory_mapped::MemoryMapped,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageSync, StorageWrite},
+    },
+    types::{
+        DeleteOptions, PurgeTombstonesOptions, PutOptions, RawBatchResult, ResetOptions,
+        ScanOptions, SerializedRecord,
+    },
+};
+use serde_json::{json, Value};
+
+// ============================================================================
+// Failing backend
+// ============================================================================
+
+/// `SqliteBackend` whose tombstone purge fails while `fail_purge` is set,
+/// standing in for a reset interrupted after its records were tombstoned.
+struct FailingPurgeBackend {
+    inner: SqliteBackend,
+    fail_purge: Arc<AtomicBool>,
+}
+
+impl StorageBackend for FailingPurgeBackend {
+    fn get_raw(&self, collection: &str, id: &str) -> Result<Option<SerializedRecord>> {
+        self.inner.get_raw(collection, id)
+    }
+
+    fn put_raw(&self, record: &SerializedRecord) -> Result<()> {
+        self.inner.put_raw(record)
+    }
+
+    fn scan_raw(&self, collection: &str, options: &ScanOptions) -> Result<RawBatchResult> {
+        self.inner.scan_raw(collection, options)
+    }
+
+    fn scan_dirty_raw(&self, collection: &str) -> Result<RawBatchResult> {
+        self.inner.scan_dirty_raw(collection)
+    }
+
+    fn count_raw(&self, collection: &str) -> Result<usize> {
+        self.inner.count_raw(collection)
+    }
+
+    fn batch_put_raw(&self, records: &[SerializedRecord]) -> Result<()> {
+        self.inner.batch_put_raw(records)
+    }
+
+    fn purge_tombstones_raw(
+        &self,
+        collection: &str,
+        options: &PurgeTombstonesOptions,
+    ) -> Result<usize> {
+        if self.fail_purge.load(Ordering::Relaxed) {
+            return Err(StorageError::ReadOnly("purge tombstones".to_string()).into());
+        }
+        self.inner.purge_tombstones_raw(collection, options)
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>> {
+        self.inner.get_meta(key)
+    }
+
+    fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.inner.set_meta(key, value)
+    }
+
+    fn transaction<F, T>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Self) -> Result<T>,
+    {
+        self.inner.transaction(|_| f(self))
+    }
+
+    fn scan_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<RawBatchResult>> {
+        self.inner.scan_index_raw(collection, scan)
+    }
+
+    fn count_index_raw(&self, collection: &str, scan: &IndexScan) -> Result<Option<usize>> {
+        self.inner.count_index_raw(collection, scan)
+    }
+
+    fn check_unique(
+        &self,
+        collection: &str,
+        index: &IndexDefinition,
+        data: &Value,
+        computed: Option<&Value>,
+        exclude_id: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .check_unique(collection, index, data, computed, exclude_id)
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn def(name: &str) -> Arc<CollectionDef> {
+    Arc::new(
+        collection(name)
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s
+            })
+            .index(&["title"])
+            .build(),
+    )
+}
+
+fn open_backend(path: &str, defs: &[&Arc<CollectionDef>]) -> SqliteBackend {
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    let defs: Vec<&CollectionDef> = defs.iter().map(|d| &***d).collect();
+    backend.initialize(&defs).expect("backend initialize");
+    backend
+}
+
+fn put(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, title: &str) -> String {
+    adapter
+        .put(def, json!({ "title": title }), &PutOptions::default())
+        .expect("put")
+        .id
+}
+
+fn title_query(title: &str) -> Query {
+    Query {
+        filter: Some(json!({ "title": title })),
+        ..Default::default()
+    }
+}
+
+fn all_rows(backend: &SqliteBackend, collection: &str) -> usize {
+    let scan = ScanOptions {
+        include_deleted: true,
+        include_archived: true,
+        ..Default::default()
+    };
+    backend.scan_raw(collection, &scan).unwrap().records.len()
+}
+
+// ============================================================================
+// reset_collection
+// ============================================================================
+
+#[test]
+fn reset_collection_wipes_only_that_collection() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reset.db");
+    let path = path.to_str().unwrap();
+    let notes = def("notes");
+    let tasks = def("tasks");
+
+    let mut adapter = Adapter::new(open_backend(path, &[&notes, &tasks]));
+    adapter
+        .initialize(&[Arc::clone(&notes), Arc::clone(&tasks)])
+        .expect("adapter initialize");
+
+    put(&adapter, &notes, "Alpha");
+    put(&adapter, &notes, "Beta");
+    let gone = put(&adapter, &notes, "Gamma");
+    adapter
+        .delete(&notes, &gone, &DeleteOptions::default())
+        .unwrap();
+    let kept = put(&adapter, &tasks, "Alpha");
+    adapter.set_last_sequence("notes", 7).unwrap();
+    adapter.set_last_sequence("tasks", 9).unwrap();
+
+    let removed = adapter
+        .reset_collection(
+            &notes,
+            &ResetOptions {
+                reset_sync_state: true,
+            },
+        )
+        .unwrap();
+    assert_eq!(removed, 3, "tombstones are removed too");
+
+    // Collection and its index are empty
+    assert_eq!(adapter.count(&notes, None).unwrap(), 0);
+    let plan = adapter.explain_query(&notes, &title_query("Alpha"));
+    let scan = plan.scan.expect("title query uses the index");
+    let backend = open_backend(path, &[&notes, &tasks]);
+    let indexed = backend.scan_index_raw("notes", &scan).unwrap().unwrap();
+    assert!(indexed.records.is_empty());
+    assert_eq!(all_rows(&backend, "notes"), 0);
+    assert_eq!(adapter.get_last_sequence("notes").unwrap(), 0);
+
+    // Other collection untouched
+    let result = adapter.query(&tasks, &title_query("Alpha")).unwrap();
+    assert_eq!(result.records.len(), 1);
+    assert_eq!(result.records[0].id, kept);
+    assert_eq!(adapter.get_last_sequence("tasks").unwrap(), 9);
+
+    // The collection stays usable
+    put(&adapter, &notes, "Delta");
+    assert_eq!(adapter.count(&notes, None).unwrap(), 1);
+}
+
+#[test]
+fn reset_collection_keeps_sync_cursor_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reset.db");
+    let path = path.to_str().unwrap();
+    let notes = def("notes");
+
+    let mut adapter = Adapter::new(open_backend(path, &[&notes]));
+    adapter
+        .initialize(std::slice::from_ref(&notes))
+        .expect("adapter initialize");
+
+    put(&adapter, &notes, "Alpha");
+    adapter.set_last_sequence("notes", 7).unwrap();
+
+    assert_eq!(
+        adapter
+            .reset_collection(&notes, &ResetOptions::default())
+            .unwrap(),
+        1
+    );
+    assert_eq!(adapter.count(&notes, None).unwrap(), 0);
+    assert_eq!(adapter.get_last_sequence("notes").unwrap(), 7);
+    assert!(adapter.get_dirty(&notes).unwrap().records.is_empty());
+}
+
+#[test]
+fn interrupted_reset_rolls_back_and_can_be_retried() {
+    let notes = def("notes");
+    let mut inner = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    inner
+        .initialize(&[notes.as_ref()])
+        .expect("backend initialize");
+    let fail_purge = Arc::new(AtomicBool::new(true));
+    let mut adapter = Adapter::new(FailingPurgeBackend {
+        inner,
+        fail_purge: Arc::clone(&fail_purge),
+    });
+    adapter
+        .initialize(std::slice::from_ref(&notes))
+        .expect("adapter initialize");
+
+    let kept = adapter
+        .put(&notes, json!({ "title": "Alpha" }), &PutOptions::default())
+        .expect("put")
+        .id;
+    adapter.set_last_sequence("notes", 7).unwrap();
+    let reset = ResetOptions {
+        reset_sync_state: true,
+    };
+
+    // The purge fails after every record was tombstoned: nothing sticks
+    assert!(adapter.reset_collection(&notes, &reset).is_err());
+    assert_eq!(adapter.count(&notes, None).unwrap(), 1);
+    let dirty = adapter.get_dirty(&notes).unwrap().records;
+    assert_eq!(dirty.len(), 1);
+    assert_eq!(dirty[0].id, kept);
+    assert!(!dirty[0].deleted);
+    assert_eq!(adapter.get_last_sequence("notes").unwrap(), 7);
+
+    fail_purge.store(false, Ordering::Relaxed);
+    assert_eq!(adapter.reset_collection(&notes, &reset).unwrap(), 1);
+    assert_eq!(adapter.count(&notes, None).unwrap(), 0);
+    assert_eq!(adapter.get_last_sequence("notes").unwrap(), 0);
+}
+
+#[test]
+fn reset_collection_on_memory_mapped_reaches_the_inner_backend() {
+    let notes = def("notes");
+    let mut inner = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    inner
+        .initialize(&[notes.as_ref()])
+        .expect("backend initialize");
+    let mut mm = MemoryMapped::new(inner);
+    mm.load_from_inner().expect("load");
+    let mut adapter = ReactiveAdapter::new(Adapter::new(mm));
+    adapter
+        .initialize(std::slice::from_ref(&notes))
+        .expect("adapter initialize");
+
+    adapter
+        .put(&notes, json!({ "title": "Alpha" }), &PutOptions::default())
+        .expect("put");
+    assert_eq!(
+        adapter
+            .reset_collection(&notes, &ResetOptions::default())
+            .unwrap(),
+        1
+    );
+    assert_eq!(adapter.count(&notes, None).unwrap(), 0);
+
+    let left = adapter.with_backend(|mm| {
+        mm.flush().expect("flush");
+        let scan = ScanOptions {
+            include_deleted: true,
+            include_archived: true,
+            ..Default::default()
+        };
+        mm.inner().scan_raw("notes", &scan).unwrap().records
+    });
+    assert!(left.is_empty());
+}
+
+// ============================================================================
+// drop_collection
+// ============================================================================
+
+#[test]
+fn drop_collection_clears_records_and_sync_cursor() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reset.db");
+    let path = path.to_str().unwrap();
+    let notes = def("notes");
+
+    let mut adapter = Adapter::new(open_backend(path, &[&notes]));
+    adapter
+        .initialize(std::slice::from_ref(&notes))
+        .expect("adapter initialize");
+
+    put(&adapter, &notes, "Alpha");
+    put(&adapter, &notes, "Beta");
+    adapter.set_last_sequence("notes", 7).unwrap();
+
+    assert_eq!(adapter.drop_collection(&notes).unwrap().len(), 2);
+    assert_eq!(adapter.count(&notes, None).unwrap(), 0);
+    assert_eq!(adapter.get_last_sequence("notes").unwrap(), 0);
+}
+
+// ============================================================================
+// rename_collection
+// ============================================================================
+
+#[test]
+fn rename_collection_moves_records_index_entries_and_dirty_state() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reset.db");
+    let path = path.to_str().unwrap();
+    let notes = def("notes");
+    let tasks = def("tasks");
+
+    let mut adapter = Adapter::new(open_backend(path, &[&notes, &tasks]));
+    adapter
+        .initialize(&[Arc::clone(&notes), Arc::clone(&tasks)])
+        .expect("adapter initialize");
+
+    let alpha = put(&adapter, &notes, "Alpha");
+    put(&adapter, &notes, "Beta");
+    let gone = put(&adapter, &notes, "Gamma");
+    adapter
+        .delete(&notes, &gone, &DeleteOptions::default())
+        .unwrap();
+    let dirty = adapter.get_dirty(&notes).unwrap().records.len();
+
+    let moved = adapter.rename_collection("notes", &tasks).unwrap();
+    assert_eq!(moved.len(), 3, "tombstones move too");
+
+    let backend = open_backend(path, &[&notes, &tasks]);
+    assert_eq!(all_rows(&backend, "notes"), 0);
+    assert_eq!(all_rows(&backend, "tasks"), 3);
+    assert_eq!(adapter.count(&tasks, None).unwrap(), 2);
+    assert_eq!(adapter.get_dirty(&tasks).unwrap().records.len(), dirty);
+
+    // Index entries were written under the new collection
+    let plan = adapter.explain_query(&tasks, &title_query("Alpha"));
+    let scan = plan.scan.expect("title query uses the index");
+    let indexed = backend.scan_index_raw("tasks", &scan).unwrap().unwrap();
+    assert_eq!(indexed.records.len(), 1);
+    assert_eq!(indexed.records[0].id, alpha);
+}
+
+#[test]
+fn rename_collection_refuses_non_empty_or_unregistered_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reset.db");
+    let path = path.to_str().unwrap();
+    let notes = def("notes");
+    let tasks = def("tasks");
+
+    let mut adapter = Adapter::new(open_backend(path, &[&notes, &tasks]));
+    adapter
+        .initialize(&[Arc::clone(&notes), Arc::clone(&tasks)])
+        .expect("adapter initialize");
+
+    put(&adapter, &notes, "Alpha");
+    put(&adapter, &tasks, "Beta");
+
+    let err = adapter.rename_collection("notes", &tasks).unwrap_err();
+    assert_eq!(err.code(), "STORAGE_COLLECTION_NOT_EMPTY");
+    let err = adapter
+        .rename_collection("notes", &def("archive"))
+        .unwrap_err();
+    assert_eq!(err.code(), "STORAGE_COLLECTION_NOT_REGISTERED");
+    assert_eq!(adapter.count(&notes, None).unwrap(), 1);
+}
@@ -0,0 +1,244 @@
+//! Tests for `Adapter::snapshot`: reads through one handle see a single
+//! point in time, on both SQLite and MemoryMapped backends.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    crdt::MIN_SESSION_ID,
+    query::types::Query,
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        memory_mapped::MemoryMapped,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageWrite},
+    },
+    types::{
+        DeleteOptions, GetOptions, ListOptions, PatchOptions, PurgeTombstonesOptions, PutOptions,
+    },
+};
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const SID: u64 = MIN_SESSION_ID;
+
+fn tasks_def() -> Arc<CollectionDef> {
+    Arc::new(
+        collection("tasks")
+            .v(1, {
+                let mut s = BTreeMap::new();
+                s.insert("title".to_string(), t::string());
+                s.insert("done".to_string(), t::boolean());
+                s
+            })
+            .build(),
+    )
+}
+
+fn sqlite_backend(def: &CollectionDef) -> SqliteBackend {
+    let mut backend = SqliteBackend::open_in_memory().expect("open in-memory DB");
+    backend.initialize(&[def]).expect("backend initialize");
+    backend
+}
+
+fn make_adapter<B: StorageBackend>(backend: B, def: &Arc<CollectionDef>) -> Adapter<B> {
+    let mut adapter = Adapter::new(backend);
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn put<B: StorageBackend>(adapter: &Adapter<B>, def: &CollectionDef, id: &str, done: bool) {
+    adapter
+        .put(
+            def,
+            json!({ "title": id, "done": done }),
+            &PutOptions {
+                id: Some(id.to_string()),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("put");
+}
+
+fn open_query() -> Query {
+    Query {
+        filter: Some(json!({ "done": false })),
+        ..Default::default()
+    }
+}
+
+/// Write between two reads of the same snapshot; the second read must not
+/// see it, while the live adapter must.
+fn assert_write_between_reads_is_invisible<B: StorageBackend>(adapter: &Adapter<B>) {
+    let def = tasks_def();
+    put(adapter, &def, "a", false);
+    put(adapter, &def, "b", true);
+
+    let snapshot = adapter.snapshot().expect("snapshot");
+    let first = snapshot.query(&def, &open_query()).expect("first query");
+    assert_eq!(first.records.len(), 1);
+
+    put(adapter, &def, "c", false);
+    adapter
+        .patch(
+            &def,
+            json!({ "done": true }),
+            &PatchOptions {
+                id: "a".to_string(),
+                session_id: Some(SID),
+                ..Default::default()
+            },
+        )
+        .expect("patch");
+
+    let second = snapshot.query(&def, &open_query()).expect("second query");
+    let ids: Vec<&str> = second.records.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["a"]);
+    assert!(snapshot
+        .get(&def, "c", &GetOptions::default())
+        .expect("get")
+        .is_none());
+    assert_eq!(snapshot.count(&def, None).expect("count"), 2);
+
+    let live = adapter.query(&def, &open_query()).expect("live query");
+    let ids: Vec<&str> = live.records.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["c"]);
+}
+
+// ============================================================================
+// Consistency
+// ============================================================================
+
+#[test]
+fn sqlite_snapshot_hides_later_writes() {
+    let def = tasks_def();
+    let adapter = make_adapter(sqlite_backend(&def), &def);
+    assert_write_between_reads_is_invisible(&adapter);
+}
+
+#[test]
+fn memory_mapped_snapshot_hides_later_writes() {
+    let def = tasks_def();
+    let mut mm = MemoryMapped::new(sqlite_backend(&def));
+    mm.load_from_inner().expect("load");
+    let adapter = make_adapter(mm, &def);
+    assert_write_between_reads_is_invisible(&adapter);
+}
+
+#[test]
+fn snapshot_keeps_deleted_records_visible() {
+    let def = tasks_def();
+    let adapter = make_adapter(sqlite_backend(&def), &def);
+    put(&adapter, &def, "a", false);
+
+    let snapshot = adapter.snapshot().expect("snapshot");
+    assert!(adapter
+        .delete(&def, "a", &DeleteOptions::default())
+        .expect("delete"));
+
+    let all = snapshot
+        .get_all(&def, &ListOptions::default())
+        .expect("get_all");
+    assert_eq!(all.records.len(), 1);
+    assert!(!all.records[0].deleted);
+    assert_eq!(adapter.count(&def, None).expect("live count"), 0);
+}
+
+/// Bulk deletes and tombstone purges on the live adapter change records the
+/// snapshot has never read; it must still see them as they were.
+fn assert_bulk_delete_and_purge_are_invisible<B: StorageBackend>(adapter: &Adapter<B>) {
+    let def = tasks_def();
+    put(adapter, &def, "a", false);
+    put(adapter, &def, "b", true);
+    put(adapter, &def, "c", false);
+    assert!(adapter
+        .delete(&def, "b", &DeleteOptions::default())
+        .expect("delete"));
+
+    let before = adapter.snapshot().expect("snapshot");
+    adapter
+        .delete_many(&def, &json!({ "done": false }), &DeleteOptions::default())
+        .expect("delete_many");
+    let between = adapter.snapshot().expect("second snapshot");
+    adapter
+        .purge_tombstones(
+            &def,
+            &PurgeTombstonesOptions {
+                older_than_seconds: None,
+                dry_run: false,
+                skip_dirty: false,
+            },
+        )
+        .expect("purge");
+
+    assert_eq!(before.count(&def, None).expect("count"), 2);
+    let all = before
+        .get_all(
+            &def,
+            &ListOptions {
+                include_deleted: true,
+                ..Default::default()
+            },
+        )
+        .expect("get_all");
+    let states: Vec<(&str, bool)> = all
+        .records
+        .iter()
+        .map(|r| (r.id.as_str(), r.deleted))
+        .collect();
+    assert_eq!(states, vec![("a", false), ("b", true), ("c", false)]);
+
+    assert_eq!(between.count(&def, None).expect("count"), 0);
+    let tombstones = between
+        .get_all(
+            &def,
+            &ListOptions {
+                include_deleted: true,
+                ..Default::default()
+            },
+        )
+        .expect("get_all");
+    assert_eq!(tombstones.records.len(), 3);
+
+    let live = adapter
+        .get_all(
+            &def,
+            &ListOptions {
+                include_deleted: true,
+                ..Default::default()
+            },
+        )
+        .expect("live get_all");
+    assert!(live.records.is_empty());
+}
+
+#[test]
+fn sqlite_snapshot_survives_bulk_delete_and_purge() {
+    let def = tasks_def();
+    let adapter = make_adapter(sqlite_backend(&def), &def);
+    assert_bulk_delete_and_purge_are_invisible(&adapter);
+}
+
+#[test]
+fn memory_mapped_snapshot_survives_bulk_delete_and_purge() {
+    let def = tasks_def();
+    let mut mm = MemoryMapped::new(sqlite_backend(&def));
+    mm.load_from_inner().expect("load");
+    let adapter = make_adapter(mm, &def);
+    assert_bulk_delete_and_purge_are_invisible(&adapter);
+}
+
+#[test]
+fn snapshot_requires_initialized_adapter() {
+    let def = tasks_def();
+    let adapter = Adapter::new(sqlite_backend(&def));
+    assert!(adapter.snapshot().is_err());
+}
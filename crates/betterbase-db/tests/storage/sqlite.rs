@@ -34,6 +34,7 @@ fn make_record(id: &str, collection: &str) -> SerializedRecord {
         dirty: false,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         computed: None,
     }
@@ -94,6 +95,7 @@ fn put_raw_then_get_raw_round_trips() {
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: Some(json!({ "source": "test" })),
         computed: Some(json!({ "emailLower": "alice@example.com" })),
     };
@@ -373,6 +375,7 @@ fn purge_tombstones_raw_removes_deleted_records() {
             &PurgeTombstonesOptions {
                 older_than_seconds: None,
                 dry_run: false,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -396,6 +399,7 @@ fn purge_tombstones_raw_dry_run_does_not_delete() {
             &PurgeTombstonesOptions {
                 older_than_seconds: None,
                 dry_run: true,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -423,6 +427,7 @@ fn purge_tombstones_raw_older_than_keeps_recent_tombstones() {
         deleted: true,
         // Use a past date for the deleted_at
         deleted_at: Some("2000-01-01T00:00:00Z".to_string()),
+        archived: false,
         ..make_record("old-tomb", "col")
     };
     backend.put_raw(&t).unwrap();
@@ -439,6 +444,7 @@ fn purge_tombstones_raw_older_than_keeps_recent_tombstones() {
             &PurgeTombstonesOptions {
                 older_than_seconds: Some(1),
                 dry_run: false,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -530,6 +536,7 @@ fn check_unique_computed_succeeds_when_no_conflict() {
         compute: Arc::new(|_| None),
         unique: true,
         sparse: false,
+        geo: None,
     });
 
     let result = backend.check_unique(
@@ -558,6 +565,7 @@ fn check_unique_computed_returns_error_on_conflict() {
         compute: Arc::new(|_| None),
         unique: true,
         sparse: false,
+        geo: None,
     });
 
     let result = backend.check_unique(
@@ -961,6 +969,7 @@ fn check_unique_sparse_computed_index_allows_null() {
         compute: Arc::new(|_| None), // always null
         unique: true,
         sparse: true,
+        geo: None,
     });
 
     let result = backend.check_unique("col", &index, &json!({}), None, None);
@@ -1158,6 +1167,7 @@ fn scan_index_computed_exact_match() {
         compute: Arc::new(|_| None),
         unique: false,
         sparse: false,
+        geo: None,
     });
 
     let scan = IndexScan {
@@ -1198,6 +1208,7 @@ fn purge_tombstones_older_than_zero_purges_all() {
     let mut t = SerializedRecord {
         deleted: true,
         deleted_at: Some("2020-01-01T00:00:00Z".to_string()),
+        archived: false,
         ..make_record("t1", "col")
     };
     backend.put_raw(&t).unwrap();
@@ -1212,6 +1223,7 @@ fn purge_tombstones_older_than_zero_purges_all() {
             &PurgeTombstonesOptions {
                 older_than_seconds: Some(0),
                 dry_run: false,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -1226,6 +1238,7 @@ fn purge_tombstones_null_deleted_at_skipped() {
     let mut t = SerializedRecord {
         deleted: true,
         deleted_at: None, // null deleted_at
+        archived: false,
         ..make_record("t1", "col")
     };
     backend.put_raw(&t).unwrap();
@@ -1240,6 +1253,7 @@ fn purge_tombstones_null_deleted_at_skipped() {
             &PurgeTombstonesOptions {
                 older_than_seconds: Some(1),
                 dry_run: false,
+                skip_dirty: false,
             },
         )
         .unwrap();
@@ -0,0 +1,134 @@
+//! Tests for per-collection `tombstone_retention` and
+//! `StorageSync::purge_expired_tombstones`: only acknowledged tombstones
+//! past the retention are dropped.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use betterbase_db::{
+    collection::builder::{collection, CollectionDef},
+    schema::node::t,
+    storage::{
+        adapter::Adapter,
+        sqlite::SqliteBackend,
+        traits::{StorageBackend, StorageLifecycle, StorageRead, StorageSync, StorageWrite},
+    },
+    types::{DeleteOptions, GetOptions, PutOptions},
+};
+use serde_json::json;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const DAY: Duration = Duration::from_secs(86_400);
+
+fn notes_def(retention: Option<Duration>) -> Arc<CollectionDef> {
+    let builder = collection("notes").v(1, {
+        let mut s = BTreeMap::new();
+        s.insert("title".to_string(), t::string());
+        s
+    });
+    let builder = match retention {
+        Some(retention) => builder.tombstone_retention(retention),
+        None => builder,
+    };
+    Arc::new(builder.build())
+}
+
+fn open_backend(path: &str, def: &CollectionDef) -> SqliteBackend {
+    let mut backend = SqliteBackend::open(path).expect("open DB");
+    backend.initialize(&[def]).expect("backend initialize");
+    backend
+}
+
+fn make_adapter(path: &str, def: &Arc<CollectionDef>) -> Adapter<SqliteBackend> {
+    let mut adapter = Adapter::new(open_backend(path, def));
+    adapter
+        .initialize(std::slice::from_ref(def))
+        .expect("adapter initialize");
+    adapter
+}
+
+fn tombstone(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, id: &str, synced: bool) {
+    let opts = PutOptions {
+        id: Some(id.to_string()),
+        ..Default::default()
+    };
+    adapter
+        .put(def, json!({ "title": id }), &opts)
+        .expect("put");
+    adapter.mark_synced(def, id, 1, None).expect("mark_synced");
+    adapter
+        .delete(def, id, &DeleteOptions::default())
+        .expect("delete");
+    if synced {
+        adapter.mark_synced(def, id, 2, None).expect("mark_synced");
+    }
+}
+
+fn backdate_deletion(path: &str, def: &CollectionDef, id: &str) {
+    let backend = open_backend(path, def);
+    let mut record = backend.get_raw(&def.name, id).unwrap().unwrap();
+    record.deleted_at = Some("2000-01-01T00:00:00Z".to_string());
+    backend.put_raw(&record).unwrap();
+}
+
+fn exists(adapter: &Adapter<SqliteBackend>, def: &CollectionDef, id: &str) -> bool {
+    let opts = GetOptions {
+        include_deleted: true,
+        ..Default::default()
+    };
+    adapter.get(def, id, &opts).unwrap().is_some()
+}
+
+// ============================================================================
+// purge_expired_tombstones
+// ============================================================================
+
+#[test]
+fn builder_records_tombstone_retention() {
+    assert_eq!(notes_def(Some(DAY)).tombstone_retention, Some(DAY));
+    assert_eq!(notes_def(None).tombstone_retention, None);
+}
+
+#[test]
+fn purge_keeps_unsynced_and_recent_tombstones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("retention.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def(Some(DAY));
+    let adapter = make_adapter(path, &def);
+
+    tombstone(&adapter, &def, "old", true);
+    tombstone(&adapter, &def, "old-unpushed", false);
+    tombstone(&adapter, &def, "recent", true);
+    backdate_deletion(path, &def, "old");
+    backdate_deletion(path, &def, "old-unpushed");
+
+    assert_eq!(adapter.purge_expired_tombstones(&def).unwrap(), 1);
+    assert!(!exists(&adapter, &def, "old"));
+    assert!(exists(&adapter, &def, "old-unpushed"));
+    assert!(exists(&adapter, &def, "recent"));
+
+    // The unsynced tombstone is still queued for push
+    let dirty = adapter.get_dirty(&def).unwrap().records;
+    assert_eq!(dirty.len(), 1);
+    assert_eq!(dirty[0].id, "old-unpushed");
+}
+
+#[test]
+fn purge_is_a_no_op_without_retention() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("retention.db");
+    let path = path.to_str().unwrap();
+    let def = notes_def(None);
+    let adapter = make_adapter(path, &def);
+
+    tombstone(&adapter, &def, "old", true);
+    backdate_deletion(path, &def, "old");
+
+    assert_eq!(adapter.purge_expired_tombstones(&def).unwrap(), 0);
+    assert!(exists(&adapter, &def, "old"));
+}
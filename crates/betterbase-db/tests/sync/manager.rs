@@ -9,17 +9,19 @@ use std::sync::{
 };
 
 use async_trait::async_trait;
+use betterbase_crypto::UCANPermission;
+use betterbase_sync_core::{MembershipState, SpaceDeletePolicy};
 use parking_lot::Mutex;
 use serde_json::json;
 
 use betterbase_db::collection::builder::{collection, CollectionDef};
 use betterbase_db::schema::node::t;
 use betterbase_db::sync::types::*;
-use betterbase_db::sync::SyncManager;
+use betterbase_db::sync::{detect_sequence_gap, SyncManager};
 use betterbase_db::types::{
-    ApplyRemoteOptions, ApplyRemoteRecordResult, ApplyRemoteResult, BatchResult,
-    DeleteConflictStrategyName, PushSnapshot, RecordError, RemoteAction, RemoteRecord,
-    StoredRecordWithMeta,
+    ApplyRemoteOptions, ApplyRemoteRecordResult, ApplyRemoteResult, BatchResult, ConflictWinner,
+    DeleteConflictStrategyName, DeleteKind, MergeConflict, PushQueueState, PushSnapshot,
+    RecordError, RemoteAction, RemoteRecord, StoredRecordWithMeta,
 };
 
 // ============================================================================
@@ -163,6 +165,7 @@ struct MockAdapterInner {
     dirty_records: HashMap<String, Vec<StoredRecordWithMeta>>,
     dirty_errors: HashMap<String, Vec<RecordError>>,
     sequences: HashMap<String, i64>,
+    push_states: HashMap<String, PushQueueState>,
     mark_synced_calls: Vec<MarkSyncedCall>,
     apply_calls: Vec<ApplyCall>,
     apply_response: Option<
@@ -181,6 +184,8 @@ struct MockAdapterInner {
     get_dirty_error: Option<String>,
     get_last_sequence_error: Option<String>,
     set_last_sequence_error: Option<String>,
+    clear_dirty_on_sync: bool,
+    purge_calls: Vec<String>,
 }
 
 struct MockAdapter {
@@ -194,6 +199,7 @@ impl MockAdapter {
                 dirty_records: HashMap::new(),
                 dirty_errors: HashMap::new(),
                 sequences: HashMap::new(),
+                push_states: HashMap::new(),
                 mark_synced_calls: Vec::new(),
                 apply_calls: Vec::new(),
                 apply_response: None,
@@ -201,6 +207,8 @@ impl MockAdapter {
                 get_dirty_error: None,
                 get_last_sequence_error: None,
                 set_last_sequence_error: None,
+                clear_dirty_on_sync: false,
+                purge_calls: Vec::new(),
             }),
         }
     }
@@ -268,6 +276,21 @@ impl MockAdapter {
         self.inner.lock().set_last_sequence_error = Some(msg.to_string());
     }
 
+    /// Drop records from the dirty set once they are marked synced, like a
+    /// real adapter.
+    fn clear_dirty_on_sync(&self) {
+        self.inner.lock().clear_dirty_on_sync = true;
+    }
+
+    fn dirty_ids(&self, collection: &str) -> Vec<String> {
+        self.inner
+            .lock()
+            .dirty_records
+            .get(collection)
+            .map(|records| records.iter().map(|r| r.id.clone()).collect())
+            .unwrap_or_default()
+    }
+
     fn mark_synced_calls(&self) -> Vec<(String, String, i64)> {
         self.inner
             .lock()
@@ -323,6 +346,11 @@ impl SyncAdapter for MockAdapter {
         if let Some(ref f) = inner.mark_synced_response {
             return f(&def.name, id, sequence);
         }
+        if inner.clear_dirty_on_sync {
+            if let Some(records) = inner.dirty_records.get_mut(&def.name) {
+                records.retain(|r| r.id != id);
+            }
+        }
         Ok(())
     }
 
@@ -359,6 +387,7 @@ impl SyncAdapter for MockAdapter {
             errors: Vec::new(),
             new_sequence: records.iter().map(|r| r.sequence).max().unwrap_or(0),
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     }
 
@@ -382,6 +411,37 @@ impl SyncAdapter for MockAdapter {
         inner.sequences.insert(collection.to_string(), sequence);
         Ok(())
     }
+
+    fn get_push_state(
+        &self,
+        collection: &str,
+    ) -> betterbase_db::error::Result<Option<PushQueueState>> {
+        Ok(self.inner.lock().push_states.get(collection).cloned())
+    }
+
+    fn set_push_state(
+        &self,
+        collection: &str,
+        state: Option<&PushQueueState>,
+    ) -> betterbase_db::error::Result<()> {
+        let mut inner = self.inner.lock();
+        match state {
+            Some(state) => {
+                inner
+                    .push_states
+                    .insert(collection.to_string(), state.clone());
+            }
+            None => {
+                inner.push_states.remove(collection);
+            }
+        }
+        Ok(())
+    }
+
+    fn purge_expired_tombstones(&self, def: &CollectionDef) -> betterbase_db::error::Result<usize> {
+        self.inner.lock().purge_calls.push(def.name.clone());
+        Ok(2)
+    }
 }
 
 // ============================================================================
@@ -407,6 +467,7 @@ fn make_dirty_record(id: &str, collection: &str) -> StoredRecordWithMeta {
         dirty: true,
         deleted: false,
         deleted_at: None,
+        archived: false,
         meta: None,
         was_migrated: false,
         original_version: None,
@@ -425,6 +486,7 @@ fn make_tombstone_record(id: &str, collection: &str) -> StoredRecordWithMeta {
         dirty: true,
         deleted: true,
         deleted_at: Some("2024-01-01T00:00:00Z".to_string()),
+        archived: false,
         meta: None,
         was_migrated: false,
         original_version: None,
@@ -437,6 +499,7 @@ fn make_remote_record(id: &str, seq: i64) -> RemoteRecord {
         version: 1,
         crdt: Some(vec![10, 20, 30]),
         deleted: false,
+        archived: false,
         sequence: seq,
         meta: None,
     }
@@ -448,6 +511,7 @@ fn make_remote_tombstone(id: &str, seq: i64) -> RemoteRecord {
         version: 1,
         crdt: None,
         deleted: true,
+        archived: false,
         sequence: seq,
         meta: None,
     }
@@ -477,9 +541,50 @@ fn make_manager_with_opts(
         on_error,
         on_progress,
         on_remote_delete,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     })
 }
 
+fn make_policy_manager(
+    transport: Arc<MockTransport>,
+    adapter: Arc<MockAdapter>,
+    permission: UCANPermission,
+) -> SyncManager {
+    SyncManager::new(policy_options(transport, adapter, permission))
+}
+
+/// Options for a "tasks" manager under a policy that makes writers archive.
+fn policy_options(
+    transport: Arc<MockTransport>,
+    adapter: Arc<MockAdapter>,
+    permission: UCANPermission,
+) -> SyncManagerOptions {
+    SyncManagerOptions {
+        transport,
+        adapter,
+        collections: vec![make_def("tasks")],
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: Some(SpaceDeletePolicy {
+            writer_delete: DeleteKind::Archive,
+        }),
+        permission: Some(permission),
+        membership: None,
+    }
+}
+
 // ============================================================================
 // Push Tests
 // ============================================================================
@@ -544,6 +649,90 @@ async fn push_tombstone_sends_null_crdt() {
     assert!(calls[0].records[0].deleted);
 }
 
+#[tokio::test]
+async fn push_archived_record_keeps_crdt() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    let mut record = make_dirty_record("r1", "tasks");
+    record.archived = true;
+    adapter.set_dirty("tasks", vec![record]);
+
+    let manager = make_manager(transport.clone(), adapter.clone());
+    let result = manager.push(&def).await;
+
+    assert_eq!(result.pushed, 1);
+    let calls = transport.push_calls();
+    assert!(calls[0].records[0].archived);
+    assert!(!calls[0].records[0].deleted);
+    assert_eq!(calls[0].records[0].crdt, Some(vec![1, 2, 3]));
+}
+
+#[tokio::test]
+async fn push_writer_delete_goes_out_as_archive() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    let mut record = make_dirty_record("r1", "tasks");
+    record.deleted = true;
+    adapter.set_dirty("tasks", vec![record]);
+
+    let manager = make_policy_manager(transport.clone(), adapter.clone(), UCANPermission::Write);
+    let result = manager.push(&def).await;
+
+    assert_eq!(result.pushed, 1);
+    let calls = transport.push_calls();
+    assert!(calls[0].records[0].archived);
+    assert!(!calls[0].records[0].deleted);
+    assert_eq!(calls[0].records[0].crdt, Some(vec![1, 2, 3]));
+}
+
+#[tokio::test]
+async fn push_admin_delete_goes_out_as_tombstone() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    adapter.set_dirty("tasks", vec![make_tombstone_record("r1", "tasks")]);
+
+    let manager = make_policy_manager(transport.clone(), adapter.clone(), UCANPermission::Admin);
+    let result = manager.push(&def).await;
+
+    assert_eq!(result.pushed, 1);
+    let calls = transport.push_calls();
+    assert!(calls[0].records[0].deleted);
+    assert!(!calls[0].records[0].archived);
+    assert!(calls[0].records[0].crdt.is_none());
+}
+
+#[tokio::test]
+async fn push_reader_delete_is_refused() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    adapter.set_dirty(
+        "tasks",
+        vec![
+            make_tombstone_record("r1", "tasks"),
+            make_dirty_record("r2", "tasks"),
+        ],
+    );
+
+    let manager = make_policy_manager(transport.clone(), adapter.clone(), UCANPermission::Read);
+    let result = manager.push(&def).await;
+
+    assert_eq!(result.pushed, 1);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].id.as_deref(), Some("r1"));
+    assert_eq!(result.errors[0].kind, SyncErrorKind::Permanent);
+    let calls = transport.push_calls();
+    assert_eq!(calls[0].records.len(), 1);
+    assert_eq!(calls[0].records[0].id, "r2");
+}
+
 #[tokio::test]
 async fn push_no_dirty_records_returns_zero() {
     let transport = Arc::new(MockTransport::new());
@@ -844,6 +1033,397 @@ async fn push_batching_invalid_batch_size_zero() {
     assert!(result.errors[0].error.contains("positive"));
 }
 
+// ============================================================================
+// Push Backoff Persistence Tests
+// ============================================================================
+
+fn make_manager_with_backoff(
+    transport: Arc<MockTransport>,
+    adapter: Arc<MockAdapter>,
+    push_backoff: PushBackoff,
+) -> SyncManager {
+    SyncManager::new(SyncManagerOptions {
+        transport,
+        adapter,
+        collections: vec![make_def("tasks")],
+        delete_strategy: None,
+        push_batch_size: Some(2),
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: Some(push_backoff),
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    })
+}
+
+#[test]
+fn push_backoff_doubles_up_to_max() {
+    let backoff = PushBackoff {
+        base_ms: 100,
+        max_ms: 350,
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+    assert_eq!(backoff.delay_ms(1), 100);
+    assert_eq!(backoff.delay_ms(2), 200);
+    assert_eq!(backoff.delay_ms(3), 350);
+    assert_eq!(backoff.delay_ms(u32::MAX), 350);
+}
+
+#[tokio::test]
+async fn push_backoff_resumes_after_reload() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+    let backoff = PushBackoff {
+        base_ms: 60_000,
+        max_ms: 600_000,
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+
+    let records: Vec<StoredRecordWithMeta> = (0..4)
+        .map(|i| make_dirty_record(&format!("r{i}"), "tasks"))
+        .collect();
+    adapter.set_dirty("tasks", records);
+    transport.on_push(|_, _| Err(SyncTransportError::new("offline")));
+
+    let before = chrono::Utc::now().timestamp_millis();
+    let manager = make_manager_with_backoff(transport.clone(), adapter.clone(), backoff);
+    manager.push(&def).await;
+    drop(manager);
+
+    let state = adapter.get_push_state("tasks").unwrap().unwrap();
+    assert_eq!(state.attempts, 1);
+    assert_eq!(state.batch_ids, vec!["r0".to_string(), "r1".to_string()]);
+    assert!(state.next_attempt_at.unwrap() >= before + 60_000);
+
+    // Reload mid-backoff: a fresh manager honours the persisted schedule
+    let manager = make_manager_with_backoff(transport.clone(), adapter.clone(), backoff);
+    let result = manager.push(&def).await;
+    assert_eq!(transport.push_calls().len(), 1);
+    assert_eq!(result.pushed, 0);
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].kind, SyncErrorKind::Transient);
+    assert!(result.errors[0].error.contains("backing off"));
+
+    // Once the delay elapses, the failed batch goes first and the attempt
+    // count continues from where the previous manager left off
+    adapter
+        .set_push_state(
+            "tasks",
+            Some(&PushQueueState {
+                batch_ids: vec!["r2".to_string(), "r3".to_string()],
+                next_attempt_at: Some(before - 1),
+                ..state
+            }),
+        )
+        .unwrap();
+    let before = chrono::Utc::now().timestamp_millis();
+    manager.push(&def).await;
+
+    let calls = transport.push_calls();
+    assert_eq!(calls.len(), 2);
+    let ids: Vec<&str> = calls[1].records.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, vec!["r2", "r3"]);
+    let state = adapter.get_push_state("tasks").unwrap().unwrap();
+    assert_eq!(state.attempts, 2);
+    assert!(state.next_attempt_at.unwrap() >= before + 120_000);
+}
+
+#[tokio::test]
+async fn push_success_clears_persisted_backoff() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    adapter.set_dirty("tasks", vec![make_dirty_record("r1", "tasks")]);
+    adapter
+        .set_push_state(
+            "tasks",
+            Some(&PushQueueState {
+                batch_ids: vec!["r1".to_string()],
+                attempts: 3,
+                next_attempt_at: Some(0),
+            }),
+        )
+        .unwrap();
+
+    let manager = make_manager(transport.clone(), adapter.clone());
+    let result = manager.push(&def).await;
+
+    assert_eq!(result.pushed, 1);
+    assert!(result.errors.is_empty());
+    assert!(adapter.get_push_state("tasks").unwrap().is_none());
+}
+
+// ============================================================================
+// Retry Backoff Tests
+// ============================================================================
+
+fn make_manager_with_retry(
+    transport: Arc<MockTransport>,
+    adapter: Arc<MockAdapter>,
+    collections: Vec<Arc<CollectionDef>>,
+    push_backoff: PushBackoff,
+    on_progress: Option<Arc<SyncProgressCallback>>,
+) -> SyncManager {
+    SyncManager::new(SyncManagerOptions {
+        transport,
+        adapter,
+        collections,
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: Some(push_backoff),
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    })
+}
+
+#[test]
+fn retry_backoff_multiplies_up_to_max() {
+    let backoff = PushBackoff {
+        base_ms: 100,
+        max_ms: 1_000,
+        multiplier: 3.0,
+        jitter: 0.0,
+    };
+    assert_eq!(backoff.delay_ms(1), 100);
+    assert_eq!(backoff.delay_ms(2), 300);
+    assert_eq!(backoff.delay_ms(3), 900);
+    assert_eq!(backoff.delay_ms(4), 1_000);
+    assert_eq!(backoff.delay_ms(u32::MAX), 1_000);
+    assert_eq!(backoff.jittered_delay_ms(2), 300);
+}
+
+#[test]
+fn retry_backoff_jitter_stays_within_fraction() {
+    let backoff = PushBackoff {
+        base_ms: 1_000,
+        max_ms: 60_000,
+        multiplier: 2.0,
+        jitter: 0.5,
+    };
+    let delays: Vec<u64> = (0..100).map(|_| backoff.jittered_delay_ms(3)).collect();
+    assert!(delays.iter().all(|d| (2_000..=4_000).contains(d)));
+    // Not every client picks the same delay
+    assert!(delays.iter().any(|d| *d != delays[0]));
+}
+
+#[tokio::test]
+async fn retry_backoff_grows_then_resets_on_success() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+    let backoff = PushBackoff {
+        base_ms: 20,
+        max_ms: 1_000,
+        multiplier: 2.0,
+        jitter: 0.0,
+    };
+
+    adapter.set_dirty("tasks", vec![make_dirty_record("r1", "tasks")]);
+    // Pulls 1-3 fail, the 4th succeeds, the 5th fails again
+    let pulls = Arc::new(AtomicUsize::new(0));
+    let p = pulls.clone();
+    transport.on_pull(move |_, _| {
+        let n = p.fetch_add(1, Ordering::SeqCst) + 1;
+        if n <= 3 || n == 5 {
+            Err(SyncTransportError::new("offline"))
+        } else {
+            Ok(PullResult {
+                records: Vec::new(),
+                latest_sequence: None,
+                failures: Vec::new(),
+            })
+        }
+    });
+
+    let progress_events: Arc<Mutex<Vec<SyncProgress>>> = Arc::new(Mutex::new(Vec::new()));
+    let pe = progress_events.clone();
+    let on_progress: Arc<dyn Fn(&SyncProgress) + Send + Sync> =
+        Arc::new(move |p: &SyncProgress| {
+            pe.lock().push(p.clone());
+        });
+
+    let manager = make_manager_with_retry(
+        transport.clone(),
+        adapter.clone(),
+        vec![make_def("tasks")],
+        backoff,
+        Some(on_progress),
+    );
+
+    for (attempt, expected_delay) in [(1, 20), (2, 40), (3, 80)] {
+        let before = chrono::Utc::now().timestamp_millis();
+        let result = manager.sync(&def).await;
+        let after = chrono::Utc::now().timestamp_millis();
+
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].error, "offline");
+        let next_retry_at = manager.next_retry_at("tasks").unwrap();
+        assert!(next_retry_at >= before + expected_delay);
+        assert!(next_retry_at <= after + expected_delay);
+        assert_eq!(
+            progress_events.lock().last().unwrap().next_retry_at,
+            Some(next_retry_at)
+        );
+
+        // Calls made while backing off never reach the transport
+        let result = manager.sync(&def).await;
+        assert_eq!(pulls.load(Ordering::SeqCst), attempt);
+        assert!(transport.push_calls().is_empty());
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].kind, SyncErrorKind::Transient);
+        assert!(result.errors[0].error.contains("backing off"));
+
+        let wait = next_retry_at - chrono::Utc::now().timestamp_millis() + 1;
+        if wait > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(wait as u64)).await;
+        }
+    }
+
+    // First success clears the backoff and lets the push through
+    let result = manager.sync(&def).await;
+    assert!(result.errors.is_empty());
+    assert_eq!(result.pushed, 1);
+    assert_eq!(manager.next_retry_at("tasks"), None);
+
+    // The next failure starts over from the initial delay
+    let before = chrono::Utc::now().timestamp_millis();
+    manager.pull(&def).await;
+    let after = chrono::Utc::now().timestamp_millis();
+    let next_retry_at = manager.next_retry_at("tasks").unwrap();
+    assert!(next_retry_at >= before + 20);
+    assert!(next_retry_at <= after + 20);
+}
+
+#[tokio::test]
+async fn retry_backoff_after_pull_failure_survives_reload() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+    let backoff = PushBackoff {
+        base_ms: 60_000,
+        jitter: 0.0,
+        ..PushBackoff::default()
+    };
+    transport.on_pull(|_, _| Err(SyncTransportError::new("offline")));
+
+    let before = chrono::Utc::now().timestamp_millis();
+    let manager = make_manager_with_retry(
+        transport.clone(),
+        adapter.clone(),
+        vec![make_def("tasks")],
+        backoff,
+        None,
+    );
+    manager.pull(&def).await;
+    drop(manager);
+
+    // The failure is recorded once, in the persisted retry state
+    let state = adapter.get_push_state("tasks").unwrap().unwrap();
+    assert_eq!(state.attempts, 1);
+    assert!(state.next_attempt_at.unwrap() >= before + 60_000);
+
+    let manager = make_manager_with_retry(
+        transport.clone(),
+        adapter.clone(),
+        vec![make_def("tasks")],
+        backoff,
+        None,
+    );
+    assert_eq!(manager.next_retry_at("tasks"), state.next_attempt_at);
+    let result = manager.sync(&def).await;
+    assert_eq!(transport.pull_calls().len(), 1);
+    assert!(result.errors[0].error.contains("backing off"));
+}
+
+#[tokio::test]
+async fn retry_backoff_is_per_collection() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let backoff = PushBackoff {
+        base_ms: 60_000,
+        ..PushBackoff::default()
+    };
+
+    transport.on_pull(|collection, _| {
+        if collection == "tasks" {
+            Err(SyncTransportError::with_kind(
+                "over capacity",
+                SyncErrorKind::Capacity,
+            ))
+        } else {
+            Ok(PullResult {
+                records: Vec::new(),
+                latest_sequence: None,
+                failures: Vec::new(),
+            })
+        }
+    });
+
+    let manager = make_manager_with_retry(
+        transport.clone(),
+        adapter.clone(),
+        vec![make_def("tasks"), make_def("notes")],
+        backoff,
+        None,
+    );
+    manager.sync_all().await;
+    let results = manager.sync_all().await;
+
+    let pulled: Vec<String> = transport
+        .pull_calls()
+        .into_iter()
+        .map(|c| c.collection)
+        .collect();
+    assert_eq!(pulled, vec!["notes", "tasks", "notes"]);
+    assert!(manager.next_retry_at("tasks").is_some());
+    assert_eq!(manager.next_retry_at("notes"), None);
+    assert!(results["notes"].errors.is_empty());
+    assert!(results["tasks"].errors[0].error.contains("backing off"));
+}
+
+#[tokio::test]
+async fn retry_backoff_ignores_permanent_failures() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    transport.on_pull(|_, _| {
+        Err(SyncTransportError::with_kind(
+            "bad request",
+            SyncErrorKind::Permanent,
+        ))
+    });
+
+    let manager = make_manager_with_retry(
+        transport.clone(),
+        adapter.clone(),
+        vec![make_def("tasks")],
+        PushBackoff::default(),
+        None,
+    );
+    manager.pull(&def).await;
+    manager.pull(&def).await;
+
+    assert_eq!(transport.pull_calls().len(), 2);
+    assert_eq!(manager.next_retry_at("tasks"), None);
+}
+
 // ============================================================================
 // Pull Tests
 // ============================================================================
@@ -1092,6 +1672,7 @@ async fn cursor_advances_even_with_partial_apply_errors() {
                     id: r.id.clone(),
                     collection: "tasks".into(),
                     error: "apply failed".into(),
+                    ..Default::default()
                 });
             } else {
                 applied.push(ApplyRemoteRecordResult {
@@ -1107,6 +1688,7 @@ async fn cursor_advances_even_with_partial_apply_errors() {
             errors,
             new_sequence: 200,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1217,6 +1799,12 @@ async fn sync_all_syncs_all_collections() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     let results = manager.sync_all().await;
@@ -1255,6 +1843,12 @@ async fn sync_all_error_in_one_does_not_block_others() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     let results = manager.sync_all().await;
@@ -1263,6 +1857,118 @@ async fn sync_all_error_in_one_does_not_block_others() {
     assert_eq!(results["notes"].pulled, 1);
 }
 
+// ============================================================================
+// Resync Tests
+// ============================================================================
+
+/// Serves `count` remote records with sequences `1..=count`, `page` at a time.
+fn paged_server(transport: &MockTransport, count: i64, page: i64) {
+    transport.on_pull(move |_, since| {
+        let records: Vec<RemoteRecord> = (since + 1..=count.min(since + page))
+            .map(|seq| make_remote_record(&format!("remote-{seq}"), seq))
+            .collect();
+        Ok(PullResult {
+            latest_sequence: records.last().map(|r| r.sequence),
+            records,
+            failures: Vec::new(),
+        })
+    });
+}
+
+#[tokio::test]
+async fn resync_pulls_all_pages_then_pushes() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    adapter.clear_dirty_on_sync();
+    adapter.set_dirty(
+        "tasks",
+        vec![
+            make_dirty_record("local-1", "tasks"),
+            make_dirty_record("local-2", "tasks"),
+        ],
+    );
+    paged_server(&transport, 5, 2);
+
+    let applies_at_push = Arc::new(Mutex::new(Vec::new()));
+    let applies_at_push_clone = applies_at_push.clone();
+    let adapter_at_push = adapter.clone();
+    transport.on_push(move |_, records| {
+        applies_at_push_clone
+            .lock()
+            .push(adapter_at_push.apply_calls().len());
+        Ok(records
+            .iter()
+            .map(|r| PushAck {
+                id: r.id.clone(),
+                sequence: 100,
+            })
+            .collect())
+    });
+
+    let manager = make_manager(transport.clone(), adapter.clone());
+    let result = manager.resync().await;
+
+    assert!(result.errors.is_empty());
+    assert_eq!(result.pulled, 5);
+    assert_eq!(result.pushed, 2);
+    let since: Vec<i64> = transport.pull_calls().iter().map(|c| c.since).collect();
+    assert_eq!(since, vec![0, 2, 4, 5]);
+    // All three non-empty pages were applied before the push went out
+    assert_eq!(*applies_at_push.lock(), vec![3]);
+    assert_eq!(adapter.get_sequence("tasks"), 5);
+    assert!(adapter.dirty_ids("tasks").is_empty());
+    let applied: Vec<String> = adapter
+        .apply_calls()
+        .iter()
+        .flat_map(|(_, records)| records.iter().map(|r| r.id.clone()))
+        .collect();
+    assert_eq!(
+        applied,
+        vec!["remote-1", "remote-2", "remote-3", "remote-4", "remote-5"]
+    );
+}
+
+#[tokio::test]
+async fn resync_is_idempotent() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    adapter.clear_dirty_on_sync();
+    adapter.set_dirty("tasks", vec![make_dirty_record("local-1", "tasks")]);
+    paged_server(&transport, 3, 2);
+
+    let manager = make_manager(transport.clone(), adapter.clone());
+    manager.resync().await;
+    let pulls = transport.pull_calls().len();
+    let pushes = transport.push_calls().len();
+    let applies = adapter.apply_calls().len();
+
+    let second = manager.resync().await;
+
+    assert!(second.errors.is_empty());
+    assert_eq!(second.pulled, 0);
+    assert_eq!(second.pushed, 0);
+    assert_eq!(transport.pull_calls().len(), pulls + 1);
+    assert_eq!(transport.pull_calls().last().unwrap().since, 3);
+    assert_eq!(transport.push_calls().len(), pushes);
+    assert_eq!(adapter.apply_calls().len(), applies);
+    assert_eq!(adapter.get_sequence("tasks"), 3);
+}
+
+#[tokio::test]
+async fn resync_stops_paging_on_transport_error() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    transport.on_pull(|_, _| Err(SyncTransportError::new("offline")));
+
+    let manager = make_manager(transport.clone(), adapter.clone());
+    let result = manager.resync().await;
+
+    assert_eq!(result.errors.len(), 1);
+    assert_eq!(result.errors[0].phase, SyncPhase::Pull);
+    assert_eq!(transport.pull_calls().len(), 1);
+    assert_eq!(adapter.get_sequence("tasks"), 0);
+}
+
 // ============================================================================
 // Callback Tests
 // ============================================================================
@@ -1369,6 +2075,7 @@ async fn on_remote_delete_called() {
             errors: Vec::new(),
             new_sequence: 50,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1397,6 +2104,77 @@ async fn on_remote_delete_called() {
     assert_eq!(events[0].previous_data, Some(json!({"name": "old"})));
 }
 
+#[tokio::test]
+async fn on_conflict_called_for_merge_conflicts() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    transport.on_pull(|_, _| {
+        Ok(PullResult {
+            records: vec![make_remote_record("r1", 50)],
+            latest_sequence: Some(50),
+            failures: Vec::new(),
+        })
+    });
+
+    adapter.on_apply(|_, records, _| {
+        let applied = records
+            .iter()
+            .map(|r| ApplyRemoteRecordResult {
+                id: r.id.clone(),
+                action: RemoteAction::Updated,
+                record: None,
+                previous_data: None,
+            })
+            .collect();
+        Ok(ApplyRemoteResult {
+            applied,
+            errors: Vec::new(),
+            new_sequence: 50,
+            merged_count: 1,
+            conflicts: vec![MergeConflict {
+                id: "r1".to_string(),
+                path: "name".to_string(),
+                local_value: json!("mine"),
+                remote_value: json!("theirs"),
+                winner: ConflictWinner::Remote,
+            }],
+        })
+    });
+
+    let conflict_events: Arc<Mutex<Vec<SyncConflictEvent>>> = Arc::new(Mutex::new(Vec::new()));
+    let ce = conflict_events.clone();
+
+    let manager = SyncManager::new(SyncManagerOptions {
+        transport: transport.clone(),
+        adapter: adapter.clone(),
+        collections: vec![def.clone()],
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: Some(Arc::new(move |e: &SyncConflictEvent| {
+            ce.lock().push(e.clone());
+        })),
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    });
+    let result = manager.pull(&def).await;
+
+    assert_eq!(result.merged, 1);
+    let events = conflict_events.lock();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].collection, "tasks");
+    assert_eq!(events[0].conflict.path, "name");
+    assert_eq!(events[0].conflict.winner, ConflictWinner::Remote);
+}
+
 // ============================================================================
 // Delete Strategy Tests
 // ============================================================================
@@ -1434,6 +2212,7 @@ async fn pull_passes_delete_strategy_to_apply() {
             errors: Vec::new(),
             new_sequence: 50,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1454,6 +2233,78 @@ async fn pull_passes_delete_strategy_to_apply() {
     );
 }
 
+#[tokio::test]
+async fn pull_passes_writer_delete_to_apply() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    transport.on_pull(|_, _| {
+        Ok(PullResult {
+            records: vec![make_remote_tombstone("r1", 50)],
+            latest_sequence: Some(50),
+            failures: Vec::new(),
+        })
+    });
+
+    let seen: Arc<Mutex<Option<DeleteKind>>> = Arc::new(Mutex::new(None));
+    let s = seen.clone();
+    adapter.on_apply(move |_, _, opts| {
+        *s.lock() = opts.writer_delete;
+        // Without membership nothing vouches for the tombstone's author
+        assert_eq!(opts.author_permission, None);
+        Ok(ApplyRemoteResult {
+            applied: Vec::new(),
+            errors: Vec::new(),
+            new_sequence: 50,
+            merged_count: 0,
+            conflicts: Vec::new(),
+        })
+    });
+
+    let manager = make_policy_manager(transport.clone(), adapter.clone(), UCANPermission::Admin);
+    manager.pull(&def).await;
+
+    assert_eq!(*seen.lock(), Some(DeleteKind::Archive));
+}
+
+#[tokio::test]
+async fn pull_derives_tombstone_author_from_membership() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let def = make_def("tasks");
+
+    transport.on_pull(|_, _| {
+        Ok(PullResult {
+            records: vec![make_remote_tombstone("r1", 50)],
+            latest_sequence: Some(50),
+            failures: Vec::new(),
+        })
+    });
+
+    let seen: Arc<Mutex<Option<UCANPermission>>> = Arc::new(Mutex::new(None));
+    let s = seen.clone();
+    adapter.on_apply(move |_, _, opts| {
+        *s.lock() = opts.author_permission;
+        Ok(ApplyRemoteResult {
+            applied: Vec::new(),
+            errors: Vec::new(),
+            new_sequence: 50,
+            merged_count: 0,
+            conflicts: Vec::new(),
+        })
+    });
+
+    // No one was ever delegated write, so only an admin can have deleted
+    let manager = SyncManager::new(SyncManagerOptions {
+        membership: Some(MembershipState::default()),
+        ..policy_options(transport.clone(), adapter.clone(), UCANPermission::Write)
+    });
+    manager.pull(&def).await;
+
+    assert_eq!(*seen.lock(), Some(UCANPermission::Admin));
+}
+
 // ============================================================================
 // Adapter Error Handling
 // ============================================================================
@@ -1575,6 +2426,7 @@ async fn quarantines_after_consecutive_permanent_failures() {
                     id: r.id.clone(),
                     collection: "tasks".into(),
                     error: "corrupt".into(),
+                    ..Default::default()
                 });
             } else {
                 applied.push(ApplyRemoteRecordResult {
@@ -1590,6 +2442,7 @@ async fn quarantines_after_consecutive_permanent_failures() {
             errors,
             new_sequence: 0,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1604,6 +2457,12 @@ async fn quarantines_after_consecutive_permanent_failures() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     let pull_count = Arc::new(AtomicUsize::new(0));
@@ -1647,6 +2506,7 @@ async fn retry_quarantined_clears_quarantine() {
                     id: r.id.clone(),
                     collection: "tasks".into(),
                     error: "corrupt".into(),
+                    ..Default::default()
                 });
             } else {
                 applied.push(ApplyRemoteRecordResult {
@@ -1662,6 +2522,7 @@ async fn retry_quarantined_clears_quarantine() {
             errors,
             new_sequence: 0,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1675,6 +2536,12 @@ async fn retry_quarantined_clears_quarantine() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     transport.on_pull(|_, _| {
@@ -1726,6 +2593,7 @@ async fn resets_failure_count_on_success() {
                     id: r.id.clone(),
                     collection: "tasks".into(),
                     error: "temp error".into(),
+                    ..Default::default()
                 });
             } else {
                 applied.push(ApplyRemoteRecordResult {
@@ -1741,6 +2609,7 @@ async fn resets_failure_count_on_success() {
             errors,
             new_sequence: 0,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -1754,6 +2623,12 @@ async fn resets_failure_count_on_success() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     transport.on_pull(|_, _| {
@@ -1811,6 +2686,12 @@ async fn does_not_track_transient_failures() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     // Pull many times
@@ -1854,6 +2735,12 @@ async fn pull_failures_with_retryable_false_count_toward_quarantine() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     // Pull twice to reach threshold for r1
@@ -1963,6 +2850,7 @@ async fn apply_remote_records_advances_cursor_even_with_errors() {
                 id: r.id.clone(),
                 collection: "tasks".into(),
                 error: "failed".into(),
+                ..Default::default()
             });
         }
         Ok(ApplyRemoteResult {
@@ -1970,6 +2858,7 @@ async fn apply_remote_records_advances_cursor_even_with_errors() {
             errors,
             new_sequence: 200,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -2071,6 +2960,12 @@ async fn get_collections_returns_all() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     let collections = manager.get_collections();
@@ -2080,6 +2975,90 @@ async fn get_collections_returns_all() {
     assert!(names.contains(&"notes".to_string()));
 }
 
+#[tokio::test]
+async fn purge_tombstones_only_visits_collections_with_retention() {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+    let mut schema = BTreeMap::new();
+    schema.insert("name".to_string(), t::string());
+    let notes = Arc::new(
+        collection("notes")
+            .v(1, schema)
+            .tombstone_retention(Duration::from_secs(3600))
+            .build(),
+    );
+
+    let manager = SyncManager::new(SyncManagerOptions {
+        transport: transport.clone(),
+        adapter: adapter.clone(),
+        collections: vec![make_def("tasks"), notes],
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    });
+
+    let report = manager.purge_tombstones().await;
+    assert_eq!(report, HashMap::from([("notes".to_string(), 2)]));
+    assert_eq!(adapter.inner.lock().purge_calls, vec!["notes".to_string()]);
+}
+
+#[tokio::test]
+async fn sync_all_follows_collection_priority() {
+    let transport = Arc::new(MockTransport::new());
+    let adapter = Arc::new(MockAdapter::new());
+
+    let manager = SyncManager::new(SyncManagerOptions {
+        transport: transport.clone(),
+        adapter: adapter.clone(),
+        collections: vec![
+            make_def("messages"),
+            make_def("notes"),
+            make_def("settings"),
+        ],
+        delete_strategy: None,
+        push_batch_size: None,
+        quarantine_threshold: None,
+        on_error: None,
+        on_progress: None,
+        on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: vec!["settings".to_string(), "unknown".to_string()],
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
+    });
+
+    let results = manager.sync_all().await;
+    assert_eq!(results.len(), 3);
+
+    let pulled: Vec<String> = transport
+        .pull_calls()
+        .into_iter()
+        .map(|c| c.collection)
+        .collect();
+    assert_eq!(pulled, vec!["settings", "messages", "notes"]);
+
+    let names: Vec<String> = manager
+        .get_collections()
+        .iter()
+        .map(|c| c.name.clone())
+        .collect();
+    assert_eq!(names, vec!["settings", "messages", "notes"]);
+}
+
 // ============================================================================
 // Merge Count Tests
 // ============================================================================
@@ -2113,6 +3092,7 @@ async fn pull_reports_merged_count() {
             errors: Vec::new(),
             new_sequence: 101,
             merged_count: 1, // 1 CRDT merge
+            conflicts: Vec::new(),
         })
     });
 
@@ -2139,6 +3119,7 @@ async fn push_reports_get_dirty_per_record_errors() {
             id: "r-bad".to_string(),
             collection: "tasks".to_string(),
             error: "corrupt record".to_string(),
+            ..Default::default()
         }],
     );
 
@@ -2256,6 +3237,7 @@ async fn apply_remote_records_quarantines_too() {
                     id: r.id.clone(),
                     collection: "tasks".into(),
                     error: "bad".into(),
+                    ..Default::default()
                 });
             } else {
                 applied.push(ApplyRemoteRecordResult {
@@ -2271,6 +3253,7 @@ async fn apply_remote_records_quarantines_too() {
             errors,
             new_sequence: 0,
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     });
 
@@ -2284,6 +3267,12 @@ async fn apply_remote_records_quarantines_too() {
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     });
 
     let records = vec![make_remote_record("r1", 100), make_remote_record("r2", 101)];
@@ -2345,3 +3334,37 @@ async fn push_outbound_includes_sequence() {
     let calls = transport.push_calls();
     assert_eq!(calls[0].records[0].sequence, 42);
 }
+
+// ============================================================================
+// Sequence gaps
+// ============================================================================
+
+#[test]
+fn contiguous_batch_has_no_gap() {
+    let records = vec![
+        make_remote_record("r2", 102),
+        make_remote_record("r1", 101),
+        make_remote_tombstone("r3", 103),
+    ];
+    assert_eq!(detect_sequence_gap(100, &records), None);
+    assert_eq!(detect_sequence_gap(100, &[]), None);
+    // Already-seen sequences are ignored
+    assert_eq!(
+        detect_sequence_gap(100, &[make_remote_record("r0", 90)]),
+        None
+    );
+}
+
+#[test]
+fn gapped_batch_returns_missing_range() {
+    let records = vec![
+        make_remote_record("r1", 101),
+        make_remote_record("r2", 105),
+        make_remote_record("r3", 108),
+    ];
+    assert_eq!(detect_sequence_gap(100, &records), Some((102, 104)));
+    assert_eq!(
+        detect_sequence_gap(100, &[make_remote_record("r2", 105)]),
+        Some((101, 104))
+    );
+}
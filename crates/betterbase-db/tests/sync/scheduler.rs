@@ -146,6 +146,7 @@ impl SyncAdapter for MockAdapter {
             errors: Vec::new(),
             new_sequence: records.iter().map(|r| r.sequence).max().unwrap_or(0),
             merged_count: 0,
+            conflicts: Vec::new(),
         })
     }
 
@@ -193,6 +194,12 @@ fn make_scheduler(
         on_error: None,
         on_progress: None,
         on_remote_delete: None,
+        on_conflict: None,
+        collection_priority: Vec::new(),
+        push_backoff: None,
+        delete_policy: None,
+        permission: None,
+        membership: None,
     }));
     SyncScheduler::new(manager, throttle_ms)
 }
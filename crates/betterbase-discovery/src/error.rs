@@ -14,6 +14,9 @@ pub enum DiscoveryError {
     #[error("Invalid discovery response: missing {field}")]
     MissingField { field: &'static str },
 
+    #[error("Invalid discovery response: sync_endpoints[{index}] {reason}")]
+    InvalidSyncEndpoint { index: usize, reason: &'static str },
+
     #[error("Invalid WebFinger response: expected object")]
     WebFingerNotAnObject,
 
@@ -26,6 +29,12 @@ pub enum DiscoveryError {
     #[error("WebFinger response has no sync endpoint link")]
     WebFingerNoSyncLink,
 
+    #[error("Invalid WebFinger response: expires is not an RFC 3339 timestamp: {0}")]
+    WebFingerInvalidExpires(String),
+
+    #[error("WebFinger sync link {href} is not on a host the server advertises")]
+    SyncHostMismatch { href: String },
+
     #[error("Invalid JSON: {0}")]
     InvalidJson(#[from] serde_json::Error),
 }
@@ -1,20 +1,27 @@
 //! Discovery types and validation for the Less platform.
 //!
 //! This crate provides types and validation for server metadata
-//! (`.well-known/betterbase`) and WebFinger (RFC 7033) responses.
+//! (`.well-known/betterbase`) and WebFinger (RFC 7033) responses, plus a
+//! [`TrustStore`] for caching that material with staleness windows.
 //!
 //! HTTP fetching is handled by the caller (e.g. browser `fetch`).
 //! This crate only validates and parses JSON responses.
 
 mod error;
 mod metadata;
+mod resolve;
+mod trust;
 mod types;
 mod webfinger;
 
 pub use error::DiscoveryError;
 pub use metadata::validate_server_metadata;
-pub use types::{ServerMetadata, UserResolution, WebFingerLink, WebFingerResponse};
-pub use webfinger::parse_webfinger_response;
+pub use resolve::resolve_user;
+pub use trust::{
+    CachedTrust, Freshness, StalenessPolicy, TrustArtifact, TrustLookup, TrustMaterial, TrustStore,
+};
+pub use types::{ServerMetadata, SyncEndpoint, UserResolution, WebFingerLink, WebFingerResponse};
+pub use webfinger::{parse_webfinger_jrd, parse_webfinger_response};
 
 /// Well-known rel type for Less sync endpoints in WebFinger responses.
 pub const SYNC_REL: &str = "https://betterbase.dev/ns/sync";
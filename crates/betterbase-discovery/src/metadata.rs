@@ -1,11 +1,12 @@
 use crate::error::DiscoveryError;
-use crate::types::ServerMetadata;
+use crate::types::{ServerMetadata, SyncEndpoint};
 use crate::SUPPORTED_VERSION;
 
 /// Validate and parse a JSON value as server metadata.
 ///
 /// The JSON should come from `GET {domain}/.well-known/betterbase`.
 /// This function validates the required fields and version number.
+/// Fields it does not know are ignored, so newer servers can add them.
 ///
 /// # Errors
 /// Returns `DiscoveryError` if the JSON is not a valid server metadata response.
@@ -64,6 +65,18 @@ pub fn validate_server_metadata(
         .get("pow_required")
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
+    let sync_endpoints = match obj.get("sync_endpoints") {
+        None | Some(serde_json::Value::Null) => Vec::new(),
+        Some(v) => v
+            .as_array()
+            .ok_or(DiscoveryError::MissingField {
+                field: "sync_endpoints",
+            })?
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| parse_sync_endpoint(index, entry))
+            .collect::<Result<_, _>>()?,
+    };
 
     Ok(ServerMetadata {
         version,
@@ -75,6 +88,37 @@ pub fn validate_server_metadata(
         webfinger,
         protocols,
         pow_required,
+        sync_endpoints,
+    })
+}
+
+fn parse_sync_endpoint(
+    index: usize,
+    entry: &serde_json::Value,
+) -> Result<SyncEndpoint, DiscoveryError> {
+    let invalid = |reason| DiscoveryError::InvalidSyncEndpoint { index, reason };
+    let obj = entry.as_object().ok_or(invalid("is not an object"))?;
+
+    let url = match obj.get("url").and_then(|v| v.as_str()) {
+        Some(s) if !s.is_empty() => s.to_string(),
+        _ => return Err(invalid("has no url")),
+    };
+    let priority = match obj.get("priority") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => Some(
+            v.as_u64()
+                .ok_or(invalid("priority is not a non-negative integer"))?,
+        ),
+    };
+    let region = obj
+        .get("region")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    Ok(SyncEndpoint {
+        url,
+        priority,
+        region,
     })
 }
 
@@ -242,6 +286,67 @@ mod tests {
         assert!(result.pow_required);
     }
 
+    #[test]
+    fn prefers_lowest_priority_sync_endpoint() {
+        let mut meta = reference_metadata();
+        meta["sync_endpoints"] = json!([
+            { "url": "https://any.example.com" },
+            { "url": "https://us.example.com", "priority": 20, "region": "us" },
+            // Unknown keys are ignored
+            { "url": "https://eu.example.com", "priority": 10, "region": "eu", "weight": 3 },
+            { "url": "https://eu2.example.com", "priority": 10, "region": "eu" }
+        ]);
+        let result = validate_server_metadata(&meta).unwrap();
+        assert_eq!(result.sync_endpoints.len(), 4);
+        assert_eq!(result.sync_endpoints[0].priority, None);
+
+        let preferred = result.preferred_sync_endpoint().unwrap();
+        assert_eq!(preferred.url, "https://eu.example.com");
+        assert_eq!(preferred.region.as_deref(), Some("eu"));
+    }
+
+    #[test]
+    fn empty_sync_endpoint_list_has_no_preference() {
+        let mut meta = reference_metadata();
+        meta["sync_endpoints"] = json!([]);
+        let result = validate_server_metadata(&meta).unwrap();
+        assert!(result.sync_endpoints.is_empty());
+        assert!(result.preferred_sync_endpoint().is_none());
+
+        let result = validate_server_metadata(&reference_metadata()).unwrap();
+        assert!(result.preferred_sync_endpoint().is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_sync_endpoints() {
+        for (endpoints, expected) in [
+            (json!("https://sync.example.com"), "missing sync_endpoints"),
+            (json!(["https://sync.example.com"]), "[0] is not an object"),
+            (json!([{ "priority": 1 }]), "[0] has no url"),
+            (
+                json!([
+                    { "url": "https://a.example.com" },
+                    { "url": "https://b.example.com", "priority": -1 }
+                ]),
+                "[1] priority is not a non-negative integer",
+            ),
+        ] {
+            let mut meta = reference_metadata();
+            meta["sync_endpoints"] = endpoints;
+            let err = validate_server_metadata(&meta).unwrap_err();
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+    }
+
+    #[test]
+    fn ignores_unknown_fields() {
+        let mut meta = reference_metadata();
+        meta["relay_endpoint"] = json!("https://relay.example.com");
+        meta["limits"] = json!({ "max_blob": 1024 });
+        let result = validate_server_metadata(&meta).unwrap();
+        assert_eq!(result.sync_endpoint, "https://sync.example.com/api/v1");
+    }
+
     #[test]
     fn serialization_round_trip() {
         let result = validate_server_metadata(&reference_metadata()).unwrap();
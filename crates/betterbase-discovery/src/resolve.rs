@@ -0,0 +1,156 @@
+use crate::error::DiscoveryError;
+use crate::types::{ServerMetadata, UserResolution, WebFingerResponse};
+use crate::SYNC_REL;
+
+/// Resolve a user from their parsed WebFinger JRD and their server's metadata.
+///
+/// Picks the first link whose `rel` is [`SYNC_REL`] and requires its host to
+/// match one the server advertises, either `sync_endpoint` or an entry of
+/// `sync_endpoints`. Hosts compare case-insensitively and ignore port and
+/// path.
+///
+/// # Errors
+/// Returns `DiscoveryError::WebFingerNoSyncLink` if the JRD has no sync link,
+/// or `DiscoveryError::SyncHostMismatch` if the server does not advertise
+/// its host.
+pub fn resolve_user(
+    webfinger: &WebFingerResponse,
+    metadata: &ServerMetadata,
+) -> Result<UserResolution, DiscoveryError> {
+    let sync_link = webfinger
+        .links
+        .iter()
+        .find(|link| link.rel == SYNC_REL)
+        .ok_or(DiscoveryError::WebFingerNoSyncLink)?;
+
+    let host = url_host(&sync_link.href);
+    let advertised = std::iter::once(metadata.sync_endpoint.as_str())
+        .chain(metadata.sync_endpoints.iter().map(|e| e.url.as_str()));
+    let matches = host.is_some_and(|host| {
+        advertised
+            .filter_map(url_host)
+            .any(|candidate| candidate.eq_ignore_ascii_case(host))
+    });
+    if !matches {
+        return Err(DiscoveryError::SyncHostMismatch {
+            href: sync_link.href.clone(),
+        });
+    }
+
+    Ok(UserResolution {
+        subject: webfinger.subject.clone(),
+        sync_endpoint: sync_link.href.clone(),
+        expires_at: webfinger.expires_at,
+    })
+}
+
+/// The host of an absolute URL, without userinfo or port. IPv6 literals keep
+/// their brackets.
+fn url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = if host_port.starts_with('[') {
+        &host_port[..=host_port.find(']')?]
+    } else {
+        host_port.split(':').next()?
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_webfinger_jrd, validate_server_metadata};
+    use serde_json::json;
+
+    fn metadata() -> ServerMetadata {
+        validate_server_metadata(&json!({
+            "version": 1,
+            "accounts_endpoint": "https://accounts.example.com",
+            "sync_endpoint": "https://sync.example.com/api/v1",
+            "sync_endpoints": [
+                { "url": "https://eu.sync.example.com/api/v1", "priority": 1 }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn webfinger(links: serde_json::Value) -> WebFingerResponse {
+        parse_webfinger_jrd(&json!({
+            "subject": "acct:alice@example.com",
+            "expires": "2026-01-02T03:04:05Z",
+            "links": links
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn resolves_sync_link_on_advertised_host() {
+        let jrd = webfinger(json!([
+            { "rel": "http://webfinger.net/rel/profile-page", "href": "https://example.com/alice" },
+            { "rel": "https://betterbase.dev/ns/sync", "href": "https://SYNC.example.com:443/api/v1" }
+        ]));
+        let resolution = resolve_user(&jrd, &metadata()).unwrap();
+        assert_eq!(
+            resolution,
+            UserResolution {
+                subject: "acct:alice@example.com".to_string(),
+                sync_endpoint: "https://SYNC.example.com:443/api/v1".to_string(),
+                expires_at: Some(1_767_323_045),
+            }
+        );
+    }
+
+    #[test]
+    fn accepts_host_from_sync_endpoints_list() {
+        let jrd = webfinger(json!([
+            { "rel": "https://betterbase.dev/ns/sync", "href": "https://eu.sync.example.com/api/v1" }
+        ]));
+        let resolution = resolve_user(&jrd, &metadata()).unwrap();
+        assert_eq!(
+            resolution.sync_endpoint,
+            "https://eu.sync.example.com/api/v1"
+        );
+    }
+
+    #[test]
+    fn rejects_missing_sync_link() {
+        let jrd = webfinger(json!([
+            { "rel": "http://webfinger.net/rel/profile-page", "href": "https://sync.example.com/alice" }
+        ]));
+        let err = resolve_user(&jrd, &metadata()).unwrap_err();
+        assert!(matches!(err, DiscoveryError::WebFingerNoSyncLink), "{err}");
+    }
+
+    #[test]
+    fn rejects_host_mismatch() {
+        for href in [
+            "https://sync.attacker.com/api/v1",
+            "https://sync.example.com@attacker.com/api/v1",
+            "not a url",
+        ] {
+            let jrd = webfinger(json!([{ "rel": "https://betterbase.dev/ns/sync", "href": href }]));
+            let err = resolve_user(&jrd, &metadata()).unwrap_err();
+            assert!(
+                matches!(&err, DiscoveryError::SyncHostMismatch { href: h } if h == href),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn url_host_strips_userinfo_port_and_path() {
+        assert_eq!(
+            url_host("https://sync.example.com"),
+            Some("sync.example.com")
+        );
+        assert_eq!(
+            url_host("https://user:pw@sync.example.com:8443/a?b#c"),
+            Some("sync.example.com")
+        );
+        assert_eq!(url_host("https://[::1]:8443/api"), Some("[::1]"));
+        assert_eq!(url_host("https:///api"), None);
+        assert_eq!(url_host("sync.example.com"), None);
+    }
+}
@@ -0,0 +1,450 @@
+//! Cached trust material with explicit staleness windows.
+//!
+//! Verification needs server metadata, JWKS, revocation sets and handle
+//! migrations that are normally fetched from the issuer's server. When that
+//! server is unreachable, callers fall back to the last copy they fetched.
+//! [`TrustStore`] records when each artifact was fetched and how long it may
+//! be relied on, so call sites can tell a fresh artifact from a stale or
+//! expired one instead of hard-failing offline or trusting a cache forever.
+//!
+//! Like the rest of this crate, no I/O happens here: callers fetch, validate
+//! and `put` material, persist the store with [`TrustStore::to_json`], and
+//! pass `now_seconds` explicitly.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DiscoveryError;
+use crate::types::ServerMetadata;
+
+// ============================================================================
+// Artifacts
+// ============================================================================
+
+/// Identifies one piece of cached trust material.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrustArtifact {
+    /// `.well-known/betterbase` metadata for a domain.
+    ServerMetadata { domain: String },
+    /// The JWK Set published at a domain's `jwks_uri`.
+    Jwks { domain: String },
+    /// Revoked UCANs for a space.
+    RevocationSet { space_id: String },
+    /// Where a handle has moved to, if anywhere.
+    HandleMigration { handle: String },
+}
+
+/// Cached trust material, tagged with the artifact it answers for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TrustMaterial {
+    ServerMetadata {
+        domain: String,
+        metadata: ServerMetadata,
+    },
+    Jwks {
+        domain: String,
+        /// JWK Set document (`{"keys": [...]}`).
+        keys: serde_json::Value,
+    },
+    RevocationSet {
+        space_id: String,
+        /// Revoked UCAN identifiers (base64url SHA-256 of the UCAN JWT).
+        revoked: BTreeSet<String>,
+    },
+    HandleMigration {
+        handle: String,
+        moved_to: String,
+    },
+}
+
+impl TrustMaterial {
+    /// The artifact this material is cached under.
+    pub fn artifact(&self) -> TrustArtifact {
+        match self {
+            Self::ServerMetadata { domain, .. } => TrustArtifact::ServerMetadata {
+                domain: domain.clone(),
+            },
+            Self::Jwks { domain, .. } => TrustArtifact::Jwks {
+                domain: domain.clone(),
+            },
+            Self::RevocationSet { space_id, .. } => TrustArtifact::RevocationSet {
+                space_id: space_id.clone(),
+            },
+            Self::HandleMigration { handle, .. } => TrustArtifact::HandleMigration {
+                handle: handle.clone(),
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Staleness policy
+// ============================================================================
+
+/// How long an artifact is fresh, and how much longer it may be used stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StalenessPolicy {
+    /// Age (seconds) up to which the artifact is [`Freshness::Fresh`].
+    pub fresh_for_seconds: u64,
+    /// Additional seconds past `fresh_for_seconds` during which the artifact
+    /// is [`Freshness::Stale`]. Beyond that it is [`Freshness::Expired`].
+    pub max_staleness_seconds: u64,
+}
+
+impl StalenessPolicy {
+    /// Default policy for an artifact kind.
+    ///
+    /// Revocation sets get the tightest window because acting on an outdated
+    /// one can readmit a removed member; handle migrations change rarely.
+    pub fn default_for(artifact: &TrustArtifact) -> Self {
+        const HOUR: u64 = 3600;
+        const DAY: u64 = 24 * HOUR;
+        match artifact {
+            TrustArtifact::ServerMetadata { .. } => Self {
+                fresh_for_seconds: HOUR,
+                max_staleness_seconds: 7 * DAY,
+            },
+            TrustArtifact::Jwks { .. } => Self {
+                fresh_for_seconds: HOUR,
+                max_staleness_seconds: DAY,
+            },
+            TrustArtifact::RevocationSet { .. } => Self {
+                fresh_for_seconds: 5 * 60,
+                max_staleness_seconds: HOUR,
+            },
+            TrustArtifact::HandleMigration { .. } => Self {
+                fresh_for_seconds: DAY,
+                max_staleness_seconds: 30 * DAY,
+            },
+        }
+    }
+
+    /// Classify an artifact of the given age.
+    pub fn classify(&self, age_seconds: u64) -> Freshness {
+        if age_seconds <= self.fresh_for_seconds {
+            Freshness::Fresh
+        } else if age_seconds - self.fresh_for_seconds <= self.max_staleness_seconds {
+            Freshness::Stale { age_seconds }
+        } else {
+            Freshness::Expired { age_seconds }
+        }
+    }
+}
+
+/// Result of checking an artifact's age against its policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Freshness {
+    /// Within the fresh window — use without qualification.
+    Fresh,
+    /// Past the fresh window but still usable; results should be annotated.
+    Stale { age_seconds: u64 },
+    /// Too old to rely on — verification must refuse.
+    Expired { age_seconds: u64 },
+}
+
+impl Freshness {
+    /// Whether material in this state may be used for verification.
+    pub fn is_usable(&self) -> bool {
+        !matches!(self, Self::Expired { .. })
+    }
+}
+
+// ============================================================================
+// TrustStore
+// ============================================================================
+
+/// One cached artifact with its fetch time and policy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedTrust {
+    pub material: TrustMaterial,
+    /// Seconds since UNIX epoch when the material was fetched.
+    pub fetched_at: u64,
+    pub policy: StalenessPolicy,
+}
+
+impl CachedTrust {
+    /// Freshness of this entry at `now_seconds`.
+    ///
+    /// A `fetched_at` in the future (clock skew) counts as age zero.
+    pub fn freshness(&self, now_seconds: u64) -> Freshness {
+        self.policy
+            .classify(now_seconds.saturating_sub(self.fetched_at))
+    }
+}
+
+/// A cached artifact looked up at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrustLookup<'a> {
+    pub material: &'a TrustMaterial,
+    pub fetched_at: u64,
+    pub freshness: Freshness,
+}
+
+/// Cache of trust material keyed by [`TrustArtifact`].
+///
+/// At most one entry is kept per artifact; `put` replaces the previous copy.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrustStore {
+    entries: Vec<CachedTrust>,
+}
+
+impl TrustStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache `material` fetched at `fetched_at` under the default policy for
+    /// its artifact kind.
+    pub fn put(&mut self, material: TrustMaterial, fetched_at: u64) {
+        let policy = StalenessPolicy::default_for(&material.artifact());
+        self.put_with_policy(material, fetched_at, policy);
+    }
+
+    /// Cache `material` with an explicit staleness policy.
+    pub fn put_with_policy(
+        &mut self,
+        material: TrustMaterial,
+        fetched_at: u64,
+        policy: StalenessPolicy,
+    ) {
+        let artifact = material.artifact();
+        let entry = CachedTrust {
+            material,
+            fetched_at,
+            policy,
+        };
+        match self
+            .entries
+            .iter_mut()
+            .find(|e| e.material.artifact() == artifact)
+        {
+            Some(existing) => *existing = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// Look up `artifact` and classify it at `now_seconds`.
+    ///
+    /// Returns `None` if nothing has been cached for the artifact.
+    pub fn get(&self, artifact: &TrustArtifact, now_seconds: u64) -> Option<TrustLookup<'_>> {
+        self.entry(artifact).map(|e| TrustLookup {
+            material: &e.material,
+            fetched_at: e.fetched_at,
+            freshness: e.freshness(now_seconds),
+        })
+    }
+
+    /// The raw cached entry for `artifact`, if any.
+    pub fn entry(&self, artifact: &TrustArtifact) -> Option<&CachedTrust> {
+        self.entries
+            .iter()
+            .find(|e| e.material.artifact() == *artifact)
+    }
+
+    /// Remove the entry for `artifact`. Returns `true` if one was present.
+    pub fn remove(&mut self, artifact: &TrustArtifact) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.material.artifact() != *artifact);
+        self.entries.len() != before
+    }
+
+    /// Drop every entry that is expired at `now_seconds`. Returns the count.
+    pub fn prune_expired(&mut self, now_seconds: u64) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| e.freshness(now_seconds).is_usable());
+        before - self.entries.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Serialize the whole store (material, fetch times and policies).
+    pub fn to_json(&self) -> Result<String, DiscoveryError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Restore a store previously produced by [`TrustStore::to_json`].
+    pub fn from_json(json: &str) -> Result<Self, DiscoveryError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: u64 = 1_700_000_000;
+
+    fn revocations(space_id: &str, revoked: &[&str]) -> TrustMaterial {
+        TrustMaterial::RevocationSet {
+            space_id: space_id.to_string(),
+            revoked: revoked.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn revocation_key(space_id: &str) -> TrustArtifact {
+        TrustArtifact::RevocationSet {
+            space_id: space_id.to_string(),
+        }
+    }
+
+    fn metadata() -> ServerMetadata {
+        ServerMetadata {
+            version: 1,
+            federation: false,
+            accounts_endpoint: "https://accounts.example.com".to_string(),
+            sync_endpoint: "https://sync.example.com/api/v1".to_string(),
+            federation_ws: String::new(),
+            jwks_uri: "https://accounts.example.com/.well-known/jwks.json".to_string(),
+            webfinger: "https://accounts.example.com/.well-known/webfinger".to_string(),
+            protocols: vec!["betterbase-rpc-v1".to_string()],
+            pow_required: false,
+            sync_endpoints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn freshness_transitions_with_clock() {
+        let policy = StalenessPolicy {
+            fresh_for_seconds: 60,
+            max_staleness_seconds: 120,
+        };
+        let mut store = TrustStore::new();
+        store.put_with_policy(revocations("space-1", &[]), T0, policy);
+        let key = revocation_key("space-1");
+
+        let at = |now| store.get(&key, now).unwrap().freshness;
+        assert_eq!(at(T0), Freshness::Fresh);
+        assert_eq!(at(T0 + 60), Freshness::Fresh);
+        assert_eq!(at(T0 + 61), Freshness::Stale { age_seconds: 61 });
+        assert_eq!(at(T0 + 180), Freshness::Stale { age_seconds: 180 });
+        assert_eq!(at(T0 + 181), Freshness::Expired { age_seconds: 181 });
+    }
+
+    #[test]
+    fn future_fetch_time_counts_as_fresh() {
+        let mut store = TrustStore::new();
+        store.put(revocations("space-1", &[]), T0 + 30);
+        let lookup = store.get(&revocation_key("space-1"), T0).unwrap();
+        assert_eq!(lookup.freshness, Freshness::Fresh);
+    }
+
+    #[test]
+    fn missing_artifact_returns_none() {
+        let store = TrustStore::new();
+        assert!(store.get(&revocation_key("space-1"), T0).is_none());
+    }
+
+    #[test]
+    fn put_replaces_existing_entry() {
+        let mut store = TrustStore::new();
+        store.put(revocations("space-1", &["a"]), T0);
+        store.put(revocations("space-1", &["a", "b"]), T0 + 10);
+        store.put(revocations("space-2", &[]), T0);
+
+        assert_eq!(store.len(), 2);
+        let lookup = store.get(&revocation_key("space-1"), T0 + 10).unwrap();
+        assert_eq!(lookup.fetched_at, T0 + 10);
+        assert_eq!(lookup.material, &revocations("space-1", &["a", "b"]));
+    }
+
+    #[test]
+    fn default_policies_differ_by_kind() {
+        let revocation = StalenessPolicy::default_for(&revocation_key("s"));
+        let metadata = StalenessPolicy::default_for(&TrustArtifact::ServerMetadata {
+            domain: "example.com".to_string(),
+        });
+        assert!(revocation.fresh_for_seconds < metadata.fresh_for_seconds);
+        assert!(revocation.max_staleness_seconds < metadata.max_staleness_seconds);
+    }
+
+    #[test]
+    fn prune_expired_drops_only_expired() {
+        let mut store = TrustStore::new();
+        store.put(revocations("space-1", &[]), T0);
+        store.put(
+            TrustMaterial::ServerMetadata {
+                domain: "example.com".to_string(),
+                metadata: metadata(),
+            },
+            T0,
+        );
+
+        // Two hours on: the revocation set (5m + 1h) is expired, metadata is stale.
+        let removed = store.prune_expired(T0 + 2 * 3600);
+        assert_eq!(removed, 1);
+        assert!(store.get(&revocation_key("space-1"), T0).is_none());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn remove_reports_presence() {
+        let mut store = TrustStore::new();
+        store.put(revocations("space-1", &[]), T0);
+        assert!(store.remove(&revocation_key("space-1")));
+        assert!(!store.remove(&revocation_key("space-1")));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn persistence_round_trip() {
+        let mut store = TrustStore::new();
+        store.put(revocations("space-1", &["ucan-a"]), T0);
+        store.put(
+            TrustMaterial::ServerMetadata {
+                domain: "example.com".to_string(),
+                metadata: metadata(),
+            },
+            T0 + 1,
+        );
+        store.put(
+            TrustMaterial::Jwks {
+                domain: "example.com".to_string(),
+                keys: serde_json::json!({ "keys": [] }),
+            },
+            T0 + 2,
+        );
+        store.put_with_policy(
+            TrustMaterial::HandleMigration {
+                handle: "alice@old.example".to_string(),
+                moved_to: "alice@new.example".to_string(),
+            },
+            T0 + 3,
+            StalenessPolicy {
+                fresh_for_seconds: 1,
+                max_staleness_seconds: 2,
+            },
+        );
+
+        let json = store.to_json().unwrap();
+        let restored = TrustStore::from_json(&json).unwrap();
+        assert_eq!(restored, store);
+
+        // Freshness is recomputed from the persisted fetch time and policy.
+        let key = TrustArtifact::HandleMigration {
+            handle: "alice@old.example".to_string(),
+        };
+        assert_eq!(
+            restored.get(&key, T0 + 10).unwrap().freshness,
+            Freshness::Expired { age_seconds: 7 }
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_garbage() {
+        assert!(matches!(
+            TrustStore::from_json("not json"),
+            Err(DiscoveryError::InvalidJson(_))
+        ));
+    }
+}
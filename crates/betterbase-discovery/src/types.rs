@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Response from `GET {domain}/.well-known/betterbase`.
@@ -12,6 +14,32 @@ pub struct ServerMetadata {
     pub webfinger: String,
     pub protocols: Vec<String>,
     pub pow_required: bool,
+    /// Alternative sync endpoints advertised by federated deployments, in
+    /// document order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sync_endpoints: Vec<SyncEndpoint>,
+}
+
+impl ServerMetadata {
+    /// The advertised endpoint with the lowest `priority` number. Entries
+    /// without a priority rank last; ties go to the earlier entry. `None`
+    /// when no list was advertised, in which case use `sync_endpoint`.
+    pub fn preferred_sync_endpoint(&self) -> Option<&SyncEndpoint> {
+        self.sync_endpoints
+            .iter()
+            .min_by_key(|e| e.priority.unwrap_or(u64::MAX))
+    }
+}
+
+/// One entry of `sync_endpoints` in server metadata.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncEndpoint {
+    pub url: String,
+    /// Lower is preferred.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
 }
 
 /// RFC 7033 WebFinger JRD response.
@@ -19,6 +47,10 @@ pub struct ServerMetadata {
 pub struct WebFingerResponse {
     pub subject: String,
     pub links: Vec<WebFingerLink>,
+    /// The JRD's `expires`, as Unix seconds. Cache the response no longer
+    /// than this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
 }
 
 /// A link in a WebFinger JRD response.
@@ -26,6 +58,12 @@ pub struct WebFingerResponse {
 pub struct WebFingerLink {
     pub rel: String,
     pub href: String,
+    /// Human-readable titles keyed by language tag (or `und`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub titles: BTreeMap<String, String>,
+    /// Link properties keyed by URI; a value may be `null`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, Option<String>>,
 }
 
 /// Parsed result from WebFinger resolution.
@@ -33,4 +71,7 @@ pub struct WebFingerLink {
 pub struct UserResolution {
     pub subject: String,
     pub sync_endpoint: String,
+    /// When the WebFinger response expires, as Unix seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
 }
@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
 use crate::error::DiscoveryError;
-use crate::types::UserResolution;
+use crate::types::{UserResolution, WebFingerLink, WebFingerResponse};
 use crate::SYNC_REL;
 
 /// Parse and validate a WebFinger JSON response, extracting the sync endpoint.
@@ -9,9 +13,31 @@ use crate::SYNC_REL;
 ///
 /// # Errors
 /// Returns `DiscoveryError` if the response is invalid or has no sync link.
-pub fn parse_webfinger_response(
-    json: &serde_json::Value,
-) -> Result<UserResolution, DiscoveryError> {
+pub fn parse_webfinger_response(json: &Value) -> Result<UserResolution, DiscoveryError> {
+    let jrd = parse_webfinger_jrd(json)?;
+    let sync_link = jrd
+        .links
+        .into_iter()
+        .find(|link| link.rel == SYNC_REL)
+        .ok_or(DiscoveryError::WebFingerNoSyncLink)?;
+
+    Ok(UserResolution {
+        subject: jrd.subject,
+        sync_endpoint: sync_link.href,
+        expires_at: jrd.expires_at,
+    })
+}
+
+/// Parse a WebFinger JRD (RFC 7033 section 4.4) without requiring a sync link.
+///
+/// Links without a string `rel` and `href` are dropped, as are `titles` and
+/// `properties` members of the wrong type. `expires` must be an RFC 3339
+/// timestamp if present.
+///
+/// # Errors
+/// Returns `DiscoveryError` if the response is not an object, lacks
+/// `subject` or `links`, or has a malformed `expires`.
+pub fn parse_webfinger_jrd(json: &Value) -> Result<WebFingerResponse, DiscoveryError> {
     let obj = json
         .as_object()
         .ok_or(DiscoveryError::WebFingerNotAnObject)?;
@@ -25,24 +51,56 @@ pub fn parse_webfinger_response(
     let links = obj
         .get("links")
         .and_then(|v| v.as_array())
-        .ok_or(DiscoveryError::WebFingerMissingLinks)?;
-
-    let sync_href = links
+        .ok_or(DiscoveryError::WebFingerMissingLinks)?
         .iter()
-        .find_map(|link| {
-            let link_obj = link.as_object()?;
-            let rel = link_obj.get("rel")?.as_str()?;
-            if rel == SYNC_REL {
-                link_obj.get("href")?.as_str().map(|s| s.to_string())
-            } else {
-                None
-            }
-        })
-        .ok_or(DiscoveryError::WebFingerNoSyncLink)?;
+        .filter_map(|link| parse_link(link.as_object()?))
+        .collect();
 
-    Ok(UserResolution {
+    let expires_at = match obj.get("expires") {
+        None => None,
+        Some(Value::String(s)) => Some(
+            chrono::DateTime::parse_from_rfc3339(s)
+                .map_err(|_| DiscoveryError::WebFingerInvalidExpires(s.clone()))?
+                .timestamp(),
+        ),
+        Some(other) => return Err(DiscoveryError::WebFingerInvalidExpires(other.to_string())),
+    };
+
+    Ok(WebFingerResponse {
         subject,
-        sync_endpoint: sync_href,
+        links,
+        expires_at,
+    })
+}
+
+fn parse_link(link: &Map<String, Value>) -> Option<WebFingerLink> {
+    let rel = link.get("rel")?.as_str()?.to_string();
+    let href = link.get("href")?.as_str()?.to_string();
+
+    let titles: BTreeMap<String, String> = link
+        .get("titles")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(lang, title)| Some((lang.clone(), title.as_str()?.to_string())))
+        .collect();
+    let properties: BTreeMap<String, Option<String>> = link
+        .get("properties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(uri, value)| match value {
+            Value::String(s) => Some((uri.clone(), Some(s.clone()))),
+            Value::Null => Some((uri.clone(), None)),
+            _ => None,
+        })
+        .collect();
+
+    Some(WebFingerLink {
+        rel,
+        href,
+        titles,
+        properties,
     })
 }
 
@@ -151,6 +209,65 @@ mod tests {
         assert_eq!(result.sync_endpoint, "https://sync.other.com/api/v1");
     }
 
+    #[test]
+    fn parses_expires_titles_and_properties() {
+        let data = json!({
+            "subject": "acct:alice@example.com",
+            "expires": "2026-01-02T03:04:05+01:00",
+            "links": [
+                {
+                    "rel": "https://betterbase.dev/ns/sync",
+                    "href": "https://sync.example.com/api/v1",
+                    "titles": { "en-us": "Sync", "und": 7 },
+                    "properties": {
+                        "https://betterbase.dev/ns/region": "eu",
+                        "https://betterbase.dev/ns/shard": null
+                    }
+                }
+            ]
+        });
+        let jrd = parse_webfinger_jrd(&data).unwrap();
+        assert_eq!(jrd.expires_at, Some(1_767_319_445));
+        let link = &jrd.links[0];
+        assert_eq!(link.titles.len(), 1);
+        assert_eq!(link.titles["en-us"], "Sync");
+        assert_eq!(
+            link.properties["https://betterbase.dev/ns/region"].as_deref(),
+            Some("eu")
+        );
+        assert_eq!(link.properties["https://betterbase.dev/ns/shard"], None);
+
+        let result = parse_webfinger_response(&data).unwrap();
+        assert_eq!(result.expires_at, Some(1_767_319_445));
+    }
+
+    #[test]
+    fn expires_is_optional() {
+        let jrd = parse_webfinger_jrd(&reference_webfinger()).unwrap();
+        assert_eq!(jrd.expires_at, None);
+        assert!(jrd.links[0].titles.is_empty());
+        assert!(jrd.links[0].properties.is_empty());
+        let result = parse_webfinger_response(&reference_webfinger()).unwrap();
+        assert_eq!(result.expires_at, None);
+    }
+
+    #[test]
+    fn rejects_malformed_expires() {
+        for expires in [
+            json!("tomorrow"),
+            json!("2026-13-01T00:00:00Z"),
+            json!(1767319445),
+        ] {
+            let mut data = reference_webfinger();
+            data["expires"] = expires;
+            let err = parse_webfinger_response(&data).unwrap_err();
+            assert!(
+                matches!(err, DiscoveryError::WebFingerInvalidExpires(_)),
+                "{err}"
+            );
+        }
+    }
+
     #[test]
     fn rejects_string_response() {
         let err = parse_webfinger_response(&json!("not an object")).unwrap_err();
@@ -0,0 +1,92 @@
+//! Per-thread heap accounting.
+//!
+//! Counters are thread-local so parallel tests in one binary don't see each
+//! other's allocations. Memory freed on a different thread than it was
+//! allocated on is clamped at zero rather than tracked.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+/// System allocator that records live and peak bytes for the current thread.
+///
+/// Register it in each fuzz test binary:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOC: betterbase_fuzz::alloc::CountingAlloc = betterbase_fuzz::alloc::CountingAlloc;
+/// ```
+pub struct CountingAlloc;
+
+fn record_alloc(size: usize) {
+    // `try_with` fails only during thread teardown; skip accounting then.
+    let _ = LIVE.try_with(|live| {
+        let now = live.get().saturating_add(size);
+        live.set(now);
+        let _ = PEAK.try_with(|peak| {
+            if now > peak.get() {
+                peak.set(now);
+            }
+        });
+    });
+}
+
+fn record_free(size: usize) {
+    let _ = LIVE.try_with(|live| live.set(live.get().saturating_sub(size)));
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record_free(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_free(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Run `f` and return its result with the peak number of heap bytes it had
+/// live at once on this thread, beyond what was live before the call.
+///
+/// Returns 0 for everything if [`CountingAlloc`] is not the global allocator.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let base = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(base));
+    let result = f();
+    let peak = PEAK.with(Cell::get);
+    (result, peak.saturating_sub(base))
+}
+
+/// Whether [`CountingAlloc`] is installed, i.e. whether [`measure`] means
+/// anything.
+pub fn is_counting() -> bool {
+    let (buf, peak) = measure(|| Vec::<u8>::with_capacity(64));
+    drop(buf);
+    peak >= 64
+}
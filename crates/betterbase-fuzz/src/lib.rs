@@ -0,0 +1,23 @@
+//! Bounded fuzzing of the parsers that see untrusted input.
+//!
+//! Each parser has a test binary under `tests/` that registers
+//! [`alloc::CountingAlloc`] as its global allocator and hands a [`Target`]
+//! to [`runner::replay_corpus`] and [`runner::fuzz`]. Inputs are derived
+//! deterministically from the checked-in corpus (`corpus/<target>/`), so a
+//! failure reproduces on every run.
+//!
+//! Corpus files end in `.accept` or `.reject`, the verdict the parser must
+//! reach. Valid samples are named `seed-*`; inputs that once crashed or
+//! misparsed are kept as `crash-*` / `regress-*`.
+//!
+//! `cargo test` runs [`DEFAULT_CASES`] mutations per seed. Set
+//! `BETTERBASE_FUZZ_CASES` for longer local runs.
+
+pub mod alloc;
+pub mod mutate;
+pub mod runner;
+
+pub use runner::{Target, Verdict};
+
+/// Mutations per corpus seed when `BETTERBASE_FUZZ_CASES` is unset.
+pub const DEFAULT_CASES: usize = 256;
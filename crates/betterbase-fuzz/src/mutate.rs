@@ -0,0 +1,291 @@
+//! Deterministic input mutation.
+//!
+//! Byte-level mutations work on any input. [`json`] mutates the parsed
+//! structure instead, so most of its outputs stay well-formed JSON and reach
+//! the parser's field handling rather than failing in the tokenizer.
+
+use serde_json::{Map, Number, Value};
+
+/// SplitMix64: tiny, seedable, and good enough to pick mutations.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Seed derived from a label (FNV-1a), so each target gets its own
+    /// stream without hand-picked constants.
+    pub fn from_label(label: &str) -> Self {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in label.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Self(hash)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must be non-zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn bytes(&mut self, n: usize) -> Vec<u8> {
+        (0..n).map(|_| self.next_u64() as u8).collect()
+    }
+}
+
+/// Bytes that tend to sit on boundaries in CBOR, JSON, base64 and varints.
+const INTERESTING_BYTES: &[u8] = &[
+    0x00, 0x01, 0x17, 0x18, 0x1f, 0x20, 0x22, 0x2c, 0x2e, 0x3a, 0x5b, 0x5c, 0x5d, 0x7b, 0x7d, 0x7f,
+    0x80, 0x9f, 0xbf, 0xf5, 0xf6, 0xff,
+];
+
+/// Integers that tend to break length and range checks.
+const INTERESTING_INTS: &[u64] = &[
+    0,
+    1,
+    0x7f,
+    0x80,
+    0xff,
+    0x100,
+    0xffff,
+    0x1_0000,
+    0x7fff_ffff,
+    0xffff_ffff,
+    0x1_0000_0000,
+    i64::MAX as u64,
+    u64::MAX,
+];
+
+/// Apply one to four stacked byte-level mutations. `donors` supplies splice
+/// material; growth stops at `max_len`.
+pub fn bytes(input: &[u8], donors: &[Vec<u8>], rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let mut out = input.to_vec();
+    for _ in 0..1 + rng.below(4) {
+        mutate_once(&mut out, donors, rng, max_len);
+    }
+    out.truncate(max_len);
+    out
+}
+
+fn mutate_once(out: &mut Vec<u8>, donors: &[Vec<u8>], rng: &mut Rng, max_len: usize) {
+    if out.is_empty() {
+        let n = 1 + rng.below(16);
+        out.extend(rng.bytes(n));
+        return;
+    }
+    let at = rng.below(out.len());
+    match rng.below(9) {
+        0 => out[at] ^= 1 << rng.below(8),
+        1 => out[at] = *rng.pick(INTERESTING_BYTES),
+        2 => {
+            let n = 1 + rng.below(8);
+            let fill = rng.bytes(n);
+            out.splice(at..at, fill);
+        }
+        3 => {
+            let end = (at + 1 + rng.below(16)).min(out.len());
+            out.drain(at..end);
+        }
+        4 => {
+            // Duplicate a span in place; on JSON this often repeats a field.
+            let end = (at + 1 + rng.below(64)).min(out.len());
+            let span = out[at..end].to_vec();
+            out.splice(end..end, span);
+        }
+        5 => {
+            let width = *rng.pick(&[1usize, 2, 4, 8]);
+            let value = rng.pick(INTERESTING_INTS).to_be_bytes();
+            let end = (at + width).min(out.len());
+            out.splice(at..end, value[8 - width..].iter().copied());
+        }
+        6 => {
+            if let Some(donor) = donors.get(rng.below(donors.len().max(1))) {
+                if !donor.is_empty() {
+                    let start = rng.below(donor.len());
+                    let end = (start + 1 + rng.below(64)).min(donor.len());
+                    out.splice(at..at, donor[start..end].iter().copied());
+                }
+            }
+        }
+        7 => out.truncate(at),
+        _ => {
+            // Pathological size: repeat a span up to the length cap.
+            let end = (at + 1 + rng.below(8)).min(out.len());
+            let span = out[at..end].to_vec();
+            let room = max_len.saturating_sub(out.len());
+            let copies = (room / span.len()).min(1 << rng.below(16));
+            let filler: Vec<u8> = span
+                .iter()
+                .copied()
+                .cycle()
+                .take(copies * span.len())
+                .collect();
+            out.splice(end..end, filler);
+        }
+    }
+}
+
+/// Mutate a JSON document structurally. Falls back to [`bytes`] when `input`
+/// is not valid JSON.
+pub fn json(input: &[u8], donors: &[Vec<u8>], rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let Ok(value) = serde_json::from_slice::<Value>(input) else {
+        return bytes(input, donors, rng, max_len);
+    };
+    let nodes = count_nodes(&value);
+    let target = rng.below(nodes);
+    let mut out = String::new();
+    let mut seen = 0;
+    render(&value, target, &mut seen, rng, &mut out);
+    let mut out = out.into_bytes();
+    out.truncate(max_len);
+    out
+}
+
+fn count_nodes(value: &Value) -> usize {
+    1 + match value {
+        Value::Array(items) => items.iter().map(count_nodes).sum(),
+        Value::Object(map) => map.values().map(count_nodes).sum(),
+        _ => 0,
+    }
+}
+
+/// Serialize `value`, mutating the `target`-th node (pre-order).
+fn render(value: &Value, target: usize, seen: &mut usize, rng: &mut Rng, out: &mut String) {
+    let index = *seen;
+    *seen += 1;
+    if index == target {
+        mutate_node(value, rng, out);
+        // Skip the subtree so node numbering stays in step with `count_nodes`.
+        *seen += count_nodes(value) - 1;
+        return;
+    }
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                render(item, target, seen, rng, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            out.push('{');
+            for (i, (key, item)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                render(item, target, seen, rng, out);
+            }
+            out.push('}');
+        }
+        leaf => out.push_str(&leaf.to_string()),
+    }
+}
+
+fn mutate_node(value: &Value, rng: &mut Rng, out: &mut String) {
+    match rng.below(6) {
+        0 => out.push_str(&replacement(rng).to_string()),
+        1 => {
+            // Deep nesting around the node.
+            let depth = 1 + rng.below(200);
+            out.push_str(&"[".repeat(depth));
+            out.push_str(&value.to_string());
+            out.push_str(&"]".repeat(depth));
+        }
+        2 => match value {
+            // Drop one member.
+            Value::Object(map) if !map.is_empty() => {
+                let skip = rng.below(map.len());
+                let kept: Map<String, Value> = map
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != skip)
+                    .map(|(_, (k, v))| (k.clone(), v.clone()))
+                    .collect();
+                out.push_str(&Value::Object(kept).to_string());
+            }
+            Value::Array(items) if !items.is_empty() => {
+                let mut items = items.clone();
+                items.remove(rng.below(items.len()));
+                out.push_str(&Value::Array(items).to_string());
+            }
+            other => out.push_str(&other.to_string()),
+        },
+        3 => match value {
+            // Repeat a member with a different value; serde_json keeps the last.
+            Value::Object(map) if !map.is_empty() => {
+                let body = Value::Object(map.clone()).to_string();
+                let (key, _) = map
+                    .iter()
+                    .nth(rng.below(map.len()))
+                    .expect("index in range");
+                out.push_str(&body[..body.len() - 1]);
+                out.push(',');
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                out.push_str(&replacement(rng).to_string());
+                out.push('}');
+            }
+            other => out.push_str(&Value::Array(vec![other.clone(), other.clone()]).to_string()),
+        },
+        4 => match value {
+            // Grow strings and arrays within the length cap.
+            Value::String(s) if !s.is_empty() => {
+                let times = 1 << rng.below(12);
+                out.push_str(&Value::from(s.repeat(times)).to_string());
+            }
+            Value::Array(items) if !items.is_empty() => {
+                let times = 1 << rng.below(10);
+                let grown: Vec<Value> = items
+                    .iter()
+                    .cycle()
+                    .take(items.len() * times)
+                    .cloned()
+                    .collect();
+                out.push_str(&Value::Array(grown).to_string());
+            }
+            other => out.push_str(&other.to_string()),
+        },
+        _ => {
+            // Byte-level damage inside an otherwise valid document.
+            let text = value.to_string().into_bytes();
+            let damaged = bytes(&text, &[], rng, text.len() + 64);
+            out.push_str(&String::from_utf8_lossy(&damaged));
+        }
+    }
+}
+
+fn replacement(rng: &mut Rng) -> Value {
+    match rng.below(10) {
+        0 => Value::Null,
+        1 => Value::Bool(rng.below(2) == 0),
+        2 => Value::from(-1),
+        3 => Value::from(*rng.pick(INTERESTING_INTS)),
+        4 => Number::from_f64(1e308)
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        5 => Value::from(""),
+        6 => Value::from("\u{0}\u{ffff}"),
+        7 => Value::Array(Vec::new()),
+        8 => Value::Object(Map::new()),
+        _ => Value::from("x".repeat(1 << rng.below(14))),
+    }
+}
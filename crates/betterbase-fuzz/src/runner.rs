@@ -0,0 +1,214 @@
+//! Corpus replay and the bounded mutation loop.
+
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use crate::alloc;
+use crate::mutate::{self, Rng};
+
+/// A parser's decision on one input. Rejections keep the error message so
+/// determinism checks cover it too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Accept,
+    Reject(String),
+}
+
+impl Verdict {
+    pub fn is_accept(&self) -> bool {
+        matches!(self, Self::Accept)
+    }
+}
+
+impl<T, E: Display> From<Result<T, E>> for Verdict {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(_) => Self::Accept,
+            Err(e) => Self::Reject(e.to_string()),
+        }
+    }
+}
+
+/// Structure-aware mutation: `(seed, donors, rng, max_len) -> input`.
+pub type Mutator = fn(&[u8], &[Vec<u8>], &mut Rng, usize) -> Vec<u8>;
+
+/// A parser under test.
+pub struct Target {
+    /// Corpus directory name under `corpus/`.
+    pub name: &'static str,
+    pub parse: fn(&[u8]) -> Verdict,
+    /// Longest input to generate.
+    pub max_len: usize,
+    /// Peak heap bytes the parser may hold for an input of the given length.
+    pub alloc_limit: fn(usize) -> usize,
+    /// Structure-aware mutation, used for half the cases alongside plain
+    /// byte-level mutation.
+    pub structural: Option<Mutator>,
+}
+
+/// Allocation budget that suits the JSON and CBOR parsers here: generous per
+/// input byte, but far below what an attacker-chosen length field would cost.
+pub fn default_alloc_limit(len: usize) -> usize {
+    256 * len + 256 * 1024
+}
+
+/// A corpus file and the verdict its name promises.
+pub struct CorpusEntry {
+    pub path: PathBuf,
+    pub data: Vec<u8>,
+    pub expect_accept: bool,
+}
+
+/// Load `corpus/<target>/`, sorted by file name.
+pub fn load_corpus(target: &str) -> Vec<CorpusEntry> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("corpus")
+        .join(target);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .unwrap_or_else(|e| panic!("read {}: {}", dir.display(), e))
+        .map(|entry| entry.expect("corpus dir entry").path())
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let expect_accept = match path.extension().and_then(|e| e.to_str()) {
+                Some("accept") => true,
+                Some("reject") => false,
+                _ => panic!("{}: expected .accept or .reject", path.display()),
+            };
+            let data =
+                std::fs::read(&path).unwrap_or_else(|e| panic!("read {}: {}", path.display(), e));
+            CorpusEntry {
+                path,
+                data,
+                expect_accept,
+            }
+        })
+        .collect()
+}
+
+/// Replay the corpus and require each file's promised verdict.
+pub fn replay_corpus(target: &Target) {
+    require_counting_alloc();
+    let corpus = load_corpus(target.name);
+    assert!(!corpus.is_empty(), "{}: corpus is empty", target.name);
+    for entry in &corpus {
+        let verdict = check(target, &entry.data);
+        assert_eq!(
+            verdict.is_accept(),
+            entry.expect_accept,
+            "{}: {:?}",
+            entry.path.display(),
+            verdict
+        );
+    }
+}
+
+/// Truncate every corpus file at every offset, then run the configured number
+/// of mutations per file.
+pub fn fuzz(target: &Target) {
+    require_counting_alloc();
+    let seeds: Vec<Vec<u8>> = load_corpus(target.name)
+        .into_iter()
+        .map(|entry| entry.data)
+        .collect();
+    let cases = cases_per_seed();
+    let mut rng = Rng::from_label(target.name);
+
+    for seed in &seeds {
+        for len in 0..seed.len() {
+            check(target, &seed[..len]);
+        }
+        for case in 0..cases {
+            let input = match target.structural {
+                Some(structural) if case % 2 == 0 => {
+                    structural(seed, &seeds, &mut rng, target.max_len)
+                }
+                _ => mutate::bytes(seed, &seeds, &mut rng, target.max_len),
+            };
+            check(target, &input);
+        }
+    }
+}
+
+/// Run the parser twice on `input`, enforcing no panic, the allocation
+/// limit, and an identical verdict both times.
+pub fn check(target: &Target, input: &[u8]) -> Verdict {
+    let limit = (target.alloc_limit)(input.len());
+    let first = run_once(target, input, limit);
+    let second = run_once(target, input, limit);
+    if first != second {
+        fail(
+            target,
+            input,
+            format!("nondeterministic verdict: {:?} then {:?}", first, second),
+        );
+    }
+    first
+}
+
+fn run_once(target: &Target, input: &[u8], limit: usize) -> Verdict {
+    let (result, peak) =
+        alloc::measure(|| panic::catch_unwind(AssertUnwindSafe(|| (target.parse)(input))));
+    let verdict = match result {
+        Ok(verdict) => verdict,
+        Err(payload) => fail(
+            target,
+            input,
+            format!("panicked: {}", panic_message(payload.as_ref())),
+        ),
+    };
+    if peak > limit {
+        fail(
+            target,
+            input,
+            format!("allocated {} bytes, limit {}", peak, limit),
+        );
+    }
+    verdict
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>")
+}
+
+fn fail(target: &Target, input: &[u8], reason: impl Display) -> ! {
+    const PREVIEW: usize = 4096;
+    panic!(
+        "{}: {}\ninput ({} bytes, hex): {}{}\n\
+         once fixed, add it as corpus/{}/crash-<name>.accept or .reject",
+        target.name,
+        reason,
+        input.len(),
+        hex(&input[..input.len().min(PREVIEW)]),
+        if input.len() > PREVIEW { "..." } else { "" },
+        target.name,
+    )
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn cases_per_seed() -> usize {
+    match std::env::var("BETTERBASE_FUZZ_CASES") {
+        Ok(v) => v
+            .parse()
+            .unwrap_or_else(|_| panic!("BETTERBASE_FUZZ_CASES: not a number: {:?}", v)),
+        Err(_) => crate::DEFAULT_CASES,
+    }
+}
+
+fn require_counting_alloc() {
+    assert!(
+        alloc::is_counting(),
+        "register betterbase_fuzz::alloc::CountingAlloc as #[global_allocator]"
+    );
+}
@@ -0,0 +1,58 @@
+//! Fuzz target: `decode_did_key_to_jwk`, issuer and audience DIDs in UCANs.
+
+use betterbase_crypto::{decode_did_key_to_jwk, encode_did_key_from_jwk};
+use betterbase_fuzz::alloc::CountingAlloc;
+use betterbase_fuzz::mutate::{self, Rng};
+use betterbase_fuzz::runner::{self, default_alloc_limit, Target};
+use betterbase_fuzz::Verdict;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+const PREFIX: &str = "did:key:z";
+
+/// Decode, and require that an accepted DID is the canonical spelling of
+/// its key. DIDs are compared as strings, so two spellings of one key would
+/// let a signer pass as someone else.
+fn parse(input: &[u8]) -> Verdict {
+    let did = String::from_utf8_lossy(input);
+    let jwk = match decode_did_key_to_jwk(&did) {
+        Ok(jwk) => jwk,
+        Err(e) => return Verdict::Reject(e.to_string()),
+    };
+    assert_eq!(encode_did_key_from_jwk(&jwk).expect("re-encode"), did);
+    Verdict::Accept
+}
+
+/// Mutate the base58-decoded payload rather than the base58 text.
+fn mutate_payload(seed: &[u8], _donors: &[Vec<u8>], rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let text = String::from_utf8_lossy(seed);
+    let payload = text
+        .strip_prefix(PREFIX)
+        .and_then(|encoded| bs58::decode(encoded).into_vec().ok());
+    let Some(payload) = payload else {
+        return mutate::bytes(seed, &[], rng, max_len);
+    };
+    let mutated = mutate::bytes(&payload, &[], rng, max_len);
+    let mut out = format!("{}{}", PREFIX, bs58::encode(&mutated).into_string()).into_bytes();
+    out.truncate(max_len);
+    out
+}
+
+const TARGET: Target = Target {
+    name: "did_key",
+    parse,
+    max_len: 256,
+    alloc_limit: default_alloc_limit,
+    structural: Some(mutate_payload),
+};
+
+#[test]
+fn corpus_replays() {
+    runner::replay_corpus(&TARGET);
+}
+
+#[test]
+fn mutations_hold_properties() {
+    runner::fuzz(&TARGET);
+}
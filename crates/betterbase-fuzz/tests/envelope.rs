@@ -0,0 +1,49 @@
+//! Fuzz target: `decode_envelope`, the CBOR inside every decrypted blob.
+
+use betterbase_fuzz::alloc::CountingAlloc;
+use betterbase_fuzz::runner::{self, default_alloc_limit, Target};
+use betterbase_fuzz::Verdict;
+use betterbase_sync_core::{decode_envelope, encode_envelope};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Decode, and require that anything accepted survives re-encoding.
+fn parse(input: &[u8]) -> Verdict {
+    let envelope = match decode_envelope(input) {
+        Ok(envelope) => envelope,
+        Err(e) => return Verdict::Reject(e.to_string()),
+    };
+    let again = decode_envelope(&encode_envelope(&envelope).expect("re-encode"))
+        .expect("re-encoded envelope decodes");
+    assert_eq!(
+        (&again.c, again.v, &again.crdt, &again.h, again.a),
+        (
+            &envelope.c,
+            envelope.v,
+            &envelope.crdt,
+            &envelope.h,
+            envelope.a
+        )
+    );
+    Verdict::Accept
+}
+
+const TARGET: Target = Target {
+    name: "envelope",
+    parse,
+    // Largest padding bucket: nothing bigger comes out of `unpad`.
+    max_len: 1 << 20,
+    alloc_limit: default_alloc_limit,
+    structural: None,
+};
+
+#[test]
+fn corpus_replays() {
+    runner::replay_corpus(&TARGET);
+}
+
+#[test]
+fn mutations_hold_properties() {
+    runner::fuzz(&TARGET);
+}
@@ -0,0 +1,77 @@
+//! Fuzz target: `decrypt_jwe`, the scoped-keys JWE from the OAuth callback.
+//!
+//! The corpus JWEs are encrypted to [`RECIPIENT_JWK`], so mutations that
+//! leave the key agreement intact reach the IV, tag and GCM handling.
+
+use std::sync::OnceLock;
+
+use betterbase_auth::decrypt_jwe;
+use betterbase_crypto::{base64url_decode, base64url_encode};
+use betterbase_fuzz::alloc::CountingAlloc;
+use betterbase_fuzz::mutate::{self, Rng};
+use betterbase_fuzz::runner::{self, default_alloc_limit, Target};
+use betterbase_fuzz::Verdict;
+use serde_json::Value;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+const RECIPIENT_JWK: &str = r#"{"crv":"P-256","d":"IgQwzQhvRuMeQyTsCsiaMMVMxQOqDRim55lgWEdANIo","kty":"EC","x":"Hw-enlmTS9vcz9gs40WFxui2Xuz25iaqCScPFaZcyeE","y":"pElgH2mgQz3K3NS3KlTJFheBjWKqqzZrMtSdgXDqg-w"}"#;
+
+fn recipient() -> &'static Value {
+    static JWK: OnceLock<Value> = OnceLock::new();
+    JWK.get_or_init(|| serde_json::from_str(RECIPIENT_JWK).expect("recipient JWK"))
+}
+
+fn parse(input: &[u8]) -> Verdict {
+    decrypt_jwe(&String::from_utf8_lossy(input), recipient()).into()
+}
+
+/// Add or drop a compact part, or mutate one part's decoded bytes (the
+/// header as JSON) and re-encode it.
+fn mutate_parts(seed: &[u8], _donors: &[Vec<u8>], rng: &mut Rng, max_len: usize) -> Vec<u8> {
+    let text = String::from_utf8_lossy(seed);
+    let mut parts: Vec<String> = text.split('.').map(str::to_string).collect();
+
+    if rng.below(4) == 0 {
+        if rng.below(2) == 0 {
+            parts.remove(rng.below(parts.len()));
+        } else {
+            let at = rng.below(parts.len() + 1);
+            parts.insert(at, String::new());
+        }
+    } else {
+        let i = rng.below(parts.len());
+        let mutated = match base64url_decode(&parts[i]) {
+            Ok(decoded) if i == 0 => mutate::json(&decoded, &[], rng, max_len),
+            Ok(decoded) => mutate::bytes(&decoded, &[], rng, max_len),
+            Err(_) => {
+                let n = rng.below(64);
+                rng.bytes(n)
+            }
+        };
+        parts[i] = base64url_encode(&mutated);
+    }
+
+    let mut out = parts.join(".").into_bytes();
+    out.truncate(max_len);
+    out
+}
+
+const TARGET: Target = Target {
+    name: "jwe",
+    parse,
+    max_len: 16 * 1024,
+    alloc_limit: default_alloc_limit,
+    structural: Some(mutate_parts),
+};
+
+#[test]
+fn corpus_replays() {
+    runner::replay_corpus(&TARGET);
+}
+
+#[test]
+fn mutations_hold_properties() {
+    runner::fuzz(&TARGET);
+}
@@ -0,0 +1,32 @@
+//! Fuzz target: `parse_membership_entry`, decrypted membership log payloads.
+
+use betterbase_fuzz::alloc::CountingAlloc;
+use betterbase_fuzz::mutate;
+use betterbase_fuzz::runner::{self, default_alloc_limit, Target};
+use betterbase_fuzz::Verdict;
+use betterbase_sync_core::parse_membership_entry;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn parse(input: &[u8]) -> Verdict {
+    parse_membership_entry(&String::from_utf8_lossy(input)).into()
+}
+
+const TARGET: Target = Target {
+    name: "membership",
+    parse,
+    max_len: 64 * 1024,
+    alloc_limit: default_alloc_limit,
+    structural: Some(mutate::json),
+};
+
+#[test]
+fn corpus_replays() {
+    runner::replay_corpus(&TARGET);
+}
+
+#[test]
+fn mutations_hold_properties() {
+    runner::fuzz(&TARGET);
+}
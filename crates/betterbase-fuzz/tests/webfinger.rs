@@ -0,0 +1,36 @@
+//! Fuzz target: `parse_webfinger_response` on raw response bodies.
+
+use betterbase_discovery::parse_webfinger_response;
+use betterbase_fuzz::alloc::CountingAlloc;
+use betterbase_fuzz::mutate;
+use betterbase_fuzz::runner::{self, default_alloc_limit, Target};
+use betterbase_fuzz::Verdict;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Mirrors the WASM binding: parse the body as JSON, then validate it.
+fn parse(input: &[u8]) -> Verdict {
+    match serde_json::from_slice(input) {
+        Ok(json) => parse_webfinger_response(&json).into(),
+        Err(e) => Verdict::Reject(e.to_string()),
+    }
+}
+
+const TARGET: Target = Target {
+    name: "webfinger",
+    parse,
+    max_len: 64 * 1024,
+    alloc_limit: default_alloc_limit,
+    structural: Some(mutate::json),
+};
+
+#[test]
+fn corpus_replays() {
+    runner::replay_corpus(&TARGET);
+}
+
+#[test]
+fn mutations_hold_properties() {
+    runner::fuzz(&TARGET);
+}
@@ -0,0 +1,209 @@
+//! Replay-checked presence and event frames.
+//!
+//! Channel frames are sealed under the space's channel key
+//! ([`derive_channel_key`](betterbase_crypto::derive_channel_key)) as
+//! `[0x04][IV:12][ciphertext+tag]`, the layout the WASM `encryptWithAad`
+//! writes. Sequenced frames use the v2 channel AAD, which binds the sender
+//! and its per-sender sequence, so a relay cannot replay an old presence
+//! update or membership notification past a [`ReplayGuard`], nor relabel it
+//! as coming from another sender.
+
+use betterbase_crypto::{
+    aes_gcm_decrypt, aes_gcm_encrypt, build_event_aad, build_event_aad_seq, build_presence_aad,
+    build_presence_aad_seq, CryptoError, CURRENT_VERSION,
+};
+
+use crate::error::SyncError;
+use crate::replay::ReplayGuard;
+
+/// Which channel a frame travels on. Each has its own AAD prefix, so a
+/// presence frame cannot be passed off as an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelKind {
+    Presence,
+    Event,
+}
+
+impl ChannelKind {
+    /// The v1 AAD without a sequence, or the v2 AAD with one.
+    fn aad(self, space_id: &str, sender: &str, sequence: Option<u64>) -> Vec<u8> {
+        match (self, sequence) {
+            (Self::Presence, None) => build_presence_aad(space_id),
+            (Self::Presence, Some(sequence)) => build_presence_aad_seq(space_id, sender, sequence),
+            (Self::Event, None) => build_event_aad(space_id),
+            (Self::Event, Some(sequence)) => build_event_aad_seq(space_id, sender, sequence),
+        }
+    }
+}
+
+/// Encrypt a channel payload from `sender` (its DID) bound to `sequence`,
+/// which must increase per sender. Receivers open it with
+/// [`decrypt_channel_guarded`].
+pub fn encrypt_channel_sequenced(
+    kind: ChannelKind,
+    channel_key: &[u8],
+    space_id: &str,
+    sender: &str,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Vec<u8>, SyncError> {
+    let aad = kind.aad(space_id, sender, Some(sequence));
+    let inner = aes_gcm_encrypt(channel_key, payload, &aad)?;
+    let mut frame = Vec::with_capacity(1 + inner.len());
+    frame.push(CURRENT_VERSION);
+    frame.extend_from_slice(&inner);
+    Ok(frame)
+}
+
+/// Decrypt a channel frame from `sender`, replay-checked against `guard`'s
+/// window for `space_id` and that sender. The sender is bound into the AAD,
+/// so a frame only opens under the sender that sealed it.
+///
+/// A `sequence` already seen or older than the window fails with
+/// [`SyncError::ReplayedFrame`] before any decryption, and is recorded only
+/// once the frame authenticates. A frame without a sequence (from a sender
+/// that predates sequencing) is opened under the v1 AAD if the guard
+/// allows unsequenced frames, and fails with [`SyncError::UnsequencedFrame`]
+/// otherwise.
+pub fn decrypt_channel_guarded(
+    kind: ChannelKind,
+    channel_key: &[u8],
+    space_id: &str,
+    sender: &str,
+    sequence: Option<u64>,
+    frame: &[u8],
+    guard: &mut ReplayGuard,
+) -> Result<Vec<u8>, SyncError> {
+    let Some(sequence) = sequence else {
+        if !guard.allows_unsequenced() {
+            return Err(SyncError::UnsequencedFrame);
+        }
+        return open_channel(kind, channel_key, space_id, sender, None, frame);
+    };
+    let window = guard.window(space_id, sender);
+    window.check(sequence)?;
+    let payload = open_channel(kind, channel_key, space_id, sender, Some(sequence), frame)?;
+    window.accept(sequence)?;
+    Ok(payload)
+}
+
+fn open_channel(
+    kind: ChannelKind,
+    channel_key: &[u8],
+    space_id: &str,
+    sender: &str,
+    sequence: Option<u64>,
+    frame: &[u8],
+) -> Result<Vec<u8>, SyncError> {
+    match frame.split_first() {
+        Some((&CURRENT_VERSION, inner)) => Ok(aes_gcm_decrypt(
+            channel_key,
+            inner,
+            &kind.aad(space_id, sender, sequence),
+        )?),
+        _ => Err(CryptoError::UnsupportedVersion(frame.first().copied().unwrap_or(0)).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    fn frame(kind: ChannelKind, sequence: u64) -> Vec<u8> {
+        encrypt_channel_sequenced(kind, &KEY, "space-1", "alice", sequence, b"online").unwrap()
+    }
+
+    fn open(
+        kind: ChannelKind,
+        sequence: Option<u64>,
+        frame: &[u8],
+        guard: &mut ReplayGuard,
+    ) -> Result<Vec<u8>, SyncError> {
+        open_as(kind, "alice", sequence, frame, guard)
+    }
+
+    fn open_as(
+        kind: ChannelKind,
+        sender: &str,
+        sequence: Option<u64>,
+        frame: &[u8],
+        guard: &mut ReplayGuard,
+    ) -> Result<Vec<u8>, SyncError> {
+        decrypt_channel_guarded(kind, &KEY, "space-1", sender, sequence, frame, guard)
+    }
+
+    #[test]
+    fn replayed_presence_frame_is_rejected() {
+        let mut guard = ReplayGuard::new(64);
+        let first = frame(ChannelKind::Presence, 1);
+        assert_eq!(
+            open(ChannelKind::Presence, Some(1), &first, &mut guard).unwrap(),
+            b"online"
+        );
+        assert!(matches!(
+            open(ChannelKind::Presence, Some(1), &first, &mut guard),
+            Err(SyncError::ReplayedFrame(1))
+        ));
+
+        // Modest reordering inside the window still opens
+        let third = frame(ChannelKind::Presence, 3);
+        let second = frame(ChannelKind::Presence, 2);
+        open(ChannelKind::Presence, Some(3), &third, &mut guard).unwrap();
+        open(ChannelKind::Presence, Some(2), &second, &mut guard).unwrap();
+    }
+
+    #[test]
+    fn event_frame_older_than_window_is_rejected() {
+        let mut guard = ReplayGuard::new(64);
+        let old = frame(ChannelKind::Event, 10);
+        let new = frame(ChannelKind::Event, 100);
+        open(ChannelKind::Event, Some(100), &new, &mut guard).unwrap();
+        assert!(matches!(
+            open(ChannelKind::Event, Some(10), &old, &mut guard),
+            Err(SyncError::ReplayedFrame(10))
+        ));
+    }
+
+    #[test]
+    fn sequence_is_authenticated() {
+        let mut guard = ReplayGuard::new(64);
+        let sealed = frame(ChannelKind::Event, 5);
+        // Relabelling the sequence fails to open and does not move the window
+        assert!(open(ChannelKind::Event, Some(6), &sealed, &mut guard).is_err());
+        assert_eq!(guard.highest("space-1", "alice"), None);
+        // Nor does the frame open on the other channel or as unsequenced
+        assert!(open(ChannelKind::Presence, Some(5), &sealed, &mut guard).is_err());
+        guard.set_allow_unsequenced(true);
+        assert!(open(ChannelKind::Event, None, &sealed, &mut guard).is_err());
+        open(ChannelKind::Event, Some(5), &sealed, &mut guard).unwrap();
+    }
+
+    #[test]
+    fn frame_does_not_open_under_another_sender() {
+        let mut guard = ReplayGuard::new(64);
+        let sealed = frame(ChannelKind::Presence, 2);
+        open(ChannelKind::Presence, Some(2), &sealed, &mut guard).unwrap();
+        // Bob's window has not seen 2, but the frame is bound to Alice
+        assert!(open_as(ChannelKind::Presence, "bob", Some(2), &sealed, &mut guard).is_err());
+        assert_eq!(guard.highest("space-1", "bob"), None);
+    }
+
+    #[test]
+    fn legacy_frames_open_only_when_allowed() {
+        let mut legacy = vec![CURRENT_VERSION];
+        legacy.extend(aes_gcm_encrypt(&KEY, b"online", &build_presence_aad("space-1")).unwrap());
+
+        let mut guard = ReplayGuard::new(64);
+        assert!(matches!(
+            open(ChannelKind::Presence, None, &legacy, &mut guard),
+            Err(SyncError::UnsequencedFrame)
+        ));
+        guard.set_allow_unsequenced(true);
+        assert_eq!(
+            open(ChannelKind::Presence, None, &legacy, &mut guard).unwrap(),
+            b"online"
+        );
+    }
+}
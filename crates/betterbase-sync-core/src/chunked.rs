@@ -0,0 +1,342 @@
+//! Chunked envelopes for large blobs.
+//!
+//! A large payload is split into fixed-size chunks. Each chunk is padded,
+//! encrypted under the same DEK with its own IV, and wrapped in a
+//! `BlobEnvelope` whose `p` field records the chunk index and total count.
+//!
+//! The AAD of each chunk also covers its index and the total, so chunks
+//! cannot be reordered, swapped between blobs, or dropped from the end
+//! without failing decryption.
+
+use std::collections::BTreeMap;
+
+use betterbase_crypto::EncryptionContext;
+
+use crate::error::SyncError;
+use crate::padding::{bucket_for, unpad, write_padded};
+use crate::types::{BlobEnvelope, ChunkPart};
+use crate::wire::WireVersion;
+
+/// Appended to the space id in the AAD context of a chunk.
+const CHUNK_SPACE_SUFFIX: &str = "\0chunk";
+
+/// Split `data` into `chunk_size` chunks and encrypt each into an envelope.
+///
+/// Every chunk, including a short last one, is padded to the bucket that
+/// fits a full chunk, so all chunks of a blob are the same size and the
+/// blob's length is not revealed. `chunk_size + 4` must fit in the largest
+/// padding bucket. Empty `data` yields a single empty chunk.
+///
+/// # Arguments
+/// * `collection` - Collection name stored in each envelope
+/// * `version` - Schema version stored in each envelope
+/// * `data` - Payload to split
+/// * `chunk_size` - Maximum plaintext bytes per chunk
+/// * `dek` - Data encryption key shared by all chunks
+/// * `context` - Record context for AAD binding
+/// * `padding_buckets` - Bucket sizes for padding (empty = no padding)
+pub fn encode_envelope_chunked(
+    collection: &str,
+    version: u64,
+    data: &[u8],
+    chunk_size: usize,
+    dek: &[u8],
+    context: &EncryptionContext,
+    padding_buckets: &[usize],
+) -> Result<Vec<BlobEnvelope>, SyncError> {
+    if chunk_size == 0 {
+        return Err(SyncError::InvalidEnvelope(
+            "chunk size must be positive".to_string(),
+        ));
+    }
+
+    let chunk_count = data.len().div_ceil(chunk_size).max(1);
+    let total = u32::try_from(chunk_count)
+        .map_err(|_| SyncError::InvalidEnvelope(format!("too many chunks: {}", chunk_count)))?;
+
+    // One bucket for every chunk, chosen from a full chunk
+    let bucket = if padding_buckets.is_empty() {
+        None
+    } else {
+        Some(bucket_for(chunk_size, padding_buckets)?)
+    };
+
+    let mut chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let part = ChunkPart {
+                i: index as u32,
+                n: total,
+            };
+            let padded = match bucket {
+                Some(bucket) => write_padded(chunk, bucket),
+                None => chunk.to_vec(),
+            };
+            let sealed = WireVersion::LATEST.seal(&padded, dek, &chunk_context(context, part))?;
+            Ok(BlobEnvelope {
+                c: collection.to_string(),
+                v: version,
+                crdt: sealed,
+                h: None,
+                a: false,
+                p: Some(part),
+                hs: None,
+            })
+        })
+        .collect()
+}
+
+/// Decrypt and reassemble envelopes produced by [`encode_envelope_chunked`].
+///
+/// Envelopes may arrive in any order. Fails with `MissingChunk`,
+/// `DuplicateChunk` or `ChunkCountMismatch` if the set is incomplete or
+/// inconsistent.
+pub fn decode_envelope_chunked(
+    envelopes: &[BlobEnvelope],
+    dek: &[u8],
+    context: &EncryptionContext,
+    padding_buckets: &[usize],
+) -> Result<Vec<u8>, SyncError> {
+    let first = envelopes
+        .first()
+        .ok_or_else(|| SyncError::InvalidEnvelope("no chunks".to_string()))?;
+    let total = chunk_part(first)?.n;
+
+    let mut by_index: BTreeMap<u32, &BlobEnvelope> = BTreeMap::new();
+    for envelope in envelopes {
+        let part = chunk_part(envelope)?;
+        if part.n != total {
+            return Err(SyncError::ChunkCountMismatch {
+                expected: total,
+                got: part.n,
+            });
+        }
+        if part.i >= total {
+            return Err(SyncError::InvalidEnvelope(format!(
+                "chunk index {} out of range for {} chunks",
+                part.i, total
+            )));
+        }
+        if by_index.insert(part.i, envelope).is_some() {
+            return Err(SyncError::DuplicateChunk { index: part.i });
+        }
+    }
+    // Indices are unique and below `total`, so a short map means a gap
+    if by_index.len() != total as usize {
+        let index = (0..total)
+            .find(|i| !by_index.contains_key(i))
+            .expect("gap exists when fewer than total chunks");
+        return Err(SyncError::MissingChunk { index, total });
+    }
+
+    let mut data = Vec::new();
+    for (index, envelope) in by_index {
+        let part = ChunkPart { i: index, n: total };
+        let decrypted = WireVersion::open(&envelope.crdt, dek, &chunk_context(context, part))?;
+        data.extend_from_slice(&unpad(&decrypted, padding_buckets)?);
+    }
+    Ok(data)
+}
+
+fn chunk_part(envelope: &BlobEnvelope) -> Result<ChunkPart, SyncError> {
+    envelope
+        .p
+        .ok_or_else(|| SyncError::InvalidEnvelope("envelope is not a chunk".to_string()))
+}
+
+/// Per-chunk AAD context.
+///
+/// The space id is suffixed with [`CHUNK_SPACE_SUFFIX`] and the record id
+/// extended with the chunk index and the total, each as 8 hex digits after
+/// a NUL. No record id chosen for a plain frame can then reproduce a
+/// chunk's AAD, as `"{id}#chunk:{i}/{n}"` could.
+fn chunk_context(context: &EncryptionContext, part: ChunkPart) -> EncryptionContext {
+    EncryptionContext {
+        space_id: format!("{}{CHUNK_SPACE_SUFFIX}", context.space_id),
+        record_id: format!("{}\0{:08x}\0{:08x}", context.record_id, part.i, part.n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::envelope::{decode_envelope, encode_envelope};
+    use crate::padding::DEFAULT_PADDING_BUCKETS;
+    use betterbase_crypto::generate_dek;
+
+    const CHUNK: usize = 1000;
+
+    fn context() -> EncryptionContext {
+        EncryptionContext {
+            space_id: "space-1".to_string(),
+            record_id: "rec-1".to_string(),
+        }
+    }
+
+    fn payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn encode(data: &[u8], dek: &[u8]) -> Vec<BlobEnvelope> {
+        encode_envelope_chunked(
+            "files",
+            1,
+            data,
+            CHUNK,
+            dek,
+            &context(),
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap()
+    }
+
+    fn decode(envelopes: &[BlobEnvelope], dek: &[u8]) -> Result<Vec<u8>, SyncError> {
+        decode_envelope_chunked(envelopes, dek, &context(), DEFAULT_PADDING_BUCKETS)
+    }
+
+    #[test]
+    fn round_trip_with_short_last_chunk() {
+        let dek = generate_dek().unwrap();
+        let data = payload(2 * CHUNK + 123);
+        let envelopes = encode(&data, &dek);
+
+        assert_eq!(envelopes.len(), 3);
+        for (i, envelope) in envelopes.iter().enumerate() {
+            assert_eq!(envelope.p, Some(ChunkPart { i: i as u32, n: 3 }));
+            assert_eq!(envelope.c, "files");
+        }
+        assert_eq!(decode(&envelopes, &dek).unwrap(), data);
+    }
+
+    #[test]
+    fn round_trip_exact_multiple_and_empty() {
+        let dek = generate_dek().unwrap();
+
+        let data = payload(2 * CHUNK);
+        let envelopes = encode(&data, &dek);
+        assert_eq!(envelopes.len(), 2);
+        assert_eq!(decode(&envelopes, &dek).unwrap(), data);
+
+        let envelopes = encode(&[], &dek);
+        assert_eq!(envelopes.len(), 1);
+        assert!(decode(&envelopes, &dek).unwrap().is_empty());
+    }
+
+    #[test]
+    fn out_of_order_delivery_reassembles() {
+        let dek = generate_dek().unwrap();
+        let data = payload(3 * CHUNK + 1);
+        let mut envelopes = encode(&data, &dek);
+        envelopes.reverse();
+        assert_eq!(decode(&envelopes, &dek).unwrap(), data);
+    }
+
+    #[test]
+    fn chunks_survive_cbor_round_trip() {
+        let dek = generate_dek().unwrap();
+        let data = payload(CHUNK + 5);
+        let envelopes: Vec<BlobEnvelope> = encode(&data, &dek)
+            .iter()
+            .map(|e| decode_envelope(&encode_envelope(e).unwrap()).unwrap())
+            .collect();
+        assert_eq!(decode(&envelopes, &dek).unwrap(), data);
+    }
+
+    #[test]
+    fn padded_chunks_hide_last_chunk_size() {
+        let dek = generate_dek().unwrap();
+        let envelopes = encode(&payload(CHUNK + 1), &dek);
+        assert_eq!(envelopes[0].crdt.len(), envelopes[1].crdt.len());
+        // A blob shorter than one chunk looks like a full one
+        let tiny = encode(&payload(1), &dek);
+        assert_eq!(tiny[0].crdt.len(), envelopes[0].crdt.len());
+        assert_eq!(decode(&tiny, &dek).unwrap(), payload(1));
+    }
+
+    #[test]
+    fn rejects_missing_chunk() {
+        let dek = generate_dek().unwrap();
+        let mut envelopes = encode(&payload(3 * CHUNK), &dek);
+        envelopes.remove(1);
+        assert!(matches!(
+            decode(&envelopes, &dek),
+            Err(SyncError::MissingChunk { index: 1, total: 3 })
+        ));
+    }
+
+    #[test]
+    fn rejects_duplicate_chunk() {
+        let dek = generate_dek().unwrap();
+        let mut envelopes = encode(&payload(2 * CHUNK), &dek);
+        envelopes.push(envelopes[0].clone());
+        assert!(matches!(
+            decode(&envelopes, &dek),
+            Err(SyncError::DuplicateChunk { index: 0 })
+        ));
+    }
+
+    #[test]
+    fn rejects_disagreeing_totals() {
+        let dek = generate_dek().unwrap();
+        let mut envelopes = encode(&payload(2 * CHUNK), &dek);
+        envelopes[1].p = Some(ChunkPart { i: 1, n: 5 });
+        assert!(matches!(
+            decode(&envelopes, &dek),
+            Err(SyncError::ChunkCountMismatch {
+                expected: 2,
+                got: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_relabelled_chunk() {
+        let dek = generate_dek().unwrap();
+        let mut envelopes = encode(&payload(2 * CHUNK), &dek);
+        envelopes[0].p = Some(ChunkPart { i: 1, n: 2 });
+        envelopes[1].p = Some(ChunkPart { i: 0, n: 2 });
+        assert!(decode(&envelopes, &dek).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_blob_relabelled_as_complete() {
+        let dek = generate_dek().unwrap();
+        let envelopes = encode(&payload(2 * CHUNK), &dek);
+        let mut first = envelopes[0].clone();
+        first.p = Some(ChunkPart { i: 0, n: 1 });
+        assert!(decode(&[first], &dek).is_err());
+    }
+
+    #[test]
+    fn chunk_does_not_open_as_a_plain_frame() {
+        let dek = generate_dek().unwrap();
+        let envelopes = encode(b"chunk body", &dek);
+        for record_id in ["rec-1#chunk:0/1", "rec-1"] {
+            let plain = EncryptionContext {
+                space_id: "space-1".to_string(),
+                record_id: record_id.to_string(),
+            };
+            assert!(WireVersion::open(&envelopes[0].crdt, &dek, &plain).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_zero_chunk_size_and_oversized_chunks() {
+        let dek = generate_dek().unwrap();
+        let encode_with = |chunk_size| {
+            encode_envelope_chunked("files", 1, b"data", chunk_size, &dek, &context(), &[256])
+        };
+        assert!(encode_with(0).is_err());
+        assert!(encode_with(2).is_ok());
+        assert!(
+            encode_envelope_chunked("files", 1, &payload(300), 300, &dek, &context(), &[256])
+                .is_err()
+        );
+    }
+}
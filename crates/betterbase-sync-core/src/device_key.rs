@@ -0,0 +1,76 @@
+//! Per-device sender keys for multi-writer spaces.
+//!
+//! deviceKey = HKDF-SHA256(epochKey, salt="betterbase:device-salt:v1", info="less:hkdf:v1\0device:{deviceDid}")
+//!
+//! Each writing device encrypts under its own subkey of the epoch key, so a
+//! compromised device key exposes only that device's writes, and ciphertexts
+//! from different devices never share a key the server could correlate.
+//! Readers hold the epoch key and derive the same subkey from the
+//! `device_did` the writer records in the envelope header. That header has to
+//! be readable before decryption, so the DID travels alongside the encrypted
+//! [`BlobEnvelope`](crate::types::BlobEnvelope), not inside it.
+
+use betterbase_crypto::derive_labeled;
+use zeroize::Zeroize;
+
+const DEVICE_SALT: &[u8] = b"betterbase:device-salt:v1";
+const DEVICE_LABEL_PREFIX: &str = "device:";
+
+/// Derive the sender key for `device_did` from an epoch key.
+///
+/// Deterministic: every member holding `epoch_key` derives the same key for
+/// the same DID, and distinct DIDs yield unrelated keys.
+pub fn derive_device_key(epoch_key: &[u8], device_did: &str) -> [u8; 32] {
+    let label = format!("{}{}", DEVICE_LABEL_PREFIX, device_did);
+    let mut okm = derive_labeled(epoch_key, DEVICE_SALT, &label, 32)
+        .expect("32 bytes is within the HKDF-SHA256 output limit");
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&okm);
+    okm.zeroize();
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use betterbase_crypto::derive_channel_key;
+
+    const ALICE: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+    const BOB: &str = "did:key:z6MkjchhfUsD6mmvni8mCdXHw216Xrm9bQe2mBH1P5RDjVJG";
+
+    #[test]
+    fn stable_for_same_inputs() {
+        let epoch_key = [0x42u8; 32];
+        assert_eq!(
+            derive_device_key(&epoch_key, ALICE),
+            derive_device_key(&epoch_key, ALICE)
+        );
+    }
+
+    #[test]
+    fn known_answer() {
+        let key = derive_device_key(&[0x42u8; 32], ALICE);
+        assert_eq!(
+            hex::encode(key),
+            "832365c8861f8c52b57173c18ad697628da395e2d7aa16642b98adf65d380c21"
+        );
+    }
+
+    #[test]
+    fn distinct_devices_get_distinct_keys() {
+        let epoch_key = [0x42u8; 32];
+        let alice = derive_device_key(&epoch_key, ALICE);
+        let bob = derive_device_key(&epoch_key, BOB);
+        assert_ne!(alice, bob);
+        assert_ne!(alice, epoch_key);
+        assert_ne!(alice, derive_channel_key(&epoch_key, ALICE).unwrap());
+    }
+
+    #[test]
+    fn distinct_epochs_get_distinct_keys() {
+        assert_ne!(
+            derive_device_key(&[0x42u8; 32], ALICE),
+            derive_device_key(&[0x43u8; 32], ALICE)
+        );
+    }
+}
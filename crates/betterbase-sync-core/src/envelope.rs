@@ -1,19 +1,104 @@
 //! BlobEnvelope CBOR encode/decode.
+//!
+//! Format: `[1 byte: envelope format version][CBOR map]`
+//! Envelopes written before the version byte existed start directly with
+//! the CBOR map header and decode as version 1.
+//!
+//! A sealed blob may carry a CRC32C footer over its ciphertext (see
+//! [`checksum_blob`]), checked before the AEAD open.
 
 use crate::error::SyncError;
 use crate::types::BlobEnvelope;
 
-/// Encode a BlobEnvelope as CBOR bytes.
+/// Version 1: `BlobEnvelope` as a CBOR map.
+const ENVELOPE_V1: u8 = 1;
+
+/// Leading byte of a checksummed blob. Wire versions are small integers,
+/// so it can't be mistaken for the version byte of a bare blob.
+pub const CHECKSUMMED_BLOB: u8 = 0xcc;
+
+/// Size of the CRC32C footer.
+const CHECKSUM_LEN: usize = 4;
+
+/// Envelope format version written by [`encode_envelope`].
+pub const ENVELOPE_VERSION: u8 = ENVELOPE_V1;
+
+/// Encode a BlobEnvelope as a version byte followed by CBOR.
 pub fn encode_envelope(envelope: &BlobEnvelope) -> Result<Vec<u8>, SyncError> {
-    let mut buf = Vec::new();
+    let mut buf = vec![ENVELOPE_V1];
     ciborium::into_writer(envelope, &mut buf)
         .map_err(|e| SyncError::CborEncode(format!("{}", e)))?;
     Ok(buf)
 }
 
-/// Decode CBOR bytes into a BlobEnvelope.
+/// Decode bytes from [`encode_envelope`] into a BlobEnvelope, dispatching
+/// on the leading version byte.
+///
+/// Fails with `UnsupportedEnvelopeVersion` for a version this build does
+/// not know.
 pub fn decode_envelope(data: &[u8]) -> Result<BlobEnvelope, SyncError> {
-    ciborium::from_reader(data).map_err(|e| SyncError::CborDecode(format!("{}", e)))
+    match data.first() {
+        None => Err(SyncError::CborDecode("empty envelope".to_string())),
+        Some(&ENVELOPE_V1) => decode_v1(&data[1..]),
+        // A CBOR map header: written before envelopes carried a version
+        Some(&(0xa0..=0xbf)) => decode_v1(data),
+        Some(&version) => Err(SyncError::UnsupportedEnvelopeVersion(version)),
+    }
+}
+
+fn decode_v1(cbor: &[u8]) -> Result<BlobEnvelope, SyncError> {
+    ciborium::from_reader(cbor).map_err(|e| SyncError::CborDecode(format!("{}", e)))
+}
+
+/// Frame a sealed blob as `[CHECKSUMMED_BLOB][blob][4 bytes: CRC32C, LE]`,
+/// the CRC covering the marker and the blob.
+///
+/// This lets a reader tell a blob damaged in transit or storage
+/// (`CorruptEnvelope`) from one that fails authentication
+/// (`DecryptionFailed`). It is a corruption check, not authentication.
+pub fn checksum_blob(blob: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(1 + blob.len() + CHECKSUM_LEN);
+    framed.push(CHECKSUMMED_BLOB);
+    framed.extend_from_slice(blob);
+    let crc = crc32c(&framed);
+    framed.extend_from_slice(&crc.to_le_bytes());
+    framed
+}
+
+/// Check and strip the footer from a [`checksum_blob`] frame, returning
+/// the sealed blob. A blob without the leading marker is returned as-is.
+pub fn verify_blob_checksum(data: &[u8]) -> Result<&[u8], SyncError> {
+    if data.first() != Some(&CHECKSUMMED_BLOB) {
+        return Ok(data);
+    }
+    let Some(split) = data.len().checked_sub(CHECKSUM_LEN).filter(|&n| n > 0) else {
+        return Err(SyncError::CorruptEnvelope(format!(
+            "{} bytes is too short for a checksum footer",
+            data.len()
+        )));
+    };
+    let (covered, footer) = data.split_at(split);
+    let expected = u32::from_le_bytes(footer.try_into().expect("footer is CHECKSUM_LEN bytes"));
+    let actual = crc32c(covered);
+    if expected != actual {
+        return Err(SyncError::CorruptEnvelope(format!(
+            "CRC32C mismatch: footer {expected:#010x}, computed {actual:#010x}"
+        )));
+    }
+    Ok(&covered[1..])
+}
+
+/// CRC-32C (Castagnoli), bitwise. Envelopes are small enough that a lookup
+/// table isn't worth it.
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
 }
 
 #[cfg(test)]
@@ -27,6 +112,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3, 4, 5],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
         let encoded = encode_envelope(&envelope).unwrap();
         let decoded = decode_envelope(&encoded).unwrap();
@@ -43,6 +131,9 @@ mod tests {
             v: 2,
             crdt: vec![10, 20, 30],
             h: Some(r#"[{"author":"did:key:z..."}]"#.to_string()),
+            a: false,
+            p: None,
+            hs: None,
         };
         let encoded = encode_envelope(&envelope).unwrap();
         let decoded = decode_envelope(&encoded).unwrap();
@@ -52,6 +143,26 @@ mod tests {
         assert_eq!(decoded.h.as_deref(), Some(r#"[{"author":"did:key:z..."}]"#));
     }
 
+    #[test]
+    fn archive_flag_round_trips_and_is_omitted_when_unset() {
+        let mut envelope = BlobEnvelope {
+            c: "notes".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        let plain = encode_envelope(&envelope).unwrap();
+        assert!(!decode_envelope(&plain).unwrap().a);
+
+        envelope.a = true;
+        let archived = encode_envelope(&envelope).unwrap();
+        assert!(archived.len() > plain.len());
+        assert!(decode_envelope(&archived).unwrap().a);
+    }
+
     #[test]
     fn empty_crdt() {
         let envelope = BlobEnvelope {
@@ -59,6 +170,9 @@ mod tests {
             v: 1,
             crdt: vec![],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
         let encoded = encode_envelope(&envelope).unwrap();
         let decoded = decode_envelope(&encoded).unwrap();
@@ -67,6 +181,108 @@ mod tests {
 
     #[test]
     fn rejects_invalid_cbor() {
-        assert!(decode_envelope(&[0xff, 0xff]).is_err());
+        assert!(decode_envelope(&[ENVELOPE_VERSION, 0xff, 0xff]).is_err());
+        assert!(decode_envelope(&[]).is_err());
+    }
+
+    #[test]
+    fn encode_writes_current_version_byte() {
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        let encoded = encode_envelope(&envelope).unwrap();
+        assert_eq!(encoded[0], ENVELOPE_VERSION);
+        assert_eq!(envelope.version(), ENVELOPE_VERSION);
+        assert_eq!(
+            decode_envelope(&encoded).unwrap().version(),
+            ENVELOPE_VERSION
+        );
+    }
+
+    #[test]
+    fn decodes_known_v1_envelope() {
+        // 0x01, then {"c": "t", "v": 1, "crdt": h'0102'}
+        let bytes = [
+            0x01, 0xa3, 0x61, b'c', 0x61, b't', 0x61, b'v', 0x01, 0x64, b'c', b'r', b'd', b't',
+            0x42, 0x01, 0x02,
+        ];
+        let decoded = decode_envelope(&bytes).unwrap();
+        assert_eq!(decoded.c, "t");
+        assert_eq!(decoded.v, 1);
+        assert_eq!(decoded.crdt, vec![1, 2]);
+
+        // The same map without a version byte predates versioning
+        let legacy = decode_envelope(&bytes[1..]).unwrap();
+        assert_eq!(legacy.c, "t");
+        assert_eq!(legacy.crdt, vec![1, 2]);
+    }
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+        assert_eq!(crc32c(b""), 0);
+    }
+
+    #[test]
+    fn checksummed_blob_round_trip() {
+        let blob = [4u8, 0xa0, 0xa1, 0xa2, 0xa3];
+        let framed = checksum_blob(&blob);
+        assert_eq!(framed[0], CHECKSUMMED_BLOB);
+        assert_eq!(framed.len(), blob.len() + 1 + CHECKSUM_LEN);
+        assert_eq!(verify_blob_checksum(&framed).unwrap(), blob);
+        // A bare blob passes through untouched
+        assert_eq!(verify_blob_checksum(&blob).unwrap(), blob);
+    }
+
+    #[test]
+    fn flipped_byte_is_corrupt() {
+        let framed = checksum_blob(&[4, 1, 2, 3, 4, 5, 6, 7]);
+        for i in 1..framed.len() {
+            let mut damaged = framed.clone();
+            damaged[i] ^= 0x01;
+            assert!(
+                matches!(
+                    verify_blob_checksum(&damaged),
+                    Err(SyncError::CorruptEnvelope(_))
+                ),
+                "flip at byte {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_checksummed_blob_is_corrupt() {
+        let framed = checksum_blob(&[4, 1, 2, 3, 4, 5, 6, 7]);
+        for len in [framed.len() - 1, 5, 4, 1] {
+            assert!(matches!(
+                verify_blob_checksum(&framed[..len]),
+                Err(SyncError::CorruptEnvelope(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        let mut encoded = encode_envelope(&envelope).unwrap();
+        encoded[0] = 99;
+        assert!(matches!(
+            decode_envelope(&encoded),
+            Err(SyncError::UnsupportedEnvelopeVersion(99))
+        ));
     }
 }
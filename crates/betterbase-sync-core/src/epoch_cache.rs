@@ -1,8 +1,12 @@
-//! Epoch key cache with forward derivation.
+//! Epoch key cache with forward derivation and rotation grace pins.
+//!
+//! [`EpochKeyCache`] holds one space's keys. [`EpochKeyCacheSet`] holds a
+//! cache per space and evicts the least recently used space once full.
 
 use crate::error::SyncError;
+use crate::membership::MembershipEntryPayload;
 use betterbase_crypto::derive_next_epoch_key;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use zeroize::Zeroize;
 
 /// Maximum number of epoch steps for forward derivation.
@@ -10,10 +14,65 @@ use zeroize::Zeroize;
 /// 1000 epochs at 30-day intervals covers ~82 years.
 const MAX_EPOCH_ADVANCE: u32 = 1000;
 
+/// Retention policy for epoch keys superseded by a rotation.
+///
+/// During a rotation, members re-encrypt and push at different times, so a
+/// space holds a mix of old-epoch and new-epoch envelopes for a while. The
+/// previous `retained_epochs` keys stay pinned for `grace_period_seconds`
+/// after the rotation is observed so those envelopes remain readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationGracePolicy {
+    /// Number of epochs before the new one to keep pinned.
+    pub retained_epochs: u32,
+    /// How long pins live after the rotation is observed.
+    pub grace_period_seconds: u64,
+}
+
+impl Default for RotationGracePolicy {
+    /// One previous epoch, pinned for 24 hours.
+    fn default() -> Self {
+        Self {
+            retained_epochs: 1,
+            grace_period_seconds: 24 * 60 * 60,
+        }
+    }
+}
+
+/// A superseded epoch key kept readable during the rotation grace period.
+struct PinnedKey {
+    key: Vec<u8>,
+    /// Expiry as seconds since UNIX epoch.
+    expires_at: u64,
+}
+
+/// Re-encryption progress for a space after a rotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationStatus {
+    /// Space ID.
+    pub space_id: String,
+    /// Current encryption epoch.
+    pub current_epoch: u32,
+    /// Epochs still pinned by the grace period, ascending.
+    pub pinned_epochs: Vec<u32>,
+    /// Number of records still wrapped at a pre-rotation epoch.
+    pub pending_records: usize,
+    /// Pending record counts keyed by wrap epoch.
+    pub pending_by_epoch: BTreeMap<u32, usize>,
+}
+
+impl RotationStatus {
+    /// True once no known record references a pre-rotation epoch,
+    /// so the pinned keys can be dropped without losing readability.
+    pub fn is_complete(&self) -> bool {
+        self.pending_records == 0
+    }
+}
+
 /// Cache for epoch-derived KEKs (Key Encryption Keys).
 ///
 /// Supports forward derivation from a base epoch key.
-/// The base key is never mutated; keys for any epoch >= base can be derived.
+/// Keys for any epoch >= base can be derived. Unbounded by default; see
+/// [`with_capacity`](Self::with_capacity) to cap the number of held keys.
 pub struct EpochKeyCache {
     /// Base KEK (the key at base_epoch).
     base_key: Vec<u8>,
@@ -25,6 +84,14 @@ pub struct EpochKeyCache {
     space_id: String,
     /// Derived key cache: epoch → KEK bytes.
     cache: HashMap<u32, Vec<u8>>,
+    /// Retention policy applied when a rotation is observed.
+    grace_policy: RotationGracePolicy,
+    /// Superseded keys pinned for the grace period: epoch → key.
+    pinned: BTreeMap<u32, PinnedKey>,
+    /// Maximum number of epoch keys held (base + derived); `None` is unbounded.
+    max_epochs: Option<usize>,
+    /// Highest epoch dropped by capacity eviction.
+    evicted_through: Option<u32>,
 }
 
 impl EpochKeyCache {
@@ -41,9 +108,37 @@ impl EpochKeyCache {
             current_epoch: base_epoch,
             space_id: space_id.to_string(),
             cache: HashMap::new(),
+            grace_policy: RotationGracePolicy::default(),
+            pinned: BTreeMap::new(),
+            max_epochs: None,
+            evicted_through: None,
         }
     }
 
+    /// Hold at most `max_epochs` epoch keys, evicting the lowest-numbered
+    /// ones first.
+    ///
+    /// Eviction rebases the cache onto the oldest retained key, so evicted
+    /// epochs fail with [`SyncError::EpochEvicted`] and must be re-derived
+    /// from the root. The current encryption epoch is never evicted.
+    /// Rotation grace pins are not counted.
+    pub fn with_capacity(mut self, max_epochs: usize) -> Self {
+        self.max_epochs = Some(max_epochs.max(1));
+        self.evict_to_capacity();
+        self
+    }
+
+    /// Set the retention policy applied to future rotations.
+    pub fn with_grace_policy(mut self, policy: RotationGracePolicy) -> Self {
+        self.grace_policy = policy;
+        self
+    }
+
+    /// Retention policy applied when a rotation is observed.
+    pub fn grace_policy(&self) -> RotationGracePolicy {
+        self.grace_policy
+    }
+
     /// Space ID.
     pub fn space_id(&self) -> &str {
         &self.space_id
@@ -63,24 +158,181 @@ impl EpochKeyCache {
     pub fn update_encryption_epoch(&mut self, epoch: u32) {
         if epoch > self.current_epoch {
             self.current_epoch = epoch;
+            self.evict_to_capacity();
+        }
+    }
+
+    /// Observe a rotation to `new_epoch` at `now_seconds` (seconds since UNIX epoch).
+    ///
+    /// Pins the keys for the previous `retained_epochs` epochs until the grace
+    /// period ends, then rebases the cache on `new_epoch` and drops every other
+    /// superseded key. Observing an epoch at or below the current one is a no-op.
+    pub fn observe_rotation(&mut self, new_epoch: u32, now_seconds: u64) -> Result<(), SyncError> {
+        if new_epoch <= self.current_epoch {
+            return Ok(());
+        }
+
+        // Derive the new base first so a failure leaves the cache untouched.
+        let new_base = self.get_kek(new_epoch)?.to_vec();
+
+        let expires_at = now_seconds.saturating_add(self.grace_policy.grace_period_seconds);
+        let oldest_pinned = new_epoch.saturating_sub(self.grace_policy.retained_epochs);
+        for epoch in oldest_pinned.max(self.base_epoch)..new_epoch {
+            let key = self.get_kek(epoch)?.to_vec();
+            if let Some(mut old) = self.pinned.insert(epoch, PinnedKey { key, expires_at }) {
+                old.key.zeroize();
+            }
+        }
+
+        self.base_key.zeroize();
+        self.base_key = new_base;
+        self.base_epoch = new_epoch;
+        self.current_epoch = new_epoch;
+        for (_, mut key) in self.cache.drain() {
+            key.zeroize();
+        }
+        Ok(())
+    }
+
+    /// Observe a membership log entry, treating an epoch newer than the
+    /// current one as a rotation.
+    pub fn observe_membership_entry(
+        &mut self,
+        entry: &MembershipEntryPayload,
+        now_seconds: u64,
+    ) -> Result<(), SyncError> {
+        match entry.epoch {
+            Some(epoch) => self.observe_rotation(epoch, now_seconds),
+            None => Ok(()),
+        }
+    }
+
+    /// Epochs currently pinned by a rotation grace period, ascending.
+    pub fn pinned_epochs(&self) -> Vec<u32> {
+        self.pinned.keys().copied().collect()
+    }
+
+    /// Drop pins whose grace period has ended. Returns the number dropped.
+    pub fn expire_pins(&mut self, now_seconds: u64) -> usize {
+        let expired: Vec<u32> = self
+            .pinned
+            .iter()
+            .filter(|(_, pin)| pin.expires_at <= now_seconds)
+            .map(|(epoch, _)| *epoch)
+            .collect();
+        for epoch in &expired {
+            if let Some(mut pin) = self.pinned.remove(epoch) {
+                pin.key.zeroize();
+            }
+        }
+        expired.len()
+    }
+
+    /// Drop all pins immediately, e.g. once [`RotationStatus::is_complete`].
+    pub fn clear_pins(&mut self) {
+        for (_, mut pin) in std::mem::take(&mut self.pinned) {
+            pin.key.zeroize();
+        }
+    }
+
+    /// Drop every key for an epoch below `epoch`, e.g. once a rotation has
+    /// completed and revoked epochs should no longer stay warm.
+    ///
+    /// Rebases the cache forward onto `epoch` (never past the current
+    /// encryption epoch) and drops pins below it. Dropped keys are zeroized,
+    /// and reads of those epochs then fail with [`SyncError::BackwardDerivation`].
+    /// Returns the number of epochs made unreadable.
+    pub fn remove_epochs_before(&mut self, epoch: u32) -> Result<usize, SyncError> {
+        let target = epoch.min(self.current_epoch);
+        let mut removed = 0;
+        if target > self.base_epoch {
+            // Derive the new base first so a failure leaves the cache untouched.
+            let new_base = self.get_kek(target)?.to_vec();
+            removed += (target - self.base_epoch) as usize;
+            self.base_key.zeroize();
+            self.base_key = new_base;
+            self.base_epoch = target;
+            self.cache.retain(|e, key| {
+                let keep = *e > target;
+                if !keep {
+                    key.zeroize();
+                }
+                keep
+            });
+        }
+
+        let kept = self.pinned.split_off(&epoch);
+        for (_, mut pin) in std::mem::replace(&mut self.pinned, kept) {
+            pin.key.zeroize();
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// Drop all derived keys and pins, keeping only the base key.
+    ///
+    /// Derived keys are re-derived on demand; pinned epochs become unreadable.
+    pub fn clear(&mut self) {
+        for (_, mut key) in self.cache.drain() {
+            key.zeroize();
+        }
+        self.clear_pins();
+    }
+
+    /// Report re-encryption progress for this space.
+    ///
+    /// `record_epochs` are the wrap epochs of locally-known records (e.g. from
+    /// [`peek_epoch`](crate::reencrypt::peek_epoch) over stored wrapped DEKs).
+    /// Records wrapped below the current epoch count as pending.
+    pub fn rotation_status(&self, record_epochs: impl IntoIterator<Item = u32>) -> RotationStatus {
+        let mut pending_by_epoch = BTreeMap::new();
+        for epoch in record_epochs {
+            if epoch < self.current_epoch {
+                *pending_by_epoch.entry(epoch).or_insert(0) += 1;
+            }
+        }
+        RotationStatus {
+            space_id: self.space_id.clone(),
+            current_epoch: self.current_epoch,
+            pinned_epochs: self.pinned_epochs(),
+            pending_records: pending_by_epoch.values().sum(),
+            pending_by_epoch,
         }
     }
 
+    /// Get the KEK for new outbound data.
+    ///
+    /// Always the current epoch: pinned keys are read-only and never used
+    /// to wrap new DEKs.
+    pub fn encryption_kek(&mut self) -> Result<(u32, &[u8]), SyncError> {
+        let epoch = self.current_epoch;
+        Ok((epoch, self.get_kek(epoch)?))
+    }
+
     /// Get the KEK for a given epoch via forward derivation from the base key.
     ///
-    /// Caches derived keys for efficiency.
+    /// Epochs below the base are served from rotation grace pins; anything
+    /// older fails with [`SyncError::EpochEvicted`] if capacity eviction
+    /// dropped it, or [`SyncError::BackwardDerivation`] otherwise. Caches derived
+    /// keys for efficiency.
     pub fn get_kek(&mut self, epoch: u32) -> Result<&[u8], SyncError> {
         // Fast path: exact match with base epoch
         if epoch == self.base_epoch {
             return Ok(&self.base_key);
         }
 
-        // Can't derive backward
+        // Can't derive backward; only grace pins cover older epochs
         if epoch < self.base_epoch {
-            return Err(SyncError::BackwardDerivation {
-                target: epoch,
-                base: self.base_epoch,
-            });
+            return match self.pinned.get(&epoch) {
+                Some(pin) => Ok(&pin.key),
+                None if self.evicted_through.is_some_and(|e| epoch <= e) => {
+                    Err(SyncError::EpochEvicted(epoch))
+                }
+                None => Err(SyncError::BackwardDerivation {
+                    target: epoch,
+                    base: self.base_epoch,
+                }),
+            };
         }
 
         let distance = epoch - self.base_epoch;
@@ -109,8 +361,168 @@ impl EpochKeyCache {
             }
         }
 
+        // A freshly derived epoch is the highest held, so it survives eviction
+        self.evict_to_capacity();
+        if epoch == self.base_epoch {
+            return Ok(&self.base_key);
+        }
         Ok(&self.cache[&epoch])
     }
+
+    /// Rebase onto the next cached key until at most `max_epochs` keys are
+    /// held, stopping at the current encryption epoch.
+    fn evict_to_capacity(&mut self) {
+        let Some(max_epochs) = self.max_epochs else {
+            return;
+        };
+        while self.cache.len() + 1 > max_epochs {
+            if self.evict_base().is_none() {
+                break;
+            }
+        }
+    }
+
+    /// Rebase onto the cached key for the next epoch, returning the old base
+    /// buffer with its bytes already zeroed. `None` if the base is the
+    /// current encryption epoch or the next key is not cached.
+    fn evict_base(&mut self) -> Option<Vec<u8>> {
+        if self.base_epoch >= self.current_epoch {
+            return None;
+        }
+        let next = self.base_epoch + 1;
+        let next_key = self.cache.remove(&next)?;
+        let mut old = std::mem::replace(&mut self.base_key, next_key);
+        old.as_mut_slice().zeroize();
+        self.evicted_through = Some(self.base_epoch);
+        self.base_epoch = next;
+        Some(old)
+    }
+
+    /// Zero every key buffer in place, leaving the cache unusable. Used on
+    /// caches leaving an [`EpochKeyCacheSet`] so the bytes are gone before
+    /// the allocations are freed.
+    fn scrub(&mut self) {
+        self.base_key.as_mut_slice().zeroize();
+        for key in self.cache.values_mut() {
+            key.as_mut_slice().zeroize();
+        }
+        for pin in self.pinned.values_mut() {
+            pin.key.as_mut_slice().zeroize();
+        }
+    }
+}
+
+/// Epoch key caches for many spaces, bounded by the number of spaces.
+///
+/// Every lookup through [`get_mut`](Self::get_mut) or
+/// [`remove_epochs_before`](Self::remove_epochs_before) marks the space as
+/// used. Once more than the capacity are held, the least recently used
+/// space is evicted. Evicted, replaced and removed caches have their keys
+/// zeroized.
+#[derive(Default)]
+pub struct EpochKeyCacheSet {
+    /// Space ID → cache and the tick it was last used at.
+    caches: HashMap<String, (EpochKeyCache, u64)>,
+    /// Maximum number of spaces held; `None` is unbounded.
+    max_spaces: Option<usize>,
+    /// Use counter; a higher tick is more recent.
+    clock: u64,
+}
+
+impl EpochKeyCacheSet {
+    /// An unbounded set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A set holding at most `max_spaces` caches (at least one).
+    pub fn with_capacity(max_spaces: usize) -> Self {
+        Self {
+            max_spaces: Some(max_spaces.max(1)),
+            ..Self::default()
+        }
+    }
+
+    /// Number of spaces held.
+    pub fn len(&self) -> usize {
+        self.caches.len()
+    }
+
+    /// Whether no space is held.
+    pub fn is_empty(&self) -> bool {
+        self.caches.is_empty()
+    }
+
+    /// Whether a cache for `space_id` is held. Does not count as a use.
+    pub fn contains_space(&self, space_id: &str) -> bool {
+        self.caches.contains_key(space_id)
+    }
+
+    /// Add `cache` under its space ID as the most recently used, replacing
+    /// any cache already held for that space, then evict down to capacity.
+    pub fn insert(&mut self, cache: EpochKeyCache) {
+        let tick = self.tick();
+        let space_id = cache.space_id().to_string();
+        if let Some((mut old, _)) = self.caches.insert(space_id, (cache, tick)) {
+            old.scrub();
+        }
+        while self.max_spaces.is_some_and(|max| self.caches.len() > max) {
+            self.evict_lru();
+        }
+    }
+
+    /// The cache for `space_id`, marked as most recently used.
+    pub fn get_mut(&mut self, space_id: &str) -> Option<&mut EpochKeyCache> {
+        let tick = self.tick();
+        let (cache, last_used) = self.caches.get_mut(space_id)?;
+        *last_used = tick;
+        Some(cache)
+    }
+
+    /// Drop the cache for `space_id`, e.g. after leaving the space.
+    /// Returns whether there was one.
+    pub fn remove_space(&mut self, space_id: &str) -> bool {
+        match self.caches.remove(space_id) {
+            Some((mut cache, _)) => {
+                cache.scrub();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// [`EpochKeyCache::remove_epochs_before`] for `space_id`. A space with
+    /// no cache has nothing to remove.
+    pub fn remove_epochs_before(&mut self, space_id: &str, epoch: u32) -> Result<usize, SyncError> {
+        match self.get_mut(space_id) {
+            Some(cache) => cache.remove_epochs_before(epoch),
+            None => Ok(0),
+        }
+    }
+
+    /// Drop every cache.
+    pub fn clear(&mut self) {
+        for (_, (mut cache, _)) in self.caches.drain() {
+            cache.scrub();
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Remove the least recently used cache, returning it already scrubbed.
+    fn evict_lru(&mut self) -> Option<EpochKeyCache> {
+        let space_id = self
+            .caches
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(space_id, _)| space_id.clone())?;
+        let (mut cache, _) = self.caches.remove(&space_id)?;
+        cache.scrub();
+        Some(cache)
+    }
 }
 
 impl Drop for EpochKeyCache {
@@ -119,12 +531,16 @@ impl Drop for EpochKeyCache {
         for (_, key) in self.cache.iter_mut() {
             key.zeroize();
         }
+        for (_, pin) in self.pinned.iter_mut() {
+            pin.key.zeroize();
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::membership::MembershipSigningVersion;
 
     fn random_key() -> [u8; 32] {
         let mut key = [0u8; 32];
@@ -163,8 +579,19 @@ mod tests {
     fn backward_derivation_fails() {
         let key = random_key();
         let mut cache = EpochKeyCache::new(&key, 5, "space-1");
-        assert!(cache.get_kek(4).is_err());
-        assert!(cache.get_kek(0).is_err());
+        assert!(matches!(
+            cache.get_kek(4),
+            Err(SyncError::BackwardDerivation { target: 4, base: 5 })
+        ));
+        assert!(matches!(
+            cache.get_kek(0),
+            Err(SyncError::BackwardDerivation { target: 0, base: 5 })
+        ));
+        // derive_forward reports the same condition the same way
+        assert!(matches!(
+            crate::reencrypt::derive_forward(&key, "space-1", 5, 4),
+            Err(SyncError::BackwardDerivation { target: 4, base: 5 })
+        ));
     }
 
     #[test]
@@ -186,6 +613,229 @@ mod tests {
         assert_eq!(cache.current_epoch(), 3);
     }
 
+    #[test]
+    fn rotation_pins_previous_epochs() {
+        let key = random_key();
+        let mut cache =
+            EpochKeyCache::new(&key, 0, "space-1").with_grace_policy(RotationGracePolicy {
+                retained_epochs: 2,
+                grace_period_seconds: 100,
+            });
+        let kek1 = cache.get_kek(1).unwrap().to_vec();
+        let kek2 = cache.get_kek(2).unwrap().to_vec();
+        let kek3 = cache.get_kek(3).unwrap().to_vec();
+
+        cache.observe_rotation(3, 1_000).unwrap();
+        assert_eq!(cache.base_epoch(), 3);
+        assert_eq!(cache.current_epoch(), 3);
+        assert_eq!(cache.pinned_epochs(), vec![1, 2]);
+        assert_eq!(cache.get_kek(1).unwrap(), kek1.as_slice());
+        assert_eq!(cache.get_kek(2).unwrap(), kek2.as_slice());
+        assert_eq!(cache.get_kek(3).unwrap(), kek3.as_slice());
+        // Outside the retained window
+        assert!(matches!(
+            cache.get_kek(0),
+            Err(SyncError::BackwardDerivation { target: 0, base: 3 })
+        ));
+    }
+
+    #[test]
+    fn stale_rotation_is_ignored() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 2, "space-1");
+        cache.observe_rotation(2, 1_000).unwrap();
+        cache.observe_rotation(1, 1_000).unwrap();
+        assert_eq!(cache.base_epoch(), 2);
+        assert!(cache.pinned_epochs().is_empty());
+    }
+
+    #[test]
+    fn pins_expire_after_grace_period() {
+        let key = random_key();
+        let mut cache =
+            EpochKeyCache::new(&key, 0, "space-1").with_grace_policy(RotationGracePolicy {
+                retained_epochs: 1,
+                grace_period_seconds: 60,
+            });
+        cache.observe_rotation(1, 1_000).unwrap();
+        assert!(cache.get_kek(0).is_ok());
+
+        assert_eq!(cache.expire_pins(1_059), 0);
+        assert!(cache.get_kek(0).is_ok());
+
+        assert_eq!(cache.expire_pins(1_060), 1);
+        assert!(cache.pinned_epochs().is_empty());
+        assert!(matches!(
+            cache.get_kek(0),
+            Err(SyncError::BackwardDerivation { .. })
+        ));
+    }
+
+    #[test]
+    fn rotation_from_membership_entry() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut entry = MembershipEntryPayload {
+            ucan: "ucan".to_string(),
+            entry_type: crate::membership::MembershipEntryType::Revoked,
+            signature: vec![],
+            signer_public_key: serde_json::json!({}),
+            epoch: None,
+            mailbox_id: None,
+            public_key_jwk: None,
+            signer_handle: None,
+            recipient_handle: None,
+            signing_version: MembershipSigningVersion::V1,
+        };
+        cache.observe_membership_entry(&entry, 1_000).unwrap();
+        assert_eq!(cache.current_epoch(), 0);
+
+        entry.epoch = Some(1);
+        cache.observe_membership_entry(&entry, 1_000).unwrap();
+        assert_eq!(cache.current_epoch(), 1);
+        assert_eq!(cache.pinned_epochs(), vec![0]);
+    }
+
+    #[test]
+    fn encryption_kek_ignores_pins() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.observe_rotation(1, 1_000).unwrap();
+        // Can't move the encryption epoch back onto a pinned epoch
+        cache.update_encryption_epoch(0);
+        let (epoch, _) = cache.encryption_kek().unwrap();
+        assert_eq!(epoch, 1);
+    }
+
+    #[test]
+    fn rotation_status_tracks_progress() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.observe_rotation(2, 1_000).unwrap();
+
+        let status = cache.rotation_status([0, 1, 1, 2]);
+        assert_eq!(status.space_id, "space-1");
+        assert_eq!(status.current_epoch, 2);
+        assert_eq!(status.pinned_epochs, vec![1]);
+        assert_eq!(status.pending_records, 3);
+        assert_eq!(status.pending_by_epoch.get(&1), Some(&2));
+        assert!(!status.is_complete());
+
+        let status = cache.rotation_status([1, 2, 2, 2]);
+        assert_eq!(status.pending_records, 1);
+
+        let status = cache.rotation_status([2, 2, 2, 2]);
+        assert_eq!(status.pending_records, 0);
+        assert!(status.is_complete());
+        cache.clear_pins();
+        assert!(cache.pinned_epochs().is_empty());
+    }
+
+    #[test]
+    fn capacity_evicts_lowest_epochs() {
+        let key = random_key();
+        let mut unbounded = EpochKeyCache::new(&key, 0, "space-1");
+        let kek5 = unbounded.get_kek(5).unwrap().to_vec();
+        let kek6 = unbounded.get_kek(6).unwrap().to_vec();
+
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1").with_capacity(3);
+        cache.update_encryption_epoch(6);
+        assert_eq!(cache.get_kek(6).unwrap(), kek6.as_slice());
+        assert_eq!(cache.base_epoch(), 4);
+        assert_eq!(cache.get_kek(5).unwrap(), kek5.as_slice());
+        assert!(cache.get_kek(4).is_ok());
+        for epoch in 0..4 {
+            assert!(matches!(
+                cache.get_kek(epoch),
+                Err(SyncError::EpochEvicted(e)) if e == epoch
+            ));
+        }
+    }
+
+    #[test]
+    fn capacity_keeps_current_epoch() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1").with_capacity(1);
+        // Looking ahead of the encryption epoch must not evict it
+        cache.get_kek(3).unwrap();
+        assert_eq!(cache.base_epoch(), 0);
+        assert_eq!(cache.encryption_kek().unwrap().1, &key);
+
+        cache.update_encryption_epoch(2);
+        assert_eq!(cache.base_epoch(), 2);
+        assert!(matches!(cache.get_kek(1), Err(SyncError::EpochEvicted(1))));
+        assert!(cache.get_kek(3).is_ok());
+    }
+
+    #[test]
+    fn rotation_drop_is_not_reported_as_eviction() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1")
+            .with_capacity(2)
+            .with_grace_policy(RotationGracePolicy {
+                retained_epochs: 0,
+                grace_period_seconds: 0,
+            });
+        cache.update_encryption_epoch(2);
+        cache.get_kek(2).unwrap();
+        cache.observe_rotation(5, 1_000).unwrap();
+        assert!(matches!(cache.get_kek(0), Err(SyncError::EpochEvicted(0))));
+        assert!(matches!(
+            cache.get_kek(3),
+            Err(SyncError::BackwardDerivation { target: 3, base: 5 })
+        ));
+    }
+
+    #[test]
+    fn remove_epochs_before_rebases_and_drops_pins() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.observe_rotation(2, 1_000).unwrap();
+        cache.update_encryption_epoch(5);
+        let kek4 = cache.get_kek(4).unwrap().to_vec();
+        let kek5 = cache.get_kek(5).unwrap().to_vec();
+
+        // Pinned 1, base 2 and 3
+        assert_eq!(cache.remove_epochs_before(4).unwrap(), 3);
+        assert_eq!(cache.base_epoch(), 4);
+        assert!(cache.pinned_epochs().is_empty());
+        assert!(matches!(
+            cache.get_kek(1),
+            Err(SyncError::BackwardDerivation { target: 1, base: 4 })
+        ));
+        assert!(matches!(
+            cache.get_kek(3),
+            Err(SyncError::BackwardDerivation { target: 3, base: 4 })
+        ));
+        assert_eq!(cache.get_kek(4).unwrap(), kek4.as_slice());
+        assert_eq!(cache.get_kek(5).unwrap(), kek5.as_slice());
+
+        assert_eq!(cache.remove_epochs_before(4).unwrap(), 0);
+    }
+
+    #[test]
+    fn remove_epochs_before_keeps_current_epoch() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.update_encryption_epoch(2);
+        cache.remove_epochs_before(10).unwrap();
+        assert_eq!(cache.base_epoch(), 2);
+        assert_eq!(cache.encryption_kek().unwrap().0, 2);
+    }
+
+    #[test]
+    fn clear_keeps_base_and_rederives() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.observe_rotation(1, 1_000).unwrap();
+        let kek3 = cache.get_kek(3).unwrap().to_vec();
+
+        cache.clear();
+        assert!(cache.pinned_epochs().is_empty());
+        assert!(cache.get_kek(0).is_err());
+        assert_eq!(cache.get_kek(3).unwrap(), kek3.as_slice());
+    }
+
     #[test]
     fn different_spaces_produce_different_keys() {
         let key = random_key();
@@ -195,4 +845,81 @@ mod tests {
         let kek2 = cache2.get_kek(1).unwrap().to_vec();
         assert_ne!(kek1, kek2);
     }
+
+    #[test]
+    fn evicted_base_buffer_is_zeroed() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.update_encryption_epoch(2);
+        let kek1 = cache.get_kek(1).unwrap().to_vec();
+
+        let evicted = cache.evict_base().unwrap();
+        assert_eq!(evicted.len(), key.len());
+        assert!(evicted.iter().all(|b| *b == 0));
+        assert_eq!(cache.base_epoch(), 1);
+        assert_eq!(cache.get_kek(1).unwrap(), kek1.as_slice());
+        assert!(matches!(cache.get_kek(0), Err(SyncError::EpochEvicted(0))));
+    }
+
+    #[test]
+    fn set_evicts_least_recently_used_space() {
+        let key = random_key();
+        let mut set = EpochKeyCacheSet::with_capacity(2);
+        set.insert(EpochKeyCache::new(&key, 0, "space-1"));
+        set.insert(EpochKeyCache::new(&key, 0, "space-2"));
+        // Using space-1 makes space-2 the oldest
+        set.get_mut("space-1").unwrap().get_kek(1).unwrap();
+
+        set.insert(EpochKeyCache::new(&key, 0, "space-3"));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains_space("space-1"));
+        assert!(!set.contains_space("space-2"));
+        assert!(set.contains_space("space-3"));
+    }
+
+    #[test]
+    fn set_scrubs_evicted_keys() {
+        let key = random_key();
+        let mut set = EpochKeyCacheSet::with_capacity(2);
+        let mut first = EpochKeyCache::new(&key, 0, "space-1");
+        first.observe_rotation(1, 1_000).unwrap();
+        first.get_kek(3).unwrap();
+        set.insert(first);
+        set.insert(EpochKeyCache::new(&key, 0, "space-2"));
+
+        let evicted = set.evict_lru().unwrap();
+        assert_eq!(evicted.space_id(), "space-1");
+        assert_eq!(evicted.base_key.len(), key.len());
+        assert!(evicted.base_key.iter().all(|b| *b == 0));
+        assert!(!evicted.cache.is_empty());
+        assert!(evicted.cache.values().flatten().all(|b| *b == 0));
+        assert_eq!(evicted.pinned_epochs(), vec![0]);
+        assert!(evicted
+            .pinned
+            .values()
+            .all(|pin| pin.key.iter().all(|b| *b == 0)));
+    }
+
+    #[test]
+    fn set_removes_spaces_and_epochs_per_space() {
+        let key = random_key();
+        let mut set = EpochKeyCacheSet::new();
+        for space in ["space-1", "space-2"] {
+            let mut cache = EpochKeyCache::new(&key, 0, space);
+            cache.update_encryption_epoch(3);
+            set.insert(cache);
+        }
+
+        assert_eq!(set.remove_epochs_before("space-1", 2).unwrap(), 2);
+        assert_eq!(set.get_mut("space-1").unwrap().base_epoch(), 2);
+        assert_eq!(set.get_mut("space-2").unwrap().base_epoch(), 0);
+        assert_eq!(set.remove_epochs_before("space-9", 2).unwrap(), 0);
+
+        assert!(set.remove_space("space-1"));
+        assert!(!set.remove_space("space-1"));
+        assert!(set.get_mut("space-1").is_none());
+
+        set.clear();
+        assert!(set.is_empty());
+    }
 }
@@ -1,3 +1,4 @@
+use betterbase_discovery::TrustArtifact;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,12 +15,40 @@ pub enum SyncError {
     #[error("Padding error: {0}")]
     PaddingError(String),
 
+    #[error("Payload of {size} bytes does not fit the largest padding bucket ({max} bytes)")]
+    PayloadTooLarge { size: usize, max: usize },
+
+    #[error("Chunk {index} of {total} is missing")]
+    MissingChunk { index: u32, total: u32 },
+
+    #[error("Chunk {index} appears more than once")]
+    DuplicateChunk { index: u32 },
+
+    #[error("Chunk totals disagree: expected {expected}, got {got}")]
+    ChunkCountMismatch { expected: u32, got: u32 },
+
     #[error("No KEK available for epoch {epoch} (record: {record_id})")]
     NoKek { epoch: u32, record_id: String },
 
     #[error("Cannot derive backward: epoch {target} < base epoch {base}")]
     BackwardDerivation { target: u32, base: u32 },
 
+    #[error("Epoch {0} was evicted from the key cache; re-derive it from the root key")]
+    EpochEvicted(u32),
+
+    #[error("Frame sequence {0} was already seen or is older than the replay window")]
+    ReplayedFrame(u64),
+
+    #[error("Frame has no sequence and unsequenced frames are not accepted")]
+    UnsequencedFrame,
+
+    #[error("Record {index} is wrapped at epoch {epoch}, below the accepted floor {floor}")]
+    EpochBelowFloor {
+        index: usize,
+        epoch: u32,
+        floor: u32,
+    },
+
     #[error("Epoch {target} too far ahead of base {base} (distance: {distance}, max: {max})")]
     EpochTooFarAhead {
         target: u32,
@@ -31,12 +60,51 @@ pub enum SyncError {
     #[error("Invalid epoch: new_epoch={new} must be > current_epoch={current}")]
     InvalidEpochAdvance { new: u32, current: u32 },
 
+    #[error("Invalid record id: {0}")]
+    InvalidRecordId(String),
+
     #[error("Missing wrapped DEK for encrypted record")]
     MissingDek,
 
     #[error("Invalid membership entry: {0}")]
     InvalidMembershipEntry(String),
 
+    #[error("Membership log entry {index} is invalid: {reason}")]
+    InvalidMembershipLogEntry { index: usize, reason: String },
+
+    #[error("Invalid space policy entry: {0}")]
+    InvalidPolicyEntry(String),
+
+    #[error("Corrupt envelope: {0}")]
+    CorruptEnvelope(String),
+
+    #[error("Unsupported envelope format version {0}")]
+    UnsupportedEnvelopeVersion(u8),
+
+    #[error("Unsupported wire version {version}: {detail}")]
+    UnsupportedWireVersion { version: u8, detail: String },
+
+    #[error("Wire version {version} is below the space minimum {minimum}")]
+    WireVersionBelowMinimum { version: u8, minimum: u8 },
+
+    #[error("No usable wire version: {0}")]
+    NoWireVersion(String),
+
+    #[error("Invalid handshake: {0}")]
+    InvalidHandshake(String),
+
+    #[error("Handshake has no common {0}")]
+    NoCommonHandshake(&'static str),
+
+    #[error("No cached trust material for {artifact:?}")]
+    TrustMaterialMissing { artifact: TrustArtifact },
+
+    #[error("Cached trust material for {artifact:?} expired ({age_seconds}s old)")]
+    TrustMaterialExpired {
+        artifact: TrustArtifact,
+        age_seconds: u64,
+    },
+
     #[error("Crypto error: {0}")]
     Crypto(#[from] betterbase_crypto::CryptoError),
 
@@ -0,0 +1,217 @@
+//! Handshake exchanged before encrypted frames: which wire versions and AEAD
+//! suites each side supports, and the epoch it is on.
+//!
+//! Layout (version 1):
+//! `[layout: 0x01][n: u8][n version bytes][m: u8][m suite ids][epoch: u32 BE]`
+//!
+//! A suite id is the version byte the suite writes
+//! ([`CipherSuite::version`]). Versions and suites this build does not know
+//! are dropped on decode, since they can never be selected.
+
+use betterbase_crypto::CipherSuite;
+
+use crate::error::SyncError;
+use crate::wire::WireVersion;
+
+/// Handshake layout written by [`encode_handshake`].
+pub const HANDSHAKE_VERSION: u8 = 1;
+
+/// What one side offers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakeParams {
+    /// Wire versions the sender reads and writes.
+    pub versions: Vec<WireVersion>,
+    /// AEAD suites the sender supports.
+    pub algorithms: Vec<CipherSuite>,
+    /// The sender's current space epoch.
+    pub current_epoch: u32,
+}
+
+/// What both sides settled on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedParams {
+    pub version: WireVersion,
+    pub algorithm: CipherSuite,
+    /// The newer of the two epochs; the side behind derives forward to it.
+    pub epoch: u32,
+}
+
+/// Encode `params` in the current handshake layout.
+///
+/// # Panics
+/// Panics if either list has more than 255 entries.
+pub fn encode_handshake(params: &HandshakeParams) -> Vec<u8> {
+    let mut out = Vec::with_capacity(7 + params.versions.len() + params.algorithms.len());
+    out.push(HANDSHAKE_VERSION);
+    out.push(list_len(params.versions.len()));
+    out.extend(params.versions.iter().map(|v| v.byte()));
+    out.push(list_len(params.algorithms.len()));
+    out.extend(params.algorithms.iter().map(|a| a.version()));
+    out.extend_from_slice(&params.current_epoch.to_be_bytes());
+    out
+}
+
+fn list_len(len: usize) -> u8 {
+    u8::try_from(len).expect("handshake lists hold at most 255 entries")
+}
+
+/// Decode a handshake. Fails on an unknown layout, truncation or trailing
+/// bytes.
+pub fn decode_handshake(bytes: &[u8]) -> Result<HandshakeParams, SyncError> {
+    let (&layout, rest) = bytes
+        .split_first()
+        .ok_or_else(|| invalid("empty handshake"))?;
+    if layout != HANDSHAKE_VERSION {
+        return Err(invalid(format!("unsupported handshake layout {layout}")));
+    }
+
+    let (versions, rest) = take_list(rest, "versions")?;
+    let (algorithms, rest) = take_list(rest, "algorithms")?;
+    let epoch: [u8; 4] = rest
+        .try_into()
+        .map_err(|_| invalid(format!("expected 4 epoch bytes, got {}", rest.len())))?;
+
+    Ok(HandshakeParams {
+        versions: versions
+            .iter()
+            .filter_map(|&b| WireVersion::from_byte(b).ok())
+            .collect(),
+        algorithms: algorithms
+            .iter()
+            .filter_map(|&id| CipherSuite::from_version(id).ok())
+            .collect(),
+        current_epoch: u32::from_be_bytes(epoch),
+    })
+}
+
+/// Split a length-prefixed list off the front of `bytes`.
+fn take_list<'a>(bytes: &'a [u8], what: &str) -> Result<(&'a [u8], &'a [u8]), SyncError> {
+    let (&len, rest) = bytes
+        .split_first()
+        .ok_or_else(|| invalid(format!("missing {what} length")))?;
+    if rest.len() < len as usize {
+        return Err(invalid(format!("truncated {what} list")));
+    }
+    Ok(rest.split_at(len as usize))
+}
+
+fn invalid(detail: impl Into<String>) -> SyncError {
+    SyncError::InvalidHandshake(detail.into())
+}
+
+/// Pick the newest wire version and suite both sides offer, and the newer
+/// epoch. Suites are ranked by id, so GCM-SIV wins over GCM when both
+/// sides have it.
+pub fn negotiate_handshake(
+    ours: &HandshakeParams,
+    theirs: &HandshakeParams,
+) -> Result<NegotiatedParams, SyncError> {
+    let version = ours
+        .versions
+        .iter()
+        .copied()
+        .filter(|v| theirs.versions.contains(v))
+        .max()
+        .ok_or(SyncError::NoCommonHandshake("wire version"))?;
+    let algorithm = ours
+        .algorithms
+        .iter()
+        .copied()
+        .filter(|a| theirs.algorithms.contains(a))
+        .max_by_key(|a| a.version())
+        .ok_or(SyncError::NoCommonHandshake("AEAD algorithm"))?;
+    Ok(NegotiatedParams {
+        version,
+        algorithm,
+        epoch: ours.current_epoch.max(theirs.current_epoch),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(algorithms: &[CipherSuite], epoch: u32) -> HandshakeParams {
+        HandshakeParams {
+            versions: WireVersion::ALL.to_vec(),
+            algorithms: algorithms.to_vec(),
+            current_epoch: epoch,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let ours = params(&[CipherSuite::Aes256Gcm, CipherSuite::Aes256GcmSiv], 7);
+        let bytes = encode_handshake(&ours);
+        assert_eq!(bytes, [1, 2, 4, 5, 2, 4, 5, 0, 0, 0, 7]);
+        assert_eq!(decode_handshake(&bytes).unwrap(), ours);
+    }
+
+    #[test]
+    fn unknown_entries_are_dropped() {
+        // Version 9 and suite 0x42 come from a newer peer
+        let bytes = [1, 2, 4, 9, 2, 0x42, 4, 0, 0, 1, 0];
+        let decoded = decode_handshake(&bytes).unwrap();
+        assert_eq!(decoded.versions, [WireVersion::V4]);
+        assert_eq!(decoded.algorithms, [CipherSuite::Aes256Gcm]);
+        assert_eq!(decoded.current_epoch, 256);
+    }
+
+    #[test]
+    fn rejects_malformed_handshakes() {
+        let valid = encode_handshake(&params(&[CipherSuite::Aes256Gcm], 1));
+        for bad in [
+            &[][..],
+            &[2, 0, 0, 0, 0, 0, 0][..],
+            &valid[..valid.len() - 1],
+            &[1, 5, 4][..],
+            &[valid.as_slice(), &[0]].concat()[..],
+        ] {
+            assert!(
+                matches!(decode_handshake(bad), Err(SyncError::InvalidHandshake(_))),
+                "{bad:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn negotiates_the_common_max() {
+        let ours = params(&[CipherSuite::Aes256Gcm, CipherSuite::Aes256GcmSiv], 3);
+        let theirs = params(&[CipherSuite::Aes256GcmSiv, CipherSuite::Aes256Gcm], 5);
+        let negotiated = negotiate_handshake(&ours, &theirs).unwrap();
+        assert_eq!(
+            negotiated,
+            NegotiatedParams {
+                version: WireVersion::V5,
+                algorithm: CipherSuite::Aes256GcmSiv,
+                epoch: 5,
+            }
+        );
+        assert_eq!(negotiate_handshake(&theirs, &ours).unwrap(), negotiated);
+
+        let gcm_only = params(&[CipherSuite::Aes256Gcm], 3);
+        assert_eq!(
+            negotiate_handshake(&ours, &gcm_only).unwrap().algorithm,
+            CipherSuite::Aes256Gcm
+        );
+    }
+
+    #[test]
+    fn no_overlap_fails() {
+        let gcm = params(&[CipherSuite::Aes256Gcm], 1);
+        let siv = params(&[CipherSuite::Aes256GcmSiv], 1);
+        assert!(matches!(
+            negotiate_handshake(&gcm, &siv),
+            Err(SyncError::NoCommonHandshake("AEAD algorithm"))
+        ));
+
+        let no_versions = HandshakeParams {
+            versions: Vec::new(),
+            ..gcm.clone()
+        };
+        assert!(matches!(
+            negotiate_handshake(&gcm, &no_versions),
+            Err(SyncError::NoCommonHandshake("wire version"))
+        ));
+    }
+}
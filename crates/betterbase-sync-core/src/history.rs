@@ -0,0 +1,148 @@
+//! Separate encryption for a record's edit chain.
+//!
+//! historyKey = HKDF-SHA256(recordDek, salt="betterbase:history-salt:v1", info="less:hkdf:v1\0history")
+//!
+//! The edit chain in [`BlobEnvelope::h`] reveals who edited a record and
+//! when. [`seal_history`] moves it into [`BlobEnvelope::hs`], encrypted under
+//! a history key derived from the record DEK rather than the DEK itself.
+//! The derivation is one-way: the history key opens the chain but never the
+//! data, while anyone holding the DEK can still derive the history key.
+
+use betterbase_crypto::{derive_labeled, EncryptionContext};
+use zeroize::Zeroize;
+
+use crate::error::SyncError;
+use crate::types::BlobEnvelope;
+use crate::wire::WireVersion;
+
+const HISTORY_SALT: &[u8] = b"betterbase:history-salt:v1";
+const HISTORY_LABEL: &str = "history";
+
+/// Derive the history key for a record from its DEK.
+pub fn derive_history_key(record_dek: &[u8]) -> [u8; 32] {
+    let mut okm = derive_labeled(record_dek, HISTORY_SALT, HISTORY_LABEL, 32)
+        .expect("32 bytes is within the HKDF-SHA256 output limit");
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&okm);
+    okm.zeroize();
+    key
+}
+
+/// Return a copy of `envelope` with its edit chain moved from `h` into `hs`,
+/// sealed under the history key of `record_dek`.
+///
+/// The chain is bound to `context` like the data payload. An envelope
+/// without an edit chain is returned unchanged.
+pub fn seal_history(
+    envelope: &BlobEnvelope,
+    record_dek: &[u8],
+    context: &EncryptionContext,
+) -> Result<BlobEnvelope, SyncError> {
+    let Some(chain) = &envelope.h else {
+        return Ok(envelope.clone());
+    };
+    let mut history_key = derive_history_key(record_dek);
+    let sealed = WireVersion::LATEST.seal(chain.as_bytes(), &history_key, context);
+    history_key.zeroize();
+    Ok(BlobEnvelope {
+        h: None,
+        hs: Some(sealed?),
+        ..envelope.clone()
+    })
+}
+
+/// Decrypt the sealed edit chain of `envelope` with a history key from
+/// [`derive_history_key`].
+///
+/// Returns the plaintext `h` if the chain was never sealed, and `None` if
+/// the envelope carries no chain at all.
+pub fn open_history(
+    envelope: &BlobEnvelope,
+    history_key: &[u8],
+    context: &EncryptionContext,
+) -> Result<Option<String>, SyncError> {
+    let Some(sealed) = &envelope.hs else {
+        return Ok(envelope.h.clone());
+    };
+    let plaintext = WireVersion::open(sealed, history_key, context)?;
+    String::from_utf8(plaintext)
+        .map(Some)
+        .map_err(|_| SyncError::InvalidEnvelope("sealed edit chain is not UTF-8".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> EncryptionContext {
+        EncryptionContext {
+            space_id: "space-1".to_string(),
+            record_id: "record-1".to_string(),
+        }
+    }
+
+    fn envelope_with_chain() -> BlobEnvelope {
+        BlobEnvelope {
+            c: "notes".to_string(),
+            v: 1,
+            crdt: vec![1, 2, 3],
+            h: Some(r#"[{"a":"did:key:alice"}]"#.to_string()),
+            a: false,
+            p: None,
+            hs: None,
+        }
+    }
+
+    #[test]
+    fn history_key_is_stable_and_distinct_from_dek() {
+        let dek = [0x11u8; 32];
+        assert_eq!(derive_history_key(&dek), derive_history_key(&dek));
+        assert_ne!(derive_history_key(&dek), dek);
+        assert_ne!(derive_history_key(&dek), derive_history_key(&[0x12u8; 32]));
+    }
+
+    #[test]
+    fn sealed_history_opens_with_history_key() {
+        let dek = [0x11u8; 32];
+        let original = envelope_with_chain();
+        let sealed = seal_history(&original, &dek, &context()).unwrap();
+        assert!(sealed.h.is_none());
+        assert_eq!(sealed.crdt, original.crdt);
+
+        let chain = open_history(&sealed, &derive_history_key(&dek), &context()).unwrap();
+        assert_eq!(chain, original.h);
+    }
+
+    #[test]
+    fn sealed_history_does_not_open_with_dek_alone() {
+        let dek = [0x11u8; 32];
+        let sealed = seal_history(&envelope_with_chain(), &dek, &context()).unwrap();
+        assert!(open_history(&sealed, &dek, &context()).is_err());
+    }
+
+    #[test]
+    fn sealed_history_is_bound_to_context() {
+        let dek = [0x11u8; 32];
+        let sealed = seal_history(&envelope_with_chain(), &dek, &context()).unwrap();
+        let other = EncryptionContext {
+            record_id: "record-2".to_string(),
+            ..context()
+        };
+        assert!(open_history(&sealed, &derive_history_key(&dek), &other).is_err());
+    }
+
+    #[test]
+    fn envelope_without_chain_is_unchanged() {
+        let dek = [0x11u8; 32];
+        let envelope = BlobEnvelope {
+            h: None,
+            ..envelope_with_chain()
+        };
+        let sealed = seal_history(&envelope, &dek, &context()).unwrap();
+        assert!(sealed.hs.is_none());
+        assert_eq!(
+            open_history(&sealed, &derive_history_key(&dek), &context()).unwrap(),
+            None
+        );
+    }
+}
@@ -1,23 +1,69 @@
-//! Sync core: envelope encoding, padding, transport encryption, epoch management, membership.
+//! Sync core: envelope encoding, chunked envelopes for large blobs, padding,
+//! transport encryption and handshake, epoch management, per-device sender
+//! keys, separately sealed edit history, membership, space policy, wire
+//! versions, space Merkle roots.
 
+pub mod channel;
+pub mod chunked;
+pub mod device_key;
 pub mod envelope;
 pub mod epoch_cache;
 pub mod error;
+pub mod handshake;
+pub mod history;
 pub mod membership;
+pub mod merkle;
 pub mod padding;
 pub mod reencrypt;
+pub mod replay;
+pub mod space_policy;
 pub mod transport;
 pub mod types;
+pub mod wire;
 
-pub use envelope::{decode_envelope, encode_envelope};
-pub use epoch_cache::EpochKeyCache;
+pub use channel::{decrypt_channel_guarded, encrypt_channel_sequenced, ChannelKind};
+pub use chunked::{decode_envelope_chunked, encode_envelope_chunked};
+pub use device_key::derive_device_key;
+pub use envelope::{
+    checksum_blob, decode_envelope, encode_envelope, verify_blob_checksum, ENVELOPE_VERSION,
+};
+pub use epoch_cache::{EpochKeyCache, EpochKeyCacheSet, RotationGracePolicy, RotationStatus};
 pub use error::SyncError;
+pub use handshake::{
+    decode_handshake, encode_handshake, negotiate_handshake, HandshakeParams, NegotiatedParams,
+    HANDSHAKE_VERSION,
+};
+pub use history::{derive_history_key, open_history, seal_history};
 pub use membership::{
-    build_membership_signing_message, decrypt_membership_payload, encrypt_membership_payload,
-    parse_membership_entry, serialize_membership_entry, sha256_hash, verify_membership_entry,
-    MembershipEntryPayload, MembershipEntryType,
+    build_membership_signing_message, build_membership_signing_message_v2, compact_log,
+    decrypt_membership_payload, detect_conflicts, encrypt_membership_payload, pad_membership_entry,
+    parse_membership_entry, serialize_membership_entry, sha256_hash, ucan_revocation_id,
+    unpad_membership_entry, verify_membership_entry, verify_membership_log_with_trust,
+    verify_ucan_chain_with_trust, ConflictReason, EntryVerdict, MemberRecord, MemberStatus,
+    MembershipConflict, MembershipEntryPayload, MembershipEntryType, MembershipLogVerification,
+    MembershipSigningVersion, MembershipState, TrustAnnotation, UCANChainVerification,
+    MEMBERSHIP_PADDING_BUCKETS,
+};
+pub use merkle::{compute_space_root, MerkleProof, SpaceMerkleTree};
+pub use padding::{
+    estimate_padded_size, pad_to_bucket, pad_to_bucket_with, unpad, PaddingProfile,
+    DEFAULT_PADDING_BUCKETS,
+};
+pub use reencrypt::{
+    derive_forward, peek_epoch, rewrap_deks, rotate_space_epoch, verify_epoch_consistency,
+    EpochConsistencyReport, RewrapReport, RewrappedRecord, RotationFailure, SpaceRotationReport,
+    WrappedDek,
+};
+pub use replay::{ReplayGuard, ReplayWindow, DEFAULT_REPLAY_WINDOW};
+pub use space_policy::{
+    build_space_policy_signing_message, parse_space_policy_entry, resolve_space_delete_policy,
+    resolve_space_wire_policy, serialize_space_policy_entry, verify_space_policy_entry, DeleteKind,
+    SpaceDeletePolicy, SpacePolicyEntry,
+};
+pub use transport::{
+    decrypt_inbound, decrypt_inbound_batch, decrypt_inbound_guarded, decrypt_inbound_sequenced,
+    encrypt_outbound, encrypt_outbound_checksummed, encrypt_outbound_sealing_history,
+    encrypt_outbound_sequenced, encrypt_outbound_with_version, validate_epoch_sequence,
 };
-pub use padding::{pad_to_bucket, unpad, DEFAULT_PADDING_BUCKETS};
-pub use reencrypt::{derive_forward, peek_epoch, rewrap_deks};
-pub use transport::{decrypt_inbound, encrypt_outbound};
-pub use types::BlobEnvelope;
+pub use types::{BlobEnvelope, ChunkPart};
+pub use wire::{negotiate_version, Capabilities, SpaceWirePolicy, WireVersion};
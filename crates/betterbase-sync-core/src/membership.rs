@@ -1,16 +1,37 @@
-//! Membership log entry signing, verification, and encryption.
+//! Membership log entry signing, verification, padding, encryption, and
+//! replay.
+
+use std::collections::{BTreeMap, BTreeSet};
 
 use crate::error::SyncError;
+use crate::padding::{pad_to_bucket, unpad};
+use crate::wire::WireVersion;
 use betterbase_crypto::{
-    base64url_decode, base64url_encode, decode_did_key_to_jwk, decrypt_v4, encode_did_key_from_jwk,
-    encrypt_v4, verify, EncryptionContext,
+    base64url_decode, base64url_encode, canonical_json, decode_did_key_to_jwk,
+    encode_did_key_from_jwk, verify_ucan_chain, verify_with_jwk, CryptoError, EncryptionContext,
+    UCANChainInfo, UCANPermission,
 };
+use betterbase_discovery::{Freshness, TrustArtifact, TrustMaterial, TrustStore};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
 /// Prefix for membership signing messages (null-byte separated fields).
 const MEMBERSHIP_PREFIX: &str = "betterbase:membership:v1\0";
 
+/// Prefix for v2 signing messages, which embed the UCAN in canonical form.
+const MEMBERSHIP_PREFIX_V2: &str = "betterbase:membership:v2\0";
+
+/// Which signing message format an entry's signature covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MembershipSigningVersion {
+    /// [`build_membership_signing_message`]: the UCAN as written.
+    #[default]
+    V1,
+    /// [`build_membership_signing_message_v2`]: the UCAN re-encoded with
+    /// canonical JSON. Marked `"v":2` in the entry payload.
+    V2,
+}
+
 /// Entry type: delegation, accepted, declined, revoked.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MembershipEntryType {
@@ -59,7 +80,7 @@ pub struct MembershipEntryPayload {
     pub ucan: String,
     /// Entry type.
     pub entry_type: MembershipEntryType,
-    /// ECDSA P-256 signature (64 bytes).
+    /// ECDSA P-256 or Ed25519 signature (64 bytes), per `signer_public_key`.
     pub signature: Vec<u8>,
     /// Signer's public key JWK.
     pub signer_public_key: serde_json::Value,
@@ -73,6 +94,8 @@ pub struct MembershipEntryPayload {
     pub signer_handle: Option<String>,
     /// Handle (user@domain) of the invitee (delegation entries only).
     pub recipient_handle: Option<String>,
+    /// Signing message format the signature covers.
+    pub signing_version: MembershipSigningVersion,
 }
 
 /// Build the canonical message to sign for a membership entry.
@@ -99,6 +122,57 @@ pub fn build_membership_signing_message(
     message.into_bytes()
 }
 
+/// Build the v2 signing message for a membership entry.
+///
+/// Same fields as [`build_membership_signing_message`] under a v2 prefix,
+/// but the UCAN's header and payload are decoded and re-encoded with
+/// `canonical_json`. Signer and verifier then agree on the bytes even when
+/// their JSON serializers order the UCAN's keys differently.
+pub fn build_membership_signing_message_v2(
+    entry_type: MembershipEntryType,
+    space_id: &str,
+    signer_did: &str,
+    ucan: &str,
+    signer_handle: &str,
+    recipient_handle: &str,
+) -> Result<Vec<u8>, SyncError> {
+    let message = format!(
+        "{}{}\0{}\0{}\0{}\0{}\0{}",
+        MEMBERSHIP_PREFIX_V2,
+        entry_type.as_str(),
+        space_id,
+        signer_did,
+        canonical_ucan(ucan)?,
+        signer_handle,
+        recipient_handle
+    );
+    Ok(message.into_bytes())
+}
+
+/// Re-encode a UCAN JWT with its header and payload in canonical JSON.
+/// The signature segment is kept as-is.
+fn canonical_ucan(ucan: &str) -> Result<String, SyncError> {
+    let parts: Vec<&str> = ucan.split('.').collect();
+    if parts.len() != 3 {
+        return Err(SyncError::InvalidMembershipEntry(
+            "invalid UCAN JWT format".to_string(),
+        ));
+    }
+    let canonical_segment = |segment: &str| -> Result<String, SyncError> {
+        let bytes = base64url_decode(segment).map_err(|e| {
+            SyncError::InvalidMembershipEntry(format!("UCAN segment decode: {}", e))
+        })?;
+        let value: serde_json::Value = serde_json::from_slice(&bytes)?;
+        Ok(base64url_encode(canonical_json(&value)?.as_bytes()))
+    };
+    Ok(format!(
+        "{}.{}.{}",
+        canonical_segment(parts[0])?,
+        canonical_segment(parts[1])?,
+        parts[2]
+    ))
+}
+
 /// Parse a membership log entry payload string.
 ///
 /// Expected format: JSON `{"u":"<ucan>","t":"d","s":"<base64url>","p":{...jwk},...}`
@@ -129,17 +203,38 @@ pub fn parse_membership_entry(payload: &str) -> Result<MembershipEntryPayload, S
     let entry_type = MembershipEntryType::from_str(entry_type_str)?;
     let signature =
         base64url_decode(sig_b64).map_err(|e| SyncError::InvalidMembershipEntry(e.to_string()))?;
+    let epoch = match obj.get("e") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => Some(
+            v.as_u64()
+                .and_then(|e| u32::try_from(e).ok())
+                .ok_or_else(|| {
+                    SyncError::InvalidMembershipEntry(format!("invalid epoch: {}", v))
+                })?,
+        ),
+    };
+    let signing_version = match obj.get("v").map(|v| v.as_u64()) {
+        None | Some(Some(1)) => MembershipSigningVersion::V1,
+        Some(Some(2)) => MembershipSigningVersion::V2,
+        Some(_) => {
+            return Err(SyncError::InvalidMembershipEntry(format!(
+                "unsupported signing version: {}",
+                obj["v"]
+            )))
+        }
+    };
 
     Ok(MembershipEntryPayload {
         ucan,
         entry_type,
         signature,
         signer_public_key,
-        epoch: obj.get("e").and_then(|v| v.as_u64()).map(|v| v as u32),
+        epoch,
         mailbox_id: obj.get("m").and_then(|v| v.as_str()).map(|s| s.to_string()),
         public_key_jwk: obj.get("k").cloned(),
         signer_handle: validate_handle(obj.get("n")),
         recipient_handle: validate_handle(obj.get("rn")),
+        signing_version,
     })
 }
 
@@ -187,14 +282,19 @@ pub fn serialize_membership_entry(entry: &MembershipEntryPayload) -> String {
     if let Some(ref h) = entry.recipient_handle {
         obj.insert("rn".to_string(), serde_json::Value::String(h.clone()));
     }
+    if entry.signing_version == MembershipSigningVersion::V2 {
+        obj.insert("v".to_string(), serde_json::Value::from(2));
+    }
     serde_json::Value::Object(obj).to_string()
 }
 
 /// Verify a membership entry's signature.
 ///
-/// 1. Verify signer's public key DID matches expected signer role
-/// 2. Verify ECDSA signature over canonical message
-/// 3. Verify the UCAN's JWT signature against the issuer's public key
+/// 1. Verify the UCAN's `with` resource is this space
+/// 2. Verify signer's public key DID matches expected signer role
+/// 3. Verify the signature over the canonical message, with the algorithm
+///    of the signer's key (P-256 or Ed25519)
+/// 4. Verify the UCAN's JWT signature against the issuer's public key
 pub fn verify_membership_entry(
     entry: &MembershipEntryPayload,
     space_id: &str,
@@ -202,6 +302,11 @@ pub fn verify_membership_entry(
     // Parse UCAN to get issuer/audience DIDs
     let parsed = parse_ucan_payload(&entry.ucan)?;
 
+    // A UCAN granted on another space must not authorize entries in this log
+    if parsed.resource != format!("space:{}", space_id) {
+        return Ok(false);
+    }
+
     // Determine expected signer DID based on entry type
     let expected_signer_did = match entry.entry_type {
         MembershipEntryType::Delegation | MembershipEntryType::Revoked => &parsed.issuer_did,
@@ -214,16 +319,28 @@ pub fn verify_membership_entry(
         return Ok(false);
     }
 
-    // Verify ECDSA signature over the membership entry message
-    let message = build_membership_signing_message(
-        entry.entry_type,
-        space_id,
-        &signer_did,
-        &entry.ucan,
-        entry.signer_handle.as_deref().unwrap_or(""),
-        entry.recipient_handle.as_deref().unwrap_or(""),
-    );
-    let valid = verify(&entry.signer_public_key, &message, &entry.signature);
+    // Verify the signature over the membership entry message
+    let signer_handle = entry.signer_handle.as_deref().unwrap_or("");
+    let recipient_handle = entry.recipient_handle.as_deref().unwrap_or("");
+    let message = match entry.signing_version {
+        MembershipSigningVersion::V1 => build_membership_signing_message(
+            entry.entry_type,
+            space_id,
+            &signer_did,
+            &entry.ucan,
+            signer_handle,
+            recipient_handle,
+        ),
+        MembershipSigningVersion::V2 => build_membership_signing_message_v2(
+            entry.entry_type,
+            space_id,
+            &signer_did,
+            &entry.ucan,
+            signer_handle,
+            recipient_handle,
+        )?,
+    };
+    let valid = verify_with_jwk(&entry.signer_public_key, &message, &entry.signature);
     if !valid {
         return Ok(false);
     }
@@ -244,8 +361,538 @@ pub fn verify_membership_entry(
     Ok(true)
 }
 
-/// Verify a UCAN JWT's ES256 signature.
-fn verify_ucan_signature(
+/// Identifier for a UCAN in a cached revocation set: base64url SHA-256 of
+/// the UCAN JWT string.
+pub fn ucan_revocation_id(ucan: &str) -> String {
+    base64url_encode(&sha256_hash(ucan.as_bytes()))
+}
+
+/// A piece of cached trust material a verification relied on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustAnnotation {
+    pub artifact: TrustArtifact,
+    pub freshness: Freshness,
+}
+
+/// Outcome for a single membership log entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryVerdict {
+    /// Signatures verify and the UCAN is not revoked.
+    Valid,
+    /// UCAN resource, signer, entry signature, or UCAN signature check failed.
+    InvalidSignature,
+    /// Signatures verify but the UCAN appears in the space's revocation set.
+    Revoked,
+}
+
+/// Result of [`verify_membership_log_with_trust`].
+#[derive(Debug, Clone)]
+pub struct MembershipLogVerification {
+    /// One verdict per input entry, in log order.
+    pub entries: Vec<EntryVerdict>,
+    /// Every cached artifact the verification consulted.
+    pub trust: Vec<TrustAnnotation>,
+}
+
+impl MembershipLogVerification {
+    /// Whether every entry verified and none was revoked.
+    pub fn all_valid(&self) -> bool {
+        self.entries.iter().all(|v| *v == EntryVerdict::Valid)
+    }
+
+    /// Whether any trust material relied on was past its fresh window.
+    pub fn is_stale(&self) -> bool {
+        self.trust
+            .iter()
+            .any(|t| matches!(t.freshness, Freshness::Stale { .. }))
+    }
+}
+
+/// Verify a membership log against cached trust material.
+///
+/// Each entry is checked with [`verify_membership_entry`]. Delegations and
+/// acceptances whose UCAN is in the space's cached revocation set are
+/// reported as [`EntryVerdict::Revoked`]; `Revoked` entries themselves are
+/// judged on their signatures alone.
+///
+/// Stale material is used and reported in [`MembershipLogVerification::trust`].
+/// Missing or expired material is refused with an error rather than treated
+/// as an empty revocation set.
+pub fn verify_membership_log_with_trust(
+    entries: &[MembershipEntryPayload],
+    space_id: &str,
+    store: &TrustStore,
+    now_seconds: u64,
+) -> Result<MembershipLogVerification, SyncError> {
+    let (revoked, annotation) = usable_revocation_set(store, space_id, now_seconds)?;
+
+    let mut verdicts = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let verdict = if !verify_membership_entry(entry, space_id)? {
+            EntryVerdict::InvalidSignature
+        } else if entry.entry_type != MembershipEntryType::Revoked
+            && revoked.contains(&ucan_revocation_id(&entry.ucan))
+        {
+            EntryVerdict::Revoked
+        } else {
+            EntryVerdict::Valid
+        };
+        verdicts.push(verdict);
+    }
+
+    Ok(MembershipLogVerification {
+        entries: verdicts,
+        trust: vec![annotation],
+    })
+}
+
+/// Result of [`verify_ucan_chain_with_trust`].
+#[derive(Debug, Clone)]
+pub struct UCANChainVerification {
+    /// Facts established by the chain, as from [`verify_ucan_chain`].
+    pub chain: UCANChainInfo,
+    /// Whether the leaf or any proof above it is in the space's revocation set.
+    pub revoked: bool,
+    /// Every cached artifact the verification consulted.
+    pub trust: Vec<TrustAnnotation>,
+}
+
+impl UCANChainVerification {
+    /// Whether the chain verified and nothing in it was revoked.
+    pub fn is_valid(&self) -> bool {
+        !self.revoked
+    }
+
+    /// Whether any trust material relied on was past its fresh window.
+    pub fn is_stale(&self) -> bool {
+        self.trust
+            .iter()
+            .any(|t| matches!(t.freshness, Freshness::Stale { .. }))
+    }
+}
+
+/// Verify a UCAN chain with [`verify_ucan_chain`], then check every UCAN in
+/// it against the space's cached revocation set.
+///
+/// Stale material is used and reported in [`UCANChainVerification::trust`].
+/// Missing or expired material is refused with an error, as in
+/// [`verify_membership_log_with_trust`].
+pub fn verify_ucan_chain_with_trust(
+    token: &str,
+    space_id: &str,
+    store: &TrustStore,
+    now_seconds: u64,
+) -> Result<UCANChainVerification, SyncError> {
+    let (revoked, annotation) = usable_revocation_set(store, space_id, now_seconds)?;
+    let chain = verify_ucan_chain(token, space_id, now_seconds)?;
+    let revoked = ucan_chain_tokens(token)?
+        .iter()
+        .any(|ucan| revoked.contains(&ucan_revocation_id(ucan)));
+    Ok(UCANChainVerification {
+        chain,
+        revoked,
+        trust: vec![annotation],
+    })
+}
+
+/// The cached revocation set for `space_id`, unless missing or expired.
+fn usable_revocation_set<'a>(
+    store: &'a TrustStore,
+    space_id: &str,
+    now_seconds: u64,
+) -> Result<(&'a BTreeSet<String>, TrustAnnotation), SyncError> {
+    let artifact = TrustArtifact::RevocationSet {
+        space_id: space_id.to_string(),
+    };
+    let lookup =
+        store
+            .get(&artifact, now_seconds)
+            .ok_or_else(|| SyncError::TrustMaterialMissing {
+                artifact: artifact.clone(),
+            })?;
+    if let Freshness::Expired { age_seconds } = lookup.freshness {
+        return Err(SyncError::TrustMaterialExpired {
+            artifact,
+            age_seconds,
+        });
+    }
+    match lookup.material {
+        TrustMaterial::RevocationSet { revoked, .. } => Ok((
+            revoked,
+            TrustAnnotation {
+                artifact,
+                freshness: lookup.freshness,
+            },
+        )),
+        // `TrustStore` keys entries by their material, so this cannot happen.
+        _ => Err(SyncError::TrustMaterialMissing { artifact }),
+    }
+}
+
+/// The UCAN JWTs in `token`'s proof chain, leaf first. Only call on a chain
+/// [`verify_ucan_chain`] accepted, so each link has at most one proof and
+/// the chain is bounded.
+fn ucan_chain_tokens(token: &str) -> Result<Vec<String>, SyncError> {
+    let mut tokens = vec![token.to_string()];
+    loop {
+        let current = &tokens[tokens.len() - 1];
+        let payload = current
+            .split('.')
+            .nth(1)
+            .and_then(|segment| base64url_decode(segment).ok())
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .ok_or_else(|| CryptoError::InvalidUcan("undecodable payload".to_string()))?;
+        match payload
+            .get("prf")
+            .and_then(|prf| prf.get(0))
+            .and_then(|proof| proof.as_str())
+        {
+            Some(proof) => tokens.push(proof.to_string()),
+            None => return Ok(tokens),
+        }
+    }
+}
+
+/// Where a recipient stands after replaying the membership log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberStatus {
+    /// Delegated to, not yet accepted or declined.
+    Invited,
+    /// Accepted the delegation currently in effect.
+    Active,
+    /// Declined the delegation currently in effect.
+    Declined,
+    /// The delegation currently in effect was revoked.
+    Revoked,
+    /// The delegation currently in effect expired (its UCAN `exp` is at or
+    /// before the replay time) while invited or active.
+    Expired,
+}
+
+/// One recipient's state in a [`MembershipState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberRecord {
+    /// Recipient DID (the delegation UCAN's audience).
+    pub did: String,
+    /// Current status.
+    pub status: MemberStatus,
+    /// UCAN of the delegation currently in effect.
+    pub ucan: String,
+    /// Command it grants, e.g. `/space/write`.
+    pub command: String,
+    /// Recipient handle from the delegation entry.
+    pub handle: Option<String>,
+    /// Recipient mailbox from the delegation entry.
+    pub mailbox_id: Option<String>,
+    /// Epoch written on the revocation entry, when `status` is `Revoked`.
+    pub revoked_at_epoch: Option<u32>,
+    /// UCAN `exp` of the delegation currently in effect, in seconds.
+    pub expires_at: Option<u64>,
+}
+
+/// Effective member set of a space, folded from its membership log.
+#[derive(Debug, Clone, Default)]
+pub struct MembershipState {
+    members: BTreeMap<String, MemberRecord>,
+}
+
+impl MembershipState {
+    /// Verify every entry with [`verify_membership_entry`] and fold them in
+    /// log order.
+    ///
+    /// - A delegation (re)invites its audience, replacing any earlier
+    ///   delegation to them, including a revoked one.
+    /// - Accept, decline and revoke entries apply only to the delegation
+    ///   currently in effect for their audience (matched by UCAN); entries for
+    ///   a superseded or unknown delegation are ignored.
+    /// - Accepting twice is a no-op. A declined or revoked delegation cannot
+    ///   be accepted. Revoking works whether or not the member accepted.
+    /// - An invited or active member whose delegation's UCAN `exp` is at or
+    ///   before `now_seconds` ends up [`MemberStatus::Expired`], the same
+    ///   cutoff [`compact_log`] drops it at.
+    ///
+    /// Fails with [`SyncError::InvalidMembershipLogEntry`] naming the first
+    /// entry that does not verify.
+    pub fn replay(
+        entries: &[MembershipEntryPayload],
+        space_id: &str,
+        now_seconds: u64,
+    ) -> Result<MembershipState, SyncError> {
+        let mut state = MembershipState::default();
+        for (index, entry) in entries.iter().enumerate() {
+            let parsed = verify_log_entry(index, entry, space_id)?;
+            state.apply(entry, parsed);
+        }
+        for member in state.members.values_mut() {
+            let expired = member.expires_at.is_some_and(|exp| exp <= now_seconds);
+            if expired && matches!(member.status, MemberStatus::Invited | MemberStatus::Active) {
+                member.status = MemberStatus::Expired;
+            }
+        }
+        Ok(state)
+    }
+
+    fn apply(&mut self, entry: &MembershipEntryPayload, parsed: ParsedUCAN) {
+        if entry.entry_type == MembershipEntryType::Delegation {
+            self.members.insert(
+                parsed.audience_did.clone(),
+                MemberRecord {
+                    did: parsed.audience_did,
+                    status: MemberStatus::Invited,
+                    ucan: entry.ucan.clone(),
+                    command: parsed.command,
+                    handle: entry.recipient_handle.clone(),
+                    mailbox_id: entry.mailbox_id.clone(),
+                    revoked_at_epoch: None,
+                    expires_at: parsed.expires_at,
+                },
+            );
+            return;
+        }
+
+        let Some(member) = self
+            .members
+            .get_mut(&parsed.audience_did)
+            .filter(|m| m.ucan == entry.ucan)
+        else {
+            return;
+        };
+        match (entry.entry_type, member.status) {
+            (MembershipEntryType::Accepted, MemberStatus::Invited) => {
+                member.status = MemberStatus::Active;
+            }
+            (MembershipEntryType::Declined, MemberStatus::Invited) => {
+                member.status = MemberStatus::Declined;
+            }
+            (MembershipEntryType::Revoked, status) if status != MemberStatus::Revoked => {
+                member.status = MemberStatus::Revoked;
+                member.revoked_at_epoch = entry.epoch;
+            }
+            _ => {}
+        }
+    }
+
+    /// The record for `did`, if it was ever delegated to.
+    pub fn member(&self, did: &str) -> Option<&MemberRecord> {
+        self.members.get(did)
+    }
+
+    /// Every recipient ever delegated to, ordered by DID.
+    pub fn members(&self) -> impl Iterator<Item = &MemberRecord> {
+        self.members.values()
+    }
+
+    /// Recipients with [`MemberStatus::Active`], ordered by DID.
+    pub fn active_members(&self) -> impl Iterator<Item = &MemberRecord> {
+        self.members
+            .values()
+            .filter(|m| m.status == MemberStatus::Active)
+    }
+
+    /// Whether `did` is an active member.
+    pub fn is_member(&self, did: &str) -> bool {
+        self.members
+            .get(did)
+            .is_some_and(|m| m.status == MemberStatus::Active)
+    }
+
+    /// The least permission any writer in the space may hold: `Write` if any
+    /// recipient was ever delegated something other than admin or read
+    /// (whatever their status now, as they may have written before it
+    /// changed), otherwise `Admin`.
+    ///
+    /// Bounds the author of a change that doesn't say who made it, such as a
+    /// pulled tombstone.
+    pub fn least_writer_permission(&self) -> UCANPermission {
+        let has_writer = self.members.values().any(|m| {
+            !matches!(
+                UCANPermission::from_command(&m.command),
+                Some(UCANPermission::Admin | UCANPermission::Read)
+            )
+        });
+        if has_writer {
+            UCANPermission::Write
+        } else {
+            UCANPermission::Admin
+        }
+    }
+}
+
+/// Verify entry `index` of a log and parse its UCAN, failing with
+/// [`SyncError::InvalidMembershipLogEntry`].
+fn verify_log_entry(
+    index: usize,
+    entry: &MembershipEntryPayload,
+    space_id: &str,
+) -> Result<ParsedUCAN, SyncError> {
+    let invalid = |reason: String| SyncError::InvalidMembershipLogEntry { index, reason };
+    match verify_membership_entry(entry, space_id) {
+        Ok(true) => {}
+        Ok(false) => return Err(invalid("signature check failed".to_string())),
+        Err(e) => return Err(invalid(e.to_string())),
+    }
+    parse_ucan_payload(&entry.ucan).map_err(|e| invalid(e.to_string()))
+}
+
+/// Compact a membership log to the entries behind its current effective
+/// membership.
+///
+/// Entries are verified and folded with the same rules as
+/// [`MembershipState::replay`]. For each recipient the result keeps the
+/// delegation currently in effect and the accept or decline entry that
+/// settled it, in their original log order. Everything else is dropped:
+/// revoked delegations with all their entries, delegations whose UCAN
+/// `exp` is at or before `now_seconds`, superseded delegations, repeated
+/// accepts, and entries that apply to no delegation.
+///
+/// Every kept entry still carries its signed UCAN, so the output verifies
+/// on its own, and replaying it yields the same state for every remaining
+/// recipient.
+pub fn compact_log(
+    entries: &[MembershipEntryPayload],
+    space_id: &str,
+    now_seconds: u64,
+) -> Result<Vec<MembershipEntryPayload>, SyncError> {
+    struct Current {
+        delegation: usize,
+        response: Option<usize>,
+        status: MemberStatus,
+        expires_at: Option<u64>,
+    }
+
+    let mut current: BTreeMap<String, Current> = BTreeMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let parsed = verify_log_entry(index, entry, space_id)?;
+        if entry.entry_type == MembershipEntryType::Delegation {
+            current.insert(
+                parsed.audience_did,
+                Current {
+                    delegation: index,
+                    response: None,
+                    status: MemberStatus::Invited,
+                    expires_at: parsed.expires_at,
+                },
+            );
+            continue;
+        }
+
+        let Some(member) = current
+            .get_mut(&parsed.audience_did)
+            .filter(|m| entries[m.delegation].ucan == entry.ucan)
+        else {
+            continue;
+        };
+        match (entry.entry_type, member.status) {
+            (MembershipEntryType::Accepted, MemberStatus::Invited) => {
+                member.status = MemberStatus::Active;
+                member.response = Some(index);
+            }
+            (MembershipEntryType::Declined, MemberStatus::Invited) => {
+                member.status = MemberStatus::Declined;
+                member.response = Some(index);
+            }
+            (MembershipEntryType::Revoked, _) => member.status = MemberStatus::Revoked,
+            _ => {}
+        }
+    }
+
+    let mut kept: Vec<usize> = current
+        .values()
+        .filter(|m| m.status != MemberStatus::Revoked)
+        .filter(|m| m.expires_at.is_none_or(|exp| exp > now_seconds))
+        .flat_map(|m| std::iter::once(m.delegation).chain(m.response))
+        .collect();
+    kept.sort_unstable();
+    Ok(kept.into_iter().map(|i| entries[i].clone()).collect())
+}
+
+/// Why [`detect_conflicts`] flagged an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// A delegation grants a mailbox a different command than the
+    /// unrevoked delegation already in effect for it.
+    MismatchedPermissions,
+    /// A revocation whose UCAN matches no earlier delegation.
+    RevokeWithoutDelegation,
+}
+
+/// Two membership log entries that contradict each other, by log index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MembershipConflict {
+    pub reason: ConflictReason,
+    /// The entry that introduced the conflict.
+    pub entry: usize,
+    /// The earlier entry it contradicts; `None` when there is none to name
+    /// ([`ConflictReason::RevokeWithoutDelegation`]).
+    pub conflicts_with: Option<usize>,
+}
+
+/// Report log entries that contradict each other, in log order. Read-only:
+/// nothing is verified or changed.
+///
+/// - [`ConflictReason::MismatchedPermissions`]: a delegation to a
+///   `mailbox_id` grants a different command than the earlier delegation
+///   still in effect for that mailbox, e.g. two admins inviting the same
+///   person with different permissions. Once the earlier delegation is
+///   revoked, a new one may grant anything.
+/// - [`ConflictReason::RevokeWithoutDelegation`]: a revocation whose UCAN
+///   matches no earlier delegation entry.
+///
+/// Delegations whose UCAN does not parse are not compared; signature and
+/// UCAN checks are left to [`MembershipState::replay`].
+pub fn detect_conflicts(entries: &[MembershipEntryPayload]) -> Vec<MembershipConflict> {
+    // Delegation index by UCAN, and the unrevoked delegation (with its
+    // command) per mailbox
+    let mut delegations: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut in_effect: BTreeMap<&str, (usize, String)> = BTreeMap::new();
+    let mut conflicts = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        match entry.entry_type {
+            MembershipEntryType::Delegation => {
+                delegations.insert(&entry.ucan, index);
+                let Some(mailbox) = entry.mailbox_id.as_deref() else {
+                    continue;
+                };
+                let Ok(parsed) = parse_ucan_payload(&entry.ucan) else {
+                    continue;
+                };
+                if let Some((earlier, command)) = in_effect.get(mailbox) {
+                    if *command != parsed.command {
+                        conflicts.push(MembershipConflict {
+                            reason: ConflictReason::MismatchedPermissions,
+                            entry: index,
+                            conflicts_with: Some(*earlier),
+                        });
+                    }
+                }
+                in_effect.insert(mailbox, (index, parsed.command));
+            }
+            MembershipEntryType::Revoked => match delegations.get(entry.ucan.as_str()) {
+                Some(&delegation) => {
+                    if let Some(mailbox) = entries[delegation].mailbox_id.as_deref() {
+                        if in_effect
+                            .get(mailbox)
+                            .is_some_and(|(i, _)| *i == delegation)
+                        {
+                            in_effect.remove(mailbox);
+                        }
+                    }
+                }
+                None => conflicts.push(MembershipConflict {
+                    reason: ConflictReason::RevokeWithoutDelegation,
+                    entry: index,
+                    conflicts_with: None,
+                }),
+            },
+            MembershipEntryType::Accepted | MembershipEntryType::Declined => {}
+        }
+    }
+    conflicts
+}
+
+/// Verify a UCAN JWT's signature with the issuer key's algorithm.
+pub(crate) fn verify_ucan_signature(
     ucan: &str,
     public_key_jwk: &serde_json::Value,
 ) -> Result<bool, SyncError> {
@@ -258,7 +905,7 @@ fn verify_ucan_signature(
     let signature_bytes =
         base64url_decode(parts[2]).map_err(|e| SyncError::InvalidMembershipEntry(e.to_string()))?;
 
-    Ok(verify(
+    Ok(verify_with_jwk(
         public_key_jwk,
         signing_input.as_bytes(),
         &signature_bytes,
@@ -266,13 +913,19 @@ fn verify_ucan_signature(
 }
 
 /// Parsed fields from a UCAN JWT payload.
-struct ParsedUCAN {
-    issuer_did: String,
-    audience_did: String,
+pub(crate) struct ParsedUCAN {
+    pub(crate) issuer_did: String,
+    pub(crate) audience_did: String,
+    /// Granted command, e.g. `/space/admin`.
+    pub(crate) command: String,
+    /// Resource the grant applies to, e.g. `space:<id>`.
+    pub(crate) resource: String,
+    /// `exp` claim in seconds, if present.
+    pub(crate) expires_at: Option<u64>,
 }
 
-/// Parse a UCAN JWT to extract issuer and audience DIDs.
-fn parse_ucan_payload(ucan: &str) -> Result<ParsedUCAN, SyncError> {
+/// Parse a UCAN JWT to extract issuer and audience DIDs, command and resource.
+pub(crate) fn parse_ucan_payload(ucan: &str) -> Result<ParsedUCAN, SyncError> {
     let parts: Vec<&str> = ucan.split('.').collect();
     if parts.len() != 3 {
         return Err(SyncError::InvalidMembershipEntry(
@@ -287,9 +940,20 @@ fn parse_ucan_payload(ucan: &str) -> Result<ParsedUCAN, SyncError> {
     let iss = normalize_did_field(payload.get("iss"));
     let aud = normalize_did_field(payload.get("aud"));
 
+    let str_field = |key: &str| {
+        payload
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
     Ok(ParsedUCAN {
         issuer_did: iss,
         audience_did: aud,
+        command: str_field("cmd"),
+        resource: str_field("with"),
+        expires_at: payload.get("exp").and_then(|v| v.as_u64()),
     })
 }
 
@@ -306,9 +970,31 @@ fn normalize_did_field(value: Option<&serde_json::Value>) -> String {
     }
 }
 
+/// Padding buckets for serialized membership entries.
+///
+/// A root delegation with handles, mailbox ID and recipient key serializes to
+/// roughly 1 KiB and an accept or revoke to less, so every common entry lands
+/// in the smallest bucket. The larger buckets absorb long handles and
+/// delegated UCANs carrying proof chains.
+pub const MEMBERSHIP_PADDING_BUCKETS: &[usize] = &[2048, 4096, 8192, 16384];
+
+/// Pad a serialized membership entry so its type is not revealed by its size.
+///
+/// Uses the [`crate::padding`] format with [`MEMBERSHIP_PADDING_BUCKETS`].
+/// Pad before encrypting for transport; ciphertext length follows plaintext
+/// length.
+pub fn pad_membership_entry(serialized: &[u8]) -> Result<Vec<u8>, SyncError> {
+    pad_to_bucket(serialized, MEMBERSHIP_PADDING_BUCKETS)
+}
+
+/// Recover a serialized membership entry padded by [`pad_membership_entry`].
+pub fn unpad_membership_entry(padded: &[u8]) -> Result<Vec<u8>, SyncError> {
+    unpad(padded, MEMBERSHIP_PADDING_BUCKETS)
+}
+
 /// Encrypt a membership entry payload for the membership log.
 ///
-/// Uses v4 encryption with AAD binding to (spaceId, seq).
+/// Sealed in [`WireVersion::LATEST`] with AAD binding to (spaceId, seq).
 pub fn encrypt_membership_payload(
     payload: &str,
     key: &[u8],
@@ -319,10 +1005,10 @@ pub fn encrypt_membership_payload(
         space_id: space_id.to_string(),
         record_id: seq.to_string(),
     };
-    Ok(encrypt_v4(payload.as_bytes(), key, Some(&context))?)
+    WireVersion::LATEST.seal(payload.as_bytes(), key, &context)
 }
 
-/// Decrypt a membership log entry payload.
+/// Decrypt a membership log entry payload in any registered wire version.
 pub fn decrypt_membership_payload(
     encrypted: &[u8],
     key: &[u8],
@@ -333,7 +1019,7 @@ pub fn decrypt_membership_payload(
         space_id: space_id.to_string(),
         record_id: seq.to_string(),
     };
-    let plaintext = decrypt_v4(encrypted, key, Some(&context))?;
+    let plaintext = WireVersion::open(encrypted, key, &context)?;
     String::from_utf8(plaintext)
         .map_err(|e| SyncError::InvalidMembershipEntry(format!("UTF-8 decode: {}", e)))
 }
@@ -401,6 +1087,17 @@ mod tests {
         assert!(parse_membership_entry(r#"{"t":"d"}"#).is_err());
     }
 
+    #[test]
+    fn parse_rejects_out_of_range_epoch() {
+        // 2^32 + 1 used to truncate to epoch 1.
+        let json = r#"{"u":"x","t":"d","s":"AA","p":{},"e":4294967297}"#;
+        assert!(parse_membership_entry(json).is_err());
+        assert!(parse_membership_entry(r#"{"u":"x","t":"d","s":"AA","p":{},"e":-1}"#).is_err());
+
+        let json = r#"{"u":"x","t":"d","s":"AA","p":{},"e":4294967295}"#;
+        assert_eq!(parse_membership_entry(json).unwrap().epoch, Some(u32::MAX));
+    }
+
     #[test]
     fn encrypt_decrypt_membership_round_trip() {
         let mut key = [0u8; 32];
@@ -422,6 +1119,21 @@ mod tests {
         assert!(decrypt_membership_payload(&encrypted, &key, "space-WRONG", 1).is_err());
     }
 
+    #[test]
+    fn membership_payload_version_comes_from_registry() {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).unwrap();
+
+        let mut encrypted = encrypt_membership_payload("payload", &key, "space-1", 1).unwrap();
+        assert_eq!(encrypted[0], WireVersion::LATEST.byte());
+
+        encrypted[0] = 9;
+        assert!(matches!(
+            decrypt_membership_payload(&encrypted, &key, "space-1", 1),
+            Err(SyncError::UnsupportedWireVersion { version: 9, .. })
+        ));
+    }
+
     #[test]
     fn sha256_hash_test() {
         let hash = sha256_hash(b"hello world");
@@ -440,6 +1152,255 @@ mod tests {
         }
     }
 
+    /// Build a valid, self-issued delegation entry for `space_id`.
+    fn signed_delegation(space_id: &str) -> MembershipEntryPayload {
+        signed_delegation_with_ucan_for(space_id, space_id)
+    }
+
+    /// Build a delegation entry for `space_id`, correctly signed but carrying
+    /// a UCAN granted on `ucan_space`.
+    fn signed_delegation_with_ucan_for(ucan_space: &str, space_id: &str) -> MembershipEntryPayload {
+        use betterbase_crypto::signing::{export_public_key_jwk, generate_p256_keypair};
+        use betterbase_crypto::ucan::{encode_did_key, issue_root_ucan, UCANPermission};
+
+        let issuer_key = generate_p256_keypair();
+        let issuer_jwk = export_public_key_jwk(issuer_key.verifying_key());
+        let issuer_did = encode_did_key(&issuer_key).unwrap();
+        let audience_did = encode_did_key(&generate_p256_keypair()).unwrap();
+
+        let ucan = issue_root_ucan(
+            &issuer_key,
+            &issuer_did,
+            &audience_did,
+            ucan_space,
+            UCANPermission::Write,
+            3600,
+            1_700_000_000,
+        )
+        .unwrap();
+        let message = build_membership_signing_message(
+            MembershipEntryType::Delegation,
+            space_id,
+            &issuer_did,
+            &ucan,
+            "",
+            "",
+        );
+        let signature = betterbase_crypto::sign(&issuer_key, &message).unwrap();
+
+        MembershipEntryPayload {
+            ucan,
+            entry_type: MembershipEntryType::Delegation,
+            signature,
+            signer_public_key: issuer_jwk,
+            epoch: Some(1),
+            mailbox_id: None,
+            public_key_jwk: None,
+            signer_handle: None,
+            recipient_handle: None,
+            signing_version: MembershipSigningVersion::V1,
+        }
+    }
+
+    fn revocation_store(space_id: &str, revoked: &[&str], fetched_at: u64) -> TrustStore {
+        let mut store = TrustStore::new();
+        store.put(
+            TrustMaterial::RevocationSet {
+                space_id: space_id.to_string(),
+                revoked: revoked.iter().map(|s| s.to_string()).collect(),
+            },
+            fetched_at,
+        );
+        store
+    }
+
+    const FETCHED: u64 = 1_700_000_000;
+
+    #[test]
+    fn verify_log_with_fresh_trust() {
+        let entry = signed_delegation("space-1");
+        let store = revocation_store("space-1", &[], FETCHED);
+
+        let result =
+            verify_membership_log_with_trust(&[entry], "space-1", &store, FETCHED + 10).unwrap();
+        assert!(result.all_valid());
+        assert!(!result.is_stale());
+        assert_eq!(
+            result.trust,
+            vec![TrustAnnotation {
+                artifact: TrustArtifact::RevocationSet {
+                    space_id: "space-1".to_string(),
+                },
+                freshness: Freshness::Fresh,
+            }]
+        );
+    }
+
+    #[test]
+    fn verify_log_with_stale_trust_is_annotated() {
+        let entry = signed_delegation("space-1");
+        let store = revocation_store("space-1", &[], FETCHED);
+
+        // Default revocation policy: fresh for 5 minutes, stale for an hour after.
+        let now = FETCHED + 30 * 60;
+        let result = verify_membership_log_with_trust(&[entry], "space-1", &store, now).unwrap();
+        assert!(result.all_valid());
+        assert!(result.is_stale());
+        assert_eq!(
+            result.trust[0].freshness,
+            Freshness::Stale { age_seconds: 1800 }
+        );
+    }
+
+    #[test]
+    fn verify_log_refuses_expired_trust() {
+        let entry = signed_delegation("space-1");
+        let store = revocation_store("space-1", &[], FETCHED);
+
+        let now = FETCHED + 2 * 3600;
+        let err = verify_membership_log_with_trust(&[entry], "space-1", &store, now).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::TrustMaterialExpired {
+                age_seconds: 7200,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn verify_log_refuses_missing_trust() {
+        let entry = signed_delegation("space-1");
+        let store = revocation_store("other-space", &[], FETCHED);
+
+        let err =
+            verify_membership_log_with_trust(&[entry], "space-1", &store, FETCHED).unwrap_err();
+        assert!(matches!(err, SyncError::TrustMaterialMissing { .. }));
+    }
+
+    #[test]
+    fn verify_log_reports_revoked_and_invalid_entries() {
+        let revoked_entry = signed_delegation("space-1");
+        let revoked_id = ucan_revocation_id(&revoked_entry.ucan);
+        let mut tampered = signed_delegation("space-1");
+        tampered.signature[0] ^= 0xff;
+        let good = signed_delegation("space-1");
+        let store = revocation_store("space-1", &[&revoked_id], FETCHED);
+
+        let result = verify_membership_log_with_trust(
+            &[good, revoked_entry, tampered],
+            "space-1",
+            &store,
+            FETCHED,
+        )
+        .unwrap();
+        assert_eq!(
+            result.entries,
+            vec![
+                EntryVerdict::Valid,
+                EntryVerdict::Revoked,
+                EntryVerdict::InvalidSignature,
+            ]
+        );
+        assert!(!result.all_valid());
+    }
+
+    /// A root UCAN from a fresh owner, delegated once to a member.
+    fn delegated_ucan(space_id: &str) -> (String, String) {
+        use betterbase_crypto::signing::generate_p256_keypair;
+        use betterbase_crypto::ucan::{
+            delegate_ucan, encode_did_key, issue_root_ucan, UCANPermission,
+        };
+
+        let (owner, member) = (generate_p256_keypair(), generate_p256_keypair());
+        let owner_did = encode_did_key(&owner).unwrap();
+        let member_did = encode_did_key(&member).unwrap();
+        let root = issue_root_ucan(
+            &owner,
+            &owner_did,
+            &member_did,
+            space_id,
+            UCANPermission::Admin,
+            4 * 3600,
+            FETCHED,
+        )
+        .unwrap();
+        let leaf = delegate_ucan(
+            &member,
+            &member_did,
+            "did:key:zRecipient",
+            space_id,
+            UCANPermission::Write,
+            2 * 3600,
+            &root,
+            FETCHED,
+        )
+        .unwrap();
+        (root, leaf)
+    }
+
+    #[test]
+    fn verify_chain_with_fresh_trust() {
+        let (_, leaf) = delegated_ucan("space-1");
+        let store = revocation_store("space-1", &[], FETCHED);
+
+        let result = verify_ucan_chain_with_trust(&leaf, "space-1", &store, FETCHED + 10).unwrap();
+        assert!(result.is_valid());
+        assert!(!result.is_stale());
+        assert_eq!(result.chain.audience, "did:key:zRecipient");
+        assert_eq!(result.chain.expires_at, FETCHED + 2 * 3600);
+        assert_eq!(
+            result.trust,
+            vec![TrustAnnotation {
+                artifact: TrustArtifact::RevocationSet {
+                    space_id: "space-1".to_string(),
+                },
+                freshness: Freshness::Fresh,
+            }]
+        );
+    }
+
+    #[test]
+    fn verify_chain_reports_revoked_proof_with_stale_trust() {
+        let (root, leaf) = delegated_ucan("space-1");
+        let store = revocation_store("space-1", &[&ucan_revocation_id(&root)], FETCHED);
+
+        let now = FETCHED + 30 * 60;
+        let result = verify_ucan_chain_with_trust(&leaf, "space-1", &store, now).unwrap();
+        assert!(result.revoked, "revoking the root revokes the leaf");
+        assert!(result.is_stale());
+        assert_eq!(
+            result.trust[0].freshness,
+            Freshness::Stale { age_seconds: 1800 }
+        );
+    }
+
+    #[test]
+    fn verify_chain_refuses_expired_or_missing_trust() {
+        let (_, leaf) = delegated_ucan("space-1");
+        let store = revocation_store("space-1", &[], FETCHED);
+
+        // The chain itself is still unexpired; only the revocation set is too old
+        let err =
+            verify_ucan_chain_with_trust(&leaf, "space-1", &store, FETCHED + 4000).unwrap_err();
+        assert!(matches!(err, SyncError::TrustMaterialExpired { .. }));
+
+        let err = verify_ucan_chain_with_trust(&leaf, "space-1", &TrustStore::new(), FETCHED)
+            .unwrap_err();
+        assert!(matches!(err, SyncError::TrustMaterialMissing { .. }));
+    }
+
+    #[test]
+    fn verify_rejects_ucan_for_other_space() {
+        let entry = signed_delegation_with_ucan_for("space-A", "space-B");
+        assert!(!verify_membership_entry(&entry, "space-B").unwrap());
+
+        let store = revocation_store("space-B", &[], FETCHED);
+        let result =
+            verify_membership_log_with_trust(&[entry], "space-B", &store, FETCHED).unwrap();
+        assert_eq!(result.entries, vec![EntryVerdict::InvalidSignature]);
+    }
+
     #[test]
     fn verify_membership_entry_end_to_end() {
         use betterbase_crypto::signing::{export_public_key_jwk, generate_p256_keypair};
@@ -491,6 +1452,7 @@ mod tests {
             public_key_jwk: None,
             signer_handle: Some(signer_handle.to_string()),
             recipient_handle: Some(recipient_handle.to_string()),
+            signing_version: MembershipSigningVersion::V1,
         };
 
         let result = verify_membership_entry(&entry, space_id).unwrap();
@@ -546,12 +1508,65 @@ mod tests {
             public_key_jwk: None,
             signer_handle: None,
             recipient_handle: None,
+            signing_version: MembershipSigningVersion::V1,
         };
 
         let result = verify_membership_entry(&entry, "space-1").unwrap();
         assert!(!result, "Wrong signer should fail verification");
     }
 
+    #[test]
+    fn verify_accepts_ed25519_member_of_p256_admin() {
+        use betterbase_crypto::signing::{
+            export_ed25519_public_key_jwk, generate_ed25519_keypair, generate_p256_keypair,
+            sign_ed25519,
+        };
+        use betterbase_crypto::ucan::{encode_did_key, issue_root_ucan, UCANPermission};
+
+        let admin_key = generate_p256_keypair();
+        let admin_did = encode_did_key(&admin_key).unwrap();
+
+        let member_key = generate_ed25519_keypair();
+        let member_jwk = export_ed25519_public_key_jwk(&member_key.verifying_key());
+        let member_did = encode_did_key_from_jwk(&member_jwk).unwrap();
+
+        let ucan = issue_root_ucan(
+            &admin_key,
+            &admin_did,
+            &member_did,
+            "space-1",
+            UCANPermission::Write,
+            3600,
+            1_700_000_000,
+        )
+        .unwrap();
+        let message = build_membership_signing_message(
+            MembershipEntryType::Accepted,
+            "space-1",
+            &member_did,
+            &ucan,
+            "",
+            "",
+        );
+
+        let mut entry = MembershipEntryPayload {
+            ucan,
+            entry_type: MembershipEntryType::Accepted,
+            signature: sign_ed25519(&member_key, &message).unwrap(),
+            signer_public_key: member_jwk,
+            epoch: Some(1),
+            mailbox_id: None,
+            public_key_jwk: None,
+            signer_handle: None,
+            recipient_handle: None,
+            signing_version: MembershipSigningVersion::V1,
+        };
+        assert!(verify_membership_entry(&entry, "space-1").unwrap());
+
+        entry.signature[0] ^= 0x01;
+        assert!(!verify_membership_entry(&entry, "space-1").unwrap());
+    }
+
     #[test]
     fn handle_validation_edge_cases() {
         // Empty string returns None
@@ -582,6 +1597,7 @@ mod tests {
             public_key_jwk: Some(serde_json::json!({"kty": "EC"})),
             signer_handle: Some("alice@example.com".to_string()),
             recipient_handle: Some("bob@example.com".to_string()),
+            signing_version: MembershipSigningVersion::V1,
         };
 
         let serialized = serialize_membership_entry(&entry);
@@ -599,4 +1615,657 @@ mod tests {
             Some("bob@example.com")
         );
     }
+
+    fn jwt(header: &str, payload: &str) -> String {
+        format!(
+            "{}.{}.c2lnbmF0dXJl",
+            base64url_encode(header.as_bytes()),
+            base64url_encode(payload.as_bytes())
+        )
+    }
+
+    #[test]
+    fn v2_signing_message_ignores_ucan_key_order() {
+        let a = jwt(
+            r#"{"alg":"ES256","typ":"JWT","ucv":"1.0"}"#,
+            r#"{"iss":"did:key:zA","aud":"did:key:zB","cmd":"/space/write","with":"space:s1"}"#,
+        );
+        let b = jwt(
+            r#"{"ucv":"1.0","typ":"JWT","alg":"ES256"}"#,
+            r#"{"with":"space:s1","cmd":"/space/write","aud":"did:key:zB","iss":"did:key:zA"}"#,
+        );
+        let build_v1 = |ucan: &str| {
+            build_membership_signing_message(
+                MembershipEntryType::Delegation,
+                "space-1",
+                "did:key:zA",
+                ucan,
+                "alice@example.com",
+                "",
+            )
+        };
+        let build_v2 = |ucan: &str| {
+            build_membership_signing_message_v2(
+                MembershipEntryType::Delegation,
+                "space-1",
+                "did:key:zA",
+                ucan,
+                "alice@example.com",
+                "",
+            )
+            .unwrap()
+        };
+
+        assert_ne!(build_v1(&a), build_v1(&b));
+        assert_eq!(build_v2(&a), build_v2(&b));
+
+        let msg = String::from_utf8(build_v2(&a)).unwrap();
+        assert!(msg.starts_with("betterbase:membership:v2\0d\0space-1\0did:key:zA\0"));
+        assert!(msg.ends_with(".c2lnbmF0dXJl\0alice@example.com\0"));
+
+        assert!(build_membership_signing_message_v2(
+            MembershipEntryType::Delegation,
+            "space-1",
+            "did:key:zA",
+            "not-a-jwt",
+            "",
+            "",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn verify_v2_entry_and_round_trip_version() {
+        use betterbase_crypto::signing::{export_public_key_jwk, generate_p256_keypair};
+        use betterbase_crypto::ucan::{encode_did_key, issue_root_ucan, UCANPermission};
+
+        let issuer_key = generate_p256_keypair();
+        let issuer_did = encode_did_key(&issuer_key).unwrap();
+        let audience_did = encode_did_key(&generate_p256_keypair()).unwrap();
+        let ucan = issue_root_ucan(
+            &issuer_key,
+            &issuer_did,
+            &audience_did,
+            "space-1",
+            UCANPermission::Write,
+            3600,
+            1_700_000_000,
+        )
+        .unwrap();
+
+        let message = build_membership_signing_message_v2(
+            MembershipEntryType::Delegation,
+            "space-1",
+            &issuer_did,
+            &ucan,
+            "",
+            "",
+        )
+        .unwrap();
+        let mut entry = MembershipEntryPayload {
+            ucan,
+            entry_type: MembershipEntryType::Delegation,
+            signature: betterbase_crypto::sign(&issuer_key, &message).unwrap(),
+            signer_public_key: export_public_key_jwk(issuer_key.verifying_key()),
+            epoch: None,
+            mailbox_id: None,
+            public_key_jwk: None,
+            signer_handle: None,
+            recipient_handle: None,
+            signing_version: MembershipSigningVersion::V2,
+        };
+        assert!(verify_membership_entry(&entry, "space-1").unwrap());
+
+        let serialized = serialize_membership_entry(&entry);
+        assert!(serialized.contains(r#""v":2"#));
+        let reparsed = parse_membership_entry(&serialized).unwrap();
+        assert_eq!(reparsed.signing_version, MembershipSigningVersion::V2);
+        assert!(verify_membership_entry(&reparsed, "space-1").unwrap());
+
+        // The prefix separates the formats: a v2 signature is not a v1 one.
+        entry.signing_version = MembershipSigningVersion::V1;
+        assert!(!verify_membership_entry(&entry, "space-1").unwrap());
+    }
+
+    #[test]
+    fn parse_signing_version_field() {
+        let base = r#""u":"x","t":"d","s":"AA","p":{}"#;
+        let parse = |extra: &str| parse_membership_entry(&format!("{{{base}{extra}}}"));
+        assert_eq!(
+            parse("").unwrap().signing_version,
+            MembershipSigningVersion::V1
+        );
+        assert_eq!(
+            parse(r#","v":1"#).unwrap().signing_version,
+            MembershipSigningVersion::V1
+        );
+        assert_eq!(
+            parse(r#","v":2"#).unwrap().signing_version,
+            MembershipSigningVersion::V2
+        );
+        assert!(parse(r#","v":3"#).is_err());
+        assert!(parse(r#","v":"2""#).is_err());
+    }
+
+    // -- MembershipState --
+
+    /// A keypair kept as JWKs so tests need no direct p256 dependency.
+    struct Party {
+        did: String,
+        public_jwk: serde_json::Value,
+        private_jwk: serde_json::Value,
+    }
+
+    fn party() -> Party {
+        use betterbase_crypto::signing::{
+            export_private_key_jwk, export_public_key_jwk, generate_p256_keypair,
+        };
+        use betterbase_crypto::ucan::encode_did_key;
+
+        let key = generate_p256_keypair();
+        Party {
+            did: encode_did_key(&key).unwrap(),
+            public_jwk: export_public_key_jwk(key.verifying_key()),
+            private_jwk: export_private_key_jwk(&key),
+        }
+    }
+
+    /// Replay time for tests: after every grant is issued, before any expires.
+    const NOW: u64 = 1_700_000_100;
+
+    fn grant(admin: &Party, member: &Party) -> String {
+        use betterbase_crypto::ucan::UCANPermission;
+        grant_with(admin, member, UCANPermission::Write)
+    }
+
+    fn grant_with(
+        admin: &Party,
+        member: &Party,
+        permission: betterbase_crypto::ucan::UCANPermission,
+    ) -> String {
+        use betterbase_crypto::signing::import_private_key_jwk;
+        use betterbase_crypto::ucan::issue_root_ucan;
+
+        let key = import_private_key_jwk(&admin.private_jwk).unwrap();
+        issue_root_ucan(
+            &key,
+            &admin.did,
+            &member.did,
+            "space-1",
+            permission,
+            3600,
+            1_700_000_000,
+        )
+        .unwrap()
+    }
+
+    fn entry(
+        signer: &Party,
+        ucan: &str,
+        entry_type: MembershipEntryType,
+        epoch: u32,
+    ) -> MembershipEntryPayload {
+        entry_with_handles(
+            signer,
+            ucan,
+            entry_type,
+            epoch,
+            None,
+            Some("bob@example.com"),
+        )
+    }
+
+    /// [`entry`] with the given handles, signed over them.
+    fn entry_with_handles(
+        signer: &Party,
+        ucan: &str,
+        entry_type: MembershipEntryType,
+        epoch: u32,
+        signer_handle: Option<&str>,
+        recipient_handle: Option<&str>,
+    ) -> MembershipEntryPayload {
+        use betterbase_crypto::signing::import_private_key_jwk;
+
+        let message = build_membership_signing_message(
+            entry_type,
+            "space-1",
+            &signer.did,
+            ucan,
+            signer_handle.unwrap_or(""),
+            recipient_handle.unwrap_or(""),
+        );
+        let key = import_private_key_jwk(&signer.private_jwk).unwrap();
+        MembershipEntryPayload {
+            ucan: ucan.to_string(),
+            entry_type,
+            signature: betterbase_crypto::sign(&key, &message).unwrap(),
+            signer_public_key: signer.public_jwk.clone(),
+            epoch: Some(epoch),
+            mailbox_id: None,
+            public_key_jwk: None,
+            signer_handle: signer_handle.map(str::to_string),
+            recipient_handle: recipient_handle.map(str::to_string),
+            signing_version: MembershipSigningVersion::V1,
+        }
+    }
+
+    fn status(state: &MembershipState, member: &Party) -> MemberStatus {
+        state.member(&member.did).unwrap().status
+    }
+
+    #[test]
+    fn replay_tracks_invite_accept_and_decline() {
+        let (admin, bob, carol) = (party(), party(), party());
+        let to_bob = grant(&admin, &bob);
+        let to_carol = grant(&admin, &carol);
+        let log = [
+            entry(&admin, &to_bob, MembershipEntryType::Delegation, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Delegation, 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            entry(&carol, &to_carol, MembershipEntryType::Declined, 1),
+        ];
+
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert_eq!(status(&state, &bob), MemberStatus::Active);
+        assert_eq!(status(&state, &carol), MemberStatus::Declined);
+        assert!(state.is_member(&bob.did));
+        assert!(!state.is_member(&carol.did));
+        assert!(!state.is_member(&admin.did));
+
+        let active: Vec<&str> = state.active_members().map(|m| m.did.as_str()).collect();
+        assert_eq!(active, vec![bob.did.as_str()]);
+        let record = state.member(&bob.did).unwrap();
+        assert_eq!(record.command, "/space/write");
+        assert_eq!(record.handle.as_deref(), Some("bob@example.com"));
+        assert_eq!(state.members().count(), 2);
+    }
+
+    #[test]
+    fn replay_of_empty_log_has_no_members() {
+        let state = MembershipState::replay(&[], "space-1", NOW).unwrap();
+        assert_eq!(state.members().count(), 0);
+        assert!(!state.is_member("did:key:zAnyone"));
+    }
+
+    #[test]
+    fn least_writer_permission_counts_every_write_delegation() {
+        use betterbase_crypto::ucan::UCANPermission;
+
+        let (admin, bob, carol, dave) = (party(), party(), party(), party());
+        let to_bob = grant_with(&admin, &bob, UCANPermission::Admin);
+        let to_carol = grant_with(&admin, &carol, UCANPermission::Read);
+        let to_dave = grant(&admin, &dave);
+        let mut log = vec![
+            entry(&admin, &to_bob, MembershipEntryType::Delegation, 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Delegation, 1),
+            entry(&carol, &to_carol, MembershipEntryType::Accepted, 1),
+        ];
+        let state = MembershipState::replay(&[], "space-1", NOW).unwrap();
+        assert_eq!(state.least_writer_permission(), UCANPermission::Admin);
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert_eq!(state.least_writer_permission(), UCANPermission::Admin);
+
+        // A revoked writer still counts: their deletes may arrive late
+        log.push(entry(&admin, &to_dave, MembershipEntryType::Delegation, 1));
+        log.push(entry(&dave, &to_dave, MembershipEntryType::Accepted, 1));
+        log.push(entry(&admin, &to_dave, MembershipEntryType::Revoked, 2));
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert_eq!(status(&state, &dave), MemberStatus::Revoked);
+        assert_eq!(state.least_writer_permission(), UCANPermission::Write);
+    }
+
+    #[test]
+    fn second_accept_is_idempotent() {
+        let (admin, bob) = (party(), party());
+        let ucan = grant(&admin, &bob);
+        let accept = entry(&bob, &ucan, MembershipEntryType::Accepted, 1);
+        let log = [
+            entry(&admin, &ucan, MembershipEntryType::Delegation, 1),
+            accept.clone(),
+            accept,
+        ];
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert_eq!(status(&state, &bob), MemberStatus::Active);
+        assert_eq!(state.active_members().count(), 1);
+    }
+
+    #[test]
+    fn revoke_before_accept_and_late_accept() {
+        let (admin, bob) = (party(), party());
+        let ucan = grant(&admin, &bob);
+        let log = [
+            entry(&admin, &ucan, MembershipEntryType::Delegation, 1),
+            entry(&admin, &ucan, MembershipEntryType::Revoked, 2),
+            // Accepting a revoked delegation must not reactivate it
+            entry(&bob, &ucan, MembershipEntryType::Accepted, 2),
+        ];
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        let record = state.member(&bob.did).unwrap();
+        assert_eq!(record.status, MemberStatus::Revoked);
+        assert_eq!(record.revoked_at_epoch, Some(2));
+        assert!(!state.is_member(&bob.did));
+    }
+
+    #[test]
+    fn reinvite_after_revocation() {
+        let (admin, bob) = (party(), party());
+        let first = grant(&admin, &bob);
+        let second = grant(&admin, &bob);
+        let mut log = vec![
+            entry(&admin, &first, MembershipEntryType::Delegation, 1),
+            entry(&bob, &first, MembershipEntryType::Accepted, 1),
+            entry(&admin, &first, MembershipEntryType::Revoked, 3),
+            entry(&admin, &second, MembershipEntryType::Delegation, 4),
+        ];
+
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        let record = state.member(&bob.did).unwrap();
+        assert_eq!(record.status, MemberStatus::Invited);
+        assert_eq!(record.ucan, second);
+        assert_eq!(record.revoked_at_epoch, None);
+
+        // Entries for the superseded delegation no longer apply
+        log.push(entry(&admin, &first, MembershipEntryType::Revoked, 4));
+        log.push(entry(&bob, &second, MembershipEntryType::Accepted, 4));
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert!(state.is_member(&bob.did));
+    }
+
+    #[test]
+    fn replay_ignores_entries_for_unknown_delegations() {
+        let (admin, bob) = (party(), party());
+        let ucan = grant(&admin, &bob);
+        let log = [
+            entry(&bob, &ucan, MembershipEntryType::Accepted, 1),
+            entry(&admin, &ucan, MembershipEntryType::Revoked, 1),
+        ];
+        let state = MembershipState::replay(&log, "space-1", NOW).unwrap();
+        assert!(state.member(&bob.did).is_none());
+    }
+
+    /// Grant issued at `1_700_000_000`, expiring an hour later.
+    const GRANT_EXPIRES_AT: u64 = 1_700_003_600;
+
+    fn ucans(log: &[MembershipEntryPayload]) -> Vec<(&str, MembershipEntryType)> {
+        log.iter()
+            .map(|e| (e.ucan.as_str(), e.entry_type))
+            .collect()
+    }
+
+    #[test]
+    fn compact_log_drops_revoked_delegations() {
+        let (admin, bob, carol) = (party(), party(), party());
+        let to_bob = grant(&admin, &bob);
+        let to_carol = grant(&admin, &carol);
+        let accept_carol = entry(&carol, &to_carol, MembershipEntryType::Accepted, 1);
+        let log = [
+            entry(&admin, &to_bob, MembershipEntryType::Delegation, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Delegation, 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            accept_carol.clone(),
+            accept_carol,
+            entry(&admin, &to_bob, MembershipEntryType::Revoked, 2),
+        ];
+
+        let compacted = compact_log(&log, "space-1", 1_700_000_100).unwrap();
+        assert_eq!(
+            ucans(&compacted),
+            vec![
+                (to_carol.as_str(), MembershipEntryType::Delegation),
+                (to_carol.as_str(), MembershipEntryType::Accepted),
+            ]
+        );
+
+        let state = MembershipState::replay(&compacted, "space-1", NOW).unwrap();
+        assert!(state.is_member(&carol.did));
+        assert!(state.member(&bob.did).is_none());
+    }
+
+    #[test]
+    fn compact_log_removes_expired_delegations() {
+        let (admin, bob, carol) = (party(), party(), party());
+        let to_bob = grant(&admin, &bob);
+        let to_carol = grant(&admin, &carol);
+        let log = [
+            entry(&admin, &to_bob, MembershipEntryType::Delegation, 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Delegation, 1),
+            entry(&carol, &to_carol, MembershipEntryType::Declined, 1),
+        ];
+
+        let before = compact_log(&log, "space-1", GRANT_EXPIRES_AT - 1).unwrap();
+        assert_eq!(before.len(), 4);
+        let state = MembershipState::replay(&before, "space-1", NOW).unwrap();
+        assert!(state.is_member(&bob.did));
+        assert_eq!(status(&state, &carol), MemberStatus::Declined);
+
+        let after = compact_log(&log, "space-1", GRANT_EXPIRES_AT).unwrap();
+        assert!(after.is_empty());
+    }
+
+    #[test]
+    fn replay_expires_delegations_at_the_compaction_cutoff() {
+        let (admin, bob, carol, dave) = (party(), party(), party(), party());
+        let to_bob = grant(&admin, &bob);
+        let to_carol = grant(&admin, &carol);
+        let to_dave = grant(&admin, &dave);
+        let log = [
+            entry(&admin, &to_bob, MembershipEntryType::Delegation, 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Delegation, 1),
+            entry(&admin, &to_dave, MembershipEntryType::Delegation, 1),
+            entry(&dave, &to_dave, MembershipEntryType::Declined, 1),
+        ];
+
+        let before = MembershipState::replay(&log, "space-1", GRANT_EXPIRES_AT - 1).unwrap();
+        assert!(before.is_member(&bob.did));
+        assert_eq!(status(&before, &carol), MemberStatus::Invited);
+        assert_eq!(
+            before.member(&bob.did).unwrap().expires_at,
+            Some(GRANT_EXPIRES_AT)
+        );
+
+        let after = MembershipState::replay(&log, "space-1", GRANT_EXPIRES_AT).unwrap();
+        assert!(!after.is_member(&bob.did));
+        assert_eq!(status(&after, &bob), MemberStatus::Expired);
+        assert_eq!(status(&after, &carol), MemberStatus::Expired);
+        // A settled decline stays what it was
+        assert_eq!(status(&after, &dave), MemberStatus::Declined);
+        assert_eq!(after.active_members().count(), 0);
+    }
+
+    #[test]
+    fn compact_log_keeps_only_the_current_delegation() {
+        let (admin, bob) = (party(), party());
+        let first = grant(&admin, &bob);
+        let second = grant(&admin, &bob);
+        let log = [
+            entry(&admin, &first, MembershipEntryType::Delegation, 1),
+            entry(&bob, &first, MembershipEntryType::Accepted, 1),
+            entry(&admin, &second, MembershipEntryType::Delegation, 2),
+        ];
+
+        let compacted = compact_log(&log, "space-1", 1_700_000_100).unwrap();
+        assert_eq!(
+            ucans(&compacted),
+            vec![(second.as_str(), MembershipEntryType::Delegation)]
+        );
+    }
+
+    #[test]
+    fn compact_log_rejects_unverifiable_entries() {
+        let (admin, bob, mallory) = (party(), party(), party());
+        let ucan = grant(&admin, &bob);
+        let log = [
+            entry(&admin, &ucan, MembershipEntryType::Delegation, 1),
+            entry(&mallory, &ucan, MembershipEntryType::Accepted, 1),
+        ];
+        let err = compact_log(&log, "space-1", 1_700_000_100).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::InvalidMembershipLogEntry { index: 1, .. }
+        ));
+    }
+
+    fn delegation_to(
+        admin: &Party,
+        ucan: &str,
+        mailbox_id: &str,
+        epoch: u32,
+    ) -> MembershipEntryPayload {
+        MembershipEntryPayload {
+            mailbox_id: Some(mailbox_id.to_string()),
+            ..entry(admin, ucan, MembershipEntryType::Delegation, epoch)
+        }
+    }
+
+    #[test]
+    fn detect_conflicts_finds_none_in_a_clean_log() {
+        let (admin, bob, carol) = (party(), party(), party());
+        let to_bob = grant(&admin, &bob);
+        let to_carol = grant(&admin, &carol);
+        let log = [
+            delegation_to(&admin, &to_bob, "mbx-bob", 1),
+            delegation_to(&admin, &to_carol, "mbx-carol", 1),
+            entry(&bob, &to_bob, MembershipEntryType::Accepted, 1),
+            entry(&admin, &to_carol, MembershipEntryType::Revoked, 2),
+        ];
+        assert!(detect_conflicts(&log).is_empty());
+        assert!(detect_conflicts(&[]).is_empty());
+    }
+
+    #[test]
+    fn detect_conflicts_flags_double_delegation_with_mismatched_permissions() {
+        use betterbase_crypto::ucan::UCANPermission;
+
+        let (alice, dave, bob) = (party(), party(), party());
+        let log = [
+            delegation_to(&alice, &grant(&alice, &bob), "mbx-bob", 1),
+            // Same permission from a second admin is redundant, not a conflict
+            delegation_to(&dave, &grant(&dave, &bob), "mbx-bob", 1),
+            delegation_to(
+                &alice,
+                &grant_with(&alice, &bob, UCANPermission::Admin),
+                "mbx-bob",
+                1,
+            ),
+        ];
+        assert_eq!(
+            detect_conflicts(&log),
+            vec![MembershipConflict {
+                reason: ConflictReason::MismatchedPermissions,
+                entry: 2,
+                conflicts_with: Some(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn detect_conflicts_flags_revokes_without_a_delegation() {
+        use betterbase_crypto::ucan::UCANPermission;
+
+        let (admin, bob) = (party(), party());
+        let first = grant(&admin, &bob);
+        let log = [
+            entry(&admin, &first, MembershipEntryType::Revoked, 1),
+            delegation_to(&admin, &first, "mbx-bob", 1),
+            entry(&admin, &first, MembershipEntryType::Revoked, 2),
+            // Re-delegating after a revoke may change the permission
+            delegation_to(
+                &admin,
+                &grant_with(&admin, &bob, UCANPermission::Read),
+                "mbx-bob",
+                3,
+            ),
+        ];
+        assert_eq!(
+            detect_conflicts(&log),
+            vec![MembershipConflict {
+                reason: ConflictReason::RevokeWithoutDelegation,
+                entry: 0,
+                conflicts_with: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn replay_names_the_failing_entry() {
+        let (admin, bob, mallory) = (party(), party(), party());
+        let ucan = grant(&admin, &bob);
+        let log = [
+            entry(&admin, &ucan, MembershipEntryType::Delegation, 1),
+            // Only the audience may accept
+            entry(&mallory, &ucan, MembershipEntryType::Accepted, 1),
+        ];
+        let err = MembershipState::replay(&log, "space-1", NOW).unwrap_err();
+        assert!(
+            matches!(err, SyncError::InvalidMembershipLogEntry { index: 1, .. }),
+            "{err}"
+        );
+
+        let mut malformed = entry(&admin, &ucan, MembershipEntryType::Delegation, 1);
+        malformed.ucan = "not-a-jwt".to_string();
+        let err = MembershipState::replay(&[malformed], "space-1", NOW).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::InvalidMembershipLogEntry { index: 0, .. }
+        ));
+
+        let err = MembershipState::replay(&log[..1], "space-2", NOW).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::InvalidMembershipLogEntry { index: 0, .. }
+        ));
+    }
+
+    // -- Padding --
+
+    #[test]
+    fn padded_entries_are_indistinguishable_by_size() {
+        let (admin, bob) = (party(), party());
+        let ucan = grant(&admin, &bob);
+
+        // Handles are signed, so set them before signing
+        let mut delegation = entry_with_handles(
+            &admin,
+            &ucan,
+            MembershipEntryType::Delegation,
+            1,
+            Some("alice@example.com"),
+            Some("bob@example.com"),
+        );
+        delegation.mailbox_id = Some("mbx-0123456789abcdef".to_string());
+        delegation.public_key_jwk = Some(bob.public_jwk.clone());
+        let accept = entry_with_handles(&bob, &ucan, MembershipEntryType::Accepted, 1, None, None);
+        let revoke = entry(&admin, &ucan, MembershipEntryType::Revoked, 2);
+
+        let serialized: Vec<String> = [&delegation, &accept, &revoke]
+            .iter()
+            .map(|e| serialize_membership_entry(e))
+            .collect();
+        assert_ne!(serialized[0].len(), serialized[1].len());
+
+        let padded: Vec<Vec<u8>> = serialized
+            .iter()
+            .map(|s| pad_membership_entry(s.as_bytes()).unwrap())
+            .collect();
+        assert!(padded
+            .iter()
+            .all(|p| p.len() == MEMBERSHIP_PADDING_BUCKETS[0]));
+
+        for (original, padded) in serialized.iter().zip(&padded) {
+            let recovered = unpad_membership_entry(padded).unwrap();
+            assert_eq!(recovered, original.as_bytes());
+            let parsed = parse_membership_entry(std::str::from_utf8(&recovered).unwrap());
+            assert!(verify_membership_entry(&parsed.unwrap(), "space-1").unwrap());
+        }
+    }
+
+    #[test]
+    fn oversized_membership_entry_is_rejected() {
+        let too_big = vec![b'x'; MEMBERSHIP_PADDING_BUCKETS[3]];
+        assert!(pad_membership_entry(&too_big).is_err());
+        assert!(unpad_membership_entry(&[0, 1]).is_err());
+    }
 }
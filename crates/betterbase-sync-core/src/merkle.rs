@@ -0,0 +1,250 @@
+//! Merkle tree over a space's record metadata MACs.
+//!
+//! Client and server each build a tree over their `(record id, mac)` pairs and
+//! compare roots: equal roots mean identical record sets. On a mismatch, an
+//! inclusion proof for a single record shows whether that record is the one
+//! that diverged.
+//!
+//! The tree shape and proof format follow RFC 9162 §2.1 (Certificate
+//! Transparency): leaves are sorted by id, split at the largest power of two,
+//! and leaf/node hashes are domain-separated with a `0x00` / `0x01` prefix.
+
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Merkle tree over `(id, mac)` pairs, sorted by id.
+#[derive(Debug, Clone)]
+pub struct SpaceMerkleTree {
+    ids: Vec<String>,
+    leaves: Vec<[u8; 32]>,
+    root: [u8; 32],
+}
+
+/// Inclusion proof for one record: the sibling hashes from its leaf up to
+/// the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// Position of the record's leaf in id order.
+    pub index: usize,
+    /// Number of leaves in the tree the proof was built from.
+    pub leaf_count: usize,
+    /// Sibling hashes, leaf level first.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl SpaceMerkleTree {
+    /// Build the tree. Input order does not matter; pairs are sorted by id
+    /// (then mac, should an id repeat) before hashing.
+    pub fn new<S: AsRef<str>>(record_macs: &[(S, [u8; 32])]) -> Self {
+        let mut sorted: Vec<(&str, &[u8; 32])> = record_macs
+            .iter()
+            .map(|(id, mac)| (id.as_ref(), mac))
+            .collect();
+        sorted.sort_unstable();
+
+        let leaves: Vec<[u8; 32]> = sorted.iter().map(|(id, mac)| leaf_hash(id, mac)).collect();
+        let root = subtree_root(&leaves);
+        Self {
+            ids: sorted.into_iter().map(|(id, _)| id.to_string()).collect(),
+            leaves,
+            root,
+        }
+    }
+
+    /// Root hash. An empty tree has the root `SHA-256("")`.
+    pub fn root(&self) -> [u8; 32] {
+        self.root
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Inclusion proof for `id`, or `None` if the tree has no such record.
+    pub fn merkle_proof(&self, id: &str) -> Option<MerkleProof> {
+        let index = self.ids.binary_search_by(|i| i.as_str().cmp(id)).ok()?;
+        let mut siblings = Vec::new();
+        audit_path(index, &self.leaves, &mut siblings);
+        Some(MerkleProof {
+            index,
+            leaf_count: self.leaves.len(),
+            siblings,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Check that `(id, mac)` is included in the tree with root `root`.
+    pub fn verify(&self, root: &[u8; 32], id: &str, mac: &[u8; 32]) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
+        // RFC 9162 §2.1.3.2
+        let mut f_n = self.index;
+        let mut s_n = self.leaf_count - 1;
+        let mut r = leaf_hash(id, mac);
+        for sibling in &self.siblings {
+            if s_n == 0 {
+                return false;
+            }
+            if f_n & 1 == 1 || f_n == s_n {
+                r = node_hash(sibling, &r);
+                while f_n & 1 == 0 && f_n != 0 {
+                    f_n >>= 1;
+                    s_n >>= 1;
+                }
+            } else {
+                r = node_hash(&r, sibling);
+            }
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+        s_n == 0 && r == *root
+    }
+}
+
+/// Root of the Merkle tree over `record_macs` (see [`SpaceMerkleTree`]).
+pub fn compute_space_root<S: AsRef<str>>(record_macs: &[(S, [u8; 32])]) -> [u8; 32] {
+    SpaceMerkleTree::new(record_macs).root()
+}
+
+fn leaf_hash(id: &str, mac: &[u8; 32]) -> [u8; 32] {
+    // Length-prefix the id so (id, mac) boundaries are unambiguous
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update((id.len() as u32).to_be_bytes());
+    hasher.update(id.as_bytes());
+    hasher.update(mac);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two strictly less than `n` (for `n >= 2`).
+fn split_point(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn subtree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    match leaves.len() {
+        0 => Sha256::digest(b"").into(),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+        }
+    }
+}
+
+fn audit_path(index: usize, leaves: &[[u8; 32]], out: &mut Vec<[u8; 32]>) {
+    let n = leaves.len();
+    if n <= 1 {
+        return;
+    }
+    let k = split_point(n);
+    if index < k {
+        audit_path(index, &leaves[..k], out);
+        out.push(subtree_root(&leaves[k..]));
+    } else {
+        audit_path(index - k, &leaves[k..], out);
+        out.push(subtree_root(&leaves[..k]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mac(byte: u8) -> [u8; 32] {
+        [byte; 32]
+    }
+
+    fn records(n: u8) -> Vec<(String, [u8; 32])> {
+        (0..n).map(|i| (format!("rec-{i:03}"), mac(i))).collect()
+    }
+
+    #[test]
+    fn root_is_order_independent() {
+        let forward = records(7);
+        let mut reversed = forward.clone();
+        reversed.reverse();
+        assert_eq!(compute_space_root(&forward), compute_space_root(&reversed));
+    }
+
+    #[test]
+    fn root_changes_when_any_mac_changes() {
+        let base = records(9);
+        let root = compute_space_root(&base);
+        for i in 0..base.len() {
+            let mut changed = base.clone();
+            changed[i].1[0] ^= 1;
+            assert_ne!(compute_space_root(&changed), root, "record {i}");
+        }
+    }
+
+    #[test]
+    fn root_changes_when_record_added_or_removed() {
+        let base = records(4);
+        let root = compute_space_root(&base);
+        assert_ne!(compute_space_root(&base[..3]), root);
+        assert_ne!(compute_space_root(&records(5)), root);
+    }
+
+    #[test]
+    fn empty_and_single_leaf_roots() {
+        let empty: [(&str, [u8; 32]); 0] = [];
+        assert_eq!(
+            hex::encode(compute_space_root(&empty)),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            compute_space_root(&[("a", mac(1))]),
+            leaf_hash("a", &mac(1))
+        );
+    }
+
+    #[test]
+    fn inclusion_proofs_verify_for_every_leaf() {
+        for n in 1..=17 {
+            let recs = records(n);
+            let tree = SpaceMerkleTree::new(&recs);
+            for (id, m) in &recs {
+                let proof = tree.merkle_proof(id).expect("proof");
+                assert!(proof.verify(&tree.root(), id, m), "n={n} id={id}");
+            }
+        }
+    }
+
+    #[test]
+    fn inclusion_proof_rejects_wrong_mac_or_root() {
+        let recs = records(6);
+        let tree = SpaceMerkleTree::new(&recs);
+        let proof = tree.merkle_proof("rec-002").expect("proof");
+
+        assert!(!proof.verify(&tree.root(), "rec-002", &mac(99)));
+        assert!(!proof.verify(&tree.root(), "rec-003", &mac(2)));
+        assert!(!proof.verify(&compute_space_root(&records(5)), "rec-002", &mac(2)));
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert!(!truncated.verify(&tree.root(), "rec-002", &mac(2)));
+    }
+
+    #[test]
+    fn proof_for_unknown_id_is_none() {
+        let tree = SpaceMerkleTree::new(&records(3));
+        assert!(tree.merkle_proof("missing").is_none());
+    }
+}
@@ -18,28 +18,150 @@ const LENGTH_PREFIX_SIZE: usize = 4;
 /// Returns `Err` if the data exceeds the largest bucket.
 /// If `buckets` is empty, returns the data unchanged (no padding).
 pub fn pad_to_bucket(data: &[u8], buckets: &[usize]) -> Result<Vec<u8>, SyncError> {
+    let size = estimate_padded_size(data.len(), buckets)?;
     if buckets.is_empty() {
         return Ok(data.to_vec());
     }
+    Ok(write_padded(data, size))
+}
 
-    let total_needed = LENGTH_PREFIX_SIZE + data.len();
-    let bucket_size = buckets
+/// Size in bytes that [`pad_to_bucket`] would produce for a payload of
+/// `len` bytes, without padding anything.
+///
+/// Fails exactly when `pad_to_bucket` does, with the same error. If
+/// `buckets` is empty, returns `len` (no padding).
+pub fn estimate_padded_size(len: usize, buckets: &[usize]) -> Result<usize, SyncError> {
+    if buckets.is_empty() {
+        return Ok(len);
+    }
+    bucket_for(len, buckets)
+}
+
+/// Smallest bucket [`pad_to_bucket`] would use for `len` bytes of data.
+/// `buckets` must be non-empty.
+pub(crate) fn bucket_for(len: usize, buckets: &[usize]) -> Result<usize, SyncError> {
+    let total_needed = LENGTH_PREFIX_SIZE.saturating_add(len);
+    buckets
         .iter()
-        .find(|&&b| b >= total_needed)
+        .copied()
+        .find(|&b| b >= total_needed)
         .ok_or_else(|| {
             SyncError::PaddingError(format!(
                 "data too large: {} bytes exceeds max bucket {}",
-                data.len(),
+                len,
                 buckets.last().unwrap_or(&0)
             ))
-        })?;
+        })
+}
+
+/// Pad data to a fixed-size bucket from a caller-chosen bucket list.
+///
+/// Same format as [`pad_to_bucket`], so [`unpad`] reads the output of
+/// either. Unlike `pad_to_bucket`, `buckets` must be non-empty, non-zero
+/// and strictly increasing: an empty list is an error rather than a
+/// passthrough, so a payload is never written out at its exact size.
+///
+/// Returns [`SyncError::PayloadTooLarge`] if the data exceeds the largest bucket.
+pub fn pad_to_bucket_with(data: &[u8], buckets: &[usize]) -> Result<Vec<u8>, SyncError> {
+    let bucket_size = checked_bucket_for(data.len(), buckets)?;
+    Ok(write_padded(data, bucket_size))
+}
+
+/// Bucket [`pad_to_bucket_with`] would use for `len` bytes of data, after
+/// validating `buckets`.
+fn checked_bucket_for(len: usize, buckets: &[usize]) -> Result<usize, SyncError> {
+    validate_buckets(buckets)?;
+    let total_needed = LENGTH_PREFIX_SIZE.saturating_add(len);
+    buckets
+        .iter()
+        .copied()
+        .find(|&b| b >= total_needed)
+        .ok_or(SyncError::PayloadTooLarge {
+            size: len,
+            max: buckets[buckets.len() - 1],
+        })
+}
 
-    let mut padded = vec![0u8; *bucket_size];
+/// A named bucket ladder, so collections with different size distributions
+/// (chat messages vs. large documents) can each pad to buckets that suit them.
+///
+/// Profiles only matter when padding: [`unpad`] reads the length prefix, so
+/// the profile used for a payload never needs to be stored alongside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaddingProfile {
+    name: String,
+    buckets: Vec<usize>,
+}
+
+impl PaddingProfile {
+    /// Create a profile. `buckets` must be non-empty, non-zero and strictly
+    /// increasing.
+    pub fn new(name: &str, buckets: Vec<usize>) -> Result<Self, SyncError> {
+        validate_buckets(&buckets)?;
+        Ok(Self {
+            name: name.to_string(),
+            buckets,
+        })
+    }
+
+    /// Profile name, for logs and configuration.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The bucket ladder, smallest first.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets
+    }
+
+    /// Pad `data` to the smallest bucket of this profile that fits.
+    pub fn pad(&self, data: &[u8]) -> Result<Vec<u8>, SyncError> {
+        pad_to_bucket_with(data, &self.buckets)
+    }
+
+    /// Padded size for a payload of `len` bytes under this profile.
+    pub fn padded_size(&self, len: usize) -> Result<usize, SyncError> {
+        checked_bucket_for(len, &self.buckets)
+    }
+}
+
+impl Default for PaddingProfile {
+    /// The `"default"` profile, using [`DEFAULT_PADDING_BUCKETS`].
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            buckets: DEFAULT_PADDING_BUCKETS.to_vec(),
+        }
+    }
+}
+
+fn validate_buckets(buckets: &[usize]) -> Result<(), SyncError> {
+    if buckets.is_empty() {
+        return Err(SyncError::PaddingError("bucket list is empty".into()));
+    }
+    if buckets[0] == 0 {
+        return Err(SyncError::PaddingError(
+            "bucket sizes must be non-zero".into(),
+        ));
+    }
+    if let Some(pair) = buckets.windows(2).find(|pair| pair[0] >= pair[1]) {
+        return Err(SyncError::PaddingError(format!(
+            "bucket sizes must be strictly increasing: {} is followed by {}",
+            pair[0], pair[1]
+        )));
+    }
+    Ok(())
+}
+
+/// Write `[u32 LE length][data][zero padding]` into a buffer of `bucket_size`
+/// bytes. The caller guarantees the bucket fits.
+pub(crate) fn write_padded(data: &[u8], bucket_size: usize) -> Vec<u8> {
+    let mut padded = vec![0u8; bucket_size];
     // Write length prefix (u32 LE)
     padded[..4].copy_from_slice(&(data.len() as u32).to_le_bytes());
     padded[LENGTH_PREFIX_SIZE..LENGTH_PREFIX_SIZE + data.len()].copy_from_slice(data);
     // Remaining bytes are already zero
-    Ok(padded)
+    padded
 }
 
 /// Remove padding from data.
@@ -150,4 +272,170 @@ mod tests {
         assert_eq!(padded[2], 0x00);
         assert_eq!(padded[3], 0x00);
     }
+
+    // ------------------------------------------------------------------
+    // pad_to_bucket_with / estimate_padded_size
+    // ------------------------------------------------------------------
+
+    /// Tiny presence-sized buckets plus one far larger than the default max.
+    const CUSTOM_BUCKETS: &[usize] = &[32, 64, 128, 4 * 1048576];
+
+    /// Payload lengths around every bucket boundary, counting both the raw
+    /// bucket size and the size left after the length prefix.
+    fn boundary_lengths(buckets: &[usize]) -> Vec<usize> {
+        let mut lens = vec![0];
+        for &b in buckets {
+            for edge in [b, b - LENGTH_PREFIX_SIZE] {
+                lens.extend([edge.saturating_sub(1), edge, edge + 1]);
+            }
+        }
+        lens
+    }
+
+    #[test]
+    fn pad_with_round_trips_every_bucket_boundary() {
+        for buckets in [DEFAULT_PADDING_BUCKETS, CUSTOM_BUCKETS] {
+            let max = *buckets.last().unwrap();
+            for len in boundary_lengths(buckets) {
+                let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+                let result = pad_to_bucket_with(&data, buckets);
+                if len + LENGTH_PREFIX_SIZE > max {
+                    match result {
+                        Err(SyncError::PayloadTooLarge { size, max: m }) => {
+                            assert_eq!((size, m), (len, max))
+                        }
+                        other => panic!(
+                            "len {len} should exceed {buckets:?}, got {:?}",
+                            other.map(|p| p.len())
+                        ),
+                    }
+                    continue;
+                }
+                let padded = result.unwrap();
+                let expected = buckets
+                    .iter()
+                    .copied()
+                    .find(|&b| b >= len + LENGTH_PREFIX_SIZE)
+                    .unwrap();
+                assert_eq!(padded.len(), expected, "len {len} in {buckets:?}");
+                assert_eq!(estimate_padded_size(len, buckets).unwrap(), padded.len());
+                assert_eq!(unpad(&padded, buckets).unwrap(), data);
+                // The length lives in the payload, so any bucket list unpads it.
+                assert_eq!(unpad(&padded, DEFAULT_PADDING_BUCKETS).unwrap(), data);
+            }
+        }
+    }
+
+    #[test]
+    fn estimate_matches_pad_to_bucket_output_len() {
+        let ladders: [&[usize]; 3] = [DEFAULT_PADDING_BUCKETS, CUSTOM_BUCKETS, &[]];
+        for buckets in ladders {
+            let sizes = (0..=2048)
+                .chain(boundary_lengths(buckets))
+                .chain(boundary_lengths(DEFAULT_PADDING_BUCKETS));
+            for len in sizes {
+                let data = vec![0u8; len];
+                match (
+                    pad_to_bucket(&data, buckets),
+                    estimate_padded_size(len, buckets),
+                ) {
+                    (Ok(padded), Ok(size)) => {
+                        assert_eq!(size, padded.len(), "len {len} in {buckets:?}")
+                    }
+                    // There is no overflow bucket: both reject what doesn't fit.
+                    (Err(SyncError::PaddingError(a)), Err(SyncError::PaddingError(b))) => {
+                        assert_eq!(a, b)
+                    }
+                    (padded, size) => panic!(
+                        "len {len} in {buckets:?}: pad {:?}, estimate {size:?}",
+                        padded.map(|p| p.len())
+                    ),
+                }
+            }
+        }
+        assert_eq!(estimate_padded_size(usize::MAX, &[]).unwrap(), usize::MAX);
+        assert!(matches!(
+            estimate_padded_size(usize::MAX, DEFAULT_PADDING_BUCKETS),
+            Err(SyncError::PaddingError(_))
+        ));
+    }
+
+    #[test]
+    fn pad_with_matches_pad_to_bucket_for_default_buckets() {
+        let data = vec![0x5A; 1000];
+        assert_eq!(
+            pad_to_bucket_with(&data, DEFAULT_PADDING_BUCKETS).unwrap(),
+            pad_to_bucket(&data, DEFAULT_PADDING_BUCKETS).unwrap()
+        );
+    }
+
+    #[test]
+    fn pad_with_covers_payloads_beyond_default_max() {
+        let data = vec![1u8; 2 * 1048576];
+        assert!(pad_to_bucket(&data, DEFAULT_PADDING_BUCKETS).is_err());
+        let padded = pad_to_bucket_with(&data, CUSTOM_BUCKETS).unwrap();
+        assert_eq!(padded.len(), 4 * 1048576);
+        assert_eq!(unpad(&padded, CUSTOM_BUCKETS).unwrap(), data);
+    }
+
+    #[test]
+    fn pad_with_rejects_invalid_bucket_lists() {
+        let invalid: [&[usize]; 4] = [&[], &[0, 64], &[64, 32], &[64, 64, 128]];
+        for buckets in invalid {
+            assert!(
+                matches!(
+                    pad_to_bucket_with(b"x", buckets),
+                    Err(SyncError::PaddingError(_))
+                ),
+                "{buckets:?} should be rejected"
+            );
+            assert!(PaddingProfile::new("invalid", buckets.to_vec()).is_err());
+        }
+    }
+
+    // ------------------------------------------------------------------
+    // PaddingProfile
+    // ------------------------------------------------------------------
+
+    #[test]
+    fn profile_pads_to_smallest_fitting_bucket() {
+        let chat = PaddingProfile::new("chat", vec![64, 128, 512]).unwrap();
+        assert_eq!(chat.name(), "chat");
+        assert_eq!(chat.pad(&[7u8; 60]).unwrap().len(), 64);
+        assert_eq!(chat.pad(&[7u8; 61]).unwrap().len(), 128);
+        assert_eq!(chat.padded_size(200).unwrap(), 512);
+        assert!(matches!(
+            chat.pad(&[7u8; 509]),
+            Err(SyncError::PayloadTooLarge {
+                size: 509,
+                max: 512
+            })
+        ));
+    }
+
+    #[test]
+    fn unpad_recovers_exact_length_under_any_profile() {
+        let profiles = [
+            PaddingProfile::default(),
+            PaddingProfile::new("chat", vec![64, 128, 512]).unwrap(),
+            PaddingProfile::new("docs", vec![65536, 4 * 1048576]).unwrap(),
+        ];
+        for profile in &profiles {
+            for len in [0, 1, 59, 60, 61, 300] {
+                let data = vec![0u8; len];
+                let padded = profile.pad(&data).unwrap();
+                // Unpadding never consults the profile that produced the payload.
+                for other in &profiles {
+                    assert_eq!(unpad(&padded, other.buckets()).unwrap().len(), len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn profile_rejects_invalid_ladder() {
+        assert!(PaddingProfile::new("empty", vec![]).is_err());
+        assert!(PaddingProfile::new("unsorted", vec![128, 64]).is_err());
+        assert_eq!(PaddingProfile::default().buckets(), DEFAULT_PADDING_BUCKETS);
+    }
 }
@@ -1,7 +1,11 @@
-//! DEK re-wrapping and epoch forward derivation.
+//! DEK re-wrapping, post-rewrap verification, and epoch forward derivation.
 
+use crate::epoch_cache::EpochKeyCache;
 use crate::error::SyncError;
-use betterbase_crypto::{derive_next_epoch_key, unwrap_dek, wrap_dek};
+use crate::wire::WireVersion;
+use betterbase_crypto::{
+    derive_next_epoch_key, unwrap_dek, wrap_dek, EncryptionContext, WRAPPED_DEK_SIZE,
+};
 use std::collections::HashMap;
 use zeroize::Zeroize;
 
@@ -46,10 +50,28 @@ pub fn derive_forward(
     Ok(current)
 }
 
+/// A wrapped DEK with the ID of the record or file it belongs to.
+pub type WrappedDek = (String, Vec<u8>);
+
+/// Outcome of [`rewrap_deks`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RewrapReport {
+    /// DEKs re-wrapped under the target epoch key, in input order.
+    pub rewrapped: Vec<WrappedDek>,
+    /// Epoch of each input DEK left untouched because it was already at the
+    /// target epoch, in input order.
+    pub skipped_epochs: Vec<u32>,
+    /// Epoch of the key the caller re-wrapped from.
+    pub source_epoch: u32,
+    /// Epoch every DEK is at afterwards.
+    pub target_epoch: u32,
+}
+
 /// Re-wrap a set of DEKs from their current epoch to a new epoch key.
 ///
 /// Builds a key cache from `current_epoch` to `new_epoch` to handle DEKs
-/// at any intermediate epoch. Returns the re-wrapped DEK bytes.
+/// at any intermediate epoch. DEKs already at `new_epoch` are skipped and
+/// left out of [`RewrapReport::rewrapped`].
 ///
 /// # Arguments
 /// * `wrapped_deks` - Pairs of (id, wrapped_dek_bytes)
@@ -59,13 +81,13 @@ pub fn derive_forward(
 /// * `new_epoch` - Target epoch number
 /// * `space_id` - Space ID for domain separation
 pub fn rewrap_deks(
-    wrapped_deks: &[(String, Vec<u8>)],
+    wrapped_deks: &[WrappedDek],
     current_key: &[u8],
     current_epoch: u32,
     new_key: &[u8],
     new_epoch: u32,
     space_id: &str,
-) -> Result<Vec<(String, Vec<u8>)>, SyncError> {
+) -> Result<RewrapReport, SyncError> {
     if new_epoch <= current_epoch {
         return Err(SyncError::InvalidEpochAdvance {
             new: new_epoch,
@@ -83,12 +105,16 @@ pub fn rewrap_deks(
     }
     derived_key.zeroize();
 
-    let mut result = Vec::new();
+    let mut report = RewrapReport {
+        source_epoch: current_epoch,
+        target_epoch: new_epoch,
+        ..Default::default()
+    };
     for (id, wrapped_dek) in wrapped_deks {
         let dek_epoch = peek_epoch(wrapped_dek)?;
         if dek_epoch == new_epoch {
-            // Already at target epoch — pass through unchanged.
-            result.push((id.clone(), wrapped_dek.clone()));
+            // Already at target epoch — nothing to write back.
+            report.skipped_epochs.push(dek_epoch);
             continue;
         }
 
@@ -101,7 +127,7 @@ pub fn rewrap_deks(
         let rewrapped = wrap_dek(&dek, new_key, new_epoch)?;
         dek.zeroize();
 
-        result.push((id.clone(), rewrapped.to_vec()));
+        report.rewrapped.push((id.clone(), rewrapped.to_vec()));
     }
 
     // Zero derived intermediate keys (not current_key — caller owns it)
@@ -111,7 +137,162 @@ pub fn rewrap_deks(
         }
     }
 
-    Ok(result)
+    Ok(report)
+}
+
+/// A record whose DEK [`rotate_space_epoch`] re-wrapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewrappedRecord {
+    pub record_id: String,
+    /// DEK wrapped under the new epoch key.
+    pub wrapped_dek: [u8; WRAPPED_DEK_SIZE],
+}
+
+/// A record whose DEK [`rotate_space_epoch`] could not re-wrap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationFailure {
+    pub record_id: String,
+    pub reason: String,
+}
+
+/// Outcome of [`rotate_space_epoch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpaceRotationReport {
+    /// Re-wrapped records, in input order.
+    pub rewrapped: Vec<RewrappedRecord>,
+    /// Records left as they were because their DEK could not be unwrapped.
+    pub failures: Vec<RotationFailure>,
+    /// Records already wrapped at the new epoch, left untouched.
+    pub skipped: Vec<String>,
+}
+
+/// Re-wrap every record DEK of a space under `new_epoch_key` in one pass.
+///
+/// DEKs may be wrapped at `old_epoch` or any later epoch below `new_epoch`;
+/// their keys are derived forward from `old_epoch_key`. A DEK that cannot be
+/// unwrapped is reported in [`SpaceRotationReport::failures`] instead of
+/// aborting the rotation. Plaintext DEKs never leave this function and are
+/// zeroized after re-wrapping.
+///
+/// # Arguments
+/// * `records` - `(record_id, wrapped_dek)` pairs
+/// * `old_epoch_key` - Epoch key at `old_epoch` (32 bytes)
+/// * `old_epoch` - Oldest epoch the DEKs may be wrapped at
+/// * `new_epoch_key` - Target epoch key (32 bytes)
+/// * `new_epoch` - Target epoch number (must be > `old_epoch`)
+/// * `space_id` - Space ID for domain separation
+pub fn rotate_space_epoch<I, R, D>(
+    records: I,
+    old_epoch_key: &[u8],
+    old_epoch: u32,
+    new_epoch_key: &[u8],
+    new_epoch: u32,
+    space_id: &str,
+) -> Result<SpaceRotationReport, SyncError>
+where
+    I: IntoIterator<Item = (R, D)>,
+    R: Into<String>,
+    D: AsRef<[u8]>,
+{
+    if new_epoch <= old_epoch {
+        return Err(SyncError::InvalidEpochAdvance {
+            new: new_epoch,
+            current: old_epoch,
+        });
+    }
+
+    let mut cache = EpochKeyCache::new(old_epoch_key, old_epoch, space_id);
+    let mut report = SpaceRotationReport::default();
+    for (record_id, wrapped_dek) in records {
+        let record_id = record_id.into();
+        match rotate_one(
+            &record_id,
+            wrapped_dek.as_ref(),
+            &mut cache,
+            new_epoch_key,
+            new_epoch,
+        ) {
+            Ok(Some(wrapped_dek)) => report.rewrapped.push(RewrappedRecord {
+                record_id,
+                wrapped_dek,
+            }),
+            Ok(None) => report.skipped.push(record_id),
+            Err(e) => report.failures.push(RotationFailure {
+                record_id,
+                reason: e.to_string(),
+            }),
+        }
+    }
+    Ok(report)
+}
+
+/// Re-wrap one DEK, or `None` if it is already at `new_epoch`.
+fn rotate_one(
+    record_id: &str,
+    wrapped_dek: &[u8],
+    cache: &mut EpochKeyCache,
+    new_epoch_key: &[u8],
+    new_epoch: u32,
+) -> Result<Option<[u8; WRAPPED_DEK_SIZE]>, SyncError> {
+    let dek_epoch = peek_epoch(wrapped_dek)?;
+    if dek_epoch == new_epoch {
+        return Ok(None);
+    }
+    if dek_epoch > new_epoch {
+        return Err(SyncError::NoKek {
+            epoch: dek_epoch,
+            record_id: record_id.to_string(),
+        });
+    }
+
+    let (mut dek, _epoch) = unwrap_dek(wrapped_dek, cache.get_kek(dek_epoch)?)?;
+    let rewrapped = wrap_dek(&dek, new_epoch_key, new_epoch);
+    dek.zeroize();
+    Ok(Some(rewrapped?))
+}
+
+/// Outcome of [`verify_epoch_consistency`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpochConsistencyReport {
+    /// Items whose DEK unwrapped and opened its sample blob.
+    pub ok: usize,
+    /// Indices into the input of items that did not.
+    pub failures: Vec<usize>,
+}
+
+impl EpochConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Check that every wrapped DEK belongs to the epoch of `epoch_key`.
+///
+/// Each item is `(wrapped_dek, sample_blob, context)`: the DEK must unwrap
+/// under `epoch_key` and then decrypt `sample_blob`. Run after
+/// [`rewrap_deks`] and only mark the migration complete when the report
+/// has no failures.
+pub fn verify_epoch_consistency(
+    items: &[(Vec<u8>, Vec<u8>, EncryptionContext)],
+    epoch_key: &[u8],
+) -> EpochConsistencyReport {
+    let mut report = EpochConsistencyReport::default();
+    for (index, (wrapped_dek, sample_blob, context)) in items.iter().enumerate() {
+        let opened = match unwrap_dek(wrapped_dek, epoch_key) {
+            Ok((mut dek, _epoch)) => {
+                let opened = WireVersion::open(sample_blob, &dek, context).is_ok();
+                dek.zeroize();
+                opened
+            }
+            Err(_) => false,
+        };
+        if opened {
+            report.ok += 1;
+        } else {
+            report.failures.push(index);
+        }
+    }
+    report
 }
 
 #[cfg(test)]
@@ -181,7 +362,10 @@ mod tests {
         let key2 = derive_next_epoch_key(&key1, space_id, 2).unwrap();
 
         // Rewrap from epoch 1 to epoch 2
-        let rewrapped = rewrap_deks(&wrapped_deks, &key1, 1, &key2, 2, space_id).unwrap();
+        let report = rewrap_deks(&wrapped_deks, &key1, 1, &key2, 2, space_id).unwrap();
+        assert_eq!((report.source_epoch, report.target_epoch), (1, 2));
+        assert!(report.skipped_epochs.is_empty());
+        let rewrapped = report.rewrapped;
 
         assert_eq!(rewrapped.len(), 2);
 
@@ -217,7 +401,9 @@ mod tests {
 
         // Rewrap to epoch 3
         let key3 = derive_next_epoch_key(&key2, space_id, 3).unwrap();
-        let rewrapped = rewrap_deks(&wrapped_deks, &key1, 1, &key3, 3, space_id).unwrap();
+        let rewrapped = rewrap_deks(&wrapped_deks, &key1, 1, &key3, 3, space_id)
+            .unwrap()
+            .rewrapped;
 
         assert_eq!(rewrapped.len(), 2);
         for (_, w) in &rewrapped {
@@ -232,26 +418,37 @@ mod tests {
     }
 
     #[test]
-    fn rewrap_passes_through_already_at_target() {
-        let key = random_key();
+    fn rewrap_skips_deks_already_at_target() {
+        let key1 = random_key();
         let space_id = "space-1";
+        let key2 = derive_next_epoch_key(&key1, space_id, 2).unwrap();
 
-        let dek = generate_dek().unwrap();
-        let wrapped = crypto_wrap_dek(&dek, &key, 2).unwrap(); // Already at epoch 2
+        let dek_old = generate_dek().unwrap();
+        let dek_current = generate_dek().unwrap();
+        let wrapped_deks = vec![
+            (
+                "rec-1".to_string(),
+                crypto_wrap_dek(&dek_current, &key2, 2).unwrap().to_vec(),
+            ),
+            (
+                "rec-2".to_string(),
+                crypto_wrap_dek(&dek_old, &key1, 1).unwrap().to_vec(),
+            ),
+            (
+                "rec-3".to_string(),
+                crypto_wrap_dek(&dek_current, &key2, 2).unwrap().to_vec(),
+            ),
+        ];
 
-        let key2 = derive_next_epoch_key(&key, space_id, 2).unwrap();
-        let result = rewrap_deks(
-            &[("rec-1".to_string(), wrapped.to_vec())],
-            &key,
-            1,
-            &key2,
-            2,
-            space_id,
-        )
-        .unwrap();
+        let report = rewrap_deks(&wrapped_deks, &key1, 1, &key2, 2, space_id).unwrap();
 
-        assert_eq!(result.len(), 1); // Passed through unchanged
-        assert_eq!(result[0].1, wrapped.to_vec());
+        assert_eq!(report.skipped_epochs, vec![2, 2]);
+        assert_eq!(report.rewrapped.len(), 1);
+        assert_eq!(report.rewrapped[0].0, "rec-2");
+        assert_eq!(peek_epoch(&report.rewrapped[0].1).unwrap(), 2);
+        let (unwrapped, _) = unwrap_dek(&report.rewrapped[0].1, &key2).unwrap();
+        assert_eq!(unwrapped, dek_old);
+        assert_eq!((report.source_epoch, report.target_epoch), (1, 2));
     }
 
     #[test]
@@ -260,8 +457,9 @@ mod tests {
         let space_id = "space-1";
         let key2 = derive_next_epoch_key(&key1, space_id, 2).unwrap();
 
-        let result = rewrap_deks(&[], &key1, 1, &key2, 2, space_id).unwrap();
-        assert!(result.is_empty());
+        let report = rewrap_deks(&[], &key1, 1, &key2, 2, space_id).unwrap();
+        assert!(report.rewrapped.is_empty());
+        assert!(report.skipped_epochs.is_empty());
     }
 
     #[test]
@@ -280,4 +478,129 @@ mod tests {
 
         assert_eq!(forward_key, cache_key.to_vec());
     }
+
+    #[test]
+    fn verify_epoch_consistency_flags_dek_left_at_old_epoch() {
+        let key1 = random_key();
+        let space_id = "space-1";
+        let key2 = derive_next_epoch_key(&key1, space_id, 2).unwrap();
+
+        let items: Vec<(Vec<u8>, Vec<u8>, EncryptionContext)> = (0..4)
+            .map(|i| {
+                let context = EncryptionContext {
+                    space_id: space_id.to_string(),
+                    record_id: format!("rec-{i}"),
+                };
+                let dek = generate_dek().unwrap();
+                let blob = WireVersion::V4.seal(b"sample", &dek, &context).unwrap();
+                // Record 2 was missed by the migration and still sits at epoch 1.
+                let wrapped = if i == 2 {
+                    crypto_wrap_dek(&dek, &key1, 1).unwrap()
+                } else {
+                    crypto_wrap_dek(&dek, &key2, 2).unwrap()
+                };
+                (wrapped.to_vec(), blob, context)
+            })
+            .collect();
+
+        let report = verify_epoch_consistency(&items, &key2);
+        assert_eq!(report.ok, 3);
+        assert_eq!(report.failures, vec![2]);
+        assert!(!report.is_consistent());
+
+        let mut fixed = items.clone();
+        fixed[2].0 = rewrap_deks(
+            &[("rec-2".to_string(), items[2].0.clone())],
+            &key1,
+            1,
+            &key2,
+            2,
+            space_id,
+        )
+        .unwrap()
+        .rewrapped
+        .remove(0)
+        .1;
+        let report = verify_epoch_consistency(&fixed, &key2);
+        assert_eq!(report.ok, 4);
+        assert!(report.is_consistent());
+    }
+
+    #[test]
+    fn rotate_space_epoch_rewraps_and_reports_failures() {
+        let space_id = "space-1";
+        let key1 = random_key();
+        let key2 = derive_next_epoch_key(&key1, space_id, 2).unwrap();
+        let new_key = random_key();
+
+        let dek_a = generate_dek().unwrap();
+        let dek_b = generate_dek().unwrap();
+        let records = vec![
+            ("rec-a", crypto_wrap_dek(&dek_a, &key1, 1).unwrap().to_vec()),
+            // Wrapped at an intermediate epoch
+            ("rec-b", crypto_wrap_dek(&dek_b, &key2, 2).unwrap().to_vec()),
+            // Corrupted ciphertext
+            ("rec-c", {
+                let mut bad = crypto_wrap_dek(&dek_a, &key1, 1).unwrap().to_vec();
+                bad[10] ^= 0xff;
+                bad
+            }),
+            ("rec-d", vec![0, 0, 0, 1, 2, 3]),
+            (
+                "rec-e",
+                crypto_wrap_dek(&dek_a, &new_key, 3).unwrap().to_vec(),
+            ),
+        ];
+
+        let report = rotate_space_epoch(records, &key1, 1, &new_key, 3, space_id).unwrap();
+
+        let ids: Vec<&str> = report
+            .rewrapped
+            .iter()
+            .map(|r| r.record_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["rec-a", "rec-b"]);
+        for (record, dek) in report.rewrapped.iter().zip([&dek_a, &dek_b]) {
+            assert_eq!(peek_epoch(&record.wrapped_dek).unwrap(), 3);
+            let (unwrapped, _) = unwrap_dek(&record.wrapped_dek, &new_key).unwrap();
+            assert_eq!(&unwrapped, dek);
+        }
+
+        let failed: Vec<&str> = report
+            .failures
+            .iter()
+            .map(|f| f.record_id.as_str())
+            .collect();
+        assert_eq!(failed, vec!["rec-c", "rec-d"]);
+        assert_eq!(report.skipped, vec!["rec-e".to_string()]);
+    }
+
+    #[test]
+    fn rotate_space_epoch_rejects_non_advancing_epoch() {
+        let key = random_key();
+        let records: Vec<(String, Vec<u8>)> = Vec::new();
+        assert!(matches!(
+            rotate_space_epoch(records, &key, 2, &key, 2, "space-1"),
+            Err(SyncError::InvalidEpochAdvance { new: 2, current: 2 })
+        ));
+    }
+
+    #[test]
+    fn verify_epoch_consistency_flags_mismatched_sample() {
+        let key = random_key();
+        let context = EncryptionContext {
+            space_id: "space-1".to_string(),
+            record_id: "rec-1".to_string(),
+        };
+        let dek = generate_dek().unwrap();
+        let other_dek = generate_dek().unwrap();
+        let blob = WireVersion::V4
+            .seal(b"sample", &other_dek, &context)
+            .unwrap();
+        let wrapped = crypto_wrap_dek(&dek, &key, 1).unwrap();
+
+        let report = verify_epoch_consistency(&[(wrapped.to_vec(), blob, context)], &key);
+        assert_eq!(report.ok, 0);
+        assert_eq!(report.failures, vec![0]);
+    }
 }
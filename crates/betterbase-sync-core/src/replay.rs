@@ -0,0 +1,301 @@
+//! Sliding-window replay guard for sequenced transport frames.
+//!
+//! Tracks the highest accepted sequence plus a bitmap of the `size`
+//! sequences at or below it. A frame is rejected if its sequence was
+//! already accepted or has fallen out of the window. Frames may arrive out
+//! of order as long as they stay within the window.
+//!
+//! [`ReplayGuard`] keeps one window per `(space, sender)`. Both serialize
+//! with serde, so a receiver can persist them and keep rejecting replays
+//! after a restart.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SyncError;
+
+/// Default number of sequences a [`ReplayWindow`] remembers.
+pub const DEFAULT_REPLAY_WINDOW: usize = 1024;
+
+/// Sliding bitmap of recently accepted frame sequences.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "ReplayWindowState", into = "ReplayWindowState")]
+pub struct ReplayWindow {
+    /// Ring of seen bits, indexed by `sequence % size`.
+    bits: Vec<u64>,
+    /// Window size in sequences; a multiple of 64.
+    size: u64,
+    /// Highest accepted sequence, `None` until the first frame.
+    highest: Option<u64>,
+}
+
+impl ReplayWindow {
+    /// A window remembering at least `size` sequences (rounded up to a
+    /// multiple of 64, so zero gives the smallest window).
+    pub fn new(size: usize) -> Self {
+        let words = size.div_ceil(64).max(1);
+        Self {
+            bits: vec![0; words],
+            size: words as u64 * 64,
+            highest: None,
+        }
+    }
+
+    /// Number of sequences the window remembers.
+    pub fn size(&self) -> usize {
+        self.size as usize
+    }
+
+    /// Highest sequence accepted so far.
+    pub fn highest(&self) -> Option<u64> {
+        self.highest
+    }
+
+    /// Fail with [`SyncError::ReplayedFrame`] if `sequence` was already
+    /// accepted or is older than the window. Does not record it.
+    pub fn check(&self, sequence: u64) -> Result<(), SyncError> {
+        let Some(highest) = self.highest else {
+            return Ok(());
+        };
+        if sequence > highest {
+            return Ok(());
+        }
+        if highest - sequence >= self.size || self.is_set(sequence) {
+            return Err(SyncError::ReplayedFrame(sequence));
+        }
+        Ok(())
+    }
+
+    /// [`check`](Self::check) `sequence`, then record it as seen.
+    ///
+    /// Call only once the frame has authenticated, so a forged sequence
+    /// cannot slide the window forward.
+    pub fn accept(&mut self, sequence: u64) -> Result<(), SyncError> {
+        self.check(sequence)?;
+        match self.highest {
+            Some(highest) if sequence <= highest => {}
+            Some(highest) => {
+                // Forget the slots the window slides past
+                if sequence - highest >= self.size {
+                    self.bits.fill(0);
+                } else {
+                    for skipped in highest + 1..sequence {
+                        self.clear(skipped);
+                    }
+                }
+                self.highest = Some(sequence);
+            }
+            None => self.highest = Some(sequence),
+        }
+        self.set(sequence);
+        Ok(())
+    }
+
+    fn slot(&self, sequence: u64) -> (usize, u64) {
+        let bit = sequence % self.size;
+        ((bit / 64) as usize, 1 << (bit % 64))
+    }
+
+    fn is_set(&self, sequence: u64) -> bool {
+        let (word, mask) = self.slot(sequence);
+        self.bits[word] & mask != 0
+    }
+
+    fn set(&mut self, sequence: u64) {
+        let (word, mask) = self.slot(sequence);
+        self.bits[word] |= mask;
+    }
+
+    fn clear(&mut self, sequence: u64) {
+        let (word, mask) = self.slot(sequence);
+        self.bits[word] &= !mask;
+    }
+}
+
+impl Default for ReplayWindow {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_WINDOW)
+    }
+}
+
+/// Serialized form of a [`ReplayWindow`]; the size is implied by `bits`.
+#[derive(Serialize, Deserialize)]
+struct ReplayWindowState {
+    bits: Vec<u64>,
+    highest: Option<u64>,
+}
+
+impl From<ReplayWindow> for ReplayWindowState {
+    fn from(window: ReplayWindow) -> Self {
+        Self {
+            bits: window.bits,
+            highest: window.highest,
+        }
+    }
+}
+
+impl TryFrom<ReplayWindowState> for ReplayWindow {
+    type Error = &'static str;
+
+    fn try_from(state: ReplayWindowState) -> Result<Self, Self::Error> {
+        if state.bits.is_empty() {
+            return Err("replay window has no bits");
+        }
+        Ok(Self {
+            size: state.bits.len() as u64 * 64,
+            bits: state.bits,
+            highest: state.highest,
+        })
+    }
+}
+
+/// Replay windows per `(space, sender)`, created on first use.
+///
+/// Frames without a sequence predate replay protection and cannot be
+/// checked; they are refused unless
+/// [`set_allow_unsequenced`](Self::set_allow_unsequenced) lets them through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayGuard {
+    /// Size of each new window.
+    window_size: usize,
+    allow_unsequenced: bool,
+    /// Space id → sender → window.
+    windows: BTreeMap<String, BTreeMap<String, ReplayWindow>>,
+}
+
+impl ReplayGuard {
+    /// A guard whose windows remember at least `window_size` sequences
+    /// (see [`ReplayWindow::new`]).
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window_size: window_size.max(1),
+            allow_unsequenced: false,
+            windows: BTreeMap::new(),
+        }
+    }
+
+    /// Whether frames without a sequence are accepted.
+    pub fn allows_unsequenced(&self) -> bool {
+        self.allow_unsequenced
+    }
+
+    /// Accept (or refuse) frames without a sequence, for senders that
+    /// predate sequencing.
+    pub fn set_allow_unsequenced(&mut self, allow: bool) {
+        self.allow_unsequenced = allow;
+    }
+
+    /// The window for `sender` in `space_id`, created empty if new.
+    pub fn window(&mut self, space_id: &str, sender: &str) -> &mut ReplayWindow {
+        let size = self.window_size;
+        self.windows
+            .entry(space_id.to_string())
+            .or_default()
+            .entry(sender.to_string())
+            .or_insert_with(|| ReplayWindow::new(size))
+    }
+
+    /// Highest sequence accepted from `sender` in `space_id`.
+    pub fn highest(&self, space_id: &str, sender: &str) -> Option<u64> {
+        self.windows.get(space_id)?.get(sender)?.highest()
+    }
+
+    /// Drop every window for `space_id`, e.g. after leaving the space.
+    /// Returns whether there were any.
+    pub fn forget_space(&mut self, space_id: &str) -> bool {
+        self.windows.remove(space_id).is_some()
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_REPLAY_WINDOW)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_in_order_and_out_of_order_within_window() {
+        let mut window = ReplayWindow::new(64);
+        for seq in [1, 2, 5, 3, 4] {
+            window.accept(seq).unwrap();
+        }
+        assert_eq!(window.highest(), Some(5));
+    }
+
+    #[test]
+    fn rejects_repeats_and_sequences_older_than_window() {
+        let mut window = ReplayWindow::new(64);
+        window.accept(10).unwrap();
+        assert!(matches!(
+            window.accept(10),
+            Err(SyncError::ReplayedFrame(10))
+        ));
+
+        window.accept(100).unwrap();
+        // 100 - 36 == 64: just outside the window
+        assert!(matches!(
+            window.check(36),
+            Err(SyncError::ReplayedFrame(36))
+        ));
+        window.accept(37).unwrap();
+    }
+
+    #[test]
+    fn sliding_forward_forgets_reused_slots() {
+        let mut window = ReplayWindow::new(64);
+        window.accept(3).unwrap();
+        // 67 shares 3's slot; 3 must no longer read as seen for 67
+        window.accept(67).unwrap();
+        window.accept(66).unwrap();
+        assert!(window.check(67).is_err());
+        assert!(window.check(3).is_err(), "3 is now outside the window");
+    }
+
+    #[test]
+    fn window_survives_a_serde_round_trip() {
+        let mut window = ReplayWindow::new(64);
+        for seq in [10, 12, 11] {
+            window.accept(seq).unwrap();
+        }
+        let json = serde_json::to_string(&window).unwrap();
+        let mut restored: ReplayWindow = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.size(), 64);
+        assert_eq!(restored.highest(), Some(12));
+        assert!(restored.check(11).is_err());
+        restored.accept(9).unwrap();
+
+        assert!(serde_json::from_str::<ReplayWindow>(r#"{"bits":[],"highest":null}"#).is_err());
+    }
+
+    #[test]
+    fn guard_tracks_each_space_and_sender_separately() {
+        let mut guard = ReplayGuard::new(64);
+        guard.window("space-1", "alice").accept(5).unwrap();
+        guard.window("space-1", "bob").accept(5).unwrap();
+        guard.window("space-2", "alice").accept(5).unwrap();
+        assert!(guard.window("space-1", "alice").check(5).is_err());
+        assert_eq!(guard.highest("space-1", "bob"), Some(5));
+        assert_eq!(guard.highest("space-1", "carol"), None);
+
+        let json = serde_json::to_string(&guard).unwrap();
+        let mut restored: ReplayGuard = serde_json::from_str(&json).unwrap();
+        assert!(restored.window("space-2", "alice").check(5).is_err());
+        assert_eq!(restored.window("space-2", "carol").size(), 64);
+
+        assert!(restored.forget_space("space-2"));
+        assert_eq!(restored.highest("space-2", "alice"), None);
+    }
+
+    #[test]
+    fn size_rounds_up_to_whole_words() {
+        assert_eq!(ReplayWindow::new(0).size(), 64);
+        assert_eq!(ReplayWindow::new(1).size(), 64);
+        assert_eq!(ReplayGuard::new(0).window("space-1", "alice").size(), 64);
+        assert_eq!(ReplayWindow::default().size(), DEFAULT_REPLAY_WINDOW);
+    }
+}
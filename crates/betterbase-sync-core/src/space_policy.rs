@@ -0,0 +1,497 @@
+//! Space delete policy: how deletes propagate per permission level.
+//!
+//! Admins always tombstone. A space may require members with write
+//! permission to archive instead, so their deletes hide records on other
+//! devices without destroying data. The policy is published as a signed
+//! entry; only holders of an admin UCAN for the space can issue one.
+//!
+//! The same entry can carry a minimum wire version for the space (see
+//! [`crate::wire`]), raised by an admin once every member can read it.
+
+use crate::error::SyncError;
+use crate::membership::{parse_ucan_payload, verify_ucan_signature};
+use crate::wire::SpaceWirePolicy;
+use betterbase_crypto::{
+    base64url_decode, base64url_encode, decode_did_key_to_jwk, encode_did_key_from_jwk,
+    verify_with_jwk, UCANPermission,
+};
+use serde::{Deserialize, Serialize};
+
+/// Prefix for space policy signing messages (null-byte separated fields).
+const SPACE_POLICY_PREFIX: &str = "betterbase:space-policy:v1\0";
+
+/// How a delete propagates to other devices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteKind {
+    /// Tombstone the record everywhere.
+    #[default]
+    Tombstone,
+    /// Hide the record but keep its data until an admin tombstones it.
+    Archive,
+}
+
+impl DeleteKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tombstone => "tombstone",
+            Self::Archive => "archive",
+        }
+    }
+
+    fn from_str(s: &str) -> Result<Self, SyncError> {
+        match s {
+            "tombstone" => Ok(Self::Tombstone),
+            "archive" => Ok(Self::Archive),
+            _ => Err(SyncError::InvalidPolicyEntry(format!(
+                "invalid delete kind: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Delete policy for a shared space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpaceDeletePolicy {
+    /// Delete kind for members holding write (not admin) permission.
+    pub writer_delete: DeleteKind,
+}
+
+impl SpaceDeletePolicy {
+    /// Delete kind for a member with `permission`, or `None` if the member
+    /// may not delete at all.
+    pub fn delete_kind_for(&self, permission: UCANPermission) -> Option<DeleteKind> {
+        match permission {
+            UCANPermission::Admin => Some(DeleteKind::Tombstone),
+            UCANPermission::Write => Some(self.writer_delete),
+            UCANPermission::Read => None,
+        }
+    }
+
+    /// Whether a member with `permission` may turn archives into tombstones.
+    pub fn can_tombstone_archives(permission: UCANPermission) -> bool {
+        permission == UCANPermission::Admin
+    }
+}
+
+/// Signed space policy entry.
+#[derive(Debug, Clone)]
+pub struct SpacePolicyEntry {
+    /// Signer's admin UCAN JWT for the space.
+    pub ucan: String,
+    pub policy: SpaceDeletePolicy,
+    /// Lowest wire version writers may use in this space.
+    pub min_wire_version: Option<u8>,
+    /// ECDSA P-256 or Ed25519 signature (64 bytes), per `signer_public_key`.
+    pub signature: Vec<u8>,
+    /// Signer's public key JWK.
+    pub signer_public_key: serde_json::Value,
+}
+
+/// Build the canonical message to sign for a space policy entry.
+///
+/// Format: `betterbase:space-policy:v1\0<spaceId>\0<signerDID>\0<ucan>\0<writerDelete>`,
+/// followed by `\0wire:<minWireVersion>` only when a minimum is set, so
+/// entries without one sign the same bytes as before.
+pub fn build_space_policy_signing_message(
+    space_id: &str,
+    signer_did: &str,
+    ucan: &str,
+    policy: &SpaceDeletePolicy,
+    min_wire_version: Option<u8>,
+) -> Vec<u8> {
+    let mut message = format!(
+        "{}{}\0{}\0{}\0{}",
+        SPACE_POLICY_PREFIX,
+        space_id,
+        signer_did,
+        ucan,
+        policy.writer_delete.as_str()
+    );
+    if let Some(version) = min_wire_version {
+        message.push_str(&format!("\0wire:{}", version));
+    }
+    message.into_bytes()
+}
+
+/// Parse a space policy entry payload string.
+///
+/// Expected format: JSON `{"u":"<ucan>","w":"archive","m":5,"s":"<base64url>","p":{...jwk}}`,
+/// where `m` (minimum wire version) is optional.
+pub fn parse_space_policy_entry(payload: &str) -> Result<SpacePolicyEntry, SyncError> {
+    let parsed: serde_json::Value = serde_json::from_str(payload)?;
+    let obj = parsed
+        .as_object()
+        .ok_or_else(|| SyncError::InvalidPolicyEntry("expected object".to_string()))?;
+
+    let field = |key: &str| {
+        obj.get(key)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SyncError::InvalidPolicyEntry(format!("missing {} field", key)))
+    };
+    let ucan = field("u")?.to_string();
+    let writer_delete = DeleteKind::from_str(field("w")?)?;
+    let min_wire_version = match obj.get("m") {
+        None => None,
+        Some(v) => Some(
+            v.as_u64()
+                .and_then(|n| u8::try_from(n).ok())
+                .ok_or_else(|| SyncError::InvalidPolicyEntry(format!("invalid m field: {}", v)))?,
+        ),
+    };
+    let signature =
+        base64url_decode(field("s")?).map_err(|e| SyncError::InvalidPolicyEntry(e.to_string()))?;
+    let signer_public_key = obj
+        .get("p")
+        .ok_or_else(|| SyncError::InvalidPolicyEntry("missing p field".to_string()))?
+        .clone();
+
+    Ok(SpacePolicyEntry {
+        ucan,
+        policy: SpaceDeletePolicy { writer_delete },
+        min_wire_version,
+        signature,
+        signer_public_key,
+    })
+}
+
+/// Serialize a space policy entry to JSON format.
+pub fn serialize_space_policy_entry(entry: &SpacePolicyEntry) -> String {
+    let mut json = serde_json::json!({
+        "u": entry.ucan,
+        "w": entry.policy.writer_delete.as_str(),
+        "s": base64url_encode(&entry.signature),
+        "p": entry.signer_public_key,
+    });
+    if let Some(version) = entry.min_wire_version {
+        json["m"] = version.into();
+    }
+    json.to_string()
+}
+
+/// Verify a space policy entry.
+///
+/// 1. The UCAN grants `/space/admin` on this space
+/// 2. The signer is the UCAN's audience
+/// 3. The signer's signature (P-256 or Ed25519) covers the canonical message
+/// 4. The UCAN JWT signature verifies against its issuer
+pub fn verify_space_policy_entry(
+    entry: &SpacePolicyEntry,
+    space_id: &str,
+) -> Result<bool, SyncError> {
+    let parsed = parse_ucan_payload(&entry.ucan)?;
+    if parsed.command != UCANPermission::Admin.as_str()
+        || parsed.resource != format!("space:{}", space_id)
+    {
+        return Ok(false);
+    }
+
+    let signer_did = encode_did_key_from_jwk(&entry.signer_public_key)?;
+    if signer_did != parsed.audience_did {
+        return Ok(false);
+    }
+
+    let message = build_space_policy_signing_message(
+        space_id,
+        &signer_did,
+        &entry.ucan,
+        &entry.policy,
+        entry.min_wire_version,
+    );
+    if !verify_with_jwk(&entry.signer_public_key, &message, &entry.signature) {
+        return Ok(false);
+    }
+
+    let issuer_jwk = if parsed.issuer_did == signer_did {
+        entry.signer_public_key.clone()
+    } else {
+        decode_did_key_to_jwk(&parsed.issuer_did)?
+    };
+    verify_ucan_signature(&entry.ucan, &issuer_jwk)
+}
+
+/// Resolve the effective policy from a space's entries, oldest first.
+///
+/// The last entry that verifies wins; invalid entries are ignored. With no
+/// valid entry the default (tombstone for everyone) applies.
+pub fn resolve_space_delete_policy(
+    entries: &[SpacePolicyEntry],
+    space_id: &str,
+) -> SpaceDeletePolicy {
+    latest_valid_entry(entries, space_id)
+        .map(|e| e.policy)
+        .unwrap_or_default()
+}
+
+/// Resolve the space's wire version minimum the same way as
+/// [`resolve_space_delete_policy`]: from the last entry that verifies.
+pub fn resolve_space_wire_policy(entries: &[SpacePolicyEntry], space_id: &str) -> SpaceWirePolicy {
+    SpaceWirePolicy {
+        min_version: latest_valid_entry(entries, space_id).and_then(|e| e.min_wire_version),
+    }
+}
+
+fn latest_valid_entry<'a>(
+    entries: &'a [SpacePolicyEntry],
+    space_id: &str,
+) -> Option<&'a SpacePolicyEntry> {
+    entries
+        .iter()
+        .rev()
+        .find(|e| verify_space_policy_entry(e, space_id).unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use betterbase_crypto::signing::{export_public_key_jwk, generate_p256_keypair};
+    use betterbase_crypto::ucan::{encode_did_key, issue_root_ucan};
+
+    const NOW: u64 = 1_700_000_000;
+
+    /// Sign `writer_delete` as a self-issued holder of `permission` on `ucan_space`.
+    fn signed_policy(
+        space_id: &str,
+        ucan_space: &str,
+        permission: UCANPermission,
+        writer_delete: DeleteKind,
+    ) -> SpacePolicyEntry {
+        signed_policy_with_wire(space_id, ucan_space, permission, writer_delete, None)
+    }
+
+    fn signed_policy_with_wire(
+        space_id: &str,
+        ucan_space: &str,
+        permission: UCANPermission,
+        writer_delete: DeleteKind,
+        min_wire_version: Option<u8>,
+    ) -> SpacePolicyEntry {
+        let key = generate_p256_keypair();
+        let jwk = export_public_key_jwk(key.verifying_key());
+        let did = encode_did_key(&key).unwrap();
+        let ucan = issue_root_ucan(&key, &did, &did, ucan_space, permission, 3600, NOW).unwrap();
+        let policy = SpaceDeletePolicy { writer_delete };
+        let message =
+            build_space_policy_signing_message(space_id, &did, &ucan, &policy, min_wire_version);
+
+        SpacePolicyEntry {
+            ucan,
+            policy,
+            min_wire_version,
+            signature: betterbase_crypto::sign(&key, &message).unwrap(),
+            signer_public_key: jwk,
+        }
+    }
+
+    #[test]
+    fn delete_kind_by_permission() {
+        let policy = SpaceDeletePolicy {
+            writer_delete: DeleteKind::Archive,
+        };
+        assert_eq!(
+            policy.delete_kind_for(UCANPermission::Admin),
+            Some(DeleteKind::Tombstone)
+        );
+        assert_eq!(
+            policy.delete_kind_for(UCANPermission::Write),
+            Some(DeleteKind::Archive)
+        );
+        assert_eq!(policy.delete_kind_for(UCANPermission::Read), None);
+        assert_eq!(
+            SpaceDeletePolicy::default().delete_kind_for(UCANPermission::Write),
+            Some(DeleteKind::Tombstone)
+        );
+        assert!(SpaceDeletePolicy::can_tombstone_archives(
+            UCANPermission::Admin
+        ));
+        assert!(!SpaceDeletePolicy::can_tombstone_archives(
+            UCANPermission::Write
+        ));
+    }
+
+    #[test]
+    fn signing_message_format() {
+        let msg = build_space_policy_signing_message(
+            "space-1",
+            "did:key:zA",
+            "ucan-jwt",
+            &SpaceDeletePolicy {
+                writer_delete: DeleteKind::Archive,
+            },
+            None,
+        );
+        let expected = "betterbase:space-policy:v1\0space-1\0did:key:zA\0ucan-jwt\0archive";
+        assert_eq!(msg, expected.as_bytes());
+
+        let msg = build_space_policy_signing_message(
+            "space-1",
+            "did:key:zA",
+            "ucan-jwt",
+            &SpaceDeletePolicy::default(),
+            Some(5),
+        );
+        let expected =
+            "betterbase:space-policy:v1\0space-1\0did:key:zA\0ucan-jwt\0tombstone\0wire:5";
+        assert_eq!(msg, expected.as_bytes());
+    }
+
+    #[test]
+    fn parse_serialize_round_trip() {
+        let entry = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        let reparsed = parse_space_policy_entry(&serialize_space_policy_entry(&entry)).unwrap();
+        assert_eq!(reparsed.ucan, entry.ucan);
+        assert_eq!(reparsed.policy, entry.policy);
+        assert_eq!(reparsed.min_wire_version, None);
+        assert_eq!(reparsed.signature, entry.signature);
+        assert!(verify_space_policy_entry(&reparsed, "space-1").unwrap());
+        assert!(!serialize_space_policy_entry(&entry).contains("\"m\""));
+
+        let entry = signed_policy_with_wire(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Tombstone,
+            Some(5),
+        );
+        let reparsed = parse_space_policy_entry(&serialize_space_policy_entry(&entry)).unwrap();
+        assert_eq!(reparsed.min_wire_version, Some(5));
+        assert!(verify_space_policy_entry(&reparsed, "space-1").unwrap());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_delete_kind() {
+        let json = r#"{"u":"x","w":"shred","s":"AA","p":{}}"#;
+        assert!(parse_space_policy_entry(json).is_err());
+        assert!(parse_space_policy_entry(r#"{"u":"x","s":"AA","p":{}}"#).is_err());
+        assert!(
+            parse_space_policy_entry(r#"{"u":"x","w":"archive","m":256,"s":"AA","p":{}}"#).is_err()
+        );
+        assert!(
+            parse_space_policy_entry(r#"{"u":"x","w":"archive","m":"5","s":"AA","p":{}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn verify_requires_admin_ucan_for_this_space() {
+        let admin = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        assert!(verify_space_policy_entry(&admin, "space-1").unwrap());
+        assert!(!verify_space_policy_entry(&admin, "space-2").unwrap());
+
+        let writer = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Write,
+            DeleteKind::Archive,
+        );
+        assert!(!verify_space_policy_entry(&writer, "space-1").unwrap());
+
+        let other_space = signed_policy(
+            "space-1",
+            "space-2",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        assert!(!verify_space_policy_entry(&other_space, "space-1").unwrap());
+    }
+
+    #[test]
+    fn verify_rejects_tampered_policy() {
+        let mut entry = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        entry.policy.writer_delete = DeleteKind::Tombstone;
+        assert!(!verify_space_policy_entry(&entry, "space-1").unwrap());
+
+        let mut entry = signed_policy_with_wire(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+            Some(5),
+        );
+        entry.min_wire_version = Some(4);
+        assert!(!verify_space_policy_entry(&entry, "space-1").unwrap());
+        entry.min_wire_version = None;
+        assert!(!verify_space_policy_entry(&entry, "space-1").unwrap());
+    }
+
+    #[test]
+    fn resolve_takes_last_valid_entry() {
+        assert_eq!(
+            resolve_space_delete_policy(&[], "space-1"),
+            SpaceDeletePolicy::default()
+        );
+
+        let archive = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        let forged = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Write,
+            DeleteKind::Tombstone,
+        );
+        let policy = resolve_space_delete_policy(&[archive.clone(), forged], "space-1");
+        assert_eq!(policy.writer_delete, DeleteKind::Archive);
+
+        let tombstone = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Tombstone,
+        );
+        let policy = resolve_space_delete_policy(&[archive, tombstone], "space-1");
+        assert_eq!(policy.writer_delete, DeleteKind::Tombstone);
+    }
+
+    #[test]
+    fn resolve_wire_minimum_from_last_valid_entry() {
+        assert_eq!(
+            resolve_space_wire_policy(&[], "space-1"),
+            SpaceWirePolicy::default()
+        );
+
+        let raised = signed_policy_with_wire(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Tombstone,
+            Some(5),
+        );
+        let forged = signed_policy_with_wire(
+            "space-1",
+            "space-1",
+            UCANPermission::Write,
+            DeleteKind::Tombstone,
+            Some(4),
+        );
+        let policy = resolve_space_wire_policy(&[raised.clone(), forged], "space-1");
+        assert_eq!(policy.min_version, Some(5));
+
+        let cleared = signed_policy(
+            "space-1",
+            "space-1",
+            UCANPermission::Admin,
+            DeleteKind::Archive,
+        );
+        let policy = resolve_space_wire_policy(&[raised, cleared], "space-1");
+        assert_eq!(policy.min_version, None);
+    }
+}
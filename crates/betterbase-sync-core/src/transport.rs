@@ -2,21 +2,40 @@
 //!
 //! Push: BlobEnvelope → CBOR → pad → encrypt(DEK) → (blob, wrapped_dek)
 //! Pull: unwrap DEK → decrypt → unpad → CBOR → BlobEnvelope
+//!
+//! A blob pushed with [`encrypt_outbound_checksummed`] carries a CRC32C
+//! footer over the ciphertext. Pull checks it before decrypting, so a
+//! damaged blob fails with `CorruptEnvelope` instead of `DecryptionFailed`.
+//!
+//! Either side may seal the edit chain under a history key derived from the
+//! DEK (see [`crate::history`]).
+//!
+//! The `_sequenced` variants bind the sender and a per-frame sequence into
+//! the AAD, so a receiver can drop re-delivered frames with a
+//! [`ReplayWindow`], or with a [`ReplayGuard`] that keeps one per space and
+//! sender.
 
-use crate::envelope::{decode_envelope, encode_envelope};
+use crate::envelope::{checksum_blob, decode_envelope, encode_envelope, verify_blob_checksum};
 use crate::epoch_cache::EpochKeyCache;
 use crate::error::SyncError;
+use crate::history::{derive_history_key, open_history, seal_history};
 use crate::padding::{pad_to_bucket, unpad};
+use crate::replay::{ReplayGuard, ReplayWindow};
 use crate::types::BlobEnvelope;
-use betterbase_crypto::{
-    decrypt_v4, encrypt_v4, generate_dek, unwrap_dek, wrap_dek, EncryptionContext,
-};
+use crate::wire::{SpaceWirePolicy, WireVersion};
+use betterbase_crypto::{generate_dek, unwrap_dek, wrap_dek, EncryptionContext};
 use zeroize::Zeroize;
 
+/// Appended to the space id in the AAD context of a sequenced frame.
+const SEQUENCED_SPACE_SUFFIX: &str = "\0seq";
+
 /// Encrypt an outbound record for push.
 ///
 /// Pipeline: envelope → CBOR → pad → encrypt(DEK) → (blob, wrapped_dek)
 ///
+/// Writes [`WireVersion::LATEST`]; use [`encrypt_outbound_with_version`]
+/// when a space policy or peer constrains the version.
+///
 /// # Arguments
 /// * `envelope` - The BlobEnvelope to encrypt
 /// * `record_id` - Record ID for AAD binding
@@ -28,22 +47,157 @@ pub fn encrypt_outbound(
     epoch_cache: &mut EpochKeyCache,
     padding_buckets: &[usize],
 ) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
-    let cbor = encode_envelope(envelope)?;
-    let padded = pad_to_bucket(&cbor, padding_buckets)?;
+    encrypt_outbound_with_version(
+        envelope,
+        record_id,
+        epoch_cache,
+        padding_buckets,
+        WireVersion::LATEST,
+        &SpaceWirePolicy::default(),
+    )
+}
 
-    let context = EncryptionContext {
-        space_id: epoch_cache.space_id().to_string(),
-        record_id: record_id.to_string(),
-    };
+/// [`encrypt_outbound`] in an explicit wire version, usually the result of
+/// [`negotiate_version`](crate::wire::negotiate_version).
+///
+/// Fails with `WireVersionBelowMinimum` if `version` is older than the
+/// space allows.
+pub fn encrypt_outbound_with_version(
+    envelope: &BlobEnvelope,
+    record_id: &str,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+    version: WireVersion,
+    policy: &SpaceWirePolicy,
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    seal_outbound(
+        envelope,
+        record_id,
+        epoch_cache,
+        padding_buckets,
+        version,
+        policy,
+        false,
+        None,
+    )
+}
 
-    let mut dek = generate_dek()?;
-    let epoch = epoch_cache.current_epoch();
-    let kek = epoch_cache.get_kek(epoch)?;
+/// [`encrypt_outbound`] with the edit chain sealed under the record's
+/// history key (see [`seal_history`]) before the envelope is encrypted.
+///
+/// [`decrypt_inbound`] opens the chain again, so full readers see `h` as
+/// usual.
+pub fn encrypt_outbound_sealing_history(
+    envelope: &BlobEnvelope,
+    record_id: &str,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    seal_outbound(
+        envelope,
+        record_id,
+        epoch_cache,
+        padding_buckets,
+        WireVersion::LATEST,
+        &SpaceWirePolicy::default(),
+        true,
+        None,
+    )
+}
+
+/// [`encrypt_outbound`] with a CRC32C footer over the sealed blob (see
+/// [`checksum_blob`]).
+///
+/// The footer diagnoses storage and transit corruption; it adds nothing to
+/// the AEAD's authentication. Every inbound path detects it from the
+/// leading byte.
+pub fn encrypt_outbound_checksummed(
+    envelope: &BlobEnvelope,
+    record_id: &str,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    let (blob, wrapped_dek) = encrypt_outbound(envelope, record_id, epoch_cache, padding_buckets)?;
+    Ok((checksum_blob(&blob), wrapped_dek))
+}
+
+/// [`encrypt_outbound`] with `sender` (the sealer's DID) and `sequence`
+/// bound into the AAD, for receivers that guard against replays with
+/// [`decrypt_inbound_sequenced`].
+///
+/// Neither is stored in the blob: they travel beside the frame. The
+/// sequence must increase monotonically per sender, since the receiver's
+/// [`ReplayWindow`] rejects anything it has seen or that is too old.
+pub fn encrypt_outbound_sequenced(
+    envelope: &BlobEnvelope,
+    record_id: &str,
+    sender: &str,
+    sequence: u64,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    seal_outbound(
+        envelope,
+        record_id,
+        epoch_cache,
+        padding_buckets,
+        WireVersion::LATEST,
+        &SpaceWirePolicy::default(),
+        false,
+        Some((sender, sequence)),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn seal_outbound(
+    envelope: &BlobEnvelope,
+    record_id: &str,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+    version: WireVersion,
+    policy: &SpaceWirePolicy,
+    separate_history: bool,
+    sequenced: Option<(&str, u64)>,
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    policy.check_write(version)?;
 
-    let blob = encrypt_v4(&padded, &dek, Some(&context))?;
-    let wrapped_dek = wrap_dek(&dek, kek, epoch)?;
+    let context = frame_context(epoch_cache.space_id(), record_id, sequenced)?;
+
+    let mut dek = generate_dek()?;
+    let result = seal_with_dek(
+        envelope,
+        &dek,
+        &context,
+        epoch_cache,
+        padding_buckets,
+        version,
+        separate_history,
+    );
     dek.zeroize();
+    result
+}
 
+fn seal_with_dek(
+    envelope: &BlobEnvelope,
+    dek: &[u8],
+    context: &EncryptionContext,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+    version: WireVersion,
+    separate_history: bool,
+) -> Result<(Vec<u8>, Vec<u8>), SyncError> {
+    let cbor = if separate_history {
+        encode_envelope(&seal_history(envelope, dek, context)?)?
+    } else {
+        encode_envelope(envelope)?
+    };
+    let padded = pad_to_bucket(&cbor, padding_buckets)?;
+
+    // Always the newest epoch, never a grace-pinned one.
+    let (epoch, kek) = epoch_cache.encryption_kek()?;
+
+    let blob = version.seal(&padded, dek, context)?;
+    let wrapped_dek = wrap_dek(dek, kek, epoch)?;
     Ok((blob, wrapped_dek.to_vec()))
 }
 
@@ -51,6 +205,9 @@ pub fn encrypt_outbound(
 ///
 /// Pipeline: unwrap DEK → decrypt → unpad → CBOR → BlobEnvelope
 ///
+/// Any registered wire version is accepted, including ones below the
+/// space minimum.
+///
 /// # Arguments
 /// * `blob` - Encrypted blob bytes
 /// * `wrapped_dek` - 44-byte wrapped DEK
@@ -63,6 +220,96 @@ pub fn decrypt_inbound(
     record_id: &str,
     epoch_cache: &mut EpochKeyCache,
     padding_buckets: &[usize],
+) -> Result<BlobEnvelope, SyncError> {
+    open_inbound(
+        blob,
+        wrapped_dek,
+        record_id,
+        None,
+        epoch_cache,
+        padding_buckets,
+    )
+}
+
+/// Decrypt a frame sealed by [`encrypt_outbound_sequenced`]. Opens only with
+/// the `sender` and `sequence` it was sealed with.
+///
+/// With a `replay` window (the one kept for `sender`), a `sequence` already
+/// seen or older than the window fails with [`SyncError::ReplayedFrame`]
+/// before any decryption, and the sequence is only recorded once the frame
+/// authenticates, so a forged frame cannot slide the window forward.
+#[allow(clippy::too_many_arguments)]
+pub fn decrypt_inbound_sequenced(
+    blob: &[u8],
+    wrapped_dek: &[u8],
+    record_id: &str,
+    sender: &str,
+    sequence: u64,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+    replay: Option<&mut ReplayWindow>,
+) -> Result<BlobEnvelope, SyncError> {
+    if let Some(window) = &replay {
+        window.check(sequence)?;
+    }
+    let envelope = open_inbound(
+        blob,
+        wrapped_dek,
+        record_id,
+        Some((sender, sequence)),
+        epoch_cache,
+        padding_buckets,
+    )?;
+    if let Some(window) = replay {
+        window.accept(sequence)?;
+    }
+    Ok(envelope)
+}
+
+/// Decrypt a frame from `sender`, replay-checked against `guard`'s window
+/// for the cache's space and that sender.
+///
+/// A frame with a `sequence` is opened as by [`decrypt_inbound_sequenced`].
+/// One without (from a sender that predates sequencing) is opened as by
+/// [`decrypt_inbound`] if the guard allows unsequenced frames, and fails
+/// with [`SyncError::UnsequencedFrame`] otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn decrypt_inbound_guarded(
+    blob: &[u8],
+    wrapped_dek: &[u8],
+    record_id: &str,
+    sender: &str,
+    sequence: Option<u64>,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+    guard: &mut ReplayGuard,
+) -> Result<BlobEnvelope, SyncError> {
+    let Some(sequence) = sequence else {
+        if !guard.allows_unsequenced() {
+            return Err(SyncError::UnsequencedFrame);
+        }
+        return decrypt_inbound(blob, wrapped_dek, record_id, epoch_cache, padding_buckets);
+    };
+    let space_id = epoch_cache.space_id().to_string();
+    decrypt_inbound_sequenced(
+        blob,
+        wrapped_dek,
+        record_id,
+        sender,
+        sequence,
+        epoch_cache,
+        padding_buckets,
+        Some(guard.window(&space_id, sender)),
+    )
+}
+
+fn open_inbound(
+    blob: &[u8],
+    wrapped_dek: &[u8],
+    record_id: &str,
+    sequenced: Option<(&str, u64)>,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
 ) -> Result<BlobEnvelope, SyncError> {
     // Peek epoch from wrapped DEK prefix
     let dek_epoch = crate::reencrypt::peek_epoch(wrapped_dek)?;
@@ -70,22 +317,113 @@ pub fn decrypt_inbound(
 
     let (mut dek, _epoch) = unwrap_dek(wrapped_dek, kek)?;
 
-    let context = EncryptionContext {
-        space_id: epoch_cache.space_id().to_string(),
-        record_id: record_id.to_string(),
-    };
+    let context = frame_context(epoch_cache.space_id(), record_id, sequenced)?;
 
-    let decrypted = decrypt_v4(blob, &dek, Some(&context));
+    let envelope = open_with_dek(blob, &dek, &context, padding_buckets);
     dek.zeroize();
-    let decrypted = decrypted?;
+    envelope
+}
 
+/// AAD context for a frame.
+///
+/// A sequenced frame suffixes the space id with [`SEQUENCED_SPACE_SUFFIX`]
+/// and appends to the record id a NUL, the sequence as 16 hex digits,
+/// another NUL and the sender. Space ids come from the epoch cache, not the
+/// wire, so no plain frame shares an AAD with a sequenced one, whatever its
+/// record id. A sequenced record id may not contain NUL, so two sequenced
+/// frames share one only for the same record, sequence and sender.
+fn frame_context(
+    space_id: &str,
+    record_id: &str,
+    sequenced: Option<(&str, u64)>,
+) -> Result<EncryptionContext, SyncError> {
+    let Some((sender, sequence)) = sequenced else {
+        return Ok(EncryptionContext {
+            space_id: space_id.to_string(),
+            record_id: record_id.to_string(),
+        });
+    };
+    if record_id.contains('\0') {
+        return Err(SyncError::InvalidRecordId(format!(
+            "{record_id:?} contains NUL"
+        )));
+    }
+    Ok(EncryptionContext {
+        space_id: format!("{space_id}{SEQUENCED_SPACE_SUFFIX}"),
+        record_id: format!("{record_id}\0{sequence:016x}\0{sender}"),
+    })
+}
+
+/// Check any checksum footer, then decrypt, unpad and decode a blob,
+/// opening a sealed edit chain back into `h`.
+fn open_with_dek(
+    blob: &[u8],
+    dek: &[u8],
+    context: &EncryptionContext,
+    padding_buckets: &[usize],
+) -> Result<BlobEnvelope, SyncError> {
+    let blob = verify_blob_checksum(blob)?;
+    let decrypted = WireVersion::open(blob, dek, context)?;
     let unpadded = unpad(&decrypted, padding_buckets)?;
-    decode_envelope(&unpadded)
+    let mut envelope = decode_envelope(&unpadded)?;
+    if envelope.hs.is_some() {
+        let mut history_key = derive_history_key(dek);
+        let chain = open_history(&envelope, &history_key, context);
+        history_key.zeroize();
+        envelope.h = chain?;
+        envelope.hs = None;
+    }
+    Ok(envelope)
+}
+
+/// Decrypt a batch of inbound records against one epoch cache.
+///
+/// Each item is `(blob, wrapped_dek, record_id)`. The cache derives each
+/// epoch key at most once for the whole batch. Results are per item and in
+/// input order, so one corrupted record does not abort the rest.
+pub fn decrypt_inbound_batch<'a, I>(
+    items: I,
+    epoch_cache: &mut EpochKeyCache,
+    padding_buckets: &[usize],
+) -> Vec<Result<BlobEnvelope, SyncError>>
+where
+    I: IntoIterator<Item = (&'a [u8], &'a [u8], &'a str)>,
+{
+    items
+        .into_iter()
+        .map(|(blob, wrapped_dek, record_id)| {
+            decrypt_inbound(blob, wrapped_dek, record_id, epoch_cache, padding_buckets)
+        })
+        .collect()
+}
+
+/// Reject a batch of inbound records if any is wrapped below the epoch floor.
+///
+/// `min_acceptable_epoch` is the client's known-current floor, advanced by
+/// the caller on legitimate rotations. This stops a server from replaying
+/// records wrapped under a rotated-out key. Epochs are read from the
+/// wrapped DEK prefix, the same as [`decrypt_inbound`].
+pub fn validate_epoch_sequence<D: AsRef<[u8]>>(
+    wrapped_deks: &[D],
+    min_acceptable_epoch: u32,
+) -> Result<(), SyncError> {
+    for (index, wrapped_dek) in wrapped_deks.iter().enumerate() {
+        let epoch = crate::reencrypt::peek_epoch(wrapped_dek.as_ref())?;
+        if epoch < min_acceptable_epoch {
+            return Err(SyncError::EpochBelowFloor {
+                index,
+                epoch,
+                floor: min_acceptable_epoch,
+            });
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::envelope::ENVELOPE_VERSION;
     use crate::padding::DEFAULT_PADDING_BUCKETS;
 
     fn random_key() -> [u8; 32] {
@@ -105,6 +443,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3, 4, 5],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) = encrypt_outbound(
@@ -140,6 +481,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) = encrypt_outbound(
@@ -160,6 +504,148 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn checksummed_blob_reports_damage_before_decrypting() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1, 2, 3],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        let (blob, wrapped_dek) = encrypt_outbound_checksummed(
+            &envelope,
+            "record-1",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        let mut open = |blob: &[u8]| {
+            decrypt_inbound(
+                blob,
+                &wrapped_dek,
+                "record-1",
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+            )
+        };
+
+        // The inner envelope is the plain version 1 format
+        assert_eq!(open(&blob).unwrap().version(), ENVELOPE_VERSION);
+
+        let mut damaged = blob.clone();
+        damaged[20] ^= 0x01;
+        assert!(matches!(open(&damaged), Err(SyncError::CorruptEnvelope(_))));
+        assert!(matches!(
+            open(&blob[..blob.len() - 8]),
+            Err(SyncError::CorruptEnvelope(_))
+        ));
+
+        // Without the footer the same damage is only an AEAD failure
+        let mut bare = verify_blob_checksum(&blob).unwrap().to_vec();
+        bare[19] ^= 0x01;
+        assert!(matches!(open(&bare), Err(SyncError::Crypto(_))));
+    }
+
+    #[test]
+    fn batch_matches_per_item_decrypt() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+
+        let mut items = Vec::new();
+        for (i, epoch) in [0, 0, 2, 2, 3].into_iter().enumerate() {
+            enc_cache.update_encryption_epoch(epoch);
+            let envelope = BlobEnvelope {
+                c: "tasks".to_string(),
+                v: 1,
+                crdt: vec![i as u8; i + 1],
+                h: Some(format!("chain-{i}")),
+                a: i % 2 == 1,
+                p: None,
+                hs: None,
+            };
+            let record_id = format!("record-{i}");
+            let (blob, wrapped_dek) = encrypt_outbound(
+                &envelope,
+                &record_id,
+                &mut enc_cache,
+                DEFAULT_PADDING_BUCKETS,
+            )
+            .unwrap();
+            items.push((blob, wrapped_dek, record_id));
+        }
+        // Corrupt one blob; the rest of the batch must still decrypt
+        let last = items[3].0.len() - 1;
+        items[3].0[last] ^= 0xff;
+
+        let mut batch_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let batch = decrypt_inbound_batch(
+            items
+                .iter()
+                .map(|(blob, dek, id)| (blob.as_slice(), dek.as_slice(), id.as_str())),
+            &mut batch_cache,
+            DEFAULT_PADDING_BUCKETS,
+        );
+        assert_eq!(batch.len(), items.len());
+        assert!(batch[3].is_err());
+        assert!(batch[4].is_ok());
+
+        for ((blob, wrapped_dek, record_id), batched) in items.iter().zip(batch) {
+            let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+            let single = decrypt_inbound(
+                blob,
+                wrapped_dek,
+                record_id,
+                &mut cache,
+                DEFAULT_PADDING_BUCKETS,
+            );
+            match (single, batched) {
+                (Ok(a), Ok(b)) => {
+                    assert_eq!(a.c, b.c);
+                    assert_eq!(a.v, b.v);
+                    assert_eq!(a.crdt, b.crdt);
+                    assert_eq!(a.h, b.h);
+                    assert_eq!(a.a, b.a);
+                }
+                (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+                (a, b) => panic!("{record_id}: per-item {a:?} vs batch {b:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn epoch_sequence_at_or_above_floor_passes() {
+        let deks: Vec<Vec<u8>> = [3u32, 4, 3, 7]
+            .iter()
+            .map(|e| [e.to_be_bytes().as_slice(), &[0u8; 40]].concat())
+            .collect();
+        validate_epoch_sequence(&deks, 3).unwrap();
+        validate_epoch_sequence::<Vec<u8>>(&[], 3).unwrap();
+    }
+
+    #[test]
+    fn epoch_sequence_below_floor_is_rejected() {
+        let deks: Vec<Vec<u8>> = [5u32, 6, 2, 6]
+            .iter()
+            .map(|e| [e.to_be_bytes().as_slice(), &[0u8; 40]].concat())
+            .collect();
+        assert!(matches!(
+            validate_epoch_sequence(&deks, 5),
+            Err(SyncError::EpochBelowFloor {
+                index: 2,
+                epoch: 2,
+                floor: 5
+            })
+        ));
+    }
+
     #[test]
     fn wrong_key_fails() {
         let key1 = random_key();
@@ -172,6 +658,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) = encrypt_outbound(
@@ -205,6 +694,9 @@ mod tests {
             v: 1,
             crdt: vec![42],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) =
@@ -233,6 +725,9 @@ mod tests {
             v: 2,
             crdt: vec![10],
             h: Some("chain-data".to_string()),
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) =
@@ -260,6 +755,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         // Empty padding_buckets = no padding
@@ -283,6 +781,9 @@ mod tests {
             v: 1,
             crdt: vec![1, 2, 3],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) =
@@ -298,6 +799,87 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn mixed_epoch_batch_during_grace() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![7],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        // One member still pushing at epoch 0, another already at epoch 1
+        let old = encrypt_outbound(
+            &envelope,
+            "rec-old",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        enc_cache.update_encryption_epoch(1);
+        let new = encrypt_outbound(
+            &envelope,
+            "rec-new",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+
+        dec_cache.observe_rotation(1, 1_000).unwrap();
+        for (id, (blob, wrapped_dek)) in [("rec-old", &old), ("rec-new", &new)] {
+            let decoded = decrypt_inbound(
+                blob,
+                wrapped_dek,
+                id,
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+            )
+            .unwrap();
+            assert_eq!(decoded.crdt, vec![7]);
+        }
+
+        // After the grace period the old epoch is gone
+        dec_cache.expire_pins(u64::MAX);
+        assert!(matches!(
+            decrypt_inbound(
+                &old.0,
+                &old.1,
+                "rec-old",
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+            ),
+            Err(SyncError::BackwardDerivation { .. })
+        ));
+    }
+
+    #[test]
+    fn encrypt_never_uses_pinned_epoch() {
+        let key = random_key();
+        let mut cache = EpochKeyCache::new(&key, 0, "space-1");
+        cache.observe_rotation(2, 1_000).unwrap();
+        cache.update_encryption_epoch(1);
+
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        let (_, wrapped_dek) =
+            encrypt_outbound(&envelope, "rec-1", &mut cache, DEFAULT_PADDING_BUCKETS).unwrap();
+        assert_eq!(crate::reencrypt::peek_epoch(&wrapped_dek).unwrap(), 2);
+    }
+
     #[test]
     fn empty_crdt_round_trip() {
         let key = random_key();
@@ -309,6 +891,9 @@ mod tests {
             v: 1,
             crdt: vec![],
             h: None,
+            a: false,
+            p: None,
+            hs: None,
         };
 
         let (blob, wrapped_dek) =
@@ -325,4 +910,417 @@ mod tests {
 
         assert!(decoded.crdt.is_empty());
     }
+
+    #[test]
+    fn write_below_space_minimum_is_refused() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        let strict = SpaceWirePolicy {
+            min_version: Some(WireVersion::LATEST.byte() + 1),
+        };
+        let err = encrypt_outbound_with_version(
+            &envelope,
+            "rec-1",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+            WireVersion::LATEST,
+            &strict,
+        )
+        .unwrap_err();
+        assert!(matches!(err, SyncError::WireVersionBelowMinimum { .. }));
+
+        // Blobs already written in an older version still read.
+        let (blob, wrapped_dek) =
+            encrypt_outbound(&envelope, "rec-1", &mut enc_cache, DEFAULT_PADDING_BUCKETS).unwrap();
+        assert_eq!(blob[0], WireVersion::LATEST.byte());
+        let decoded = decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec-1",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        assert_eq!(decoded.crdt, vec![1]);
+    }
+
+    #[test]
+    fn negotiated_v5_round_trips_through_transport() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![5, 5, 5],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        let policy = SpaceWirePolicy {
+            min_version: Some(5),
+        };
+        let version = crate::wire::negotiate_version(WireVersion::ALL, &policy, Some(5)).unwrap();
+        assert_eq!(version, WireVersion::V5);
+
+        let (blob, wrapped_dek) = encrypt_outbound_with_version(
+            &envelope,
+            "rec-5",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+            version,
+            &policy,
+        )
+        .unwrap();
+        assert_eq!(blob[0], 5);
+
+        let decoded = decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec-5",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        assert_eq!(decoded.crdt, vec![5, 5, 5]);
+
+        assert!(decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec-WRONG",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn sealed_history_round_trips_and_hides_chain() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let chain = r#"[{"a":"did:key:alice","t":1}]"#.to_string();
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1, 2, 3],
+            h: Some(chain.clone()),
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        let (blob, wrapped_dek) = encrypt_outbound_sealing_history(
+            &envelope,
+            "rec-1",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+
+        let decoded = decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec-1",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        assert_eq!(decoded.h, Some(chain));
+        assert!(decoded.hs.is_none());
+
+        // Inside the data payload the chain is only present sealed.
+        let (dek, _) = unwrap_dek(&wrapped_dek, dec_cache.get_kek(0).unwrap()).unwrap();
+        let context = EncryptionContext {
+            space_id: "space-1".to_string(),
+            record_id: "rec-1".to_string(),
+        };
+        let inner = decode_envelope(
+            &unpad(
+                &WireVersion::open(&blob, &dek, &context).unwrap(),
+                DEFAULT_PADDING_BUCKETS,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(inner.h.is_none());
+        assert!(open_history(&inner, &dek, &context).is_err());
+        assert!(open_history(&inner, &derive_history_key(&dek), &context).is_ok());
+    }
+
+    fn sequenced_frame(seq: u64, cache: &mut EpochKeyCache) -> (Vec<u8>, Vec<u8>) {
+        sequenced_frame_from("alice", seq, cache)
+    }
+
+    fn sequenced_frame_from(
+        sender: &str,
+        seq: u64,
+        cache: &mut EpochKeyCache,
+    ) -> (Vec<u8>, Vec<u8>) {
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![seq as u8],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        encrypt_outbound_sequenced(
+            &envelope,
+            "rec-1",
+            sender,
+            seq,
+            cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap()
+    }
+
+    fn open_sequenced(
+        frame: &(Vec<u8>, Vec<u8>),
+        seq: u64,
+        cache: &mut EpochKeyCache,
+        window: &mut ReplayWindow,
+    ) -> Result<BlobEnvelope, SyncError> {
+        decrypt_inbound_sequenced(
+            &frame.0,
+            &frame.1,
+            "rec-1",
+            "alice",
+            seq,
+            cache,
+            DEFAULT_PADDING_BUCKETS,
+            Some(window),
+        )
+    }
+
+    #[test]
+    fn sequenced_frames_in_order_are_accepted() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut window = ReplayWindow::default();
+
+        for seq in 1..=5 {
+            let frame = sequenced_frame(seq, &mut enc_cache);
+            let decoded = open_sequenced(&frame, seq, &mut dec_cache, &mut window).unwrap();
+            assert_eq!(decoded.crdt, vec![seq as u8]);
+        }
+        assert_eq!(window.highest(), Some(5));
+    }
+
+    #[test]
+    fn replayed_frame_is_rejected() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut window = ReplayWindow::default();
+
+        let frame = sequenced_frame(7, &mut enc_cache);
+        open_sequenced(&frame, 7, &mut dec_cache, &mut window).unwrap();
+        let err = open_sequenced(&frame, 7, &mut dec_cache, &mut window).unwrap_err();
+        assert!(matches!(err, SyncError::ReplayedFrame(7)));
+
+        // Relabelling the captured frame with a fresh sequence breaks the AAD
+        assert!(open_sequenced(&frame, 8, &mut dec_cache, &mut window).is_err());
+        assert_eq!(window.highest(), Some(7), "failed frames are not recorded");
+    }
+
+    #[test]
+    fn frame_older_than_window_is_rejected() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut window = ReplayWindow::new(64);
+
+        let stale = sequenced_frame(1, &mut enc_cache);
+        let fresh = sequenced_frame(100, &mut enc_cache);
+        open_sequenced(&fresh, 100, &mut dec_cache, &mut window).unwrap();
+        let err = open_sequenced(&stale, 1, &mut dec_cache, &mut window).unwrap_err();
+        assert!(matches!(err, SyncError::ReplayedFrame(1)));
+    }
+
+    #[test]
+    fn guard_rejects_replays_per_sender_and_allows_reordering() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut open =
+            |frame: &(Vec<u8>, Vec<u8>), sender: &str, seq: u64, guard: &mut ReplayGuard| {
+                decrypt_inbound_guarded(
+                    &frame.0,
+                    &frame.1,
+                    "rec-1",
+                    sender,
+                    Some(seq),
+                    &mut dec_cache,
+                    DEFAULT_PADDING_BUCKETS,
+                    guard,
+                )
+            };
+
+        let mut guard = ReplayGuard::new(64);
+        let second = sequenced_frame(2, &mut enc_cache);
+        let first = sequenced_frame(1, &mut enc_cache);
+        open(&second, "alice", 2, &mut guard).unwrap();
+        open(&first, "alice", 1, &mut guard).unwrap();
+        assert!(matches!(
+            open(&second, "alice", 2, &mut guard),
+            Err(SyncError::ReplayedFrame(2))
+        ));
+        // Relabelling Alice's frame as Bob's fails, and leaves Bob's window alone
+        assert!(open(&second, "bob", 2, &mut guard).is_err());
+        assert_eq!(guard.highest("space-1", "bob"), None);
+        // Bob's own sequences are independent of Alice's
+        let bobs = sequenced_frame_from("bob", 2, &mut enc_cache);
+        open(&bobs, "bob", 2, &mut guard).unwrap();
+
+        // A restored guard still remembers
+        let mut restored: ReplayGuard =
+            serde_json::from_str(&serde_json::to_string(&guard).unwrap()).unwrap();
+        assert!(matches!(
+            open(&first, "alice", 1, &mut restored),
+            Err(SyncError::ReplayedFrame(1))
+        ));
+    }
+
+    #[test]
+    fn guard_accepts_unsequenced_frames_only_when_allowed() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+        let (blob, wrapped_dek) =
+            encrypt_outbound(&envelope, "rec-1", &mut enc_cache, DEFAULT_PADDING_BUCKETS).unwrap();
+        let mut open = |guard: &mut ReplayGuard| {
+            decrypt_inbound_guarded(
+                &blob,
+                &wrapped_dek,
+                "rec-1",
+                "alice",
+                None,
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+                guard,
+            )
+        };
+
+        let mut guard = ReplayGuard::default();
+        assert!(matches!(open(&mut guard), Err(SyncError::UnsequencedFrame)));
+        guard.set_allow_unsequenced(true);
+        assert_eq!(open(&mut guard).unwrap().crdt, vec![1]);
+    }
+
+    #[test]
+    fn sequenced_frame_does_not_open_as_plain() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+
+        let (blob, wrapped_dek) = sequenced_frame(1, &mut enc_cache);
+        assert!(decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec-1",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn nul_in_record_id_is_rejected_only_for_sequenced_frames() {
+        let key = random_key();
+        let mut enc_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let mut dec_cache = EpochKeyCache::new(&key, 0, "space-1");
+        let envelope = BlobEnvelope {
+            c: "tasks".to_string(),
+            v: 1,
+            crdt: vec![1],
+            h: None,
+            a: false,
+            p: None,
+            hs: None,
+        };
+
+        // Plain frames take any record id, NUL included
+        let (blob, wrapped_dek) = encrypt_outbound(
+            &envelope,
+            "rec\0plain",
+            &mut enc_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .unwrap();
+        assert!(decrypt_inbound(
+            &blob,
+            &wrapped_dek,
+            "rec\0plain",
+            &mut dec_cache,
+            DEFAULT_PADDING_BUCKETS,
+        )
+        .is_ok());
+
+        // A plain id spelling the sequenced suffix still doesn't open a
+        // sequenced frame
+        let (blob, wrapped_dek) = sequenced_frame(7, &mut enc_cache);
+        let forged_id = format!("rec-1\0{:016x}\0alice", 7u64);
+        assert!(matches!(
+            decrypt_inbound(
+                &blob,
+                &wrapped_dek,
+                &forged_id,
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+            ),
+            Err(SyncError::Crypto(_))
+        ));
+
+        assert!(matches!(
+            encrypt_outbound_sequenced(
+                &envelope,
+                "rec\0",
+                "alice",
+                1,
+                &mut enc_cache,
+                DEFAULT_PADDING_BUCKETS
+            ),
+            Err(SyncError::InvalidRecordId(_))
+        ));
+        let mut window = ReplayWindow::new(64);
+        assert!(matches!(
+            decrypt_inbound_sequenced(
+                &blob,
+                &wrapped_dek,
+                "rec\0",
+                "alice",
+                7,
+                &mut dec_cache,
+                DEFAULT_PADDING_BUCKETS,
+                Some(&mut window),
+            ),
+            Err(SyncError::InvalidRecordId(_))
+        ));
+    }
 }
@@ -16,4 +16,33 @@ pub struct BlobEnvelope {
     /// Serialized edit chain (JSON string).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub h: Option<String>,
+    /// Edit chain sealed under the record's history key, in place of `h`
+    /// (see [`seal_history`](crate::history::seal_history)).
+    #[serde(default, with = "serde_bytes", skip_serializing_if = "Option::is_none")]
+    pub hs: Option<Vec<u8>>,
+    /// Archived by a non-admin delete (see `space_policy`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub a: bool,
+    /// Chunk position, set only on envelopes from
+    /// [`encode_envelope_chunked`](crate::chunked::encode_envelope_chunked).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p: Option<ChunkPart>,
+}
+
+impl BlobEnvelope {
+    /// Envelope format version. Decoding upgrades older layouts to the
+    /// current one, so every in-memory envelope reports
+    /// [`ENVELOPE_VERSION`](crate::envelope::ENVELOPE_VERSION).
+    pub fn version(&self) -> u8 {
+        crate::envelope::ENVELOPE_VERSION
+    }
+}
+
+/// Position of one chunk within a chunked blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkPart {
+    /// Zero-based chunk index.
+    pub i: u32,
+    /// Total number of chunks in the blob.
+    pub n: u32,
 }
@@ -0,0 +1,397 @@
+//! Wire version registry for encrypted record blobs.
+//!
+//! Every blob starts with a version byte. [`WireVersion`] is the single list
+//! of versions this build understands and what each one means; encode paths
+//! pick one with [`negotiate_version`] and decode paths dispatch through
+//! [`WireVersion::open`]. Capability decisions are exhaustive matches on
+//! `WireVersion`, so registering a version fails to compile until each of
+//! them has an answer for it.
+//!
+//! A space may set a minimum version through its policy entry
+//! ([`SpacePolicyEntry::min_wire_version`](crate::space_policy::SpacePolicyEntry::min_wire_version)).
+//! Writers refuse to go below it; readers still open anything registered.
+
+use betterbase_crypto::{decrypt_v4, encrypt_v4, CipherSuite, EncryptionContext};
+
+use crate::error::SyncError;
+
+/// A registered blob wire format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WireVersion {
+    /// AES-256-GCM under a random per-record DEK, wrapped separately with
+    /// AES-KW under the epoch KEK. `[0x04][IV:12][ciphertext+tag]`.
+    V4,
+    /// V4 with an AES-256-GCM-SIV body, so a repeated IV under one DEK does
+    /// not leak the key stream. `[0x05][IV:12][ciphertext+tag]`.
+    V5,
+}
+
+/// AEAD construction used for the blob body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aead {
+    Aes256Gcm,
+    Aes256GcmSiv,
+}
+
+/// Where a blob's data key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DekMode {
+    /// Random per record, shipped wrapped under the epoch KEK.
+    Wrapped,
+}
+
+/// Where a record's edit chain travels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainPlacement {
+    /// Inside the encrypted envelope (`BlobEnvelope::h`).
+    Envelope,
+}
+
+/// What a wire version does, for code that has to branch on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub aead: Aead,
+    pub dek_mode: DekMode,
+    /// Body split into independently authenticated chunks.
+    pub chunked: bool,
+    /// Ciphertext commits to the key, so it opens under exactly one key.
+    pub key_committing: bool,
+    pub chain: ChainPlacement,
+}
+
+impl WireVersion {
+    /// Every registered version, oldest first.
+    pub const ALL: &'static [WireVersion] = &[WireVersion::V4, WireVersion::V5];
+
+    /// The version new blobs are written in when nothing constrains it.
+    ///
+    /// Stays at V4 until clients that cannot read V5 have aged out; pick V5
+    /// through [`negotiate_version`] when the peer advertises it.
+    pub const LATEST: WireVersion = WireVersion::V4;
+
+    /// The version byte written at the front of the blob.
+    pub const fn byte(self) -> u8 {
+        match self {
+            Self::V4 => 4,
+            Self::V5 => 5,
+        }
+    }
+
+    pub const fn capabilities(self) -> Capabilities {
+        match self {
+            Self::V4 => Capabilities {
+                aead: Aead::Aes256Gcm,
+                dek_mode: DekMode::Wrapped,
+                chunked: false,
+                key_committing: false,
+                chain: ChainPlacement::Envelope,
+            },
+            Self::V5 => Capabilities {
+                aead: Aead::Aes256GcmSiv,
+                dek_mode: DekMode::Wrapped,
+                chunked: false,
+                key_committing: false,
+                chain: ChainPlacement::Envelope,
+            },
+        }
+    }
+
+    /// Look up a version byte.
+    pub fn from_byte(byte: u8) -> Result<Self, SyncError> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|v| v.byte() == byte)
+            .ok_or_else(|| unsupported(byte))
+    }
+
+    /// The version of an encoded blob, read from its first byte.
+    pub fn of_blob(blob: &[u8]) -> Result<Self, SyncError> {
+        let byte = blob
+            .first()
+            .ok_or_else(|| SyncError::InvalidEnvelope("empty blob".to_string()))?;
+        Self::from_byte(*byte)
+    }
+
+    /// Encrypt `plaintext` as a blob in this version.
+    pub fn seal(
+        self,
+        plaintext: &[u8],
+        dek: &[u8],
+        context: &EncryptionContext,
+    ) -> Result<Vec<u8>, SyncError> {
+        match self {
+            Self::V4 => Ok(encrypt_v4(
+                plaintext,
+                dek,
+                Some(context),
+                CipherSuite::Aes256Gcm,
+            )?),
+            Self::V5 => Ok(encrypt_v4(
+                plaintext,
+                dek,
+                Some(context),
+                CipherSuite::Aes256GcmSiv,
+            )?),
+        }
+    }
+
+    /// Decrypt a blob of any registered version.
+    pub fn open(
+        blob: &[u8],
+        dek: &[u8],
+        context: &EncryptionContext,
+    ) -> Result<Vec<u8>, SyncError> {
+        match Self::of_blob(blob)? {
+            Self::V4 | Self::V5 => Ok(decrypt_v4(blob, dek, Some(context))?),
+        }
+    }
+}
+
+fn unsupported(byte: u8) -> SyncError {
+    let readable = WireVersion::ALL
+        .iter()
+        .map(|v| format!("v{}", v.byte()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let newest = WireVersion::ALL.iter().map(|v| v.byte()).max().unwrap_or(0);
+    let detail = if byte > newest {
+        format!("written by a newer client (this one reads {readable}); upgrade to read it")
+    } else {
+        format!("not a registered format (this client reads {readable})")
+    };
+    SyncError::UnsupportedWireVersion {
+        version: byte,
+        detail,
+    }
+}
+
+/// Per-space constraint on which versions may be written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpaceWirePolicy {
+    /// Lowest version byte writers may use. May name a version this build
+    /// does not know, in which case it cannot write to the space at all.
+    pub min_version: Option<u8>,
+}
+
+impl SpaceWirePolicy {
+    /// Refuse to write `version` if it is below the space minimum.
+    pub fn check_write(&self, version: WireVersion) -> Result<(), SyncError> {
+        match self.min_version {
+            Some(minimum) if version.byte() < minimum => Err(SyncError::WireVersionBelowMinimum {
+                version: version.byte(),
+                minimum,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Pick the version to write.
+///
+/// Takes the newest version that this client supports (`local_supported`),
+/// the space allows (`policy`), and the peer can read (`peer_hint`, the
+/// newest version byte it advertises; `None` when unknown). Fails rather
+/// than write something the space forbids or the peer cannot open.
+pub fn negotiate_version(
+    local_supported: &[WireVersion],
+    policy: &SpaceWirePolicy,
+    peer_hint: Option<u8>,
+) -> Result<WireVersion, SyncError> {
+    let allowed = local_supported
+        .iter()
+        .copied()
+        .filter(|v| policy.check_write(*v).is_ok());
+    let chosen = match peer_hint {
+        Some(peer) => allowed.filter(|v| v.byte() <= peer).max(),
+        None => allowed.max(),
+    };
+    chosen.ok_or_else(|| {
+        SyncError::NoWireVersion(format!(
+            "local supports {:?}, space minimum {:?}, peer reads up to {:?}",
+            local_supported.iter().map(|v| v.byte()).collect::<Vec<_>>(),
+            policy.min_version,
+            peer_hint
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> EncryptionContext {
+        EncryptionContext {
+            space_id: "space-1".to_string(),
+            record_id: "record-1".to_string(),
+        }
+    }
+
+    fn dek() -> Vec<u8> {
+        (0u8..32).collect()
+    }
+
+    /// AES-256-GCM, DEK 00..1f, IV a0..ab, AAD for space-1/record-1,
+    /// plaintext "betterbase wire v4". Produced independently of this crate.
+    const V4_VECTOR: &str = "04a0a1a2a3a4a5a6a7a8a9aaab847d085920b960de1100a7a46e08a5fe\
+                             06984955af9b8b78ad1d8aeb8d1a30580f23";
+
+    #[test]
+    fn v4_regression_vector_opens() {
+        let blob = hex::decode(V4_VECTOR).unwrap();
+        assert_eq!(WireVersion::of_blob(&blob).unwrap(), WireVersion::V4);
+        let plaintext = WireVersion::open(&blob, &dek(), &context()).unwrap();
+        assert_eq!(plaintext, b"betterbase wire v4");
+    }
+
+    #[test]
+    fn v4_seal_keeps_layout() {
+        let blob = WireVersion::V4
+            .seal(b"betterbase wire v4", &dek(), &context())
+            .unwrap();
+        assert_eq!(blob[0], 0x04);
+        assert_eq!(blob.len(), 1 + 12 + 18 + 16);
+        let vector = hex::decode(V4_VECTOR).unwrap();
+        assert_eq!(blob.len(), vector.len());
+    }
+
+    #[test]
+    fn v5_seal_uses_gcm_siv() {
+        let blob = WireVersion::V5
+            .seal(b"payload", &dek(), &context())
+            .unwrap();
+        assert_eq!(blob[0], 0x05);
+        assert_eq!(WireVersion::of_blob(&blob).unwrap(), WireVersion::V5);
+        assert_eq!(WireVersion::V5.capabilities().aead, Aead::Aes256GcmSiv);
+        assert_eq!(
+            decrypt_v4(&blob, &dek(), Some(&context())).unwrap(),
+            b"payload"
+        );
+
+        // The version byte selects the AEAD, so relabelling a blob breaks it.
+        let mut as_v4 = blob.clone();
+        as_v4[0] = 0x04;
+        assert!(WireVersion::open(&as_v4, &dek(), &context()).is_err());
+    }
+
+    #[test]
+    fn every_registered_version_round_trips() {
+        for &version in WireVersion::ALL {
+            let blob = version.seal(b"payload", &dek(), &context()).unwrap();
+            assert_eq!(WireVersion::of_blob(&blob).unwrap(), version);
+            assert_eq!(
+                WireVersion::open(&blob, &dek(), &context()).unwrap(),
+                b"payload"
+            );
+        }
+    }
+
+    #[test]
+    fn registry_matches_version_bytes() {
+        for byte in 0..=u8::MAX {
+            let registered = WireVersion::ALL.iter().find(|v| v.byte() == byte);
+            match WireVersion::from_byte(byte) {
+                Ok(v) => assert_eq!(Some(&v), registered),
+                Err(_) => assert!(registered.is_none(), "v{byte} registered but not found"),
+            }
+        }
+        assert!(WireVersion::ALL.contains(&WireVersion::LATEST));
+        assert!(WireVersion::ALL.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn unknown_future_version_error_says_upgrade() {
+        let mut blob = hex::decode(V4_VECTOR).unwrap();
+        blob[0] = 9;
+        let err = WireVersion::open(&blob, &dek(), &context()).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::UnsupportedWireVersion { version: 9, .. }
+        ));
+        let msg = err.to_string();
+        assert!(msg.contains("9"), "{msg}");
+        assert!(msg.contains("v4"), "{msg}");
+        assert!(msg.contains("upgrade"), "{msg}");
+    }
+
+    #[test]
+    fn unknown_old_version_error_does_not_suggest_upgrade() {
+        let err = WireVersion::from_byte(3).unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("v4"), "{msg}");
+        assert!(!msg.contains("upgrade"), "{msg}");
+        assert!(WireVersion::of_blob(&[]).is_err());
+    }
+
+    #[test]
+    fn check_write_refuses_below_minimum() {
+        let policy = SpaceWirePolicy {
+            min_version: Some(5),
+        };
+        let err = policy.check_write(WireVersion::V4).unwrap_err();
+        assert!(matches!(
+            err,
+            SyncError::WireVersionBelowMinimum {
+                version: 4,
+                minimum: 5
+            }
+        ));
+        assert!(SpaceWirePolicy::default()
+            .check_write(WireVersion::V4)
+            .is_ok());
+        assert!(SpaceWirePolicy {
+            min_version: Some(4)
+        }
+        .check_write(WireVersion::V4)
+        .is_ok());
+    }
+
+    /// `(local, policy, peer hint, expected)` for [`negotiate_version`].
+    type NegotiationCase<'a> = (
+        &'a [WireVersion],
+        SpaceWirePolicy,
+        Option<u8>,
+        Option<WireVersion>,
+    );
+
+    #[test]
+    fn negotiation_truth_table() {
+        let all = WireVersion::ALL;
+        let none = SpaceWirePolicy::default();
+        let min4 = SpaceWirePolicy {
+            min_version: Some(4),
+        };
+        let min5 = SpaceWirePolicy {
+            min_version: Some(5),
+        };
+        let min6 = SpaceWirePolicy {
+            min_version: Some(6),
+        };
+        let v4_only: &[WireVersion] = &[WireVersion::V4];
+
+        // (local, policy, peer hint) -> chosen version, or None for an error.
+        let cases: &[NegotiationCase] = &[
+            (all, none, None, Some(WireVersion::V5)),
+            (all, none, Some(4), Some(WireVersion::V4)),
+            (all, none, Some(5), Some(WireVersion::V5)),
+            (all, none, Some(200), Some(WireVersion::V5)),
+            (all, none, Some(3), None),
+            (all, min4, None, Some(WireVersion::V5)),
+            (all, min4, Some(4), Some(WireVersion::V4)),
+            (all, min5, None, Some(WireVersion::V5)),
+            (all, min5, Some(200), Some(WireVersion::V5)),
+            (all, min5, Some(4), None),
+            (all, min6, None, None),
+            (v4_only, none, Some(5), Some(WireVersion::V4)),
+            (v4_only, min5, None, None),
+            (&[], none, None, None),
+        ];
+        for (local, policy, hint, expected) in cases {
+            let got = negotiate_version(local, policy, *hint).ok();
+            assert_eq!(
+                got, *expected,
+                "local={local:?} policy={policy:?} hint={hint:?}"
+            );
+        }
+    }
+}
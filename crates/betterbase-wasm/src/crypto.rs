@@ -3,12 +3,13 @@
 use crate::error::{to_js_error, to_js_value};
 use betterbase_crypto::{
     aes_gcm_decrypt, aes_gcm_encrypt, base64url_decode, base64url_encode, build_event_aad,
-    build_presence_aad, canonical_json, compress_p256_public_key, decrypt_v4, delegate_ucan,
-    derive_channel_key, derive_epoch_key_from_root, derive_next_epoch_key, encode_did_key,
-    encode_did_key_from_jwk, encrypt_v4, export_private_key_jwk, export_public_key_jwk,
-    generate_dek, generate_p256_keypair, hkdf_derive, import_private_key_jwk, issue_root_ucan,
-    parse_edit_chain, reconstruct_state, serialize_edit_chain, sign, sign_edit_entry, unwrap_dek,
-    value_diff, verify, verify_edit_chain, verify_edit_entry, wrap_dek, EditDiff, EditEntry,
+    build_event_aad_seq, build_presence_aad, build_presence_aad_seq, canonical_json,
+    compress_p256_public_key, decrypt_v4, delegate_ucan, derive_channel_key,
+    derive_epoch_key_from_root, derive_next_epoch_key, encode_did_key, encode_did_key_from_jwk,
+    encrypt_v4, export_private_key_jwk, export_public_key_jwk, generate_dek, generate_p256_keypair,
+    hkdf_derive, import_private_key_jwk, issue_root_ucan, parse_edit_chain, reconstruct_state,
+    serialize_edit_chain, sign, sign_edit_entry, unwrap_dek, value_diff, value_diff_granular,
+    verify, verify_edit_chain, verify_edit_entry, wrap_dek, CipherSuite, EditDiff, EditEntry,
     EncryptionContext, UCANPermission, CURRENT_VERSION, SUPPORTED_VERSIONS,
 };
 use serde_json::Value;
@@ -41,13 +42,20 @@ pub fn wasm_base64url_decode(encoded: &str) -> Result<Vec<u8>, JsValue> {
 
 // --- AES-256-GCM v4 ---
 
+/// `version` picks the wire version to write: 4 (AES-256-GCM, the default)
+/// or 5 (AES-256-GCM-SIV).
 #[wasm_bindgen(js_name = "encryptV4")]
 pub fn wasm_encrypt_v4(
     data: &[u8],
     dek: &[u8],
     space_id: Option<String>,
     record_id: Option<String>,
+    version: Option<u8>,
 ) -> Result<Vec<u8>, JsValue> {
+    let suite = match version {
+        Some(version) => CipherSuite::from_version(version).map_err(to_js_error)?,
+        None => CipherSuite::default(),
+    };
     let context = match (&space_id, &record_id) {
         (Some(s), Some(r)) => Some(EncryptionContext {
             space_id: s.clone(),
@@ -55,7 +63,7 @@ pub fn wasm_encrypt_v4(
         }),
         _ => None,
     };
-    encrypt_v4(data, dek, context.as_ref()).map_err(to_js_error)
+    encrypt_v4(data, dek, context.as_ref(), suite).map_err(to_js_error)
 }
 
 #[wasm_bindgen(js_name = "decryptV4")]
@@ -148,6 +156,16 @@ pub fn wasm_build_event_aad(space_id: &str) -> Vec<u8> {
     build_event_aad(space_id)
 }
 
+#[wasm_bindgen(js_name = "buildPresenceAadSeq")]
+pub fn wasm_build_presence_aad_seq(space_id: &str, sender: &str, sequence: u64) -> Vec<u8> {
+    build_presence_aad_seq(space_id, sender, sequence)
+}
+
+#[wasm_bindgen(js_name = "buildEventAadSeq")]
+pub fn wasm_build_event_aad_seq(space_id: &str, sender: &str, sequence: u64) -> Vec<u8> {
+    build_event_aad_seq(space_id, sender, sequence)
+}
+
 // --- Signing ---
 
 #[wasm_bindgen(js_name = "generateP256Keypair")]
@@ -271,6 +289,18 @@ pub fn wasm_value_diff(
     to_js_value(&diffs)
 }
 
+#[wasm_bindgen(js_name = "valueDiffGranular")]
+pub fn wasm_value_diff_granular(
+    old_view: JsValue,
+    new_view: JsValue,
+    prefix: Option<String>,
+) -> Result<JsValue, JsValue> {
+    let old: Value = serde_wasm_bindgen::from_value(old_view).map_err(to_js_error)?;
+    let new: Value = serde_wasm_bindgen::from_value(new_view).map_err(to_js_error)?;
+    let diffs = value_diff_granular(&old, &new, prefix.as_deref());
+    to_js_value(&diffs)
+}
+
 #[wasm_bindgen(js_name = "signEditEntry")]
 pub fn wasm_sign_edit_entry(
     private_key_jwk: JsValue,
@@ -2,10 +2,16 @@
 
 use crate::error::{to_js_error, to_js_value};
 use betterbase_sync_core::{
-    build_membership_signing_message, decrypt_inbound, decrypt_membership_payload, derive_forward,
-    encrypt_membership_payload, encrypt_outbound, pad_to_bucket, parse_membership_entry,
-    peek_epoch, rewrap_deks, serialize_membership_entry, unpad, verify_membership_entry,
-    BlobEnvelope, EpochKeyCache, MembershipEntryType, DEFAULT_PADDING_BUCKETS,
+    build_membership_signing_message, build_membership_signing_message_v2,
+    build_space_policy_signing_message, decrypt_channel_guarded, decrypt_inbound,
+    decrypt_inbound_batch, decrypt_inbound_guarded, decrypt_membership_payload, derive_forward,
+    encrypt_channel_sequenced, encrypt_membership_payload, encrypt_outbound,
+    encrypt_outbound_sequenced, pad_membership_entry, pad_to_bucket, parse_membership_entry,
+    parse_space_policy_entry, peek_epoch, resolve_space_delete_policy, resolve_space_wire_policy,
+    rewrap_deks, rotate_space_epoch, serialize_membership_entry, unpad, unpad_membership_entry,
+    validate_epoch_sequence, verify_membership_entry, verify_space_policy_entry, BlobEnvelope,
+    ChannelKind, DeleteKind, EpochKeyCache, MembershipEntryType, MembershipSigningVersion,
+    ReplayGuard, SpaceDeletePolicy, WrappedDek, DEFAULT_PADDING_BUCKETS,
 };
 use wasm_bindgen::prelude::*;
 
@@ -21,9 +27,23 @@ pub fn wasm_unpad(data: &[u8]) -> Result<Vec<u8>, JsValue> {
     unpad(data, DEFAULT_PADDING_BUCKETS).map_err(to_js_error)
 }
 
+#[wasm_bindgen(js_name = "padMembershipEntry")]
+pub fn wasm_pad_membership_entry(serialized: &[u8]) -> Result<Vec<u8>, JsValue> {
+    pad_membership_entry(serialized).map_err(to_js_error)
+}
+
+#[wasm_bindgen(js_name = "unpadMembershipEntry")]
+pub fn wasm_unpad_membership_entry(padded: &[u8]) -> Result<Vec<u8>, JsValue> {
+    unpad_membership_entry(padded).map_err(to_js_error)
+}
+
 // --- Transport encrypt/decrypt ---
 
+/// With a `sequence` (a `bigint`, increasing per sender), the frame is
+/// bound to it and to `sender` (the caller's DID, required with a sequence)
+/// for replay checks by `decryptInboundGuarded`.
 #[wasm_bindgen(js_name = "encryptOutbound")]
+#[allow(clippy::too_many_arguments)]
 pub fn wasm_encrypt_outbound(
     collection: &str,
     version: u32,
@@ -34,19 +54,36 @@ pub fn wasm_encrypt_outbound(
     base_epoch: u32,
     current_epoch: u32,
     space_id: &str,
+    archived: Option<bool>,
+    sequence: Option<u64>,
+    sender: Option<String>,
 ) -> Result<JsValue, JsValue> {
     let envelope = BlobEnvelope {
         c: collection.to_string(),
         v: version as u64,
         crdt: crdt.to_vec(),
         h: edit_chain,
+        a: archived.unwrap_or(false),
+        p: None,
+        hs: None,
     };
     let mut cache = EpochKeyCache::new(epoch_key, base_epoch, space_id);
     cache.update_encryption_epoch(current_epoch);
 
-    let (blob, wrapped_dek) =
-        encrypt_outbound(&envelope, record_id, &mut cache, DEFAULT_PADDING_BUCKETS)
-            .map_err(to_js_error)?;
+    let (blob, wrapped_dek) = match sequence {
+        Some(sequence) => encrypt_outbound_sequenced(
+            &envelope,
+            record_id,
+            sender
+                .as_deref()
+                .ok_or_else(|| JsValue::from_str("sender is required with a sequence"))?,
+            sequence,
+            &mut cache,
+            DEFAULT_PADDING_BUCKETS,
+        ),
+        None => encrypt_outbound(&envelope, record_id, &mut cache, DEFAULT_PADDING_BUCKETS),
+    }
+    .map_err(to_js_error)?;
 
     // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
     let result = js_sys::Object::new();
@@ -84,7 +121,205 @@ pub fn wasm_decrypt_inbound(
         DEFAULT_PADDING_BUCKETS,
     )
     .map_err(to_js_error)?;
+    Ok(envelope_to_js(&envelope))
+}
+
+/// `decryptInbound` with replay protection per space and `sender`.
+///
+/// `replayGuard` is the JSON state returned by the previous call (omit it
+/// to start fresh). Returns `{ envelope, replayGuard }`; persist the new
+/// `replayGuard` to keep rejecting replays across reloads. A failed call
+/// leaves the previous state valid. Frames without a `sequence` are only
+/// accepted when `allowUnsequenced` is set.
+#[wasm_bindgen(js_name = "decryptInboundGuarded")]
+pub fn wasm_decrypt_inbound_guarded(
+    blob: &[u8],
+    wrapped_dek: &[u8],
+    record_id: &str,
+    epoch_key: &[u8],
+    base_epoch: u32,
+    space_id: &str,
+    sender: &str,
+    sequence: Option<u64>,
+    replay_guard: Option<String>,
+    allow_unsequenced: bool,
+) -> Result<JsValue, JsValue> {
+    let mut guard = match replay_guard {
+        Some(json) => serde_json::from_str::<ReplayGuard>(&json).map_err(to_js_error)?,
+        None => ReplayGuard::default(),
+    };
+    guard.set_allow_unsequenced(allow_unsequenced);
+    let mut cache = EpochKeyCache::new(epoch_key, base_epoch, space_id);
+
+    let envelope = decrypt_inbound_guarded(
+        blob,
+        wrapped_dek,
+        record_id,
+        sender,
+        sequence,
+        &mut cache,
+        DEFAULT_PADDING_BUCKETS,
+        &mut guard,
+    )
+    .map_err(to_js_error)?;
+    let guard_json = serde_json::to_string(&guard).map_err(to_js_error)?;
+
+    // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"envelope".into(), &envelope_to_js(&envelope)).unwrap();
+    js_sys::Reflect::set(
+        &result,
+        &"replayGuard".into(),
+        &JsValue::from_str(&guard_json),
+    )
+    .unwrap();
+    Ok(result.into())
+}
+
+// --- Channel frames ---
+
+fn channel_kind(kind: &str) -> Result<ChannelKind, JsValue> {
+    match kind {
+        "presence" => Ok(ChannelKind::Presence),
+        "event" => Ok(ChannelKind::Event),
+        other => Err(JsValue::from_str(&format!(
+            "unknown channel kind {other:?} (expected \"presence\" or \"event\")"
+        ))),
+    }
+}
+
+/// Encrypt a presence or event payload (`kind` is `"presence"` or
+/// `"event"`) bound to `sender` (the caller's DID) and `sequence`, a
+/// `bigint` increasing per sender.
+#[wasm_bindgen(js_name = "encryptChannelSequenced")]
+pub fn wasm_encrypt_channel_sequenced(
+    kind: &str,
+    channel_key: &[u8],
+    space_id: &str,
+    sender: &str,
+    sequence: u64,
+    payload: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    encrypt_channel_sequenced(
+        channel_kind(kind)?,
+        channel_key,
+        space_id,
+        sender,
+        sequence,
+        payload,
+    )
+    .map_err(to_js_error)
+}
+
+/// Decrypt a presence or event frame with replay protection per space and
+/// `sender`. `replayGuard` and the `{ payload, replayGuard }` result work as
+/// in `decryptInboundGuarded`.
+#[wasm_bindgen(js_name = "decryptChannelGuarded")]
+#[allow(clippy::too_many_arguments)]
+pub fn wasm_decrypt_channel_guarded(
+    kind: &str,
+    channel_key: &[u8],
+    space_id: &str,
+    sender: &str,
+    sequence: Option<u64>,
+    frame: &[u8],
+    replay_guard: Option<String>,
+    allow_unsequenced: bool,
+) -> Result<JsValue, JsValue> {
+    let mut guard = match replay_guard {
+        Some(json) => serde_json::from_str::<ReplayGuard>(&json).map_err(to_js_error)?,
+        None => ReplayGuard::default(),
+    };
+    guard.set_allow_unsequenced(allow_unsequenced);
+
+    let payload = decrypt_channel_guarded(
+        channel_kind(kind)?,
+        channel_key,
+        space_id,
+        sender,
+        sequence,
+        frame,
+        &mut guard,
+    )
+    .map_err(to_js_error)?;
+    let guard_json = serde_json::to_string(&guard).map_err(to_js_error)?;
+
+    // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(
+        &result,
+        &"payload".into(),
+        &js_sys::Uint8Array::from(payload.as_slice()),
+    )
+    .unwrap();
+    js_sys::Reflect::set(
+        &result,
+        &"replayGuard".into(),
+        &JsValue::from_str(&guard_json),
+    )
+    .unwrap();
+    Ok(result.into())
+}
+
+/// Decrypt many pulled records in one boundary crossing.
+///
+/// `blobs`, `wrapped_deks` (arrays of `Uint8Array`) and `record_ids` are
+/// parallel. Returns one `{ ok: envelope }` or `{ error: message }` per
+/// item, where `envelope` has the shape returned by `decryptInbound`.
+#[wasm_bindgen(js_name = "decryptBatch")]
+pub fn wasm_decrypt_batch(
+    blobs: js_sys::Array,
+    wrapped_deks: js_sys::Array,
+    record_ids: Vec<String>,
+    epoch_key: &[u8],
+    base_epoch: u32,
+    space_id: &str,
+) -> Result<JsValue, JsValue> {
+    let len = record_ids.len();
+    if blobs.length() as usize != len || wrapped_deks.length() as usize != len {
+        return Err(JsValue::from_str(
+            "blobs, wrappedDeks and recordIds must have the same length",
+        ));
+    }
+    let blobs = bytes_array(&blobs, "blobs")?;
+    let wrapped_deks = bytes_array(&wrapped_deks, "wrappedDeks")?;
+
+    let mut cache = EpochKeyCache::new(epoch_key, base_epoch, space_id);
+    let items = blobs
+        .iter()
+        .zip(&wrapped_deks)
+        .zip(&record_ids)
+        .map(|((blob, dek), id)| (blob.as_slice(), dek.as_slice(), id.as_str()));
+    let results = decrypt_inbound_batch(items, &mut cache, DEFAULT_PADDING_BUCKETS);
+
+    // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
+    let out = js_sys::Array::new_with_length(len as u32);
+    for (i, result) in results.into_iter().enumerate() {
+        let (key, value) = match result {
+            Ok(envelope) => ("ok", envelope_to_js(&envelope)),
+            Err(e) => ("error", JsValue::from_str(&e.to_string())),
+        };
+        let item = js_sys::Object::new();
+        js_sys::Reflect::set(&item, &key.into(), &value).unwrap();
+        out.set(i as u32, item.into());
+    }
+    Ok(out.into())
+}
+
+/// Copy a JS array of `Uint8Array`s into Rust.
+fn bytes_array(array: &js_sys::Array, name: &str) -> Result<Vec<Vec<u8>>, JsValue> {
+    array
+        .iter()
+        .map(|v| {
+            v.dyn_into::<js_sys::Uint8Array>()
+                .map(|bytes| bytes.to_vec())
+                .map_err(|_| JsValue::from_str(&format!("{name} must contain only Uint8Arrays")))
+        })
+        .collect()
+}
 
+/// Convert a decrypted envelope to the JS object returned by `decryptInbound`.
+fn envelope_to_js(envelope: &BlobEnvelope) -> JsValue {
     // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
     let result = js_sys::Object::new();
     js_sys::Reflect::set(
@@ -108,7 +343,10 @@ pub fn wasm_decrypt_inbound(
     if let Some(ref h) = envelope.h {
         js_sys::Reflect::set(&result, &"editChain".into(), &JsValue::from_str(h)).unwrap();
     }
-    Ok(result.into())
+    if envelope.a {
+        js_sys::Reflect::set(&result, &"archived".into(), &JsValue::TRUE).unwrap();
+    }
+    result.into()
 }
 
 // --- Epoch / re-encryption ---
@@ -118,6 +356,15 @@ pub fn wasm_peek_epoch(wrapped_dek: &[u8]) -> Result<u32, JsValue> {
     peek_epoch(wrapped_dek).map_err(to_js_error)
 }
 
+#[wasm_bindgen(js_name = "validateEpochSequence")]
+pub fn wasm_validate_epoch_sequence(
+    wrapped_deks: js_sys::Array,
+    min_acceptable_epoch: u32,
+) -> Result<(), JsValue> {
+    let wrapped_deks = bytes_array(&wrapped_deks, "wrappedDeks")?;
+    validate_epoch_sequence(&wrapped_deks, min_acceptable_epoch).map_err(to_js_error)
+}
+
 #[wasm_bindgen(js_name = "deriveForward")]
 pub fn wasm_derive_forward(
     key: &[u8],
@@ -137,9 +384,8 @@ pub fn wasm_rewrap_deks(
     new_epoch: u32,
     space_id: &str,
 ) -> Result<String, JsValue> {
-    let input: Vec<(String, Vec<u8>)> =
-        serde_json::from_str(wrapped_deks_json).map_err(to_js_error)?;
-    let result = rewrap_deks(
+    let input: Vec<WrappedDek> = serde_json::from_str(wrapped_deks_json).map_err(to_js_error)?;
+    let report = rewrap_deks(
         &input,
         current_key,
         current_epoch,
@@ -148,7 +394,81 @@ pub fn wasm_rewrap_deks(
         space_id,
     )
     .map_err(to_js_error)?;
-    serde_json::to_string(&result).map_err(to_js_error)
+    serde_json::to_string(&report.rewrapped).map_err(to_js_error)
+}
+
+/// Re-wrap every record DEK of a space under a new epoch key.
+///
+/// `record_ids` and `wrapped_deks` (array of `Uint8Array`) are parallel.
+/// Returns `{ rewrapped: { recordId, wrappedDek }[], failures: { recordId,
+/// error }[], skipped: string[] }`.
+#[wasm_bindgen(js_name = "rotateSpaceEpoch")]
+pub fn wasm_rotate_space_epoch(
+    record_ids: Vec<String>,
+    wrapped_deks: js_sys::Array,
+    old_epoch_key: &[u8],
+    old_epoch: u32,
+    new_epoch_key: &[u8],
+    new_epoch: u32,
+    space_id: &str,
+) -> Result<JsValue, JsValue> {
+    if wrapped_deks.length() as usize != record_ids.len() {
+        return Err(JsValue::from_str(
+            "recordIds and wrappedDeks must have the same length",
+        ));
+    }
+    let wrapped_deks = bytes_array(&wrapped_deks, "wrappedDeks")?;
+    let report = rotate_space_epoch(
+        record_ids.into_iter().zip(wrapped_deks),
+        old_epoch_key,
+        old_epoch,
+        new_epoch_key,
+        new_epoch,
+        space_id,
+    )
+    .map_err(to_js_error)?;
+
+    // Reflect::set on a plain Object cannot fail (no proxy traps, no sealed object).
+    let rewrapped = js_sys::Array::new();
+    for record in &report.rewrapped {
+        let item = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &item,
+            &"recordId".into(),
+            &JsValue::from_str(&record.record_id),
+        )
+        .unwrap();
+        js_sys::Reflect::set(
+            &item,
+            &"wrappedDek".into(),
+            &js_sys::Uint8Array::from(record.wrapped_dek.as_slice()),
+        )
+        .unwrap();
+        rewrapped.push(&item);
+    }
+    let failures = js_sys::Array::new();
+    for failure in &report.failures {
+        let item = js_sys::Object::new();
+        js_sys::Reflect::set(
+            &item,
+            &"recordId".into(),
+            &JsValue::from_str(&failure.record_id),
+        )
+        .unwrap();
+        js_sys::Reflect::set(&item, &"error".into(), &JsValue::from_str(&failure.reason)).unwrap();
+        failures.push(&item);
+    }
+    let skipped: js_sys::Array = report
+        .skipped
+        .iter()
+        .map(|id| JsValue::from_str(id))
+        .collect();
+
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"rewrapped".into(), &rewrapped).unwrap();
+    js_sys::Reflect::set(&result, &"failures".into(), &failures).unwrap();
+    js_sys::Reflect::set(&result, &"skipped".into(), &skipped).unwrap();
+    Ok(result.into())
 }
 
 // --- Membership ---
@@ -173,6 +493,29 @@ pub fn wasm_build_membership_signing_message(
     ))
 }
 
+/// v2 signing message: the UCAN is embedded in canonical JSON form.
+/// Entries signed this way must be serialized with `"v":2`.
+#[wasm_bindgen(js_name = "buildMembershipSigningMessageV2")]
+pub fn wasm_build_membership_signing_message_v2(
+    entry_type: &str,
+    space_id: &str,
+    signer_did: &str,
+    ucan: &str,
+    signer_handle: &str,
+    recipient_handle: &str,
+) -> Result<Vec<u8>, JsValue> {
+    let et = parse_entry_type(entry_type)?;
+    build_membership_signing_message_v2(
+        et,
+        space_id,
+        signer_did,
+        ucan,
+        signer_handle,
+        recipient_handle,
+    )
+    .map_err(to_js_error)
+}
+
 #[wasm_bindgen(js_name = "parseMembershipEntry")]
 pub fn wasm_parse_membership_entry(payload: &str) -> Result<JsValue, JsValue> {
     let entry = parse_membership_entry(payload).map_err(to_js_error)?;
@@ -212,6 +555,9 @@ pub fn wasm_parse_membership_entry(payload: &str) -> Result<JsValue, JsValue> {
     if let Some(ref h) = entry.recipient_handle {
         js_sys::Reflect::set(&obj, &"recipientHandle".into(), &JsValue::from_str(h)).unwrap();
     }
+    if entry.signing_version == MembershipSigningVersion::V2 {
+        js_sys::Reflect::set(&obj, &"signingVersion".into(), &JsValue::from(2)).unwrap();
+    }
     Ok(obj.into())
 }
 
@@ -247,6 +593,67 @@ pub fn wasm_decrypt_membership_payload(
     decrypt_membership_payload(encrypted, key, space_id, seq).map_err(to_js_error)
 }
 
+// --- Space policy ---
+
+#[wasm_bindgen(js_name = "buildSpacePolicySigningMessage")]
+pub fn wasm_build_space_policy_signing_message(
+    space_id: &str,
+    signer_did: &str,
+    ucan: &str,
+    writer_delete: &str,
+    min_wire_version: Option<u8>,
+) -> Result<Vec<u8>, JsValue> {
+    let policy = SpaceDeletePolicy {
+        writer_delete: parse_delete_kind(writer_delete)?,
+    };
+    Ok(build_space_policy_signing_message(
+        space_id,
+        signer_did,
+        ucan,
+        &policy,
+        min_wire_version,
+    ))
+}
+
+#[wasm_bindgen(js_name = "verifySpacePolicyEntry")]
+pub fn wasm_verify_space_policy_entry(payload: &str, space_id: &str) -> Result<bool, JsValue> {
+    let entry = parse_space_policy_entry(payload).map_err(to_js_error)?;
+    verify_space_policy_entry(&entry, space_id).map_err(to_js_error)
+}
+
+/// Resolve the effective writer delete kind from policy entry payloads,
+/// oldest first. Unparseable entries are skipped like unverifiable ones.
+#[wasm_bindgen(js_name = "resolveSpaceDeletePolicy")]
+pub fn wasm_resolve_space_delete_policy(payloads: Vec<String>, space_id: &str) -> String {
+    let entries: Vec<_> = payloads
+        .iter()
+        .filter_map(|p| parse_space_policy_entry(p).ok())
+        .collect();
+    resolve_space_delete_policy(&entries, space_id)
+        .writer_delete
+        .as_str()
+        .to_string()
+}
+
+/// Resolve the space's minimum wire version from policy entry payloads,
+/// oldest first. Returns `undefined` when the space sets none.
+#[wasm_bindgen(js_name = "resolveSpaceWireMinimum")]
+pub fn wasm_resolve_space_wire_minimum(payloads: Vec<String>, space_id: &str) -> Option<u8> {
+    let entries: Vec<_> = payloads
+        .iter()
+        .filter_map(|p| parse_space_policy_entry(p).ok())
+        .collect();
+    resolve_space_wire_policy(&entries, space_id).min_version
+}
+
+fn parse_delete_kind(s: &str) -> Result<DeleteKind, JsValue> {
+    match s {
+        "tombstone" => Ok(DeleteKind::Tombstone),
+        "archive" => Ok(DeleteKind::Archive),
+        _ => Err(JsValue::from_str(&format!("invalid delete kind: {}", s))),
+    }
+}
+
 fn parse_entry_type(s: &str) -> Result<MembershipEntryType, JsValue> {
     match s {
         "d" => Ok(MembershipEntryType::Delegation),
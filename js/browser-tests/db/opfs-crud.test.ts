@@ -1,5 +1,6 @@
 import { describe, it, expect, beforeEach, afterEach } from "vitest";
 import type { Database } from "../../src/db/index.js";
+import { BetterbaseDbError } from "../../src/db/index.js";
 import {
   buildUsersCollection,
   openFreshOpfsDb,
@@ -114,4 +115,16 @@ describe("OPFS CRUD", () => {
     const deleted = await db.delete(users, "nonexistent");
     expect(deleted).toBe(false);
   });
+
+  it("unique violation rejects with a coded BetterbaseDbError", async () => {
+    await db.put(users, { name: "Alice", email: "dup@test.com", age: 30 });
+
+    const err = await db
+      .put(users, { name: "Bob", email: "dup@test.com", age: 25 })
+      .catch((e: unknown) => e);
+
+    expect(err).toBeInstanceOf(BetterbaseDbError);
+    expect((err as BetterbaseDbError).code).toBe("STORAGE_UNIQUE");
+    expect((err as BetterbaseDbError).collection).toBe("users");
+  });
 });
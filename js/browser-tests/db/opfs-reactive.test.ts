@@ -77,4 +77,31 @@ describe("OPFS reactive", () => {
 
     unsub();
   });
+
+  it("flush resolves after initial snapshots are delivered", async () => {
+    const alice = await db.put(users, {
+      name: "Alice",
+      email: "alice@test.com",
+      age: 30,
+    });
+
+    const records: unknown[] = [];
+    const queries: unknown[] = [];
+    const unsubRecord = db.observe(users, alice.id, (record) => {
+      records.push(record);
+    });
+    const unsubQuery = db.observeQuery(users, {}, (result) => {
+      queries.push(result);
+    });
+
+    await db.flush();
+
+    expect(records.length).toBe(1);
+    expect((records[0] as { name: string }).name).toBe("Alice");
+    expect(queries.length).toBe(1);
+    expect((queries[0] as { records: unknown[] }).records.length).toBe(1);
+
+    unsubRecord();
+    unsubQuery();
+  });
 });
@@ -88,6 +88,21 @@ export function valueDiff(
   return ensureWasm().valueDiff(oldView, newView, prefix);
 }
 
+/**
+ * Like {@link valueDiff}, but diffs arrays element by element.
+ *
+ * - Same-length arrays: one diff per changed element at `path.<index>`,
+ *   recursing into object elements (`tags.0.name`)
+ * - Length change: full-value replacement at the array's path
+ */
+export function valueDiffGranular(
+  oldView: Record<string, unknown>,
+  newView: Record<string, unknown>,
+  prefix?: string,
+): EditDiff[] {
+  return ensureWasm().valueDiffGranular(oldView, newView, prefix);
+}
+
 /** Serialize an edit chain to a JSON string for storage in BlobEnvelope.h. */
 export function serializeEditChain(entries: EditEntry[]): string {
   return ensureWasm().serializeEditChain(entries);
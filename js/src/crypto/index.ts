@@ -35,6 +35,7 @@ export {
   verifyEditEntry,
   verifyEditChain,
   valueDiff,
+  valueDiffGranular,
   serializeEditChain,
   parseEditChain,
   reconstructState,
@@ -95,14 +95,22 @@ export class SyncCrypto implements Disposable {
  * @param data - Plaintext bytes to encrypt
  * @param dek - 32-byte Data Encryption Key for this record
  * @param context - Optional encryption context for AAD binding (spaceId + recordId)
- * @returns Encrypted blob: [version=4:1B][IV:12B][ciphertext+tag]
+ * @param version - Wire version to write: 4 (AES-256-GCM, default) or 5 (AES-256-GCM-SIV)
+ * @returns Encrypted blob: [version:1B][IV:12B][ciphertext+tag]
  */
 export function encryptV4(
   data: Uint8Array,
   dek: Uint8Array,
   context?: EncryptionContext,
+  version?: number,
 ): Uint8Array {
-  return ensureWasm().encryptV4(data, dek, context?.spaceId, context?.recordId);
+  return ensureWasm().encryptV4(
+    data,
+    dek,
+    context?.spaceId,
+    context?.recordId,
+    version,
+  );
 }
 
 /**
@@ -94,6 +94,20 @@ describe("collection builder", () => {
     });
   });
 
+  it("declares named unique indexes", () => {
+    const def = collection("members")
+      .v(1, { orgId: t.string(), email: t.string() })
+      .unique(["orgId", "email"], "org_email_unique")
+      .build();
+
+    const bp = def[BLUEPRINT];
+    expect(bp.indexes[0]).toEqual({
+      type: "field",
+      fields: ["orgId", "email"],
+      options: { name: "org_email_unique", unique: true },
+    });
+  });
+
   // --------------------------------------------------------------------------
   // Computed indexes
   // --------------------------------------------------------------------------
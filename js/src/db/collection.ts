@@ -80,6 +80,12 @@ export interface CollectionBuilderWithVersions<
   /** Define a field index. */
   index(fields: string[], options?: IndexOptions): this;
 
+  /**
+   * Define a named unique index, e.g.
+   * `.unique(["orgId", "email"], "org_email_unique")`.
+   */
+  unique(fields: string[], name: string): this;
+
   /** Define a computed index. */
   computed(
     name: string,
@@ -89,6 +95,12 @@ export interface CollectionBuilderWithVersions<
     options?: ComputedOptions,
   ): this;
 
+  /**
+   * Keep tombstones for at least `ms` after deletion; `purgeTombstones()`
+   * then drops the ones the server has acknowledged.
+   */
+  tombstoneRetention(ms: number): this;
+
   /** Build the collection definition. */
   build(): CollectionDefHandle<TName, TSchema>;
 }
@@ -128,17 +140,20 @@ class CollectionBuilderWithVersionsImpl<
   #currentSchema: TSchema;
   #versions: VersionEntry[];
   #indexes: IndexEntry[];
+  #tombstoneRetentionMs: number | undefined;
 
   constructor(
     name: TName,
     currentSchema: TSchema,
     versions: VersionEntry[],
     indexes: IndexEntry[],
+    tombstoneRetentionMs?: number,
   ) {
     this.#name = name;
     this.#currentSchema = currentSchema;
     this.#versions = versions;
     this.#indexes = indexes;
+    this.#tombstoneRetentionMs = tombstoneRetentionMs;
   }
 
   /**
@@ -162,6 +177,7 @@ class CollectionBuilderWithVersionsImpl<
       schema,
       [...this.#versions, { version, schema, migrate }],
       [],
+      this.#tombstoneRetentionMs,
     );
   }
 
@@ -170,6 +186,10 @@ class CollectionBuilderWithVersionsImpl<
     return this;
   }
 
+  unique(fields: string[], name: string): this {
+    return this.index(fields, { name, unique: true });
+  }
+
   computed(
     name: string,
     compute: (
@@ -181,6 +201,16 @@ class CollectionBuilderWithVersionsImpl<
     return this;
   }
 
+  tombstoneRetention(ms: number): this {
+    if (!Number.isFinite(ms) || ms < 0) {
+      throw new Error(
+        `[betterbase-db] collection "${this.#name}": tombstone retention must be a non-negative number of milliseconds`,
+      );
+    }
+    this.#tombstoneRetentionMs = ms;
+    return this;
+  }
+
   build(): CollectionDefHandle<TName, TSchema> {
     return {
       name: this.#name,
@@ -189,6 +219,7 @@ class CollectionBuilderWithVersionsImpl<
       [BLUEPRINT]: {
         versions: this.#versions,
         indexes: this.#indexes,
+        tombstoneRetentionMs: this.#tombstoneRetentionMs,
       },
     };
   }
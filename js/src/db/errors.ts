@@ -0,0 +1,91 @@
+/**
+ * Structured errors thrown by the WASM DB bindings.
+ *
+ * The Rust side throws a JS `Error` named "BetterbaseDbError" with a stable
+ * `code` (e.g. "STORAGE_UNIQUE") and, when the failure names them, the
+ * `collection` and `field` involved. Unique-constraint violations are named
+ * "UniqueConstraintError" instead and also carry the `index`, the colliding
+ * `fields` and the `existingId` of the record holding them. These fields do
+ * not survive
+ * `postMessage` on an `Error`, so the worker flattens them into its response
+ * and the main thread rebuilds the error here.
+ */
+
+/** Error raised by the database with a machine-readable code. */
+export class BetterbaseDbError extends Error {
+  override name = "BetterbaseDbError";
+
+  constructor(
+    message: string,
+    public readonly code: string,
+    public readonly collection?: string,
+    public readonly field?: string,
+  ) {
+    super(message);
+  }
+}
+
+/** A write collided with a unique index. `code` is "STORAGE_UNIQUE". */
+export class UniqueConstraintError extends BetterbaseDbError {
+  override name = "UniqueConstraintError";
+
+  constructor(
+    message: string,
+    collection: string | undefined,
+    public readonly index: string,
+    public readonly fields: Record<string, unknown>,
+    public readonly existingId: string,
+  ) {
+    super(message, "STORAGE_UNIQUE", collection, index);
+  }
+}
+
+/** Wire form of a thrown error, as carried on a worker response. */
+export interface SerializedDbError {
+  error: string;
+  code?: string;
+  collection?: string;
+  field?: string;
+  index?: string;
+  fields?: Record<string, unknown>;
+  existingId?: string;
+}
+
+/** Flatten a caught value into its wire form, keeping any structured fields. */
+export function serializeDbError(e: unknown): SerializedDbError {
+  const error = e instanceof Error ? e.message : String(e);
+  if (typeof e !== "object" || e === null) return { error };
+  const { code, collection, field, index, fields, existingId } = e as Record<
+    string,
+    unknown
+  >;
+  return {
+    error,
+    ...(typeof code === "string" && { code }),
+    ...(typeof collection === "string" && { collection }),
+    ...(typeof field === "string" && { field }),
+    ...(typeof index === "string" && { index }),
+    ...(typeof fields === "object" &&
+      fields !== null && { fields: fields as Record<string, unknown> }),
+    ...(typeof existingId === "string" && { existingId }),
+  };
+}
+
+/** Rebuild the error a worker response describes. */
+export function deserializeDbError(e: SerializedDbError): Error {
+  if (e.code === undefined) return new Error(e.error);
+  if (
+    e.code === "STORAGE_UNIQUE" &&
+    e.index !== undefined &&
+    e.existingId !== undefined
+  ) {
+    return new UniqueConstraintError(
+      e.error,
+      e.collection,
+      e.index,
+      e.fields ?? {},
+      e.existingId,
+    );
+  }
+  return new BetterbaseDbError(e.error, e.code, e.collection, e.field);
+}
@@ -49,7 +49,11 @@ export type {
   Query,
   QueryOptions,
   QueryResult,
+  AggregateMetric,
+  AggregateSpec,
+  AggregateRow,
   SortDirection,
+  NullsOrder,
   SortEntry,
   // CRUD options
   PutOptions,
@@ -57,10 +61,13 @@ export type {
   PatchOptions,
   DeleteOptions,
   ListOptions,
+  BatchOp,
   // Results
   BatchResult,
   BulkDeleteResult,
   RecordError,
+  UniqueConflict,
+  MigrationReport,
   // Change events
   ChangeEvent,
   // Sync types
@@ -96,6 +103,9 @@ export { initWasm, setWasmForTesting } from "./wasm-init.js";
 // Re-export builder option types
 export type { IndexOptions, ComputedOptions } from "./collection.js";
 
+// Errors
+export { BetterbaseDbError, UniqueConstraintError } from "./errors.js";
+
 // OPFS database
 export { Database } from "./opfs/OpfsDb.js";
 export { createDatabase } from "./createOpfsDb.js";
@@ -14,12 +14,16 @@ import type {
   CollectionPatch,
   QueryOptions,
   QueryResult,
+  AggregateSpec,
+  AggregateRow,
   PutOptions,
   GetOptions,
   DeleteOptions,
   ListOptions,
+  BatchOp,
   BatchResult,
   BulkDeleteResult,
+  MigrationReport,
   ChangeEvent,
   RemoteRecord,
   ApplyRemoteOptions,
@@ -219,6 +223,16 @@ export class Database {
     ])) as number;
   }
 
+  async aggregate<S extends SchemaShape>(
+    def: CollectionDefHandle<string, S>,
+    spec: AggregateSpec,
+  ): Promise<AggregateRow[]> {
+    return (await this.rpc.call("aggregate", [
+      def.name,
+      spec,
+    ])) as AggregateRow[];
+  }
+
   async getAll<S extends SchemaShape>(
     def: CollectionDefHandle<string, S>,
     options?: ListOptions,
@@ -283,6 +297,67 @@ export class Database {
     return result;
   }
 
+  /**
+   * Apply `ops` atomically in one storage transaction. Resolves to one
+   * result per op: the record for put/patch, the boolean for delete. If any
+   * op fails nothing is applied, no change events fire, and the rejection's
+   * `opIndex` names the failing op.
+   */
+  async batch(ops: BatchOp[]): Promise<unknown[]> {
+    const wire = ops.map((op) => {
+      switch (op.type) {
+        case "put":
+          return {
+            type: op.type,
+            collection: op.collection.name,
+            data: serializeForRust(op.data),
+            options: op.options ?? null,
+          };
+        case "patch": {
+          const { id, ...fields } = op.data;
+          return {
+            type: op.type,
+            collection: op.collection.name,
+            id,
+            data: serializeForRust(fields),
+            options: { ...op.options, id },
+          };
+        }
+        case "delete":
+          return {
+            type: op.type,
+            collection: op.collection.name,
+            id: op.id,
+            options: op.options ?? null,
+          };
+      }
+    });
+    const results = (await this.rpc.call("batch", [wire])) as unknown[];
+
+    const touched = new Map<string, string[]>();
+    const out = results.map((result, i) => {
+      const op = ops[i]!;
+      const ids = touched.get(op.collection.name) ?? [];
+      touched.set(op.collection.name, ids);
+      if (op.type === "delete") {
+        if (result) ids.push(op.id);
+        return result;
+      }
+      const record = deserializeFromRust(
+        result as Record<string, unknown>,
+        this.schemaFor(op.collection),
+      ) as Record<string, unknown>;
+      ids.push(record.id as string);
+      return record;
+    });
+    for (const [collection, ids] of touched) {
+      if (ids.length > 0) {
+        this.emitAndBroadcast({ type: "bulk", collection, ids });
+      }
+    }
+    return out;
+  }
+
   // ========================================================================
   // Observe (reactive subscriptions)
   // ========================================================================
@@ -384,6 +459,15 @@ export class Database {
     };
   }
 
+  /**
+   * Resolve once every observer registered so far has received its initial
+   * snapshot. Subscriptions and this call are processed by the worker in
+   * order, and their notifications are posted before the response.
+   */
+  async flush(): Promise<void> {
+    await this.rpc.call("flush", []);
+  }
+
   /**
    * Register a global change listener. Returns an unsubscribe function synchronously.
    *
@@ -397,6 +481,80 @@ export class Database {
     };
   }
 
+  // ========================================================================
+  // Maintenance
+  // ========================================================================
+
+  /**
+   * Eagerly migrate every stale record in `def` to the current schema
+   * version. Reads migrate lazily anyway; this pays the cost up front.
+   * Records whose migration fails are reported, not dropped.
+   */
+  async migrateAll(def: CollectionDefHandle): Promise<MigrationReport> {
+    const report = (await this.rpc.call("migrateAll", [
+      def.name,
+    ])) as MigrationReport;
+    if (report.migrated.length > 0) {
+      this.emitAndBroadcast({
+        type: "bulk",
+        collection: def.name,
+        ids: report.migrated,
+      });
+    }
+    return report;
+  }
+
+  /**
+   * Drop and recreate every index of `def` and recompute stored computed
+   * values, so an index added after records were written sees them.
+   * Resolves to the number of records reindexed.
+   */
+  async reindex(def: CollectionDefHandle): Promise<number> {
+    return (await this.rpc.call("reindex", [def.name])) as number;
+  }
+
+  /**
+   * Hard-remove every record of `def`, with its history and index entries,
+   * leaving other collections alone. Nothing is pushed to the server. With
+   * `resetSyncState`, the pull cursor is cleared too, so the next sync
+   * downloads the collection again. Resolves to the number of records
+   * removed.
+   */
+  async resetCollection(
+    def: CollectionDefHandle,
+    options?: { resetSyncState?: boolean },
+  ): Promise<number> {
+    const removed = (await this.rpc.call("resetCollection", [
+      def.name,
+      options?.resetSyncState ?? false,
+    ])) as number;
+    if (removed > 0) {
+      // The removed ids aren't returned; an empty list marks the whole collection
+      this.emitAndBroadcast({ type: "bulk", collection: def.name, ids: [] });
+    }
+    return removed;
+  }
+
+  /**
+   * Purge tombstones older than each collection's `tombstoneRetention` that
+   * the server has acknowledged; unpushed deletes are kept. Collections
+   * without a retention are skipped. Resolves to the purged count per
+   * collection.
+   */
+  async purgeTombstones(): Promise<Record<string, number>> {
+    const report = (await this.rpc.call("purgeTombstones", [])) as Record<
+      string,
+      number
+    >;
+    for (const [collection, purged] of Object.entries(report)) {
+      if (purged > 0) {
+        // Refresh includeDeleted queries that still show the purged rows
+        this.emitAndBroadcast({ type: "bulk", collection, ids: [] });
+      }
+    }
+    return report;
+  }
+
   // ========================================================================
   // Sync storage
   // ========================================================================
@@ -411,6 +569,7 @@ export class Database {
       _v: r.version,
       crdt: new Uint8Array(r.crdt),
       deleted: r.deleted,
+      archived: r.archived ?? false,
       sequence: r.sequence,
       meta: r.meta ?? undefined,
       pendingPatchesLength: r.pending_patches.length,
@@ -442,6 +601,7 @@ export class Database {
       version: r._v,
       crdt: r.crdt ? Array.from(r.crdt) : null,
       deleted: r.deleted,
+      archived: r.archived ?? false,
       sequence: r.sequence,
       meta: r.meta,
     }));
@@ -11,6 +11,7 @@ import type {
   WorkerNotification,
 } from "./types.js";
 import type { WasmDbInstance } from "../wasm-init.js";
+import { serializeDbError } from "../errors.js";
 
 export class OpfsWorkerHost {
   private wasm: WasmDbInstance;
@@ -47,8 +48,11 @@ export class OpfsWorkerHost {
             self.postMessage(response);
           },
           (e) => {
-            const error = e instanceof Error ? e.message : String(e);
-            const response: WorkerResponse = { type: "response", id, error };
+            const response: WorkerResponse = {
+              type: "response",
+              id,
+              ...serializeDbError(e),
+            };
             self.postMessage(response);
           },
         );
@@ -57,8 +61,11 @@ export class OpfsWorkerHost {
         self.postMessage(response);
       }
     } catch (e) {
-      const error = e instanceof Error ? e.message : String(e);
-      const response: WorkerResponse = { type: "response", id, error };
+      const response: WorkerResponse = {
+        type: "response",
+        id,
+        ...serializeDbError(e),
+      };
       self.postMessage(response);
     }
   }
@@ -92,6 +99,8 @@ export class OpfsWorkerHost {
         return this.wasm.query(args[0] as string, args[1]);
       case "count":
         return this.wasm.count(args[0] as string, args[1] ?? null);
+      case "aggregate":
+        return this.wasm.aggregate(args[0] as string, args[1]);
       case "getAll":
         return this.wasm.getAll(args[0] as string, args[1] ?? null);
 
@@ -108,6 +117,8 @@ export class OpfsWorkerHost {
           args[1] as string[],
           args[2] ?? null,
         );
+      case "batch":
+        return this.wasm.batch(args[0] as unknown[]);
 
       // Reactive subscriptions
       case "observe":
@@ -116,6 +127,21 @@ export class OpfsWorkerHost {
         return this.handleObserveQuery(requestId, args);
       case "onChange":
         return this.handleOnChange(requestId, args);
+      case "flush":
+        return this.wasm.flushAsync();
+
+      // Maintenance
+      case "migrateAll":
+        return this.wasm.migrateAll(args[0] as string);
+      case "reindex":
+        return this.wasm.reindex(args[0] as string);
+      case "resetCollection":
+        return this.wasm.resetCollection(
+          args[0] as string,
+          args[1] as boolean,
+        );
+      case "purgeTombstones":
+        return this.wasm.purgeTombstones();
 
       // Sync
       case "getDirty":
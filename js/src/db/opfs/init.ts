@@ -20,6 +20,7 @@ import type { CollectionDefHandle, CollectionBlueprint } from "../types.js";
 import { BLUEPRINT } from "../types.js";
 import type { MainToWorkerMessage, WorkerResponse } from "./types.js";
 import { OpfsWorkerHost } from "./OpfsWorkerHost.js";
+import { serializeDbError } from "../errors.js";
 
 export function initWorker(collections: CollectionDefHandle[]): void {
   // We need to listen for an "open" message with the database name.
@@ -80,6 +81,10 @@ export function initWorker(collections: CollectionDefHandle[]): void {
           }
         }
 
+        if (blueprint.tombstoneRetentionMs !== undefined) {
+          builder.tombstoneRetention(blueprint.tombstoneRetentionMs);
+        }
+
         wasmDefs.push(builder.build());
       }
 
@@ -99,11 +104,10 @@ export function initWorker(collections: CollectionDefHandle[]): void {
       };
       self.postMessage(response);
     } catch (e) {
-      const error = e instanceof Error ? e.message : String(e);
       const response: WorkerResponse = {
         type: "response",
         id: requestId,
-        error,
+        ...serializeDbError(e),
       };
       self.postMessage(response);
     }
@@ -35,6 +35,14 @@ export interface WorkerResponse {
   id: number;
   result?: unknown;
   error?: string;
+  /** Stable error code, set when `error` came from a `BetterbaseDbError`. */
+  code?: string;
+  collection?: string;
+  field?: string;
+  /** Set with `fields` and `existingId` on unique-constraint violations. */
+  index?: string;
+  fields?: Record<string, unknown>;
+  existingId?: string;
 }
 
 /** Push notification for an active subscription. */
@@ -13,6 +13,7 @@
 
 import type { MainToWorkerMessage, WorkerToMainMessage } from "./types.js";
 import type { RpcTransport } from "./rpc-transport.js";
+import { deserializeDbError } from "../errors.js";
 import { DirectTransport } from "./direct-transport.js";
 
 /** Default timeout for RPC calls (30 seconds). */
@@ -71,7 +72,7 @@ export class RpcClient {
           this.pending.delete(msg.id);
 
           if (msg.error) {
-            entry.reject(new Error(msg.error));
+            entry.reject(deserializeDbError({ ...msg, error: msg.error }));
           } else {
             entry.resolve(msg.result);
           }
@@ -215,18 +215,20 @@ export class SyncManager {
 
     const pushSnapshots = new Map<
       string,
-      { pendingPatchesLength: number; deleted: boolean }
+      { pendingPatchesLength: number; deleted: boolean; archived: boolean }
     >();
     const allOutbound: OutboundRecord[] = dirtyRecords.map((record) => {
       pushSnapshots.set(record.id, {
         pendingPatchesLength: record.pendingPatchesLength,
         deleted: record.deleted,
+        archived: record.archived,
       });
       return {
         id: record.id,
         _v: record._v,
         crdt: record.deleted ? null : record.crdt,
         deleted: record.deleted,
+        archived: record.archived && !record.deleted,
         sequence: record.sequence,
         meta: record.meta,
       };
@@ -259,6 +261,7 @@ export class SyncManager {
               ? {
                   pending_patches_length: snapshot.pendingPatchesLength,
                   deleted: snapshot.deleted,
+                  archived: snapshot.archived,
                 }
               : undefined,
           );
@@ -165,9 +165,13 @@ export type CollectionPatch<S extends SchemaShape> = {
 
 export type SortDirection = "asc" | "desc";
 
+export type NullsOrder = "first" | "last";
+
 export interface SortEntry {
   field: string;
   direction: SortDirection;
+  /** Placement of null/missing values. Defaults to "last" for asc, "first" for desc. */
+  nulls?: NullsOrder;
 }
 
 export interface QueryOptions {
@@ -182,6 +186,28 @@ export interface QueryResult<T> {
   total?: number;
 }
 
+/** A value computed per group. Field metrics skip records where the field is not a number. */
+export type AggregateMetric =
+  | { op: "count" }
+  | { op: "sum" | "avg" | "min" | "max"; field: string };
+
+export interface AggregateSpec {
+  /** Field paths forming the group key. Omit for a single whole-collection group. */
+  groupBy?: string[];
+  metrics: AggregateMetric[];
+  /** Only aggregate records matching this filter (same language as `QueryOptions.filter`). */
+  filter?: Record<string, unknown>;
+}
+
+export interface AggregateRow {
+  /** Group key values, in `groupBy` order (null for missing fields). */
+  group: unknown[];
+  /** Metric results, in `metrics` order (null if no record had a numeric value). */
+  values: (number | null)[];
+  /** Per metric, records whose field held a non-null, non-numeric value (0 for count). */
+  skipped: number[];
+}
+
 // ============================================================================
 // CRUD option types
 // ============================================================================
@@ -195,6 +221,7 @@ export interface PutOptions {
 
 export interface GetOptions {
   includeDeleted?: boolean;
+  includeArchived?: boolean;
   migrate?: boolean;
 }
 
@@ -208,14 +235,38 @@ export interface PatchOptions {
 export interface DeleteOptions {
   sessionId?: number;
   meta?: unknown;
+  /** "archive" hides the record but keeps its data; defaults to "tombstone". */
+  kind?: "tombstone" | "archive";
 }
 
 export interface ListOptions {
   includeDeleted?: boolean;
+  includeArchived?: boolean;
   limit?: number;
   offset?: number;
 }
 
+/** One write in an atomic `batch()` call. `patch` data must carry the `id`. */
+export type BatchOp =
+  | {
+      type: "put";
+      collection: CollectionDefHandle;
+      data: Record<string, unknown>;
+      options?: PutOptions;
+    }
+  | {
+      type: "patch";
+      collection: CollectionDefHandle;
+      data: Record<string, unknown> & { id: string };
+      options?: Omit<PutOptions, "id">;
+    }
+  | {
+      type: "delete";
+      collection: CollectionDefHandle;
+      id: string;
+      options?: DeleteOptions;
+    };
+
 // ============================================================================
 // Result types
 // ============================================================================
@@ -229,6 +280,18 @@ export interface RecordError {
   id: string;
   collection: string;
   error: string;
+  /** Position of the failing record in a `bulkPut` input. */
+  input_index?: number;
+  /** Set when the write collided with a unique index. */
+  unique?: UniqueConflict;
+}
+
+export interface UniqueConflict {
+  index: string;
+  /** Indexed fields and the values that collided, in index order. */
+  fields: Record<string, unknown>;
+  /** Id of the record already holding these values. */
+  existing_id: string;
 }
 
 export interface BulkDeleteResult {
@@ -236,6 +299,13 @@ export interface BulkDeleteResult {
   errors: RecordError[];
 }
 
+export interface MigrationReport {
+  /** Ids now stored at the current schema version. */
+  migrated: string[];
+  /** Records whose migration failed; they keep their stored version. */
+  failed: RecordError[];
+}
+
 // ============================================================================
 // Sync types
 // ============================================================================
@@ -245,6 +315,7 @@ export interface RemoteRecord {
   _v: number;
   crdt: Uint8Array | null;
   deleted: boolean;
+  archived?: boolean;
   sequence: number;
   meta?: Record<string, unknown>;
 }
@@ -259,6 +330,7 @@ export interface RustRemoteRecord {
   version: number;
   crdt?: number[] | null;
   deleted: boolean;
+  archived?: boolean;
   sequence: number;
   meta?: Record<string, unknown>;
 }
@@ -266,6 +338,7 @@ export interface RustRemoteRecord {
 export interface PushSnapshot {
   pending_patches_length: number;
   deleted: boolean;
+  archived?: boolean;
 }
 
 export interface ApplyRemoteOptions {
@@ -321,6 +394,8 @@ export type IndexEntry =
 export interface CollectionBlueprint {
   versions: VersionEntry[];
   indexes: IndexEntry[];
+  /** Minimum tombstone age (ms) before scheduled purges may drop it. */
+  tombstoneRetentionMs?: number;
 }
 
 export interface CollectionDefHandle<
@@ -344,6 +419,7 @@ export interface OutboundRecord {
   _v: number;
   crdt: Uint8Array | null;
   deleted: boolean;
+  archived?: boolean;
   sequence: number;
   meta?: Record<string, unknown>;
 }
@@ -384,6 +460,7 @@ export interface DirtyRecord {
   _v: number;
   crdt: Uint8Array;
   deleted: boolean;
+  archived: boolean;
   sequence: number;
   meta?: Record<string, unknown>;
   pendingPatchesLength: number;
@@ -403,6 +480,7 @@ export interface RustStoredRecordWithMeta {
   dirty: boolean;
   deleted: boolean;
   deleted_at?: string | null;
+  archived?: boolean;
   meta?: Record<string, unknown> | null;
   was_migrated: boolean;
   original_version?: number | null;
@@ -22,6 +22,12 @@ export interface WasmDbInstance {
   deleteDatabase(): Promise<void>;
   put(collection: string, data: unknown, options: unknown): unknown;
   get(collection: string, id: string, options: unknown): unknown;
+  getOrCreate(
+    collection: string,
+    id: string,
+    data: unknown,
+    options: unknown,
+  ): { record: unknown; created: boolean };
   patch(collection: string, data: unknown, options: unknown): unknown;
   delete(collection: string, id: string, options: unknown): boolean;
   query(
@@ -29,6 +35,7 @@ export interface WasmDbInstance {
     query: unknown,
   ): { records: unknown[]; total?: number };
   count(collection: string, query: unknown): number;
+  aggregate(collection: string, spec: unknown): unknown[];
   getAll(collection: string, options: unknown): unknown[];
   bulkPut(
     collection: string,
@@ -46,18 +53,71 @@ export interface WasmDbInstance {
     deleted_ids: string[];
     errors: { id: string; collection: string; error: string }[];
   };
+  batch(ops: unknown[]): unknown[];
   observe(
     collection: string,
     id: string,
     callback: (data: unknown) => void,
+    options?: { label?: string },
   ): () => void;
   observeQuery(
     collection: string,
     query: unknown,
     callback: (result: unknown) => void,
+    options?: { label?: string },
   ): () => void;
+  observeWindow(
+    collection: string,
+    query: unknown,
+    offset: number,
+    limit: number,
+    callback: (result: {
+      records: unknown[];
+      offset: number;
+      total: number;
+    }) => void,
+    options?: { label?: string },
+  ): () => void;
+  observeQueryDiff(
+    collection: string,
+    query: unknown,
+    callback: (diff: {
+      added: unknown[];
+      removed: string[];
+      updated: { old: unknown; new: unknown }[];
+      moved: string[];
+      total: number;
+    }) => void,
+    options?: { label?: string },
+  ): () => void;
+  observeAggregate(
+    collection: string,
+    spec: unknown,
+    filter: unknown,
+    callback: (rows: unknown[]) => void,
+    options?: { label?: string },
+  ): () => void;
+  subscriptionReport(): unknown;
+  setSubscriptionDiagnostics(
+    options: {
+      maxSubscriptions?: number;
+      staleAfterMs?: number;
+      staleAfterChanges?: number;
+    } | null,
+  ): void;
   onChange(callback: (event: unknown) => void): () => void;
+  onLifecycle(callback: (event: unknown) => void): () => void;
   flush(): void;
+  flushAsync(): Promise<void>;
+  migrateAll(collection: string): {
+    migrated: string[];
+    failed: { id: string; collection: string; error: string }[];
+  };
+  reindex(collection: string): number;
+  resetCollection(collection: string, resetSyncState?: boolean): number;
+  renameCollection(from: string, to: string): number;
+  dropCollection(collection: string): number;
+  purgeTombstones(): Record<string, number>;
   getDirty(collection: string): unknown[];
   markSynced(
     collection: string,
@@ -82,6 +142,7 @@ export interface WasmCollectionBuilderInstance {
   index(fields: string[], options: unknown): void;
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   computed(name: string, compute: (data: any) => any, options: unknown): void;
+  tombstoneRetention(ms: number): void;
   build(): { readonly name: string; readonly currentVersion: number };
 }
 
@@ -65,6 +65,8 @@ interface BlobEnvelope {
   crdt: Uint8Array;
   /** Serialized edit chain (JSON string). */
   h?: string;
+  /** Archived: hidden on other devices until an admin tombstones it. */
+  a?: boolean;
 }
 
 /**
@@ -86,6 +88,31 @@ export const DEFAULT_PADDING_BUCKETS = [
 /** Length prefix size for padding (4 bytes, u32 LE). */
 const PADDING_LENGTH_PREFIX = 4;
 
+/**
+ * Envelope format version byte written before the CBOR map.
+ * Must match `ENVELOPE_VERSION` in betterbase-sync-core.
+ */
+const ENVELOPE_VERSION = 1;
+
+/**
+ * Leading byte of a blob framed with a CRC32C footer (u32 LE) over the
+ * marker and the sealed blob. Must match `CHECKSUMMED_BLOB` in
+ * betterbase-sync-core.
+ */
+const CHECKSUMMED_BLOB = 0xcc;
+
+/** CRC-32C (Castagnoli), matching the blob footer in betterbase-sync-core. */
+function crc32c(data: Uint8Array): number {
+  let crc = 0xffffffff;
+  for (const byte of data) {
+    crc ^= byte;
+    for (let i = 0; i < 8; i++) {
+      crc = (crc >>> 1) ^ (0x82f63b78 & -(crc & 1));
+    }
+  }
+  return (crc ^ 0xffffffff) >>> 0;
+}
+
 /** Identity for signing edit chain entries. */
 export interface EditChainIdentity {
   /** P-256 private key JWK for signing. */
@@ -282,6 +309,7 @@ export class SyncTransport implements SyncTransportInterface {
         v: record._v,
         crdt: record.crdt!,
       };
+      if (record.archived) envelope.a = true;
 
       // Warn once if edit chains are configured but identity is unavailable
       if (
@@ -331,8 +359,13 @@ export class SyncTransport implements SyncTransportInterface {
     const identity = this.identity!;
     const meta = record.meta as Record<string, unknown> | undefined;
 
-    // Read existing chain from meta (treat parse failure as fresh chain)
-    const existingChainStr = meta?._editChain as string | undefined;
+    // Read existing chain from meta (treat parse failure as fresh chain). A
+    // chain trimmed by local compaction no longer verifies from its first
+    // entry, so it is never extended or pushed: start a fresh chain instead.
+    const existingChainStr =
+      meta?._editChainTrimmed === true
+        ? undefined
+        : (meta?._editChain as string | undefined);
     let chain: EditEntry[] = [];
     if (existingChainStr) {
       try {
@@ -633,6 +666,7 @@ export class SyncTransport implements SyncTransportInterface {
           _v: item.envelope.v,
           crdt: item.envelope.crdt,
           deleted: false,
+          archived: item.envelope.a === true,
           sequence: item.sequence,
           meta: this.buildPullMeta(
             item.envelope,
@@ -749,6 +783,7 @@ export class SyncTransport implements SyncTransportInterface {
         _v: envelope.v,
         crdt: envelope.crdt,
         deleted: false,
+        archived: envelope.a === true,
         sequence: change.sequence,
         meta: this.buildPullMeta(envelope, change.id, collection, baseMeta),
       });
@@ -768,7 +803,10 @@ export class SyncTransport implements SyncTransportInterface {
     envelope: BlobEnvelope,
     recordId: string,
   ): Promise<{ blob: Uint8Array; wrappedDEK?: Uint8Array }> {
-    const bytes = cborEncode(envelope);
+    const cbor = cborEncode(envelope);
+    const bytes = new Uint8Array(1 + cbor.length);
+    bytes[0] = ENVELOPE_VERSION;
+    bytes.set(cbor, 1);
     const padded = this.pad(bytes);
 
     if (this.baseKek) {
@@ -930,12 +968,34 @@ export class SyncTransport implements SyncTransportInterface {
     return this.decodeEnvelope(decrypted);
   }
 
+  /**
+   * Check and strip the CRC32C footer of a checksummed blob, so damage is
+   * reported as corruption rather than a decryption failure.
+   */
+  private verifyBlobChecksum(blob: Uint8Array): Uint8Array {
+    if (blob[0] !== CHECKSUMMED_BLOB) return blob;
+    if (blob.length < 6) {
+      throw new Error("Corrupt envelope: too short for a checksum footer");
+    }
+    const covered = blob.subarray(0, blob.length - 4);
+    const footer = new DataView(
+      blob.buffer,
+      blob.byteOffset + blob.length - 4,
+      4,
+    ).getUint32(0, true);
+    if (crc32c(covered) !== footer) {
+      throw new Error("Corrupt envelope: checksum mismatch");
+    }
+    return covered.subarray(1);
+  }
+
   private async decryptBlob(
     blob: Uint8Array,
     recordId: string,
     wrappedDEKBytes?: Uint8Array,
   ): Promise<Uint8Array> {
     if (this.baseKek && wrappedDEKBytes) {
+      blob = this.verifyBlobChecksum(blob);
       // spaceId is guaranteed by constructor when kek is set
       const context = { spaceId: this.spaceId!, recordId };
 
@@ -978,9 +1038,20 @@ export class SyncTransport implements SyncTransportInterface {
   }
 
   private decodeEnvelope(decrypted: Uint8Array): BlobEnvelope {
+    const version = decrypted[0];
+    let cbor: Uint8Array;
+    if (version === ENVELOPE_VERSION) {
+      cbor = decrypted.subarray(1);
+    } else if (version === undefined || (version >= 0xa0 && version <= 0xbf)) {
+      // CBOR map header: written before envelopes carried a version byte
+      cbor = decrypted;
+    } else {
+      throw new Error(`Unsupported envelope format version ${version}`);
+    }
+
     let parsed: unknown;
     try {
-      parsed = cborDecode(decrypted);
+      parsed = cborDecode(cbor);
     } catch {
       throw new Error(
         `Failed to decode CBOR envelope (${decrypted.length} bytes)`,
@@ -1012,6 +1083,9 @@ export class SyncTransport implements SyncTransportInterface {
     if (typeof obj.h === "string") {
       envelope.h = obj.h;
     }
+    if (obj.a === true) {
+      envelope.a = true;
+    }
     return envelope;
   }
 
@@ -18,6 +18,7 @@ export interface WasmModule {
     dek: Uint8Array,
     spaceId?: string,
     recordId?: string,
+    version?: number,
   ): Uint8Array;
   decryptV4(
     blob: Uint8Array,
@@ -44,6 +45,16 @@ export interface WasmModule {
   deriveChannelKey(epochKey: Uint8Array, spaceId: string): Uint8Array;
   buildPresenceAad(spaceId: string): Uint8Array;
   buildEventAad(spaceId: string): Uint8Array;
+  buildPresenceAadSeq(
+    spaceId: string,
+    sender: string,
+    sequence: bigint,
+  ): Uint8Array;
+  buildEventAadSeq(
+    spaceId: string,
+    sender: string,
+    sequence: bigint,
+  ): Uint8Array;
   generateP256Keypair(): {
     privateKeyJwk: JsonWebKey;
     publicKeyJwk: JsonWebKey;
@@ -79,6 +90,11 @@ export interface WasmModule {
     newView: Record<string, unknown>,
     prefix?: string,
   ): EditDiff[];
+  valueDiffGranular(
+    oldView: Record<string, unknown>,
+    newView: Record<string, unknown>,
+    prefix?: string,
+  ): EditDiff[];
   signEditEntry(
     privateKeyJwk: JsonWebKey,
     publicKeyJwk: JsonWebKey,
@@ -143,6 +159,8 @@ export interface WasmModule {
   // --- sync ---
   padToBucket(data: Uint8Array): Uint8Array;
   unpad(data: Uint8Array): Uint8Array;
+  padMembershipEntry(serialized: Uint8Array): Uint8Array;
+  unpadMembershipEntry(padded: Uint8Array): Uint8Array;
   encryptOutbound(
     collection: string,
     version: number,
@@ -153,6 +171,9 @@ export interface WasmModule {
     baseEpoch: number,
     currentEpoch: number,
     spaceId: string,
+    archived?: boolean,
+    sequence?: bigint,
+    sender?: string,
   ): { blob: Uint8Array; wrappedDek: Uint8Array };
   decryptInbound(
     blob: Uint8Array,
@@ -166,8 +187,97 @@ export interface WasmModule {
     version: number;
     crdt: Uint8Array;
     editChain?: string;
+    archived?: boolean;
+  };
+  decryptInboundGuarded(
+    blob: Uint8Array,
+    wrappedDek: Uint8Array,
+    recordId: string,
+    epochKey: Uint8Array,
+    baseEpoch: number,
+    spaceId: string,
+    sender: string,
+    sequence: bigint | undefined,
+    replayGuard: string | undefined,
+    allowUnsequenced: boolean,
+  ): {
+    envelope: {
+      collection: string;
+      version: number;
+      crdt: Uint8Array;
+      editChain?: string;
+      archived?: boolean;
+    };
+    replayGuard: string;
   };
+  encryptChannelSequenced(
+    kind: "presence" | "event",
+    channelKey: Uint8Array,
+    spaceId: string,
+    sender: string,
+    sequence: bigint,
+    payload: Uint8Array,
+  ): Uint8Array;
+  decryptChannelGuarded(
+    kind: "presence" | "event",
+    channelKey: Uint8Array,
+    spaceId: string,
+    sender: string,
+    sequence: bigint | undefined,
+    frame: Uint8Array,
+    replayGuard: string | undefined,
+    allowUnsequenced: boolean,
+  ): { payload: Uint8Array; replayGuard: string };
+  decryptBatch(
+    blobs: Uint8Array[],
+    wrappedDeks: Uint8Array[],
+    recordIds: string[],
+    epochKey: Uint8Array,
+    baseEpoch: number,
+    spaceId: string,
+  ): Array<
+    | {
+        ok: {
+          collection: string;
+          version: number;
+          crdt: Uint8Array;
+          editChain?: string;
+          archived?: boolean;
+        };
+      }
+    | { error: string }
+  >;
   peekEpoch(wrappedDek: Uint8Array): number;
+  rotateSpaceEpoch(
+    recordIds: string[],
+    wrappedDeks: Uint8Array[],
+    oldEpochKey: Uint8Array,
+    oldEpoch: number,
+    newEpochKey: Uint8Array,
+    newEpoch: number,
+    spaceId: string,
+  ): {
+    rewrapped: { recordId: string; wrappedDek: Uint8Array }[];
+    failures: { recordId: string; error: string }[];
+    skipped: string[];
+  };
+  validateEpochSequence(wrappedDeks: Uint8Array[], minAcceptableEpoch: number): void;
+  buildSpacePolicySigningMessage(
+    spaceId: string,
+    signerDid: string,
+    ucan: string,
+    writerDelete: "tombstone" | "archive",
+    minWireVersion?: number,
+  ): Uint8Array;
+  verifySpacePolicyEntry(payload: string, spaceId: string): boolean;
+  resolveSpaceDeletePolicy(
+    payloads: string[],
+    spaceId: string,
+  ): "tombstone" | "archive";
+  resolveSpaceWireMinimum(
+    payloads: string[],
+    spaceId: string,
+  ): number | undefined;
   deriveForward(
     key: Uint8Array,
     spaceId: string,
@@ -190,6 +300,14 @@ export interface WasmModule {
     signerHandle: string,
     recipientHandle: string,
   ): Uint8Array;
+  buildMembershipSigningMessageV2(
+    entryType: string,
+    spaceId: string,
+    signerDid: string,
+    ucan: string,
+    signerHandle: string,
+    recipientHandle: string,
+  ): Uint8Array;
   parseMembershipEntry(payload: string): MembershipEntryPayload;
   serializeMembershipEntry(entryJson: string): string;
   verifyMembershipEntry(payload: string, spaceId: string): boolean;
@@ -223,6 +341,8 @@ export interface EditEntry {
   p: string | null;
   s: Uint8Array;
   k: JsonWebKey;
+  /** Key rotation entries only: the did:key the author rotates to. */
+  r?: string;
 }
 
 export interface AppKeypairJwk {
@@ -243,6 +363,8 @@ export interface MembershipEntryPayload {
   publicKeyJwk?: JsonWebKey;
   signerHandle?: string;
   recipientHandle?: string;
+  /** Present (as 2) when the signature covers the v2 signing message. */
+  signingVersion?: 2;
 }
 
 // --- Singleton ---